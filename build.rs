@@ -0,0 +1,27 @@
+//! Generates the C header when the `ffi` feature is enabled.
+
+fn main() {
+    #[cfg(feature = "ffi")]
+    generate_header();
+}
+
+/// Writes the header for the functions in `src/ffi.rs` to `fancy_tree.h` in the
+/// output directory. The copy in `include/` is checked against it by the tests.
+#[cfg(feature = "ffi")]
+fn generate_header() {
+    println!("cargo::rerun-if-changed=src/ffi.rs");
+    println!("cargo::rerun-if-changed=cbindgen.toml");
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("Cargo should set the manifest dir");
+    let out_dir = std::env::var("OUT_DIR").expect("Cargo should set the output dir");
+    let config = cbindgen::Config::from_file(format!("{crate_dir}/cbindgen.toml"))
+        .expect("cbindgen.toml should be valid");
+    // NOTE Only the FFI module is parsed, since the rest of the crate has types that
+    //      cbindgen can't read, like `Box<dyn Error>`.
+    cbindgen::Builder::new()
+        .with_src(format!("{crate_dir}/src/ffi.rs"))
+        .with_config(config)
+        .generate()
+        .expect("The header should be generated")
+        // NOTE The file is only written if it changed.
+        .write_to_file(format!("{out_dir}/fancy_tree.h"));
+}
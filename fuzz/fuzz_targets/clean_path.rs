@@ -0,0 +1,24 @@
+#![no_main]
+use fancy_tree::tree::fuzzing;
+use libfuzzer_sys::fuzz_target;
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+fuzz_target!(|input: (&[u8], &[u8])| {
+    let (root, path) = input;
+    // NOTE Nul bytes can't be in real paths, and are rejected by the file system.
+    if root.contains(&0) || path.contains(&0) {
+        return;
+    }
+    let root = Path::new(OsStr::from_bytes(root));
+    let path = Path::new(OsStr::from_bytes(path));
+
+    if let Some(normalized) = fuzzing::normalize_absolute(path) {
+        assert!(normalized.is_absolute());
+        assert_eq!(Some(&normalized), fuzzing::normalize_absolute(&normalized).as_ref());
+    }
+    if let Some(relative) = fuzzing::clean_path_for_git2(root, path) {
+        assert!(relative.is_relative());
+    }
+});
@@ -0,0 +1,14 @@
+#![no_main]
+use fancy_tree::sorting::Method;
+use libfuzzer_sys::fuzz_target;
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+
+fuzz_target!(|input: (&[u8], &[u8])| {
+    let (left, right) = input;
+    let left = OsStr::from_bytes(left);
+    let right = OsStr::from_bytes(right);
+    let ordering = Method::Natural.cmp(left, right);
+    assert_eq!(ordering.reverse(), Method::Natural.cmp(right, left));
+    assert!(Method::Natural.cmp(left, left).is_eq());
+});
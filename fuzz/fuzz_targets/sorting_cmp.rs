@@ -0,0 +1,55 @@
+#![no_main]
+use arbitrary::Arbitrary;
+use fancy_tree::sorting::{Direction, Directories, Method, Sorting, TieBreak};
+use libfuzzer_sys::fuzz_target;
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+#[derive(Debug, Arbitrary)]
+struct Input<'a> {
+    natural: bool,
+    descending: bool,
+    directories: u8,
+    ignore_case: bool,
+    ignore_dot: bool,
+    lowercase_first: bool,
+    left: &'a [u8],
+    right: &'a [u8],
+}
+
+fuzz_target!(|input: Input| {
+    let left = Path::new(OsStr::from_bytes(input.left));
+    let right = Path::new(OsStr::from_bytes(input.right));
+    // NOTE Sorted paths are always directory entries, which have file names.
+    if left.file_name().is_none() || right.file_name().is_none() {
+        return;
+    }
+    let mut sorting = Sorting::default();
+    sorting.method = if input.natural {
+        Method::Natural
+    } else {
+        Method::Naive
+    };
+    sorting.direction = if input.descending {
+        Direction::Desc
+    } else {
+        Direction::Asc
+    };
+    sorting.directories = match input.directories % 3 {
+        0 => Directories::Mixed,
+        1 => Directories::First,
+        _ => Directories::Last,
+    };
+    sorting.ignore_case = input.ignore_case;
+    sorting.ignore_dot = input.ignore_dot;
+    sorting.tie_break = if input.lowercase_first {
+        TieBreak::Lowercase
+    } else {
+        TieBreak::Bytes
+    };
+
+    let ordering = sorting.cmp(left, right);
+    assert_eq!(ordering.reverse(), sorting.cmp(right, left));
+    assert_eq!(left.file_name() == right.file_name(), ordering.is_eq());
+});
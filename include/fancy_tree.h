@@ -0,0 +1,62 @@
+/* Generated by cbindgen from src/ffi.rs. Don't edit this file. */
+
+#ifndef FANCY_TREE_H
+#define FANCY_TREE_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Writes the tree at `root` and returns it as a new string, which must be freed
+ * with [`fancy_tree_free`].
+ *
+ * `options_json` is a JSON object like `{"format": "json", "level": 2}`, and can be
+ * `NULL` for the defaults. The options are `format`, `color`, `icons`, `level`,
+ * `max_lines`, and `config`, which loads the user's configuration files.
+ *
+ * Returns `NULL` if the tree can't be written, and the error can be read with
+ * [`fancy_tree_last_error`].
+ *
+ * # Safety
+ *
+ * `root` must be a valid NUL-terminated string, and `options_json` must be either
+ * `NULL` or a valid NUL-terminated string.
+ */
+char *fancy_tree_render(const char *root, const char *options_json);
+
+/**
+ * Frees a string that was returned by [`fancy_tree_render`]. Does nothing if the
+ * string is `NULL`.
+ *
+ * # Safety
+ *
+ * `s` must be `NULL` or a string returned by [`fancy_tree_render`] that hasn't
+ * been freed yet.
+ */
+void fancy_tree_free(char *s);
+
+/**
+ * Gets the error from the last call on this thread that failed, or `NULL` if
+ * there wasn't one. The string is owned by the library, and is valid until the
+ * next call on this thread fails.
+ */
+const char *fancy_tree_last_error(void);
+
+/**
+ * Gets the version of the C ABI, so that callers can check that they're
+ * compatible with the library they loaded.
+ */
+uint32_t fancy_tree_abi_version(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* FANCY_TREE_H */
@@ -5,31 +5,174 @@
 ---@nodiscard
 local function filename(path) end
 
+--- Gets the file name without its extension, like "config" for "path/config.lua".
+---@param path string
+---@return string|nil
+---@nodiscard
+local function stem(path) end
+
+--- Gets the extension without the leading ".", like "lua" for "config.lua".
+---@param path string
+---@return string|nil
+---@nodiscard
+local function extension(path) end
+
+--- Gets both extensions of names like "archive.tar.gz", like "tar" and "gz", or nil
+--- if the name doesn't have two extensions.
+---@param path string
+---@return string|nil prefix
+---@return string|nil suffix
+---@nodiscard
+local function double_extension(path) end
+
+--- Joins the parts onto a path with the OS's separator. An absolute part replaces
+--- the path before it.
+---@param path string
+---@param ... string
+---@return string
+---@nodiscard
+local function join(path, ...) end
+
+--- Gets the path without its last component, or nil if there isn't one, like for
+--- "/" or "config.lua".
+---@param path string
+---@return string|nil
+---@nodiscard
+local function parent(path) end
+
+--- Gets the path relative to a base path, or nil if the path isn't inside it.
+---@param path string
+---@param base string
+---@return string|nil
+---@nodiscard
+local function relative_to(path, base) end
+
+---@param path string
+---@return boolean
+---@nodiscard
+local function is_absolute(path) end
+
+---@class GlobOptions
+---@field case_insensitive boolean|nil Ignore case, so that "*.JPG" matches "photo.jpg"
+---@field literal_separator boolean|nil Don't match "/" with "*" or "?"
+
 ---@param glob string
+---@param path string
+---@param options GlobOptions|nil
+---@return boolean
+---@nodiscard
+local function glob_matches(glob, path, options) end
+
+--- A compiled glob, for matching the same pattern against many paths.
+---@class Glob
+local Glob = {}
+
 ---@param path string
 ---@return boolean
 ---@nodiscard
-local function glob_matches(glob, path) end
+function Glob:matches(path) end
+
+--- Compiles a glob. Errors if the glob isn't valid.
+---@param glob string
+---@param options GlobOptions|nil
+---@return Glob
+---@nodiscard
+local function glob(glob, options) end
+
+--- Expands a leading `~` or `~user`, and `$VAR` or `${VAR}` environment variables.
+--- Errors if a variable isn't set.
+---@param path string
+---@return string
+---@nodiscard
+local function expand(path) end
 
 ---@class Path
 local path = {
   filename = filename,
+  stem = stem,
+  extension = extension,
+  double_extension = double_extension,
+  join = join,
+  parent = parent,
+  relative_to = relative_to,
+  is_absolute = is_absolute,
   glob_matches = glob_matches,
+  glob = glob,
+  expand = expand,
 }
 
 ---@param path string
 ---@return boolean
 local function is_ignored(path) end
 
+--- Gets the statuses of a path relative to the repository's root, like `src/lib.rs`
+--- or `./src/lib.rs`, or of an absolute path in the repository.
+---@param path string
+---@return FileGitStatus
+local function status(path) end
+
+--- Gets the root directory of the repository's working tree, or nil for bare
+--- repositories.
+---@return string|nil
+local function root() end
+
+--- Gets the name of the checked out branch, or nil if `HEAD` is detached.
+---@return string|nil
+local function branch() end
+
+--- Gets the numbers of commits that the checked out branch is ahead of and behind
+--- its upstream branch, or nil if there is no upstream branch.
+---@return integer|nil ahead
+---@return integer|nil behind
+local function ahead_behind() end
+
+--- Gets the number of changed files in the tree, or nil if the statuses couldn't be
+--- computed.
+---@return integer|nil
+local function changed_files() end
+
 ---@class Git
 local git = {
   is_ignored = is_ignored,
+  status = status,
+  root = root,
+  branch = branch,
+  ahead_behind = ahead_behind,
+  changed_files = changed_files,
 }
 
+---@class Line A written line of the tree
+---@field text string The line without colors or hyperlinks. Changing it writes the new text without styles
+---@field styled string The line as it's written
+
+---@class Stats
+---@field directories integer
+---@field files integer
+---@field total_size integer|nil The total size in bytes, only set with --du
+
+--- Hooks that a plugin registers. Each hook receives the value from the config and
+--- the plugins before it, and returning nil keeps that value, except for `on_color`
+--- where nil means no color.
+---@class PluginHooks
+---@field on_entry (fun(path: string, attributes: FileAttributes, skip: boolean): boolean|nil)|nil Returns true to hide the entry
+---@field on_icon (fun(path: string, attributes: FileAttributes, icon: string): string|nil)|nil
+---@field on_color (fun(path: string, attributes: FileAttributes, color: Style|nil): Style|nil)|nil
+---@field on_summary (fun(summary: string): string|nil)|nil
+
+--- Registers hooks from a plugin in the `plugins` directory. Errors when called
+--- outside of a plugin.
+---@param hooks PluginHooks
+local function register(hooks) end
+
+--- The API is read-only, so setting any of its fields is an error.
 ---@class FancyTree
+---@field api_version integer Incremented when features are added to the API
 ---@field is_unix boolean
 ---@field os string
+---@field builtin lightuserdata Returned by icon and color functions to use the built-in choice
 ---@field git Git|nil
+---@field stats Stats|nil Only set after the tree is written
 fancytree = {
   path = path,
+  register = register,
 }
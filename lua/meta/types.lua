@@ -4,7 +4,61 @@
 ---@field file_type "directory"|"file"|"symlink"
 ---@field is_hidden boolean
 ---@field is_executable boolean
----@field language string|nil
+---@field is_empty boolean|nil If this is an empty directory
+---@field is_expanded boolean|nil If this is a directory whose entries are written below it. Only set for icons
+local FileAttributes = {}
+
+---@return string
+function FileAttributes:path() end
+
+---@return boolean
+function FileAttributes:is_dir() end
+
+--- The size in bytes when this is a file.
+---@return integer|nil
+function FileAttributes:size() end
+
+--- The total size in bytes of everything inside this when it's a directory, like
+--- `du`. This is only computed when it's called, unless the tree already computed
+--- it, like with --du.
+---@return integer|nil
+function FileAttributes:total_size() end
+
+---@return string|nil
+function FileAttributes:language() end
+
+--- The number of children when this is a directory. This is only read when it's
+--- called.
+---@return integer|nil
+function FileAttributes:child_count() end
+
+--- Seconds since the Unix epoch when this was last modified.
+---@return integer|nil
+function FileAttributes:modified() end
+
+--- Seconds since this was last modified.
+---@return integer|nil
+function FileAttributes:age() end
+
+--- The permissions, like `-rw-r--r--` on Unix or `-a----` on Windows.
+---@return string
+function FileAttributes:permissions() end
+
+--- The target when this is a symlink.
+---@return string|nil
+function FileAttributes:target() end
+
+--- If this is a symlink whose target doesn't exist.
+---@return boolean
+function FileAttributes:is_broken() end
+
+--- The statuses when this is in a repository.
+---@return FileGitStatus|nil
+function FileAttributes:git_status() end
+
+---@class FileGitStatus
+---@field tracked GitStatus|nil
+---@field untracked GitStatus|nil
 
 ---@class RGB
 ---@field r integer
@@ -12,17 +66,87 @@
 ---@field b integer
 
 ---@alias ANSI "black"|"red"|"green"|"yellow"|"blue"|"magenta"|"cyan"|"white"|"bright-black"|"bright-red"|"bright-green"|"bright-yellow"|"bright-blue"|"bright-magenta"|"bright-cyan"|"bright-white"
----@alias Color ANSI|RGB
+-- An ANSI color name, an RGB table, or one of the 256 xterm colors, like 208 for orange.
+---@alias Color ANSI|RGB|integer
+
+---@class StyleTable
+---@field fg Color|nil The text's color
+---@field bg Color|nil The background's color
+---@field bold boolean|nil
+---@field italic boolean|nil
+---@field underline boolean|nil
+---@field dim boolean|nil
+---@field strikethrough boolean|nil
+
+-- A foreground color, or a table like `{ fg = "red", bold = true }`.
+---@alias Style Color|StyleTable
+
+---@class AgeGradient
+---@field fresh Color|nil The color of the most recently changed files
+---@field stale Color|nil The color of the least recently changed files
 
 ---@alias GitStatus "added"|"modified"|"removed"|"renamed"
 
+---@class ThemeKinds
+---@field file Style|nil Files that don't have a color for their language
+---@field executable Style|nil
+---@field directory Style|nil
+---@field symlink Style|nil
+---@field broken_symlink Style|nil
+
+---@class ThemeStatuses
+---@field added Style|nil
+---@field modified Style|nil
+---@field removed Style|nil
+---@field renamed Style|nil
+
+---@class Theme
+---@field kinds ThemeKinds|nil
+---@field git_statuses { tracked: ThemeStatuses|nil, untracked: ThemeStatuses|nil }|nil
+---@field connectors Style|nil The tree's branches
+---@field ignored Style|nil The names of ignored entries
+
 ---@class SortingConfig
 ---@field method "naive"|"natural"|nil
 ---@field direction "asc"|"desc"|"ascending"|"descending"|nil
 ---@field directories "mixed"|"first"|"last"|nil
 ---@field ignore_case boolean|nil
 ---@field ignore_dot boolean|nil
+---@field tie_break "bytes"|"lowercase"|nil How to order names that are otherwise equal
 
 ---@alias SortingFn fun(left: string, right: string): -1|0|1
 
 ---@alias Sorting SortingConfig|SortingFn
+
+---@alias CharsetName "unicode"|"ascii"|"rounded"|"double"|"none"
+
+---@class CharsetConfig
+---@field branch string|nil
+---@field last_branch string|nil
+---@field vertical string|nil
+---@field indent string|nil
+
+---@class AnnotationCommand
+---@field glob string Patterns for file names, separated by `|`, like "*.png|*.jpg"
+---@field command string[] The program and its arguments, which the path is added to
+
+---@class AnnotationOptions
+---@field jobs integer|nil The most commands to run at once, which defaults to the number of CPUs
+---@field timeout number|nil Seconds before a command is stopped, which defaults to 5
+---@field commands AnnotationCommand[]|nil The first command that matches an entry is used
+
+---@class GitStatusesOptions
+---@field roll_up boolean|nil
+
+---@class GitOptions
+---@field renames boolean|nil
+---@field rename_threshold integer|nil
+
+---@alias IconFn fun(filepath: string, attributes: FileAttributes, default: string): string|lightuserdata|nil
+
+---@class ColorsConfig
+---@field icons fun(filepath: string, attributes: FileAttributes, default: Style|nil): Style|lightuserdata|nil|nil
+---@field git_statuses { tracked: (fun(status: GitStatus, default: Style|nil): Style|nil)|nil, untracked: (fun(status: GitStatus, default: Style|nil): Style|nil)|nil }|nil
+---@field age AgeGradient|nil
+---@field permissions fun(permissions: string, default: Style|nil): Style|nil|nil
+---@field tint fun(filepath: string, attributes: FileAttributes, inherited: Style|nil): Style|nil|nil
@@ -0,0 +1,173 @@
+//! The `fancy_tree` Python module, which writes trees with the library's
+//! [`Session`] API.
+//!
+//! ```python
+//! import fancy_tree
+//!
+//! print(fancy_tree.render(".", level=2))
+//! for entry in fancy_tree.entries(".", level=2):
+//!     print(entry.depth, entry.path)
+//! ```
+use fancy_tree::session::{Options, Session};
+use fancy_tree::tree::export::{EntryType, FlatNode};
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use std::path::PathBuf;
+use std::vec;
+
+/// An entry of a tree.
+#[pyclass(frozen, get_all, module = "fancy_tree")]
+struct Entry {
+    /// An identifier that stays the same as long as the relative path does.
+    id: String,
+    /// The ID of the parent entry. `None` for the root.
+    parent: Option<String>,
+    /// The name of the entry.
+    name: String,
+    /// The path relative to the tree's root, with `/` separators.
+    path: String,
+    /// `"directory"`, `"file"`, or `"symlink"`.
+    r#type: &'static str,
+    /// How many levels below the tree's root the entry is. The root is `0`.
+    depth: usize,
+    /// The tracked version control status, like `"modified"`.
+    tracked: Option<&'static str>,
+    /// The untracked version control status, like `"added"`.
+    untracked: Option<&'static str>,
+    /// The icon that's displayed for the entry.
+    icon: Option<String>,
+    /// The color of the entry's icon, as a color name or a hex code.
+    color: Option<String>,
+}
+
+#[pymethods]
+impl Entry {
+    fn __repr__(&self) -> String {
+        format!("Entry(path={:?}, type={:?})", self.path, self.r#type)
+    }
+}
+
+impl From<FlatNode> for Entry {
+    fn from(node: FlatNode) -> Self {
+        let r#type = match node.entry_type {
+            EntryType::Directory => "directory",
+            EntryType::File => "file",
+            EntryType::Symlink => "symlink",
+        };
+        Self {
+            id: node.id,
+            parent: node.parent,
+            name: node.name,
+            path: node.path,
+            r#type,
+            depth: node.depth,
+            tracked: node
+                .status
+                .and_then(|status| status.tracked)
+                .map(|status| status.name()),
+            untracked: node
+                .status
+                .and_then(|status| status.untracked)
+                .map(|status| status.name()),
+            icon: node.icon,
+            color: node.color,
+        }
+    }
+}
+
+/// An iterator over the entries of a tree, in depth-first order.
+#[pyclass(module = "fancy_tree")]
+struct Entries {
+    /// The entries that haven't been returned yet.
+    entries: vec::IntoIter<FlatNode>,
+}
+
+#[pymethods]
+impl Entries {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>) -> Option<Entry> {
+        slf.entries.next().map(Entry::from)
+    }
+}
+
+/// Creates a session from the arguments of a Python function.
+fn session(
+    path: PathBuf,
+    format: Option<String>,
+    color: Option<String>,
+    icons: Option<String>,
+    level: Option<usize>,
+    max_lines: Option<usize>,
+    config: bool,
+) -> Session {
+    Session::new(path).options(Options {
+        format,
+        color,
+        icons,
+        level,
+        max_lines,
+        config,
+        ..Options::default()
+    })
+}
+
+/// Writes the tree at the path and returns it.
+///
+/// The options are named like the command-line's options, like `format="json"` and
+/// `level=2`. `config=True` loads the user's configuration files.
+#[pyfunction]
+#[pyo3(signature = (path, *, format=None, color=None, icons=None, level=None, max_lines=None, config=false))]
+#[allow(clippy::too_many_arguments)]
+fn render(
+    py: Python<'_>,
+    path: PathBuf,
+    format: Option<String>,
+    color: Option<String>,
+    icons: Option<String>,
+    level: Option<usize>,
+    max_lines: Option<usize>,
+    config: bool,
+) -> PyResult<String> {
+    let session = session(path, format, color, icons, level, max_lines, config);
+    py.detach(|| session.render().map_err(|e| e.to_string()))
+        .map_err(PyRuntimeError::new_err)
+}
+
+/// Iterates over the entries of the tree at the path, starting with the root.
+///
+/// The options are the same as `render`'s, and `format` is ignored.
+#[pyfunction]
+#[pyo3(signature = (path, *, format=None, color=None, icons=None, level=None, max_lines=None, config=false))]
+#[allow(clippy::too_many_arguments)]
+fn entries(
+    py: Python<'_>,
+    path: PathBuf,
+    format: Option<String>,
+    color: Option<String>,
+    icons: Option<String>,
+    level: Option<usize>,
+    max_lines: Option<usize>,
+    config: bool,
+) -> PyResult<Entries> {
+    let session = session(path, format, color, icons, level, max_lines, config);
+    let entries = py
+        .detach(|| session.entries().map_err(|e| e.to_string()))
+        .map_err(PyRuntimeError::new_err)?;
+    Ok(Entries {
+        entries: entries.into_iter(),
+    })
+}
+
+/// Writes trees of directories, with version control statuses and icons.
+#[pymodule]
+#[pyo3(name = "fancy_tree")]
+fn python_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(render, m)?)?;
+    m.add_function(wrap_pyfunction!(entries, m)?)?;
+    m.add_class::<Entry>()?;
+    m.add_class::<Entries>()?;
+    Ok(())
+}
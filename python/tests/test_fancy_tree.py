@@ -0,0 +1,48 @@
+import json
+import os
+import tempfile
+import unittest
+
+import fancy_tree
+
+
+class FancyTreeTest(unittest.TestCase):
+    def setUp(self):
+        self.dir = tempfile.TemporaryDirectory(prefix="fancy-tree-")
+        self.root = self.dir.name
+        os.mkdir(os.path.join(self.root, "src"))
+        for path in ["README.md", "src/main.rs"]:
+            with open(os.path.join(self.root, path), "w"):
+                pass
+
+    def tearDown(self):
+        self.dir.cleanup()
+
+    def test_render(self):
+        text = fancy_tree.render(self.root, icons="none")
+        self.assertIn("main.rs\n", text)
+        self.assertNotIn("\x1b", text)
+
+        node = json.loads(fancy_tree.render(self.root, format="json", level=1))
+        self.assertEqual(2, len(node["children"]))
+
+    def test_render_error(self):
+        with self.assertRaisesRegex(RuntimeError, 'invalid format "yaml"'):
+            fancy_tree.render(self.root, format="yaml")
+
+    def test_entries(self):
+        entries = list(fancy_tree.entries(self.root))
+        self.assertEqual(
+            [".", "README.md", "src", "src/main.rs"],
+            [entry.path for entry in entries],
+        )
+        self.assertEqual("directory", entries[2].type)
+        self.assertEqual(entries[2].id, entries[3].parent)
+        self.assertEqual(2, entries[3].depth)
+
+        entries = fancy_tree.entries(self.root, level=1)
+        self.assertEqual(3, sum(1 for _ in entries))
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -1,27 +1,141 @@
 //! CLI utilities.
-use crate::color::ColorChoice;
-use crate::config::{self, ConfigDir, ConfigFile as _};
-use crate::git::Git;
+use crate::color::{Color, ColorChoice};
+use crate::config::{self, ConfigDir, ConfigFile, ConfigLoader, ProjectOverrides};
+use crate::diagnostics::{Diagnostic, Diagnostics, Source};
+use crate::editor::Editor;
+use crate::expand;
+use crate::i18n::Lang;
+use crate::icons::IconSet;
+use crate::ignore::Ignore;
 use crate::lua;
+use crate::pager::{self, Paging};
+use crate::rules::Rules;
+use crate::session::{self, Parts, Setup};
 use crate::tree;
-use clap::{Parser, ValueEnum};
-use std::fs;
-use std::path::PathBuf;
+use crate::vcs::Since;
+use clap::builder::{OsStringValueParser, RangedU64ValueParser, TypedValueParser};
+use clap::{Parser, Subcommand, ValueEnum};
+use owo_colors::AnsiColors;
+use similar::TextDiff;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, IsTerminal as _, Write as _};
+use std::path::{Path, PathBuf};
 
 /// Lists files in a directory.
 #[derive(Parser)]
 #[command(version)]
 #[deny(missing_docs)]
 pub struct Cli {
-    /// The path to search in.
-    #[arg(default_value = ".")]
+    /// A command to run instead of listing files.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// The path to search in. `~` and environment variables like `$HOME` are
+    /// expanded.
+    #[arg(default_value = ".", value_parser = path_parser())]
     pub path: PathBuf,
 
+    /// The format to write the tree in.
+    #[arg(long, alias = "format", default_value = "tree")]
+    pub output: tree::Format,
+
+    /// Write the tree as JSON. Shorthand for `--output json`.
+    #[arg(long, conflicts_with = "output")]
+    pub json: bool,
+
+    /// List one relative path per line instead of a tree. Shorthand for `--output
+    /// flat`.
+    #[arg(long, conflicts_with_all = ["output", "json"])]
+    pub flat: bool,
+
+    /// Write plain sentences for screen readers, like `folder src, 3 items`,
+    /// instead of icons, colors, and box-drawing characters. Shorthand for `--output
+    /// accessible`.
+    #[arg(long, conflicts_with_all = ["output", "json", "flat"])]
+    pub accessible: bool,
+
+    /// Sort every entry in the tree at once. Requires `--flat`.
+    #[arg(long, value_name = "KEY", requires = "flat")]
+    pub sort: Option<tree::SortKey>,
+
+    /// Reverse the order of `--sort`, like to list the largest files first. Requires
+    /// `--flat`.
+    #[arg(long, requires = "flat")]
+    pub reverse: bool,
+
+    /// Link each entry to its path under this URL, like `tree -H`. Used with
+    /// `--output html`.
+    #[arg(long, value_name = "URL")]
+    pub html_base: Option<String>,
+
+    /// Wrap names in backticks. Used with `--output markdown`.
+    #[arg(long)]
+    pub markdown_code: bool,
+
     /// Controls colorization.
-    #[arg(long = "color")]
+    #[arg(long = "color", global = true)]
     pub color_choice: Option<ColorChoice>,
 
-    /// Go only this many levels deep.
+    /// When to pipe the output through `$FANCY_TREE_PAGER`, `$PAGER`, or `less`.
+    /// `auto` pages output that's taller than the terminal. Overrides `paging` from
+    /// the configuration.
+    #[arg(long, value_name = "WHEN")]
+    pub paging: Option<Paging>,
+
+    /// Show entries that are ignored by git or by `.gitignore` and `.ignore` files.
+    #[arg(long)]
+    pub no_ignore: bool,
+
+    /// Show hidden entries, like dotfiles. The configuration's `skip` function
+    /// receives this as its `show_hidden` argument.
+    #[arg(short = 'a', long = "all", visible_alias = "show-hidden")]
+    pub show_hidden: bool,
+
+    /// Only list files whose names match this pattern, like `*.rs` or `*.rs|*.toml`.
+    ///
+    /// Directories are always listed. Use `--prune` to hide the ones that don't
+    /// contain matching files.
+    #[arg(short = 'P', long)]
+    pub pattern: Option<tree::NamePattern>,
+
+    /// Hide directories that end up empty, like after filtering with `--pattern`.
+    #[arg(long, overrides_with = "no_prune")]
+    pub prune: bool,
+
+    /// Show directories that end up empty, even if the configuration prunes them.
+    #[arg(long, overrides_with = "prune")]
+    pub no_prune: bool,
+
+    /// Traverse symlinks to directories, like they're directories.
+    #[arg(short = 'l', long, overrides_with = "no_follow_symlinks")]
+    pub follow_symlinks: bool,
+
+    /// Don't traverse symlinks, even if the configuration follows them.
+    #[arg(long, overrides_with = "follow_symlinks")]
+    pub no_follow_symlinks: bool,
+
+    /// The default icons. `auto` uses Nerd Font icons unless the locale isn't UTF-8.
+    /// Overrides `icon_set` from the configuration.
+    #[arg(long = "icons", value_name = "SET")]
+    pub icon_set: Option<IconSet>,
+
+    /// The color theme, like `catppuccin`, `gruvbox`, or `nord`. A file in the
+    /// configuration's `themes` directory, like `themes/mine.lua`, can be used by
+    /// its name. Overrides `theme` from the configuration.
+    #[arg(long, value_name = "NAME")]
+    pub theme: Option<String>,
+
+    /// The language of messages, like the summary and hints. Defaults to the
+    /// language of the locale, from `LC_ALL`, `LC_MESSAGES`, or `LANG`.
+    #[arg(long, value_name = "LANG")]
+    pub lang: Option<Lang>,
+
+    /// How to show `.git` directories. Overrides `show_git_dir` from the
+    /// configuration.
+    #[arg(long, value_name = "MODE")]
+    pub git_dir: Option<tree::GitDir>,
+
+    /// Go only this many levels deep. 0 only prints the root.
     #[arg(short = 'L', long)]
     pub level: Option<usize>,
 
@@ -31,9 +145,262 @@ pub struct Cli {
     #[arg(long, alias = "unset-level", conflicts_with = "level")]
     pub max_level: bool,
 
-    /// Edit the main configuration file and exit.
+    /// Stop printing after this many lines, marking directories that weren't printed
+    /// completely as truncated. The markers are included in the lines.
+    #[arg(long, value_name = "N", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub max_lines: Option<usize>,
+
+    /// Write at most this many entries in each directory, followed by a line with the
+    /// number of entries that weren't written.
+    #[arg(long, value_name = "N")]
+    pub filelimit: Option<usize>,
+
+    /// What happens to directories with more entries than `--filelimit`: `truncate`
+    /// writes the entries up to the limit, and `skip` doesn't open the directory, like
+    /// GNU tree. Overrides `filelimit_mode` from the configuration.
+    #[arg(long, value_name = "MODE")]
+    pub filelimit_mode: Option<tree::FileLimitMode>,
+
+    /// Show the sizes of files, and with `--size=recursive`, the total sizes of
+    /// directories.
+    #[arg(long = "size", num_args = 0..=1, require_equals = true, default_missing_value = "files")]
+    pub size_mode: Option<tree::SizeMode>,
+
+    /// Show the total sizes of directories, like `du`, and the total size of the
+    /// tree in the summary. This implies `--size=recursive`.
+    #[arg(long = "du", conflicts_with = "size_mode")]
+    pub disk_usage: bool,
+
+    /// The characters for the tree's branches.
+    #[arg(long, value_name = "STYLE")]
+    pub charset: Option<tree::CharsetStyle>,
+
+    /// Show the permissions of each entry, like `drwxr-xr-x`. On Windows, this shows
+    /// the file attributes instead.
+    #[arg(short = 'p', long, overrides_with = "no_permissions")]
+    pub permissions: bool,
+
+    /// Don't show permissions, even if the configuration shows them.
+    #[arg(long, overrides_with = "permissions")]
+    pub no_permissions: bool,
+
+    /// Link names to their files, so that terminals that support OSC 8 hyperlinks
+    /// open them when they're clicked. Names are only linked when colors are on.
+    #[arg(long, overrides_with = "no_hyperlinks")]
+    pub hyperlinks: bool,
+
+    /// Don't link names to their files, even if the configuration links them.
+    #[arg(long, overrides_with = "hyperlinks")]
+    pub no_hyperlinks: bool,
+
+    /// Write the path as a breadcrumb, like `a › b › c`, instead of one long line.
+    /// With colors on, each directory in it can be clicked in terminals that support
+    /// OSC 8 hyperlinks.
+    #[arg(long, overrides_with = "no_breadcrumb")]
+    pub breadcrumb: bool,
+
+    /// Write the path as one line, even if the configuration writes a breadcrumb.
+    #[arg(long, overrides_with = "breadcrumb")]
+    pub no_breadcrumb: bool,
+
+    /// Don't print the number of directories and files after the tree.
+    #[arg(long)]
+    pub no_summary: bool,
+
+    /// Print how many entries are at each depth and under each top-level directory
+    /// after the tree.
+    #[arg(long)]
+    pub histogram: bool,
+
+    /// Show when each entry was last modified, using the `mtime_format` from the
+    /// configuration.
+    #[arg(long, overrides_with = "no_mtime")]
+    pub mtime: bool,
+
+    /// Don't show modification times, even if the configuration shows them.
+    #[arg(long, overrides_with = "mtime")]
+    pub no_mtime: bool,
+
+    /// Mark files that were modified within `--recent-window` with `[recent]`.
+    #[arg(long, overrides_with = "no_recent")]
+    pub recent: bool,
+
+    /// Don't mark recently modified files, even if the configuration marks them.
+    #[arg(long, overrides_with = "recent")]
+    pub no_recent: bool,
+
+    /// How recently files must have been modified to be marked by `--recent`, like
+    /// 90s, 15min, 2h, or 1d. Defaults to 15min.
+    #[arg(long, value_name = "DURATION")]
+    pub recent_window: Option<tree::RecentWindow>,
+
+    /// Highlight files larger than this size, like 50M.
+    #[arg(long, value_name = "SIZE")]
+    pub warn_size: Option<tree::Size>,
+
+    /// Exit with an error and list the files larger than `--warn-size` or the
+    /// configured `warn_size`, if there are any. Also exit with an error if there
+    /// were any errors, like from the configuration, instead of only warning.
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Suggest `.gitignore` lines for untracked build artifacts, like `target/` or
+    /// `*.o`, after the tree.
+    #[arg(long)]
+    pub suggest_ignores: bool,
+
+    /// After the tree, list empty directories and/or entries that are untracked or
+    /// ignored.
+    #[arg(long, value_delimiter = ',', value_name = "REPORTS")]
+    pub report: Vec<tree::report::Report>,
+
+    /// Show the number of commits that changed each file.
+    #[arg(long)]
+    pub churn: bool,
+
+    /// Write the tree before git statuses are ready, and list the statuses of its
+    /// entries after it. This starts output sooner in large repositories.
+    #[arg(long)]
+    pub two_pass: bool,
+
+    /// Show files that were deleted but are still tracked by git, struck through in
+    /// the color of removed files.
+    ///
+    /// Deleted files are filtered by `--all` and `--pattern`. The configuration's
+    /// `skip` function isn't called with them, since they don't have attributes.
+    #[arg(long)]
+    pub deleted: bool,
+
+    /// Write the tree level by level, listing every entry at one depth before the
+    /// entries at the next depth, under the paths of their directories.
+    #[arg(long)]
+    pub bfs: bool,
+
+    /// Write the git branch, how far it is ahead of and behind its upstream branch,
+    /// and how many files changed above the tree.
+    #[arg(long)]
+    pub git_header: bool,
+
+    /// Only count commits since a date (YYYY-MM-DD) or a time ago (like 30d, 2w, 6mo,
+    /// or 1y).
+    #[arg(long, requires = "churn")]
+    pub since: Option<Since>,
+
+    /// Also write a timestamped JSON export of the tree to this directory.
+    ///
+    /// Snapshots can be compared with the `diff-json` command.
+    #[arg(long, value_name = "DIR", value_parser = path_parser())]
+    pub snapshot_dir: Option<PathBuf>,
+
+    /// Color file names by the age of their most recent commit.
+    #[arg(long)]
+    pub age: bool,
+
+    /// Disable everything that depends on the environment (configuration files,
+    /// the global git excludes file, platform-specific sorting, color detection, and
+    /// paging), so that the output is identical across machines.
+    #[arg(long)]
+    pub deterministic: bool,
+
+    /// Use the configuration files in this directory. Defaults to the
+    /// `FANCY_TREE_CONFIG_DIR` environment variable, or else the platform's
+    /// configuration directory.
+    #[arg(long, value_name = "PATH", value_parser = path_parser())]
+    pub config_dir: Option<PathBuf>,
+
+    /// Use this file instead of the main configuration file.
+    #[arg(long, value_name = "PATH", value_parser = path_parser(), conflicts_with = "deterministic")]
+    pub config_file: Option<PathBuf>,
+
+    /// Don't merge the project's `.fancytree/config.lua` or `.fancytree.lua` over
+    /// the main configuration.
+    #[arg(long)]
+    pub no_project_config: bool,
+
+    /// Don't sandbox the project's configuration file, so that it can use all of
+    /// Lua and set every option, like `annotations` and `wasm_plugins`. Only use this
+    /// for projects you trust.
+    #[arg(long, conflicts_with = "no_project_config")]
+    pub trust_project_config: bool,
+
+    /// Write the default configuration files, whose comments explain each value and
+    /// function that can be set, and exit. Files that already exist are kept.
+    #[arg(long)]
+    pub bootstrap: bool,
+
+    /// Print where each configuration file is loaded from and exit.
+    #[arg(long)]
+    pub print_config: bool,
+
+    /// Load the configuration files, call their functions with sample entries, and
+    /// report their errors instead of writing a tree.
+    #[arg(long)]
+    pub check_config: bool,
+
+    /// Edit the main configuration file and exit. The file is checked after the
+    /// editor closes, and can be reopened to fix its errors.
+    ///
+    /// The editor is `$FANCY_TREE_EDITOR`, `$VISUAL`, or `$EDITOR`, or else a
+    /// common editor that's installed.
     #[arg(long, num_args = 0..=1, default_missing_value = "config")]
     pub edit_config: Option<EditConfig>,
+
+    /// Print the path to a configuration file and exit, so that it can be edited
+    /// some other way. The file might not exist yet.
+    #[arg(long, num_args = 0..=1, default_missing_value = "config", value_name = "WHICH")]
+    pub print_config_path: Option<EditConfig>,
+
+    /// Instead of editing, restore the default configuration file, saving the current
+    /// one with a `.bak` extension. Existing backups are kept, and a new backup is
+    /// numbered, like `.bak.1`.
+    #[arg(long, requires = "edit_config", conflicts_with = "diff")]
+    pub reset: bool,
+
+    /// Instead of editing, show how the configuration file differs from the default.
+    #[arg(long, requires = "edit_config")]
+    pub diff: bool,
+}
+
+/// Gets the value of a flag and its `--no-` flag, or `None` if neither was passed,
+/// so that the configuration is used.
+fn flag(yes: bool, no: bool) -> Option<bool> {
+    (yes || no).then_some(yes)
+}
+
+/// Parses paths, expanding `~` and environment variables.
+fn path_parser() -> impl TypedValueParser<Value = PathBuf> {
+    OsStringValueParser::new().try_map(expand::parse_path)
+}
+
+/// Commands other than listing files.
+#[derive(Subcommand)]
+pub enum Command {
+    /// Compare two trees exported with `--output json` or `--output json-flat`, and
+    /// show the entries that were added, removed, or changed.
+    DiffJson {
+        /// The older export.
+        #[arg(value_parser = path_parser())]
+        old: PathBuf,
+        /// The newer export.
+        #[arg(value_parser = path_parser())]
+        new: PathBuf,
+    },
+    /// Write every built-in rule that maps filenames, extensions, and globs to icons
+    /// and colors, for tools that generate themes or documentation.
+    #[command(hide = true)]
+    DumpRules {
+        /// The format to write the rules in.
+        #[arg(long, value_name = "FORMAT", default_value = "json")]
+        output: RulesFormat,
+    },
+}
+
+/// Choices for the format of `dump-rules`.
+#[derive(ValueEnum, Clone, Copy)]
+pub enum RulesFormat {
+    /// Pretty-printed JSON, with the icon rules and the color rules in the order
+    /// that they're checked.
+    Json,
 }
 
 /// Choices for which config file to edit.
@@ -48,14 +415,45 @@ pub enum EditConfig {
 }
 
 impl Cli {
-    /// An environment variable the user can set to specify which editor to use.
-    const EDITOR_ENV_VAR: &str = "FANCY_TREE_EDITOR";
+    /// An environment variable the user can set to hide hints, like the one on the
+    /// first run.
+    const NO_HINTS_ENV_VAR: &str = "FANCY_TREE_NO_HINTS";
 
     /// Runs the CLI.
     pub fn run(&self) -> crate::Result {
         // NOTE Early return for edit mode
         if let Some(edit_config) = self.edit_config {
-            return self.edit_file(edit_config);
+            return if self.reset {
+                self.reset_file(edit_config)
+            } else if self.diff {
+                self.diff_file(edit_config)
+            } else {
+                self.edit_file(edit_config)
+            };
+        }
+
+        if let Some(which) = self.print_config_path {
+            let config_dir = self.config_dir()?;
+            println!("{}", Self::config_file(&config_dir, which).0.display());
+            return Ok(());
+        }
+
+        if self.bootstrap {
+            return self.bootstrap();
+        }
+
+        if self.print_config {
+            return self.print_config();
+        }
+
+        if self.check_config {
+            return self.check_config();
+        }
+
+        match self.command {
+            Some(Command::DiffJson { ref old, ref new }) => return self.diff_json(old, new),
+            Some(Command::DumpRules { output }) => return Self::dump_rules(output),
+            None => {}
         }
 
         self.run_tree()
@@ -63,51 +461,68 @@ impl Cli {
 
     /// Runs the main tree functionality.
     fn run_tree(&self) -> crate::Result {
-        let git = Git::new(&self.path).expect("Should be able to read the git repository");
+        if !self.deterministic && self.config_file.is_none() {
+            self.print_first_run_hint();
+        }
 
-        // NOTE The Lua state must live as long as the configuration values.
-        let lua_state = {
-            let mut builder = lua::state::Builder::new();
-            if let Some(ref git) = git {
-                builder = builder.with_git(git);
-            }
-            builder.build().expect("The lua state should be valid")
+        // NOTE Errors are collected so that the tree is still written, and are
+        //      reported after it.
+        let diagnostics = Diagnostics::default();
+        let mut setup = Setup::new(&self.path, diagnostics.clone()).theme(self.theme.as_deref());
+        setup = if self.deterministic {
+            setup.deterministic()
+        } else {
+            setup.loader(self.config_loader())
         };
+        setup.run(|parts| self.write_tree(parts, &diagnostics))
+    }
 
-        // TODO Skip loading the config instead of panicking.
-        let config_dir = ConfigDir::new().expect("A config dir should be available");
+    /// Writes the tree with the parts from the setup, and the reports after it.
+    fn write_tree(&self, parts: Parts<'_, '_>, diagnostics: &Diagnostics) -> crate::Result {
+        let git = parts.git;
+        let vcs = parts.vcs;
+        let lua_state = parts.lua_state;
+        let config = parts.config.as_ref();
 
-        let lua_inner = lua_state.to_inner();
-        let config = config_dir
-            .load_main(lua_inner)
-            .expect("The configuration should be valid");
-        let icons = config_dir
-            .load_icons(lua_inner)
-            .expect("The icon configuration should be valid");
-        let colors = config_dir
-            .load_colors(lua_inner)
-            .expect("The color configuration should be valid");
-
-        let mut builder = tree::Builder::new(&self.path);
+        // NOTE The icons are detected unless they're chosen, and deterministic output
+        //      always uses the same icons.
+        let icon_set = self.icon_set.or_else(|| {
+            let is_configured = config.is_some_and(|config| config.icon_set().is_some());
+            (!self.deterministic && !is_configured).then_some(IconSet::Auto)
+        });
+        let paging = self.paging(config);
+
+        let mut builder = parts
+            .builder(&self.path)
+            .format(self.format())
+            .lang(self.lang());
+        if let Some(ref base) = self.html_base {
+            builder = builder.html_base(base);
+        }
+        if self.markdown_code {
+            builder = builder.markdown_code();
+        }
 
         // NOTE Apply configuration overrides from CLI.
         if let Some(color_choice) = self.color_choice {
             builder = builder.color_choice(color_choice);
         }
 
-        // NOTE Apply configurations if they exist
-        if let Some(config) = config {
-            builder = builder.config(config);
-        }
-        if let Some(icons) = icons {
-            builder = builder.icons(icons);
-        }
-        if let Some(colors) = colors {
-            builder = builder.colors(colors);
+        if let Some(icon_set) = icon_set {
+            builder = builder.icon_set(icon_set);
         }
 
-        if let Some(ref git) = git {
-            builder = builder.git(git);
+        if let Some(vcs) = vcs {
+            if self.churn
+                && let Some(churn) = diagnostics.check(Source::Vcs, vcs.churn(self.since))
+            {
+                builder = builder.churn(churn);
+            }
+            if self.age
+                && let Some(last_commits) = diagnostics.check(Source::Vcs, vcs.last_commits())
+            {
+                builder = builder.last_commits(last_commits);
+            }
         }
 
         if let Some(level) = self.level {
@@ -116,25 +531,525 @@ impl Cli {
             builder = builder.unset_level();
         }
 
+        if let Some(max_lines) = self.max_lines {
+            builder = builder.max_lines(max_lines);
+        }
+        if let Some(filelimit) = self.filelimit {
+            builder = builder.file_limit(filelimit);
+        }
+        if let Some(filelimit_mode) = self.filelimit_mode {
+            builder = builder.file_limit_mode(filelimit_mode);
+        }
+
+        builder = if self.no_ignore {
+            builder.no_ignore()
+        } else if self.deterministic {
+            builder.ignore(Ignore::isolated(&self.path))
+        } else {
+            builder.ignore(Ignore::new(&self.path))
+        };
+        if self.show_hidden {
+            builder = builder.show_hidden();
+        }
+
+        if let Some(ref pattern) = self.pattern {
+            builder = builder.pattern(pattern.clone());
+        }
+        if let Some(prune) = flag(self.prune, self.no_prune) {
+            builder = builder.prune(prune);
+        }
+        if let Some(follow_symlinks) = flag(self.follow_symlinks, self.no_follow_symlinks) {
+            builder = builder.follow_symlinks(follow_symlinks);
+        }
+        if let Some(git_dir) = self.git_dir {
+            builder = builder.git_dir(git_dir);
+        }
+
+        if let Some(size_mode) = self.size_mode {
+            builder = builder.size_mode(size_mode);
+        }
+        if self.disk_usage {
+            builder = builder.disk_usage();
+        }
+        if let Some(charset) = self.charset {
+            builder = builder.charset(charset.charset());
+        }
+
+        if let Some(permissions) = flag(self.permissions, self.no_permissions) {
+            builder = builder.permissions(permissions);
+        }
+        if let Some(hyperlinks) = flag(self.hyperlinks, self.no_hyperlinks) {
+            builder = builder.hyperlinks(hyperlinks);
+        }
+        if let Some(breadcrumb) = flag(self.breadcrumb, self.no_breadcrumb) {
+            builder = builder.breadcrumb(breadcrumb);
+        }
+        if self.two_pass {
+            builder = builder.two_pass();
+        }
+        if self.deleted {
+            builder = builder.show_deleted();
+        }
+        if self.bfs {
+            builder = builder.breadth_first();
+        }
+        if let Some(sort) = self.sort {
+            builder = builder.sort_key(sort);
+        }
+        if self.reverse {
+            builder = builder.reverse();
+        }
+        if let Some(mtime) = flag(self.mtime, self.no_mtime) {
+            builder = builder.mtime(mtime);
+        }
+        if let Some(recent) = flag(self.recent, self.no_recent) {
+            builder = builder.recent(recent);
+        }
+        if let Some(window) = self.recent_window {
+            builder = builder.recent_window(window);
+        }
+
+        if let Some(warn_size) = self.warn_size {
+            builder = builder.warn_size(warn_size);
+        }
+
         let tree = builder.build();
 
-        lua_state.in_git_scope(|| tree.write_to_stdout().map_err(mlua::Error::external))?;
+        // NOTE Everything after the tree, like the summary, is paged with it.
+        let mut output = pager::Output::new(paging, self.lang());
+        lua_state.in_git_scope(|| {
+            if self.git_header && self.format().is_text() {
+                // NOTE The header is optional, so a repository that can't be read just
+                //      doesn't get one.
+                if let Some(header) = git.and_then(|git| git.header().ok()) {
+                    writeln!(output, "{header}\n").map_err(mlua::Error::external)?;
+                }
+            }
+            tree.write(&mut output).map_err(mlua::Error::external)?;
+            if !self.no_summary && self.format().is_text() {
+                lua_state.set_stats(tree.stats())?;
+                if let Some(summary) = tree.summary() {
+                    writeln!(output, "\n{summary}").map_err(mlua::Error::external)?;
+                }
+            }
+            if self.histogram && self.format().is_text() {
+                write!(output, "\n{}", tree.histogram()).map_err(mlua::Error::external)?;
+            }
+            if let Some(ref snapshot_dir) = self.snapshot_dir {
+                tree.write_snapshot(snapshot_dir)
+                    .map_err(mlua::Error::external)?;
+            }
+            if !self.report.is_empty() {
+                let findings = tree.report(&self.report).map_err(mlua::Error::external)?;
+                findings
+                    .write(&mut output, self.lang())
+                    .map_err(mlua::Error::external)?;
+            }
+            if self.suggest_ignores {
+                let mut suggestions = tree.suggest_ignores().map_err(mlua::Error::external)?;
+                if !suggestions.is_empty() {
+                    tree::suggest::Suggestion::write_all(&mut output, &mut suggestions)
+                        .map_err(mlua::Error::external)?;
+                }
+            }
+            Ok(())
+        })?;
+        // NOTE Warnings are written after the pager quits, so that they aren't
+        //      written over it.
+        output.finish()?;
+
+        // NOTE Plugins are isolated, so their errors are warnings instead of failing.
+        let lang = self.lang();
+        for error in tree.plugins().errors() {
+            eprintln!("{}", lang.message("warning", &[("message", &error)]));
+        }
+        for error in tree.wasm_plugins().errors() {
+            eprintln!("{}", lang.message("warning", &[("message", &error)]));
+        }
+        let diagnostics = diagnostics.take();
+        for diagnostic in diagnostics.iter() {
+            eprintln!("{}", lang.message("warning", &[("message", diagnostic)]));
+        }
+
+        if self.strict {
+            if !diagnostics.is_empty() {
+                let count = diagnostics.len();
+                return Err(lang
+                    .message("errors-while-writing", &[("count", &count)])
+                    .into());
+            }
+            let oversized =
+                lua_state.in_git_scope(|| tree.oversized_files().map_err(mlua::Error::external))?;
+            if !oversized.is_empty() {
+                let warn_size = tree
+                    .warn_size()
+                    .expect("Files are only oversized if there is a warning size");
+                eprintln!(
+                    "{}",
+                    lang.message("files-larger-than", &[("size", &warn_size)])
+                );
+                for (path, size) in oversized.iter() {
+                    eprintln!("  {} ({size})", path.display());
+                }
+                let count = oversized.len();
+                return Err(lang
+                    .message(
+                        "files-larger-than-count",
+                        &[("count", &count), ("size", &warn_size)],
+                    )
+                    .into());
+            }
+        }
 
         Ok(())
     }
 
-    /// Opens an editor for the file the user specified, creating the config directory
-    /// if needed.
-    fn edit_file(&self, edit_config: EditConfig) -> crate::Result {
-        let config_dir = ConfigDir::new()?;
-        fs::create_dir_all(config_dir.path())?;
+    /// Gets when to page the tree. Deterministic output is only paged when it's
+    /// requested.
+    fn paging(&self, config: Option<&config::Main>) -> Paging {
+        self.paging
+            .or_else(|| self.deterministic.then_some(Paging::Never))
+            .or_else(|| config.and_then(|config| config.paging()))
+            .unwrap_or_default()
+    }
+
+    /// Gets the language of messages. Deterministic output is always in English.
+    fn lang(&self) -> Lang {
+        if self.deterministic {
+            Lang::En
+        } else {
+            self.lang.unwrap_or_else(Lang::detect)
+        }
+    }
+
+    /// Gets the format to write the tree in.
+    fn format(&self) -> tree::Format {
+        if self.json {
+            tree::Format::Json
+        } else if self.flat {
+            tree::Format::Flat
+        } else if self.accessible {
+            tree::Format::Accessible
+        } else {
+            self.output
+        }
+    }
+
+    /// Compares two exported trees and writes the differences.
+    fn diff_json(&self, old: &Path, new: &Path) -> crate::Result {
+        /// Reads either kind of JSON export.
+        fn read_export(path: &Path) -> crate::Result<Vec<tree::export::FlatNode>> {
+            let reader = BufReader::new(File::open(path)?);
+            let export: tree::export::diff::Export = serde_json::from_reader(reader)?;
+            Ok(export.into_flat())
+        }
+
+        let diff = tree::export::diff::Diff::new(read_export(old)?, read_export(new)?);
+
+        let lua_state = lua::state::Builder::new().build()?;
+        let (config, colors) = if self.deterministic {
+            (config::Main::deterministic(), None)
+        } else {
+            let (config, _, colors) = self.load_configs(&lua_state);
+            (config.unwrap_or_default(), colors)
+        };
+        let colors = colors.unwrap_or_default();
+        let color_choice = self.color_choice.unwrap_or(config.color_choice());
+        let charset = self
+            .charset
+            .map(tree::CharsetStyle::charset)
+            .or_else(|| config.charset().cloned())
+            .unwrap_or_default();
+
+        let mut stdout = io::stdout();
+        diff.write(&mut stdout, &charset, &colors, color_choice)?;
+        for diagnostic in lua_state.diagnostics().take() {
+            eprintln!(
+                "{}",
+                self.lang().message("warning", &[("message", &diagnostic)])
+            );
+        }
+        Ok(())
+    }
+
+    /// Writes the built-in rules for icons and colors.
+    fn dump_rules(format: RulesFormat) -> crate::Result {
+        let rules = Rules::builtin();
+        match format {
+            RulesFormat::Json => rules.write_json(io::stdout().lock())?,
+        }
+        Ok(())
+    }
+
+    /// Loads the user's configuration files.
+    fn load_configs(
+        &self,
+        lua_state: &lua::state::State,
+    ) -> (
+        Option<config::Main>,
+        Option<config::Icons>,
+        Option<config::Colors>,
+    ) {
+        session::load_configs(lua_state, &self.config_loader(), self.theme.as_deref())
+    }
+
+    /// Gets the configuration directory, which can be set with `--config-dir`.
+    fn config_dir(&self) -> Result<ConfigDir, &'static str> {
+        ConfigDir::new(self.config_dir.as_deref())
+    }
+
+    /// Creates the loader for the configuration files.
+    fn config_loader(&self) -> ConfigLoader {
+        let mut loader = ConfigLoader::new();
+        // TODO Report a missing home directory instead of only using the defaults.
+        if let Ok(config_dir) = self.config_dir() {
+            loader = loader.global_dir(config_dir);
+        }
+        if let Some(ref path) = self.config_file {
+            loader = loader.file::<config::Main, _>(path);
+        }
+        if let Some(overrides) = self.project_overrides() {
+            loader = loader.project_overrides(overrides);
+        }
+        loader
+    }
+
+    /// Finds the project's configuration file, unless it's skipped with
+    /// `--no-project-config`.
+    fn project_overrides(&self) -> Option<ProjectOverrides> {
+        if self.no_project_config {
+            return None;
+        }
+        let overrides = session::project_overrides(&self.path)?;
+        Some(if self.trust_project_config {
+            overrides.trusted()
+        } else {
+            overrides
+        })
+    }
+
+    /// Prints where each configuration file is loaded from.
+    fn print_config(&self) -> crate::Result {
+        /// Prints the source of the configuration file of type `T`.
+        fn print_source<T>(loader: &ConfigLoader)
+        where
+            T: ConfigFile,
+        {
+            println!("{}: {}", T::FILENAME, loader.source::<T>());
+        }
+
+        let loader = self.config_loader();
+        print_source::<config::Main>(&loader);
+        print_source::<config::Icons>(&loader);
+        print_source::<config::Colors>(&loader);
+        if let Some(overrides) = loader.overrides() {
+            let kind = if overrides.is_trusted() {
+                "trusted"
+            } else {
+                "sandboxed"
+            };
+            println!("overrides: {} ({kind})", overrides.path().display());
+        }
+        Ok(())
+    }
+
+    /// Checks each configuration file, printing where it's loaded from and the errors
+    /// from loading it and calling its functions.
+    fn check_config(&self) -> crate::Result {
+        /// Prints the source of the configuration file of type `T`, and its errors.
+        fn print_errors<T>(loader: &ConfigLoader, errors: &[Diagnostic], lang: Lang)
+        where
+            T: ConfigFile,
+        {
+            let status = match errors.len() {
+                0 => lang.message("config-check-ok", &[]),
+                count => lang.message("config-check-errors", &[("count", &count)]),
+            };
+            println!("{}: {}: {status}", T::FILENAME, loader.source::<T>());
+            for error in errors {
+                // NOTE Tracebacks are indented with the rest of the error.
+                println!("  {}", error.message.replace('\n', "\n  "));
+            }
+        }
+
+        let diagnostics = Diagnostics::default();
+        let lua_state = lua::state::Builder::new()
+            .with_diagnostics(diagnostics.clone())
+            .build()?;
+        let (config, icons, colors) = self.load_configs(&lua_state);
+        config::check::run(config.as_ref(), icons.as_ref(), colors.as_ref())?;
+
+        let loader = self.config_loader();
+        let lang = self.lang();
+        let errors = diagnostics.take();
+        let errors_from = |source| {
+            errors
+                .iter()
+                .filter(|error| error.source == source)
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        print_errors::<config::Main>(&loader, &errors_from(Source::Config), lang);
+        print_errors::<config::Icons>(&loader, &errors_from(Source::Icons), lang);
+        print_errors::<config::Colors>(&loader, &errors_from(Source::Colors), lang);
 
-        let (file_path, default_contents) = match edit_config {
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            let count = errors.len();
+            Err(lang
+                .message("config-error-count", &[("count", &count)])
+                .into())
+        }
+    }
+
+    /// Writes the default configuration files that don't exist yet.
+    fn bootstrap(&self) -> crate::Result {
+        let config_dir = self.config_dir()?;
+        let lang = self.lang();
+        for (path, written) in config_dir.bootstrap()? {
+            let id = if written {
+                "config-created"
+            } else {
+                "config-kept"
+            };
+            println!("{}", lang.message(id, &[("path", &path.display())]));
+        }
+        Ok(())
+    }
+
+    /// Prints where the configuration files go and how to create them, if none of
+    /// them exist. This is only printed once, and only to a terminal.
+    fn print_first_run_hint(&self) {
+        let hints_disabled =
+            std::env::var_os(Self::NO_HINTS_ENV_VAR).is_some_and(|value| !value.is_empty());
+        if hints_disabled || !io::stderr().is_terminal() {
+            return;
+        }
+        let Ok(config_dir) = self.config_dir() else {
+            return;
+        };
+        let hint_path = config_dir.hint_path();
+        if !config_dir.is_unconfigured() || hint_path.try_exists().unwrap_or(true) {
+            return;
+        }
+        // NOTE If the hint can't be recorded, it's skipped instead of shown every time.
+        let recorded =
+            fs::create_dir_all(config_dir.cache_path()).and_then(|()| fs::write(&hint_path, ""));
+        if recorded.is_err() {
+            return;
+        }
+        let hint = self.lang().message(
+            "first-run-hint",
+            &[
+                ("dir", &config_dir.path().display()),
+                ("var", &Self::NO_HINTS_ENV_VAR),
+            ],
+        );
+        eprintln!("{hint}");
+    }
+
+    /// Gets the path of the config file the user specified, and its default contents.
+    fn config_file(config_dir: &ConfigDir, edit_config: EditConfig) -> (PathBuf, &'static str) {
+        match edit_config {
             EditConfig::Config => (config_dir.main_path(), config::Main::DEFAULT_MODULE),
             EditConfig::Icons => (config_dir.icons_path(), config::Icons::DEFAULT_MODULE),
             EditConfig::Colors => (config_dir.colors_path(), config::Colors::DEFAULT_MODULE),
+        }
+    }
+
+    /// Restores the default contents of the file the user specified, backing up the
+    /// existing file.
+    fn reset_file(&self, edit_config: EditConfig) -> crate::Result {
+        let lang = self.lang();
+        let config_dir = self.config_dir()?;
+        fs::create_dir_all(config_dir.path())?;
+        let (file_path, default_contents) = Self::config_file(&config_dir, edit_config);
+
+        if file_path.try_exists()? {
+            let backup_path = back_up(&file_path)?;
+            let message = lang.message(
+                "config-saved-as",
+                &[
+                    ("path", &file_path.display()),
+                    ("backup", &backup_path.display()),
+                ],
+            );
+            println!("{message}");
+        }
+        fs::write(&file_path, default_contents)?;
+        println!(
+            "{}",
+            lang.message("config-restored", &[("path", &file_path.display())])
+        );
+
+        Ok(())
+    }
+
+    /// Shows how the file the user specified differs from the default contents.
+    fn diff_file(&self, edit_config: EditConfig) -> crate::Result {
+        /// The color of lines that were removed from the default.
+        const REMOVED_COLOR: Option<Color> = Some(Color::Ansi(AnsiColors::Red));
+        /// The color of lines that were added to the default.
+        const ADDED_COLOR: Option<Color> = Some(Color::Ansi(AnsiColors::Green));
+        /// The color of the hunk headers.
+        const HUNK_COLOR: Option<Color> = Some(Color::Ansi(AnsiColors::Cyan));
+
+        let lang = self.lang();
+        let config_dir = self.config_dir()?;
+        let (file_path, default_contents) = Self::config_file(&config_dir, edit_config);
+
+        // NOTE A missing file is the same as the default.
+        let contents = match fs::read_to_string(&file_path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                println!(
+                    "{}",
+                    lang.message("config-missing", &[("path", &file_path.display())])
+                );
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
         };
 
+        let diff = TextDiff::from_lines(default_contents, &contents);
+        if diff.ratio() == 1.0 {
+            println!(
+                "{}",
+                lang.message("config-same-as-default", &[("path", &file_path.display())])
+            );
+            return Ok(());
+        }
+
+        let color_choice = self.color_choice.unwrap_or(ColorChoice::Auto);
+        let mut stdout = io::stdout();
+        let file_path = file_path.display().to_string();
+        let mut unified = diff.unified_diff();
+        let unified = unified.header("default", &file_path);
+        for line in unified.to_string().lines() {
+            let color = match line.chars().next() {
+                _ if line.starts_with("---") || line.starts_with("+++") => None,
+                Some('-') => REMOVED_COLOR,
+                Some('+') => ADDED_COLOR,
+                Some('@') => HUNK_COLOR,
+                _ => None,
+            };
+            color_choice.write_to(&mut stdout, line, color)?;
+            writeln!(stdout)?;
+        }
+
+        Ok(())
+    }
+
+    /// Opens an editor for the file the user specified, creating the config directory
+    /// if needed. After the editor closes, the file's errors are printed, and the
+    /// user can reopen it.
+    fn edit_file(&self, edit_config: EditConfig) -> crate::Result {
+        let config_dir = self.config_dir()?;
+        fs::create_dir_all(config_dir.path())?;
+
+        let (file_path, default_contents) = Self::config_file(&config_dir, edit_config);
+
         // NOTE If we can't check if it exists, we'll be safe and skip overwriting it.
         if !file_path.try_exists().unwrap_or(false) {
             // NOTE Ignore error, because editing the file is a higher priority than
@@ -142,19 +1057,255 @@ impl Cli {
             let _ = fs::write(&file_path, default_contents);
         }
 
-        println!("Opening `{}`", file_path.display());
+        let lang = self.lang();
+        let path = file_path.display();
+        let editor = Editor::find().map_err(|e| e.message(lang))?;
+        loop {
+            println!("{}", lang.message("editor-opening", &[("path", &path)]));
+            editor.open(&file_path)?;
 
-        let finder = find_editor::Finder::with_extra_environment_variables([Self::EDITOR_ENV_VAR]);
-        /// Should the program wait for the editor to close before continuing?
-        const WAIT: bool = true;
-        finder.open_editor(file_path, WAIT)?;
+            let errors = Self::validate_file(&file_path, edit_config)?;
+            if errors.is_empty() {
+                return Ok(());
+            }
+            eprintln!("{}", lang.message("editor-errors", &[("path", &path)]));
+            for error in errors.iter() {
+                // NOTE Tracebacks are indented with the rest of the error.
+                eprintln!("  {}", error.message.replace('\n', "\n  "));
+            }
+            if !Self::confirm(&lang.message("editor-reopen", &[]))? {
+                let count = errors.len();
+                return Err(lang
+                    .message("editor-error-count", &[("count", &count), ("path", &path)])
+                    .into());
+            }
+        }
+    }
 
-        Ok(())
+    /// Loads a configuration file that was edited and calls its functions with sample
+    /// entries, returning the errors.
+    fn validate_file(file_path: &Path, edit_config: EditConfig) -> crate::Result<Vec<Diagnostic>> {
+        let diagnostics = Diagnostics::default();
+        let lua_state = lua::state::Builder::new()
+            .with_diagnostics(diagnostics.clone())
+            .build()?;
+        let lua_inner = lua_state.to_inner();
+        // NOTE The file is loaded by itself, without the bytecode cache or the other
+        //      configuration files, so that its errors aren't hidden.
+        let (config, icons, colors) = match edit_config {
+            EditConfig::Config => {
+                let loader = ConfigLoader::new().file::<config::Main, _>(file_path);
+                let config = diagnostics
+                    .check(Source::Config, loader.load_main(lua_inner))
+                    .and_then(|config| config.value);
+                (config, None, None)
+            }
+            EditConfig::Icons => {
+                let loader = ConfigLoader::new().file::<config::Icons, _>(file_path);
+                let icons = diagnostics
+                    .check(Source::Icons, loader.load_icons(lua_inner, None))
+                    .and_then(|icons| icons.value);
+                (None, icons, None)
+            }
+            EditConfig::Colors => {
+                let loader = ConfigLoader::new().file::<config::Colors, _>(file_path);
+                let colors = diagnostics
+                    .check(Source::Colors, loader.load_colors(lua_inner, None))
+                    .and_then(|colors| colors.value);
+                (None, None, colors)
+            }
+        };
+        config::check::run(config.as_ref(), icons.as_ref(), colors.as_ref())?;
+        Ok(diagnostics.take())
+    }
+
+    /// Asks the user a yes-or-no question, where the default is yes. The answer is no
+    /// without asking when stdin isn't a terminal, since nobody could answer.
+    fn confirm(question: &str) -> io::Result<bool> {
+        let stdin = io::stdin();
+        if !stdin.is_terminal() {
+            return Ok(false);
+        }
+        print!("{question} [Y/n] ");
+        io::stdout().flush()?;
+        Self::read_answer(&mut stdin.lock())
+    }
+
+    /// Reads the answer to a yes-or-no question. An empty line is yes, but the end of
+    /// the input is no, since nobody answered.
+    fn read_answer<R>(reader: &mut R) -> io::Result<bool>
+    where
+        R: BufRead,
+    {
+        let mut answer = String::new();
+        if reader.read_line(&mut answer)? == 0 {
+            return Ok(false);
+        }
+        let answer = answer.trim();
+        Ok(answer.is_empty()
+            || answer.eq_ignore_ascii_case("y")
+            || answer.eq_ignore_ascii_case("yes"))
+    }
+}
+
+/// Copies a file to a new backup next to it, like `config.lua.bak`, and returns the
+/// backup's path. When backups already exist, the next number is used, like
+/// `config.lua.bak.1`, so that no backup is overwritten.
+fn back_up(path: &Path) -> io::Result<PathBuf> {
+    let mut original = File::open(path)?;
+    for number in 0.. {
+        let mut backup_path = path.as_os_str().to_os_string();
+        backup_path.push(".bak");
+        if number > 0 {
+            backup_path.push(format!(".{number}"));
+        }
+        let backup_path = PathBuf::from(backup_path);
+        // NOTE The backup is created only if it doesn't exist, so that a backup
+        //      that's created at the same time isn't overwritten either.
+        let mut backup = match File::create_new(&backup_path) {
+            Ok(backup) => backup,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        };
+        io::copy(&mut original, &mut backup)?;
+        return Ok(backup_path);
     }
+    unreachable!("There should be a backup number that isn't used")
 }
 
 // Runs the CLI. Can exit early without returning an error. For example, this will exit
 // early if the user passes `-h` as CLI argument.
 pub fn run() -> crate::Result {
-    Cli::parse().run()
+    // NOTE A reader that stops early, like `fancy-tree dump-rules | head`, closes the
+    //      pipe, which isn't an error.
+    Cli::parse().run().or_else(|error| {
+        if is_broken_pipe(&*error) {
+            Ok(())
+        } else {
+            Err(error)
+        }
+    })
+}
+
+/// Checks if an error is from writing to a pipe that was closed.
+fn is_broken_pipe(error: &(dyn std::error::Error + 'static)) -> bool {
+    error
+        .downcast_ref::<io::Error>()
+        .is_some_and(|error| error.kind() == io::ErrorKind::BrokenPipe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Fixture;
+    use rstest::rstest;
+    use std::iter;
+
+    #[rstest]
+    #[case("", false)]
+    #[case("\n", true)]
+    #[case("y\n", true)]
+    #[case(" YES \n", true)]
+    #[case("n\n", false)]
+    #[case("maybe", false)]
+    fn test_read_answer(#[case] input: &str, #[case] expected: bool) {
+        let answer = Cli::read_answer(&mut input.as_bytes()).unwrap();
+        assert_eq!(expected, answer);
+    }
+
+    #[rstest]
+    #[case(EditConfig::Config, "return {}", 0)]
+    #[case(EditConfig::Config, "return {", 1)]
+    #[case(
+        EditConfig::Config,
+        "return { skip = function() error('oops') end }",
+        1
+    )]
+    #[case(EditConfig::Icons, "return function() return 1 + {} end", 1)]
+    #[case(EditConfig::Colors, "return {}", 0)]
+    fn test_validate_file(
+        #[case] edit_config: EditConfig,
+        #[case] contents: &str,
+        #[case] expected: usize,
+    ) {
+        let fixture = Fixture::builder()
+            .file("config.lua", contents)
+            .build()
+            .unwrap();
+
+        let errors = Cli::validate_file(&fixture.join("config.lua"), edit_config).unwrap();
+        assert_eq!(expected, errors.len(), "{errors:?}");
+    }
+
+    #[test]
+    fn test_validate_default_files() {
+        let fixture = Fixture::builder().build().unwrap();
+        let config_dir = ConfigDir::new(Some(fixture.path())).unwrap();
+        config_dir.bootstrap().unwrap();
+        for edit_config in [EditConfig::Config, EditConfig::Icons, EditConfig::Colors] {
+            let (path, _) = Cli::config_file(&config_dir, edit_config);
+            let errors = Cli::validate_file(&path, edit_config).unwrap();
+            assert!(errors.is_empty(), "{}: {errors:?}", path.display());
+        }
+    }
+
+    #[rstest]
+    #[case(&[], None)]
+    #[case(&["--prune"], Some(true))]
+    #[case(&["--no-prune"], Some(false))]
+    #[case(&["--prune", "--no-prune"], Some(false))]
+    #[case(&["--no-prune", "--prune"], Some(true))]
+    fn test_flag(#[case] args: &[&str], #[case] expected: Option<bool>) {
+        let cli = Cli::parse_from(iter::once("fancy-tree").chain(args.iter().copied()));
+        assert_eq!(expected, flag(cli.prune, cli.no_prune));
+    }
+
+    #[rstest]
+    #[case(&["--flat", "--sort", "size", "--reverse"], true)]
+    #[case(&["--sort", "size"], false)]
+    #[case(&["--reverse"], false)]
+    fn test_sort_requires_flat(#[case] args: &[&str], #[case] expected: bool) {
+        let cli = Cli::try_parse_from(iter::once("fancy-tree").chain(args.iter().copied()));
+        assert_eq!(expected, cli.is_ok());
+    }
+
+    #[rstest]
+    #[case(&[], Paging::Auto)]
+    #[case(&["--deterministic"], Paging::Never)]
+    #[case(&["--deterministic", "--paging", "always"], Paging::Always)]
+    fn test_paging(#[case] args: &[&str], #[case] expected: Paging) {
+        let cli = Cli::parse_from(iter::once("fancy-tree").chain(args.iter().copied()));
+        assert_eq!(expected, cli.paging(None));
+    }
+
+    #[test]
+    fn test_is_broken_pipe() {
+        let error: Box<dyn std::error::Error> = io::Error::from(io::ErrorKind::BrokenPipe).into();
+        assert!(is_broken_pipe(&*error));
+        let error: Box<dyn std::error::Error> = io::Error::other("failed").into();
+        assert!(!is_broken_pipe(&*error));
+        let error: Box<dyn std::error::Error> = "failed".into();
+        assert!(!is_broken_pipe(&*error));
+    }
+
+    #[test]
+    fn test_back_up() {
+        let fixture = Fixture::builder()
+            .file("config.lua", "new")
+            .file("config.lua.bak", "old")
+            .build()
+            .unwrap();
+        let path = fixture.join("config.lua");
+
+        let backup = back_up(&path).unwrap();
+        assert_eq!(fixture.join("config.lua.bak.1"), backup);
+        assert_eq!("new", fs::read_to_string(&backup).unwrap());
+        assert_eq!(
+            "old",
+            fs::read_to_string(fixture.join("config.lua.bak")).unwrap()
+        );
+
+        let backup = back_up(&path).unwrap();
+        assert_eq!(fixture.join("config.lua.bak.2"), backup);
+    }
 }
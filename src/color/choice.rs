@@ -1,5 +1,5 @@
 //! Module for color choices that control what colors actually get displayed.
-use super::Color;
+use super::{Color, Style};
 use clap::ValueEnum;
 use mlua::{FromLua, Lua};
 use owo_colors::{
@@ -8,17 +8,22 @@ use owo_colors::{
         BrightRed, BrightWhite, BrightYellow, Cyan, Green, Magenta, Red, White, Yellow,
     },
     DynColors, OwoColorize,
-    Stream::Stdout,
 };
+use std::env;
 use std::fmt::Display;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal as _, Write};
+use std::sync::LazyLock;
+use supports_color::Stream;
 
 /// Supports users choosing the colors they would like to display.
 #[derive(Debug, ValueEnum, Clone, Copy)]
 pub enum ColorChoice {
     /// Let the application decide.
     ///
-    /// *This checks if the `Stdout` stream supports colors.*
+    /// *This checks if the `Stdout` stream is a terminal that supports colors, and how
+    /// many. Colors that it doesn't support are replaced with the closest color that
+    /// it does. `NO_COLOR` turns colors off, `CLICOLOR_FORCE` turns them on even when
+    /// the output is redirected, and `CLICOLOR=0` turns them off.*
     Auto,
     /// Show all colors.
     On,
@@ -53,63 +58,69 @@ impl ColorChoice {
         matches!(self, Self::Off)
     }
 
-    /// Writes a colorized display value to the writer.
-    pub fn write_to<W, D>(
-        &self,
-        writer: &mut W,
-        display: D,
-        fg: Option<Color>,
-        bg: Option<Color>,
-    ) -> io::Result<()>
+    /// Detects if colors are off if this is [`ColorChoice::Auto`], so that callers can
+    /// skip calculating colors that wouldn't be written.
+    pub fn resolve(self) -> Self {
+        match self {
+            Self::Auto if ColorDepth::detect().is_none() => Self::Off,
+            choice => choice,
+        }
+    }
+
+    /// Checks if the environment turns colors on or off, using `var` to read
+    /// environment variables. `is_terminal` is if `Stdout` is a terminal. `None`
+    /// means that the terminal's support decides.
+    ///
+    /// This follows <https://no-color.org> and <https://bixense.com/clicolors>:
+    /// `NO_COLOR` wins over `CLICOLOR_FORCE`, which wins over the other checks.
+    fn env_colors<F>(var: F, is_terminal: bool) -> Option<bool>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let var = |name| var(name).filter(|value| !value.is_empty());
+        if var("NO_COLOR").is_some() {
+            Some(false)
+        } else if var("CLICOLOR_FORCE").is_some_and(|value| value != "0") {
+            Some(true)
+        } else if !is_terminal || var("CLICOLOR").is_some_and(|value| value == "0") {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Writes a styled display value to the writer. The style can be a [`Style`],
+    /// or a [`Color`] for the foreground.
+    pub fn write_to<W, D, S>(&self, writer: &mut W, display: D, style: S) -> io::Result<()>
     where
         W: Write,
         D: Display + OwoColorize,
+        S: Into<Style>,
     {
-        match (self, fg, bg) {
+        let style = style.into();
+        match self {
             // NOTE These variants must be on top
-            (Self::Off, _, _) | (_, None, None) => Self::off_write_to(writer, display),
-            (Self::Auto, fg, bg) => Self::auto_write_to(writer, display, fg, bg),
-            (Self::On, fg, bg) => Self::on_write_to(writer, display, fg, bg),
-            (Self::Ansi, fg, bg) => Self::ansi_write_to(writer, display, fg, bg),
+            _ if style.is_plain() => Self::off_write_to(writer, display),
+            Self::Off => Self::off_write_to(writer, display),
+            Self::Auto => Self::auto_write_to(writer, display, style),
+            Self::On => Self::on_write_to(writer, display, style),
+            Self::Ansi => Self::ansi_write_to(writer, display, style),
         }
     }
 
     /// Writes the display with color support detected.
-    fn auto_write_to<W, D, Fg, Bg>(
-        writer: &mut W,
-        display: D,
-        fg: Option<Fg>,
-        bg: Option<Bg>,
-    ) -> io::Result<()>
+    fn auto_write_to<W, D>(writer: &mut W, display: D, style: Style) -> io::Result<()>
     where
         W: Write,
         D: Display + OwoColorize,
-        DynColors: From<Fg>,
-        DynColors: From<Bg>,
     {
-        let fg = fg.map(DynColors::from);
-        let bg = bg.map(DynColors::from);
-
         // HACK This assumes that the writer is always Stdout, which might not be best
         //      if we ever support other writers (Stderr, file, etc.).
-        match (fg, bg) {
-            (None, None) => unreachable!("Should use the off writer"),
-            (Some(fg), None) => write!(
-                writer,
-                "{}",
-                display.if_supports_color(Stdout, |display| display.color(fg))
-            ),
-            (None, Some(bg)) => write!(
-                writer,
-                "{}",
-                display.if_supports_color(Stdout, |display| display.on_color(bg))
-            ),
-            (Some(fg), Some(bg)) => write!(
-                writer,
-                "{}",
-                display.if_supports_color(Stdout, |display| display.color(fg).on_color(bg))
-            ),
-        }
+        let Some(depth) = ColorDepth::detect() else {
+            return Self::off_write_to(writer, display);
+        };
+        let style = style.map_colors(|color| depth.convert(color));
+        Self::on_write_to(writer, display, style)
     }
 
     /// Writes the display with no colorization.
@@ -123,74 +134,126 @@ impl ColorChoice {
     }
 
     /// Writes the display with colorization on.
-    fn on_write_to<W, D, Fg, Bg>(
-        writer: &mut W,
-        display: D,
-        fg: Option<Fg>,
-        bg: Option<Bg>,
-    ) -> io::Result<()>
+    fn on_write_to<W, D>(writer: &mut W, display: D, style: Style) -> io::Result<()>
     where
         W: Write,
         D: Display + OwoColorize,
-        DynColors: From<Fg>,
-        DynColors: From<Bg>,
     {
-        let fg = fg.map(DynColors::from);
-        let bg = bg.map(DynColors::from);
-        match (fg, bg) {
-            (None, None) => unreachable!("Should use the off writer"),
-            (Some(fg), None) => write!(writer, "{}", display.color(fg)),
-            (None, Some(bg)) => write!(writer, "{}", display.on_color(bg)),
-            (Some(fg), Some(bg)) => write!(writer, "{}", display.color(fg).on_color(bg)),
+        debug_assert!(!style.is_plain(), "Should use the off writer");
+        let fg = style.fg.map(DynColors::from);
+        let bg = style.bg.map(DynColors::from);
+        // NOTE Colors without attributes are written like they were before styles,
+        //      which resets only the colors instead of every attribute.
+        if !style.has_attributes() {
+            return match (fg, bg) {
+                (None, None) => unreachable!("Should use the off writer"),
+                (Some(fg), None) => write!(writer, "{}", display.color(fg)),
+                (None, Some(bg)) => write!(writer, "{}", display.on_color(bg)),
+                (Some(fg), Some(bg)) => write!(writer, "{}", display.color(fg).on_color(bg)),
+            };
+        }
+        let mut owo_style = owo_colors::Style::new();
+        if let Some(fg) = fg {
+            owo_style = owo_style.color(fg);
         }
+        if let Some(bg) = bg {
+            owo_style = owo_style.on_color(bg);
+        }
+        if style.bold {
+            owo_style = owo_style.bold();
+        }
+        if style.italic {
+            owo_style = owo_style.italic();
+        }
+        if style.underline {
+            owo_style = owo_style.underline();
+        }
+        if style.dim {
+            owo_style = owo_style.dimmed();
+        }
+        if style.strikethrough {
+            owo_style = owo_style.strikethrough();
+        }
+        write!(writer, "{}", display.style(owo_style))
     }
 
     /// Writes the display with colorization set to ANSI.
-    fn ansi_write_to<W, D>(
-        writer: &mut W,
-        display: D,
-        fg: Option<Color>,
-        bg: Option<Color>,
-    ) -> io::Result<()>
+    fn ansi_write_to<W, D>(writer: &mut W, display: D, style: Style) -> io::Result<()>
     where
         W: Write,
         D: Display + OwoColorize,
     {
-        let convert = |color: Color| DynColors::Ansi(Self::color_to_ansi(color));
-        let fg = fg.map(convert);
-        let bg = bg.map(convert);
-        Self::on_write_to(writer, display, fg, bg)
+        let style = style.map_colors(|color| Color::Ansi(Self::color_to_ansi(color)));
+        Self::on_write_to(writer, display, style)
     }
 
     /// Converts the [`Color`] to ANSI.
     fn color_to_ansi(color: Color) -> AnsiColors {
         match color {
             Color::Ansi(ansi) => ansi,
+            Color::Ansi256(index) if index < 16 => Self::COLOR_INDEX[usize::from(index)],
+            Color::Ansi256(_) => {
+                let (r, g, b) = color.rgb();
+                Self::ansi_from_rgb(r, g, b)
+            }
             Color::Rgb(r, g, b) => Self::ansi_from_rgb(r, g, b),
         }
     }
 
+    /// Gets the closest of the 256 xterm colors from RGB values, which is either in
+    /// the 6x6x6 color cube or on the grayscale ramp.
+    fn ansi256_from_rgb(r: u8, g: u8, b: u8) -> u8 {
+        /// Gets the index of the closest level in the color cube, whose levels are
+        /// 0, 95, 135, 175, 215, and 255.
+        const fn cube_index(channel: u8) -> u8 {
+            match channel {
+                0..48 => 0,
+                48..115 => 1,
+                _ => (channel - 35) / 40,
+            }
+        }
+        /// Gets the squared distance between two colors.
+        fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+            [(r1, r2), (g1, g2), (b1, b2)]
+                .into_iter()
+                .map(|(a, b)| u32::from(a.abs_diff(b)).pow(2))
+                .sum()
+        }
+
+        let cube = 16 + 36 * cube_index(r) + 6 * cube_index(g) + cube_index(b);
+        let average = ((u16::from(r) + u16::from(g) + u16::from(b)) / 3) as u8;
+        let gray = 232 + (average.saturating_sub(3) / 10).min(23);
+        let rgb = (r, g, b);
+        if distance(Color::Ansi256(gray).rgb(), rgb) < distance(Color::Ansi256(cube).rgb(), rgb) {
+            gray
+        } else {
+            cube
+        }
+    }
+
+    /// Stores colors to be indexed into by a 3-bit union of the RGB values. These are
+    /// also the first 16 xterm colors, in order.
+    const COLOR_INDEX: [AnsiColors; 16] = [
+        Black,
+        Red,
+        Green,
+        Yellow,
+        Blue,
+        Magenta,
+        Cyan,
+        White,
+        BrightBlack,
+        BrightRed,
+        BrightGreen,
+        BrightYellow,
+        BrightBlue,
+        BrightMagenta,
+        BrightCyan,
+        BrightWhite,
+    ];
+
     /// Tries to get the closest ANSI color from RGB values.
     fn ansi_from_rgb(r: u8, g: u8, b: u8) -> AnsiColors {
-        /// Stores colors to be indexed into by a 3-bit union of the RGB values.
-        const COLOR_INDEX: [AnsiColors; 16] = [
-            Black,
-            Red,
-            Green,
-            Yellow,
-            Blue,
-            Magenta,
-            Cyan,
-            White,
-            BrightBlack,
-            BrightRed,
-            BrightGreen,
-            BrightYellow,
-            BrightBlue,
-            BrightMagenta,
-            BrightCyan,
-            BrightWhite,
-        ];
         /// Converts a color channel into a single bit at the given index.
         #[inline]
         const fn channel_bit(channel: u8, index: u8) -> u8 {
@@ -202,8 +265,8 @@ impl ColorChoice {
         let color_index = usize::from(channel_bit(r, 0) | channel_bit(g, 1) | channel_bit(b, 2));
         debug_assert!(color_index <= 0b111);
         let index = brightness_index + color_index;
-        debug_assert!(index < COLOR_INDEX.len());
-        COLOR_INDEX[index]
+        debug_assert!(index < Self::COLOR_INDEX.len());
+        Self::COLOR_INDEX[index]
     }
 
     /// Detects if an RGB color is bright.
@@ -229,6 +292,61 @@ impl ColorChoice {
     }
 }
 
+/// How many colors a terminal supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColorDepth {
+    /// The 16 ANSI colors.
+    Ansi,
+    /// The 256 xterm colors.
+    Ansi256,
+    /// Full RGB.
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// Detects how many colors `Stdout` supports, or `None` if it doesn't support
+    /// colors or the environment turns them off.
+    fn detect() -> Option<Self> {
+        /// The detected depth, which doesn't change while the program runs.
+        static DEPTH: LazyLock<Option<ColorDepth>> = LazyLock::new(|| {
+            let is_terminal = io::stdout().is_terminal();
+            let detected = || supports_color::on(Stream::Stdout).map(ColorDepth::from_level);
+            match ColorChoice::env_colors(|name| env::var(name).ok(), is_terminal) {
+                Some(false) => None,
+                // NOTE Forced colors are at least the 16 ANSI colors, even if the
+                //      terminal's support couldn't be detected.
+                Some(true) => detected().or(Some(ColorDepth::Ansi)),
+                None => detected(),
+            }
+        });
+        *DEPTH
+    }
+
+    /// Gets the depth of a detected level of support.
+    fn from_level(level: supports_color::ColorLevel) -> Self {
+        if level.has_16m {
+            Self::TrueColor
+        } else if level.has_256 {
+            Self::Ansi256
+        } else {
+            Self::Ansi
+        }
+    }
+
+    /// Converts the color to the closest color that's supported.
+    fn convert(self, color: Color) -> Color {
+        match (self, color) {
+            (Self::Ansi, Color::Ansi256(_) | Color::Rgb(..)) => {
+                Color::Ansi(ColorChoice::color_to_ansi(color))
+            }
+            (Self::Ansi256, Color::Rgb(r, g, b)) => {
+                Color::Ansi256(ColorChoice::ansi256_from_rgb(r, g, b))
+            }
+            (_, color) => color,
+        }
+    }
+}
+
 impl Default for ColorChoice {
     #[inline]
     /// The auto variant.
@@ -265,6 +383,7 @@ impl FromLua for ColorChoice {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing;
     use rstest::rstest;
 
     #[rstest]
@@ -282,4 +401,78 @@ mod tests {
     ) {
         assert_eq!(expected, ColorChoice::ansi_from_rgb(r, g, b));
     }
+
+    #[rstest]
+    #[case::black(0, 0, 0, 16)]
+    #[case::white(255, 255, 255, 231)]
+    #[case::orange(255, 135, 0, 208)]
+    #[case::near_cube(100, 140, 170, 67)]
+    #[case::gray(128, 128, 128, 244)]
+    #[case::dark_gray(20, 22, 18, 233)]
+    fn test_ansi256_from_rgb(#[case] r: u8, #[case] g: u8, #[case] b: u8, #[case] expected: u8) {
+        assert_eq!(expected, ColorChoice::ansi256_from_rgb(r, g, b));
+    }
+
+    #[rstest]
+    #[case(ColorChoice::On, Style::from(Color::Ansi(Red)), "\x1b[31mx\x1b[39m")]
+    #[case(ColorChoice::On, Style::new().bold().underline(), "\x1b[1;4mx\x1b[0m")]
+    #[case(
+        ColorChoice::On,
+        Style::new().fg(Color::Ansi256(208)).italic(),
+        "\x1b[38;5;208;3mx\x1b[0m"
+    )]
+    #[case(
+        ColorChoice::Ansi,
+        Style::new().fg(Color::Rgb(0xFF, 0xFF, 0xFF)).dim().strikethrough(),
+        "\x1b[97;2;9mx\x1b[0m"
+    )]
+    #[case(ColorChoice::Off, Style::new().fg(Color::Ansi(Red)).bold(), "x")]
+    #[case(ColorChoice::On, Style::new(), "x")]
+    fn test_write_to(#[case] choice: ColorChoice, #[case] style: Style, #[case] expected: &str) {
+        let mut output = Vec::new();
+        choice.write_to(&mut output, "x", style).unwrap();
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+
+    #[rstest]
+    #[case(&[], true, None)]
+    #[case(&[], false, Some(false))]
+    #[case(&[("NO_COLOR", "1")], true, Some(false))]
+    #[case(&[("NO_COLOR", "")], true, None)]
+    #[case(&[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")], false, Some(false))]
+    #[case(&[("CLICOLOR_FORCE", "1")], false, Some(true))]
+    #[case(&[("CLICOLOR_FORCE", "0")], false, Some(false))]
+    #[case(&[("CLICOLOR_FORCE", "1"), ("CLICOLOR", "0")], true, Some(true))]
+    #[case(&[("CLICOLOR", "0")], true, Some(false))]
+    #[case(&[("CLICOLOR", "1")], true, None)]
+    #[case(&[("CLICOLOR", "1")], false, Some(false))]
+    fn test_env_colors(
+        #[case] vars: &[(&str, &str)],
+        #[case] is_terminal: bool,
+        #[case] expected: Option<bool>,
+    ) {
+        assert_eq!(
+            expected,
+            ColorChoice::env_colors(testing::env(vars), is_terminal)
+        );
+    }
+
+    #[rstest]
+    #[case(ColorDepth::TrueColor, Color::Rgb(1, 2, 3), Color::Rgb(1, 2, 3))]
+    #[case(ColorDepth::Ansi256, Color::Rgb(255, 135, 0), Color::Ansi256(208))]
+    #[case(ColorDepth::Ansi256, Color::Ansi(Red), Color::Ansi(Red))]
+    #[case(ColorDepth::Ansi, Color::Ansi256(9), Color::Ansi(BrightRed))]
+    #[case(ColorDepth::Ansi, Color::Ansi256(21), Color::Ansi(BrightBlue))]
+    #[case(
+        ColorDepth::Ansi,
+        Color::Rgb(0xFF, 0xFF, 0xFF),
+        Color::Ansi(BrightWhite)
+    )]
+    fn test_color_depth_convert(
+        #[case] depth: ColorDepth,
+        #[case] color: Color,
+        #[case] expected: Color,
+    ) {
+        assert_eq!(expected, depth.convert(color));
+    }
 }
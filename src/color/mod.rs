@@ -7,15 +7,22 @@ use owo_colors::{
         self, Black, Blue, BrightBlack, BrightBlue, BrightCyan, BrightGreen, BrightMagenta,
         BrightRed, BrightWhite, BrightYellow, Cyan, Green, Magenta, Red, White, Yellow,
     },
-    DynColors,
+    DynColors, XtermColors,
 };
+pub use style::Style;
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
 
 mod choice;
+mod style;
 
-/// Either ANSI colors or full RGB.
+/// Either ANSI colors, the 256 xterm colors, or full RGB.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Color {
     Ansi(AnsiColors),
+    /// One of the 256 xterm colors, where the first 16 are the ANSI colors.
+    Ansi256(u8),
     Rgb(u8, u8, u8),
 }
 
@@ -76,6 +83,50 @@ impl Color {
             .expect("The mapping should exist")
     }
 
+    /// Gets the RGB values of the color. ANSI colors use xterm's default palette,
+    /// since the terminal's palette isn't known.
+    pub const fn rgb(self) -> (u8, u8, u8) {
+        /// The levels of each channel in the 6x6x6 color cube.
+        const CUBE_LEVELS: [u8; 6] = [0x00, 0x5f, 0x87, 0xaf, 0xd7, 0xff];
+
+        match self {
+            Color::Ansi256(index @ 0..16) => {
+                Color::Ansi(Self::ANSI_NAME_MAP[index as usize].1).rgb()
+            }
+            Color::Ansi256(index @ 16..232) => {
+                let index = (index - 16) as usize;
+                (
+                    CUBE_LEVELS[index / 36],
+                    CUBE_LEVELS[index / 6 % 6],
+                    CUBE_LEVELS[index % 6],
+                )
+            }
+            Color::Ansi256(index) => {
+                let level = 8 + (index - 232) * 10;
+                (level, level, level)
+            }
+            Color::Ansi(ansi_colors) => match ansi_colors {
+                Black => (0x00, 0x00, 0x00),
+                Red => (0xcd, 0x00, 0x00),
+                Green => (0x00, 0xcd, 0x00),
+                Yellow => (0xcd, 0xcd, 0x00),
+                Blue => (0x00, 0x00, 0xee),
+                Magenta => (0xcd, 0x00, 0xcd),
+                Cyan => (0x00, 0xcd, 0xcd),
+                White => (0xe5, 0xe5, 0xe5),
+                BrightBlack => (0x7f, 0x7f, 0x7f),
+                BrightRed => (0xff, 0x00, 0x00),
+                BrightGreen => (0x00, 0xff, 0x00),
+                BrightYellow => (0xff, 0xff, 0x00),
+                BrightBlue => (0x5c, 0x5c, 0xff),
+                BrightMagenta => (0xff, 0x00, 0xff),
+                BrightCyan => (0x00, 0xff, 0xff),
+                BrightWhite | AnsiColors::Default => (0xff, 0xff, 0xff),
+            },
+            Color::Rgb(r, g, b) => (r, g, b),
+        }
+    }
+
     /// Converts RGB into a table.
     #[inline]
     fn rgb_to_table(lua: &Lua, r: u8, g: u8, b: u8) -> mlua::Result<mlua::Table> {
@@ -87,6 +138,18 @@ impl FromLua for Color {
     fn from_lua(value: mlua::Value, lua: &Lua) -> mlua::Result<Self> {
         type AnsiOrRgb = Either<String, mlua::Table>;
         let type_name = value.type_name();
+        // NOTE Numbers like 1.5 aren't truncated, since they're probably mistakes.
+        if let mlua::Value::Integer(_) | mlua::Value::Number(_) = value {
+            return value
+                .as_integer()
+                .and_then(|index| u8::try_from(index).ok())
+                .map(Self::Ansi256)
+                .ok_or_else(|| mlua::Error::FromLuaConversionError {
+                    from: type_name,
+                    to: String::from("Color"),
+                    message: Some(String::from("Expected an xterm color from 0 to 255")),
+                });
+        }
         let ansi_or_rgb = AnsiOrRgb::from_lua(value, lua)?;
 
         match ansi_or_rgb {
@@ -100,11 +163,65 @@ impl IntoLua for Color {
     fn into_lua(self, lua: &Lua) -> mlua::Result<mlua::Value> {
         match self {
             Color::Ansi(ansi_colors) => Color::ansi_name(ansi_colors).into_lua(lua),
+            Color::Ansi256(index) => index.into_lua(lua),
             Color::Rgb(r, g, b) => Color::rgb_to_table(lua, r, g, b)?.into_lua(lua),
         }
     }
 }
 
+impl Display for Color {
+    /// Writes the ANSI color's name, the xterm color's number, or the RGB color as a
+    /// hex code like `#ff0080`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Color::Ansi(ansi_colors) => write!(f, "{}", Color::ansi_name(ansi_colors)),
+            Color::Ansi256(index) => write!(f, "{index}"),
+            Color::Rgb(r, g, b) => write!(f, "#{r:02x}{g:02x}{b:02x}"),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    /// Parses the ANSI color's name, the xterm color's number, or the RGB color as a
+    /// hex code like `#ff0080`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(index) = s.parse() {
+            return Ok(Self::Ansi256(index));
+        }
+        if let Some(hex) = s.strip_prefix('#') {
+            let channel = |index: usize| {
+                hex.get(index..index + 2)
+                    .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+            };
+            return match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(Self::Rgb(r, g, b)),
+                _ => Err(ParseColorError),
+            };
+        }
+        Self::ANSI_NAME_MAP
+            .into_iter()
+            .find_map(|(key, value)| (key == s).then_some(Self::Ansi(value)))
+            .ok_or(ParseColorError)
+    }
+}
+
+/// Error when a string isn't a color name or hex code.
+#[derive(Debug)]
+pub struct ParseColorError;
+
+impl Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Expected an ansi color name, an xterm color from 0 to 255, or a hex code like #ff0080"
+        )
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
 impl From<AnsiColors> for Color {
     #[inline]
     fn from(value: AnsiColors) -> Self {
@@ -116,16 +233,59 @@ impl From<Color> for DynColors {
     fn from(value: Color) -> Self {
         match value {
             Color::Ansi(color) => Self::Ansi(color),
+            Color::Ansi256(index) => Self::Xterm(XtermColors::from(index)),
             Color::Rgb(r, g, b) => Self::Rgb(r, g, b),
         }
     }
 }
 
+/// Removes the escape sequences for colors and hyperlinks from text, leaving the text
+/// that's displayed.
+pub fn strip_escapes(text: &str) -> String {
+    const ESCAPE: char = '\x1b';
+    const BELL: char = '\x07';
+
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != ESCAPE {
+            stripped.push(c);
+            continue;
+        }
+        match chars.next() {
+            // NOTE A control sequence, like a color, ends with a character in `@`..=`~`.
+            Some('[') => {
+                chars.find(|c| ('@'..='~').contains(c));
+            }
+            // NOTE An operating system command, like a hyperlink, ends with a bell or
+            //      with `ESC \`.
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == BELL || (c == ESCAPE && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    stripped
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use rstest::rstest;
 
+    #[rstest]
+    #[case("plain", "plain")]
+    #[case("\x1b[31;9mred\x1b[0m text", "red text")]
+    #[case("\x1b]8;;file:///a\x1b\\a\x1b]8;;\x1b\\", "a")]
+    #[case("\x1b]8;;file:///b\x07b\x1b]8;;\x07", "b")]
+    fn test_strip_escapes(#[case] text: &str, #[case] expected: &str) {
+        assert_eq!(expected, strip_escapes(text));
+    }
+
     #[rstest]
     #[case("black", AnsiColors::Black)]
     #[case("magenta", AnsiColors::Magenta)]
@@ -142,6 +302,26 @@ mod tests {
         assert_eq!(expected_ansi, ansi);
     }
 
+    #[rstest]
+    #[case(Color::Ansi(AnsiColors::BrightRed), "bright-red")]
+    #[case(Color::Ansi256(208), "208")]
+    #[case(Color::Rgb(255, 0, 128), "#ff0080")]
+    fn test_display(#[case] color: Color, #[case] expected: &str) {
+        assert_eq!(expected, color.to_string());
+    }
+
+    #[rstest]
+    #[case("bright-red", Some(Color::Ansi(AnsiColors::BrightRed)))]
+    #[case("#ff0080", Some(Color::Rgb(255, 0, 128)))]
+    #[case("208", Some(Color::Ansi256(208)))]
+    #[case("256", None)]
+    #[case("#ff008", None)]
+    #[case("#gg0080", None)]
+    #[case("pink", None)]
+    fn test_from_str(#[case] s: &str, #[case] expected: Option<Color>) {
+        assert_eq!(expected, s.parse().ok());
+    }
+
     #[test]
     fn test_from_lua_string_err() {
         let lua = Lua::new();
@@ -152,6 +332,28 @@ mod tests {
         assert!(Color::from_lua(value, &lua).is_err());
     }
 
+    #[rstest]
+    #[case("208", Some(Color::Ansi256(208)))]
+    #[case("0", Some(Color::Ansi256(0)))]
+    #[case("256", None)]
+    #[case("-1", None)]
+    #[case("1.5", None)]
+    fn test_from_lua_integer(#[case] chunk: &str, #[case] expected: Option<Color>) {
+        let lua = Lua::new();
+        assert_eq!(expected, lua.load(chunk).eval::<Color>().ok());
+    }
+
+    #[rstest]
+    #[case(Color::Ansi256(1), (0xcd, 0x00, 0x00))]
+    #[case(Color::Ansi256(16), (0x00, 0x00, 0x00))]
+    #[case(Color::Ansi256(208), (0xff, 0x87, 0x00))]
+    #[case(Color::Ansi256(231), (0xff, 0xff, 0xff))]
+    #[case(Color::Ansi256(232), (0x08, 0x08, 0x08))]
+    #[case(Color::Ansi256(255), (0xee, 0xee, 0xee))]
+    fn test_ansi256_rgb(#[case] color: Color, #[case] expected: (u8, u8, u8)) {
+        assert_eq!(expected, color.rgb());
+    }
+
     #[test]
     fn test_from_lua_tuple_ok() {
         let lua = Lua::new();
@@ -0,0 +1,312 @@
+//! Module for styles, which combine colors with text attributes like bold.
+use super::Color;
+use mlua::{FromLua, IntoLua, Lua};
+
+/// The colors and attributes of text.
+///
+/// In Lua, a style is either a [`Color`], for a foreground color, or a table like
+/// `{ fg = "red", bold = true }`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Style {
+    /// The foreground color.
+    pub fg: Option<Color>,
+    /// The background color.
+    pub bg: Option<Color>,
+    /// Is the text bold?
+    pub bold: bool,
+    /// Is the text italic?
+    pub italic: bool,
+    /// Is the text underlined?
+    pub underline: bool,
+    /// Is the text dimmed?
+    pub dim: bool,
+    /// Is the text struck through?
+    pub strikethrough: bool,
+}
+
+impl Style {
+    /// Creates a style without colors or attributes.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            fg: None,
+            bg: None,
+            bold: false,
+            italic: false,
+            underline: false,
+            dim: false,
+            strikethrough: false,
+        }
+    }
+
+    /// Sets the foreground color.
+    #[inline]
+    #[must_use]
+    pub const fn fg(self, color: Color) -> Self {
+        Self {
+            fg: Some(color),
+            ..self
+        }
+    }
+
+    /// Sets the background color.
+    #[inline]
+    #[must_use]
+    pub const fn bg(self, color: Color) -> Self {
+        Self {
+            bg: Some(color),
+            ..self
+        }
+    }
+
+    /// Makes the text bold.
+    #[inline]
+    #[must_use]
+    pub const fn bold(self) -> Self {
+        Self { bold: true, ..self }
+    }
+
+    /// Makes the text italic.
+    #[inline]
+    #[must_use]
+    pub const fn italic(self) -> Self {
+        Self {
+            italic: true,
+            ..self
+        }
+    }
+
+    /// Underlines the text.
+    #[inline]
+    #[must_use]
+    pub const fn underline(self) -> Self {
+        Self {
+            underline: true,
+            ..self
+        }
+    }
+
+    /// Dims the text.
+    #[inline]
+    #[must_use]
+    pub const fn dim(self) -> Self {
+        Self { dim: true, ..self }
+    }
+
+    /// Strikes through the text.
+    #[inline]
+    #[must_use]
+    pub const fn strikethrough(self) -> Self {
+        Self {
+            strikethrough: true,
+            ..self
+        }
+    }
+
+    /// Checks if the style has any attributes, like bold.
+    #[inline]
+    pub const fn has_attributes(&self) -> bool {
+        self.bold || self.italic || self.underline || self.dim || self.strikethrough
+    }
+
+    /// Checks if the style doesn't change the text at all.
+    #[inline]
+    pub const fn is_plain(&self) -> bool {
+        self.fg.is_none() && self.bg.is_none() && !self.has_attributes()
+    }
+
+    /// Gets the colors that this style doesn't set from another style, and adds the
+    /// other style's attributes.
+    #[must_use]
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            fg: self.fg.or(other.fg),
+            bg: self.bg.or(other.bg),
+            bold: self.bold || other.bold,
+            italic: self.italic || other.italic,
+            underline: self.underline || other.underline,
+            dim: self.dim || other.dim,
+            strikethrough: self.strikethrough || other.strikethrough,
+        }
+    }
+
+    /// Layers a style over another, like an icon's color over a directory's tint.
+    /// See [`Style::or`].
+    pub fn layer(over: Option<Self>, under: Option<Self>) -> Option<Self> {
+        match (over, under) {
+            (Some(over), Some(under)) => Some(over.or(under)),
+            (over, under) => over.or(under),
+        }
+    }
+
+    /// Converts the colors, keeping the attributes.
+    #[must_use]
+    pub fn map_colors<F>(self, f: F) -> Self
+    where
+        F: Fn(Color) -> Color,
+    {
+        Self {
+            fg: self.fg.map(&f),
+            bg: self.bg.map(&f),
+            ..self
+        }
+    }
+
+    /// Gets the attributes as they're named in Lua.
+    fn attributes(&self) -> [(&'static str, bool); 5] {
+        [
+            ("bold", self.bold),
+            ("italic", self.italic),
+            ("underline", self.underline),
+            ("dim", self.dim),
+            ("strikethrough", self.strikethrough),
+        ]
+    }
+
+    /// Gets a style from a table like `{ fg = "red", bold = true }`.
+    fn from_lua_table(table: mlua::Table) -> mlua::Result<Self> {
+        let attribute_keys = Self::new().attributes().map(|(key, _)| key);
+        // NOTE Unknown keys are errors, since they're probably typos like `bolt`.
+        for pair in table.pairs::<mlua::Value, mlua::Value>() {
+            let (key, _) = pair?;
+            let key = key.to_string()?;
+            if key != "fg" && key != "bg" && !attribute_keys.contains(&key.as_str()) {
+                return Err(mlua::Error::FromLuaConversionError {
+                    from: "table",
+                    to: String::from("Style"),
+                    message: Some(format!(
+                        "Unknown key `{key}`, expected `fg`, `bg`, or one of {attribute_keys:?}"
+                    )),
+                });
+            }
+        }
+        // NOTE Any value converts to a boolean in Lua, so other types are rejected
+        //      instead of being true.
+        let attribute = |key| match table.get::<mlua::Value>(key)? {
+            mlua::Value::Nil => Ok(false),
+            mlua::Value::Boolean(value) => Ok(value),
+            value => Err(mlua::Error::FromLuaConversionError {
+                from: value.type_name(),
+                to: String::from("Style"),
+                message: Some(format!("`{key}` should be a boolean")),
+            }),
+        };
+        Ok(Self {
+            fg: table.get("fg")?,
+            bg: table.get("bg")?,
+            bold: attribute("bold")?,
+            italic: attribute("italic")?,
+            underline: attribute("underline")?,
+            dim: attribute("dim")?,
+            strikethrough: attribute("strikethrough")?,
+        })
+    }
+}
+
+impl From<Color> for Style {
+    #[inline]
+    fn from(value: Color) -> Self {
+        Self::new().fg(value)
+    }
+}
+
+impl From<Option<Color>> for Style {
+    #[inline]
+    fn from(value: Option<Color>) -> Self {
+        Self {
+            fg: value,
+            ..Self::new()
+        }
+    }
+}
+
+impl From<Option<Style>> for Style {
+    #[inline]
+    fn from(value: Option<Style>) -> Self {
+        value.unwrap_or_default()
+    }
+}
+
+impl FromLua for Style {
+    fn from_lua(value: mlua::Value, lua: &Lua) -> mlua::Result<Self> {
+        // NOTE RGB colors are tables too, so tables with their keys are colors.
+        match value {
+            mlua::Value::Table(table) if !table.contains_key("r")? => Self::from_lua_table(table),
+            value => Color::from_lua(value, lua).map(Self::from),
+        }
+    }
+}
+
+impl IntoLua for Style {
+    /// Converts a style with only a foreground color into the color, so that
+    /// functions that compare colors, like `default == "red"`, keep working.
+    fn into_lua(self, lua: &Lua) -> mlua::Result<mlua::Value> {
+        if self.bg.is_none() && !self.has_attributes() {
+            return self.fg.into_lua(lua);
+        }
+        let table = lua.create_table()?;
+        table.set("fg", self.fg)?;
+        table.set("bg", self.bg)?;
+        for (key, value) in self.attributes() {
+            if value {
+                table.set(key, true)?;
+            }
+        }
+        table.into_lua(lua)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use owo_colors::AnsiColors::{Blue, Red};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(r#""red""#, Style::new().fg(Color::Ansi(Red)))]
+    #[case("{ r = 1, g = 2, b = 3 }", Style::new().fg(Color::Rgb(1, 2, 3)))]
+    #[case("208", Style::new().fg(Color::Ansi256(208)))]
+    #[case(r#"{ fg = "red", bold = true }"#, Style::new().fg(Color::Ansi(Red)).bold())]
+    #[case(
+        r#"{ bg = { r = 1, g = 2, b = 3 }, italic = true, underline = true }"#,
+        Style::new().bg(Color::Rgb(1, 2, 3)).italic().underline()
+    )]
+    #[case("{ dim = true, strikethrough = true, bold = false }", Style::new().dim().strikethrough())]
+    #[case("{}", Style::new())]
+    fn test_from_lua(#[case] chunk: &str, #[case] expected: Style) {
+        let lua = Lua::new();
+        assert_eq!(expected, lua.load(chunk).eval::<Style>().unwrap());
+    }
+
+    #[rstest]
+    #[case(r#"{ fg = "red", bolt = true }"#)]
+    #[case(r#"{ fg = "pink" }"#)]
+    #[case(r#"{ bold = "yes" }"#)]
+    fn test_from_lua_err(#[case] chunk: &str) {
+        let lua = Lua::new();
+        assert!(lua.load(chunk).eval::<Style>().is_err());
+    }
+
+    #[rstest]
+    #[case(Style::new().fg(Color::Ansi(Blue)))]
+    #[case(Style::new().fg(Color::Ansi(Red)).bg(Color::Ansi256(1)).dim())]
+    #[case(Style::new().bold().italic().underline().strikethrough())]
+    #[case(Style::new())]
+    fn test_lua_round_trip(#[case] style: Style) {
+        let lua = Lua::new();
+        let value = style.into_lua(&lua).unwrap();
+        assert_eq!(
+            style,
+            Option::<Style>::from_lua(value, &lua)
+                .unwrap()
+                .unwrap_or_default()
+        );
+    }
+
+    #[test]
+    fn test_into_lua_color() {
+        let lua = Lua::new();
+        let value = Style::from(Color::Ansi(Red)).into_lua(&lua).unwrap();
+        assert_eq!("red", value.to_string().unwrap());
+    }
+}
@@ -1,19 +1,30 @@
 //! Provides colors for filepaths.
 use crate::color::Color;
 use crate::ext::PathExt as _;
+use crate::rules::{Matcher, Rule};
 use owo_colors::AnsiColors::{Black, Blue, Cyan, Green, Red, Yellow};
 use std::path::Path;
 use std::sync::LazyLock;
 
 /// Gets a color for a path.
 pub fn for_path<P>(path: P) -> Option<Color>
+where
+    P: AsRef<Path>,
+{
+    match_path(path).map(|(_, color)| color)
+}
+
+/// Gets a color for a path, and what the path matched to get it.
+pub(crate) fn match_path<P>(path: P) -> Option<(Matcher, Color)>
 where
     P: AsRef<Path>,
 {
     let path = path.as_ref();
+    let matched = |matcher| move |color| (matcher, color);
     path.file_name()
         .and_then(|s| s.to_str())
         .and_then(for_filename)
+        .map(matched(Matcher::Filename))
         .or_else(|| {
             path.double_extension()
                 .and_then(|(prefix, suffix)| {
@@ -22,65 +33,113 @@ where
                         .and_then(|prefix| suffix.to_str().map(|suffix| (prefix, suffix)))
                 })
                 .and_then(for_double_extension)
+                .map(matched(Matcher::DoubleExtension))
         })
         .or_else(|| {
             path.extension()
                 .and_then(|extension| extension.to_str())
                 .and_then(for_extension)
+                .map(matched(Matcher::Extension))
         })
-        .or_else(|| for_filename_glob(path))
+        .or_else(|| for_filename_glob(path).map(matched(Matcher::Glob)))
+}
+
+/// Names mapped to colors, grouped by color.
+///
+/// NOTE These should be in alphabetical order and ignoring any leading `.` for
+///      easier code review.
+type Table = &'static [(&'static [&'static str], Color)];
+
+/// Filenames mapped to colors.
+const FILENAMES: Table = &[
+    (
+        &[
+            ".git",
+            ".gitattributes",
+            ".gitignore",
+            ".gitmodules",
+            ".git-blame-ignore-revs",
+        ],
+        Color::Ansi(Red),
+    ),
+    (&[".github"], Color::Ansi(Black)),
+    (
+        &["LICENCE", "LICENSE", "licence", "license"],
+        shared::LICENSE,
+    ),
+    (&[".vscode"], Color::Ansi(Blue)),
+];
+
+/// Extensions mapped to colors.
+const EXTENSIONS: Table = &[
+    (&["7z"], Color::Ansi(Black)),
+    (&["gif"], Color::Ansi(Green)),
+    (&["jpeg", "jpg"], Color::Ansi(Yellow)),
+    (&["png"], Color::Ansi(Cyan)),
+    (&["sqlite", "sqlite3"], Color::Ansi(Blue)),
+    (&["tar"], Color::Ansi(Green)),
+    (&["zip"], Color::Ansi(Blue)),
+];
+
+/// Double extensions mapped to colors, like `tar.gz`.
+const DOUBLE_EXTENSIONS: &[((&str, &str), Color)] = &[(("tar", "gz"), Color::Ansi(Green))];
+
+/// Maps a raw glob pattern to a color with `(glob, color)` tuples.
+const GLOBS: &[(&str, Color)] = &[("LICEN[CS]E-*", shared::LICENSE)];
+
+/// Gets the built-in rules that map names to colors, in the order that they're
+/// checked.
+pub(crate) fn rules() -> impl Iterator<Item = Rule<Color>> {
+    let names = |table: Table| {
+        table
+            .iter()
+            .flat_map(|(names, color)| names.iter().map(move |name| (*name, *color)))
+    };
+    let double_extensions = DOUBLE_EXTENSIONS.iter().map(|((prefix, suffix), color)| {
+        Rule::new(
+            Matcher::DoubleExtension,
+            format!("{prefix}.{suffix}"),
+            *color,
+        )
+    });
+    let globs = GLOBS
+        .iter()
+        .map(|(glob, color)| Rule::new(Matcher::Glob, *glob, *color).ignore_case());
+    names(FILENAMES)
+        .map(|(name, color)| Rule::new(Matcher::Filename, name, color))
+        .chain(double_extensions)
+        .chain(names(EXTENSIONS).map(|(name, color)| Rule::new(Matcher::Extension, name, color)))
+        .chain(globs)
+}
+
+/// Gets the color for a name from a table.
+fn lookup(table: Table, name: &str) -> Option<Color> {
+    table
+        .iter()
+        .find_map(|(names, color)| names.contains(&name).then_some(*color))
 }
 
 /// Gets a color for a filename.
 fn for_filename(filename: &str) -> Option<Color> {
-    // NOTE These should be in alphabetical order and ignoring any leading `.` for
-    //      easier code review.
-    let color = match filename {
-        ".git" | ".gitattributes" | ".gitignore" | ".gitmodules" | ".git-blame-ignore-revs" => {
-            Red.into()
-        }
-        ".github" => Black.into(),
-        "LICENCE" | "LICENSE" | "licence" | "license" => shared::LICENSE,
-        ".vscode" => Blue.into(),
-        _ => return None,
-    };
-    Some(color)
+    lookup(FILENAMES, filename)
 }
 
 /// Gets a color for a file extension.
 fn for_extension(extension: &str) -> Option<Color> {
-    // NOTE These should be in alphabetical order for easier code review.
-    let color = match extension {
-        "7z" => Black.into(),
-        "gif" => Green.into(),
-        "jpeg" | "jpg" => Yellow.into(),
-        "png" => Cyan.into(),
-        "sqlite" | "sqlite3" => Blue.into(),
-        "tar" => Green.into(),
-        "zip" => Blue.into(),
-        _ => return None,
-    };
-
-    Some(color)
+    lookup(EXTENSIONS, extension)
 }
 
 /// Gets a color for the double extension.
 fn for_double_extension(double_extension: (&str, &str)) -> Option<Color> {
-    let color = match double_extension {
-        ("tar", "gz") => Green.into(),
-        _ => return None,
-    };
-
-    Some(color)
+    DOUBLE_EXTENSIONS
+        .iter()
+        .find_map(|(double, color)| (*double == double_extension).then_some(*color))
 }
 
 /// Gets a color based on a matching glob for a path.
 fn for_filename_glob(path: &Path) -> Option<Color> {
     use glob::{MatchOptions, Pattern};
 
-    /// Maps a raw glob pattern to a color with `(glob, color)` tuples.
-    const RAW_MAPPINGS: &[(&str, Color)] = &[("LICEN[CS]E-*", shared::LICENSE)];
-
     const OPTIONS: MatchOptions = MatchOptions {
         case_sensitive: false,
         require_literal_separator: false,
@@ -89,7 +148,7 @@ fn for_filename_glob(path: &Path) -> Option<Color> {
 
     /// The compiled glob-to-color mappings.
     static COMPILED_MAPPINGS: LazyLock<Vec<(Pattern, Color)>> = LazyLock::new(|| {
-        RAW_MAPPINGS
+        GLOBS
             .iter()
             .map(|(raw, color)| (Pattern::new(raw).expect("Pattern should be valid"), *color))
             .collect()
@@ -0,0 +1,211 @@
+//! Module for caching compiled configuration files.
+use mlua::{ChunkMode, Lua};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Caches the bytecode of compiled configuration files, so that they don't need to be
+/// compiled on every run.
+///
+/// Each cache file starts with a header that holds a hash of the source and a
+/// checksum of the bytecode. A cache file is only used when both match, so editing
+/// the configuration, upgrading, or a partially written cache file all cause the
+/// source to be compiled again.
+pub struct BytecodeCache {
+    /// The directory that contains the cache files.
+    dir: PathBuf,
+}
+
+impl BytecodeCache {
+    /// Identifies the cache file format.
+    const MAGIC: &[u8; 8] = b"FTLUAC\x00\x01";
+    /// The length of the header, which is the magic bytes, source hash, and checksum.
+    const HEADER_LEN: usize = Self::MAGIC.len() + 8 + 8;
+
+    /// Creates a cache that stores files in `dir`.
+    #[inline]
+    pub fn new<P>(dir: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self { dir: dir.into() }
+    }
+
+    /// Loads a Lua file, using the cached bytecode if it's still valid, and caching
+    /// the bytecode otherwise.
+    ///
+    /// Failing to read or write the cache isn't an error, since the source can always
+    /// be compiled instead.
+    pub fn load(&self, lua: &Lua, path: &Path) -> mlua::Result<mlua::Function> {
+        let source = fs::read(path)?;
+        // NOTE The name matches what Lua uses when loading a file directly, so that
+        //      error messages point to the file.
+        let name = format!("@{}", path.display());
+        let key = Self::key(lua, &source);
+        let cache_path = self.cache_path(path);
+
+        let cached = fs::read(&cache_path)
+            .ok()
+            .and_then(|cached| Self::validate(&cached, key).map(<[u8]>::to_vec));
+        if let Some(bytecode) = cached {
+            let chunk = lua
+                .load(bytecode)
+                .set_name(&name)
+                .set_mode(ChunkMode::Binary);
+            if let Ok(function) = chunk.into_function() {
+                return Ok(function);
+            }
+        }
+
+        let function = lua
+            .load(source)
+            .set_name(name)
+            .set_mode(ChunkMode::Text)
+            .into_function()?;
+        // NOTE Debug info is kept so that runtime errors still have line numbers.
+        let bytecode = function.dump(false);
+        let _ = self.write(&cache_path, key, &bytecode);
+        Ok(function)
+    }
+
+    /// Gets the path of the cache file for a source file.
+    ///
+    /// The name includes a hash of the source's full path, so that files with the
+    /// same name from different directories don't replace each other's caches, and a
+    /// relative path shares the cache of the same file's absolute path.
+    fn cache_path(&self, path: &Path) -> PathBuf {
+        let full_path = fs::canonicalize(path)
+            .or_else(|_| std::path::absolute(path))
+            .unwrap_or_else(|_| path.to_path_buf());
+        let path_hash = fnv1a(&[], full_path.as_os_str().as_encoded_bytes());
+        let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(format!(".{path_hash:016x}.luac"));
+        self.dir.join(file_name)
+    }
+
+    /// Creates the key that cached bytecode must match, from the source and the
+    /// versions of Lua and this tool, since bytecode isn't portable across Lua
+    /// versions.
+    fn key(lua: &Lua, source: &[u8]) -> u64 {
+        let lua_version = lua.globals().get::<String>("_VERSION").unwrap_or_default();
+        let versions = [lua_version.as_bytes(), env!("CARGO_PKG_VERSION").as_bytes()];
+        versions
+            .into_iter()
+            .fold(fnv1a(Self::MAGIC, source), |hash, bytes| {
+                fnv1a(&hash.to_le_bytes(), bytes)
+            })
+    }
+
+    /// Gets the bytecode from a cache file if its header matches the key and the
+    /// bytecode's checksum.
+    fn validate(cached: &[u8], key: u64) -> Option<&[u8]> {
+        if cached.len() < Self::HEADER_LEN {
+            return None;
+        }
+        let (header, bytecode) = cached.split_at(Self::HEADER_LEN);
+        let (magic, header) = header.split_at(Self::MAGIC.len());
+        let (cached_key, checksum) = header.split_at(8);
+        let is_valid = magic == Self::MAGIC
+            && cached_key == key.to_le_bytes()
+            && checksum == fnv1a(&[], bytecode).to_le_bytes();
+        is_valid.then_some(bytecode)
+    }
+
+    /// Writes a cache file.
+    ///
+    /// The file is written to a temporary path first, so that another process never
+    /// sees a partially written file.
+    fn write(&self, cache_path: &Path, key: u64, bytecode: &[u8]) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let mut contents = Vec::with_capacity(Self::HEADER_LEN + bytecode.len());
+        contents.extend_from_slice(Self::MAGIC);
+        contents.extend_from_slice(&key.to_le_bytes());
+        contents.extend_from_slice(&fnv1a(&[], bytecode).to_le_bytes());
+        contents.extend_from_slice(bytecode);
+
+        let temp_path = cache_path.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&temp_path, contents)?;
+        fs::rename(&temp_path, cache_path).inspect_err(|_| {
+            let _ = fs::remove_file(&temp_path);
+        })
+    }
+}
+
+/// Hashes a prefix followed by bytes with 64-bit FNV-1a, which gives the same result
+/// across versions and platforms.
+fn fnv1a(prefix: &[u8], bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    prefix.iter().chain(bytes).fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Creates a cache and a Lua file in a temporary directory.
+    fn setup(source: &str) -> (TempDir, BytecodeCache, PathBuf) {
+        let dir = TempDir::with_prefix("fancy-tree-").unwrap();
+        let path = dir.path().join("config.lua");
+        fs::write(&path, source).unwrap();
+        let cache = BytecodeCache::new(dir.path().join("cache"));
+        (dir, cache, path)
+    }
+
+    #[test]
+    fn test_load_writes_and_uses_cache() {
+        let (_dir, cache, path) = setup("return 1 + 1");
+        let lua = Lua::new();
+
+        let value: i64 = cache.load(&lua, &path).unwrap().call(()).unwrap();
+        assert_eq!(2, value);
+        let cache_path = cache.cache_path(&path);
+        assert!(cache_path.exists());
+
+        let value: i64 = cache.load(&lua, &path).unwrap().call(()).unwrap();
+        assert_eq!(2, value);
+    }
+
+    #[test]
+    fn test_cache_path() {
+        let (dir, cache, path) = setup("return 1");
+        let other = dir.path().join("other/config.lua");
+        fs::create_dir(dir.path().join("other")).unwrap();
+        fs::write(&other, "return 2").unwrap();
+        let dotted = dir.path().join("other/../config.lua");
+
+        assert_ne!(cache.cache_path(&path), cache.cache_path(&other));
+        assert_eq!(cache.cache_path(&path), cache.cache_path(&dotted));
+    }
+
+    #[test]
+    fn test_load_invalidates_on_change() {
+        let (_dir, cache, path) = setup("return 1");
+        let lua = Lua::new();
+        let _ = cache.load(&lua, &path).unwrap();
+
+        fs::write(&path, "return 2").unwrap();
+        let value: i64 = cache.load(&lua, &path).unwrap().call(()).unwrap();
+        assert_eq!(2, value);
+    }
+
+    #[test]
+    fn test_load_ignores_corrupted_cache() {
+        let (_dir, cache, path) = setup("return 3");
+        let lua = Lua::new();
+        let _ = cache.load(&lua, &path).unwrap();
+
+        let cache_path = cache.cache_path(&path);
+        let mut contents = fs::read(&cache_path).unwrap();
+        let last = contents.len() - 1;
+        contents[last] ^= 0xff;
+        fs::write(&cache_path, contents).unwrap();
+
+        let value: i64 = cache.load(&lua, &path).unwrap().call(()).unwrap();
+        assert_eq!(3, value);
+    }
+}
@@ -0,0 +1,163 @@
+//! Module for checking configurations by calling their functions with sample
+//! entries.
+use super::{Colors, Icons, Main};
+use crate::lua::interop::Line;
+use crate::tree::{Entry, Stats};
+use crate::vcs::status::{FileStatus, Status};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// A temporary directory with sample entries, like a source file, a dotfile, and a
+/// directory, which is removed when this is dropped.
+struct Samples {
+    /// The directory containing the sample entries.
+    dir: TempDir,
+}
+
+impl Samples {
+    /// The sample files, which are created with empty contents.
+    const FILES: [&str; 5] = [
+        "README.md",
+        "main.rs",
+        ".gitignore",
+        "src/lib.rs",
+        "no_extension",
+    ];
+
+    /// Creates the sample entries in a new temporary directory.
+    ///
+    /// The directory has a random name and is only accessible by the current user,
+    /// so that other users can't plant files or links in it.
+    fn create() -> io::Result<Self> {
+        let dir = TempDir::with_prefix("fancy-tree-check-")?;
+        let samples = Self { dir };
+        for file in Self::FILES {
+            let path = samples.dir.path().join(file);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, "")?;
+        }
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("main.rs", samples.dir.path().join("link"))?;
+        Ok(samples)
+    }
+
+    /// Gets the sample entries, including the directories.
+    fn entries(&self) -> io::Result<Vec<Entry<PathBuf>>> {
+        let dir = self.dir.path();
+        let mut paths = vec![dir.to_path_buf(), dir.join("src")];
+        paths.extend(Self::FILES.map(|file| dir.join(file)));
+        #[cfg(unix)]
+        paths.push(dir.join("link"));
+        paths.into_iter().map(Entry::new).collect()
+    }
+}
+
+/// Calls each function of the configurations with sample entries and statuses, so
+/// that their errors are reported to the configurations' diagnostics instead of
+/// while a tree is written.
+pub fn run(
+    config: Option<&Main>,
+    icons: Option<&Icons>,
+    colors: Option<&Colors>,
+) -> io::Result<()> {
+    const STATUSES: [Status; 4] = [
+        Status::Added,
+        Status::Modified,
+        Status::Removed,
+        Status::Renamed,
+    ];
+    const PERMISSIONS: [&str; 2] = ["drwxr-xr-x", "-rw-r--r--"];
+
+    let samples = Samples::create()?;
+    let entries = samples.entries()?;
+    let statuses = [
+        None,
+        Some(FileStatus {
+            tracked: Some(Status::Modified),
+            untracked: None,
+        }),
+        Some(FileStatus {
+            tracked: None,
+            untracked: Some(Status::Added),
+        }),
+    ];
+
+    for entry in entries.iter() {
+        for status in statuses {
+            if let Some(config) = config {
+                config.should_skip(entry, false, || status, || false);
+                config.filelimit_for(entry, || status, Some(10));
+            }
+            if let Some(icons) = icons {
+                icons.get_icon(entry, true, || status);
+            }
+            if let Some(colors) = colors {
+                colors.for_icon(entry, || status);
+            }
+        }
+    }
+    if let Some(config) = config {
+        let stats = Stats {
+            directories: 1,
+            files: Samples::FILES.len(),
+            total_size: None,
+        };
+        config.summary(stats.to_string());
+        let lines = entries
+            .iter()
+            .map(|entry| Line(entry.path().display().to_string()))
+            .collect();
+        config.postprocess(lines);
+        for pair in entries.windows(2) {
+            config.cmp(pair[0].path(), pair[1].path());
+        }
+    }
+    if let Some(colors) = colors {
+        for permissions in PERMISSIONS {
+            colors.for_permissions(permissions);
+        }
+        for status in STATUSES {
+            colors.for_tracked_git_status(status);
+            colors.for_untracked_git_status(status);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::{Diagnostics, Source};
+    use mlua::Lua;
+
+    #[test]
+    fn test_run() {
+        let lua = Lua::new();
+        let diagnostics = Diagnostics::default();
+        diagnostics.attach(&lua);
+        let config = lua
+            .load(r#"return { filelimit = function() return "many" end }"#)
+            .eval::<Main>()
+            .unwrap();
+        let icons = lua
+            .load("return function() return nil end")
+            .eval::<Icons>()
+            .unwrap();
+        let colors = lua
+            .load(r#"return { permissions = function() error("bad") end }"#)
+            .eval::<Colors>()
+            .unwrap();
+
+        run(Some(&config), Some(&icons), Some(&colors)).unwrap();
+        let sources = diagnostics
+            .take()
+            .into_iter()
+            .map(|diagnostic| diagnostic.source)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![Source::Config, Source::Colors], sources);
+    }
+}
@@ -1,23 +1,51 @@
 return {
   ---@param filepath string
   ---@param attributes FileAttributes
-  ---@param default Color|nil
-  ---@return Color|nil
+  ---@param default Style|nil
+  ---@return Style|nil
+  -- The color of each entry's icon. Return nil to leave the icon uncolored, or
+  -- fancytree.builtin to use the built-in color.
   icons = function(filepath, attributes, default)
     return default
   end,
   git_statuses = {
     ---@param status GitStatus
-    ---@param default Color|nil
-    ---@return Color|nil
+    ---@param default Style|nil
+    ---@return Style|nil
+    -- The color of statuses of files that aren't staged, like new or modified files.
     untracked = function(status, default)
       return default
     end,
     ---@param status GitStatus
-    ---@param default Color|nil
-    ---@return Color|nil
+    ---@param default Style|nil
+    ---@return Style|nil
+    -- The color of statuses of files that are staged.
     tracked = function(status, default)
       return default
     end,
   },
+  ---@param permissions string The formatted permissions, like "drwxr-xr-x"
+  ---@param default Style|nil
+  ---@return Style|nil
+  -- The color of the permissions shown with --permissions.
+  permissions = function(permissions, default)
+    return default
+  end,
+  ---@param filepath string
+  ---@param attributes FileAttributes
+  ---@param inherited Style|nil The tint of the entry's directory
+  ---@return Style|nil
+  -- The tint of each entry, which is layered under the colors of its icon and name,
+  -- and which the entries in a directory inherit. Return { dim = true } for "vendor"
+  -- to dim everything under it, or nil to stop inheriting a tint.
+  tint = function(filepath, attributes, inherited)
+    return inherited
+  end,
+  ---@type AgeGradient
+  -- The colors of names with --age, from the most recently changed file to the least
+  -- recently changed file.
+  age = {
+    fresh = { r = 255, g = 255, b = 255 },
+    stale = { r = 96, g = 96, b = 96 },
+  },
 }
@@ -1,16 +1,24 @@
 //! Module for configuring colors.
 use super::ConfigFile;
-use crate::color::Color;
+use crate::color::{Color, Style};
 use crate::colors;
-use crate::git::status::{self, Status};
-use crate::lua::interop;
+use crate::diagnostics::{Diagnostics, Source};
+use crate::lua::{api::Builtin, interop};
+use crate::rules::{self, Precedence};
 use crate::tree::{
     Entry,
     entry::{Attributes, attributes::FileAttributes},
 };
-use mlua::{FromLua, Lua};
+use crate::vcs::status::{FileStatus, Status};
+use mlua::{
+    Either::{self, Left, Right},
+    FromLua, Lua,
+};
 use owo_colors::AnsiColors;
 use std::path::Path;
+pub use theme::Theme;
+
+mod theme;
 
 /// The configuration for application colors.
 #[derive(Debug, Default)]
@@ -18,70 +26,194 @@ pub struct Colors {
     /// Function to get the color for an entry's icon.
     for_icon: Option<mlua::Function>,
     git_statuses: GitStatuses,
+    /// The gradient for coloring file names by the age of their last commit.
+    age: AgeGradient,
+    /// Function to get the color for an entry's permissions.
+    permissions: Option<mlua::Function>,
+    /// Function to get the tint of an entry, which its children inherit.
+    tint: Option<mlua::Function>,
+    /// The default colors, which the functions receive.
+    theme: Theme,
+    /// How the built-in colors and the function decide an icon's color.
+    precedence: Precedence,
+    /// Where errors from the functions are reported.
+    diagnostics: Diagnostics,
+    /// The Lua state that the functions are from.
+    scope: interop::Scope,
 }
 
 impl Colors {
-    /// The default color to use for files.
-    const DEFAULT_FILE_COLOR: Option<Color> = None;
-    /// The default color to use when a file is an executable.
-    const DEFAULT_EXECUTABLE_COLOR: Option<Color> = Some(Color::Ansi(AnsiColors::Green));
-    /// The default color to use for directories/folders.
-    const DEFAULT_DIRECTORY_COLOR: Option<Color> = Some(Color::Ansi(AnsiColors::Blue));
-    /// The default color to use for symlinks.
-    const DEFAULT_SYMLINK_COLOR: Option<Color> = Some(Color::Ansi(AnsiColors::Cyan));
-
-    /// Get the color for an entry's icon.
-    pub fn for_icon<P>(&self, entry: &Entry<P>) -> Option<Color>
+    /// The default style to use for permissions.
+    const DEFAULT_PERMISSIONS_STYLE: Option<Style> =
+        Some(Style::new().fg(Color::Ansi(AnsiColors::Magenta)));
+
+    /// Uses the theme's colors as the defaults.
+    #[inline]
+    #[must_use]
+    pub fn theme(self, theme: Theme) -> Self {
+        Self { theme, ..self }
+    }
+
+    /// Sets how the built-in colors and the function decide an icon's color.
+    #[inline]
+    #[must_use]
+    pub fn precedence(self, precedence: Precedence) -> Self {
+        Self { precedence, ..self }
+    }
+
+    /// Get the style for an entry's icon.
+    ///
+    /// `status` gets the entry's version control status, and is only called if the
+    /// configuration has a function for icon colors.
+    pub fn for_icon<P, S>(&self, entry: &Entry<P>, status: S) -> Option<Style>
     where
         P: AsRef<Path>,
+        S: FnOnce() -> Option<FileStatus>,
+    {
+        self.for_icon_with_source(entry, status).0
+    }
+
+    /// Like [`Colors::for_icon`], but also gets what decided the style.
+    pub fn for_icon_with_source<P, S>(
+        &self,
+        entry: &Entry<P>,
+        status: S,
+    ) -> (Option<Style>, rules::Source)
+    where
+        P: AsRef<Path>,
+        S: FnOnce() -> Option<FileStatus>,
     {
         let path = entry.path();
-        let default = colors::for_path(entry.path()).or_else(|| Self::default_entry_color(entry));
-        let attributes = interop::FileAttributes::from(entry);
-
-        // TODO Report error
-        self.for_icon
-            .as_ref()
-            .map_or(Ok(default), |f| {
-                f.call::<Option<Color>>((path, attributes, default))
+        let (default, source) = colors::match_path(path).map_or_else(
+            || self.default_entry_style(entry),
+            |(matcher, color)| (Some(Style::from(color)), matcher.into()),
+        );
+
+        if self.precedence == Precedence::BuiltinFirst && source.is_builtin() {
+            return (default, source);
+        }
+        let style = self.for_icon.as_ref().and_then(|f| {
+            let attributes = interop::FileAttributes::from(entry);
+            self.scope.with_status(attributes, status, |attributes| {
+                self.diagnostics.call::<Either<Builtin, Option<Style>>>(
+                    Source::Colors,
+                    "icons",
+                    f,
+                    (path, attributes, default),
+                )
             })
-            .unwrap_or(default)
+        });
+        let style = match style {
+            None | Some(Left(Builtin)) => default,
+            Some(Right(style)) if self.precedence == Precedence::Merge => {
+                Style::layer(style, default)
+            }
+            Some(Right(style)) => style,
+        };
+        (
+            style,
+            source.changed_by(&default, &style, rules::Source::Lua),
+        )
     }
 
-    /// Get the color for an untracked file's status.
-    pub fn for_untracked_git_status(&self, status: Status) -> Option<Color> {
-        self.git_statuses.get_untracked_color(status)
+    /// Get the tint of an entry, which is layered under the styles of its icon and
+    /// name, and which its children inherit. `inherited` is the tint of the entry's
+    /// directory.
+    ///
+    /// `status` gets the entry's version control status, and is only called if the
+    /// configuration has a function for tints.
+    pub fn for_tint<P, S>(
+        &self,
+        entry: &Entry<P>,
+        status: S,
+        inherited: Option<Style>,
+    ) -> Option<Style>
+    where
+        P: AsRef<Path>,
+        S: FnOnce() -> Option<FileStatus>,
+    {
+        self.tint.as_ref().map_or(inherited, |f| {
+            let path = entry.path();
+            let attributes = interop::FileAttributes::from(entry);
+            self.scope
+                .with_status(attributes, status, |attributes| {
+                    self.diagnostics
+                        .call(Source::Colors, "tint", f, (path, attributes, inherited))
+                })
+                .unwrap_or(inherited)
+        })
+    }
+
+    /// Get the style for an untracked file's status.
+    pub fn for_untracked_git_status(&self, status: Status) -> Option<Style> {
+        let default = self.theme.untracked.get(status);
+        self.git_statuses.get_untracked_style(status, default)
+    }
+
+    /// Get the style for an tracked file's status.
+    pub fn for_tracked_git_status(&self, status: Status) -> Option<Style> {
+        let default = self.theme.tracked.get(status);
+        self.git_statuses.get_tracked_style(status, default)
+    }
+
+    /// Get the style for the tree's branches.
+    #[inline]
+    pub fn for_connectors(&self) -> Option<Style> {
+        self.theme.connectors
     }
 
-    /// Get the color for an tracked file's status.
-    pub fn for_tracked_git_status(&self, status: Status) -> Option<Color> {
-        self.git_statuses.get_tracked_color(status)
+    /// Get the style for the names of ignored entries.
+    #[inline]
+    pub fn for_ignored(&self) -> Option<Style> {
+        self.theme.ignored
     }
 
-    fn default_entry_color<P>(entry: &Entry<P>) -> Option<Color>
+    /// Get the color for a file name, where `staleness` goes from `0.0` for the most
+    /// recently changed files to `1.0` for the least recently changed files.
+    pub fn for_age(&self, staleness: f64) -> Color {
+        self.age.get(staleness)
+    }
+
+    /// Get the style for formatted permissions, like `drwxr-xr-x`.
+    pub fn for_permissions(&self, permissions: &str) -> Option<Style> {
+        let default = Self::DEFAULT_PERMISSIONS_STYLE;
+        self.permissions.as_ref().map_or(default, |f| {
+            self.diagnostics
+                .call(Source::Colors, "permissions", f, (permissions, default))
+                .unwrap_or(default)
+        })
+    }
+
+    /// Gets the style for the kind of entry, and what decided it.
+    fn default_entry_style<P>(&self, entry: &Entry<P>) -> (Option<Style>, rules::Source)
     where
         P: AsRef<Path>,
     {
-        match entry.attributes() {
-            Attributes::Directory(_) => Self::DEFAULT_DIRECTORY_COLOR,
-            Attributes::File(attributes) => Self::get_file_color(attributes),
-            Attributes::Symlink(_) => Self::DEFAULT_SYMLINK_COLOR,
-        }
+        let kinds = &self.theme.kinds;
+        let style = match entry.attributes() {
+            Attributes::Directory(_) => kinds.directory,
+            Attributes::File(attributes) => return self.get_file_style(attributes),
+            Attributes::Symlink(attributes) if attributes.is_broken() => kinds.broken_symlink,
+            Attributes::Symlink(_) => kinds.symlink,
+        };
+        (style, rules::Source::Default)
     }
 
-    /// Gets the color for a file.
-    fn get_file_color(attributes: &FileAttributes) -> Option<Color> {
-        attributes
-            .language()
-            .map(|language| language.rgb())
-            .map(|(r, g, b)| Color::Rgb(r, g, b))
-            .or_else(|| {
-                attributes
-                    .is_executable()
-                    .then_some(Self::DEFAULT_EXECUTABLE_COLOR)
-                    .flatten()
-            })
-            .or(Self::DEFAULT_FILE_COLOR)
+    /// Gets the style for a file, and what decided it.
+    fn get_file_style(&self, attributes: &FileAttributes) -> (Option<Style>, rules::Source) {
+        let kinds = &self.theme.kinds;
+        if let Some((r, g, b)) = attributes.language().map(|language| language.rgb()) {
+            return (
+                Some(Style::from(Color::Rgb(r, g, b))),
+                rules::Source::Language,
+            );
+        }
+        let style = attributes
+            .is_executable()
+            .then_some(kinds.executable)
+            .flatten()
+            .or(kinds.file);
+        (style, rules::Source::Default)
     }
 }
 
@@ -94,16 +226,31 @@ impl FromLua for Colors {
     fn from_lua(value: mlua::Value, lua: &Lua) -> mlua::Result<Self> {
         const FOR_ICON_KEY: &str = "icons";
         const GIT_STATUSES_KEY: &str = "git_statuses";
+        const AGE_KEY: &str = "age";
+        const PERMISSIONS_KEY: &str = "permissions";
+        const TINT_KEY: &str = "tint";
 
         let table = mlua::Table::from_lua(value, lua)?;
         let for_icon = table.get(FOR_ICON_KEY)?;
         let git_statuses = table
             .get::<Option<GitStatuses>>(GIT_STATUSES_KEY)?
             .unwrap_or_default();
+        let age = table
+            .get::<Option<AgeGradient>>(AGE_KEY)?
+            .unwrap_or_default();
+        let permissions = table.get(PERMISSIONS_KEY)?;
+        let tint = table.get(TINT_KEY)?;
 
         let colors = Self {
             for_icon,
             git_statuses,
+            age,
+            permissions,
+            tint,
+            theme: Theme::default(),
+            precedence: Precedence::default(),
+            diagnostics: Diagnostics::for_lua(lua),
+            scope: interop::Scope::new(lua),
         };
         Ok(colors)
     }
@@ -116,39 +263,30 @@ struct GitStatuses {
     tracked: Option<mlua::Function>,
     /// Function to get the color for untracked statuses.
     untracked: Option<mlua::Function>,
+    /// Where errors from the functions are reported.
+    diagnostics: Diagnostics,
 }
 
 impl GitStatuses {
-    /// Gets the default color for a git status.
-    const fn get_default_color<S>(status: Status) -> Option<Color>
-    where
-        S: StatusColor,
-    {
-        let color = match status {
-            Status::Added => S::DEFAULT_ADDED,
-            Status::Modified => S::DEFAULT_MODIFIED,
-            Status::Removed => S::DEFAULT_REMOVED,
-            Status::Renamed => S::DEFAULT_RENAMED,
-        };
-        Some(Color::Ansi(color))
-    }
-
-    /// Gets the color for a tracked git status.
-    fn get_tracked_color(&self, status: Status) -> Option<Color> {
-        let default = Self::get_default_color::<status::Tracked>(status);
-        // TODO Report error
+    /// Gets the style for a tracked git status.
+    fn get_tracked_style(&self, status: Status, default: Option<Style>) -> Option<Style> {
         self.tracked.as_ref().map_or(default, |f| {
-            f.call::<Option<Color>>((status, default))
+            self.diagnostics
+                .call(Source::Colors, "git_statuses.tracked", f, (status, default))
                 .unwrap_or(default)
         })
     }
 
-    /// Gets the color for an untracked git status.
-    fn get_untracked_color(&self, status: Status) -> Option<Color> {
-        let default = Self::get_default_color::<status::Untracked>(status);
-        // TODO Report error
+    /// Gets the style for an untracked git status.
+    fn get_untracked_style(&self, status: Status, default: Option<Style>) -> Option<Style> {
         self.untracked.as_ref().map_or(default, |f| {
-            f.call::<Option<Color>>((status, default))
+            self.diagnostics
+                .call(
+                    Source::Colors,
+                    "git_statuses.untracked",
+                    f,
+                    (status, default),
+                )
                 .unwrap_or(default)
         })
     }
@@ -163,33 +301,152 @@ impl FromLua for GitStatuses {
         let tracked = table.get(TRACKED_KEY)?;
         let untracked = table.get(UNTRACKED_KEY)?;
 
-        let git_statuses = Self { tracked, untracked };
+        let git_statuses = Self {
+            tracked,
+            untracked,
+            diagnostics: Diagnostics::for_lua(lua),
+        };
         Ok(git_statuses)
     }
 }
 
-/// Private trait to generalize getting the color for a status.
-trait StatusColor {
-    /// Default color for added status.
-    const DEFAULT_ADDED: AnsiColors;
-    /// Default color for modified status.
-    const DEFAULT_MODIFIED: AnsiColors;
-    /// Default color for removed status.
-    const DEFAULT_REMOVED: AnsiColors;
-    /// Default color for renamed status.
-    const DEFAULT_RENAMED: AnsiColors;
+/// The gradient endpoints for coloring by age.
+#[derive(Debug)]
+struct AgeGradient {
+    /// The color of the most recently changed files.
+    fresh: Color,
+    /// The color of the least recently changed files.
+    stale: Color,
+}
+
+impl AgeGradient {
+    /// The default color for fresh files.
+    const DEFAULT_FRESH: Color = Color::Rgb(0xFF, 0xFF, 0xFF);
+    /// The default color for stale files.
+    const DEFAULT_STALE: Color = Color::Rgb(0x60, 0x60, 0x60);
+
+    /// Gets the color between the endpoints.
+    fn get(&self, staleness: f64) -> Color {
+        let staleness = staleness.clamp(0.0, 1.0);
+        match (self.fresh, self.stale) {
+            (Color::Rgb(r1, g1, b1), Color::Rgb(r2, g2, b2)) => {
+                let mix = |fresh: u8, stale: u8| {
+                    let fresh = f64::from(fresh);
+                    let stale = f64::from(stale);
+                    (fresh + (stale - fresh) * staleness).round() as u8
+                };
+                Color::Rgb(mix(r1, r2), mix(g1, g2), mix(b1, b2))
+            }
+            // NOTE ANSI colors can't be mixed, so the closest endpoint is used.
+            (fresh, _) if staleness < 0.5 => fresh,
+            (_, stale) => stale,
+        }
+    }
+}
+
+impl Default for AgeGradient {
+    fn default() -> Self {
+        Self {
+            fresh: Self::DEFAULT_FRESH,
+            stale: Self::DEFAULT_STALE,
+        }
+    }
 }
 
-impl StatusColor for status::Tracked {
-    const DEFAULT_ADDED: AnsiColors = AnsiColors::Green;
-    const DEFAULT_MODIFIED: AnsiColors = AnsiColors::Yellow;
-    const DEFAULT_REMOVED: AnsiColors = AnsiColors::Red;
-    const DEFAULT_RENAMED: AnsiColors = AnsiColors::Cyan;
+impl FromLua for AgeGradient {
+    fn from_lua(value: mlua::Value, lua: &Lua) -> mlua::Result<Self> {
+        let table = mlua::Table::from_lua(value, lua)?;
+        let fresh = table
+            .get::<Option<Color>>("fresh")?
+            .unwrap_or(Self::DEFAULT_FRESH);
+        let stale = table
+            .get::<Option<Color>>("stale")?
+            .unwrap_or(Self::DEFAULT_STALE);
+
+        let age = Self { fresh, stale };
+        Ok(age)
+    }
 }
 
-impl StatusColor for status::Untracked {
-    const DEFAULT_ADDED: AnsiColors = AnsiColors::BrightGreen;
-    const DEFAULT_MODIFIED: AnsiColors = AnsiColors::BrightYellow;
-    const DEFAULT_REMOVED: AnsiColors = AnsiColors::BrightRed;
-    const DEFAULT_RENAMED: AnsiColors = AnsiColors::BrightCyan;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0.0, Color::Rgb(200, 100, 0))]
+    #[case(0.5, Color::Rgb(150, 100, 50))]
+    #[case(1.0, Color::Rgb(100, 100, 100))]
+    #[case(2.0, Color::Rgb(100, 100, 100))]
+    fn test_age_gradient_rgb(#[case] staleness: f64, #[case] expected: Color) {
+        let age = AgeGradient {
+            fresh: Color::Rgb(200, 100, 0),
+            stale: Color::Rgb(100, 100, 100),
+        };
+        assert_eq!(expected, age.get(staleness));
+    }
+
+    #[rstest]
+    #[case(0.25, Color::Ansi(AnsiColors::BrightWhite))]
+    #[case(0.75, Color::Ansi(AnsiColors::BrightBlack))]
+    fn test_age_gradient_ansi(#[case] staleness: f64, #[case] expected: Color) {
+        let age = AgeGradient {
+            fresh: Color::Ansi(AnsiColors::BrightWhite),
+            stale: Color::Ansi(AnsiColors::BrightBlack),
+        };
+        assert_eq!(expected, age.get(staleness));
+    }
+
+    #[rstest]
+    #[case(Precedence::LuaFirst, "a.png", Some(Style::new().bold()))]
+    #[case(
+        Precedence::Merge,
+        "a.png",
+        Some(Style::new().fg(Color::Ansi(AnsiColors::Cyan)).bold())
+    )]
+    #[case(
+        Precedence::BuiltinFirst,
+        "a.png",
+        Some(Style::from(Color::Ansi(AnsiColors::Cyan)))
+    )]
+    #[case(
+        Precedence::LuaFirst,
+        "a.jpg",
+        Some(Style::from(Color::Ansi(AnsiColors::Yellow)))
+    )]
+    #[case(Precedence::LuaFirst, "dir", None)]
+    #[case(
+        Precedence::Merge,
+        "dir",
+        Some(Style::from(Color::Ansi(AnsiColors::Blue)))
+    )]
+    #[case(Precedence::BuiltinFirst, "dir", None)]
+    fn test_for_icon_precedence(
+        #[case] precedence: Precedence,
+        #[case] name: &str,
+        #[case] expected: Option<Style>,
+    ) {
+        let root = tempfile::TempDir::with_prefix("fancy-tree-").unwrap();
+        std::fs::create_dir(root.path().join("dir")).unwrap();
+        for name in ["a.png", "a.jpg"] {
+            std::fs::File::create_new(root.path().join(name)).unwrap();
+        }
+        let lua = Lua::new();
+        lua.globals().set("builtin", Builtin).unwrap();
+        let colors = lua
+            .load(
+                r#"return {
+                    icons = function(path)
+                        if path:match("%.png$") then return { bold = true } end
+                        if path:match("%.jpg$") then return builtin end
+                    end,
+                }"#,
+            )
+            .eval::<Colors>()
+            .unwrap()
+            .precedence(precedence);
+
+        let entry = Entry::new(root.path().join(name)).unwrap();
+        assert_eq!(expected, colors.for_icon(&entry, || None));
+    }
 }
@@ -0,0 +1,236 @@
+//! Module for color themes, which set the default colors in one table.
+use crate::color::{Color, Style};
+use crate::vcs::status::Status;
+use mlua::{FromLua, Lua};
+use owo_colors::AnsiColors;
+
+/// The default colors of a tree, which the color configuration's functions receive
+/// as their `default` argument.
+///
+/// Themes are Lua files that return a table, like the built-in `themes/gruvbox.lua`.
+/// Values that a theme doesn't set use the default theme's values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// The colors for the kinds of entries.
+    pub(super) kinds: KindColors,
+    /// The colors for tracked git statuses.
+    pub(super) tracked: StatusColors,
+    /// The colors for untracked git statuses.
+    pub(super) untracked: StatusColors,
+    /// The color for the tree's branches.
+    pub(super) connectors: Option<Style>,
+    /// The color for the names of ignored entries.
+    pub(super) ignored: Option<Style>,
+}
+
+/// The colors for the kinds of entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct KindColors {
+    /// The color for files that don't have a color for their language.
+    pub(super) file: Option<Style>,
+    /// The color for executables.
+    pub(super) executable: Option<Style>,
+    /// The color for directories.
+    pub(super) directory: Option<Style>,
+    /// The color for symlinks.
+    pub(super) symlink: Option<Style>,
+    /// The color for symlinks whose targets don't exist.
+    pub(super) broken_symlink: Option<Style>,
+}
+
+/// The colors for git statuses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct StatusColors {
+    /// The color for added files.
+    added: Option<Style>,
+    /// The color for modified files.
+    modified: Option<Style>,
+    /// The color for removed files.
+    removed: Option<Style>,
+    /// The color for renamed files.
+    renamed: Option<Style>,
+}
+
+impl Theme {
+    /// The themes that are shipped with this crate, by name.
+    pub const BUILTIN: [(&str, &str); 3] = [
+        ("catppuccin", include_str!("./themes/catppuccin.lua")),
+        ("gruvbox", include_str!("./themes/gruvbox.lua")),
+        ("nord", include_str!("./themes/nord.lua")),
+    ];
+
+    /// Gets the source of a built-in theme.
+    pub fn builtin(name: &str) -> Option<&'static str> {
+        Self::BUILTIN
+            .into_iter()
+            .find_map(|(builtin, source)| (builtin == name).then_some(source))
+    }
+
+    /// Gets the names of the built-in themes.
+    pub fn builtin_names() -> impl Iterator<Item = &'static str> {
+        Self::BUILTIN.into_iter().map(|(name, _)| name)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        use AnsiColors::{
+            Black, Blue, BrightCyan, BrightGreen, BrightRed, BrightYellow, Cyan, Green, Red, Yellow,
+        };
+        let fg = |color| Style::from(Color::Ansi(color));
+
+        Self {
+            kinds: KindColors {
+                file: None,
+                executable: Some(fg(Green)),
+                directory: Some(fg(Blue)),
+                symlink: Some(fg(Cyan)),
+                broken_symlink: Some(fg(Red)),
+            },
+            tracked: StatusColors {
+                added: Some(fg(Green)),
+                modified: Some(fg(Yellow)),
+                removed: Some(fg(Red)),
+                renamed: Some(fg(Cyan)),
+            },
+            untracked: StatusColors {
+                added: Some(fg(BrightGreen)),
+                modified: Some(fg(BrightYellow)),
+                removed: Some(fg(BrightRed)),
+                renamed: Some(fg(BrightCyan)),
+            },
+            connectors: None,
+            ignored: Some(fg(Black)),
+        }
+    }
+}
+
+impl FromLua for Theme {
+    fn from_lua(value: mlua::Value, lua: &Lua) -> mlua::Result<Self> {
+        let table = mlua::Table::from_lua(value, lua)?;
+        let default = Self::default();
+
+        let kinds = match table.get::<Option<mlua::Table>>("kinds")? {
+            Some(kinds) => KindColors::from_table(&kinds, default.kinds)?,
+            None => default.kinds,
+        };
+        let git_statuses = table.get::<Option<mlua::Table>>("git_statuses")?;
+        let statuses = |key, default| match git_statuses {
+            Some(ref git_statuses) => match git_statuses.get::<Option<mlua::Table>>(key)? {
+                Some(statuses) => StatusColors::from_table(&statuses, default),
+                None => Ok(default),
+            },
+            None => Ok(default),
+        };
+        let tracked = statuses("tracked", default.tracked)?;
+        let untracked = statuses("untracked", default.untracked)?;
+        let connectors = table
+            .get::<Option<Style>>("connectors")?
+            .or(default.connectors);
+        let ignored = table.get::<Option<Style>>("ignored")?.or(default.ignored);
+
+        let theme = Self {
+            kinds,
+            tracked,
+            untracked,
+            connectors,
+            ignored,
+        };
+        Ok(theme)
+    }
+}
+
+impl KindColors {
+    /// Gets the colors from a table, using the defaults for missing colors.
+    fn from_table(table: &mlua::Table, default: Self) -> mlua::Result<Self> {
+        let get = |key, default: Option<Style>| -> mlua::Result<Option<Style>> {
+            Ok(table.get::<Option<Style>>(key)?.or(default))
+        };
+        Ok(Self {
+            file: get("file", default.file)?,
+            executable: get("executable", default.executable)?,
+            directory: get("directory", default.directory)?,
+            symlink: get("symlink", default.symlink)?,
+            broken_symlink: get("broken_symlink", default.broken_symlink)?,
+        })
+    }
+}
+
+impl StatusColors {
+    /// Gets the color for a status.
+    pub(super) const fn get(&self, status: Status) -> Option<Style> {
+        match status {
+            Status::Added => self.added,
+            Status::Modified => self.modified,
+            Status::Removed => self.removed,
+            Status::Renamed => self.renamed,
+        }
+    }
+
+    /// Gets the colors from a table, using the defaults for missing colors.
+    fn from_table(table: &mlua::Table, default: Self) -> mlua::Result<Self> {
+        let get = |key, default: Option<Style>| -> mlua::Result<Option<Style>> {
+            Ok(table.get::<Option<Style>>(key)?.or(default))
+        };
+        Ok(Self {
+            added: get("added", default.added)?,
+            modified: get("modified", default.modified)?,
+            removed: get("removed", default.removed)?,
+            renamed: get("renamed", default.renamed)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn test_from_lua() {
+        let lua = Lua::new();
+        let theme = lua
+            .load(
+                r#"return {
+                    kinds = { directory = { r = 1, g = 2, b = 3 } },
+                    git_statuses = { untracked = { added = "magenta" } },
+                    connectors = { fg = "bright-black", dim = true },
+                }"#,
+            )
+            .eval::<Theme>()
+            .unwrap();
+        let default = Theme::default();
+
+        assert_eq!(
+            Some(Style::from(Color::Rgb(1, 2, 3))),
+            theme.kinds.directory
+        );
+        assert_eq!(default.kinds.symlink, theme.kinds.symlink);
+        assert_eq!(
+            Some(Style::from(Color::Ansi(AnsiColors::Magenta))),
+            theme.untracked.get(Status::Added)
+        );
+        assert_eq!(
+            default.untracked.get(Status::Removed),
+            theme.untracked.get(Status::Removed)
+        );
+        assert_eq!(default.tracked, theme.tracked);
+        assert_eq!(
+            Some(Style::new().fg(Color::Ansi(AnsiColors::BrightBlack)).dim()),
+            theme.connectors
+        );
+        assert_eq!(default.ignored, theme.ignored);
+    }
+
+    #[rstest]
+    #[case("catppuccin")]
+    #[case("gruvbox")]
+    #[case("nord")]
+    fn test_builtin(#[case] name: &str) {
+        let lua = Lua::new();
+        let source = Theme::builtin(name).unwrap();
+        let theme = lua.load(source).eval::<Theme>().unwrap();
+        assert_ne!(Theme::default(), theme);
+        assert!(theme.connectors.is_some());
+    }
+}
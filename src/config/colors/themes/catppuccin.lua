@@ -0,0 +1,41 @@
+-- The Mocha flavor of the Catppuccin palette: https://catppuccin.com/palette
+local palette = {
+  red = { r = 0xf3, g = 0x8b, b = 0xa8 },
+  maroon = { r = 0xeb, g = 0xa0, b = 0xac },
+  peach = { r = 0xfa, g = 0xb3, b = 0x87 },
+  yellow = { r = 0xf9, g = 0xe2, b = 0xaf },
+  green = { r = 0xa6, g = 0xe3, b = 0xa1 },
+  teal = { r = 0x94, g = 0xe2, b = 0xd5 },
+  sky = { r = 0x89, g = 0xdc, b = 0xeb },
+  blue = { r = 0x89, g = 0xb4, b = 0xfa },
+  mauve = { r = 0xcb, g = 0xa6, b = 0xf7 },
+  overlay0 = { r = 0x6c, g = 0x70, b = 0x86 },
+  surface2 = { r = 0x58, g = 0x5b, b = 0x70 },
+}
+
+---@type Theme
+return {
+  kinds = {
+    file = nil,
+    executable = palette.green,
+    directory = palette.blue,
+    symlink = palette.sky,
+    broken_symlink = palette.red,
+  },
+  git_statuses = {
+    tracked = {
+      added = palette.green,
+      modified = palette.peach,
+      removed = palette.red,
+      renamed = palette.mauve,
+    },
+    untracked = {
+      added = palette.teal,
+      modified = palette.yellow,
+      removed = palette.maroon,
+      renamed = palette.sky,
+    },
+  },
+  connectors = palette.surface2,
+  ignored = palette.overlay0,
+}
@@ -0,0 +1,41 @@
+-- The dark gruvbox palette: https://github.com/morhetz/gruvbox
+local palette = {
+  red = { r = 0xfb, g = 0x49, b = 0x34 },
+  green = { r = 0xb8, g = 0xbb, b = 0x26 },
+  yellow = { r = 0xfa, g = 0xbd, b = 0x2f },
+  blue = { r = 0x83, g = 0xa5, b = 0x98 },
+  aqua = { r = 0x8e, g = 0xc0, b = 0x7c },
+  dark_red = { r = 0xcc, g = 0x24, b = 0x1d },
+  dark_green = { r = 0x98, g = 0x97, b = 0x1a },
+  dark_yellow = { r = 0xd7, g = 0x99, b = 0x21 },
+  dark_aqua = { r = 0x68, g = 0x9d, b = 0x6a },
+  gray = { r = 0x92, g = 0x83, b = 0x74 },
+  bg3 = { r = 0x66, g = 0x5c, b = 0x54 },
+}
+
+---@type Theme
+return {
+  kinds = {
+    file = nil,
+    executable = palette.green,
+    directory = palette.blue,
+    symlink = palette.aqua,
+    broken_symlink = palette.red,
+  },
+  git_statuses = {
+    tracked = {
+      added = palette.dark_green,
+      modified = palette.dark_yellow,
+      removed = palette.dark_red,
+      renamed = palette.dark_aqua,
+    },
+    untracked = {
+      added = palette.green,
+      modified = palette.yellow,
+      removed = palette.red,
+      renamed = palette.aqua,
+    },
+  },
+  connectors = palette.bg3,
+  ignored = palette.gray,
+}
@@ -0,0 +1,39 @@
+-- The Nord palette: https://www.nordtheme.com/docs/colors-and-palettes
+local palette = {
+  nord3 = { r = 0x4c, g = 0x56, b = 0x6a },
+  nord7 = { r = 0x8f, g = 0xbc, b = 0xbb },
+  nord8 = { r = 0x88, g = 0xc0, b = 0xd0 },
+  nord9 = { r = 0x81, g = 0xa1, b = 0xc1 },
+  nord11 = { r = 0xbf, g = 0x61, b = 0x6a },
+  nord12 = { r = 0xd0, g = 0x87, b = 0x70 },
+  nord13 = { r = 0xeb, g = 0xcb, b = 0x8b },
+  nord14 = { r = 0xa3, g = 0xbe, b = 0x8c },
+  nord15 = { r = 0xb4, g = 0x8e, b = 0xad },
+}
+
+---@type Theme
+return {
+  kinds = {
+    file = nil,
+    executable = palette.nord14,
+    directory = palette.nord9,
+    symlink = palette.nord8,
+    broken_symlink = palette.nord11,
+  },
+  git_statuses = {
+    tracked = {
+      added = palette.nord14,
+      modified = palette.nord13,
+      removed = palette.nord11,
+      renamed = palette.nord15,
+    },
+    untracked = {
+      added = palette.nord7,
+      modified = palette.nord12,
+      removed = palette.nord11,
+      renamed = palette.nord8,
+    },
+  },
+  connectors = palette.nord3,
+  ignored = palette.nord3,
+}
@@ -1,6 +1,10 @@
+-- Chooses the icon of each entry. Return nil to write the entry without an icon, or
+-- fancytree.builtin to use the built-in icon.
 ---@param filepath string Path to the file relative to the starting directory
 ---@param attributes FileAttributes
----@param default string The default icon
+---@param default string The default icon. Directories with special names, like `src`,
+---have their own icons, and other directories have an open folder icon when
+---`attributes.is_expanded` is true.
 ---@return string|nil
 local function icons(filepath, attributes, default)
   return default
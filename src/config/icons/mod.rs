@@ -1,12 +1,18 @@
 //! Module for the icon config.
 use super::ConfigFile;
-use crate::icons;
-use crate::lua::interop;
+use crate::diagnostics::{Diagnostics, Source};
+use crate::icons::{self, IconSet, Kind};
+use crate::lua::{api::Builtin, interop};
+use crate::rules::{self, Precedence};
 use crate::tree::{
     Entry,
     entry::{Attributes, attributes::FileAttributes},
 };
-use mlua::{FromLua, Lua};
+use crate::vcs::status::FileStatus;
+use mlua::{
+    Either::{self, Left, Right},
+    FromLua, Lua,
+};
 use std::path::Path;
 
 /// The configuration for icons.
@@ -14,66 +20,183 @@ use std::path::Path;
 pub struct Icons {
     /// Function to get the icon for an entry.
     get_icon: Option<mlua::Function>,
+    /// Where errors from the function are reported.
+    diagnostics: Diagnostics,
+    /// The Lua state that the function is from.
+    scope: interop::Scope,
+    /// The set that the default icons come from.
+    set: IconSet,
+    /// How the built-in icons and the function decide an icon.
+    precedence: Precedence,
 }
 
 impl Icons {
     /// The default icon to display for files.
-    const DEFAULT_FILE_ICON: &'static str = "\u{f0214}"; // 󰈔
+    pub(crate) const DEFAULT_FILE_ICON: &'static str = IconSet::Nerd.icon(Kind::File);
     /// The default icon to display when a file is an executable.
-    const DEFAULT_EXECUTABLE_ICON: &'static str = "\u{f070e}"; // 󰜎
+    pub(crate) const DEFAULT_EXECUTABLE_ICON: &'static str = IconSet::Nerd.icon(Kind::Executable);
     /// The default icon to display for directories/folders.
-    const DEFAULT_DIRECTORY_ICON: &'static str = "\u{f024b}"; // 󰉋
+    pub(crate) const DEFAULT_DIRECTORY_ICON: &'static str = IconSet::Nerd.icon(Kind::Directory);
+    /// The default icon to display for empty directories/folders.
+    pub(crate) const DEFAULT_EMPTY_DIRECTORY_ICON: &'static str =
+        IconSet::Nerd.icon(Kind::EmptyDirectory);
     /// The default icon to display for symlinks.
-    const DEFAULT_SYMLINK_ICON: &'static str = "\u{cf481}"; // 
+    pub(crate) const DEFAULT_SYMLINK_ICON: &'static str = IconSet::Nerd.icon(Kind::Symlink);
+    /// The default icon to display for symlinks whose targets don't exist.
+    pub(crate) const DEFAULT_BROKEN_SYMLINK_ICON: &'static str =
+        IconSet::Nerd.icon(Kind::BrokenSymlink);
 
     /// The icon (padding) to use if there is no icon.
     const EMPTY_ICON: &'static str = " ";
 
+    /// Uses the icons from a set as the defaults, like emoji instead of Nerd Font
+    /// icons.
+    #[inline]
+    #[must_use]
+    pub fn with_set(self, set: IconSet) -> Self {
+        Self { set, ..self }
+    }
+
+    /// Sets how the built-in icons and the function decide an icon.
+    #[inline]
+    #[must_use]
+    pub fn with_precedence(self, precedence: Precedence) -> Self {
+        Self { precedence, ..self }
+    }
+
     /// Get the icon for the entry. If the configuration returns `nil`, a string with
-    /// invisible characters will be returned.
+    /// invisible characters will be returned, unless the precedence is
+    /// [`Precedence::Merge`].
+    ///
+    /// On a Lua error or `fancytree.builtin`, this falls back to the default icon
+    /// choice. The icon is empty
+    /// if the set is [`IconSet::None`], without calling the configuration.
     ///
-    /// On a Lua error, this falls back to the default icon choice.
-    pub fn get_icon<P>(&self, entry: &Entry<P>) -> String
+    /// `expanded` is if a directory's entries are written below it, so that it gets
+    /// an open folder icon. `status` gets the entry's version control status, and is
+    /// only called if the configuration has a function.
+    pub fn get_icon<P, S>(&self, entry: &Entry<P>, expanded: bool, status: S) -> String
     where
         P: AsRef<Path>,
+        S: FnOnce() -> Option<FileStatus>,
     {
+        self.get_icon_with_source(entry, expanded, status).0
+    }
+
+    /// Like [`Icons::get_icon`], but also gets what decided the icon.
+    pub fn get_icon_with_source<P, S>(
+        &self,
+        entry: &Entry<P>,
+        expanded: bool,
+        status: S,
+    ) -> (String, rules::Source)
+    where
+        P: AsRef<Path>,
+        S: FnOnce() -> Option<FileStatus>,
+    {
+        if self.set == IconSet::None {
+            return (String::new(), rules::Source::Default);
+        }
         // TODO Use Cow
-        let default_icon =
-            icons::for_path(entry.path()).unwrap_or_else(|| Self::default_icon(entry));
-        self.get_icon
-            .as_ref()
-            .and_then(|f| {
-                let path = entry.path();
-                let attributes = interop::FileAttributes::from(entry);
-                // TODO Report the error when this function fails
-                f.call::<Option<String>>((path, attributes, default_icon))
-                    .ok()
+        let (default_icon, source) = self.default_icon(entry, expanded);
+        if self.precedence == Precedence::BuiltinFirst && source.is_builtin() {
+            return (String::from(default_icon), source);
+        }
+        let icon = self.get_icon.as_ref().and_then(|f| {
+            let path = entry.path();
+            let attributes = interop::FileAttributes::from(entry).with_expanded(expanded);
+            self.scope.with_status(attributes, status, |attributes| {
+                self.diagnostics.call::<Either<Builtin, Option<String>>>(
+                    Source::Icons,
+                    "icons",
+                    f,
+                    (path, attributes, default_icon),
+                )
             })
-            .unwrap_or_else(|| Some(String::from(default_icon)))
-            .unwrap_or_else(|| String::from(Self::EMPTY_ICON))
+        });
+        let icon = match icon {
+            None | Some(Left(Builtin)) => String::from(default_icon),
+            Some(Right(Some(icon))) => icon,
+            Some(Right(None)) if self.precedence == Precedence::Merge => String::from(default_icon),
+            Some(Right(None)) => String::from(Self::EMPTY_ICON),
+        };
+        // NOTE The default configuration returns the default, which isn't a change.
+        let source = source.changed_by(&default_icon, &icon.as_str(), rules::Source::Lua);
+        (icon, source)
     }
 
-    /// Gets the default icon choice for an entry.
-    fn default_icon<P>(entry: &Entry<P>) -> &str
+    /// Gets the built-in icon for a path that isn't on disk, like a deleted file, from
+    /// its name. The configuration isn't called, since there are no attributes to
+    /// give it.
+    pub fn get_deleted_icon<P>(&self, path: P, is_directory: bool) -> &'static str
     where
         P: AsRef<Path>,
     {
-        match entry.attributes() {
-            Attributes::Directory(_) => Self::DEFAULT_DIRECTORY_ICON,
-            Attributes::File(attributes) => Self::get_file_icon(attributes),
-            Attributes::Symlink(_) => Self::DEFAULT_SYMLINK_ICON,
-        }
+        let kind = if is_directory {
+            icons::kind_for_directory(path).unwrap_or(Kind::Directory)
+        } else {
+            icons::kind_for_path(path).unwrap_or(Kind::File)
+        };
+        self.set.icon(kind)
+    }
+
+    /// Gets the default icon choice for an entry, and what decided it.
+    ///
+    /// Directories get their icons from their names, like `src`, and otherwise from
+    /// whether they're empty or expanded. A directory is only known to be empty when
+    /// its entries were already read, like when it's expanded, so that directories
+    /// aren't read just for their icons. Files and symlinks get their icons from their
+    /// names and extensions.
+    fn default_icon<P>(&self, entry: &Entry<P>, expanded: bool) -> (&'static str, rules::Source)
+    where
+        P: AsRef<Path>,
+    {
+        let path = entry.path();
+        let (kind, source) = match entry.attributes() {
+            Attributes::Directory(attributes) => {
+                let kind = if attributes.cached_child_count() == Some(Some(0)) {
+                    Kind::EmptyDirectory
+                } else if expanded {
+                    Kind::OpenDirectory
+                } else {
+                    Kind::Directory
+                };
+                icons::kind_for_directory(path).map_or((kind, rules::Source::Default), |kind| {
+                    (kind, rules::Source::Directory)
+                })
+            }
+            Attributes::File(attributes) => {
+                return icons::match_path(path)
+                    .map(|(matcher, kind)| (self.set.icon(kind), matcher.into()))
+                    .unwrap_or_else(|| self.get_file_icon(attributes));
+            }
+            Attributes::Symlink(attributes) => {
+                let kind = if attributes.is_broken() {
+                    Kind::BrokenSymlink
+                } else {
+                    Kind::Symlink
+                };
+                icons::match_path(path).map_or((kind, rules::Source::Default), |(matcher, kind)| {
+                    (kind, matcher.into())
+                })
+            }
+        };
+        (self.set.icon(kind), source)
     }
 
-    /// Gets the default icon for a file entry.
-    fn get_file_icon(attributes: &FileAttributes) -> &'static str {
+    /// Gets the default icon for a file entry, and what decided it.
+    fn get_file_icon(&self, attributes: &FileAttributes) -> (&'static str, rules::Source) {
         if attributes.is_executable() {
-            return Self::DEFAULT_EXECUTABLE_ICON;
+            return (self.set.icon(Kind::Executable), rules::Source::Default);
         }
         attributes
             .language()
+            .filter(|_| self.set.has_language_icons())
             .and_then(|language| language.nerd_font_glyph())
-            .unwrap_or(Self::DEFAULT_FILE_ICON)
+            .map_or(
+                (self.set.icon(Kind::File), rules::Source::Default),
+                |icon| (icon, rules::Source::Language),
+            )
     }
 }
 
@@ -84,6 +207,52 @@ impl ConfigFile for Icons {
 
 impl FromLua for Icons {
     fn from_lua(value: mlua::Value, lua: &Lua) -> mlua::Result<Self> {
-        Option::<mlua::Function>::from_lua(value, lua).map(|get_icon| Self { get_icon })
+        Option::<mlua::Function>::from_lua(value, lua).map(|get_icon| Self {
+            get_icon,
+            diagnostics: Diagnostics::for_lua(lua),
+            scope: interop::Scope::new(lua),
+            set: IconSet::default(),
+            precedence: Precedence::default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[rstest]
+    #[case(Precedence::LuaFirst, "a.zip", "Z")]
+    #[case(Precedence::BuiltinFirst, "a.zip", IconSet::Ascii.icon(Kind::Archive))]
+    #[case(Precedence::LuaFirst, "a.jpg", IconSet::Ascii.icon(Kind::Image))]
+    #[case(Precedence::LuaFirst, "a", Icons::EMPTY_ICON)]
+    #[case(Precedence::Merge, "a", IconSet::Ascii.icon(Kind::File))]
+    #[case(Precedence::BuiltinFirst, "a", Icons::EMPTY_ICON)]
+    fn test_get_icon_precedence(
+        #[case] precedence: Precedence,
+        #[case] name: &str,
+        #[case] expected: &str,
+    ) {
+        let root = TempDir::with_prefix("fancy-tree-").unwrap();
+        fs::File::create_new(root.path().join(name)).unwrap();
+        let lua = Lua::new();
+        lua.globals().set("builtin", Builtin).unwrap();
+        let icons = lua
+            .load(
+                r#"return function(path)
+                    if path:match("%.zip$") then return "Z" end
+                    if path:match("%.jpg$") then return builtin end
+                end"#,
+            )
+            .eval::<Icons>()
+            .unwrap()
+            .with_set(IconSet::Ascii)
+            .with_precedence(precedence);
+
+        let entry = Entry::new(root.path().join(name)).unwrap();
+        assert_eq!(expected, icons.get_icon(&entry, false, || None));
     }
 }
@@ -0,0 +1,472 @@
+//! Module for loading configuration files from multiple sources.
+use super::cache::BytecodeCache;
+use super::{ConfigDir, ConfigFile, ProjectOverrides};
+use crate::diagnostics::{self, Diagnostics};
+use mlua::{FromLua, FromLuaMulti, Lua};
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::path::{Component, Path, PathBuf};
+
+/// Where a configuration file was loaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// A file that was given explicitly, like with `--config-file`.
+    Explicit(PathBuf),
+    /// A file in the project's configuration directory.
+    Project(PathBuf),
+    /// A file in the user's configuration directory.
+    Global(PathBuf),
+    /// The built-in defaults, which match the embedded default modules.
+    Default,
+}
+
+impl Source {
+    /// Gets the path of the file, which is `None` for the built-in defaults.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Explicit(path) | Self::Project(path) | Self::Global(path) => Some(path),
+            Self::Default => None,
+        }
+    }
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            Self::Explicit(_) => "explicit",
+            Self::Project(_) => "project",
+            Self::Global(_) => "global",
+            Self::Default => return write!(f, "built-in defaults"),
+        };
+        let path = self
+            .path()
+            .expect("Sources other than the defaults have paths");
+        write!(f, "{} ({kind})", path.display())
+    }
+}
+
+/// A loaded configuration value, and where it was loaded from.
+#[derive(Debug)]
+pub struct Loaded<T> {
+    /// The configuration, which is `None` when the built-in defaults are used.
+    pub value: Option<T>,
+    /// Where the configuration was loaded from.
+    pub source: Source,
+}
+
+/// Loads configuration files, checking sources in order of precedence.
+///
+/// For each configuration file, the first source that has it wins: explicit files,
+/// then the project directory, then the user's configuration directory, and finally
+/// the built-in defaults.
+#[derive(Default)]
+pub struct ConfigLoader {
+    /// Explicit paths, keyed by the filename of the configuration file they replace.
+    explicit: HashMap<&'static str, PathBuf>,
+    /// The optional project configuration directory.
+    project: Option<PathBuf>,
+    /// The optional user configuration directory.
+    global: Option<ConfigDir>,
+    /// The optional project file that overrides the main configuration.
+    overrides: Option<ProjectOverrides>,
+}
+
+impl ConfigLoader {
+    /// Creates a loader without any sources, so that only the defaults are used.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses an explicit file instead of the configuration file of type `T`.
+    #[inline]
+    #[must_use]
+    pub fn file<T, P>(mut self, path: P) -> Self
+    where
+        T: ConfigFile,
+        P: Into<PathBuf>,
+    {
+        self.explicit.insert(T::FILENAME, path.into());
+        self
+    }
+
+    /// Loads configuration files from a project directory.
+    #[inline]
+    #[must_use]
+    pub fn project_dir<P>(self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            project: Some(path.into()),
+            ..self
+        }
+    }
+
+    /// Loads configuration files from the user's configuration directory.
+    #[inline]
+    #[must_use]
+    pub fn global_dir(self, config_dir: ConfigDir) -> Self {
+        Self {
+            global: Some(config_dir),
+            ..self
+        }
+    }
+
+    /// Merges a project's configuration file over the main configuration.
+    #[inline]
+    #[must_use]
+    pub fn project_overrides(self, overrides: ProjectOverrides) -> Self {
+        Self {
+            overrides: Some(overrides),
+            ..self
+        }
+    }
+
+    /// Gets the project file that overrides the main configuration, if any.
+    #[inline]
+    pub fn overrides(&self) -> Option<&ProjectOverrides> {
+        self.overrides.as_ref()
+    }
+
+    /// Finds where the configuration file of type `T` would be loaded from.
+    ///
+    /// Explicit files are always used, so that a missing file is reported as an
+    /// error instead of being silently skipped.
+    pub fn source<T>(&self) -> Source
+    where
+        T: ConfigFile,
+    {
+        if let Some(path) = self.explicit.get(T::FILENAME) {
+            return Source::Explicit(path.clone());
+        }
+        let project = self
+            .project
+            .as_ref()
+            .map(|dir| Source::Project(dir.join(T::FILENAME)));
+        let global = self
+            .global
+            .as_ref()
+            .map(|config_dir| Source::Global(config_dir.path().join(T::FILENAME)));
+        project
+            .into_iter()
+            .chain(global)
+            .find(|source| source.path().is_some_and(Path::exists))
+            .unwrap_or(Source::Default)
+    }
+
+    /// Loads the configuration file of type `T` from the source with the highest
+    /// precedence.
+    pub fn load<T>(&self, lua: &Lua) -> mlua::Result<Loaded<T>>
+    where
+        T: ConfigFile + FromLuaMulti,
+    {
+        let source = self.source::<T>();
+        let value = source
+            .path()
+            .map(|path| self.load_path(lua, path)?.call::<T>(()))
+            .transpose()?;
+        Ok(Loaded { value, source })
+    }
+
+    /// Loads the main configuration file, merging the project's overrides over it.
+    ///
+    /// Overrides that can't be loaded are reported, and the main configuration is
+    /// used without them.
+    pub fn load_main(&self, lua: &Lua) -> mlua::Result<Loaded<super::Main>> {
+        let Some(ref overrides) = self.overrides else {
+            return self.load(lua);
+        };
+        let source = self.source::<super::Main>();
+        let table = match source.path() {
+            Some(path) => self.load_path(lua, path)?.call::<mlua::Table>(())?,
+            None => lua.create_table()?,
+        };
+        Diagnostics::for_lua(lua).check(
+            diagnostics::Source::Config,
+            overrides.merge_into(lua, &table),
+        );
+        let value = super::Main::from_lua(mlua::Value::Table(table), lua)?;
+        Ok(Loaded {
+            value: Some(value),
+            source,
+        })
+    }
+
+    /// Loads the icon configuration file.
+    ///
+    /// Without an icon configuration file, the `icons` function of the main
+    /// configuration is used, so that everything can be configured in one file.
+    pub fn load_icons(
+        &self,
+        lua: &Lua,
+        main: Option<&super::Main>,
+    ) -> mlua::Result<Loaded<super::Icons>> {
+        let loaded = self.load(lua)?;
+        let icons = main.and_then(super::Main::icons);
+        match (loaded.source, icons) {
+            (Source::Default, Some(icons)) => Ok(Loaded {
+                value: Some(super::Icons::from_lua(
+                    mlua::Value::Function(icons.clone()),
+                    lua,
+                )?),
+                source: self.source::<super::Main>(),
+            }),
+            (source, _) => Ok(Loaded {
+                value: loaded.value,
+                source,
+            }),
+        }
+    }
+
+    /// Loads the colors configuration file, merged over the `colors` table of the
+    /// main configuration.
+    pub fn load_colors(
+        &self,
+        lua: &Lua,
+        main: Option<&super::Main>,
+    ) -> mlua::Result<Loaded<super::Colors>> {
+        let Some(colors) = main.and_then(super::Main::colors) else {
+            return self.load(lua);
+        };
+        let source = self.source::<super::Colors>();
+        if let Some(path) = source.path() {
+            let file = self.load_path(lua, path)?.call::<mlua::Table>(())?;
+            super::merge(colors, file)?;
+        }
+        let source = match source {
+            Source::Default => self.source::<super::Main>(),
+            source => source,
+        };
+        let value = super::Colors::from_lua(mlua::Value::Table(colors.clone()), lua)?;
+        Ok(Loaded {
+            value: Some(value),
+            source,
+        })
+    }
+
+    /// Loads a theme by name. A theme in the user's `themes` directory replaces the
+    /// built-in theme with the same name.
+    ///
+    /// Names that aren't a single file name, like `../theme` or absolute paths, are
+    /// errors, so that a theme can't load a file outside of the `themes` directory.
+    pub fn load_theme(&self, lua: &Lua, name: &str) -> mlua::Result<super::Theme> {
+        let mut components = Path::new(name).components();
+        let is_file_name = matches!(
+            (components.next(), components.next()),
+            (Some(Component::Normal(_)), None)
+        );
+        if !is_file_name {
+            return Err(mlua::Error::runtime(format!(
+                "invalid theme `{name}`, expected a name without a path"
+            )));
+        }
+        let path = self
+            .global
+            .as_ref()
+            .map(|config_dir| config_dir.themes_path().join(format!("{name}.lua")))
+            .filter(|path| path.is_file());
+        if let Some(path) = path {
+            return self.load_path(lua, &path)?.call(());
+        }
+        let Some(source) = super::Theme::builtin(name) else {
+            let names = super::Theme::builtin_names().collect::<Vec<_>>().join(", ");
+            return Err(mlua::Error::runtime(format!(
+                "unknown theme `{name}`, expected one of {names} or a file in the themes directory"
+            )));
+        };
+        lua.load(source)
+            .set_name(format!("@themes/{name}.lua"))
+            .call(())
+    }
+
+    /// Loads the plugins in the user's configuration directory. Plugins aren't loaded
+    /// from project directories, since they would run for anyone listing the
+    /// project.
+    pub fn load_plugins(&self, lua: &Lua) -> super::Plugins {
+        match self.global {
+            Some(ref config_dir) => super::Plugins::load(lua, &config_dir.plugins_path(), |path| {
+                self.load_path(lua, path)
+            }),
+            None => Default::default(),
+        }
+    }
+
+    /// Loads the WASM plugins listed in the main configuration. Relative paths are
+    /// relative to the user's configuration directory.
+    pub fn load_wasm_plugins(&self, config: &super::Main) -> super::WasmPlugins {
+        let paths = config.wasm_plugins().iter().map(|path| match self.global {
+            Some(ref config_dir) => config_dir.path().join(path),
+            None => path.to_path_buf(),
+        });
+        super::WasmPlugins::load(paths)
+    }
+
+    /// Loads a Lua file, using the bytecode cache when there is a user configuration
+    /// directory to keep it in.
+    fn load_path(&self, lua: &Lua, path: &Path) -> mlua::Result<mlua::Function> {
+        match self.global {
+            Some(ref config_dir) => BytecodeCache::new(config_dir.cache_path()).load(lua, path),
+            None => lua.load(path).into_function(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Colors, Icons, Main};
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_precedence() {
+        let root = TempDir::with_prefix("fancy-tree-").unwrap();
+        let project = root.path().join("project");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join(Main::FILENAME), "return {}").unwrap();
+        fs::write(project.join(Icons::FILENAME), "return {}").unwrap();
+        let explicit = root.path().join("explicit.lua");
+
+        let loader = ConfigLoader::new()
+            .file::<Main, _>(&explicit)
+            .project_dir(&project);
+        assert_eq!(Source::Explicit(explicit), loader.source::<Main>());
+        assert_eq!(
+            Source::Project(project.join(Icons::FILENAME)),
+            loader.source::<Icons>()
+        );
+        assert_eq!(Source::Default, loader.source::<Colors>());
+    }
+
+    #[test]
+    fn test_load() {
+        let root = TempDir::with_prefix("fancy-tree-").unwrap();
+        let path = root.path().join("custom.lua");
+        fs::write(&path, "return { level = 2 }").unwrap();
+        let lua = Lua::new();
+
+        let loader = ConfigLoader::new().file::<Main, _>(&path);
+        let main = loader.load_main(&lua).unwrap();
+        assert_eq!(Some(2), main.value.unwrap().level());
+        assert_eq!(Source::Explicit(path), main.source);
+
+        let colors = loader.load_colors(&lua, None).unwrap();
+        assert!(colors.value.is_none());
+        assert_eq!(Source::Default, colors.source);
+    }
+
+    #[test]
+    fn test_load_theme() {
+        use crate::color::Color;
+
+        let root = TempDir::with_prefix("fancy-tree-").unwrap();
+        let themes = root.path().join("themes");
+        fs::create_dir_all(&themes).unwrap();
+        fs::write(themes.join("nord.lua"), r#"return { connectors = "red" }"#).unwrap();
+        let lua = Lua::new();
+        let connectors = |theme| {
+            Colors::default()
+                .theme(theme)
+                .for_connectors()
+                .and_then(|style| style.fg)
+        };
+
+        let loader = ConfigLoader::new();
+        let gruvbox = loader.load_theme(&lua, "gruvbox").unwrap();
+        assert_eq!(Some(Color::Rgb(0x66, 0x5c, 0x54)), connectors(gruvbox));
+        let error = loader.load_theme(&lua, "unknown").unwrap_err();
+        assert!(
+            error.to_string().contains("unknown theme `unknown`"),
+            "{error}"
+        );
+
+        let loader = ConfigLoader::new().global_dir(ConfigDir::new(Some(root.path())).unwrap());
+        for name in ["../themes/nord", "/etc/theme", "", "."] {
+            let error = loader.load_theme(&lua, name).unwrap_err();
+            assert!(error.to_string().contains("invalid theme"), "{error}");
+        }
+
+        let loader = ConfigLoader::new().global_dir(ConfigDir::new(Some(root.path())).unwrap());
+        let nord = loader.load_theme(&lua, "nord").unwrap();
+        assert_eq!(
+            Some(Color::Ansi(owo_colors::AnsiColors::Red)),
+            connectors(nord)
+        );
+    }
+
+    #[test]
+    fn test_missing_explicit_file() {
+        let lua = Lua::new();
+        let loader = ConfigLoader::new().file::<Main, _>("/does/not/exist.lua");
+        assert!(loader.load_main(&lua).is_err());
+    }
+
+    #[test]
+    fn test_project_overrides() {
+        let root = TempDir::with_prefix("fancy-tree-").unwrap();
+        let path = root.path().join("custom.lua");
+        fs::write(&path, "return { level = 2, prune = true }").unwrap();
+        fs::write(root.path().join(".fancytree.lua"), "return { level = 3 }").unwrap();
+        let overrides = ProjectOverrides::find([root.path()]).unwrap();
+        let lua = Lua::new();
+
+        let loader = ConfigLoader::new()
+            .file::<Main, _>(&path)
+            .project_overrides(overrides.clone());
+        let main = loader.load_main(&lua).unwrap();
+        let value = main.value.unwrap();
+        assert_eq!(Some(3), value.level());
+        assert!(value.prune());
+        assert_eq!(Source::Explicit(path), main.source);
+
+        let loader = ConfigLoader::new().project_overrides(overrides);
+        let main = loader.load_main(&lua).unwrap();
+        assert_eq!(Some(3), main.value.unwrap().level());
+        assert_eq!(Source::Default, main.source);
+    }
+
+    #[test]
+    fn test_single_file() {
+        let root = TempDir::with_prefix("fancy-tree-").unwrap();
+        let config_path = root.path().join(Main::FILENAME);
+        fs::write(
+            &config_path,
+            r#"return {
+              icons = function() return "I" end,
+              colors = { permissions = function() return "red" end, age = { fresh = "blue" } },
+            }"#,
+        )
+        .unwrap();
+        let colors_path = root.path().join(Colors::FILENAME);
+        fs::write(&colors_path, r#"return { age = { stale = "green" } }"#).unwrap();
+        let lua = Lua::new();
+        let loader = ConfigLoader::new()
+            .file::<Main, _>(&config_path)
+            .file::<Colors, _>(&colors_path);
+        let main = loader.load_main(&lua).unwrap().value;
+
+        let icons = loader.load_icons(&lua, main.as_ref()).unwrap();
+        assert_eq!(Source::Explicit(config_path), icons.source);
+        let entry = crate::tree::Entry::new(root.path()).unwrap();
+        assert_eq!("I", icons.value.unwrap().get_icon(&entry, false, || None));
+
+        let colors = loader.load_colors(&lua, main.as_ref()).unwrap();
+        assert_eq!(Source::Explicit(colors_path), colors.source);
+        let colors = colors.value.unwrap();
+        assert_eq!(
+            Some(crate::color::Color::Ansi(owo_colors::AnsiColors::Red)),
+            colors
+                .for_permissions("-rw-r--r--")
+                .and_then(|style| style.fg)
+        );
+        assert_eq!(
+            crate::color::Color::Ansi(owo_colors::AnsiColors::Blue),
+            colors.for_age(0.0)
+        );
+        assert_eq!(
+            crate::color::Color::Ansi(owo_colors::AnsiColors::Green),
+            colors.for_age(1.0)
+        );
+    }
+}
@@ -4,17 +4,151 @@ return {
   ---@param filepath string Path to the file relative to the starting directory
   ---@param attributes FileAttributes
   ---@param default boolean
+  ---@param show_hidden boolean Are hidden files shown, like with --all?
   ---@return boolean
-  skip = function(filepath, attributes, default)
+  skip = function(filepath, attributes, default, show_hidden)
     -- The default is to hide dotfiles on Unix and files with the hidden attribute on
-    -- Windows.
+    -- Windows, unless show_hidden is true. Attributes like attributes:size() and
+    -- attributes:age() can be used to skip more, e.g.
+    -- `(attributes:size() or 0) > 100 * 1024 * 1024` for files over 100MB.
     return default
   end,
   ---@type Sorting|nil
-  -- When this is nil, the default sorting algorithm will be used.
+  -- When this is nil, the default sorting algorithm will be used. Names that are still
+  -- equal, like README and readme with ignore_case, are ordered by tie_break ("bytes"
+  -- by default), so the order is the same on every run.
   sorting = nil,
   ---@type integer|nil
   -- When this is not nil, it will set how many levels deep this tool should search in
-  -- the directory tree.
+  -- the directory tree. 0 only shows the starting directory.
   level = nil,
+  ---@type integer|nil
+  -- When this is not nil, the output is cut off after this many lines. Directories
+  -- that couldn't be printed completely are marked as truncated, and the markers are
+  -- included in the lines.
+  max_lines = nil,
+  ---@type integer|fun(path: string, attributes: FileAttributes, default: integer|nil): integer|nil|nil
+  -- When this is not nil, directories only show this many entries, followed by a line
+  -- like "… (+1234 more)", like --filelimit. A function decides the limit for each
+  -- directory, receiving the limit from --filelimit as default, and returns nil to show
+  -- every entry or 0 to show only the marker.
+  filelimit = nil,
+  ---@type "truncate"|"skip"|nil
+  -- What happens to directories with more entries than filelimit, like
+  -- --filelimit-mode. "truncate" shows the entries up to the limit, and "skip" doesn't
+  -- open the directory, like GNU tree, and only shows the line with the count.
+  filelimit_mode = nil,
+  ---@type "off"|"files"|"recursive"|nil
+  -- Shows the sizes of files, and with "recursive", the total sizes of directories.
+  size = nil,
+  ---@type string|integer|nil
+  -- Highlights files larger than this size, like "50M", like --warn-size.
+  warn_size = nil,
+  ---@type boolean|nil
+  -- Hides directories without any displayed entries, like --prune.
+  prune = false,
+  ---@type boolean|nil
+  -- Traverses symlinks to directories, like --follow-symlinks.
+  follow_symlinks = false,
+  ---@type "collapsed"|"hidden"|"expanded"|nil
+  -- How .git directories are shown, like --git-dir. "collapsed" shows the directory
+  -- with the checked out branch, but not its entries.
+  show_git_dir = "collapsed",
+  ---@type "auto"|"always"|"never"|nil
+  -- When output is piped through $FANCY_TREE_PAGER, $PAGER, or less, like --paging.
+  -- "auto" pages output that's taller than the terminal, and nil is the same as "auto".
+  paging = "auto",
+  ---@type "auto"|"nerd"|"emoji"|"ascii"|"none"|nil
+  -- The default icons, like --icons. "auto" uses Nerd Font icons unless the locale
+  -- isn't UTF-8, and nil is the same as "auto".
+  icon_set = nil,
+  ---@type "catppuccin"|"gruvbox"|"nord"|string|nil
+  -- The color theme, like --theme. Themes set the colors of kinds of entries, git
+  -- statuses, the tree's branches, and ignored entries. A file in the themes directory,
+  -- like themes/mine.lua, is used by its name, and replaces a built-in theme.
+  theme = nil,
+  ---@type "builtin-first"|"lua-first"|"merge"|nil
+  -- How the built-in icons and colors and the icons functions decide an entry's icon
+  -- and color. "lua-first" passes the built-in choice to the functions as their
+  -- default, "builtin-first" only calls them for entries without a built-in choice, and
+  -- "merge" keeps the built-in choice when they return nil and fills in the colors that
+  -- their styles don't set. In every mode, returning fancytree.builtin uses the
+  -- built-in choice. nil is the same as "lua-first".
+  rule_precedence = nil,
+  ---@type boolean|nil
+  -- Shows permissions, like --permissions.
+  permissions = false,
+  ---@type boolean|nil
+  -- Links names to their files, like --hyperlinks.
+  hyperlinks = false,
+  ---@type boolean|nil
+  -- Writes the root's path as a breadcrumb, like "a › b › c", like --breadcrumb.
+  breadcrumb = false,
+  ---@type boolean|nil
+  -- Shows when each entry was last modified, like --mtime.
+  mtime = false,
+  ---@type string|nil
+  -- The strftime format for modification times shown with --mtime, like "%b %e %H:%M".
+  -- When this is nil, "%Y-%m-%d %H:%M" is used.
+  mtime_format = nil,
+  ---@type boolean|nil
+  -- Marks files modified within recent_window with [recent], like --recent.
+  recent = false,
+  ---@type string|nil
+  -- How recently files must have been modified to be marked, like "90s", "15min", "2h",
+  -- or "1d". When this is nil, "15min" is used.
+  recent_window = nil,
+  ---@type AnnotationOptions|nil
+  -- Runs external commands for matching entries, and writes their trimmed output after
+  -- the entry's name. The entry's path is passed as the last argument. For example:
+  -- { jobs = 4, timeout = 2, commands = { { glob = "*.png|*.jpg", command = { "identify", "-format", "%wx%h" } } } }
+  annotations = nil,
+  ---@type CharsetName|CharsetConfig|nil
+  -- The characters for the tree's branches. This can be the name of a built-in
+  -- charset, or a table like { branch = "|-- ", last_branch = "`-- ", vertical = "|   ",
+  -- indent = "    " }. Each string must be the same width.
+  charset = nil,
+  ---@param default string The summary, like "2 directories, 3 files"
+  ---@return string|nil
+  -- Customizes the summary after the tree. fancytree.stats has the numbers of
+  -- directories and files, and the total size with --du. Return nil to hide the
+  -- summary.
+  summary = function(default)
+    return default
+  end,
+  ---@type (fun(lines: Line[]): (Line|string)[])|nil
+  -- Changes the lines of the tree before they're written, like to add a blank line
+  -- before each top-level directory. Each line is a table like
+  -- { text = "├── src", styled = "..." }, where text has no colors, and the function
+  -- returns the lines to write, which can be these tables or strings. When this is set,
+  -- the whole tree is written at once instead of line by line.
+  postprocess = nil,
+  ---@type string[]|nil
+  -- WASM modules that decorate entries with icons and colors, like { "plugins/hash.wasm" }.
+  -- Relative paths are relative to this directory. This needs a build with the "wasm"
+  -- feature.
+  wasm_plugins = nil,
+  ---@type GitOptions|nil
+  git = {
+    -- Set this to false to disable rename detection, which can be slow in repositories
+    -- with many new files.
+    renames = true,
+    -- The similarity threshold (0-100) for detecting renames. When this is nil,
+    -- libgit2's default is used.
+    rename_threshold = nil,
+  },
+  ---@type GitStatusesOptions|nil
+  git_statuses = {
+    -- Set this to true to show the combined statuses of their contents on directories,
+    -- like ~ when any file inside was modified.
+    roll_up = false,
+  },
+  ---@type IconFn|nil
+  -- The icon function, like the one icons.lua returns, so that everything can be
+  -- configured in this file. When icons.lua exists, it's used instead.
+  icons = nil,
+  ---@type ColorsConfig|nil
+  -- The colors, like the table colors.lua returns. When colors.lua exists, its table is
+  -- merged over this one.
+  colors = nil,
 }
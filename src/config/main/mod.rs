@@ -1,20 +1,32 @@
 //! Module for the main config.
 use super::ConfigFile;
 use crate::color::ColorChoice;
+use crate::diagnostics::{Diagnostics, Source};
+use crate::git;
+use crate::icons::IconSet;
 use crate::lua::interop;
+use crate::pager::Paging;
+use crate::rules::Precedence;
 use crate::sorting;
-use crate::tree::Entry;
+use crate::tree::{
+    Annotations, Charset, Entry, FileLimitMode, GitDir, MtimeFormat, RecentWindow, Size, SizeMode,
+};
+use crate::vcs::status::FileStatus;
 use mlua::{
     Either::{self, Left, Right},
     FromLua, Lua,
 };
 use std::cmp::Ordering;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Either a sorting configuration, or a function that takes two values and returns
 /// a negative number for less-than, 0 for equal, or a positive number for greater-than.
 type Sorting = Either<sorting::Sorting, mlua::Function>;
 
+/// Either a number of entries, or a function that decides the number for each
+/// directory.
+type FileLimit = Either<usize, mlua::Function>;
+
 /// The main configuration type.
 #[derive(Debug)]
 pub struct Main {
@@ -26,6 +38,66 @@ pub struct Main {
     sorting: Sorting,
     /// How many levels deep to search before stopping.
     level: Option<usize>,
+    /// How many lines to print before truncating the output.
+    max_lines: Option<usize>,
+    /// How many entries to write in each directory before truncating it.
+    filelimit: Option<FileLimit>,
+    /// What happens to directories with more entries than the file limit.
+    filelimit_mode: Option<FileLimitMode>,
+    /// Which entries have their sizes displayed.
+    size: Option<SizeMode>,
+    /// Should modification times be displayed?
+    mtime: bool,
+    /// The format for modification times.
+    mtime_format: Option<MtimeFormat>,
+    /// Should recently modified files be highlighted?
+    recent: bool,
+    /// How recently files must have been modified to be highlighted.
+    recent_window: Option<RecentWindow>,
+    /// The commands whose output annotates entries.
+    annotations: Option<Annotations>,
+    /// Should permissions be displayed?
+    permissions: bool,
+    /// Should names link to their files?
+    hyperlinks: bool,
+    /// Should the root's path be written as a breadcrumb?
+    breadcrumb: bool,
+    /// Should directories without any displayed entries be hidden?
+    prune: bool,
+    /// Should symlinks to directories be traversed?
+    follow_symlinks: bool,
+    /// How `.git` directories are written.
+    show_git_dir: Option<GitDir>,
+    /// When output is piped through a pager.
+    paging: Option<Paging>,
+    /// The set of default icons.
+    icon_set: Option<IconSet>,
+    /// The name of the color theme.
+    theme: Option<String>,
+    /// How the built-in rules and the icon and color functions decide icons and
+    /// colors.
+    rule_precedence: Precedence,
+    /// The size above which files are highlighted.
+    warn_size: Option<Size>,
+    /// The characters for the tree's branches.
+    charset: Option<Charset<'static>>,
+    /// Function to customize the summary after the tree.
+    summary: Option<mlua::Function>,
+    /// Function to change the lines of the written tree.
+    postprocess: Option<mlua::Function>,
+    /// The WASM plugins to load, relative to the configuration directory.
+    wasm_plugins: Vec<PathBuf>,
+    /// Options for git integration.
+    git: git::Options,
+    /// The icon function, for configurations that don't use a separate `icons.lua`.
+    icons: Option<mlua::Function>,
+    /// The color configuration, for configurations that don't use a separate
+    /// `colors.lua`.
+    colors: Option<mlua::Table>,
+    /// Where errors from the configuration's functions are reported.
+    diagnostics: Diagnostics,
+    /// The Lua state that the configuration's functions are from.
+    scope: interop::Scope,
 }
 
 impl Main {
@@ -36,37 +108,142 @@ impl Main {
     }
     /// Should a file be skipped according to the configuration?
     ///
-    /// `git_helper` is used to provide interoperability with git, which this config
-    /// type isn't aware of.
-    pub fn should_skip<P, F>(&self, entry: &Entry<P>, git_helper: F) -> bool
+    /// Hidden files are skipped by default, unless `show_hidden` is set, like with
+    /// `--all`. The `skip` function also receives `show_hidden`.
+    ///
+    /// `status` and `git_helper` are used to provide interoperability with git, which
+    /// this config type isn't aware of. `status` is only called if the configuration
+    /// has a `skip` function.
+    pub fn should_skip<P, S, F>(
+        &self,
+        entry: &Entry<P>,
+        show_hidden: bool,
+        status: S,
+        git_helper: F,
+    ) -> bool
     where
         P: AsRef<Path>,
+        S: FnOnce() -> Option<FileStatus>,
         F: FnOnce() -> bool,
     {
-        let default = entry.is_hidden() || git_helper();
+        let default = (!show_hidden && entry.is_hidden()) || git_helper();
         let path = entry.path();
-        let attributes = interop::FileAttributes::from(entry);
 
-        // TODO Report error
         self.skip
             .as_ref()
-            .map_or(Ok(default), |f| f.call::<bool>((path, attributes, default)))
+            .and_then(|f| {
+                let attributes = interop::FileAttributes::from(entry);
+                self.scope.with_status(attributes, status, |attributes| {
+                    self.diagnostics.call(
+                        Source::Config,
+                        "skip",
+                        f,
+                        (path, attributes, default, show_hidden),
+                    )
+                })
+            })
             .unwrap_or(default)
     }
 
+    /// Gets how many entries to write in a directory, starting from `default`, or
+    /// `None` to write all of them.
+    ///
+    /// `status` is only called if the configuration has a `filelimit` function.
+    pub fn filelimit_for<P, S>(
+        &self,
+        entry: &Entry<P>,
+        status: S,
+        default: Option<usize>,
+    ) -> Option<usize>
+    where
+        P: AsRef<Path>,
+        S: FnOnce() -> Option<FileStatus>,
+    {
+        let Some(Right(ref f)) = self.filelimit else {
+            return default;
+        };
+        let path = entry.path();
+        let attributes = interop::FileAttributes::from(entry);
+        self.scope
+            .with_status(attributes, status, |attributes| {
+                self.diagnostics
+                    .call(Source::Config, "filelimit", f, (path, attributes, default))
+            })
+            .unwrap_or(default)
+    }
+
+    /// Gets the summary to write after the tree, or `None` to hide it.
+    pub fn summary(&self, default: String) -> Option<String> {
+        match self.summary {
+            Some(ref f) => self
+                .diagnostics
+                .call(Source::Config, "summary", f, default.as_str())
+                .unwrap_or(Some(default)),
+            None => Some(default),
+        }
+    }
+
+    /// Checks if there's a function to change the lines of the written tree, which
+    /// means that the tree has to be written all at once.
+    #[inline]
+    pub fn has_postprocess(&self) -> bool {
+        self.postprocess.is_some()
+    }
+
+    /// Gets the lines to write instead of the lines of the written tree. On an error,
+    /// the lines are kept.
+    pub fn postprocess(&self, lines: Vec<interop::Line>) -> Vec<interop::Line> {
+        match self.postprocess {
+            Some(ref f) => self
+                .diagnostics
+                .call(Source::Config, "postprocess", f, lines.clone())
+                .unwrap_or(lines),
+            None => lines,
+        }
+    }
+
     /// Compares two paths for sorting.
     pub fn cmp<L, R>(&self, left: L, right: R) -> Ordering
     where
         L: AsRef<Path>,
         R: AsRef<Path>,
     {
-        // TODO Report error
         match self.sorting.as_ref() {
             Left(sorting) => sorting.cmp(left, right),
-            Right(f) => f
-                .call((left.as_ref(), right.as_ref()))
+            // NOTE Ties from a function are broken like the default sorting, so that
+            //      the order is the same on every run.
+            Right(f) => self
+                .diagnostics
+                .call(
+                    Source::Config,
+                    "sorting",
+                    f,
+                    (left.as_ref(), right.as_ref()),
+                )
                 .map(Self::isize_to_ordering)
-                .unwrap_or(Ordering::Equal),
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| {
+                    sorting::TieBreak::default().cmp(
+                        sorting::file_name(left.as_ref()),
+                        sorting::file_name(right.as_ref()),
+                    )
+                }),
+        }
+    }
+
+    /// Creates a configuration that doesn't depend on the environment, so that output
+    /// is identical across machines.
+    ///
+    /// Colors are off, and sorting doesn't use platform-specific defaults.
+    pub fn deterministic() -> Self {
+        let sorting = sorting::Sorting {
+            ignore_case: false,
+            ..Default::default()
+        };
+        Self {
+            color: ColorChoice::Off,
+            sorting: Left(sorting),
+            ..Default::default()
         }
     }
 
@@ -88,6 +265,138 @@ impl Main {
     pub fn level(&self) -> Option<usize> {
         self.level
     }
+
+    /// How many lines to print before truncating the output.
+    pub fn max_lines(&self) -> Option<usize> {
+        self.max_lines
+    }
+
+    /// How many entries to write in each directory, unless a function decides it
+    /// for each directory.
+    pub fn filelimit(&self) -> Option<usize> {
+        self.filelimit
+            .as_ref()
+            .and_then(|filelimit| filelimit.as_ref().left().copied())
+    }
+
+    /// What happens to directories with more entries than the file limit.
+    pub fn filelimit_mode(&self) -> Option<FileLimitMode> {
+        self.filelimit_mode
+    }
+
+    /// Which entries have their sizes displayed.
+    pub fn size_mode(&self) -> Option<SizeMode> {
+        self.size
+    }
+
+    /// Should modification times be displayed?
+    pub fn mtime(&self) -> bool {
+        self.mtime
+    }
+
+    /// The format for modification times.
+    pub fn mtime_format(&self) -> Option<&MtimeFormat> {
+        self.mtime_format.as_ref()
+    }
+
+    /// Should recently modified files be highlighted?
+    pub fn recent(&self) -> bool {
+        self.recent
+    }
+
+    /// How recently files must have been modified to be highlighted.
+    pub fn recent_window(&self) -> Option<RecentWindow> {
+        self.recent_window
+    }
+
+    /// The commands whose output annotates entries.
+    pub fn annotations(&self) -> Option<&Annotations> {
+        self.annotations.as_ref()
+    }
+
+    /// Should permissions be displayed?
+    pub fn permissions(&self) -> bool {
+        self.permissions
+    }
+
+    /// Should names link to their files?
+    pub fn hyperlinks(&self) -> bool {
+        self.hyperlinks
+    }
+
+    /// Should the root's path be written as a breadcrumb?
+    pub fn breadcrumb(&self) -> bool {
+        self.breadcrumb
+    }
+
+    /// Should directories without any displayed entries be hidden?
+    pub fn prune(&self) -> bool {
+        self.prune
+    }
+
+    /// Should symlinks to directories be traversed?
+    pub fn follow_symlinks(&self) -> bool {
+        self.follow_symlinks
+    }
+
+    /// How `.git` directories are written.
+    pub fn show_git_dir(&self) -> Option<GitDir> {
+        self.show_git_dir
+    }
+
+    /// When output is piped through a pager.
+    pub fn paging(&self) -> Option<Paging> {
+        self.paging
+    }
+
+    /// The set of default icons.
+    pub fn icon_set(&self) -> Option<IconSet> {
+        self.icon_set
+    }
+
+    /// The name of the color theme.
+    pub fn theme(&self) -> Option<&str> {
+        self.theme.as_deref()
+    }
+
+    /// How the built-in rules and the icon and color functions decide icons and
+    /// colors.
+    pub fn rule_precedence(&self) -> Precedence {
+        self.rule_precedence
+    }
+
+    /// The size above which files are highlighted.
+    pub fn warn_size(&self) -> Option<Size> {
+        self.warn_size
+    }
+
+    /// The characters for the tree's branches.
+    pub fn charset(&self) -> Option<&Charset<'static>> {
+        self.charset.as_ref()
+    }
+
+    /// The WASM plugins to load, relative to the configuration directory.
+    pub fn wasm_plugins(&self) -> &[PathBuf] {
+        &self.wasm_plugins
+    }
+
+    /// Options for git integration.
+    #[inline]
+    pub(crate) fn git_options(&self) -> &git::Options {
+        &self.git
+    }
+
+    /// The icon function from the `icons` key.
+    #[inline]
+    pub(crate) fn icons(&self) -> Option<&mlua::Function> {
+        self.icons.as_ref()
+    }
+
+    /// The color configuration from the `colors` key.
+    #[inline]
+    pub(crate) fn colors(&self) -> Option<&mlua::Table> {
+        self.colors.as_ref()
+    }
 }
 
 impl Default for Main {
@@ -97,6 +406,35 @@ impl Default for Main {
             skip: None,
             sorting: Self::default_sorting(),
             level: None,
+            max_lines: None,
+            filelimit: None,
+            filelimit_mode: None,
+            size: None,
+            mtime: false,
+            mtime_format: None,
+            recent: false,
+            recent_window: None,
+            annotations: None,
+            permissions: false,
+            hyperlinks: false,
+            breadcrumb: false,
+            prune: false,
+            follow_symlinks: false,
+            show_git_dir: None,
+            paging: None,
+            icon_set: None,
+            theme: None,
+            rule_precedence: Precedence::default(),
+            warn_size: None,
+            charset: None,
+            summary: None,
+            postprocess: None,
+            wasm_plugins: Vec::new(),
+            git: Default::default(),
+            icons: None,
+            colors: None,
+            diagnostics: Diagnostics::default(),
+            scope: interop::Scope::default(),
         }
     }
 }
@@ -107,7 +445,7 @@ impl ConfigFile for Main {
 }
 
 impl FromLua for Main {
-    fn from_lua(value: mlua::Value, _lua: &Lua) -> mlua::Result<Self> {
+    fn from_lua(value: mlua::Value, lua: &Lua) -> mlua::Result<Self> {
         let type_name = value.type_name();
 
         let conversion_error = || mlua::Error::FromLuaConversionError {
@@ -125,11 +463,83 @@ impl FromLua for Main {
             .get::<Option<Sorting>>("sorting")?
             .unwrap_or_else(Self::default_sorting);
         let level = table.get("level")?;
+        let max_lines = table.get("max_lines")?;
+        let filelimit = table.get("filelimit")?;
+        let filelimit_mode = table.get("filelimit_mode")?;
+        let size = table.get("size")?;
+        let mtime = table.get::<Option<bool>>("mtime")?.unwrap_or_default();
+        let mtime_format = table.get("mtime_format")?;
+        let recent = table.get::<Option<bool>>("recent")?.unwrap_or_default();
+        let recent_window = table.get("recent_window")?;
+        let annotations = table.get("annotations")?;
+        let permissions = table
+            .get::<Option<bool>>("permissions")?
+            .unwrap_or_default();
+        let hyperlinks = table.get::<Option<bool>>("hyperlinks")?.unwrap_or_default();
+        let breadcrumb = table.get::<Option<bool>>("breadcrumb")?.unwrap_or_default();
+        let prune = table.get::<Option<bool>>("prune")?.unwrap_or_default();
+        let follow_symlinks = table
+            .get::<Option<bool>>("follow_symlinks")?
+            .unwrap_or_default();
+        let show_git_dir = table.get("show_git_dir")?;
+        let paging = table.get("paging")?;
+        let icon_set = table.get("icon_set")?;
+        let theme = table.get("theme")?;
+        let rule_precedence = table
+            .get::<Option<Precedence>>("rule_precedence")?
+            .unwrap_or_default();
+        let warn_size = table.get("warn_size")?;
+        let charset = table.get("charset")?;
+        let summary = table.get("summary")?;
+        let postprocess = table.get("postprocess")?;
+        let wasm_plugins = table
+            .get::<Option<Vec<PathBuf>>>("wasm_plugins")?
+            .unwrap_or_default();
+        let mut git = table
+            .get::<Option<git::Options>>("git")?
+            .unwrap_or_default();
+        git.roll_up = table
+            .get::<Option<mlua::Table>>("git_statuses")?
+            .map(|git_statuses| git_statuses.get::<Option<bool>>("roll_up"))
+            .transpose()?
+            .flatten()
+            .unwrap_or_default();
+        let icons = table.get("icons")?;
+        let colors = table.get("colors")?;
         let main = Main {
             color,
             skip,
             sorting,
             level,
+            max_lines,
+            filelimit,
+            filelimit_mode,
+            size,
+            mtime,
+            mtime_format,
+            recent,
+            recent_window,
+            annotations,
+            permissions,
+            hyperlinks,
+            breadcrumb,
+            prune,
+            follow_symlinks,
+            show_git_dir,
+            paging,
+            icon_set,
+            theme,
+            rule_precedence,
+            warn_size,
+            charset,
+            summary,
+            postprocess,
+            wasm_plugins,
+            git,
+            icons,
+            colors,
+            diagnostics: Diagnostics::for_lua(lua),
+            scope: interop::Scope::new(lua),
         };
         Ok(main)
     }
@@ -140,6 +550,13 @@ mod tests {
     use super::*;
     use rstest::rstest;
 
+    #[test]
+    fn test_deterministic_ignores_platform() {
+        let config = Main::deterministic();
+        assert!(config.color_choice().is_off());
+        assert_eq!(Ordering::Less, config.cmp("B", "a"));
+    }
+
     #[rstest]
     #[case(-1, Ordering::Less)]
     #[case(0, Ordering::Equal)]
@@ -147,4 +564,160 @@ mod tests {
     fn test_isize_to_ordering(#[case] n: isize, #[case] expected: Ordering) {
         assert_eq!(expected, Main::isize_to_ordering(n));
     }
+
+    #[test]
+    fn test_options() {
+        let lua = Lua::new();
+        let chunk = r#"return { level = 1, prune = true, mtime = true, warn_size = "1K", recent_window = "1h" }"#;
+        let config = lua.load(chunk).eval::<Main>().unwrap();
+        assert_eq!(Some(1), config.level());
+        assert!(config.prune());
+        assert!(config.mtime());
+        assert!(!config.permissions());
+        assert!(!config.follow_symlinks());
+        assert_eq!(Some(Size(1024)), config.warn_size());
+        assert!(!config.recent());
+        assert_eq!(Some("1h".parse().unwrap()), config.recent_window());
+        assert!(!config.git_options().roll_up);
+    }
+
+    #[test]
+    fn test_roll_up() {
+        let lua = Lua::new();
+        let chunk = "return { git = { renames = false }, git_statuses = { roll_up = true } }";
+        let config = lua.load(chunk).eval::<Main>().unwrap();
+        assert!(config.git_options().roll_up);
+        assert!(!config.git_options().renames);
+    }
+
+    #[test]
+    fn test_filelimit_function() {
+        let lua = Lua::new();
+        let chunk = r#"
+            return {
+              filelimit = function(path, attributes, default)
+                if path:match("node_modules$") then return 0 end
+                return default
+              end,
+            }
+        "#;
+        let config = lua.load(chunk).eval::<Main>().unwrap();
+        assert_eq!(None, config.filelimit());
+
+        let root = tempfile::TempDir::with_prefix("fancy-tree-").unwrap();
+        let node_modules = root.path().join("node_modules");
+        std::fs::create_dir(&node_modules).unwrap();
+        let entry = Entry::new(&node_modules).unwrap();
+        assert_eq!(Some(0), config.filelimit_for(&entry, || None, Some(10)));
+        let entry = Entry::new(root.path()).unwrap();
+        assert_eq!(Some(10), config.filelimit_for(&entry, || None, Some(10)));
+    }
+
+    #[test]
+    fn test_postprocess() {
+        use interop::Line;
+
+        let lua = Lua::new();
+        let chunk = r#"
+            return {
+              postprocess = function(lines)
+                local result = {}
+                for i, line in ipairs(lines) do
+                  if i > 1 and not line.text:match("^[│├└ ]") then
+                    table.insert(result, "---")
+                  end
+                  table.insert(result, line)
+                end
+                return result
+              end,
+            }
+        "#;
+        let config = lua.load(chunk).eval::<Main>().unwrap();
+        assert!(config.has_postprocess());
+        let lines = ["root", "├── \x1b[34ma\x1b[0m", "b"]
+            .map(|line| Line(String::from(line)))
+            .to_vec();
+        let expected = ["root", "├── \x1b[34ma\x1b[0m", "---", "b"]
+            .map(|line| Line(String::from(line)))
+            .to_vec();
+        assert_eq!(expected, config.postprocess(lines));
+        assert!(!Main::default().has_postprocess());
+    }
+
+    // NOTE Dotfiles are only hidden on Unix.
+    #[cfg(not(windows))]
+    #[rstest]
+    #[case(false, true)]
+    #[case(true, false)]
+    fn test_skip_show_hidden(#[case] show_hidden: bool, #[case] expected: bool) {
+        let lua = Lua::new();
+        let chunk = r#"return {
+            skip = function(path, attributes, default, show_hidden)
+                assert(default == not show_hidden)
+                return default
+            end,
+        }"#;
+        let config = lua.load(chunk).eval::<Main>().unwrap();
+
+        let root = tempfile::TempDir::with_prefix("fancy-tree-").unwrap();
+        let hidden = root.path().join(".hidden");
+        std::fs::File::create_new(&hidden).unwrap();
+        let entry = Entry::new(&hidden).unwrap();
+        assert_eq!(
+            expected,
+            config.should_skip(&entry, show_hidden, || None, || false)
+        );
+    }
+
+    #[rstest]
+    #[case("return false", false, 0)]
+    #[case("return attributes:git_status().untracked == 'added'", true, 1)]
+    #[case("return attributes:git_status() ~= attributes:git_status()", true, 1)]
+    fn test_skip_status_is_lazy(
+        #[case] body: &str,
+        #[case] expected: bool,
+        #[case] expected_calls: usize,
+    ) {
+        use crate::vcs::status::{FileStatus, Status};
+        use std::cell::Cell;
+
+        let lua = Lua::new();
+        let chunk = format!("return {{ skip = function(path, attributes) {body} end }}");
+        let config = lua.load(chunk).eval::<Main>().unwrap();
+
+        let root = tempfile::TempDir::with_prefix("fancy-tree-").unwrap();
+        let entry = Entry::new(root.path()).unwrap();
+        let calls = Cell::new(0);
+        let status = || {
+            calls.set(calls.get() + 1);
+            Some(FileStatus {
+                untracked: Some(Status::Added),
+                ..FileStatus::CURRENT
+            })
+        };
+        assert_eq!(
+            expected,
+            config.should_skip(&entry, false, status, || false)
+        );
+        assert_eq!(expected_calls, calls.get());
+    }
+
+    #[test]
+    fn test_skip_error_is_reported() {
+        let lua = Lua::new();
+        let diagnostics = Diagnostics::default();
+        diagnostics.attach(&lua);
+        let chunk = r#"return { skip = function() error("boom") end }"#;
+        let config = lua.load(chunk).eval::<Main>().unwrap();
+
+        let root = tempfile::TempDir::with_prefix("fancy-tree-").unwrap();
+        let entry = Entry::new(root.path()).unwrap();
+        assert!(!config.should_skip(&entry, false, || None, || false));
+        assert!(config.should_skip(&entry, false, || None, || true));
+
+        let reported = diagnostics.take();
+        assert_eq!(1, reported.len());
+        assert_eq!(Source::Config, reported[0].source);
+        assert_eq!(2, reported[0].count);
+    }
 }
@@ -1,21 +1,35 @@
 //! This module provides utilities for configuration files.
 use crate::Result;
-pub use colors::Colors;
+pub use colors::{Colors, Theme};
 use directories::ProjectDirs;
 pub use icons::Icons;
+pub use loader::{ConfigLoader, Loaded, Source};
 pub use main::Main;
-use mlua::{FromLuaMulti, Lua};
-use std::fs;
+use mlua::{Table, Value};
+pub use overrides::ProjectOverrides;
+pub use plugins::{PluginError, Plugins};
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Write as _};
 use std::path::{Path, PathBuf};
+pub use wasm::{WasmPluginError, WasmPlugins};
 
+mod cache;
+pub mod check;
 mod colors;
 mod icons;
+mod loader;
 mod main;
+mod overrides;
+mod plugins;
+pub mod wasm;
 
 /// The project configuration directory.
 pub struct ConfigDir {
-    /// Utility for finding the project directories.
-    project_dirs: ProjectDirs,
+    /// The directory containing the config files.
+    config: PathBuf,
+    /// The directory for cached data.
+    cache: PathBuf,
 }
 
 impl ConfigDir {
@@ -25,12 +39,38 @@ impl ConfigDir {
     const ORGANIZATION: &str = "";
     /// The project name.
     const APPLICATION: &str = env!("CARGO_PKG_NAME");
+    /// An environment variable the user can set to use another configuration
+    /// directory.
+    pub const ENV_VAR: &str = "FANCY_TREE_CONFIG_DIR";
 
     /// The directory containing the config files.
-    pub fn new() -> Result<Self, &'static str> {
-        ProjectDirs::from(Self::QUALIFIER, Self::ORGANIZATION, Self::APPLICATION)
-            .map(|project_dirs| Self { project_dirs })
-            .ok_or("Missing home directory")
+    ///
+    /// This is the explicit path if there is one, or else the path in the
+    /// `FANCY_TREE_CONFIG_DIR` environment variable, or else the platform's
+    /// configuration directory.
+    pub fn new(path: Option<&Path>) -> Result<Self, &'static str> {
+        let project_dirs =
+            ProjectDirs::from(Self::QUALIFIER, Self::ORGANIZATION, Self::APPLICATION);
+        let path = path.map(Path::to_path_buf).or_else(|| {
+            env::var_os(Self::ENV_VAR)
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from)
+        });
+        match (path, project_dirs) {
+            // NOTE Another configuration directory still uses the user's cache, unless
+            //      there's no home directory to keep it in.
+            (Some(config), project_dirs) => {
+                let cache = project_dirs
+                    .map(|project_dirs| project_dirs.cache_dir().to_path_buf())
+                    .unwrap_or_else(|| config.join("cache"));
+                Ok(Self { config, cache })
+            }
+            (None, Some(project_dirs)) => Ok(Self {
+                config: project_dirs.config_dir().to_path_buf(),
+                cache: project_dirs.cache_dir().to_path_buf(),
+            }),
+            (None, None) => Err("Missing home directory"),
+        }
     }
 
     /// Creates the configuration directory if it doesn't exist.
@@ -40,42 +80,72 @@ impl ConfigDir {
         Ok(())
     }
 
-    /// Loads the main configuration file.
+    /// Checks if none of the configuration files exist, like the first time this
+    /// runs.
+    pub fn is_unconfigured(&self) -> bool {
+        // NOTE If we can't check if a file exists, we'll assume that it does.
+        [self.main_path(), self.icons_path(), self.colors_path()]
+            .iter()
+            .all(|path| !path.try_exists().unwrap_or(true))
+    }
+
+    /// Writes the default configuration files, whose comments explain each value and
+    /// function that can be set. Files that already exist are kept.
+    ///
+    /// Returns the path of each file, and if it was written.
+    pub fn bootstrap(&self) -> Result<Vec<(PathBuf, bool)>> {
+        self.create_dir()?;
+        let files = [
+            (self.main_path(), Main::DEFAULT_MODULE),
+            (self.icons_path(), Icons::DEFAULT_MODULE),
+            (self.colors_path(), Colors::DEFAULT_MODULE),
+        ];
+        let mut written = Vec::with_capacity(files.len());
+        for (path, contents) in files {
+            // NOTE The file is only created if it's missing, so that a file that was
+            //      created in the meantime isn't replaced.
+            match File::create_new(&path) {
+                Ok(mut file) => {
+                    file.write_all(contents.as_bytes())?;
+                    written.push((path, true));
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => written.push((path, false)),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(written)
+    }
+
+    /// Gets the path of the file that records that the first-run hint was shown, so
+    /// that it's only shown once.
     #[inline]
-    pub fn load_main(&self, lua: &Lua) -> mlua::Result<Option<Main>> {
-        self.load_file(lua)
+    pub fn hint_path(&self) -> PathBuf {
+        self.cache_path().join("hint-shown")
     }
 
-    /// Loads the icon configuration file.
+    /// Gets the config directory for the project.
     #[inline]
-    pub fn load_icons(&self, lua: &Lua) -> mlua::Result<Option<Icons>> {
-        self.load_file(lua)
+    pub fn path(&self) -> &Path {
+        &self.config
     }
 
-    /// Loads the colors configuration file.
+    /// Gets the directory for cached data, like compiled configuration files.
     #[inline]
-    pub fn load_colors(&self, lua: &Lua) -> mlua::Result<Option<Colors>> {
-        self.load_file(lua)
+    pub fn cache_path(&self) -> &Path {
+        &self.cache
     }
 
-    /// Loads a `.lua` file from the configuration directory.
-    fn load_file<T>(&self, lua: &Lua) -> mlua::Result<Option<T>>
-    where
-        T: ConfigFile + FromLuaMulti,
-    {
-        let path = self.path().join(T::FILENAME);
-        path.exists()
-            .then(|| {
-                let chunk = lua.load(path);
-                chunk.call::<T>(())
-            })
-            .transpose()
+    /// Gets the directory of plugins. See [`Plugins`].
+    #[inline]
+    pub fn plugins_path(&self) -> PathBuf {
+        self.path().join("plugins")
     }
 
-    /// Gets the config directory for the project.
+    /// Gets the directory of the user's themes, like `themes/gruvbox.lua`. See
+    /// [`Theme`].
     #[inline]
-    pub fn path(&self) -> &Path {
-        self.project_dirs.config_dir()
+    pub fn themes_path(&self) -> PathBuf {
+        self.path().join("themes")
     }
 
     /// Gets the path of a file in the configuration directory from its filename.
@@ -105,6 +175,22 @@ impl ConfigDir {
     }
 }
 
+/// Sets each value of `overrides` in `base`. Tables with keys, like `git`, are
+/// merged, while other values, including lists, replace the value in `base`.
+fn merge(base: &Table, overrides: Table) -> mlua::Result<()> {
+    for pair in overrides.pairs::<Value, Value>() {
+        let (key, value) = pair?;
+        if let (Value::Table(base), Value::Table(value)) = (base.get::<Value>(&key)?, &value)
+            && value.raw_len() == 0
+        {
+            merge(&base, value.clone())?;
+            continue;
+        }
+        base.set(key, value)?;
+    }
+    Ok(())
+}
+
 /// Common behavior for configuration files.
 pub trait ConfigFile {
     /// The filename in the configuration directory.
@@ -112,3 +198,61 @@ pub trait ConfigFile {
     /// The default lua module.
     const DEFAULT_MODULE: &'static str;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Fixture;
+
+    #[test]
+    fn test_explicit_config_dir() {
+        let config_dir = ConfigDir::new(Some(Path::new("/path/to/config"))).unwrap();
+        assert_eq!(Path::new("/path/to/config"), config_dir.path());
+        assert_eq!(
+            Path::new("/path/to/config/config.lua"),
+            config_dir.main_path()
+        );
+        assert_eq!(
+            Path::new("/path/to/config/plugins"),
+            config_dir.plugins_path()
+        );
+    }
+
+    #[test]
+    fn test_bootstrap() {
+        let fixture = Fixture::builder()
+            .file("fancy-tree/icons.lua", "return nil")
+            .build()
+            .unwrap();
+        let config_dir = ConfigDir::new(Some(&fixture.path().join("fancy-tree"))).unwrap();
+        assert!(!config_dir.is_unconfigured());
+
+        let written = config_dir.bootstrap().unwrap();
+        assert_eq!(
+            vec![
+                (config_dir.main_path(), true),
+                (config_dir.icons_path(), false),
+                (config_dir.colors_path(), true),
+            ],
+            written
+        );
+        assert_eq!(
+            Main::DEFAULT_MODULE,
+            fs::read_to_string(config_dir.main_path()).unwrap()
+        );
+        assert_eq!(
+            "return nil",
+            fs::read_to_string(config_dir.icons_path()).unwrap()
+        );
+        assert!(
+            config_dir
+                .bootstrap()
+                .unwrap()
+                .iter()
+                .all(|(_, written)| !written)
+        );
+
+        let empty = ConfigDir::new(Some(&fixture.path().join("empty"))).unwrap();
+        assert!(empty.is_unconfigured());
+    }
+}
@@ -0,0 +1,306 @@
+//! Module for configuration files that projects keep in their own directories.
+use super::merge;
+use crate::diagnostics::{Diagnostics, Source};
+use mlua::{Chunk, HookTriggers, Lua, Table, Value, VmState};
+use std::path::{Path, PathBuf};
+
+/// A project's main configuration, like `.fancytree/config.lua`, whose values
+/// override the user's main configuration.
+///
+/// Project files come from whoever wrote the project, so by default they're
+/// sandboxed: they can only use Lua's pure functions and the `fancytree` API, and
+/// they can only set the values that decide which entries are listed and how they
+/// look.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectOverrides {
+    /// The path to the project's configuration file.
+    path: PathBuf,
+    /// If the file is trusted, so that it isn't sandboxed.
+    trusted: bool,
+}
+
+impl ProjectOverrides {
+    /// The paths that are checked in each directory, in order.
+    const FILENAMES: [&str; 2] = [".fancytree/config.lua", ".fancytree.lua"];
+
+    /// The globals that sandboxed files can use.
+    const SANDBOX_GLOBALS: [&str; 15] = [
+        "assert",
+        "error",
+        "ipairs",
+        "next",
+        "pairs",
+        "pcall",
+        "rawequal",
+        "rawget",
+        "rawlen",
+        "select",
+        "tonumber",
+        "tostring",
+        "type",
+        "xpcall",
+        "fancytree",
+    ];
+
+    /// The libraries that sandboxed files get copies of, so that they can't change
+    /// the functions that trusted files call.
+    const SANDBOX_LIBRARIES: [&str; 4] = ["math", "string", "table", "utf8"];
+
+    /// The keys that sandboxed files can set. Other keys, like ones that run
+    /// commands, load files, or change the whole output, are ignored, including keys
+    /// that are added later.
+    const SANDBOX_KEYS: [&str; 22] = [
+        "charset",
+        "colors",
+        "filelimit",
+        "filelimit_mode",
+        "git",
+        "git_statuses",
+        "icon_set",
+        "icons",
+        "level",
+        "max_lines",
+        "mtime",
+        "mtime_format",
+        "permissions",
+        "prune",
+        "recent",
+        "recent_window",
+        "rule_precedence",
+        "show_git_dir",
+        "size",
+        "skip",
+        "sorting",
+        "warn_size",
+    ];
+
+    /// The most instructions that a sandboxed file can run while it's loaded, so that
+    /// a file that never returns, like one with `while true do end`, can't hang the
+    /// tree.
+    const SANDBOX_INSTRUCTIONS: u32 = 10_000_000;
+
+    /// Finds the first project configuration file in the directories, like the
+    /// tree's root and then the repository's root.
+    pub fn find<I, P>(dirs: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        dirs.into_iter()
+            .flat_map(|dir| Self::FILENAMES.map(|filename| dir.as_ref().join(filename)))
+            .find(|path| path.is_file())
+            .map(|path| Self {
+                path,
+                trusted: false,
+            })
+    }
+
+    /// Trusts the file, so that it isn't sandboxed.
+    #[inline]
+    #[must_use]
+    pub fn trusted(self) -> Self {
+        Self {
+            trusted: true,
+            ..self
+        }
+    }
+
+    /// Gets the path to the project's configuration file.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Checks if the file is trusted.
+    #[inline]
+    pub fn is_trusted(&self) -> bool {
+        self.trusted
+    }
+
+    /// Loads the file's table and merges it over the user's configuration table.
+    ///
+    /// Sandboxed files that set keys other than [`Self::SANDBOX_KEYS`] have those
+    /// keys reported and ignored, so that the rest of the file still applies.
+    pub(super) fn merge_into(&self, lua: &Lua, base: &Table) -> mlua::Result<()> {
+        let chunk = lua.load(self.path.as_path());
+        let overrides = if self.trusted {
+            chunk.call::<Table>(())?
+        } else {
+            self.call_sandboxed(lua, chunk.set_environment(Self::sandbox(lua)?))?
+        };
+        if !self.trusted {
+            let diagnostics = Diagnostics::for_lua(lua);
+            let mut untrusted = Vec::new();
+            for pair in overrides.pairs::<Value, Value>() {
+                let (key, _) = pair?;
+                let name = key.to_string()?;
+                if !Self::SANDBOX_KEYS.contains(&name.as_str()) {
+                    untrusted.push((name, key));
+                }
+            }
+            // NOTE Keys are sorted so that the reports don't depend on the table's order.
+            untrusted.sort_by(|(left, _), (right, _)| left.cmp(right));
+            for (name, key) in untrusted {
+                diagnostics.report(
+                    Source::Config,
+                    format!(
+                        "{}: `{name}` is ignored unless the project configuration is trusted",
+                        self.path.display()
+                    ),
+                );
+                overrides.set(key, Value::Nil)?;
+            }
+        }
+        merge(base, overrides)
+    }
+
+    /// Calls a sandboxed file's chunk, stopping it with an error when it runs more
+    /// than [`Self::SANDBOX_INSTRUCTIONS`].
+    fn call_sandboxed(&self, lua: &Lua, chunk: Chunk) -> mlua::Result<Table> {
+        let message = format!(
+            "{}: the project configuration ran for too long",
+            self.path.display()
+        );
+        let triggers = HookTriggers::new().every_nth_instruction(Self::SANDBOX_INSTRUCTIONS);
+        lua.set_hook(triggers, move |lua, _| {
+            // NOTE Every instruction fails after the limit, so that `pcall` can't
+            //      catch the error and keep going.
+            let message = message.clone();
+            let triggers = HookTriggers::new().every_nth_instruction(1);
+            lua.set_hook(triggers, move |_, _| {
+                Err::<VmState, _>(mlua::Error::runtime(&message))
+            })?;
+            Ok(VmState::Continue)
+        })?;
+        let result = chunk.call::<Table>(());
+        lua.remove_hook();
+        result
+    }
+
+    /// Creates the environment of a sandboxed file.
+    fn sandbox(lua: &Lua) -> mlua::Result<Table> {
+        let globals = lua.globals();
+        let environment = lua.create_table()?;
+        for name in Self::SANDBOX_GLOBALS {
+            environment.set(name, globals.get::<Value>(name)?)?;
+        }
+        for name in Self::SANDBOX_LIBRARIES {
+            // NOTE The application's state doesn't load every library.
+            let Some(library) = globals.get::<Option<Table>>(name)? else {
+                continue;
+            };
+            let copy = lua.create_table()?;
+            for pair in library.pairs::<Value, Value>() {
+                let (key, value) = pair?;
+                copy.set(key, value)?;
+            }
+            environment.set(name, copy)?;
+        }
+        Ok(environment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Fixture;
+    use rstest::rstest;
+
+    #[test]
+    fn test_find() {
+        let fixture = Fixture::builder()
+            .file("a/.fancytree.lua", "return {}")
+            .file("b/.fancytree.lua", "return {}")
+            .file("b/.fancytree/config.lua", "return {}")
+            .dir("c")
+            .build()
+            .unwrap();
+        let dir = |path| fixture.path().join(path);
+
+        let found = ProjectOverrides::find([dir("c"), dir("a")]).unwrap();
+        assert_eq!(dir("a/.fancytree.lua"), found.path());
+        assert!(!found.is_trusted());
+        let found = ProjectOverrides::find([dir("b"), dir("a")]).unwrap();
+        assert_eq!(dir("b/.fancytree/config.lua"), found.path());
+        assert!(ProjectOverrides::find([dir("c")]).is_none());
+    }
+
+    #[test]
+    fn test_merge_into() {
+        let fixture = Fixture::builder()
+            .file(
+                ".fancytree.lua",
+                r#"return { level = 2, git = { renames = false }, sorting = { "name" } }"#,
+            )
+            .build()
+            .unwrap();
+        let lua = Lua::new();
+        let base = lua
+            .load(r#"return { level = 1, prune = true, git = { rename_threshold = 50 }, sorting = { "type", "name" } }"#)
+            .eval::<Table>()
+            .unwrap();
+        let overrides = ProjectOverrides::find([fixture.path()]).unwrap();
+        overrides.merge_into(&lua, &base).unwrap();
+
+        assert_eq!(2, base.get::<usize>("level").unwrap());
+        assert!(base.get::<bool>("prune").unwrap());
+        let git = base.get::<Table>("git").unwrap();
+        assert!(!git.get::<bool>("renames").unwrap());
+        assert_eq!(50, git.get::<usize>("rename_threshold").unwrap());
+        assert_eq!(1, base.get::<Table>("sorting").unwrap().raw_len());
+    }
+
+    #[test]
+    fn test_sandbox() {
+        let fixture = Fixture::builder()
+            .file(
+                ".fancytree.lua",
+                r#"
+                    string.len = function() return 0 end
+                    return { level = os and 1 or ("ab"):len(), annotations = {}, paging = "always", hyperlinks = true }
+                "#,
+            )
+            .file("trusted/.fancytree.lua", "return { level = os and 1 }")
+            .build()
+            .unwrap();
+        let lua = Lua::new();
+        let diagnostics = Diagnostics::default();
+        diagnostics.attach(&lua);
+
+        let base = lua.create_table().unwrap();
+        let overrides = ProjectOverrides::find([fixture.path()]).unwrap();
+        overrides.merge_into(&lua, &base).unwrap();
+        assert_eq!(2, base.get::<usize>("level").unwrap());
+        for key in ["annotations", "paging", "hyperlinks"] {
+            assert!(base.get::<Value>(key).unwrap().is_nil(), "{key}");
+        }
+        assert_eq!(3, diagnostics.take().len());
+        let len = lua.load(r#"return string.len("ab")"#).eval::<usize>();
+        assert_eq!(2, len.unwrap());
+
+        let base = lua.create_table().unwrap();
+        let overrides = ProjectOverrides::find([fixture.path().join("trusted")])
+            .unwrap()
+            .trusted();
+        overrides.merge_into(&lua, &base).unwrap();
+        assert_eq!(1, base.get::<usize>("level").unwrap());
+    }
+
+    #[rstest]
+    #[case("while true do end")]
+    #[case("while true do pcall(function() while true do end end) end")]
+    fn test_sandbox_instruction_limit(#[case] contents: &str) {
+        let fixture = Fixture::builder()
+            .file(".fancytree.lua", contents)
+            .build()
+            .unwrap();
+        let lua = Lua::new();
+
+        let base = lua.create_table().unwrap();
+        let overrides = ProjectOverrides::find([fixture.path()]).unwrap();
+        let error = overrides.merge_into(&lua, &base).unwrap_err();
+        assert!(error.to_string().contains("ran for too long"), "{error}");
+        let sum = lua.load("local sum = 0 for i = 1, 100000 do sum = sum + i end return sum");
+        assert_eq!(5_000_050_000_i64, sum.eval::<i64>().unwrap());
+    }
+}
@@ -0,0 +1,410 @@
+//! Module for plugins, which are Lua files that register hooks.
+use crate::color::Style;
+use crate::lua::{api, interop};
+use crate::tree::Entry;
+use crate::vcs::status::FileStatus;
+use mlua::{FromLua, FromLuaMulti, IntoLuaMulti, Lua};
+use std::cell::OnceCell;
+use std::fmt::{self, Display};
+use std::fs;
+use std::path::Path;
+
+/// The plugins in the `plugins` directory of the configuration directory.
+///
+/// Each `.lua` file in the directory is a plugin, and plugins are loaded in order of
+/// their file names. A plugin registers its hooks by calling `fancytree.register`
+/// with a table of functions:
+///
+/// - `on_entry(path, attributes, skip)` returns `true` to hide an entry, `false` to
+///   show it, or `nil` to keep the decision.
+/// - `on_icon(path, attributes, icon)` returns the icon for an entry.
+/// - `on_color(path, attributes, color)` returns the color of an entry's icon.
+/// - `on_summary(summary)` returns the summary after the tree.
+///
+/// Hooks receive the value from the configuration and the plugins before them, and
+/// returning `nil` keeps that value. A plugin that fails to load, or whose hook
+/// fails, is disabled without affecting the other plugins.
+#[derive(Debug, Default)]
+pub struct Plugins {
+    /// The plugins that were loaded, in order.
+    plugins: Vec<Plugin>,
+    /// The plugins that couldn't be loaded.
+    load_errors: Vec<PluginError>,
+    /// The Lua state that the plugins are loaded in.
+    scope: interop::Scope,
+}
+
+impl Plugins {
+    /// The names of the hooks that plugins can register.
+    const HOOK_NAMES: [&str; 4] = ["on_entry", "on_icon", "on_color", "on_summary"];
+
+    /// Loads the plugins in a directory. A directory that doesn't exist has no
+    /// plugins.
+    ///
+    /// `load` loads a file into a function, so that callers can cache it.
+    pub fn load<F>(lua: &Lua, dir: &Path, load: F) -> Self
+    where
+        F: Fn(&Path) -> mlua::Result<mlua::Function>,
+    {
+        let mut paths = fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "lua"))
+                    .filter(|path| path.is_file())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        // NOTE Sorting by bytes makes the order the same on every platform.
+        paths.sort_by(|left, right| {
+            left.as_os_str()
+                .as_encoded_bytes()
+                .cmp(right.as_os_str().as_encoded_bytes())
+        });
+
+        let mut plugins = Self {
+            scope: interop::Scope::new(lua),
+            ..Self::default()
+        };
+        for path in paths {
+            let name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            match Self::load_plugin(lua, &path, &load) {
+                Ok(hooks) => plugins.plugins.push(Plugin {
+                    name,
+                    hooks,
+                    error: OnceCell::new(),
+                }),
+                Err(error) => plugins.load_errors.push(PluginError { name, error }),
+            }
+        }
+        plugins
+    }
+
+    /// Runs a plugin, and combines the hooks that it registered.
+    fn load_plugin<F>(lua: &Lua, path: &Path, load: &F) -> mlua::Result<Hooks>
+    where
+        F: Fn(&Path) -> mlua::Result<mlua::Function>,
+    {
+        let registered = lua.create_table()?;
+        lua.set_named_registry_value(api::REGISTERED_HOOKS, &registered)?;
+        let result = load(path).and_then(|f| f.call::<()>(()));
+        lua.unset_named_registry_value(api::REGISTERED_HOOKS)?;
+        result?;
+
+        let mut hooks = Hooks::default();
+        for table in registered.sequence_values::<mlua::Table>() {
+            hooks.extend(Hooks::from_lua(mlua::Value::Table(table?), lua)?);
+        }
+        Ok(hooks)
+    }
+
+    /// Checks if there are no plugins.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Gets the plugins that failed to load or whose hooks failed.
+    pub fn errors(&self) -> Vec<PluginError> {
+        let hook_errors = self.plugins.iter().filter_map(|plugin| {
+            plugin.error.get().map(|error| PluginError {
+                name: plugin.name.clone(),
+                error: error.clone(),
+            })
+        });
+        self.load_errors
+            .iter()
+            .cloned()
+            .chain(hook_errors)
+            .collect()
+    }
+
+    /// Decides if an entry should be hidden, starting from `skip`.
+    pub fn should_skip<P, S>(&self, entry: &Entry<P>, status: S, skip: bool) -> bool
+    where
+        P: AsRef<Path>,
+        S: FnOnce() -> Option<FileStatus>,
+    {
+        self.fold_entry(entry, status, skip, |hooks| hooks.on_entry.as_ref())
+    }
+
+    /// Gets the icon for an entry, starting from `icon`.
+    pub fn icon<P, S>(&self, entry: &Entry<P>, status: S, icon: String) -> String
+    where
+        P: AsRef<Path>,
+        S: FnOnce() -> Option<FileStatus>,
+    {
+        self.fold_entry(entry, status, icon, |hooks| hooks.on_icon.as_ref())
+    }
+
+    /// Gets the style of an entry's icon, starting from `style`.
+    pub fn color<P, S>(&self, entry: &Entry<P>, status: S, style: Option<Style>) -> Option<Style>
+    where
+        P: AsRef<Path>,
+        S: FnOnce() -> Option<FileStatus>,
+    {
+        // NOTE Unlike other hooks, returning nil doesn't keep the color, since nil
+        //      is the color for uncolored entries.
+        fn hook(hooks: &Hooks) -> Option<&mlua::Function> {
+            hooks.on_color.as_ref()
+        }
+        if !self.has_hook(hook) {
+            return style;
+        }
+        let path = entry.path();
+        let attributes = interop::FileAttributes::from(entry);
+        self.scope
+            .with_status(attributes, status, |attributes| {
+                let style = self.plugins.iter().fold(style, |style, plugin| {
+                    plugin
+                        .call::<Option<Style>, _>(hook, (path, attributes.clone(), style))
+                        .unwrap_or(style)
+                });
+                Some(style)
+            })
+            .unwrap_or(style)
+    }
+
+    /// Gets the summary after the tree, starting from `summary`.
+    pub fn summary(&self, summary: String) -> String {
+        self.plugins.iter().fold(summary, |summary, plugin| {
+            plugin
+                .call::<Option<String>, _>(|hooks| hooks.on_summary.as_ref(), summary.as_str())
+                .flatten()
+                .unwrap_or(summary)
+        })
+    }
+
+    /// Passes a value for an entry through each plugin's hook.
+    fn fold_entry<P, S, T, H>(&self, entry: &Entry<P>, status: S, value: T, hook: H) -> T
+    where
+        P: AsRef<Path>,
+        S: FnOnce() -> Option<FileStatus>,
+        T: FromLua + mlua::IntoLua + Clone,
+        H: Fn(&Hooks) -> Option<&mlua::Function>,
+    {
+        // HACK Avoids reading attributes for entries when no plugin uses them.
+        if !self.has_hook(&hook) {
+            return value;
+        }
+        let path = entry.path();
+        let attributes = interop::FileAttributes::from(entry);
+        // NOTE The value is kept in case the status's scope can't be created.
+        let fallback = value.clone();
+        self.scope
+            .with_status(attributes, status, |attributes| {
+                let value = self.plugins.iter().fold(value, |value, plugin| {
+                    plugin
+                        .call::<Option<T>, _>(&hook, (path, attributes.clone(), value.clone()))
+                        .flatten()
+                        .unwrap_or(value)
+                });
+                Some(value)
+            })
+            .unwrap_or(fallback)
+    }
+
+    /// Checks if any working plugin has a hook.
+    fn has_hook<H>(&self, hook: H) -> bool
+    where
+        H: Fn(&Hooks) -> Option<&mlua::Function>,
+    {
+        self.plugins
+            .iter()
+            .any(|plugin| plugin.error.get().is_none() && hook(&plugin.hooks).is_some())
+    }
+}
+
+/// A loaded plugin.
+#[derive(Debug)]
+struct Plugin {
+    /// The file name of the plugin without the extension.
+    name: String,
+    /// The hooks that the plugin registered.
+    hooks: Hooks,
+    /// The first error from a hook, which disables the plugin.
+    error: OnceCell<mlua::Error>,
+}
+
+impl Plugin {
+    /// Calls one of the plugin's hooks. Returns `None` if the plugin doesn't have
+    /// the hook, is disabled, or the hook fails.
+    fn call<R, H>(&self, hook: H, args: impl IntoLuaMulti) -> Option<R>
+    where
+        R: FromLuaMulti,
+        H: Fn(&Hooks) -> Option<&mlua::Function>,
+    {
+        if self.error.get().is_some() {
+            return None;
+        }
+        let f = hook(&self.hooks)?;
+        f.call::<R>(args)
+            .inspect_err(|error| {
+                let _ = self.error.set(error.clone());
+            })
+            .ok()
+    }
+}
+
+/// The hooks that a plugin registered.
+#[derive(Debug, Default)]
+struct Hooks {
+    on_entry: Option<mlua::Function>,
+    on_icon: Option<mlua::Function>,
+    on_color: Option<mlua::Function>,
+    on_summary: Option<mlua::Function>,
+}
+
+impl Hooks {
+    /// Adds hooks from a later registration, which replace the same hooks.
+    fn extend(&mut self, other: Self) {
+        self.on_entry = other.on_entry.or(self.on_entry.take());
+        self.on_icon = other.on_icon.or(self.on_icon.take());
+        self.on_color = other.on_color.or(self.on_color.take());
+        self.on_summary = other.on_summary.or(self.on_summary.take());
+    }
+}
+
+impl FromLua for Hooks {
+    fn from_lua(value: mlua::Value, lua: &Lua) -> mlua::Result<Self> {
+        let table = mlua::Table::from_lua(value, lua)?;
+        for key in table.pairs::<String, mlua::Value>() {
+            let (key, _) = key?;
+            if !Plugins::HOOK_NAMES.contains(&key.as_str()) {
+                return Err(mlua::Error::runtime(format!(
+                    "unknown hook {key:?}, expected one of {}",
+                    Plugins::HOOK_NAMES.join(", ")
+                )));
+            }
+        }
+        Ok(Self {
+            on_entry: table.get("on_entry")?,
+            on_icon: table.get("on_icon")?,
+            on_color: table.get("on_color")?,
+            on_summary: table.get("on_summary")?,
+        })
+    }
+}
+
+/// A plugin that failed to load, or whose hook failed.
+#[derive(Debug, Clone)]
+pub struct PluginError {
+    /// The file name of the plugin without the extension.
+    pub name: String,
+    /// The error.
+    pub error: mlua::Error,
+}
+
+impl Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "plugin {:?} was disabled: {}", self.name, self.error)
+    }
+}
+
+impl std::error::Error for PluginError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::lua;
+    use owo_colors::AnsiColors;
+    use tempfile::TempDir;
+
+    /// Loads plugins from files, using a Lua state with the API.
+    fn load(plugins: &[(&str, &str)]) -> (lua::state::State<'static>, TempDir, Plugins) {
+        let state = lua::state::Builder::new().build().unwrap();
+        let dir = TempDir::with_prefix("fancy-tree-").unwrap();
+        for (name, source) in plugins {
+            fs::write(dir.path().join(name), source).unwrap();
+        }
+        let lua = state.to_inner();
+        let plugins = Plugins::load(lua, dir.path(), |path| lua.load(path).into_function());
+        (state, dir, plugins)
+    }
+
+    #[test]
+    fn test_order() {
+        let (_state, dir, plugins) = load(&[
+            (
+                "b.lua",
+                r#"fancytree.register({ on_icon = function(_, _, icon) return icon .. "b" end })"#,
+            ),
+            (
+                "a.lua",
+                r#"fancytree.register({ on_icon = function(_, _, icon) return icon .. "a" end })"#,
+            ),
+            ("c.txt", "error('not a plugin')"),
+        ]);
+        let entry = Entry::new(dir.path()).unwrap();
+
+        assert!(plugins.errors().is_empty());
+        assert_eq!("-ab", plugins.icon(&entry, || None, String::from("-")));
+    }
+
+    #[test]
+    fn test_hooks() {
+        let (_state, dir, plugins) = load(&[(
+            "plugin.lua",
+            r#"
+            fancytree.register({
+              on_entry = function(path, attributes, skip)
+                return attributes:is_dir()
+              end,
+              on_color = function() return "red" end,
+            })
+            fancytree.register({
+              on_summary = function(summary) return summary .. "!" end,
+            })
+            "#,
+        )]);
+        let entry = Entry::new(dir.path()).unwrap();
+
+        assert!(plugins.should_skip(&entry, || None, false));
+        assert_eq!(
+            Some(Style::from(Color::Ansi(AnsiColors::Red))),
+            plugins.color(&entry, || None, None)
+        );
+        assert_eq!("1 file!", plugins.summary(String::from("1 file")));
+    }
+
+    #[test]
+    fn test_isolation() {
+        let (_state, dir, plugins) = load(&[
+            ("a.lua", "error('failed to load')"),
+            (
+                "b.lua",
+                r#"fancytree.register({ on_icon = function() error("failed") end })"#,
+            ),
+            (
+                "c.lua",
+                r#"fancytree.register({ on_icon = function(_, _, icon) return icon .. "c" end })"#,
+            ),
+            (
+                "d.lua",
+                r#"fancytree.register({ on_click = function() end })"#,
+            ),
+        ]);
+        let entry = Entry::new(dir.path()).unwrap();
+
+        assert_eq!("-c", plugins.icon(&entry, || None, String::from("-")));
+        assert_eq!("-c", plugins.icon(&entry, || None, String::from("-")));
+        let names = plugins
+            .errors()
+            .into_iter()
+            .map(|error| error.name)
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["a", "d", "b"], names);
+    }
+
+    #[test]
+    fn test_register_outside_plugin() {
+        let state = lua::state::Builder::new().build().unwrap();
+        let result = state.to_inner().load("fancytree.register({})").exec();
+        assert!(result.is_err());
+    }
+}
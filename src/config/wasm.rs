@@ -0,0 +1,551 @@
+//! Module for WASM plugins, which decorate entries from sandboxed modules.
+use crate::color::{self, Color};
+use crate::tree::Entry;
+use crate::tree::export::EntryType;
+use crate::vcs::status::FileStatus;
+use serde::{Deserialize, Serialize};
+use std::cell::{OnceCell, RefCell};
+use std::fmt::{self, Display};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The WASM plugins listed in the `wasm_plugins` option of the main config.
+///
+/// A plugin is a WASM module that exports:
+///
+/// - `memory`, its linear memory.
+/// - `alloc(len: i32) -> i32`, which reserves `len` bytes and returns their offset.
+/// - `decorate(ptr: i32, len: i32) -> i64`, which receives an entry as JSON, like
+///   `{"path": "./src/main.rs", "type": "file", "size": 120, "status": null}`, and
+///   returns the offset of a JSON style in the upper 32 bits and its length in the
+///   lower 32 bits, or `0` to leave the entry alone. A style is an object like
+///   `{"icon": "R", "color": "#dea584"}`, where both keys are optional.
+/// - Optionally, `dealloc(ptr: i32, len: i32)`, which frees memory from `alloc` and
+///   `decorate` after it's read.
+///
+/// The entry's `path` is the same path that Lua plugins receive: the tree's root, as
+/// it was given, joined with the entry's path inside it. It's absolute when the root
+/// is, so plugins should match the end of it, like its file name or extension.
+///
+/// Plugins are sandboxed: they can't import anything, so they can only compute a
+/// style from the entry they're given, and each call has limited fuel and memory.
+/// Later plugins override the styles of earlier ones. A plugin that fails to load,
+/// or whose call fails, is disabled without affecting the other plugins.
+#[derive(Debug, Default)]
+pub struct WasmPlugins {
+    /// The plugins that were loaded, in order.
+    plugins: Vec<WasmPlugin>,
+    /// The plugins that couldn't be loaded.
+    load_errors: Vec<(PathBuf, Error)>,
+    /// The last entry that was decorated, since the icon and color are requested
+    /// separately.
+    last: RefCell<Option<(PathBuf, Style)>>,
+}
+
+impl WasmPlugins {
+    /// Loads the plugins at the paths, in order.
+    pub fn load<I>(paths: I) -> Self
+    where
+        I: IntoIterator<Item = PathBuf>,
+    {
+        let mut plugins = Self::default();
+        for path in paths {
+            match runtime::Runtime::load(&path) {
+                Ok(runtime) => plugins.plugins.push(WasmPlugin {
+                    path,
+                    runtime,
+                    error: OnceCell::new(),
+                }),
+                Err(error) => plugins.load_errors.push((path, error)),
+            }
+        }
+        plugins
+    }
+
+    /// Checks if there are no plugins.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Gets the plugins that failed to load or whose calls failed.
+    pub fn errors(&self) -> Vec<WasmPluginError<'_>> {
+        let call_errors = self.plugins.iter().filter_map(|plugin| {
+            plugin.error.get().map(|error| WasmPluginError {
+                path: &plugin.path,
+                error,
+            })
+        });
+        self.load_errors
+            .iter()
+            .map(|(path, error)| WasmPluginError { path, error })
+            .chain(call_errors)
+            .collect()
+    }
+
+    /// Gets the icon for an entry, starting from `icon`.
+    pub fn icon<P, S>(&self, entry: &Entry<P>, status: S, icon: String) -> String
+    where
+        P: AsRef<Path>,
+        S: FnOnce() -> Option<FileStatus>,
+    {
+        self.decorate(entry, status).icon.unwrap_or(icon)
+    }
+
+    /// Gets the style of an entry's icon, starting from `style`. Plugins only set
+    /// the foreground color, so the style's other attributes are kept.
+    pub fn color<P, S>(
+        &self,
+        entry: &Entry<P>,
+        status: S,
+        style: Option<color::Style>,
+    ) -> Option<color::Style>
+    where
+        P: AsRef<Path>,
+        S: FnOnce() -> Option<FileStatus>,
+    {
+        match self.decorate(entry, status).color {
+            Some(color) => Some(style.unwrap_or_default().fg(color)),
+            None => style,
+        }
+    }
+
+    /// Combines the styles from each plugin for an entry.
+    fn decorate<P, S>(&self, entry: &Entry<P>, status: S) -> Style
+    where
+        P: AsRef<Path>,
+        S: FnOnce() -> Option<FileStatus>,
+    {
+        if self.is_empty() {
+            return Style::default();
+        }
+        let path = entry.path();
+        if let Some((ref last_path, ref style)) = *self.last.borrow()
+            && last_path == path
+        {
+            return style.clone();
+        }
+
+        let input = Input {
+            path: path.to_string_lossy().into_owned(),
+            entry_type: entry.attributes().into(),
+            size: entry.attributes().file().map(|file| file.size()),
+            status: status(),
+        };
+        let input = serde_json::to_vec(&input).expect("An entry should serialize to JSON");
+        let style = self
+            .plugins
+            .iter()
+            .filter_map(|plugin| plugin.call(&input))
+            .fold(Style::default(), |style, later| Style {
+                icon: later.icon.or(style.icon),
+                color: later.color.or(style.color),
+            });
+        *self.last.borrow_mut() = Some((path.to_path_buf(), style.clone()));
+        style
+    }
+}
+
+/// A loaded WASM plugin.
+#[derive(Debug)]
+struct WasmPlugin {
+    /// The path the plugin was loaded from.
+    path: PathBuf,
+    /// The instantiated module.
+    runtime: runtime::Runtime,
+    /// The first error from a call, which disables the plugin.
+    error: OnceCell<Error>,
+}
+
+impl WasmPlugin {
+    /// Calls the plugin's `decorate` function. Returns `None` if the plugin is
+    /// disabled, returned no style, or failed.
+    fn call(&self, input: &[u8]) -> Option<Style> {
+        if self.error.get().is_some() {
+            return None;
+        }
+        self.runtime
+            .decorate(input)
+            .and_then(|output| output.map(|output| Style::from_json(&output)).transpose())
+            .unwrap_or_else(|error| {
+                let _ = self.error.set(error);
+                None
+            })
+    }
+}
+
+/// The entry that's passed to a plugin.
+#[derive(Debug, Serialize)]
+struct Input {
+    /// The path of the entry.
+    path: String,
+    /// The type of the entry.
+    #[serde(rename = "type")]
+    entry_type: EntryType,
+    /// The size of a file in bytes.
+    size: Option<u64>,
+    /// The version control status of the entry.
+    status: Option<FileStatus>,
+}
+
+/// The style that a plugin returns for an entry.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Style {
+    /// The icon that replaces the entry's icon.
+    icon: Option<String>,
+    /// The color that replaces the color of the entry's icon.
+    color: Option<Color>,
+}
+
+impl Style {
+    /// Parses a style from the JSON returned by a plugin.
+    fn from_json(json: &[u8]) -> Result<Self, Error> {
+        /// The style as it's written in JSON.
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Raw {
+            icon: Option<String>,
+            color: Option<String>,
+        }
+
+        let raw: Raw = serde_json::from_slice(json).map_err(Error::Style)?;
+        let color = raw
+            .color
+            .map(|color| color.parse().map_err(|_| Error::Color(color)))
+            .transpose()?;
+        Ok(Self {
+            icon: raw.icon,
+            color,
+        })
+    }
+}
+
+/// An error from a WASM plugin.
+#[derive(Debug)]
+pub enum Error {
+    /// This build doesn't include the `wasm` feature.
+    Unsupported,
+    /// The module couldn't be read.
+    Io(io::Error),
+    /// The module is invalid, couldn't be instantiated, or trapped.
+    #[cfg(feature = "wasm")]
+    Wasm(wasmi::Error),
+    /// The module doesn't export something in the plugin interface.
+    MissingExport(&'static str),
+    /// The style returned by `decorate` is out of bounds.
+    OutOfBounds,
+    /// The style returned by `decorate` isn't valid JSON.
+    Style(serde_json::Error),
+    /// The style returned by `decorate` has an invalid color.
+    Color(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported => write!(f, "this build doesn't support WASM plugins"),
+            Self::Io(error) => write!(f, "couldn't read the module: {error}"),
+            #[cfg(feature = "wasm")]
+            Self::Wasm(error) => write!(f, "{error}"),
+            Self::MissingExport(name) => write!(f, "the module doesn't export {name:?}"),
+            Self::OutOfBounds => write!(f, "the style is outside of the module's memory"),
+            Self::Style(error) => write!(f, "invalid style: {error}"),
+            Self::Color(color) => write!(f, "invalid color {color:?}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(feature = "wasm")]
+impl From<wasmi::Error> for Error {
+    #[inline]
+    fn from(error: wasmi::Error) -> Self {
+        Self::Wasm(error)
+    }
+}
+
+/// A plugin that was disabled because of an error.
+#[derive(Debug)]
+pub struct WasmPluginError<'a> {
+    /// The path of the plugin.
+    pub path: &'a Path,
+    /// The error that disabled the plugin.
+    pub error: &'a Error,
+}
+
+impl Display for WasmPluginError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "WASM plugin {:?} was disabled: {}",
+            self.path.display(),
+            self.error
+        )
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod runtime {
+    //! Runs plugins with the [`wasmi`] interpreter.
+    use super::Error;
+    use std::cell::RefCell;
+    use std::fmt;
+    use std::fs;
+    use std::path::Path;
+    use wasmi::{
+        Config, Engine, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, TypedFunc,
+    };
+
+    /// The fuel for instantiating a plugin and for each call, which stops plugins
+    /// that never return.
+    const FUEL: u64 = 10_000_000;
+
+    /// The most memory that a plugin can use, in bytes.
+    const MEMORY_LIMIT: usize = 16 * 1024 * 1024;
+
+    /// An instantiated plugin.
+    pub(super) struct Runtime {
+        store: RefCell<Store<StoreLimits>>,
+        memory: Memory,
+        alloc: TypedFunc<i32, i32>,
+        decorate: TypedFunc<(i32, i32), i64>,
+        dealloc: Option<TypedFunc<(i32, i32), ()>>,
+    }
+
+    impl Runtime {
+        /// Reads and instantiates a plugin.
+        pub(super) fn load(path: &Path) -> Result<Self, Error> {
+            let wasm = fs::read(path).map_err(Error::Io)?;
+            let mut config = Config::default();
+            config.consume_fuel(true);
+            let engine = Engine::new(&config);
+            let module = Module::new(&engine, &wasm)?;
+
+            let limits = StoreLimitsBuilder::new()
+                .memory_size(MEMORY_LIMIT)
+                .instances(1)
+                .build();
+            let mut store = Store::new(&engine, limits);
+            store.limiter(|limits| limits);
+            store.set_fuel(FUEL).map_err(wasmi::Error::from)?;
+            // NOTE Nothing is linked, so modules that import anything fail to load.
+            let linker = Linker::<StoreLimits>::new(&engine);
+            let instance = linker.instantiate(&mut store, &module)?.start(&mut store)?;
+
+            let memory = instance
+                .get_memory(&store, "memory")
+                .ok_or(Error::MissingExport("memory"))?;
+            let alloc = instance
+                .get_typed_func(&store, "alloc")
+                .map_err(|_| Error::MissingExport("alloc"))?;
+            let decorate = instance
+                .get_typed_func(&store, "decorate")
+                .map_err(|_| Error::MissingExport("decorate"))?;
+            let dealloc = instance.get_typed_func(&store, "dealloc").ok();
+            Ok(Self {
+                store: RefCell::new(store),
+                memory,
+                alloc,
+                decorate,
+                dealloc,
+            })
+        }
+
+        /// Passes the JSON for an entry to `decorate`, and returns the JSON for the
+        /// style, if any.
+        pub(super) fn decorate(&self, input: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+            let mut store = self.store.borrow_mut();
+            store.set_fuel(FUEL).map_err(wasmi::Error::from)?;
+            let len = i32::try_from(input.len()).map_err(|_| Error::OutOfBounds)?;
+            let ptr = self.alloc.call(&mut *store, len)?;
+            self.memory
+                .write(&mut *store, offset(ptr)?, input)
+                .map_err(wasmi::Error::from)?;
+            let packed = self.decorate.call(&mut *store, (ptr, len))?;
+            self.free(&mut store, ptr, len)?;
+            if packed == 0 {
+                return Ok(None);
+            }
+
+            // NOTE The offset and length are unsigned halves of the result.
+            let packed = packed as u64;
+            let (out_ptr, out_len) = ((packed >> 32) as u32, packed as u32);
+            let mut output = vec![0; out_len as usize];
+            self.memory
+                .read(&*store, out_ptr as usize, &mut output)
+                .map_err(|_| Error::OutOfBounds)?;
+            self.free(&mut store, out_ptr as i32, out_len as i32)?;
+            Ok(Some(output))
+        }
+
+        /// Frees memory with `dealloc`, if the plugin exports it.
+        fn free(&self, store: &mut Store<StoreLimits>, ptr: i32, len: i32) -> Result<(), Error> {
+            match self.dealloc {
+                Some(ref dealloc) => Ok(dealloc.call(store, (ptr, len))?),
+                None => Ok(()),
+            }
+        }
+    }
+
+    impl fmt::Debug for Runtime {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Runtime").finish_non_exhaustive()
+        }
+    }
+
+    /// Converts an offset from a plugin into an index into its memory.
+    fn offset(ptr: i32) -> Result<usize, Error> {
+        usize::try_from(ptr).map_err(|_| Error::OutOfBounds)
+    }
+}
+
+#[cfg(not(feature = "wasm"))]
+mod runtime {
+    //! Rejects plugins, since this build can't run them.
+    use super::Error;
+    use std::path::Path;
+
+    /// A plugin, which can't be created without the `wasm` feature.
+    #[derive(Debug)]
+    pub(super) enum Runtime {}
+
+    impl Runtime {
+        /// Fails, since this build can't run plugins.
+        pub(super) fn load(_path: &Path) -> Result<Self, Error> {
+            Err(Error::Unsupported)
+        }
+
+        /// Never called, since a plugin can't be created.
+        pub(super) fn decorate(&self, _input: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+            match *self {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(r#"{"icon": "R"}"#, Some("R"), None)]
+    #[case(
+        r##"{"icon": null, "color": "#ff0080"}"##,
+        None,
+        Some(Color::Rgb(255, 0, 128))
+    )]
+    #[case("{}", None, None)]
+    fn test_style_from_json(
+        #[case] json: &str,
+        #[case] icon: Option<&str>,
+        #[case] color: Option<Color>,
+    ) {
+        let expected = Style {
+            icon: icon.map(String::from),
+            color,
+        };
+        assert_eq!(expected, Style::from_json(json.as_bytes()).unwrap());
+    }
+
+    #[rstest]
+    #[case(r#"{"icon": "R", "label": "x"}"#)]
+    #[case(r#"{"color": "pink"}"#)]
+    #[case("not json")]
+    fn test_style_from_json_err(#[case] json: &str) {
+        assert!(Style::from_json(json.as_bytes()).is_err());
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    #[test]
+    fn test_unsupported() {
+        let plugins = WasmPlugins::load([PathBuf::from("plugin.wasm")]);
+        assert!(plugins.is_empty());
+        let errors = plugins.errors();
+        assert_eq!(1, errors.len());
+        assert!(matches!(errors[0].error, Error::Unsupported));
+    }
+
+    #[cfg(feature = "wasm")]
+    mod wasm {
+        use super::*;
+        use crate::testing::Fixture;
+
+        /// A bump allocator, which plugins in these tests share.
+        const ALLOC: &str = r#"
+            (memory (export "memory") 1)
+            (global $next (mut i32) (i32.const 1024))
+            (func (export "alloc") (param $len i32) (result i32)
+              (local $ptr i32)
+              (local.set $ptr (global.get $next))
+              (global.set $next (i32.add (global.get $next) (local.get $len)))
+              (local.get $ptr))
+        "#;
+
+        /// Creates a plugin whose `decorate` has `body`.
+        fn plugin(data: &str, body: &str) -> Vec<u8> {
+            let source = format!(
+                r#"(module {ALLOC}
+                  (data (i32.const 0) "{data}")
+                  (func (export "decorate") (param i32 i32) (result i64) {body}))"#
+            );
+            wat::parse_str(source).unwrap()
+        }
+
+        /// Returns the style at offset 0, which is `len` bytes long.
+        fn constant(style: &str) -> Vec<u8> {
+            let escaped = style.replace('"', "\\\"");
+            plugin(&escaped, &format!("(i64.const {})", style.len()))
+        }
+
+        #[test]
+        fn test_decorate() {
+            let fixture = Fixture::builder()
+                .file("main.rs", "")
+                .file("a.wasm", constant(r#"{"icon": "A", "color": "red"}"#))
+                .file("b.wasm", constant(r#"{"icon": "B"}"#))
+                .file("none.wasm", plugin("", "(i64.const 0)"))
+                .build()
+                .unwrap();
+            let paths = ["a.wasm", "b.wasm", "none.wasm"].map(|name| fixture.path().join(name));
+            let plugins = WasmPlugins::load(paths);
+            assert!(plugins.errors().is_empty());
+
+            let entry = Entry::new(fixture.path().join("main.rs")).unwrap();
+            assert_eq!("B", plugins.icon(&entry, || None, String::from("?")));
+            assert_eq!(
+                Some(color::Style::from(Color::Ansi(owo_colors::AnsiColors::Red))),
+                plugins.color(&entry, || None, None)
+            );
+        }
+
+        #[test]
+        fn test_isolation() {
+            let fixture = Fixture::builder()
+                .file(
+                    "loop.wasm",
+                    plugin("", "(loop $forever (br $forever)) (i64.const 0)"),
+                )
+                .file(
+                    "import.wasm",
+                    wat::parse_str(r#"(module (import "env" "read" (func)))"#).unwrap(),
+                )
+                .file("ok.wasm", constant(r#"{"icon": "O"}"#))
+                .file("main.rs", "")
+                .build()
+                .unwrap();
+            let paths =
+                ["loop.wasm", "import.wasm", "ok.wasm"].map(|name| fixture.path().join(name));
+            let plugins = WasmPlugins::load(paths);
+
+            let entry = Entry::new(fixture.path().join("main.rs")).unwrap();
+            assert_eq!("O", plugins.icon(&entry, || None, String::from("?")));
+            let errors = plugins
+                .errors()
+                .iter()
+                .map(|error| error.path.file_name().unwrap().to_owned())
+                .collect::<Vec<_>>();
+            assert_eq!(vec!["import.wasm", "loop.wasm"], errors);
+        }
+    }
+}
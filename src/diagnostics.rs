@@ -0,0 +1,215 @@
+//! Module for collecting errors that don't stop the tree from being written.
+use mlua::{FromLuaMulti, IntoLuaMulti, Lua};
+use std::cell::RefCell;
+use std::fmt::{self, Display};
+use std::rc::Rc;
+
+/// Errors that were reported while the tree was written, like a config function
+/// that failed. Instead of panicking, the default value is used, and the errors are
+/// written after the tree.
+///
+/// Clones share the same errors, so the configurations that are loaded from the
+/// same Lua state report to the same place.
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics(Rc<RefCell<Vec<Diagnostic>>>);
+
+/// An error that was reported, and how many times it was reported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Where the error came from.
+    pub source: Source,
+    /// The error's message.
+    pub message: String,
+    /// How many times the same error was reported, like for every entry in the tree.
+    pub count: usize,
+}
+
+/// Where an error came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// The main configuration.
+    Config,
+    /// The icon configuration.
+    Icons,
+    /// The color configuration.
+    Colors,
+    /// Version control.
+    Vcs,
+    /// Reading the file system.
+    Io,
+}
+
+impl Diagnostics {
+    /// Gets the diagnostics of a Lua state, so that values converted from it can
+    /// report errors. A Lua state without diagnostics gets new ones, which aren't
+    /// shared.
+    pub fn for_lua(lua: &Lua) -> Self {
+        lua.app_data_ref::<Self>()
+            .map(|diagnostics| diagnostics.clone())
+            .unwrap_or_default()
+    }
+
+    /// Makes the values that are converted from a Lua state report to these
+    /// diagnostics.
+    pub fn attach(&self, lua: &Lua) {
+        lua.set_app_data(self.clone());
+    }
+
+    /// Reports an error. Errors that were already reported are counted instead of
+    /// added again.
+    pub fn report<E>(&self, source: Source, error: E)
+    where
+        E: Display,
+    {
+        let message = error.to_string();
+        let mut diagnostics = self.0.borrow_mut();
+        match diagnostics
+            .iter_mut()
+            .find(|diagnostic| diagnostic.source == source && diagnostic.message == message)
+        {
+            Some(diagnostic) => diagnostic.count += 1,
+            None => diagnostics.push(Diagnostic {
+                source,
+                message,
+                count: 1,
+            }),
+        }
+    }
+
+    /// Gets the value of a result, reporting the error if it failed.
+    pub fn check<T, E>(&self, source: Source, result: Result<T, E>) -> Option<T>
+    where
+        E: Display,
+    {
+        result.map_err(|e| self.report(source, e)).ok()
+    }
+
+    /// Calls a configuration function, reporting the error if it failed. The error
+    /// says which function failed and where it's defined, like
+    /// `` `skip` (config.lua:3): ... ``.
+    pub fn call<R>(
+        &self,
+        source: Source,
+        name: &str,
+        f: &mlua::Function,
+        args: impl IntoLuaMulti,
+    ) -> Option<R>
+    where
+        R: FromLuaMulti,
+    {
+        f.call(args)
+            .map_err(|e| {
+                let info = f.info();
+                let message = match (info.short_src, info.line_defined) {
+                    (Some(src), Some(line)) => format!("`{name}` ({src}:{line}): {e}"),
+                    _ => format!("`{name}`: {e}"),
+                };
+                self.report(source, message);
+            })
+            .ok()
+    }
+
+    /// Checks if any errors were reported.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
+
+    /// Takes the errors that were reported, in the order they were first reported.
+    #[inline]
+    pub fn take(&self) -> Vec<Diagnostic> {
+        self.0.take()
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.source, self.message)?;
+        if self.count > 1 {
+            write!(f, " ({} times)", self.count)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Config => "config",
+            Self::Icons => "icons",
+            Self::Colors => "colors",
+            Self::Vcs => "version control",
+            Self::Io => "io",
+        };
+        f.write_str(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let diagnostics = Diagnostics::default();
+        let shared = diagnostics.clone();
+        shared.report(Source::Config, "bad skip");
+        shared.report(Source::Icons, "bad icon");
+        shared.report(Source::Config, "bad skip");
+        assert_eq!(
+            None,
+            diagnostics.check(Source::Io, Err::<(), _>("unreadable"))
+        );
+        assert_eq!(Some(1), diagnostics.check(Source::Io, Ok::<_, String>(1)));
+
+        let messages = diagnostics
+            .take()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "config: bad skip (2 times)",
+                "icons: bad icon",
+                "io: unreadable"
+            ],
+            messages
+        );
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_for_lua() {
+        let lua = Lua::new();
+        assert!(Diagnostics::for_lua(&lua).is_empty());
+
+        let diagnostics = Diagnostics::default();
+        diagnostics.attach(&lua);
+        Diagnostics::for_lua(&lua).report(Source::Colors, "bad color");
+        assert_eq!(1, diagnostics.take().len());
+    }
+
+    #[test]
+    fn test_call() {
+        let lua = Lua::new();
+        let diagnostics = Diagnostics::default();
+        let f = lua
+            .load("return function(n)\n  return n > 0\nend")
+            .set_name("@config.lua")
+            .eval::<mlua::Function>()
+            .unwrap();
+
+        assert_eq!(Some(true), diagnostics.call(Source::Config, "skip", &f, 1));
+        assert_eq!(
+            None,
+            diagnostics.call::<bool>(Source::Config, "skip", &f, "a")
+        );
+        let reported = diagnostics.take();
+        assert_eq!(1, reported.len());
+        assert!(
+            reported[0].message.starts_with("`skip` (config.lua:1): "),
+            "{}",
+            reported[0].message
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! Module for amounts of time, like `15min` or `6mo`, which are shared by every
+//! option that takes one.
+use std::fmt::{self, Display};
+use std::time::Duration;
+
+/// Seconds in a day.
+const DAY: u64 = 60 * 60 * 24;
+
+/// The suffixes of the units and their lengths in seconds, from the longest.
+///
+/// Minutes and months both start with `m`, so neither is `m`.
+const UNITS: [(&str, u64); 7] = [
+    ("y", 365 * DAY),
+    ("mo", 30 * DAY),
+    ("w", 7 * DAY),
+    ("d", DAY),
+    ("h", 60 * 60),
+    ("min", 60),
+    ("s", 1),
+];
+
+/// Parses an amount of seconds (`s`), minutes (`min`), hours (`h`), days (`d`), weeks
+/// (`w`), months (`mo`, 30 days), or years (`y`, 365 days).
+pub fn parse(s: &str) -> Result<Duration, ParseDurationError> {
+    let unit_index = s
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or(ParseDurationError::Invalid)?;
+    let (amount, unit) = s.split_at(unit_index);
+    if unit == "m" {
+        return Err(ParseDurationError::Ambiguous);
+    }
+    let (_, seconds) = UNITS
+        .into_iter()
+        .find(|&(suffix, _)| suffix == unit)
+        .ok_or(ParseDurationError::Invalid)?;
+    let amount = amount
+        .parse::<u64>()
+        .map_err(|_| ParseDurationError::Invalid)?;
+    amount
+        .checked_mul(seconds)
+        .map(Duration::from_secs)
+        .ok_or(ParseDurationError::Invalid)
+}
+
+/// Writes an amount of time in the largest unit that it's a whole amount of, so that
+/// [`parse`] reads it back.
+pub fn display(duration: Duration) -> impl Display {
+    /// Writes the duration.
+    struct Amount(u64);
+
+    impl Display for Amount {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let seconds = self.0;
+            let (suffix, unit) = UNITS
+                .into_iter()
+                .find(|&(_, unit)| seconds != 0 && seconds.is_multiple_of(unit))
+                .unwrap_or(("s", 1));
+            write!(f, "{}{suffix}", seconds / unit)
+        }
+    }
+
+    Amount(duration.as_secs())
+}
+
+/// Error for an invalid amount of time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseDurationError {
+    /// The amount or the unit isn't valid.
+    Invalid,
+    /// The unit is `m`, which could be minutes or months.
+    Ambiguous,
+}
+
+impl Display for ParseDurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid => write!(
+                f,
+                "expected an amount of seconds (s), minutes (min), hours (h), days (d), weeks (w), months (mo), or years (y), like 15min"
+            ),
+            Self::Ambiguous => write!(f, "use min for minutes or mo for months instead of m"),
+        }
+    }
+}
+
+impl std::error::Error for ParseDurationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("90s", 90)]
+    #[case("15min", 15 * 60)]
+    #[case("2h", 2 * 60 * 60)]
+    #[case("1d", DAY)]
+    #[case("2w", 14 * DAY)]
+    #[case("6mo", 180 * DAY)]
+    #[case("1y", 365 * DAY)]
+    fn test_parse(#[case] s: &str, #[case] expected: u64) {
+        let duration = parse(s).unwrap();
+        assert_eq!(Duration::from_secs(expected), duration);
+        assert_eq!(s, display(duration).to_string());
+    }
+
+    #[rstest]
+    #[case("", ParseDurationError::Invalid)]
+    #[case("min", ParseDurationError::Invalid)]
+    #[case("15", ParseDurationError::Invalid)]
+    #[case("15x", ParseDurationError::Invalid)]
+    #[case("-1d", ParseDurationError::Invalid)]
+    #[case("+1d", ParseDurationError::Invalid)]
+    #[case("15é", ParseDurationError::Invalid)]
+    #[case("99999999999999999999y", ParseDurationError::Invalid)]
+    #[case("15m", ParseDurationError::Ambiguous)]
+    fn test_parse_invalid(#[case] s: &str, #[case] expected: ParseDurationError) {
+        assert_eq!(Err(expected), parse(s));
+    }
+
+    #[test]
+    fn test_display_zero() {
+        assert_eq!("0s", display(Duration::ZERO).to_string());
+    }
+}
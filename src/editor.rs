@@ -0,0 +1,232 @@
+//! Module for finding the editor that configuration files are opened in.
+use crate::i18n::Lang;
+use std::env;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The environment variables that are checked for an editor, in order.
+const ENV_VARS: [&str; 3] = ["FANCY_TREE_EDITOR", "VISUAL", "EDITOR"];
+
+/// The editors that are tried when none of the environment variables are set, in
+/// order.
+#[cfg(windows)]
+const DEFAULTS: &[&str] = &["notepad.exe"];
+#[cfg(not(windows))]
+const DEFAULTS: &[&str] = &["sensible-editor", "nano", "vim", "vi"];
+
+/// An editor that was found on `$PATH`, with the arguments that are passed before
+/// the file, like `--wait` for `code --wait`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Editor {
+    /// The path to the editor's executable.
+    program: PathBuf,
+    /// The arguments that are passed before the file.
+    args: Vec<String>,
+}
+
+/// The error when no editor was found, listing each editor that was tried.
+#[derive(Clone, PartialEq, Eq)]
+pub struct NotFound {
+    /// Each editor that was tried, and why it couldn't be used.
+    tried: Vec<(String, Reason)>,
+}
+
+/// Why an editor couldn't be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Reason {
+    /// The environment variable isn't set.
+    NotSet,
+    /// The environment variable's command couldn't be split into words.
+    Invalid(String),
+    /// The program isn't on `$PATH`.
+    NotFound(String),
+}
+
+impl Editor {
+    /// Finds the editor from `$FANCY_TREE_EDITOR`, `$VISUAL`, or `$EDITOR`, or else
+    /// the first of the platform's common editors that's on `$PATH`.
+    pub fn find() -> Result<Self, NotFound> {
+        Self::find_with(
+            |var| env::var(var).ok(),
+            |program| which::which(program).ok(),
+        )
+    }
+
+    /// Finds the editor, using `env` to read environment variables and `which` to
+    /// find programs.
+    fn find_with<E, W>(env: E, which: W) -> Result<Self, NotFound>
+    where
+        E: Fn(&str) -> Option<String>,
+        W: Fn(&str) -> Option<PathBuf>,
+    {
+        let mut tried = Vec::new();
+        for var in ENV_VARS {
+            let name = format!("${var}");
+            let Some(value) = env(var).filter(|value| !value.trim().is_empty()) else {
+                tried.push((name, Reason::NotSet));
+                continue;
+            };
+            let name = format!("{name} (`{value}`)");
+            let mut words = match shell_words::split(&value) {
+                Ok(words) => words.into_iter(),
+                Err(e) => {
+                    tried.push((name, Reason::Invalid(e.to_string())));
+                    continue;
+                }
+            };
+            let Some(program) = words.next() else {
+                tried.push((name, Reason::NotSet));
+                continue;
+            };
+            match which(&program) {
+                Some(program) => {
+                    return Ok(Self {
+                        program,
+                        args: words.collect(),
+                    });
+                }
+                None => tried.push((name, Reason::NotFound(program))),
+            }
+        }
+        for program in DEFAULTS {
+            match which(program) {
+                Some(program) => {
+                    return Ok(Self {
+                        program,
+                        args: Vec::new(),
+                    });
+                }
+                None => tried.push((format!("`{program}`"), Reason::NotFound(String::new()))),
+            }
+        }
+        Err(NotFound { tried })
+    }
+
+    /// Opens the file in the editor, and waits for the editor to close.
+    pub fn open<P>(&self, file: P) -> crate::Result
+    where
+        P: AsRef<Path>,
+    {
+        let status = Command::new(&self.program)
+            .args(&self.args)
+            .arg(file.as_ref())
+            .status()?;
+        if !status.success() {
+            return Err(format!("`{}` exited with {status}", self.program.display()).into());
+        }
+        Ok(())
+    }
+}
+
+impl NotFound {
+    /// Gets the error's message in the language.
+    pub fn message(&self, lang: Lang) -> String {
+        let mut message = lang.message("editor-not-found", &[]);
+        for (name, reason) in self.tried.iter() {
+            let reason = match reason {
+                Reason::NotSet => lang.message("editor-not-set", &[]),
+                Reason::Invalid(error) => error.clone(),
+                // NOTE The name of a default editor is the program.
+                Reason::NotFound(program) if program.is_empty() => {
+                    lang.message("editor-default-not-found", &[])
+                }
+                Reason::NotFound(program) => {
+                    lang.message("editor-program-not-found", &[("program", program)])
+                }
+            };
+            message.push_str(&format!("\n  {name}: {reason}"));
+        }
+        let var = format!("${}", ENV_VARS[0]);
+        message.push('\n');
+        message.push_str(&lang.message("editor-not-found-hint", &[("var", &var)]));
+        message
+    }
+}
+
+impl Display for NotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message(Lang::En))
+    }
+}
+
+// NOTE `main` writes errors with `Debug`, so this writes the same list as `Display`.
+impl fmt::Debug for NotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl Error for NotFound {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use rstest::rstest;
+
+    /// Finds the editor with the environment variables, where only the programs in
+    /// `installed` are on `$PATH`.
+    fn find(vars: &[(&str, &str)], installed: &[&str]) -> Result<Editor, NotFound> {
+        Editor::find_with(testing::env(vars), |program| {
+            installed
+                .contains(&program)
+                .then(|| Path::new("/bin").join(program))
+        })
+    }
+
+    #[rstest]
+    #[case(&[("VISUAL", "code --wait"), ("EDITOR", "vi")], &["code", "vi"], "code", &["--wait"])]
+    #[case(&[("FANCY_TREE_EDITOR", "vi"), ("VISUAL", "code")], &["code", "vi"], "vi", &[])]
+    #[case(&[("VISUAL", "code"), ("EDITOR", "vi")], &["vi"], "vi", &[])]
+    #[case(&[("EDITOR", "  ")], DEFAULTS, DEFAULTS[0], &[])]
+    #[case(&[], &[DEFAULTS[DEFAULTS.len() - 1]], DEFAULTS[DEFAULTS.len() - 1], &[])]
+    fn test_find(
+        #[case] vars: &[(&str, &str)],
+        #[case] installed: &[&str],
+        #[case] program: &str,
+        #[case] args: &[&str],
+    ) {
+        let editor = find(vars, installed).unwrap();
+        assert_eq!(Path::new("/bin").join(program), editor.program);
+        assert_eq!(args, editor.args);
+    }
+
+    #[test]
+    fn test_not_found_es() {
+        let error = find(&[("EDITOR", "vi")], &[]).unwrap_err();
+        let message = error.message(Lang::Es);
+        assert!(
+            message.starts_with("no se encontró ningún editor"),
+            "{message}"
+        );
+        assert!(
+            message.contains("  $EDITOR (`vi`): no se encontró `vi`\n"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn test_not_found() {
+        let error = find(&[("VISUAL", "code --wait"), ("EDITOR", "'vi")], &[]).unwrap_err();
+        let message = error.to_string();
+        assert!(message.starts_with("no editor was found"), "{message}");
+        assert!(
+            message.contains("  $FANCY_TREE_EDITOR: not set\n"),
+            "{message}"
+        );
+        assert!(
+            message.contains("  $VISUAL (`code --wait`): `code` not found\n"),
+            "{message}"
+        );
+        assert!(message.contains("  $EDITOR (`'vi`): "), "{message}");
+        for program in DEFAULTS {
+            assert!(
+                message.contains(&format!("  `{program}`: not found\n")),
+                "{message}"
+            );
+        }
+        assert_eq!(ENV_VARS.len() + DEFAULTS.len(), error.tried.len());
+    }
+}
@@ -0,0 +1,183 @@
+//! Module for expanding `~` and environment variables in paths.
+//!
+//! Shells don't expand these inside quotes, and configuration files can't rely on a
+//! shell at all, so paths are expanded the same way everywhere.
+use directories::BaseDirs;
+use std::env;
+use std::ffi::OsString;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Expands a leading `~` or `~user`, and `$VAR` or `${VAR}` anywhere in the text.
+pub fn expand(s: &str) -> Result<String, ExpandError> {
+    expand_vars_with(s, |name| env::var(name).ok())
+}
+
+/// Expands text like [`expand`], using `var` to read environment variables.
+pub fn expand_vars_with<V>(s: &str, var: V) -> Result<String, ExpandError>
+where
+    V: Fn(&str) -> Option<String>,
+{
+    expand_with(s, var, home_dir)
+}
+
+/// Parses a path from the command line, expanding it if it's valid UTF-8.
+pub fn parse_path(s: OsString) -> Result<PathBuf, ExpandError> {
+    match s.into_string() {
+        Ok(s) => expand(&s).map(PathBuf::from),
+        // NOTE Paths that aren't UTF-8 can't contain anything to expand that we'd
+        //      recognize, so they're used as-is.
+        Err(s) => Ok(PathBuf::from(s)),
+    }
+}
+
+/// Expands text using the provided lookups for variables and home directories.
+///
+/// `home` receives `None` for the current user.
+fn expand_with<V, H>(s: &str, var: V, home: H) -> Result<String, ExpandError>
+where
+    V: Fn(&str) -> Option<String>,
+    H: Fn(Option<&str>) -> Option<PathBuf>,
+{
+    let (mut expanded, rest) = match s.strip_prefix('~') {
+        Some(rest) => {
+            let end = rest
+                .find(['/', std::path::MAIN_SEPARATOR])
+                .unwrap_or(rest.len());
+            let (user, rest) = rest.split_at(end);
+            let user = (!user.is_empty()).then_some(user);
+            let home = home(user).ok_or_else(|| ExpandError::User(user.map(String::from)))?;
+            (home.to_string_lossy().into_owned(), rest)
+        }
+        None => (String::new(), s),
+    };
+
+    let mut chars = rest.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        if c != '$' {
+            expanded.push(c);
+            continue;
+        }
+        let braced = chars.next_if(|&(_, c)| c == '{').is_some();
+        let start = index + if braced { 2 } else { 1 };
+        let mut end = start;
+        while let Some((i, c)) = chars.next_if(|&(_, c)| c.is_ascii_alphanumeric() || c == '_') {
+            end = i + c.len_utf8();
+        }
+        let name = &rest[start..end];
+        if braced && chars.next_if(|&(_, c)| c == '}').is_none() {
+            return Err(ExpandError::Unclosed);
+        }
+        if name.is_empty() {
+            // NOTE A `$` that isn't followed by a name is kept as-is.
+            expanded.push_str(&rest[index..end]);
+            continue;
+        }
+        let value = var(name).ok_or_else(|| ExpandError::Var(name.to_string()))?;
+        expanded.push_str(&value);
+    }
+    Ok(expanded)
+}
+
+/// Gets the home directory of the current user, or of another user by name.
+fn home_dir(user: Option<&str>) -> Option<PathBuf> {
+    let home = BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf());
+    let Some(user) = user else {
+        return home;
+    };
+    user_home_dir(user).or_else(|| {
+        // NOTE Fall back to assuming that users' home directories are siblings.
+        let home = home?;
+        let sibling = home.parent()?.join(user);
+        sibling.is_dir().then_some(sibling)
+    })
+}
+
+/// Looks up another user's home directory in `/etc/passwd`.
+#[cfg(unix)]
+fn user_home_dir(user: &str) -> Option<PathBuf> {
+    let passwd = std::fs::read_to_string("/etc/passwd").ok()?;
+    passwd.lines().find_map(|line| {
+        let fields = line.split(':').collect::<Vec<_>>();
+        (fields.first() == Some(&user))
+            .then(|| fields.get(5).map(PathBuf::from))
+            .flatten()
+    })
+}
+
+/// Looks up another user's home directory.
+#[cfg(not(unix))]
+#[inline]
+fn user_home_dir(_user: &str) -> Option<PathBuf> {
+    None
+}
+
+/// An error from expanding a path.
+#[derive(Debug)]
+pub enum ExpandError {
+    /// The home directory couldn't be found. Contains the user's name if it isn't
+    /// the current user.
+    User(Option<String>),
+    /// An environment variable isn't set.
+    Var(String),
+    /// A `${` wasn't closed by a `}`.
+    Unclosed,
+}
+
+impl fmt::Display for ExpandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::User(None) => write!(f, "couldn't find the home directory"),
+            Self::User(Some(user)) => write!(f, "couldn't find the home directory of {user:?}"),
+            Self::Var(name) => write!(f, "environment variable {name:?} is not set"),
+            Self::Unclosed => write!(f, "expected a `}}` after `${{`"),
+        }
+    }
+}
+
+impl std::error::Error for ExpandError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn var(name: &str) -> Option<String> {
+        match name {
+            "PROJECTS" => Some(String::from("/srv/projects")),
+            "NAME" => Some(String::from("fancy-tree")),
+            _ => None,
+        }
+    }
+
+    fn home(user: Option<&str>) -> Option<PathBuf> {
+        match user {
+            None => Some(PathBuf::from("/home/me")),
+            Some("other") => Some(PathBuf::from("/home/other")),
+            Some(_) => None,
+        }
+    }
+
+    #[rstest]
+    #[case("src", "src")]
+    #[case("~", "/home/me")]
+    #[case("~/src", "/home/me/src")]
+    #[case("~other/src", "/home/other/src")]
+    #[case("a/~/b", "a/~/b")]
+    #[case("$PROJECTS/$NAME", "/srv/projects/fancy-tree")]
+    #[case("${NAME}-backup", "fancy-tree-backup")]
+    #[case("~/$NAME", "/home/me/fancy-tree")]
+    #[case("cost$", "cost$")]
+    #[case("$/x", "$/x")]
+    fn test_expand(#[case] s: &str, #[case] expected: &str) {
+        assert_eq!(expected, expand_with(s, var, home).unwrap());
+    }
+
+    #[rstest]
+    #[case("~nobody/src")]
+    #[case("$MISSING")]
+    #[case("${NAME")]
+    fn test_expand_err(#[case] s: &str) {
+        assert!(expand_with(s, var, home).is_err());
+    }
+}
@@ -0,0 +1,258 @@
+//! Module for the C ABI, so that editors and tools written in other languages can
+//! write trees without spawning a process.
+//!
+//! The header is `include/fancy_tree.h`. It's generated in the build's output
+//! directory when this crate is built with the `ffi` feature, and the tests check
+//! that the copy in `include/` matches it. Build a library to link with like this:
+//!
+//! ```sh
+//! cargo rustc --release --lib --features ffi --crate-type cdylib
+//! ```
+use crate::session::{Options, Session};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+/// The version of the C ABI, which changes when a function's signature or an
+/// option's meaning changes.
+const ABI_VERSION: u32 = 1;
+
+thread_local! {
+    /// The error from the last call on this thread that failed.
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Writes the tree at the root with the options, which are a JSON object.
+fn render(root: &Path, options: &str) -> crate::Result<String> {
+    let options: Options = if options.trim().is_empty() {
+        Options::default()
+    } else {
+        serde_json::from_str(options)?
+    };
+    Session::new(root).options(options).render()
+}
+
+/// Converts a string from the caller to a path.
+///
+/// On Unix, paths are bytes, so paths that aren't UTF-8 are kept as they are. Other
+/// platforms' paths are read as UTF-8.
+fn to_path(s: &CStr) -> Result<PathBuf, &'static str> {
+    #[cfg(unix)]
+    {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        Ok(PathBuf::from(OsStr::from_bytes(s.to_bytes())))
+    }
+    #[cfg(not(unix))]
+    {
+        s.to_str()
+            .map(PathBuf::from)
+            .map_err(|_| "root isn't valid UTF-8")
+    }
+}
+
+/// Sets the error that [`fancy_tree_last_error`] returns.
+fn set_last_error(message: &str) {
+    // NOTE Messages can't contain NUL, so it's replaced instead of losing the message.
+    let message =
+        CString::new(message.replace('\0', "\u{FFFD}")).expect("The message shouldn't contain NUL");
+    LAST_ERROR.with_borrow_mut(|last_error| *last_error = Some(message));
+}
+
+/// Writes the tree at `root` and returns it as a new string, which must be freed
+/// with [`fancy_tree_free`].
+///
+/// `options_json` is a JSON object like `{"format": "json", "level": 2}`, and can be
+/// `NULL` for the defaults. The options are `format`, `color`, `icons`, `level`,
+/// `max_lines`, and `config`, which loads the user's configuration files.
+///
+/// Returns `NULL` if the tree can't be written, and the error can be read with
+/// [`fancy_tree_last_error`].
+///
+/// # Safety
+///
+/// `root` must be a valid NUL-terminated string, and `options_json` must be either
+/// `NULL` or a valid NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fancy_tree_render(
+    root: *const c_char,
+    options_json: *const c_char,
+) -> *mut c_char {
+    if root.is_null() {
+        set_last_error("root is NULL");
+        return ptr::null_mut();
+    }
+    // SAFETY The caller guarantees that the strings are valid.
+    let root = match to_path(unsafe { CStr::from_ptr(root) }) {
+        Ok(root) => root,
+        Err(e) => {
+            set_last_error(e);
+            return ptr::null_mut();
+        }
+    };
+    let options = if options_json.is_null() {
+        ""
+    } else {
+        // SAFETY The caller guarantees that the strings are valid.
+        match unsafe { CStr::from_ptr(options_json) }.to_str() {
+            Ok(options) => options,
+            Err(_) => {
+                set_last_error("options_json isn't valid UTF-8");
+                return ptr::null_mut();
+            }
+        }
+    };
+    // NOTE Unwinding into the caller's language is undefined behavior.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| render(&root, options)));
+    let output = match result {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => {
+            set_last_error(&e.to_string());
+            return ptr::null_mut();
+        }
+        Err(_) => {
+            set_last_error("panicked while writing the tree");
+            return ptr::null_mut();
+        }
+    };
+    match CString::new(output) {
+        Ok(output) => output.into_raw(),
+        Err(e) => {
+            set_last_error(&e.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Frees a string that was returned by [`fancy_tree_render`]. Does nothing if the
+/// string is `NULL`.
+///
+/// # Safety
+///
+/// `s` must be `NULL` or a string returned by [`fancy_tree_render`] that hasn't
+/// been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fancy_tree_free(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY The caller guarantees that the string came from `into_raw`.
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// Gets the error from the last call on this thread that failed, or `NULL` if
+/// there wasn't one. The string is owned by the library, and is valid until the
+/// next call on this thread fails.
+#[unsafe(no_mangle)]
+pub extern "C" fn fancy_tree_last_error() -> *const c_char {
+    LAST_ERROR.with_borrow(|last_error| {
+        last_error
+            .as_ref()
+            .map_or(ptr::null(), |last_error| last_error.as_ptr())
+    })
+}
+
+/// Gets the version of the C ABI, so that callers can check that they're
+/// compatible with the library they loaded.
+#[unsafe(no_mangle)]
+pub extern "C" fn fancy_tree_abi_version() -> u32 {
+    ABI_VERSION
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Fixture;
+
+    /// Calls [`fancy_tree_render`] like a C caller would.
+    fn call<R>(root: R, options: Option<&str>) -> Result<String, String>
+    where
+        R: Into<Vec<u8>>,
+    {
+        let root = CString::new(root).unwrap();
+        let options = options.map(|options| CString::new(options).unwrap());
+        let options = options
+            .as_ref()
+            .map_or(ptr::null(), |options| options.as_ptr());
+        // SAFETY The strings are valid, and the result is freed below.
+        let output = unsafe { fancy_tree_render(root.as_ptr(), options) };
+        if output.is_null() {
+            // SAFETY The error is valid until the next failed call on this thread.
+            let error = unsafe { CStr::from_ptr(fancy_tree_last_error()) };
+            return Err(error.to_string_lossy().into_owned());
+        }
+        // SAFETY The output was just returned by `fancy_tree_render`.
+        let text = unsafe { CStr::from_ptr(output) }
+            .to_string_lossy()
+            .into_owned();
+        // SAFETY The output hasn't been freed yet.
+        unsafe { fancy_tree_free(output) };
+        Ok(text)
+    }
+
+    #[test]
+    fn test_render() {
+        let fixture = Fixture::builder()
+            .file("src/main.rs", "")
+            .file("README.md", "")
+            .build()
+            .unwrap();
+        let root = fixture.path().to_str().unwrap();
+
+        let text = call(root, None).unwrap();
+        assert!(text.contains(" main.rs\n"), "{text}");
+        assert!(!text.contains('\x1b'), "{text}");
+
+        let json = call(root, Some(r#"{"format": "json", "level": 1}"#)).unwrap();
+        let node: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let children = node["children"].as_array().unwrap();
+        assert_eq!(2, children.len());
+        assert!(children.iter().all(|child| child.get("children").is_none()));
+    }
+
+    #[test]
+    fn test_render_errors() {
+        let error = call(".", Some(r#"{"format": "yaml"}"#)).unwrap_err();
+        assert_eq!(r#"invalid format "yaml""#, error);
+        let error = call(".", Some(r#"{"depth": 1}"#)).unwrap_err();
+        assert!(error.starts_with("unknown field `depth`"), "{error}");
+        // SAFETY A NULL root is reported instead of read.
+        assert!(unsafe { fancy_tree_render(ptr::null(), ptr::null()) }.is_null());
+        let options = CString::new(b"{\"format\": \"\xff\"}".to_vec()).unwrap();
+        // SAFETY The strings are valid.
+        let output = unsafe { fancy_tree_render(c".".as_ptr(), options.as_ptr()) };
+        assert!(output.is_null());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_render_non_utf8_root() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let fixture = Fixture::builder().file("file", "").build().unwrap();
+        let name = std::ffi::OsStr::from_bytes(b"dir-\xff");
+        // NOTE Some file systems, like on macOS, only allow UTF-8 names.
+        if std::fs::create_dir(fixture.path().join(name)).is_err() {
+            return;
+        }
+        std::fs::write(fixture.path().join(name).join("inner"), "").unwrap();
+        let mut root = fixture.path().as_os_str().as_bytes().to_vec();
+        root.extend_from_slice(b"/dir-\xff");
+
+        let text = call(root, None).unwrap();
+        assert!(text.contains(" inner\n"), "{text}");
+    }
+
+    #[test]
+    fn test_header() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/fancy_tree.h"));
+        let committed = include_str!("../include/fancy_tree.h");
+        assert!(
+            generated == committed,
+            "include/fancy_tree.h is outdated. Copy it from {}",
+            concat!(env!("OUT_DIR"), "/fancy_tree.h")
+        );
+    }
+}
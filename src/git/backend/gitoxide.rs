@@ -0,0 +1,282 @@
+//! Module for the [gitoxide][gix] backend.
+//!
+//! [gix]: https://docs.rs/gix
+use super::Backend;
+use crate::git::Options;
+use crate::vcs::status::{FileStatus, Status};
+use crate::vcs::{Error, Head, Since};
+use gix::ObjectId;
+use gix::bstr::BString;
+use gix::diff::Rewrites;
+use gix::diff::index::Change;
+use gix::discover::upwards::Error as DiscoverError;
+use gix::object::tree::diff::Action;
+use gix::refs::FullNameRef;
+use gix::remote::Direction;
+use gix::revision::walk::Sorting;
+use gix::status::index_worktree::iter::Summary;
+use gix::status::{Item, UntrackedFiles, tree_index::TrackRenames};
+use gix::traverse::commit::simple::CommitTimeOrder;
+use gix::{Repository, index::entry::Mode, worktree::stack::state::ignore::Source};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{self, Path, PathBuf};
+
+/// Git functionality provided by gitoxide.
+pub struct Gitoxide {
+    /// The repository.
+    repository: Repository,
+    /// The ignore rules, which are read the first time that a path is checked, and
+    /// reused for every other path.
+    excludes: RefCell<Option<gix::worktree::Stack>>,
+}
+
+impl Gitoxide {
+    /// Creates the rename tracking configuration.
+    fn rewrites(options: &Options) -> Option<Rewrites> {
+        /// libgit2 uses a 0-100 threshold, while gitoxide uses a fraction.
+        const PERCENTAGE_SCALE: f32 = 100.0;
+
+        options.renames.then(|| {
+            let mut rewrites = Rewrites::default();
+            if let Some(threshold) = options.rename_threshold {
+                rewrites.percentage = Some(f32::from(threshold) / PERCENTAGE_SCALE);
+            }
+            rewrites
+        })
+    }
+
+    /// Converts a path to a pattern for limiting statuses.
+    fn pattern(path: &Path) -> BString {
+        /// Makes the pattern a literal path relative to the repository root instead of
+        /// the current directory.
+        const MAGIC: &str = ":(top,literal)";
+
+        let path = gix::path::into_bstr(path);
+        let path = gix::path::to_unix_separators_on_windows(path);
+        let mut pattern = BString::from(MAGIC);
+        pattern.extend_from_slice(&path);
+        pattern
+    }
+
+    /// Converts a repository-relative path from gitoxide into a native path.
+    fn native_path(path: &gix::bstr::BStr) -> PathBuf {
+        gix::path::to_native_path_on_windows(path).into_owned()
+    }
+
+    /// Gets the worktree status from a gitoxide status summary.
+    fn untracked_status(summary: Summary) -> Status {
+        match summary {
+            Summary::Added | Summary::IntentToAdd => Status::Added,
+            Summary::Removed => Status::Removed,
+            Summary::Modified | Summary::TypeChange | Summary::Conflict => Status::Modified,
+            Summary::Renamed | Summary::Copied => Status::Renamed,
+        }
+    }
+
+    /// Gets the index status from a change between `HEAD` and the index.
+    fn tracked_status(change: &Change) -> Status {
+        match change {
+            Change::Addition { .. } => Status::Added,
+            Change::Deletion { .. } => Status::Removed,
+            Change::Modification { .. } => Status::Modified,
+            Change::Rewrite { .. } => Status::Renamed,
+        }
+    }
+
+    /// Counts the commits that a branch is ahead of and behind its upstream branch.
+    ///
+    /// Returns `None` if the branch has no upstream branch, or if it hasn't been
+    /// fetched.
+    fn ahead_behind(&self, name: &FullNameRef, local: ObjectId) -> Option<(usize, usize)> {
+        let upstream = self
+            .repository
+            .branch_remote_tracking_ref_name(name, Direction::Fetch)?
+            .ok()?;
+        let upstream = self
+            .repository
+            .find_reference(upstream.as_ref())
+            .ok()?
+            .into_fully_peeled_id()
+            .ok()?
+            .detach();
+        // NOTE The commits reachable from the tip, but not from the other branch.
+        let count = |tip: ObjectId, hidden: ObjectId| {
+            let walk = self.repository.rev_walk([tip]).with_hidden([hidden]);
+            walk.all()
+                .ok()?
+                .try_fold(0, |count, commit| commit.ok().map(|_| count + 1))
+        };
+        Some((count(local, upstream)?, count(upstream, local)?))
+    }
+}
+
+impl Backend for Gitoxide {
+    fn discover(root: &Path, isolated: bool) -> Result<Option<Self>, Error> {
+        // NOTE gitoxide reports paths relative to the discovered path, but absolute
+        //      paths are expected.
+        let root = path::absolute(root)?;
+        let result = if isolated {
+            let options = gix::open::Options::isolated();
+            let trust_map = gix::sec::trust::Mapping {
+                full: options.clone(),
+                reduced: options,
+            };
+            gix::ThreadSafeRepository::discover_opts(root, Default::default(), trust_map)
+                .map(gix::Repository::from)
+        } else {
+            gix::discover(root)
+        };
+        match result {
+            Ok(repository) => Ok(Some(Self {
+                repository,
+                excludes: RefCell::new(None),
+            })),
+            Err(gix::discover::Error::Discover(
+                DiscoverError::NoGitRepository { .. }
+                | DiscoverError::NoGitRepositoryWithinCeiling { .. }
+                | DiscoverError::NoGitRepositoryWithinFs { .. },
+            )) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    #[inline]
+    fn workdir(&self) -> Option<&Path> {
+        self.repository.workdir()
+    }
+
+    fn statuses(
+        &self,
+        options: &Options,
+        scope: Option<&Path>,
+    ) -> Result<HashMap<PathBuf, FileStatus>, Error> {
+        let rewrites = Self::rewrites(options);
+        let track_renames = rewrites.map_or(TrackRenames::Disabled, TrackRenames::Given);
+        let patterns = scope.map(Self::pattern);
+
+        let items = self
+            .repository
+            .status(gix::progress::Discard)?
+            .untracked_files(UntrackedFiles::Collapsed)
+            .index_worktree_rewrites(rewrites)
+            .tree_index_track_renames(track_renames)
+            .into_iter(patterns)?;
+
+        let mut statuses = HashMap::<PathBuf, FileStatus>::new();
+        for item in items {
+            let item = item?;
+            let path = Self::native_path(item.location());
+            match item {
+                Item::IndexWorktree(item) => {
+                    // NOTE Items without a summary are only used to communicate
+                    //      updates for the index.
+                    let Some(summary) = item.summary() else {
+                        continue;
+                    };
+                    statuses.entry(path).or_default().untracked =
+                        Some(Self::untracked_status(summary));
+                }
+                Item::TreeIndex(change) => {
+                    statuses.entry(path).or_default().tracked = Some(Self::tracked_status(&change));
+                }
+            }
+        }
+        Ok(statuses)
+    }
+
+    fn status_file(&self, path: &Path) -> Result<FileStatus, Error> {
+        let statuses = self.statuses(&Options::default(), Some(path))?;
+        let status = statuses.get(path).copied().unwrap_or(FileStatus::CURRENT);
+        Ok(status)
+    }
+
+    fn is_ignored(&self, path: &Path) -> Result<bool, Error> {
+        let mut excludes = self.excludes.borrow_mut();
+        let excludes = match *excludes {
+            Some(ref mut excludes) => excludes,
+            None => {
+                let index = self.repository.index_or_empty()?;
+                let stack = self
+                    .repository
+                    .excludes(&index, None, Source::WorktreeThenIdMappingIfNotSkipped)?
+                    .detach();
+                excludes.insert(stack)
+            }
+        };
+        // NOTE Some ignore rules only match directories.
+        let is_dir = self
+            .repository
+            .workdir()
+            .is_some_and(|workdir| workdir.join(path).is_dir());
+        let mode = is_dir.then_some(Mode::DIR);
+        let platform = excludes.at_path(path, mode, &self.repository.objects)?;
+        Ok(platform.is_excluded())
+    }
+
+    fn head(&self) -> Result<Head, Error> {
+        let name = self.repository.head_name()?;
+        let branch = name.as_ref().map(|name| name.shorten().to_string());
+        // NOTE The HEAD ID can't be resolved if there are no commits yet.
+        let id = self.repository.head_id().ok().map(|id| id.detach());
+        let ahead_behind = name
+            .zip(id)
+            .and_then(|(name, id)| self.ahead_behind(name.as_ref(), id));
+        let id = id.map(|id| id.to_string());
+        Ok(Head {
+            branch,
+            id,
+            ahead_behind,
+        })
+    }
+
+    fn history(
+        &self,
+        since: Option<Since>,
+        visit: &mut dyn FnMut(&Path, i64),
+    ) -> Result<(), Error> {
+        // NOTE There are no commits yet, so nothing has been changed.
+        let Ok(head_id) = self.repository.head_id() else {
+            return Ok(());
+        };
+        let order = CommitTimeOrder::NewestFirst;
+        let sorting = match since {
+            Some(since) => Sorting::ByCommitTimeCutoff {
+                order,
+                seconds: since.seconds(),
+            },
+            None => Sorting::ByCommitTime(order),
+        };
+        let walk = self.repository.rev_walk([head_id]).sorting(sorting).all()?;
+        // NOTE Reusing the cache avoids reading attributes for every commit.
+        let mut cache = self.repository.diff_resource_cache_for_tree_diff()?;
+        let empty_tree = self.repository.empty_tree();
+
+        for info in walk {
+            let info = info?;
+            let time = info.commit_time();
+            if info.parent_ids.len() > 1 {
+                continue;
+            }
+            let tree = info.object()?.tree()?;
+            let parent_tree = match info.parent_ids().next() {
+                Some(parent_id) => parent_id.object()?.into_commit().tree()?,
+                None => empty_tree.clone(),
+            };
+            parent_tree
+                .changes()?
+                .options(|options| {
+                    options.track_path().track_rewrites(None);
+                })
+                .for_each_to_obtain_tree_with_cache(&tree, &mut cache, |change| {
+                    if !change.entry_mode().is_tree() {
+                        visit(&gix::path::from_bstr(change.location()), time);
+                    }
+                    Ok::<_, Error>(Action::Continue)
+                })?;
+            cache.clear_resource_cache_keep_allocation();
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,280 @@
+//! Module for the [libgit2][git2] backend.
+//!
+//! [git2]: https://docs.rs/git2
+use super::Backend;
+use crate::git::Options;
+use crate::vcs::status::{FileStatus, Status};
+use crate::vcs::{Error, Head, Since};
+use git2::{ConfigLevel, Repository, Sort, StatusOptions};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+use util::StatusEntryExt;
+
+mod util;
+
+/// Git functionality provided by libgit2.
+pub struct Libgit2(Repository);
+
+impl Libgit2 {
+    /// Stops libgit2 from finding the user's and the system's configuration files,
+    /// and the global excludes file.
+    ///
+    /// libgit2 only searches for these files in paths that are shared by the whole
+    /// process, so this isolates every repository that's opened afterwards.
+    fn isolate() -> Result<(), Error> {
+        static ISOLATE: Once = Once::new();
+        let mut result = Ok(());
+        ISOLATE.call_once(|| {
+            let levels = [
+                ConfigLevel::ProgramData,
+                ConfigLevel::System,
+                ConfigLevel::XDG,
+                ConfigLevel::Global,
+            ];
+            result = levels.into_iter().try_for_each(|level| {
+                // SAFETY: The paths are only set once, while the first isolated
+                //         repository is opened, before the statuses are computed
+                //         on another thread.
+                unsafe { git2::opts::set_search_path(level, "") }
+            });
+        });
+        result.map_err(Error::from)
+    }
+
+    /// Creates the status options for fetching statuses.
+    fn status_options(config: &Options, scope: Option<&Path>) -> StatusOptions {
+        let mut options = StatusOptions::new();
+        options
+            .include_untracked(true)
+            // NOTE Including unmodified files forces libgit2 to report every tracked
+            //      file, which is very slow for large repositories. Missing entries
+            //      are treated as unmodified instead.
+            .include_unmodified(false)
+            .renames_head_to_index(config.renames)
+            .renames_index_to_workdir(config.renames)
+            // NOTE We'll be using literal paths, not patterns
+            .disable_pathspec_match(true);
+        if let Some(threshold) = config.rename_threshold {
+            options.rename_threshold(threshold);
+        }
+        if let Some(scope) = scope {
+            // NOTE Pathspec matching is disabled, so this is used as a literal path
+            //      prefix.
+            options.pathspec(scope);
+        }
+        options
+    }
+
+    /// Gets the combined statuses from a git2 status.
+    fn file_status(status: git2::Status) -> FileStatus {
+        FileStatus {
+            tracked: Self::tracked_from_git2(status),
+            untracked: Self::untracked_from_git2(status),
+        }
+    }
+
+    /// Gets the index status from the git2 status.
+    fn tracked_from_git2(status: git2::Status) -> Option<Status> {
+        use Status::*;
+
+        let status = if status.is_index_renamed() {
+            Renamed
+        } else if status.is_index_new() {
+            Added
+        } else if status.is_index_modified() {
+            Modified
+        } else if status.is_index_deleted() {
+            Removed
+        } else {
+            return None;
+        };
+
+        Some(status)
+    }
+
+    /// Gets the worktree status from the git2 status.
+    fn untracked_from_git2(status: git2::Status) -> Option<Status> {
+        use Status::*;
+
+        let status = if status.is_wt_renamed() {
+            Renamed
+        } else if status.is_wt_new() {
+            Added
+        } else if status.is_wt_modified() {
+            Modified
+        } else if status.is_wt_deleted() {
+            Removed
+        } else {
+            return None;
+        };
+
+        Some(status)
+    }
+
+    /// Counts the commits that a branch is ahead of and behind its upstream branch.
+    ///
+    /// Returns `None` if the branch has no upstream branch, or if it hasn't been
+    /// fetched.
+    fn ahead_behind(&self, branch: &git2::Reference) -> Option<(usize, usize)> {
+        let local = branch.target()?;
+        let upstream = self.0.branch_upstream_name(branch.name()?).ok()?;
+        let upstream = self.0.refname_to_id(upstream.as_str()?).ok()?;
+        self.0.graph_ahead_behind(local, upstream).ok()
+    }
+}
+
+impl Backend for Libgit2 {
+    fn discover(root: &Path, isolated: bool) -> Result<Option<Self>, Error> {
+        if isolated {
+            Self::isolate()?;
+        }
+        let result = Repository::discover(root);
+        let repo_not_found = result
+            .as_ref()
+            .is_err_and(|err| matches!(err.code(), git2::ErrorCode::NotFound));
+        if repo_not_found {
+            Ok(None)
+        } else {
+            result.map(Self).map(Some).map_err(Error::from)
+        }
+    }
+
+    #[inline]
+    fn workdir(&self) -> Option<&Path> {
+        self.0.workdir()
+    }
+
+    fn statuses(
+        &self,
+        options: &Options,
+        scope: Option<&Path>,
+    ) -> Result<HashMap<PathBuf, FileStatus>, Error> {
+        let mut options = Self::status_options(options, scope);
+        let statuses = self
+            .0
+            .statuses(Some(&mut options))?
+            .iter()
+            .filter_map(|entry| {
+                entry
+                    .path_buf()
+                    .map(|path| (path, Self::file_status(entry.status())))
+            })
+            .collect::<HashMap<_, _>>();
+        Ok(statuses)
+    }
+
+    fn status_file(&self, path: &Path) -> Result<FileStatus, Error> {
+        let status = self.0.status_file(path)?;
+        Ok(Self::file_status(status))
+    }
+
+    fn is_ignored(&self, path: &Path) -> Result<bool, Error> {
+        let is_ignored = self.0.is_path_ignored(path)?;
+        Ok(is_ignored)
+    }
+
+    fn head(&self) -> Result<Head, Error> {
+        /// The prefix for local branches.
+        const BRANCH_PREFIX: &str = "refs/heads/";
+
+        let head = match self.0.head() {
+            Ok(head) => head,
+            Err(err) if matches!(err.code(), git2::ErrorCode::UnbornBranch) => {
+                // NOTE There are no commits yet, but HEAD still points to a branch.
+                let head = self.0.find_reference("HEAD")?;
+                let branch = head
+                    .symbolic_target()
+                    .map(|target| target.strip_prefix(BRANCH_PREFIX).unwrap_or(target))
+                    .map(String::from);
+                return Ok(Head {
+                    branch,
+                    id: None,
+                    ahead_behind: None,
+                });
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let branch = head
+            .is_branch()
+            .then(|| head.shorthand().map(String::from))
+            .flatten();
+        let id = head.target().map(|oid| oid.to_string());
+        let ahead_behind = head.is_branch().then(|| self.ahead_behind(&head)).flatten();
+        Ok(Head {
+            branch,
+            id,
+            ahead_behind,
+        })
+    }
+
+    fn history(
+        &self,
+        since: Option<Since>,
+        visit: &mut dyn FnMut(&Path, i64),
+    ) -> Result<(), Error> {
+        let mut revwalk = self.0.revwalk()?;
+        match revwalk.push_head() {
+            Ok(()) => {}
+            // NOTE There are no commits yet, so nothing has been changed.
+            Err(err) if matches!(err.code(), git2::ErrorCode::UnbornBranch) => return Ok(()),
+            Err(err) => return Err(err.into()),
+        }
+        revwalk.set_sorting(Sort::TIME)?;
+
+        for oid in revwalk {
+            let commit = self.0.find_commit(oid?)?;
+            let time = commit.time().seconds();
+            // NOTE Commits are sorted newest first, so every commit after this one is
+            //      also too old.
+            if since.is_some_and(|since| time < since.seconds()) {
+                break;
+            }
+            if commit.parent_count() > 1 {
+                continue;
+            }
+            let tree = commit.tree()?;
+            let parent_tree = commit.parents().next().map(|parent| parent.tree());
+            let parent_tree = parent_tree.transpose()?;
+            let diff = self
+                .0
+                .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+            let paths = diff
+                .deltas()
+                .filter_map(|delta| delta.new_file().path().or(delta.old_file().path()));
+            paths.for_each(|path| visit(path, time));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Status::*;
+    use git2::Status as Libgit;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Libgit::INDEX_NEW, Some(Added))]
+    #[case(Libgit::INDEX_MODIFIED, Some(Modified))]
+    #[case(Libgit::INDEX_DELETED, Some(Removed))]
+    #[case(Libgit::INDEX_RENAMED, Some(Renamed))]
+    #[case(Libgit::INDEX_RENAMED | Libgit::INDEX_NEW, Some(Renamed))]
+    #[case(Libgit::WT_NEW, None)]
+    fn test_tracked_from_git2(#[case] libgit: Libgit, #[case] expected: Option<Status>) {
+        assert_eq!(expected, Libgit2::tracked_from_git2(libgit));
+    }
+
+    #[rstest]
+    #[case(Libgit::WT_NEW, Some(Added))]
+    #[case(Libgit::WT_MODIFIED, Some(Modified))]
+    #[case(Libgit::WT_DELETED, Some(Removed))]
+    #[case(Libgit::WT_RENAMED, Some(Renamed))]
+    #[case(Libgit::WT_RENAMED | Libgit::WT_NEW, Some(Renamed))]
+    #[case(Libgit::INDEX_NEW, None)]
+    fn test_untracked_from_git2(#[case] libgit: Libgit, #[case] expected: Option<Status>) {
+        assert_eq!(expected, Libgit2::untracked_from_git2(libgit));
+    }
+}
@@ -0,0 +1,67 @@
+//! Module for the libraries that provide the actual git functionality.
+//!
+//! When the `gix` feature is enabled, the pure-Rust [gitoxide][gix] backend is used.
+//! Otherwise, [libgit2][git2] is used.
+//!
+//! [gix]: https://docs.rs/gix
+//! [git2]: https://docs.rs/git2
+use super::Options;
+use crate::vcs::{Error, Head, Since, status::FileStatus};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[cfg(not(any(feature = "git2", feature = "gix")))]
+compile_error!("Either the `git2` or the `gix` feature must be enabled");
+
+#[cfg(feature = "gix")]
+mod gitoxide;
+#[cfg(all(feature = "git2", not(feature = "gix")))]
+mod libgit2;
+
+/// The backend selected by the enabled features.
+#[cfg(feature = "gix")]
+pub type Selected = gitoxide::Gitoxide;
+
+/// The backend selected by the enabled features.
+#[cfg(all(feature = "git2", not(feature = "gix")))]
+pub type Selected = libgit2::Libgit2;
+
+/// Common behavior for git implementations.
+pub trait Backend: Sized {
+    /// Discovers the repository containing `root`.
+    ///
+    /// Returns `Ok(None)` if there is no repository. When `isolated` is `true`, the
+    /// user's and the system's git configuration aren't read, so that the global
+    /// excludes file doesn't hide entries.
+    fn discover(root: &Path, isolated: bool) -> Result<Option<Self>, Error>;
+
+    /// Gets the root directory of the repository's working tree.
+    ///
+    /// Returns `None` for bare repositories.
+    fn workdir(&self) -> Option<&Path>;
+
+    /// Computes the statuses of all files that are not current (unmodified).
+    ///
+    /// `scope` is a path relative to the working directory that statuses should be
+    /// limited to.
+    fn statuses(
+        &self,
+        options: &Options,
+        scope: Option<&Path>,
+    ) -> Result<HashMap<PathBuf, FileStatus>, Error>;
+
+    /// Computes the status of a single file. The path is relative to the working
+    /// directory.
+    fn status_file(&self, path: &Path) -> Result<FileStatus, Error>;
+
+    /// Checks if a path is ignored. The path is relative to the working directory.
+    fn is_ignored(&self, path: &Path) -> Result<bool, Error>;
+
+    /// Gets information about `HEAD`.
+    fn head(&self) -> Result<Head, Error>;
+
+    /// Walks the commits reachable from `HEAD`, newest first, calling `visit` with
+    /// each changed path and the commit time. Stops at commits older than `since`.
+    fn history(&self, since: Option<Since>, visit: &mut dyn FnMut(&Path, i64))
+    -> Result<(), Error>;
+}
@@ -0,0 +1,85 @@
+//! Module for summarizing a repository above the tree.
+use crate::vcs::Head;
+use std::fmt::{self, Display};
+
+/// A summary of a repository, like `On branch main, ahead 2, 3 changed files`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// Information about `HEAD`.
+    pub head: Head,
+    /// The number of changed files in the tree. `None` if the statuses couldn't be
+    /// computed.
+    pub changed: Option<usize>,
+}
+
+impl Header {
+    /// The number of characters of a commit ID to show when `HEAD` is detached.
+    const SHORT_ID_LEN: usize = 7;
+}
+
+impl Display for Header {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.head.branch, &self.head.id) {
+            (Some(branch), _) => write!(f, "On branch {branch}")?,
+            (None, Some(id)) => {
+                let id = id.get(..Self::SHORT_ID_LEN).unwrap_or(id);
+                write!(f, "HEAD detached at {id}")?;
+            }
+            (None, None) => write!(f, "No commits yet")?,
+        }
+        match self.head.ahead_behind {
+            Some((0, 0)) => write!(f, ", up to date")?,
+            Some((ahead, behind)) => {
+                if ahead > 0 {
+                    write!(f, ", ahead {ahead}")?;
+                }
+                if behind > 0 {
+                    write!(f, ", behind {behind}")?;
+                }
+            }
+            None => {}
+        }
+        match self.changed {
+            Some(0) => write!(f, ", clean"),
+            Some(1) => write!(f, ", 1 changed file"),
+            Some(changed) => write!(f, ", {changed} changed files"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Some("main"), None, Some((0, 0)), Some(0), "On branch main, up to date, clean")]
+    #[case(
+        Some("main"),
+        None,
+        Some((2, 1)),
+        Some(3),
+        "On branch main, ahead 2, behind 1, 3 changed files"
+    )]
+    #[case(Some("main"), None, Some((0, 4)), Some(1), "On branch main, behind 4, 1 changed file")]
+    #[case(None, Some("0123456789abcdef"), None, None, "HEAD detached at 0123456")]
+    #[case(None, None, None, Some(2), "No commits yet, 2 changed files")]
+    fn test_display(
+        #[case] branch: Option<&str>,
+        #[case] id: Option<&str>,
+        #[case] ahead_behind: Option<(usize, usize)>,
+        #[case] changed: Option<usize>,
+        #[case] expected: &str,
+    ) {
+        let header = Header {
+            head: Head {
+                branch: branch.map(String::from),
+                id: id.map(String::from),
+                ahead_behind,
+            },
+            changed,
+        };
+        assert_eq!(expected, header.to_string());
+    }
+}
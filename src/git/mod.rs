@@ -1,19 +1,49 @@
 //! Module for git integration.
-use git2::{Repository, StatusOptions};
-use status::StatusGetter;
+use crate::vcs::{
+    self, Error, Head, Since, Vcs,
+    status::{self, FileStatus, Status},
+};
+use backend::Backend as _;
+pub use header::Header;
+pub use options::Options;
+use std::cell::{OnceCell, RefCell};
 use std::collections::HashMap;
+use std::fs;
 use std::path::{Path, PathBuf};
-use util::StatusEntryExt;
+use std::thread::{self, JoinHandle};
 
-pub mod status;
-mod util;
+/// The statuses of files that aren't current, by path.
+type Statuses = HashMap<PathBuf, FileStatus>;
+
+mod backend;
+mod header;
+mod options;
 
 /// The main struct for git integration.
 pub struct Git {
-    /// The main repository.
-    repository: Repository,
+    /// The git implementation.
+    backend: backend::Selected,
     /// Cached file statuses.
-    statuses: HashMap<PathBuf, git2::Status>,
+    ///
+    /// These are computed either by [`Git::load_statuses`] or on first use. Only
+    /// files that are *not* current (unmodified) are cached, so a path that is
+    /// missing from the cache is clean. `None` if the statuses couldn't be computed.
+    statuses: OnceCell<Option<Statuses>>,
+    /// The statuses that are being computed on another thread by
+    /// [`Git::load_statuses_in_background`].
+    pending: RefCell<Option<JoinHandle<Result<Statuses, Error>>>>,
+    /// The numbers of files that aren't ignored in each directory inside an
+    /// untracked directory, which are counted together when the first one is needed.
+    untracked_counts: RefCell<HashMap<PathBuf, usize>>,
+    /// The directory that the repository was discovered from.
+    root: PathBuf,
+    /// Was the repository opened without the user's and the system's configuration?
+    isolated: bool,
+    /// The path of the searched directory relative to the repository's root.
+    ///
+    /// This limits status computation to the subtree being displayed. `None` if
+    /// the whole repository should be searched.
+    scope: Option<PathBuf>,
 }
 
 impl Git {
@@ -21,112 +51,391 @@ impl Git {
     ///
     /// If the repository does not exist, this returns `Ok(None)`. Other errors get
     /// passed back to the caller.
-    pub fn new<P>(root: P) -> Result<Option<Self>, git2::Error>
+    pub fn new<P>(root: P) -> Result<Option<Self>, Error>
     where
         P: AsRef<Path>,
     {
-        let result = Repository::discover(root);
-        let repo_not_found = result
-            .as_ref()
-            .is_err_and(|err| matches!(err.code(), git2::ErrorCode::NotFound));
-        if repo_not_found {
-            Ok(None)
-        } else {
-            result.and_then(|repository| Self::from_repository(repository).map(Some))
-        }
+        Self::discover(root.as_ref(), false)
     }
 
-    /// Creates a Git struct from a git2 repository.
-    fn from_repository(repository: Repository) -> Result<Self, git2::Error> {
-        let statuses = Self::statuses(&repository)?;
-        let git = Self {
-            repository,
-            statuses,
-        };
-        Ok(git)
-    }
-
-    /// Creates a hashmap of paths to statuses for a repository.
-    fn statuses(repository: &Repository) -> Result<HashMap<PathBuf, git2::Status>, git2::Error> {
-        let mut options = Self::status_options();
-        let statuses = repository
-            .statuses(Some(&mut options))?
-            .iter()
-            .filter_map(|entry| entry.path_buf().map(|path| (path, entry.status())))
-            .collect::<HashMap<_, _>>();
-        Ok(statuses)
+    /// Creates a new Git struct that doesn't read the user's or the system's git
+    /// configuration, like the global excludes file, so that the same entries are
+    /// ignored on every machine.
+    pub fn isolated<P>(root: P) -> Result<Option<Self>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        Self::discover(root.as_ref(), true)
     }
 
-    /// Creates the status options for fetching statuses.
-    fn status_options() -> StatusOptions {
-        let mut options = StatusOptions::new();
-        options
-            .include_untracked(true)
-            .include_unmodified(true)
-            .renames_head_to_index(true)
-            .renames_index_to_workdir(true)
-            // NOTE We'll be using literal paths, not patterns
-            .disable_pathspec_match(true);
-        options
+    /// Discovers the repository containing `root`.
+    fn discover(root: &Path, isolated: bool) -> Result<Option<Self>, Error> {
+        let backend = backend::Selected::discover(root, isolated)?;
+        let git = backend.map(|backend| Self::from_backend(backend, root, isolated));
+        Ok(git)
     }
 
-    /// Gets the status for a file.
-    pub fn status<S, P>(&self, path: P) -> Result<Option<status::Status>, git2::Error>
+    /// Creates a Git struct from a backend.
+    fn from_backend<P>(backend: backend::Selected, root: P, isolated: bool) -> Self
     where
-        S: StatusGetter,
         P: AsRef<Path>,
     {
-        self.git2_status(path).map(S::from_git2)
+        let scope = backend
+            .workdir()
+            .and_then(|workdir| vcs::scope(workdir, &root));
+        Self {
+            backend,
+            statuses: OnceCell::new(),
+            pending: RefCell::new(None),
+            untracked_counts: RefCell::new(HashMap::new()),
+            root: root.as_ref().to_path_buf(),
+            isolated,
+            scope,
+        }
     }
 
-    /// Gets the original gt2 status for a file.
+    /// Computes and caches the statuses of the repository using the provided options.
     ///
-    /// Path should be relative to the repository's root, and ideally should be as
-    /// `path/to/file.ext`. In other words, paths should be as simple as possible, and
-    /// not have `./` or `../`
-    fn git2_status<P>(&self, path: P) -> Result<git2::Status, git2::Error>
-    where
-        P: AsRef<Path>,
-    {
-        // NOTE If the status is not in the cache, then maybe we're looking at an
-        //      ignored file or a file that wasn't in the cache due to the status
-        //      options set. If that happens we get the status on demand.
-        self.cached_git2_status(&path)
-            .map(Ok)
-            .unwrap_or_else(|| self.on_demand_git2_status(path))
+    /// This does nothing if the statuses have already been computed.
+    pub fn load_statuses(&self, options: &Options) -> Result<(), Error> {
+        if self.statuses.get().is_none() {
+            let statuses = match self.join_pending() {
+                Some(statuses) => statuses?,
+                None => Self::compute_statuses(&self.backend, options, self.scope.as_deref())?,
+            };
+            // NOTE We already checked that the cell is empty.
+            let _ = self.statuses.set(Some(statuses));
+        }
+        Ok(())
     }
 
-    /// Gets the cached git2 status for a path.
-    fn cached_git2_status<P>(&self, path: P) -> Option<git2::Status>
-    where
-        P: AsRef<Path>,
-    {
-        let path = path.as_ref();
-        self.statuses.get(path).cloned()
+    /// Starts computing the statuses of the repository on another thread, so that
+    /// the tree can be searched at the same time. The first status that's needed
+    /// waits for them.
+    ///
+    /// This does nothing if the statuses have already been computed or started.
+    pub fn load_statuses_in_background(&self, options: Options) {
+        let mut pending = self.pending.borrow_mut();
+        if self.statuses.get().is_some() || pending.is_some() {
+            return;
+        }
+        // NOTE Repositories can't be shared between threads, so the thread opens
+        //      its own.
+        let root = self.root.clone();
+        let scope = self.scope.clone();
+        let isolated = self.isolated;
+        let handle = thread::spawn(move || {
+            let backend = backend::Selected::discover(&root, isolated)?
+                .ok_or("The repository should still exist")?;
+            Self::compute_statuses(&backend, &options, scope.as_deref())
+        });
+        *pending = Some(handle);
+    }
+
+    /// Computes the statuses of the repository, and rolls them up to directories if
+    /// the options enable it.
+    fn compute_statuses(
+        backend: &backend::Selected,
+        options: &Options,
+        scope: Option<&Path>,
+    ) -> Result<Statuses, Error> {
+        let mut statuses = backend.statuses(options, scope)?;
+        if options.roll_up {
+            status::roll_up(&mut statuses);
+        }
+        Ok(statuses)
+    }
+
+    /// Waits for the statuses that are being computed on another thread, if any.
+    fn join_pending(&self) -> Option<Result<Statuses, Error>> {
+        let handle = self.pending.borrow_mut().take()?;
+        let statuses = handle
+            .join()
+            .unwrap_or_else(|_| Err("The git status thread panicked".into()));
+        Some(statuses)
+    }
+
+    /// Gets the cached statuses, computing them with the default options if they
+    /// haven't been loaded yet.
+    fn cached_statuses(&self) -> Option<&Statuses> {
+        // NOTE If the statuses can't be computed, then all statuses will be fetched
+        //      on demand.
+        self.statuses
+            .get_or_init(|| {
+                self.join_pending()
+                    .unwrap_or_else(|| {
+                        self.backend
+                            .statuses(&Options::default(), self.scope.as_deref())
+                    })
+                    .ok()
+            })
+            .as_ref()
     }
 
-    /// Gets the on-demand git2 status for a path.
-    fn on_demand_git2_status<P>(&self, path: P) -> Result<git2::Status, git2::Error>
+    /// Gets the cached status for a path.
+    ///
+    /// Returns `None` if there is no cache.
+    fn cached_file_status<P>(&self, path: P) -> Option<FileStatus>
     where
         P: AsRef<Path>,
     {
         let path = path.as_ref();
-        self.repository.status_file(path)
+        // NOTE Unmodified and ignored files are not in the cache, and neither have a
+        //      status that gets displayed, so they can be treated as current.
+        self.cached_statuses()
+            .map(|statuses| statuses.get(path).copied().unwrap_or(FileStatus::CURRENT))
+    }
+
+    /// Summarizes the repository, like the checked out branch and how many files in
+    /// the tree changed.
+    ///
+    /// This waits for the statuses if they're being computed in the background.
+    pub fn header(&self) -> Result<Header, Error> {
+        let head = self.head_info()?;
+        let changed = self.changed_files();
+        Ok(Header { head, changed })
+    }
+
+    /// Gets the number of files in the tree that changed, or `None` if the
+    /// statuses couldn't be computed.
+    pub fn changed_files(&self) -> Option<usize> {
+        self.cached_statuses().map(status::count_changed)
+    }
+
+    /// Counts the files in a directory that aren't ignored, searching its
+    /// subdirectories. The directory is relative to the working tree.
+    ///
+    /// The counts of the directory and of each subdirectory are cached, so that
+    /// counting an untracked directory once counts every directory inside it.
+    fn count_files(&self, workdir: &Path, directory: &Path) -> usize {
+        let count = match fs::read_dir(workdir.join(directory)) {
+            Ok(entries) => entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.file_name() != ".git")
+                .map(|entry| directory.join(entry.file_name()))
+                .filter(|path| !self.backend.is_ignored(path).unwrap_or(false))
+                .map(|path| match workdir.join(&path).symlink_metadata() {
+                    Ok(metadata) if metadata.is_dir() => self.count_files(workdir, &path),
+                    _ => 1,
+                })
+                .sum(),
+            Err(_) => 0,
+        };
+        self.untracked_counts
+            .borrow_mut()
+            .insert(directory.to_path_buf(), count);
+        count
     }
 
     /// Checks if a path is ignored.
-    pub fn is_ignored<P>(&self, path: P) -> Result<bool, git2::Error>
+    pub fn is_ignored<P>(&self, path: P) -> Result<bool, Error>
     where
         P: AsRef<Path>,
     {
-        self.repository.is_path_ignored(path)
+        self.backend.is_ignored(path.as_ref())
     }
+}
 
+impl Vcs for Git {
     /// Gets the root directory of the git repository's working tree.
     ///
     /// Returns `None` for bare repositories.
     #[inline]
-    pub fn root_dir(&self) -> Option<&Path> {
-        self.repository.workdir()
+    fn root_dir(&self) -> Option<&Path> {
+        self.backend.workdir()
+    }
+
+    fn status(&self, path: &Path) -> Result<FileStatus, Error> {
+        // NOTE If the statuses couldn't be cached, we get the status on demand.
+        self.cached_file_status(path)
+            .map(Ok)
+            .unwrap_or_else(|| self.backend.status_file(path))
+    }
+
+    #[inline]
+    fn is_ignored(&self, path: &Path) -> Result<bool, Error> {
+        self.backend.is_ignored(path)
+    }
+
+    #[inline]
+    fn head_info(&self) -> Result<Head, Error> {
+        self.backend.head()
+    }
+
+    /// Checks the cached statuses for an untracked directory, or one inside an
+    /// untracked directory, and counts the files in it that aren't ignored.
+    fn untracked_files(&self, directory: &Path) -> Option<usize> {
+        let statuses = self.cached_statuses()?;
+        let workdir = self.backend.workdir()?;
+        // NOTE A directory that statuses were rolled up to has the paths inside it
+        //      in the cache, while an untracked directory is only one path.
+        let is_untracked = |path: &Path| {
+            statuses
+                .get(path)
+                .is_some_and(|status| status.untracked == Some(Status::Added))
+                && !statuses
+                    .keys()
+                    .any(|key| key != path && key.starts_with(path))
+        };
+        let cached = || self.untracked_counts.borrow().get(directory).copied();
+        if let Some(count) = cached() {
+            return Some(count);
+        }
+        // NOTE The outermost untracked directory is counted, so that the directories
+        //      inside it are counted with it instead of once for each of them.
+        let untracked = directory
+            .ancestors()
+            .take_while(|path| !path.as_os_str().is_empty())
+            .filter(|path| is_untracked(path))
+            .last()?;
+        self.count_files(workdir, untracked);
+        cached().or_else(|| Some(self.count_files(workdir, directory)))
+    }
+
+    /// Gets the deleted paths in a directory from the cached statuses.
+    fn deleted(&self, directory: &Path) -> Vec<PathBuf> {
+        let (Some(statuses), Some(workdir)) = (self.cached_statuses(), self.backend.workdir())
+        else {
+            return Vec::new();
+        };
+        let is_removed = |status: &FileStatus| {
+            status.tracked == Some(Status::Removed) || status.untracked == Some(Status::Removed)
+        };
+        // NOTE A path deeper in the directory means that its first component, which
+        //      is in the directory, was deleted if it doesn't exist anymore.
+        let mut deleted = statuses
+            .iter()
+            .filter(|(_, status)| is_removed(status))
+            .filter_map(|(path, _)| {
+                let name = path.strip_prefix(directory).ok()?.components().next()?;
+                Some(directory.join(name))
+            })
+            .filter(|path| workdir.join(path).symlink_metadata().is_err())
+            .collect::<Vec<_>>();
+        deleted.sort();
+        deleted.dedup();
+        deleted
+    }
+
+    #[inline]
+    fn walk_history(
+        &self,
+        since: Option<Since>,
+        visit: &mut dyn FnMut(&Path, i64),
+    ) -> Result<(), Error> {
+        self.backend.history(since, visit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Fixture;
+
+    #[test]
+    fn test_load_statuses_in_background() {
+        let fixture = Fixture::builder()
+            .git_init()
+            .file("tracked", "")
+            .git_commit("Initial commit")
+            .file("tracked", "modified")
+            .file("new", "")
+            .build()
+            .unwrap();
+        let git = Git::new(fixture.path()).unwrap().unwrap();
+        let expected = git
+            .backend
+            .statuses(&Options::default(), git.scope.as_deref())
+            .unwrap();
+
+        git.load_statuses_in_background(Options::default());
+        assert_eq!(Some(&expected), git.cached_statuses());
+        assert!(git.pending.borrow().is_none());
+    }
+
+    #[test]
+    fn test_untracked_files_counted_once() {
+        let fixture = Fixture::builder()
+            .git_init()
+            .file("tracked", "")
+            .git_commit("Initial commit")
+            .file("new/a/b/file", "")
+            .file("new/a/file", "")
+            .file("new/file", "")
+            .build()
+            .unwrap();
+        let git = Git::new(fixture.path()).unwrap().unwrap();
+        git.load_statuses(&Options::default()).unwrap();
+
+        assert_eq!(Some(1), git.untracked_files(Path::new("new/a/b")));
+        let expected = [("new", 3), ("new/a", 2), ("new/a/b", 1)]
+            .map(|(path, count)| (PathBuf::from(path), count))
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        assert_eq!(expected, *git.untracked_counts.borrow());
+        assert_eq!(Some(3), git.untracked_files(Path::new("new")));
+        assert_eq!(None, git.untracked_files(Path::new("tracked")));
+    }
+
+    #[test]
+    fn test_isolated_keeps_repository_excludes() {
+        let fixture = Fixture::builder()
+            .git_init()
+            .file(".gitignore", "ignored\n")
+            .file(".git/info/exclude", "excluded\n")
+            .file("ignored", "")
+            .file("excluded", "")
+            .file("kept", "")
+            .build()
+            .unwrap();
+        let git = Git::isolated(fixture.path()).unwrap().unwrap();
+
+        assert!(git.is_ignored("ignored").unwrap());
+        assert!(git.is_ignored("excluded").unwrap());
+        assert!(!git.is_ignored("kept").unwrap());
+    }
+
+    #[test]
+    fn test_head() {
+        // NOTE This runs on this project's own repository, and is skipped if the
+        //      code isn't in a git repository.
+        let Ok(Some(git)) = Git::new(env!("CARGO_MANIFEST_DIR")) else {
+            eprintln!("Probably not a git repository");
+            return;
+        };
+        let head = git.head_info().expect("HEAD should be readable");
+        assert!(head.branch.is_some() || head.id.is_some());
+    }
+
+    #[test]
+    fn test_header() {
+        let fixture = Fixture::builder()
+            .git_init()
+            .file("tracked", "")
+            .git_commit("Initial commit")
+            .git([
+                "config",
+                "remote.origin.url",
+                "https://example.com/repo.git",
+            ])
+            .git([
+                "config",
+                "remote.origin.fetch",
+                "+refs/heads/*:refs/remotes/origin/*",
+            ])
+            .git(["config", "branch.main.remote", "origin"])
+            .git(["config", "branch.main.merge", "refs/heads/main"])
+            .git(["update-ref", "refs/remotes/origin/main", "HEAD"])
+            .git_commit("Second commit")
+            .file("tracked", "modified")
+            .file("new", "")
+            .build()
+            .unwrap();
+        let git = Git::new(fixture.path()).unwrap().unwrap();
+
+        let header = git.header().unwrap();
+        assert_eq!(Some("main"), header.head.branch.as_deref());
+        assert_eq!(Some((1, 0)), header.head.ahead_behind);
+        assert_eq!(Some(2), header.changed);
     }
 }
@@ -0,0 +1,98 @@
+//! Module for configuring how git statuses are computed.
+use mlua::{FromLua, Lua};
+
+/// Options for computing git statuses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// Should renames be detected?
+    ///
+    /// Disabling this can speed up status computation in repositories with many new
+    /// files.
+    pub renames: bool,
+    /// The similarity threshold (0-100) for rename detection.
+    ///
+    /// When this is `None`, libgit2's default threshold is used.
+    pub rename_threshold: Option<u16>,
+    /// Should directories show the combined statuses of their contents?
+    ///
+    /// This is set by `git_statuses.roll_up` in the main config, instead of by the
+    /// `git` table.
+    pub roll_up: bool,
+}
+
+impl Options {
+    /// Default value for if renames should be detected.
+    const DEFAULT_RENAMES: bool = true;
+
+    /// The maximum similarity threshold.
+    const MAX_RENAME_THRESHOLD: u16 = 100;
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            renames: Self::DEFAULT_RENAMES,
+            rename_threshold: None,
+            roll_up: false,
+        }
+    }
+}
+
+impl FromLua for Options {
+    fn from_lua(value: mlua::Value, lua: &Lua) -> mlua::Result<Self> {
+        let table = mlua::Table::from_lua(value, lua)?;
+        let renames = table
+            .get::<Option<bool>>("renames")?
+            .unwrap_or(Self::DEFAULT_RENAMES);
+        let rename_threshold = table.get::<Option<u16>>("rename_threshold")?;
+
+        if let Some(threshold) = rename_threshold
+            && threshold > Self::MAX_RENAME_THRESHOLD
+        {
+            return Err(mlua::Error::FromLuaConversionError {
+                from: "integer",
+                to: String::from("git::Options"),
+                message: Some(format!(
+                    "rename_threshold must be between 0 and {}",
+                    Self::MAX_RENAME_THRESHOLD
+                )),
+            });
+        }
+
+        let options = Self {
+            renames,
+            rename_threshold,
+            ..Default::default()
+        };
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("{}", Options::default())]
+    #[case(
+        "{ renames = false }",
+        Options { renames: false, ..Default::default() },
+    )]
+    #[case(
+        "{ rename_threshold = 75 }",
+        Options { rename_threshold: Some(75), ..Default::default() },
+    )]
+    fn test_from_lua(#[case] chunk: &str, #[case] expected: Options) {
+        let lua = Lua::new();
+        let actual: Options = lua.load(chunk).eval().unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_from_lua_err() {
+        let lua = Lua::new();
+        let chunk = "{ rename_threshold = 101 }";
+        assert!(lua.load(chunk).eval::<Options>().is_err())
+    }
+}
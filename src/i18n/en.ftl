@@ -0,0 +1,68 @@
+# Messages in English.
+#
+# This is a subset of Fluent's syntax (https://projectfluent.org/). `{ $name }` is
+# replaced with an argument, and `{ $count -> [one] file *[other] files }` picks the
+# variant for the argument's plural category or exact value, where `*` marks the
+# default. Like in Fluent, literal braces are written as string literals, like
+# `{ "{" }`. Indented lines continue the message above them.
+
+## The summary after the tree
+
+summary = { $directories } { $directories -> [one] directory *[other] directories }, { $files } { $files -> [one] file *[other] files }
+summary-total-size = { $summary }, { $size } in total
+
+## Markers for entries that aren't written
+
+marker-truncated = … (truncated)
+marker-truncated-count = … { $count } more (truncated)
+marker-omitted = … (+{ $count } more)
+
+## Reports after the tree
+
+report-empty = Empty directories ({ $count }):
+report-orphans = Orphans ({ $count }):
+orphan-untracked = untracked
+orphan-ignored = ignored
+
+## Warnings and errors
+
+warning = warning: { $message }
+errors-while-writing = { $count } { $count -> [one] error *[other] errors } while writing the tree
+files-larger-than = Files larger than { $size }:
+files-larger-than-count = { $count } { $count -> [one] file *[other] files } larger than { $size }
+
+## The hint on the first run
+
+first-run-hint =
+    hint: No configuration files were found in `{ $dir }`.
+    hint: Run `fancy-tree --bootstrap` to create them, with comments that
+    hint: explain each option, or `fancy-tree --edit-config` to edit one.
+    hint: This is only shown once. Set { $var }=1 to hide hints.
+
+## Configuration files
+
+config-created = Created `{ $path }`
+config-kept = Kept the existing `{ $path }`
+config-saved-as = Saved `{ $path }` as `{ $backup }`
+config-restored = Restored the default `{ $path }`
+config-missing = `{ $path }` doesn't exist, so the default is used
+config-same-as-default = `{ $path }` is the same as the default
+config-error-count = { $count } { $count -> [one] error *[other] errors } in the configuration
+config-check-ok = ok
+config-check-errors = { $count } { $count -> [one] error *[other] errors }
+
+## Editing configuration files
+
+editor-opening = Opening `{ $path }`
+editor-errors = `{ $path }` has errors:
+editor-reopen = Reopen the editor?
+editor-error-count = { $count } { $count -> [one] error *[other] errors } in `{ $path }`
+editor-not-found = no editor was found. Tried:
+editor-not-set = not set
+editor-program-not-found = `{ $program }` not found
+editor-default-not-found = not found
+editor-not-found-hint = Set `{ $var }` to an editor, or use `--print-config-path` to find the file
+
+## The built-in pager
+
+pager-prompt = -- More: press Enter to continue, or q and Enter to quit --
@@ -0,0 +1,64 @@
+# Mensajes en español.
+#
+# See en.ftl for the syntax.
+
+## The summary after the tree
+
+summary = { $directories } { $directories -> [one] directorio *[other] directorios }, { $files } { $files -> [one] archivo *[other] archivos }
+summary-total-size = { $summary }, { $size } en total
+
+## Markers for entries that aren't written
+
+marker-truncated = … (truncado)
+marker-truncated-count = … { $count } más (truncado)
+marker-omitted = … (+{ $count } más)
+
+## Reports after the tree
+
+report-empty = Directorios vacíos ({ $count }):
+report-orphans = Huérfanos ({ $count }):
+orphan-untracked = sin seguimiento
+orphan-ignored = ignorado
+
+## Warnings and errors
+
+warning = advertencia: { $message }
+errors-while-writing = { $count } { $count -> [one] error *[other] errores } al escribir el árbol
+files-larger-than = Archivos de más de { $size }:
+files-larger-than-count = { $count } { $count -> [one] archivo *[other] archivos } de más de { $size }
+
+## The hint on the first run
+
+first-run-hint =
+    pista: No se encontraron archivos de configuración en `{ $dir }`.
+    pista: Ejecuta `fancy-tree --bootstrap` para crearlos, con comentarios que
+    pista: explican cada opción, o `fancy-tree --edit-config` para editar uno.
+    pista: Esto solo se muestra una vez. Define { $var }=1 para ocultar las pistas.
+
+## Configuration files
+
+config-created = Se creó `{ $path }`
+config-kept = Se conservó el archivo existente `{ $path }`
+config-saved-as = Se guardó `{ $path }` como `{ $backup }`
+config-restored = Se restauró el archivo predeterminado `{ $path }`
+config-missing = `{ $path }` no existe, así que se usa el predeterminado
+config-same-as-default = `{ $path }` es igual al predeterminado
+config-error-count = { $count } { $count -> [one] error *[other] errores } en la configuración
+config-check-ok = ok
+config-check-errors = { $count } { $count -> [one] error *[other] errores }
+
+## Editing configuration files
+
+editor-opening = Abriendo `{ $path }`
+editor-errors = `{ $path }` tiene errores:
+editor-reopen = ¿Volver a abrir el editor?
+editor-error-count = { $count } { $count -> [one] error *[other] errores } en `{ $path }`
+editor-not-found = no se encontró ningún editor. Se probó:
+editor-not-set = no está definida
+editor-program-not-found = no se encontró `{ $program }`
+editor-default-not-found = no se encontró
+editor-not-found-hint = Define `{ $var }` con un editor, o usa `--print-config-path` para encontrar el archivo
+
+## The built-in pager
+
+pager-prompt = -- Más: pulsa Enter para continuar, o q y Enter para salir --
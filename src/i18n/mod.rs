@@ -0,0 +1,311 @@
+//! Module for translating user-facing messages, like the summary and hints.
+//!
+//! Each language has a catalog of messages, like `en.ftl`, in a subset of Fluent's
+//! syntax. Messages that a catalog doesn't have are written in English.
+use clap::ValueEnum;
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Display;
+use std::sync::LazyLock;
+
+/// A parsed catalog, from message IDs to their patterns.
+type Catalog = HashMap<&'static str, String>;
+
+/// The English catalog, which has every message.
+static EN: LazyLock<Catalog> = LazyLock::new(|| parse(include_str!("./en.ftl")));
+/// The Spanish catalog.
+static ES: LazyLock<Catalog> = LazyLock::new(|| parse(include_str!("./es.ftl")));
+
+/// A language that messages can be written in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Lang {
+    /// English.
+    #[default]
+    En,
+    /// Spanish.
+    Es,
+}
+
+impl Lang {
+    /// Detects the language from the locale, like `LANG=es_ES.UTF-8`, using English
+    /// for locales that don't have a catalog.
+    pub fn detect() -> Self {
+        Self::detect_with(|name| env::var(name).ok())
+    }
+
+    /// Detects the language, using `var` to read environment variables.
+    fn detect_with<F>(var: F) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let var = |name| var(name).filter(|value| !value.is_empty());
+        // NOTE The first of these that's set decides the language of messages.
+        let locale = var("LC_ALL")
+            .or_else(|| var("LC_MESSAGES"))
+            .or_else(|| var("LANG"));
+        // NOTE Locales look like `es_ES.UTF-8`, `es-ES`, or `es`.
+        let language = locale
+            .as_deref()
+            .and_then(|locale| locale.split(['_', '-', '.', '@']).next())
+            .map(str::to_ascii_lowercase);
+        match language.as_deref() {
+            Some("es") => Self::Es,
+            _ => Self::En,
+        }
+    }
+
+    /// Gets a message, replacing its placeables with the arguments.
+    ///
+    /// Messages that this language's catalog doesn't have are written in English.
+    pub fn message(self, id: &str, args: &[(&str, &dyn Display)]) -> String {
+        let pattern = self.catalog().get(id).or_else(|| EN.get(id));
+        debug_assert!(pattern.is_some(), "Every message should be in en.ftl: {id}");
+        match pattern {
+            Some(pattern) => format(pattern, args),
+            None => String::from(id),
+        }
+    }
+
+    /// Gets this language's catalog.
+    fn catalog(self) -> &'static Catalog {
+        match self {
+            Self::En => &EN,
+            Self::Es => &ES,
+        }
+    }
+
+    /// Gets the plural category of a number, like `one` for `1`. Other values, like
+    /// paths, are always `other`.
+    fn plural_category(self, value: &str) -> &'static str {
+        // NOTE English and Spanish both only use `one` for exactly 1.
+        match self {
+            Self::En | Self::Es if value == "1" => "one",
+            Self::En | Self::Es => "other",
+        }
+    }
+}
+
+/// Parses a catalog. Comments start with `#`, messages look like `id = pattern`,
+/// and indented lines continue the message above them.
+fn parse(source: &'static str) -> Catalog {
+    let mut catalog = Catalog::new();
+    let mut current: Option<(&'static str, String)> = None;
+    for line in source.lines() {
+        if line.starts_with([' ', '\t']) && !line.trim().is_empty() {
+            if let Some((_, ref mut pattern)) = current {
+                if !pattern.is_empty() {
+                    pattern.push('\n');
+                }
+                pattern.push_str(line.trim());
+            }
+            continue;
+        }
+        if let Some((id, pattern)) = current.take() {
+            catalog.insert(id, pattern);
+        }
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some((id, pattern)) = line.split_once('=') {
+            current = Some((id.trim(), String::from(pattern.trim())));
+        }
+    }
+    if let Some((id, pattern)) = current {
+        catalog.insert(id, pattern);
+    }
+    catalog
+}
+
+/// Replaces the placeables of a pattern, like `{ $path }`, with the arguments.
+fn format(pattern: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut formatted = String::with_capacity(pattern.len());
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        let inner = &rest[start + 1..];
+        // NOTE A string literal, like `{ "}" }`, can contain braces.
+        let end = match string_literal(inner) {
+            Some((text, after)) => after
+                .find('}')
+                .filter(|end| after[..*end].trim().is_empty())
+                .map(|end| (text, inner.len() - after.len() + end)),
+            None => inner
+                .find('}')
+                .map(|end| (placeable(inner[..end].trim(), args), end)),
+        };
+        let Some((text, end)) = end else {
+            break;
+        };
+        formatted.push_str(&rest[..start]);
+        formatted.push_str(&text);
+        rest = &inner[end + 1..];
+    }
+    formatted.push_str(rest);
+    formatted
+}
+
+/// Reads a string literal at the start of a placeable, like `"{"`, and returns its
+/// text and what's after it. `\"` and `\\` are a quote and a backslash.
+fn string_literal(s: &str) -> Option<(String, &str)> {
+    let s = s.trim_start().strip_prefix('"')?;
+    let mut text = String::new();
+    let mut chars = s.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Some((text, &s[index + 1..])),
+            '\\' => text.push(chars.next()?.1),
+            c => text.push(c),
+        }
+    }
+    None
+}
+
+/// Gets the text of a placeable's expression, like `$path` or
+/// `$count -> [one] file *[other] files`.
+fn placeable(expression: &str, args: &[(&str, &dyn Display)]) -> String {
+    let (selector, variants) = match expression.split_once("->") {
+        Some((selector, variants)) => (selector.trim(), Some(variants)),
+        None => (expression, None),
+    };
+    let value = selector
+        .strip_prefix('$')
+        .and_then(|name| args.iter().find(|(arg, _)| *arg == name))
+        .map(|(_, value)| value.to_string());
+    // NOTE Missing arguments are written as they are, so that they're noticed.
+    let Some(value) = value else {
+        return format!("{{{expression}}}");
+    };
+    match variants {
+        Some(variants) => String::from(select(variants, &value)),
+        None => value,
+    }
+}
+
+/// Picks the variant whose key is the value, or else the value's plural category,
+/// or else the default variant.
+fn select<'a>(variants: &'a str, value: &str) -> &'a str {
+    // NOTE The plural rules of the supported languages are the same.
+    let category = Lang::En.plural_category(value);
+    let mut matched = None;
+    let mut default = "";
+    let mut rest = variants;
+    while let Some(open) = rest.find('[') {
+        let is_default = rest[..open].trim_end().ends_with('*');
+        let Some(close) = rest[open..].find(']').map(|close| open + close) else {
+            break;
+        };
+        let key = rest[open + 1..close].trim();
+        let body = &rest[close + 1..];
+        let next = body.find('[').unwrap_or(body.len());
+        let text = body[..next].trim_end().trim_end_matches('*').trim();
+        if key == value {
+            return text;
+        }
+        if key == category {
+            matched.get_or_insert(text);
+        }
+        if is_default {
+            default = text;
+        }
+        rest = &body[next..];
+    }
+    matched.unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(&[("LANG", "es_ES.UTF-8")], Lang::Es)]
+    #[case(&[("LANG", "es")], Lang::Es)]
+    #[case(&[("LC_ALL", "en_US.UTF-8"), ("LANG", "es_ES.UTF-8")], Lang::En)]
+    #[case(&[("LC_MESSAGES", "es_MX"), ("LANG", "en_US")], Lang::Es)]
+    #[case(&[("LC_ALL", ""), ("LANG", "es-AR")], Lang::Es)]
+    #[case(&[("LANG", "C")], Lang::En)]
+    #[case(&[("LANG", "ja_JP.UTF-8")], Lang::En)]
+    #[case(&[], Lang::En)]
+    fn test_detect(#[case] vars: &[(&str, &str)], #[case] expected: Lang) {
+        assert_eq!(expected, Lang::detect_with(testing::env(vars)));
+    }
+
+    #[rstest]
+    #[case(Lang::En, 1, 1, "1 directory, 1 file")]
+    #[case(Lang::En, 0, 2, "0 directories, 2 files")]
+    #[case(Lang::Es, 1, 3, "1 directorio, 3 archivos")]
+    fn test_summary(
+        #[case] lang: Lang,
+        #[case] directories: usize,
+        #[case] files: usize,
+        #[case] expected: &str,
+    ) {
+        let summary = lang.message(
+            "summary",
+            &[("directories", &directories), ("files", &files)],
+        );
+        assert_eq!(expected, summary);
+    }
+
+    #[test]
+    fn test_parse() {
+        let catalog = parse(
+            "# A comment\n\nsingle = One line\nmulti =\n    First\n    Second\nafter = { $a }\n",
+        );
+        assert_eq!("One line", catalog["single"]);
+        assert_eq!("First\nSecond", catalog["multi"]);
+        assert_eq!("{ $a }", catalog["after"]);
+    }
+
+    #[rstest]
+    #[case("Opening `{ $path }`", "Opening `a.lua`")]
+    #[case("{ $count -> [0] none [one] one *[other] many }", "many")]
+    #[case("{ $missing }", "{$missing}")]
+    #[case("{ $path", "{ $path")]
+    #[case(r#"{ "{" }{ $path }{ "}" }"#, "{a.lua}")]
+    #[case(r#"{"{ $path }"}"#, "{ $path }")]
+    #[case(r#"{ "\"\\" }"#, r#""\"#)]
+    #[case(r#"{ "open"#, r#"{ "open"#)]
+    fn test_format(#[case] pattern: &str, #[case] expected: &str) {
+        let args: [(&str, &dyn Display); 2] = [("path", &"a.lua"), ("count", &2)];
+        assert_eq!(expected, format(pattern, &args));
+    }
+
+    #[rstest]
+    #[case("0", "none")]
+    #[case("1", "one")]
+    #[case("2", "many")]
+    fn test_select(#[case] value: &str, #[case] expected: &str) {
+        assert_eq!(expected, select("[0] none [one] one *[other] many", value));
+    }
+
+    #[test]
+    fn test_catalogs() {
+        for (lang, catalog) in [(Lang::Es, &*ES)] {
+            for id in catalog.keys() {
+                assert!(EN.contains_key(id), "{lang:?} has an unknown message {id}");
+            }
+            for (id, pattern) in EN.iter() {
+                let translated = catalog.get(id);
+                assert!(translated.is_some(), "{lang:?} is missing {id}");
+                // NOTE Translations use the same arguments as English.
+                let arguments = |pattern: &str| {
+                    let mut arguments = pattern
+                        .split('$')
+                        .skip(1)
+                        .map(|rest| rest.split([' ', '}']).next().unwrap_or_default())
+                        .map(String::from)
+                        .collect::<Vec<_>>();
+                    arguments.sort();
+                    arguments.dedup();
+                    arguments
+                };
+                assert_eq!(
+                    arguments(pattern),
+                    arguments(translated.unwrap()),
+                    "{lang:?} {id}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Module for the ASCII icons, for terminals and fonts without any symbols.
+use super::Kind;
+
+/// Gets the ASCII icon for a kind of entry.
+///
+/// The kinds of entries use the same characters as `ls -F`, like `/` for
+/// directories, and the other icons are a letter for what the file is. Special
+/// directories, like `src`, are still `/`. Source code
+/// in any language is `#`.
+pub(super) const fn icon(kind: Kind) -> &'static str {
+    match kind {
+        Kind::File => "-",
+        Kind::Executable => "*",
+        Kind::Directory => "/",
+        Kind::OpenDirectory => "/",
+        Kind::EmptyDirectory => "/",
+        Kind::Symlink => "@",
+        Kind::BrokenSymlink => "!",
+
+        Kind::Archive => "z",
+        Kind::Audio => "a",
+        Kind::Backup => "~",
+        Kind::Binary => "b",
+        Kind::Certificate => "k",
+        Kind::Config => "c",
+        Kind::Database => "d",
+        Kind::Diff => "+",
+        Kind::Doc => "i",
+        Kind::Document => "t",
+        Kind::Font => "f",
+        Kind::Image => "p",
+        Kind::Key => "k",
+        Kind::License => "l",
+        Kind::Lock => "k",
+        Kind::Log => "t",
+        Kind::Pdf => "t",
+        Kind::Presentation => "t",
+        Kind::Spreadsheet => "t",
+        Kind::Text => "t",
+        Kind::Video => "v",
+
+        Kind::Json | Kind::Toml | Kind::Xml | Kind::Yaml => "c",
+        Kind::Markdown => "t",
+
+        Kind::Docker | Kind::Gradle | Kind::Make | Kind::Nix | Kind::Npm | Kind::Terraform => "m",
+
+        Kind::BuildDirectory
+        | Kind::ConfigDirectory
+        | Kind::DocsDirectory
+        | Kind::GitDirectory
+        | Kind::SourceDirectory
+        | Kind::TestDirectory => "/",
+
+        Kind::EditorConfig => "c",
+        Kind::Git => "g",
+        Kind::GitHub => "/",
+        Kind::GitLab => "g",
+        Kind::NodeModules => "/",
+        Kind::VsCode => "/",
+
+        Kind::Code
+        | Kind::C
+        | Kind::Clojure
+        | Kind::Cpp
+        | Kind::CSharp
+        | Kind::Css
+        | Kind::Dart
+        | Kind::Elixir
+        | Kind::Elm
+        | Kind::Erlang
+        | Kind::FSharp
+        | Kind::Fortran
+        | Kind::Go
+        | Kind::Groovy
+        | Kind::Haskell
+        | Kind::Html
+        | Kind::Java
+        | Kind::JavaScript
+        | Kind::Julia
+        | Kind::Kotlin
+        | Kind::Lua
+        | Kind::OCaml
+        | Kind::Perl
+        | Kind::Php
+        | Kind::PowerShell
+        | Kind::Python
+        | Kind::R
+        | Kind::Ruby
+        | Kind::Rust
+        | Kind::Sass
+        | Kind::Scala
+        | Kind::Shell
+        | Kind::Sql
+        | Kind::Svelte
+        | Kind::Swift
+        | Kind::TypeScript
+        | Kind::Vim
+        | Kind::Vue
+        | Kind::Zig => "#",
+    }
+}
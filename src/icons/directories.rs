@@ -0,0 +1,84 @@
+//! Module for the icons of directories with special names, like `src`.
+use super::Kind;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// Directory names mapped to kinds, grouped by kind. Names are lowercase, and are
+/// matched ignoring case.
+///
+/// NOTE Kinds and the names of each kind should be in alphabetical order, ignoring
+///      any leading `.` or `_`, for easier code review.
+const NAMES: &[(Kind, &[&str])] = &[
+    (Kind::BuildDirectory, &["build", "dist", "out", "target"]),
+    (Kind::ConfigDirectory, &[".config", "config", "configs"]),
+    (Kind::DocsDirectory, &["doc", "docs", "documentation"]),
+    (Kind::GitDirectory, &[".git"]),
+    (Kind::GitHub, &[".github"]),
+    (Kind::GitLab, &[".gitlab"]),
+    (Kind::NodeModules, &["node_modules"]),
+    (Kind::Python, &["__pycache__", ".venv", "venv"]),
+    (Kind::Rust, &[".cargo"]),
+    (Kind::SourceDirectory, &["lib", "source", "src"]),
+    (
+        Kind::TestDirectory,
+        &["spec", "specs", "test", "__tests__", "tests"],
+    ),
+    (Kind::VsCode, &[".vscode"]),
+];
+
+/// Gets every directory name and the kind it's mapped to.
+pub(super) fn rules() -> impl Iterator<Item = (&'static str, Kind)> {
+    NAMES
+        .iter()
+        .flat_map(|(kind, names)| names.iter().map(|name| (*name, *kind)))
+}
+
+/// Gets the kind of icon for a directory's name.
+pub(super) fn for_name(name: &str) -> Option<Kind> {
+    static INDEX: LazyLock<HashMap<&str, Kind>> = LazyLock::new(|| rules().collect());
+    let name = if name.bytes().any(|b| b.is_ascii_uppercase()) {
+        Cow::Owned(name.to_ascii_lowercase())
+    } else {
+        Cow::Borrowed(name)
+    };
+    INDEX.get(name.as_ref()).copied()
+}
+
+/// Gets every kind that a directory can have.
+#[cfg(test)]
+pub(super) fn kinds() -> impl Iterator<Item = Kind> {
+    NAMES.iter().map(|(kind, _)| *kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("src", Some(Kind::SourceDirectory))]
+    #[case("tests", Some(Kind::TestDirectory))]
+    #[case("Docs", Some(Kind::DocsDirectory))]
+    #[case("node_modules", Some(Kind::NodeModules))]
+    #[case(".github", Some(Kind::GitHub))]
+    #[case(".git", Some(Kind::GitDirectory))]
+    #[case("target", Some(Kind::BuildDirectory))]
+    #[case("src.rs", None)]
+    fn test_for_name(#[case] name: &str, #[case] expected: Option<Kind>) {
+        assert_eq!(expected, for_name(name));
+    }
+
+    #[test]
+    fn test_names_are_unique_and_lowercase() {
+        let names = NAMES
+            .iter()
+            .flat_map(|(_, names)| names.iter())
+            .collect::<Vec<_>>();
+        for name in names.iter() {
+            assert_eq!(name.to_ascii_lowercase(), **name);
+        }
+        let unique = names.iter().collect::<std::collections::HashSet<_>>();
+        assert_eq!(names.len(), unique.len());
+    }
+}
@@ -0,0 +1,97 @@
+//! Module for the emoji icons, for terminals without a Nerd Font.
+use super::Kind;
+
+/// Gets the emoji for a kind of entry.
+///
+/// Each emoji is displayed as an emoji by default, without a variation selector, so
+/// that terminals agree on its width. Languages without an emoji of their own share
+/// the one for source code.
+pub(super) const fn icon(kind: Kind) -> &'static str {
+    match kind {
+        Kind::File => "📄",
+        Kind::Executable => "⚡",
+        Kind::Directory => "📁",
+        Kind::OpenDirectory => "📂",
+        Kind::EmptyDirectory => "📂",
+        Kind::Symlink => "🔗",
+        Kind::BrokenSymlink => "💔",
+
+        Kind::Archive => "📦",
+        Kind::Audio => "🎵",
+        Kind::Backup => "🔙",
+        Kind::Binary => "🔢",
+        Kind::Certificate => "📛",
+        Kind::Config => "🔧",
+        Kind::Database => "💾",
+        Kind::Diff => "🩹",
+        Kind::Doc => "📖",
+        Kind::Document => "📝",
+        Kind::Font => "🔤",
+        Kind::Image => "📷",
+        Kind::Key => "🔑",
+        Kind::License => "📜",
+        Kind::Lock => "🔒",
+        Kind::Log => "🪵",
+        Kind::Pdf => "📕",
+        Kind::Presentation => "📋",
+        Kind::Spreadsheet => "📊",
+        Kind::Text => "📃",
+        Kind::Video => "🎬",
+
+        Kind::Json | Kind::Toml | Kind::Xml | Kind::Yaml => "🔧",
+        Kind::Markdown => "📝",
+
+        Kind::Docker => "🐳",
+        Kind::Gradle | Kind::Make => "🔨",
+        Kind::Nix => "🧊",
+        Kind::Npm | Kind::NodeModules => "📦",
+        Kind::Terraform => "🌍",
+
+        Kind::BuildDirectory => "🚧",
+        Kind::ConfigDirectory => "🔧",
+        Kind::DocsDirectory => "📚",
+        Kind::GitDirectory => "🔀",
+        Kind::SourceDirectory => "📁",
+        Kind::TestDirectory => "🧪",
+
+        Kind::EditorConfig => "🔧",
+        Kind::Git => "🔀",
+        Kind::GitHub => "🐙",
+        Kind::GitLab => "🦊",
+        Kind::VsCode => "💻",
+
+        Kind::Erlang | Kind::Elixir => "💧",
+        Kind::Go => "🐹",
+        Kind::Html => "🌐",
+        Kind::Css | Kind::Sass => "🎨",
+        Kind::Java | Kind::Kotlin | Kind::Groovy | Kind::Scala => "☕",
+        Kind::Lua => "🌙",
+        Kind::OCaml => "🐫",
+        Kind::Perl => "🐪",
+        Kind::Php => "🐘",
+        Kind::PowerShell | Kind::Shell => "🐚",
+        Kind::Python => "🐍",
+        Kind::R => "📈",
+        Kind::Ruby => "💎",
+        Kind::Rust => "🦀",
+        Kind::Sql => "💾",
+        Kind::Swift => "🐦",
+        Kind::Vue => "💚",
+        Kind::Zig => "🦎",
+        Kind::Code
+        | Kind::C
+        | Kind::Clojure
+        | Kind::Cpp
+        | Kind::CSharp
+        | Kind::Dart
+        | Kind::Elm
+        | Kind::FSharp
+        | Kind::Fortran
+        | Kind::Haskell
+        | Kind::JavaScript
+        | Kind::Julia
+        | Kind::Svelte
+        | Kind::TypeScript
+        | Kind::Vim => "📜",
+    }
+}
@@ -0,0 +1,196 @@
+//! Module for the kinds of entries that have their own icons.
+use serde::Serialize;
+
+/// What an icon represents, so that every icon set has an icon for the same
+/// entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[non_exhaustive]
+pub enum Kind {
+    /// A file without a more specific icon.
+    File,
+    /// An executable file.
+    Executable,
+    /// A directory whose entries aren't written, like when they're cut off by the
+    /// maximum level.
+    Directory,
+    /// A directory whose entries are written below it.
+    OpenDirectory,
+    /// A directory without any entries.
+    EmptyDirectory,
+    /// A symlink.
+    Symlink,
+    /// A symlink whose target doesn't exist.
+    BrokenSymlink,
+
+    /// An archive or a package, like `.zip` or `.deb`.
+    Archive,
+    /// Audio, like `.mp3`.
+    Audio,
+    /// A backup, like `.bak`.
+    Backup,
+    /// A compiled binary, like `.so` or `.class`.
+    Binary,
+    /// A certificate, like `.pem`.
+    Certificate,
+    /// Source code in a language without its own icon, like assembly.
+    Code,
+    /// A configuration file, like `.ini`.
+    Config,
+    /// A database, like `.sqlite`.
+    Database,
+    /// A diff or a patch.
+    Diff,
+    /// Documentation about a project, like a README or a changelog.
+    Doc,
+    /// A word processor document, like `.docx`.
+    Document,
+    /// A font, like `.ttf`.
+    Font,
+    /// An image, like `.png`.
+    Image,
+    /// A key, like `.gpg`.
+    Key,
+    /// A license.
+    License,
+    /// A lock file, like `package-lock.json`.
+    Lock,
+    /// A log.
+    Log,
+    /// A PDF.
+    Pdf,
+    /// A presentation, like `.pptx`.
+    Presentation,
+    /// A spreadsheet or a table, like `.xlsx` or `.csv`.
+    Spreadsheet,
+    /// Plain text.
+    Text,
+    /// A video, like `.mp4`.
+    Video,
+
+    /// JSON.
+    Json,
+    /// Markdown.
+    Markdown,
+    /// TOML.
+    Toml,
+    /// XML.
+    Xml,
+    /// YAML.
+    Yaml,
+
+    /// Docker files, like a `Dockerfile`.
+    Docker,
+    /// Gradle files.
+    Gradle,
+    /// Build files for make and similar tools, like a `Makefile`.
+    Make,
+    /// Nix files.
+    Nix,
+    /// npm files, like `package.json`.
+    Npm,
+    /// Terraform files.
+    Terraform,
+
+    /// A directory of build outputs, like `target`.
+    BuildDirectory,
+    /// A directory of configuration files, like `.config`.
+    ConfigDirectory,
+    /// A directory of documentation, like `docs`.
+    DocsDirectory,
+    /// A `.git` directory.
+    GitDirectory,
+    /// A directory of source code, like `src`.
+    SourceDirectory,
+    /// A directory of tests, like `tests`.
+    TestDirectory,
+
+    /// An `.editorconfig` file.
+    EditorConfig,
+    /// Git's files, like `.gitignore`.
+    Git,
+    /// The `.github` directory.
+    GitHub,
+    /// GitLab's files, like the `.gitlab` directory.
+    GitLab,
+    /// The `node_modules` directory.
+    NodeModules,
+    /// The `.vscode` directory.
+    VsCode,
+
+    /// C.
+    C,
+    /// Clojure.
+    Clojure,
+    /// C++.
+    Cpp,
+    /// C#.
+    CSharp,
+    /// CSS.
+    Css,
+    /// Dart.
+    Dart,
+    /// Elixir.
+    Elixir,
+    /// Elm.
+    Elm,
+    /// Erlang.
+    Erlang,
+    /// F#.
+    FSharp,
+    /// Fortran.
+    Fortran,
+    /// Go.
+    Go,
+    /// Groovy.
+    Groovy,
+    /// Haskell.
+    Haskell,
+    /// HTML.
+    Html,
+    /// Java.
+    Java,
+    /// JavaScript.
+    JavaScript,
+    /// Julia.
+    Julia,
+    /// Kotlin.
+    Kotlin,
+    /// Lua.
+    Lua,
+    /// OCaml.
+    OCaml,
+    /// Perl.
+    Perl,
+    /// PHP.
+    Php,
+    /// PowerShell.
+    PowerShell,
+    /// Python.
+    Python,
+    /// R.
+    R,
+    /// Ruby.
+    Ruby,
+    /// Rust.
+    Rust,
+    /// Sass and SCSS.
+    Sass,
+    /// Scala.
+    Scala,
+    /// Shell scripts.
+    Shell,
+    /// SQL.
+    Sql,
+    /// Svelte.
+    Svelte,
+    /// Swift.
+    Swift,
+    /// TypeScript.
+    TypeScript,
+    /// Vim script.
+    Vim,
+    /// Vue.
+    Vue,
+    /// Zig.
+    Zig,
+}
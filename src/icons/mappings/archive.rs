@@ -0,0 +1,41 @@
+//! Module for archives, packages, and compiled binaries.
+use super::Table;
+use crate::icons::Kind;
+
+/// Filenames of archives and binaries.
+pub(super) const FILENAMES: Table = &[];
+
+/// Extensions of archives and binaries.
+pub(super) const EXTENSIONS: Table = &[
+    (
+        Kind::Archive,
+        &[
+            "7z", "aab", "apk", "ar", "bz2", "cab", "cpio", "crate", "deb", "dmg", "ear", "gem",
+            "gz", "img", "iso", "jar", "lz", "lz4", "lzma", "nupkg", "rar", "rpm", "tar", "tbz2",
+            "tgz", "txz", "war", "whl", "xz", "zip", "zst",
+        ],
+    ),
+    (
+        Kind::Binary,
+        &[
+            "a", "bin", "class", "dll", "dylib", "elf", "exe", "ko", "lib", "o", "obj", "pyc",
+            "pyo", "so", "wasm",
+        ],
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::icons::{Kind, kind_for_path};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("release.zip", Kind::Archive)]
+    #[case("fancy-tree.deb", Kind::Archive)]
+    #[case("release.tar.zst", Kind::Archive)]
+    #[case("libfancy_tree.so", Kind::Binary)]
+    #[case("module.wasm", Kind::Binary)]
+    fn test_kind(#[case] path: &str, #[case] expected: Kind) {
+        assert_eq!(Some(expected), kind_for_path(path));
+    }
+}
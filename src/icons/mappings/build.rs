@@ -0,0 +1,86 @@
+//! Module for build tools and package managers.
+use super::Table;
+use crate::icons::Kind;
+
+/// Filenames of build tools.
+pub(super) const FILENAMES: Table = &[
+    (
+        Kind::Docker,
+        &[
+            "compose.yaml",
+            "compose.yml",
+            "Containerfile",
+            "docker-compose.yaml",
+            "docker-compose.yml",
+            ".dockerignore",
+            "Dockerfile",
+        ],
+    ),
+    (
+        Kind::Gradle,
+        &[
+            "build.gradle",
+            "build.gradle.kts",
+            "gradlew",
+            "gradlew.bat",
+            "settings.gradle",
+            "settings.gradle.kts",
+        ],
+    ),
+    (
+        Kind::Make,
+        &[
+            "BUILD",
+            "BUILD.bazel",
+            "build.ninja",
+            "CMakeLists.txt",
+            "configure",
+            "GNUmakefile",
+            "justfile",
+            ".justfile",
+            "Justfile",
+            "makefile",
+            "Makefile",
+            "meson.build",
+            "meson_options.txt",
+            "MODULE.bazel",
+            "WORKSPACE",
+        ],
+    ),
+    (
+        Kind::Nix,
+        &["default.nix", "flake.lock", "flake.nix", "shell.nix"],
+    ),
+    (
+        Kind::Npm,
+        &[".npmignore", ".npmrc", ".nvmrc", "package.json"],
+    ),
+];
+
+/// Extensions of build tools.
+pub(super) const EXTENSIONS: Table = &[
+    (Kind::Docker, &["dockerfile"]),
+    (Kind::Gradle, &["gradle"]),
+    (Kind::Make, &["bazel", "bzl", "cmake", "mak", "mk", "ninja"]),
+    (Kind::Nix, &["nix"]),
+    (Kind::Terraform, &["hcl", "tf", "tfvars"]),
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::icons::{Kind, kind_for_path};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("Dockerfile", Kind::Docker)]
+    #[case("app.dockerfile", Kind::Docker)]
+    #[case("Makefile", Kind::Make)]
+    #[case("CMakeLists.txt", Kind::Make)]
+    #[case("build.gradle.kts", Kind::Gradle)]
+    #[case("flake.lock", Kind::Nix)]
+    #[case("package.json", Kind::Npm)]
+    #[case("main.tf", Kind::Terraform)]
+    fn test_kind(#[case] path: &str, #[case] expected: Kind) {
+        assert_eq!(Some(expected), kind_for_path(path));
+    }
+}
@@ -0,0 +1,69 @@
+//! Module for data, configuration, and keys.
+use super::Table;
+use crate::icons::Kind;
+
+/// Filenames of data and configuration files.
+pub(super) const FILENAMES: Table = &[
+    (
+        Kind::Config,
+        &[
+            ".clang-format",
+            ".clang-tidy",
+            ".env",
+            ".envrc",
+            ".eslintrc",
+            ".prettierrc",
+        ],
+    ),
+    (
+        Kind::Lock,
+        &["package-lock.json", "pnpm-lock.yaml", "yarn.lock"],
+    ),
+];
+
+/// Extensions of data and configuration files.
+pub(super) const EXTENSIONS: Table = &[
+    (Kind::Backup, &["bak", "old", "orig", "swp"]),
+    (
+        Kind::Certificate,
+        &["cer", "crt", "csr", "der", "p12", "pem", "pfx"],
+    ),
+    (
+        Kind::Config,
+        &["cfg", "conf", "config", "env", "ini", "properties"],
+    ),
+    (Kind::Database, &["accdb", "db", "mdb", "sqlite", "sqlite3"]),
+    (Kind::Diff, &["diff", "patch"]),
+    (
+        Kind::Json,
+        &["geojson", "json", "json5", "jsonc", "jsonl", "webmanifest"],
+    ),
+    (Kind::Key, &["asc", "gpg", "kdbx", "pub"]),
+    (Kind::Lock, &["lock"]),
+    (Kind::Log, &["log"]),
+    (Kind::Toml, &["toml"]),
+    (Kind::Xml, &["plist", "xml", "xsd", "xsl", "xslt"]),
+    (Kind::Yaml, &["yaml", "yml"]),
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::icons::{Kind, kind_for_path};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("tsconfig.base.json", Kind::Json)]
+    #[case("config.toml", Kind::Toml)]
+    #[case("ci.yml", Kind::Yaml)]
+    #[case("settings.ini", Kind::Config)]
+    #[case(".env", Kind::Config)]
+    #[case("yarn.lock", Kind::Lock)]
+    #[case("server.pem", Kind::Certificate)]
+    #[case("id_ed25519.pub", Kind::Key)]
+    #[case("fix.patch", Kind::Diff)]
+    #[case("app.log", Kind::Log)]
+    #[case("data.db", Kind::Database)]
+    fn test_kind(#[case] path: &str, #[case] expected: Kind) {
+        assert_eq!(Some(expected), kind_for_path(path));
+    }
+}
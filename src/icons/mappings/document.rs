@@ -0,0 +1,78 @@
+//! Module for documents and a project's documentation.
+use super::Table;
+use crate::icons::Kind;
+
+/// Filenames of documentation.
+pub(super) const FILENAMES: Table = &[
+    (
+        Kind::Doc,
+        &[
+            "AUTHORS",
+            "CHANGELOG",
+            "CHANGELOG.md",
+            "CHANGES.md",
+            "CODE_OF_CONDUCT.md",
+            "CONTRIBUTING.md",
+            "HISTORY.md",
+            "NOTICE",
+            "README",
+            "README.md",
+            "README.rst",
+            "README.txt",
+            "SECURITY.md",
+        ],
+    ),
+    (
+        Kind::License,
+        &[
+            "COPYING",
+            "COPYING.LESSER",
+            "LICENCE",
+            "LICENCE.md",
+            "LICENCE.txt",
+            "LICENSE",
+            "LICENSE.md",
+            "LICENSE.txt",
+            "licence",
+            "license",
+            "UNLICENSE",
+        ],
+    ),
+];
+
+/// Extensions of documents.
+pub(super) const EXTENSIONS: Table = &[
+    (Kind::Doc, &["adoc", "asciidoc", "bib", "org", "rst", "tex"]),
+    (
+        Kind::Document,
+        &["doc", "docx", "epub", "mobi", "odt", "pages", "rtf"],
+    ),
+    (Kind::Markdown, &["markdown", "md", "mdx"]),
+    (Kind::Pdf, &["pdf"]),
+    (Kind::Presentation, &["odp", "ppt", "pptx"]),
+    (
+        Kind::Spreadsheet,
+        &["csv", "numbers", "ods", "tsv", "xls", "xlsx"],
+    ),
+    (Kind::Text, &["text", "txt"]),
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::icons::{Kind, kind_for_path};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("README.md", Kind::Doc)]
+    #[case("CHANGELOG.md", Kind::Doc)]
+    #[case("COPYING", Kind::License)]
+    #[case("guide.md", Kind::Markdown)]
+    #[case("manual.pdf", Kind::Pdf)]
+    #[case("letter.docx", Kind::Document)]
+    #[case("slides.pptx", Kind::Presentation)]
+    #[case("table.csv", Kind::Spreadsheet)]
+    #[case("notes.txt", Kind::Text)]
+    fn test_kind(#[case] path: &str, #[case] expected: Kind) {
+        assert_eq!(Some(expected), kind_for_path(path));
+    }
+}
@@ -0,0 +1,28 @@
+//! Module for fonts.
+use super::Table;
+use crate::icons::Kind;
+
+/// Filenames of fonts.
+pub(super) const FILENAMES: Table = &[];
+
+/// Extensions of fonts.
+pub(super) const EXTENSIONS: Table = &[(
+    Kind::Font,
+    &[
+        "eot", "fnt", "fon", "otf", "pfb", "pfm", "ttc", "ttf", "woff", "woff2",
+    ],
+)];
+
+#[cfg(test)]
+mod tests {
+    use crate::icons::{Kind, kind_for_path};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("FiraCode-Regular.ttf")]
+    #[case("Inter.otf")]
+    #[case("icons.woff2")]
+    fn test_kind(#[case] path: &str) {
+        assert_eq!(Some(Kind::Font), kind_for_path(path));
+    }
+}
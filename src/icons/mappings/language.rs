@@ -0,0 +1,129 @@
+//! Module for programming languages and their projects' files.
+use super::Table;
+use crate::icons::Kind;
+
+/// Filenames of languages' projects, like their package manifests.
+pub(super) const FILENAMES: Table = &[
+    (Kind::Dart, &["pubspec.lock", "pubspec.yaml"]),
+    (Kind::Elixir, &["mix.lock"]),
+    (Kind::Go, &["go.mod", "go.sum", "go.work"]),
+    (Kind::Haskell, &["cabal.project", "stack.yaml"]),
+    (Kind::Php, &["composer.json", "composer.lock"]),
+    (
+        Kind::Python,
+        &[
+            "Pipfile",
+            "Pipfile.lock",
+            "poetry.lock",
+            "pyproject.toml",
+            "requirements.txt",
+            "setup.cfg",
+            "setup.py",
+            "uv.lock",
+        ],
+    ),
+    (
+        Kind::Ruby,
+        &["Gemfile", "Gemfile.lock", "Rakefile", ".rubocop.yml"],
+    ),
+    (
+        Kind::Rust,
+        &[
+            "Cargo.lock",
+            "Cargo.toml",
+            "clippy.toml",
+            ".rustfmt.toml",
+            "rustfmt.toml",
+            "rust-toolchain",
+            "rust-toolchain.toml",
+        ],
+    ),
+    (
+        Kind::Shell,
+        &[
+            ".bash_profile",
+            ".bashrc",
+            ".profile",
+            ".zprofile",
+            ".zshenv",
+            ".zshrc",
+        ],
+    ),
+    (Kind::TypeScript, &["tsconfig.json"]),
+    (Kind::Vim, &["_gvimrc", ".gvimrc", "_vimrc", ".vimrc"]),
+    (Kind::Zig, &["build.zig", "build.zig.zon"]),
+];
+
+/// Extensions of source code.
+pub(super) const EXTENSIONS: Table = &[
+    (Kind::C, &["c", "h", "m"]),
+    (Kind::Clojure, &["clj", "cljc", "cljs", "edn"]),
+    (Kind::Code, &["asm", "s"]),
+    (
+        Kind::Cpp,
+        &[
+            "c++", "cc", "cpp", "cxx", "h++", "hh", "hpp", "hxx", "ino", "mm",
+        ],
+    ),
+    (Kind::CSharp, &["cs", "csx"]),
+    (Kind::Css, &["css", "less", "styl"]),
+    (Kind::Dart, &["dart"]),
+    (Kind::Elixir, &["ex", "exs", "heex"]),
+    (Kind::Elm, &["elm"]),
+    (Kind::Erlang, &["erl", "hrl"]),
+    (Kind::FSharp, &["fs", "fsi", "fsx"]),
+    (Kind::Fortran, &["f", "f03", "f90", "f95", "for"]),
+    (Kind::Go, &["go"]),
+    (Kind::Groovy, &["groovy", "gvy"]),
+    (Kind::Haskell, &["hs", "lhs"]),
+    (Kind::Html, &["htm", "html", "xhtml"]),
+    (Kind::Java, &["java"]),
+    (Kind::JavaScript, &["cjs", "js", "jsx", "mjs"]),
+    (Kind::Julia, &["jl"]),
+    (Kind::Kotlin, &["kt", "kts"]),
+    (Kind::Lua, &["lua", "luau", "rockspec"]),
+    (Kind::OCaml, &["ml", "mli"]),
+    (Kind::Perl, &["pl", "pm", "t"]),
+    (Kind::Php, &["php"]),
+    (Kind::PowerShell, &["ps1", "psd1", "psm1"]),
+    (Kind::Python, &["ipynb", "py", "pyi", "pyw"]),
+    (Kind::R, &["r", "rmd"]),
+    (Kind::Ruby, &["erb", "gemspec", "rake", "rb"]),
+    (Kind::Rust, &["rs"]),
+    (Kind::Sass, &["sass", "scss"]),
+    (Kind::Scala, &["sbt", "sc", "scala"]),
+    (Kind::Shell, &["bash", "fish", "ksh", "sh", "zsh"]),
+    (Kind::Sql, &["sql"]),
+    (Kind::Svelte, &["svelte"]),
+    (Kind::Swift, &["swift"]),
+    (Kind::TypeScript, &["cts", "mts", "ts", "tsx"]),
+    (Kind::Vim, &["vim"]),
+    (Kind::Vue, &["vue"]),
+    (Kind::Zig, &["zig", "zon"]),
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::icons::{Kind, kind_for_path};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("main.rs", Kind::Rust)]
+    #[case("Cargo.toml", Kind::Rust)]
+    #[case("main.go", Kind::Go)]
+    #[case("go.mod", Kind::Go)]
+    #[case("app.py", Kind::Python)]
+    #[case("pyproject.toml", Kind::Python)]
+    #[case("index.d.ts", Kind::TypeScript)]
+    #[case("tsconfig.json", Kind::TypeScript)]
+    #[case("App.tsx", Kind::TypeScript)]
+    #[case("main.c", Kind::C)]
+    #[case("main.cpp", Kind::Cpp)]
+    #[case("install.sh", Kind::Shell)]
+    #[case(".zshrc", Kind::Shell)]
+    #[case("init.lua", Kind::Lua)]
+    #[case("boot.s", Kind::Code)]
+    fn test_kind(#[case] path: &str, #[case] expected: Kind) {
+        assert_eq!(Some(expected), kind_for_path(path));
+    }
+}
@@ -0,0 +1,47 @@
+//! Module for images, audio, and video.
+use super::Table;
+use crate::icons::Kind;
+
+/// Filenames of media.
+pub(super) const FILENAMES: Table = &[];
+
+/// Extensions of media.
+pub(super) const EXTENSIONS: Table = &[
+    (
+        Kind::Audio,
+        &[
+            "aac", "aif", "aiff", "alac", "flac", "m4a", "mid", "midi", "mp3", "oga", "ogg",
+            "opus", "wav", "wma",
+        ],
+    ),
+    (
+        Kind::Image,
+        &[
+            "avif", "bmp", "cr2", "dng", "gif", "heic", "heif", "icns", "ico", "jpeg", "jpg",
+            "jxl", "nef", "png", "psd", "raw", "svg", "tif", "tiff", "webp", "xcf",
+        ],
+    ),
+    (
+        Kind::Video,
+        &[
+            "3gp", "avi", "flv", "m4v", "mkv", "mov", "mp4", "mpeg", "mpg", "ogv", "webm", "wmv",
+        ],
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::icons::{Kind, kind_for_path};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("song.flac", Kind::Audio)]
+    #[case("song.mp3", Kind::Audio)]
+    #[case("logo.svg", Kind::Image)]
+    #[case("IMG_0001.HEIC", Kind::Image)]
+    #[case("clip.mkv", Kind::Video)]
+    #[case("clip.webm", Kind::Video)]
+    fn test_kind(#[case] path: &str, #[case] expected: Kind) {
+        assert_eq!(Some(expected), kind_for_path(path));
+    }
+}
@@ -0,0 +1,135 @@
+//! Module for the built-in mappings from names to kinds of icons, with a module for
+//! each category of files.
+//!
+//! Each category has a table of filenames and a table of extensions, grouped by
+//! kind. A name should only be in one table.
+use super::Kind;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+mod archive;
+mod build;
+mod data;
+mod document;
+mod font;
+mod language;
+mod media;
+mod project;
+
+/// Names mapped to kinds, grouped by kind.
+///
+/// NOTE Kinds and the names of each kind should be in alphabetical order, ignoring
+///      any leading `.`, for easier code review.
+type Table = &'static [(Kind, &'static [&'static str])];
+
+/// The filename tables of each category.
+const FILENAMES: [Table; 8] = [
+    archive::FILENAMES,
+    build::FILENAMES,
+    data::FILENAMES,
+    document::FILENAMES,
+    font::FILENAMES,
+    language::FILENAMES,
+    media::FILENAMES,
+    project::FILENAMES,
+];
+
+/// The extension tables of each category. Extensions are lowercase, without the
+/// leading `.`.
+const EXTENSIONS: [Table; 8] = [
+    archive::EXTENSIONS,
+    build::EXTENSIONS,
+    data::EXTENSIONS,
+    document::EXTENSIONS,
+    font::EXTENSIONS,
+    language::EXTENSIONS,
+    media::EXTENSIONS,
+    project::EXTENSIONS,
+];
+
+/// Indexes tables by name.
+fn index(tables: &'static [Table]) -> HashMap<&'static str, Kind> {
+    entries(tables).collect()
+}
+
+/// Gets the kind of icon for a filename.
+pub(super) fn for_filename(filename: &str) -> Option<Kind> {
+    static INDEX: LazyLock<HashMap<&str, Kind>> = LazyLock::new(|| index(&FILENAMES));
+    INDEX.get(filename).copied()
+}
+
+/// Gets the kind of icon for a file extension, ignoring its case.
+pub(super) fn for_extension(extension: &str) -> Option<Kind> {
+    static INDEX: LazyLock<HashMap<&str, Kind>> = LazyLock::new(|| index(&EXTENSIONS));
+    // NOTE Extensions are sometimes uppercase, like photos from cameras.
+    let extension = if extension.bytes().any(|b| b.is_ascii_uppercase()) {
+        Cow::Owned(extension.to_ascii_lowercase())
+    } else {
+        Cow::Borrowed(extension)
+    };
+    INDEX.get(extension.as_ref()).copied()
+}
+
+/// Gets every filename and the kind it's mapped to.
+pub(super) fn filenames() -> impl Iterator<Item = (&'static str, Kind)> {
+    entries(&FILENAMES)
+}
+
+/// Gets every extension and the kind it's mapped to.
+pub(super) fn extensions() -> impl Iterator<Item = (&'static str, Kind)> {
+    entries(&EXTENSIONS)
+}
+
+/// Gets every name in the tables and the kind it's mapped to.
+fn entries(tables: &'static [Table]) -> impl Iterator<Item = (&'static str, Kind)> {
+    tables
+        .iter()
+        .flat_map(|table| table.iter())
+        .flat_map(|(kind, names)| names.iter().map(|name| (*name, *kind)))
+}
+
+/// Gets every kind that a table maps to.
+#[cfg(test)]
+pub(super) fn kinds() -> impl Iterator<Item = Kind> {
+    FILENAMES
+        .into_iter()
+        .chain(EXTENSIONS)
+        .flat_map(|table| table.iter().map(|(kind, _)| *kind))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(&FILENAMES)]
+    #[case(&EXTENSIONS)]
+    fn test_names_are_unique(#[case] tables: &'static [Table]) {
+        let count = tables
+            .iter()
+            .flat_map(|table| table.iter())
+            .map(|(_, names)| names.len())
+            .sum::<usize>();
+        assert_eq!(count, index(tables).len());
+    }
+
+    #[test]
+    fn test_extensions_are_lowercase() {
+        for (_, extensions) in EXTENSIONS.iter().flat_map(|table| table.iter()) {
+            for extension in extensions.iter() {
+                assert_eq!(extension.to_ascii_lowercase(), *extension);
+                assert!(!extension.starts_with('.'), "{extension}");
+            }
+        }
+    }
+
+    #[rstest]
+    #[case("JPG", Some(Kind::Image))]
+    #[case("Txt", Some(Kind::Text))]
+    #[case("unknown", None)]
+    fn test_for_extension(#[case] extension: &str, #[case] expected: Option<Kind>) {
+        assert_eq!(expected, for_extension(extension));
+    }
+}
@@ -0,0 +1,39 @@
+//! Module for the files of version control, forges, and editors. Their directories,
+//! like `.github`, have their own icons.
+use super::Table;
+use crate::icons::Kind;
+
+/// Filenames of version control and editor files.
+pub(super) const FILENAMES: Table = &[
+    (Kind::EditorConfig, &[".editorconfig"]),
+    (
+        Kind::Git,
+        &[
+            ".git",
+            ".gitattributes",
+            ".git-blame-ignore-revs",
+            ".gitignore",
+            ".gitkeep",
+            ".gitmodules",
+            ".mailmap",
+        ],
+    ),
+    (Kind::GitLab, &[".gitlab-ci.yml"]),
+];
+
+/// Extensions of version control and editor files.
+pub(super) const EXTENSIONS: Table = &[];
+
+#[cfg(test)]
+mod tests {
+    use crate::icons::{Kind, kind_for_path};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(".gitignore", Kind::Git)]
+    #[case(".mailmap", Kind::Git)]
+    #[case(".gitlab-ci.yml", Kind::GitLab)]
+    fn test_kind(#[case] path: &str, #[case] expected: Kind) {
+        assert_eq!(Some(expected), kind_for_path(path));
+    }
+}
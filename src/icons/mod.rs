@@ -1,17 +1,47 @@
 //! Provides icons for filepaths.
 use crate::ext::PathExt as _;
+use crate::rules::{Matcher, Rule};
 use std::path::Path;
 use std::sync::LazyLock;
 
-/// Gets an icon for a path.
+pub use kind::Kind;
+pub use set::IconSet;
+
+mod ascii;
+mod directories;
+mod emoji;
+mod kind;
+mod mappings;
+mod nerd;
+mod set;
+
+/// Gets the Nerd Font icon for a path.
 pub fn for_path<P>(path: P) -> Option<&'static str>
+where
+    P: AsRef<Path>,
+{
+    kind_for_path(path).map(nerd::icon)
+}
+
+/// Gets the kind of icon for a path.
+pub fn kind_for_path<P>(path: P) -> Option<Kind>
+where
+    P: AsRef<Path>,
+{
+    match_path(path).map(|(_, kind)| kind)
+}
+
+/// Gets the kind of icon for a path, and what the path matched to get it.
+pub(crate) fn match_path<P>(path: P) -> Option<(Matcher, Kind)>
 where
     P: AsRef<Path>,
 {
     let path = path.as_ref();
+    let matched = |matcher| move |kind| (matcher, kind);
     path.file_name()
         .and_then(|s| s.to_str())
-        .and_then(for_filename)
+        .and_then(mappings::for_filename)
+        .map(matched(Matcher::Filename))
         .or_else(|| {
             path.double_extension()
                 .and_then(|(prefix, suffix)| {
@@ -20,79 +50,104 @@ where
                         .and_then(|prefix| suffix.to_str().map(|suffix| (prefix, suffix)))
                 })
                 .and_then(for_double_extension)
+                .map(matched(Matcher::DoubleExtension))
         })
         .or_else(|| {
             path.extension()
                 .and_then(|extension| extension.to_str())
-                .and_then(for_extension)
+                .and_then(mappings::for_extension)
+                .map(matched(Matcher::Extension))
         })
-        .or_else(|| for_filename_glob(path))
+        .or_else(|| for_filename_glob(path).map(matched(Matcher::Glob)))
 }
 
-/// Gets an icon for a filename.
-fn for_filename(filename: &str) -> Option<&'static str> {
-    // NOTE These should be in alphabetical order and ignoring any leading `.` for
-    //      easier code review.
-    let icon = match filename {
-        "CONTRIBUTING.md" => shared::DOC,
-        ".editorconfig" => "\u{e652}", // 
-        ".git" | ".gitattributes" | ".gitignore" | ".gitmodules" | ".git-blame-ignore-revs" => {
-            "\u{e702}"
-        } // 
-        ".github" => "\u{e709}",       // 
-        "LICENCE" | "LICENSE" | "licence" | "license" => shared::LICENSE,
-        "package-lock.json" | "pnpm-lock.yaml" => shared::LOCK,
-        "README" | "README.md" => shared::DOC,
-        ".vscode" => "\u{e8da}", // 
-        _ => return None,
-    };
-    Some(icon)
+/// Gets the kind of icon for a directory from its name, like
+/// [`Kind::SourceDirectory`] for `src`.
+///
+/// Directories don't use the mappings of files, so a directory named like a file,
+/// like `build.rs`, only gets a directory icon.
+pub fn kind_for_directory<P>(path: P) -> Option<Kind>
+where
+    P: AsRef<Path>,
+{
+    path.as_ref()
+        .file_name()
+        .and_then(|s| s.to_str())
+        .and_then(directories::for_name)
 }
 
-/// Gets an icon for a file extension.
-fn for_extension(extension: &str) -> Option<&'static str> {
-    // NOTE These should be in alphabetical order for easier code review.
-    let icon = match extension {
-        "7z" | "tar" | "zip" => shared::ARCHIVE,
-        "bak" => "\u{f006f}", // 󰁯
-        "cfg" => "\u{e615}",  // 
-        "gif" | "jpeg" | "jpg" | "png" => shared::IMAGE,
-        "lock" => shared::LOCK,
-        "sqlite" | "sqlite3" => shared::DATABASE,
-        _ => return None,
-    };
+/// Double extensions mapped to kinds, as the first extension and the extensions
+/// that can follow it.
+const DOUBLE_EXTENSIONS: &[(Kind, &str, &[&str])] = &[
+    (
+        Kind::Archive,
+        "tar",
+        &["bz2", "gz", "lz", "lz4", "xz", "zst"],
+    ),
+    (Kind::TypeScript, "d", &["ts"]),
+];
 
-    Some(icon)
-}
+/// Maps a raw glob pattern to a kind with `(glob, kind)` tuples.
+const GLOBS: &[(&str, Kind)] = &[
+    ("Dockerfile.*", Kind::Docker),
+    (".env.*", Kind::Config),
+    ("LICEN[CS]E-*", Kind::License),
+];
 
-/// Gets an icon for the double extension.
-fn for_double_extension(double_extension: (&str, &str)) -> Option<&'static str> {
-    let color = match double_extension {
-        ("tar", "gz") => shared::ARCHIVE,
-        _ => return None,
-    };
+/// Gets the built-in rules that map names to kinds of icons, in the order that
+/// they're checked.
+pub(crate) fn rules() -> impl Iterator<Item = Rule<Kind>> {
+    let double_extensions = DOUBLE_EXTENSIONS
+        .iter()
+        .flat_map(|(kind, prefix, suffixes)| {
+            suffixes.iter().map(move |suffix| {
+                Rule::new(
+                    Matcher::DoubleExtension,
+                    format!("{prefix}.{suffix}"),
+                    *kind,
+                )
+            })
+        });
+    let globs = GLOBS
+        .iter()
+        .map(|(glob, kind)| Rule::new(Matcher::Glob, *glob, *kind).ignore_case());
+    let directories = directories::rules()
+        .map(|(name, kind)| Rule::new(Matcher::Directory, name, kind).ignore_case());
+    mappings::filenames()
+        .map(|(name, kind)| Rule::new(Matcher::Filename, name, kind))
+        .chain(double_extensions)
+        .chain(
+            mappings::extensions()
+                .map(|(name, kind)| Rule::new(Matcher::Extension, name, kind).ignore_case()),
+        )
+        .chain(globs)
+        .chain(directories)
+}
 
-    Some(color)
+/// Gets the kind of icon for the double extension.
+fn for_double_extension((prefix, suffix): (&str, &str)) -> Option<Kind> {
+    DOUBLE_EXTENSIONS
+        .iter()
+        .find_map(|(kind, double_prefix, suffixes)| {
+            (*double_prefix == prefix && suffixes.contains(&suffix)).then_some(*kind)
+        })
 }
 
-/// Gets an icon based on a matching glob for a path.
-fn for_filename_glob(path: &Path) -> Option<&'static str> {
+/// Gets the kind of icon based on a matching glob for a path.
+fn for_filename_glob(path: &Path) -> Option<Kind> {
     use glob::{MatchOptions, Pattern};
 
-    /// Maps a raw glob pattern to an icon with `(glob, icon)` tuples.
-    const RAW_MAPPINGS: &[(&str, &str)] = &[("LICEN[CS]E-*", shared::LICENSE)];
-
     const OPTIONS: MatchOptions = MatchOptions {
         case_sensitive: false,
         require_literal_separator: false,
         require_literal_leading_dot: false,
     };
 
-    /// The compiled glob-to-icon mappings.
-    static COMPILED_MAPPINGS: LazyLock<Vec<(Pattern, &'static str)>> = LazyLock::new(|| {
-        RAW_MAPPINGS
+    /// The compiled glob-to-kind mappings.
+    static COMPILED_MAPPINGS: LazyLock<Vec<(Pattern, Kind)>> = LazyLock::new(|| {
+        GLOBS
             .iter()
-            .map(|(raw, icon)| (Pattern::new(raw).expect("Pattern should be valid"), *icon))
+            .map(|(raw, kind)| (Pattern::new(raw).expect("Pattern should be valid"), *kind))
             .collect()
     });
 
@@ -100,42 +155,29 @@ fn for_filename_glob(path: &Path) -> Option<&'static str> {
     path.file_name().and_then(|s| s.to_str()).and_then(|path| {
         COMPILED_MAPPINGS
             .iter()
-            .find_map(|(glob, icon)| glob.matches_with(path, OPTIONS).then_some(*icon))
+            .find_map(|(glob, kind)| glob.matches_with(path, OPTIONS).then_some(*kind))
     })
 }
 
-/// Icons that represent one file type, but have multiple filenames and/or extensions
-/// for that file type.
-mod shared {
-    /// Icon for archive files, like `.zip` or `.tar.gz`.
-    pub const ARCHIVE: &str = "\u{ea98}"; // 
-    /// Icon for database files.
-    pub const DATABASE: &str = "\u{e706}"; // 
-    /// Icon for documentation files, like READMEs.
-    pub const DOC: &str = "\u{eaa4}"; // 
-    /// Icon for license files.
-    pub const LICENSE: &str = "\u{e60a}"; // 
-    /// Icon for lock files.
-    pub const LOCK: &str = "\u{e672}"; // 
-    /// Icon for image files.
-    pub const IMAGE: &str = "\u{f1c5}"; // 
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use rstest::rstest;
 
     #[rstest]
-    #[case("example.tar.gz", Some(shared::ARCHIVE))]
-    #[case("example.gif", Some(shared::IMAGE))]
-    #[case("example.jpeg", Some(shared::IMAGE))]
-    #[case("example.jpg", Some(shared::IMAGE))]
-    #[case("example.png", Some(shared::IMAGE))]
-    fn test_for_path<P>(#[case] path: P, #[case] expected: Option<&str>)
+    #[case("example.tar.gz", Some(Kind::Archive))]
+    #[case("example.gif", Some(Kind::Image))]
+    #[case("example.jpeg", Some(Kind::Image))]
+    #[case("example.jpg", Some(Kind::Image))]
+    #[case("example.png", Some(Kind::Image))]
+    #[case("LICENSE-MIT", Some(Kind::License))]
+    #[case("Dockerfile.dev", Some(Kind::Docker))]
+    #[case(".env.local", Some(Kind::Config))]
+    #[case("example.unknown", None)]
+    fn test_kind_for_path<P>(#[case] path: P, #[case] expected: Option<Kind>)
     where
         P: AsRef<Path>,
     {
-        assert_eq!(expected, for_path(path));
+        assert_eq!(expected, kind_for_path(path));
     }
 }
@@ -0,0 +1,104 @@
+//! Module for the Nerd Font icons, which are the default.
+use super::Kind;
+
+/// Gets the Nerd Font icon for a kind of entry.
+pub(super) const fn icon(kind: Kind) -> &'static str {
+    match kind {
+        Kind::File => "\u{f0214}",           // 󰈔
+        Kind::Executable => "\u{f070e}",     // 󰜎
+        Kind::Directory => "\u{f024b}",      // 󰉋
+        Kind::OpenDirectory => "\u{f0770}",  // 󰝰
+        Kind::EmptyDirectory => "\u{f0256}", // 󰉖
+        Kind::Symlink => "\u{cf481}",        //
+        Kind::BrokenSymlink => "\u{f0338}",  // 󰌸
+
+        Kind::Archive => "\u{ea98}",      //
+        Kind::Audio => "\u{f1c7}",        //
+        Kind::Backup => "\u{f006f}",      // 󰁯
+        Kind::Binary => "\u{eae8}",       //
+        Kind::Certificate => "\u{f0a3}",  //
+        Kind::Code => "\u{f1c9}",         //
+        Kind::Config => "\u{e615}",       //
+        Kind::Database => "\u{e706}",     //
+        Kind::Diff => "\u{f440}",         //
+        Kind::Doc => "\u{eaa4}",          //
+        Kind::Document => "\u{f1c2}",     //
+        Kind::Font => "\u{f031}",         //
+        Kind::Image => "\u{f1c5}",        //
+        Kind::Key => "\u{f084}",          //
+        Kind::License => "\u{e60a}",      //
+        Kind::Lock => "\u{e672}",         //
+        Kind::Log => "\u{f1da}",          //
+        Kind::Pdf => "\u{f1c1}",          //
+        Kind::Presentation => "\u{f1c4}", //
+        Kind::Spreadsheet => "\u{f1c3}",  //
+        Kind::Text => "\u{f0f6}",         //
+        Kind::Video => "\u{f1c8}",        //
+
+        Kind::Json => "\u{e60b}",     //
+        Kind::Markdown => "\u{e73e}", //
+        Kind::Toml => "\u{e6b2}",     //
+        Kind::Xml => "\u{f05c0}",     // 󰗀
+        Kind::Yaml => "\u{e6a8}",     //
+
+        Kind::Docker => "\u{f308}",     //
+        Kind::Gradle => "\u{e660}",     //
+        Kind::Make => "\u{e779}",       //
+        Kind::Nix => "\u{f313}",        //
+        Kind::Npm => "\u{e71e}",        //
+        Kind::Terraform => "\u{f1062}", // 󱁢
+
+        Kind::BuildDirectory => "\u{f487}",  //
+        Kind::ConfigDirectory => "\u{e5fc}", //
+        Kind::DocsDirectory => "\u{f405}",   //
+        Kind::GitDirectory => "\u{e5fb}",    //
+        Kind::SourceDirectory => "\u{f44f}", //
+        Kind::TestDirectory => "\u{f0668}",  // 󰙨
+
+        Kind::EditorConfig => "\u{e652}", //
+        Kind::Git => "\u{e702}",          //
+        Kind::GitHub => "\u{e5fd}",       //
+        Kind::GitLab => "\u{f296}",       //
+        Kind::NodeModules => "\u{e5fa}",  //
+        Kind::VsCode => "\u{e8da}",       //
+
+        Kind::C => "\u{e61e}",           //
+        Kind::Clojure => "\u{e768}",     //
+        Kind::Cpp => "\u{e61d}",         //
+        Kind::CSharp => "\u{f031b}",     // 󰌛
+        Kind::Css => "\u{e749}",         //
+        Kind::Dart => "\u{e798}",        //
+        Kind::Elixir => "\u{e62d}",      //
+        Kind::Elm => "\u{e62c}",         //
+        Kind::Erlang => "\u{e7b1}",      //
+        Kind::FSharp => "\u{e7a7}",      //
+        Kind::Fortran => "\u{f121a}",    // 󱈚
+        Kind::Go => "\u{e627}",          //
+        Kind::Groovy => "\u{e775}",      //
+        Kind::Haskell => "\u{e777}",     //
+        Kind::Html => "\u{e736}",        //
+        Kind::Java => "\u{e738}",        //
+        Kind::JavaScript => "\u{e74e}",  //
+        Kind::Julia => "\u{e624}",       //
+        Kind::Kotlin => "\u{e634}",      //
+        Kind::Lua => "\u{e620}",         //
+        Kind::OCaml => "\u{e67a}",       //
+        Kind::Perl => "\u{e769}",        //
+        Kind::Php => "\u{e73d}",         //
+        Kind::PowerShell => "\u{f0a0a}", // 󰨊
+        Kind::Python => "\u{e606}",      //
+        Kind::R => "\u{f07d4}",          // 󰟔
+        Kind::Ruby => "\u{e739}",        //
+        Kind::Rust => "\u{e7a8}",        //
+        Kind::Sass => "\u{e603}",        //
+        Kind::Scala => "\u{e737}",       //
+        Kind::Shell => "\u{e795}",       //
+        Kind::Sql => "\u{e706}",         //
+        Kind::Svelte => "\u{e697}",      //
+        Kind::Swift => "\u{e755}",       //
+        Kind::TypeScript => "\u{e628}",  //
+        Kind::Vim => "\u{e62b}",         //
+        Kind::Vue => "\u{f0844}",        // 󰡄
+        Kind::Zig => "\u{e6a9}",         //
+    }
+}
@@ -0,0 +1,164 @@
+//! Module for choosing the icons that are displayed.
+use super::{Kind, ascii, emoji, kind_for_path, nerd};
+use clap::ValueEnum;
+use mlua::{FromLua, Lua};
+use std::env;
+use std::path::Path;
+
+/// A set of icons, for terminals with different fonts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum IconSet {
+    /// Nerd Font icons if the locale supports Unicode, otherwise ASCII.
+    Auto,
+    /// Nerd Font icons, including icons for languages.
+    #[default]
+    Nerd,
+    /// Emoji, for terminals without a Nerd Font.
+    Emoji,
+    /// ASCII characters, like `/` for directories.
+    Ascii,
+    /// No icons.
+    None,
+}
+
+impl IconSet {
+    /// Detects the icon set if this is [`IconSet::Auto`].
+    pub fn resolve(self) -> Self {
+        match self {
+            Self::Auto => Self::detect(|name| env::var(name).ok()),
+            set => set,
+        }
+    }
+
+    /// Detects the icon set from the environment. Nerd Fonts can't be detected, so
+    /// they're assumed unless the locale isn't UTF-8 or the terminal is a console
+    /// that can't display them.
+    fn detect<F>(var: F) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let var = |name| var(name).filter(|value| !value.is_empty());
+        // NOTE The first of these that's set decides the character encoding.
+        let locale = var("LC_ALL")
+            .or_else(|| var("LC_CTYPE"))
+            .or_else(|| var("LANG"));
+        let is_utf8 = locale.map_or(cfg!(windows), |locale| {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        });
+        let is_console = var("TERM").is_some_and(|term| term == "linux" || term == "dumb");
+        if is_utf8 && !is_console {
+            Self::Nerd
+        } else {
+            Self::Ascii
+        }
+    }
+
+    /// Gets the icon for a kind of entry. This is empty for [`IconSet::None`].
+    pub const fn icon(self, kind: Kind) -> &'static str {
+        match self {
+            Self::Auto | Self::Nerd => nerd::icon(kind),
+            Self::Emoji => emoji::icon(kind),
+            Self::Ascii => ascii::icon(kind),
+            Self::None => "",
+        }
+    }
+
+    /// Gets the icon for a path from its name or extension, like an archive icon for
+    /// `.zip`.
+    pub fn for_path<P>(self, path: P) -> Option<&'static str>
+    where
+        P: AsRef<Path>,
+    {
+        kind_for_path(path).map(|kind| self.icon(kind))
+    }
+
+    /// Checks if the set has icons for programming languages.
+    #[inline]
+    pub fn has_language_icons(self) -> bool {
+        matches!(self, Self::Auto | Self::Nerd)
+    }
+}
+
+impl FromLua for IconSet {
+    fn from_lua(value: mlua::Value, _lua: &Lua) -> mlua::Result<Self> {
+        const VALID_VALUES: [&str; 5] = ["auto", "nerd", "emoji", "ascii", "none"];
+        let type_name = value.type_name();
+        let make_conversion_error = || mlua::Error::FromLuaConversionError {
+            from: type_name,
+            to: String::from("IconSet"),
+            message: Some(format!("Must be one of {VALID_VALUES:?} or nil")),
+        };
+        let icon_set = value
+            .as_string()
+            .ok_or_else(make_conversion_error)?
+            .to_string_lossy();
+        let icon_set = match icon_set.as_str() {
+            "auto" => Self::Auto,
+            "nerd" => Self::Nerd,
+            "emoji" => Self::Emoji,
+            "ascii" => Self::Ascii,
+            "none" => Self::None,
+            _ => return Err(make_conversion_error()),
+        };
+        Ok(icon_set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::icons::{directories, mappings};
+    use crate::testing;
+    use rstest::rstest;
+    use unicode_width::UnicodeWidthStr;
+
+    #[rstest]
+    #[case(&[("LANG", "en_US.UTF-8")], IconSet::Nerd)]
+    #[case(&[("LC_ALL", "C.utf8"), ("LANG", "C")], IconSet::Nerd)]
+    #[case(&[("LC_ALL", "C"), ("LANG", "en_US.UTF-8")], IconSet::Ascii)]
+    #[case(&[("LANG", "en_US.UTF-8"), ("TERM", "linux")], IconSet::Ascii)]
+    #[case(&[("LANG", "")], if cfg!(windows) { IconSet::Nerd } else { IconSet::Ascii })]
+    fn test_detect(#[case] vars: &[(&str, &str)], #[case] expected: IconSet) {
+        assert_eq!(expected, IconSet::detect(testing::env(vars)));
+    }
+
+    #[rstest]
+    #[case(IconSet::Nerd, "\u{ea98}")]
+    #[case(IconSet::Emoji, "📦")]
+    #[case(IconSet::Ascii, "z")]
+    #[case(IconSet::None, "")]
+    fn test_for_path(#[case] set: IconSet, #[case] expected: &str) {
+        assert_eq!(Some(expected), set.for_path("archive.tar.gz"));
+        assert_eq!(None, set.for_path("main.unknown"));
+    }
+
+    #[test]
+    fn test_ascii_and_emoji_widths() {
+        let kinds = [
+            Kind::File,
+            Kind::Executable,
+            Kind::Directory,
+            Kind::OpenDirectory,
+            Kind::Symlink,
+        ];
+        let kinds = kinds
+            .into_iter()
+            .chain(directories::kinds())
+            .chain(mappings::kinds());
+        for kind in kinds {
+            assert_eq!(1, IconSet::Ascii.icon(kind).width());
+            assert_eq!(2, IconSet::Emoji.icon(kind).width());
+        }
+    }
+
+    #[rstest]
+    #[case(r#""emoji""#, Some(IconSet::Emoji))]
+    #[case(r#""none""#, Some(IconSet::None))]
+    #[case(r#""nerdfont""#, None)]
+    #[case("1", None)]
+    fn test_from_lua(#[case] chunk: &str, #[case] expected: Option<IconSet>) {
+        let lua = Lua::new();
+        assert_eq!(expected, lua.load(chunk).eval::<IconSet>().ok());
+    }
+}
@@ -0,0 +1,259 @@
+//! Module for ignore files that works without a git repository.
+//!
+//! This reads `.gitignore` and `.ignore` files in the searched directories, and the
+//! global git ignore file, using the same rules as git.
+use directories::BaseDirs;
+use glob::{MatchOptions, Pattern};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Checks if paths are ignored by ignore files.
+pub struct Ignore {
+    /// The directory that the paths are in. Ignore files are read starting here.
+    root: PathBuf,
+    /// The rules from the global ignore file.
+    global: Vec<Rule>,
+    /// The cached rules for each directory.
+    dirs: RefCell<HashMap<PathBuf, Rc<[Rule]>>>,
+}
+
+impl Ignore {
+    /// The names of the ignore files in each directory, from lowest to highest
+    /// precedence.
+    pub const FILE_NAMES: [&str; 2] = [".gitignore", ".ignore"];
+
+    /// Creates an ignore checker for paths in `root`, including the rules from the
+    /// global git ignore file.
+    pub fn new<P>(root: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        let root = root.as_ref();
+        let global = Self::global_path()
+            .map(|path| Rule::load(&path, root))
+            .unwrap_or_default();
+        Self {
+            global,
+            ..Self::isolated(root)
+        }
+    }
+
+    /// Creates an ignore checker for paths in `root` that only reads the ignore
+    /// files in the searched directories, and not the global git ignore file.
+    pub fn isolated<P>(root: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            global: Vec::new(),
+            dirs: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Gets the path of the global git ignore file, which is
+    /// `$XDG_CONFIG_HOME/git/ignore` or `~/.config/git/ignore`.
+    fn global_path() -> Option<PathBuf> {
+        let config_dir = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| BaseDirs::new().map(|dirs| dirs.home_dir().join(".config")))?;
+        Some(config_dir.join("git").join("ignore"))
+    }
+
+    /// Checks if a path is ignored. The path should start with the root.
+    ///
+    /// Like git, a path inside an ignored directory is always ignored.
+    pub fn is_ignored<P>(&self, path: P, is_dir: bool) -> bool
+    where
+        P: AsRef<Path>,
+    {
+        let Ok(relative) = path.as_ref().strip_prefix(&self.root) else {
+            return false;
+        };
+        let components = relative.components().collect::<Vec<_>>();
+        let mut dir = self.root.clone();
+        let mut rules = vec![self.rules(&dir)];
+        for (index, component) in components.iter().enumerate() {
+            let path = dir.join(component);
+            let is_last = index + 1 == components.len();
+            let path_is_dir = !is_last || is_dir;
+            if self.matches(&rules, &path, path_is_dir) {
+                return true;
+            }
+            if !is_last {
+                rules.push(self.rules(&path));
+            }
+            dir = path;
+        }
+        false
+    }
+
+    /// Checks if the last matching rule ignores the path.
+    fn matches(&self, rules: &[Rc<[Rule]>], path: &Path, is_dir: bool) -> bool {
+        let rules = self
+            .global
+            .iter()
+            .chain(rules.iter().flat_map(|rules| rules.iter()));
+        rules
+            .rev()
+            .find(|rule| rule.matches(path, is_dir))
+            .is_some_and(|rule| !rule.negated)
+    }
+
+    /// Gets the rules from the ignore files in a directory.
+    fn rules(&self, dir: &Path) -> Rc<[Rule]> {
+        if let Some(rules) = self.dirs.borrow().get(dir) {
+            return Rc::clone(rules);
+        }
+        let rules = Self::FILE_NAMES
+            .iter()
+            .flat_map(|name| Rule::load(&dir.join(name), dir))
+            .collect::<Rc<[Rule]>>();
+        self.dirs
+            .borrow_mut()
+            .insert(dir.to_path_buf(), Rc::clone(&rules));
+        rules
+    }
+}
+
+/// A single pattern from an ignore file.
+#[derive(Debug)]
+struct Rule {
+    /// The pattern to match.
+    pattern: Pattern,
+    /// The directory that the pattern is relative to.
+    base: PathBuf,
+    /// Does the pattern start with `!`, so that it un-ignores matches?
+    negated: bool,
+    /// Does the pattern end with `/`, so that it only matches directories?
+    dir_only: bool,
+    /// Does the pattern contain a `/`, so that it's matched against the whole path
+    /// instead of only the name?
+    anchored: bool,
+}
+
+impl Rule {
+    /// Options for matching, so that `*` doesn't match `/`.
+    const OPTIONS: MatchOptions = MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+
+    /// Reads the rules from an ignore file. A file that can't be read has no rules.
+    fn load(path: &Path, base: &Path) -> Vec<Self> {
+        let Ok(contents) = fs::read_to_string(path) else {
+            return Vec::new();
+        };
+        contents
+            .lines()
+            .filter_map(|line| Self::parse(line, base))
+            .collect()
+    }
+
+    /// Parses a line from an ignore file. Returns `None` for blank lines, comments,
+    /// and invalid patterns.
+    fn parse(line: &str, base: &Path) -> Option<Self> {
+        let line = line.trim_end_matches(['\r', ' ']);
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(line) => (true, line),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let line = line.strip_prefix('/').unwrap_or(line);
+        let pattern = Pattern::new(line).ok()?;
+        Some(Self {
+            pattern,
+            base: base.to_path_buf(),
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    /// Checks if the rule matches a path.
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        if self.anchored {
+            path.strip_prefix(&self.base)
+                .is_ok_and(|path| self.pattern.matches_path_with(path, Self::OPTIONS))
+        } else {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| self.pattern.matches_with(name, Self::OPTIONS))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use tempfile::TempDir;
+
+    /// Creates a directory with ignore files, without the global rules.
+    fn setup(files: &[(&str, &str)]) -> (TempDir, Ignore) {
+        let root = TempDir::with_prefix("fancy-tree-").unwrap();
+        for (path, contents) in files {
+            let path = root.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        let ignore = Ignore::isolated(root.path());
+        (root, ignore)
+    }
+
+    #[rstest]
+    #[case("target", true, true)]
+    #[case("src/target", true, true)]
+    #[case("target", false, false)]
+    #[case("debug.log", false, true)]
+    #[case("logs/debug.log", false, true)]
+    #[case("keep.log", false, false)]
+    #[case("build/out.o", false, true)]
+    #[case("src/build/out.o", false, false)]
+    #[case("docs/secret.txt", false, true)]
+    #[case("docs/public.txt", false, false)]
+    #[case("docs/nested/secret.txt", false, false)]
+    #[case("src/lib.rs", false, false)]
+    #[case("local.txt", false, true)]
+    fn test_is_ignored(#[case] path: &str, #[case] is_dir: bool, #[case] expected: bool) {
+        let (root, ignore) = setup(&[
+            (
+                ".gitignore",
+                "# Comment\ntarget/\n*.log\n!keep.log\n/build\n",
+            ),
+            (".ignore", "local.txt\n"),
+            ("docs/.gitignore", "/secret.txt\n"),
+        ]);
+        let path = root.path().join(path);
+        assert_eq!(expected, ignore.is_ignored(path, is_dir));
+    }
+
+    #[test]
+    fn test_ignore_file_overrides_gitignore() {
+        let (root, ignore) = setup(&[(".gitignore", "*.txt\n"), (".ignore", "!notes.txt\n")]);
+        assert!(!ignore.is_ignored(root.path().join("notes.txt"), false));
+        assert!(ignore.is_ignored(root.path().join("other.txt"), false));
+    }
+
+    #[test]
+    fn test_outside_root() {
+        let (_root, ignore) = setup(&[(".gitignore", "*\n")]);
+        assert!(!ignore.is_ignored("/somewhere/else", false));
+    }
+}
@@ -5,12 +5,26 @@ pub mod cli;
 pub mod color;
 pub mod colors;
 pub mod config;
+pub mod diagnostics;
+pub mod duration;
+mod editor;
+mod expand;
 pub(crate) mod ext;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 mod git;
+pub mod i18n;
 pub mod icons;
+pub mod ignore;
 pub mod lua;
+pub mod pager;
+pub mod rules;
+pub mod session;
 pub mod sorting;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod tree;
+mod vcs;
 
 /// The standard result type.
 pub type Result<T = (), E = Box<dyn Error>> = core::result::Result<T, E>;
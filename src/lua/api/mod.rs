@@ -1,12 +1,17 @@
 //! Module for creating the `fancytree` API for Lua.
-use mlua::Lua;
+use mlua::{FromLua, IntoLua, Lua};
 
 mod path;
 
+/// Reads an environment variable.
+pub type Env = fn(&str) -> Option<String>;
+
 /// Builder for the API table.
 pub struct Builder {
     /// Adds `.path` API namespace when true.
     add_path_api: bool,
+    /// Reads the environment variables that paths are expanded with.
+    env: Env,
 }
 
 impl Builder {
@@ -15,19 +20,33 @@ impl Builder {
     pub fn new() -> Self {
         Self {
             add_path_api: false,
+            env: |name| std::env::var(name).ok(),
         }
     }
 
     /// Instructs the builder to add the `.path` namespace that provides path utilities.
     #[must_use]
     pub fn with_path(self) -> Self {
-        Self { add_path_api: true }
+        Self {
+            add_path_api: true,
+            ..self
+        }
+    }
+
+    /// Reads environment variables with `env` instead of from the process, like to
+    /// test `fancytree.path.expand`.
+    #[must_use]
+    pub fn with_env(self, env: Env) -> Self {
+        Self { env, ..self }
     }
 
     /// Builds the API table.
     pub fn build(self, lua: &Lua) -> mlua::Result<mlua::Table> {
         let api = Self::core(lua)?;
-        let path_api = self.add_path_api.then(|| path::create(lua)).transpose()?;
+        let path_api = self
+            .add_path_api
+            .then(|| path::create(lua, self.env).and_then(|path_api| read_only(lua, path_api)))
+            .transpose()?;
         api.set("path", path_api)?;
 
         Ok(api)
@@ -36,13 +55,95 @@ impl Builder {
     /// Creates the core API table.
     fn core(lua: &Lua) -> mlua::Result<mlua::Table> {
         let api = lua.create_table()?;
+        api.set("api_version", API_VERSION)?;
         api.set("is_unix", IS_UNIX)?;
         api.set("os", OS)?;
+        api.set("register", lua.create_function(register)?)?;
+        api.set("builtin", Builtin)?;
 
         Ok(api)
     }
 }
 
+/// The value of `fancytree.builtin`, which icon and color functions return to use
+/// the built-in choice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Builtin;
+
+impl Builtin {
+    /// Gets the pointer that represents the value in Lua. It's the address of a
+    /// static, so that no other value can be equal to it.
+    fn userdata() -> mlua::LightUserData {
+        static ADDRESS: u8 = 0;
+        mlua::LightUserData(std::ptr::addr_of!(ADDRESS).cast_mut().cast())
+    }
+}
+
+impl IntoLua for Builtin {
+    fn into_lua(self, _lua: &Lua) -> mlua::Result<mlua::Value> {
+        Ok(mlua::Value::LightUserData(Self::userdata()))
+    }
+}
+
+impl FromLua for Builtin {
+    fn from_lua(value: mlua::Value, _lua: &Lua) -> mlua::Result<Self> {
+        match value {
+            mlua::Value::LightUserData(userdata) if userdata == Self::userdata() => Ok(Self),
+            value => Err(mlua::Error::FromLuaConversionError {
+                from: value.type_name(),
+                to: String::from("Builtin"),
+                message: Some(String::from("expected `fancytree.builtin`")),
+            }),
+        }
+    }
+}
+
+/// Wraps a table in a proxy that can be read from, but not written to.
+///
+/// Writing to the proxy is an error, so that configs can't accidentally replace
+/// API functions. The wrapped table can still be changed from Rust.
+pub fn read_only(lua: &Lua, table: mlua::Table) -> mlua::Result<mlua::Table> {
+    let metatable = lua.create_table()?;
+    metatable.set("__index", &table)?;
+    let newindex = lua.create_function(|_lua, (_, key): (mlua::Value, mlua::Value)| {
+        let key = key.to_string()?;
+        Err::<(), _>(mlua::Error::runtime(format!(
+            "cannot set {key:?}: the fancytree API is read-only"
+        )))
+    })?;
+    metatable.set("__newindex", newindex)?;
+    // NOTE pairs() would see the empty proxy, so it iterates the wrapped table.
+    let pairs = lua.create_function(move |lua, _: mlua::Value| {
+        let next = lua.globals().get::<mlua::Function>("next")?;
+        Ok((next, table.clone(), mlua::Value::Nil))
+    })?;
+    metatable.set("__pairs", pairs)?;
+    // NOTE This hides the metatable, so that it can't be removed.
+    metatable.set("__metatable", false)?;
+
+    let proxy = lua.create_table()?;
+    proxy.set_metatable(Some(metatable))?;
+    Ok(proxy)
+}
+
+/// Registers a plugin's hooks. This is only allowed while a plugin is being loaded.
+fn register(lua: &Lua, hooks: mlua::Table) -> mlua::Result<()> {
+    let registered = lua.named_registry_value::<Option<mlua::Table>>(REGISTERED_HOOKS)?;
+    let Some(registered) = registered else {
+        return Err(mlua::Error::runtime(
+            "fancytree.register can only be called by plugins",
+        ));
+    };
+    registered.push(hooks)
+}
+
+/// The registry key for the hooks registered by the plugin that's being loaded.
+pub(crate) const REGISTERED_HOOKS: &str = "fancytree.registered_hooks";
+
+/// The version of the API, which is incremented when features are added, so that
+/// configs can check what is available.
+const API_VERSION: u32 = 6;
+
 const IS_UNIX: bool = cfg!(unix);
 
 const OS: &str = os_name();
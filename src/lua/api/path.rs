@@ -1,11 +1,16 @@
 //! Module for path utilities in Lua.
-use mlua::{IntoLua, Lua};
+use super::Env;
+use crate::expand;
+use crate::ext::PathExt as _;
+use glob::{MatchOptions, Pattern};
+use mlua::{FromLua, IntoLua, Lua, UserData, UserDataMethods};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::{LazyLock, RwLock};
 
-/// Creates the table for the API utilities under the path namespace.
-pub fn create(lua: &Lua) -> mlua::Result<mlua::Table> {
+/// Creates the table for the API utilities under the path namespace. Paths are
+/// expanded with the environment variables from `env`.
+pub fn create(lua: &Lua, env: Env) -> mlua::Result<mlua::Table> {
     let api = lua.create_table()?;
     let filename = lua.create_function(|lua, (path,): (String,)| {
         Path::new(&path)
@@ -14,34 +19,154 @@ pub fn create(lua: &Lua) -> mlua::Result<mlua::Table> {
             .transpose()
     })?;
     api.set("filename", filename)?;
-    let glob_matches = lua.create_function(|_lua, (glob, path): (String, String)| {
-        Ok(glob_matches_impl(glob, path))
+    let stem = lua.create_function(|lua, (path,): (String,)| {
+        Path::new(&path)
+            .file_stem()
+            .map(|os_str| os_str.into_lua(lua))
+            .transpose()
+    })?;
+    api.set("stem", stem)?;
+    let extension = lua.create_function(|lua, (path,): (String,)| {
+        Path::new(&path)
+            .extension()
+            .map(|os_str| os_str.into_lua(lua))
+            .transpose()
+    })?;
+    api.set("extension", extension)?;
+    let double_extension = lua.create_function(|lua, (path,): (String,)| {
+        let Some((prefix, suffix)) = Path::new(&path).double_extension() else {
+            return Ok((mlua::Value::Nil, mlua::Value::Nil));
+        };
+        Ok((prefix.into_lua(lua)?, suffix.into_lua(lua)?))
+    })?;
+    api.set("double_extension", double_extension)?;
+    let join = lua.create_function(|_lua, (path, parts): (String, mlua::Variadic<String>)| {
+        let joined = parts
+            .iter()
+            .fold(PathBuf::from(path), |joined, part| joined.join(part));
+        Ok(joined)
+    })?;
+    api.set("join", join)?;
+    let parent = lua.create_function(|_lua, (path,): (String,)| {
+        // NOTE A bare name's parent is an empty path, which isn't useful in Lua.
+        let parent = Path::new(&path)
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .map(Path::to_path_buf);
+        Ok(parent)
+    })?;
+    api.set("parent", parent)?;
+    let relative_to = lua.create_function(|_lua, (path, base): (String, String)| {
+        let relative = Path::new(&path)
+            .strip_prefix(&base)
+            .ok()
+            .map(Path::to_path_buf);
+        Ok(relative)
     })?;
+    api.set("relative_to", relative_to)?;
+    let is_absolute =
+        lua.create_function(|_lua, (path,): (String,)| Ok(Path::new(&path).is_absolute()))?;
+    api.set("is_absolute", is_absolute)?;
+    let glob_matches = lua.create_function(
+        |_lua, (glob, path, options): (String, String, Option<GlobOptions>)| {
+            Ok(glob_matches_impl(glob, path, options.unwrap_or_default()))
+        },
+    )?;
     api.set("glob_matches", glob_matches)?;
+    let glob = lua.create_function(|_lua, (glob, options): (String, Option<GlobOptions>)| {
+        let pattern = Pattern::new(&glob).map_err(mlua::Error::external)?;
+        Ok(Glob {
+            pattern,
+            options: options.unwrap_or_default(),
+        })
+    })?;
+    api.set("glob", glob)?;
+    let expand = lua.create_function(move |_lua, (path,): (String,)| {
+        expand::expand_vars_with(&path, env).map_err(mlua::Error::external)
+    })?;
+    api.set("expand", expand)?;
 
     Ok(api)
 }
 
+/// The options for matching a glob, from a table like `{ case_insensitive = true }`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct GlobOptions {
+    /// Ignore case, so that `*.JPG` matches `photo.jpg`.
+    case_insensitive: bool,
+    /// Only match `/` with a literal `/`, so that `*` doesn't match across
+    /// directories.
+    literal_separator: bool,
+}
+
+impl GlobOptions {
+    /// Converts to the options that the glob is matched with.
+    fn match_options(self) -> MatchOptions {
+        MatchOptions {
+            case_sensitive: !self.case_insensitive,
+            require_literal_separator: self.literal_separator,
+            require_literal_leading_dot: false,
+        }
+    }
+}
+
+impl FromLua for GlobOptions {
+    fn from_lua(value: mlua::Value, lua: &Lua) -> mlua::Result<Self> {
+        let table = mlua::Table::from_lua(value, lua)?;
+        Ok(Self {
+            case_insensitive: table
+                .get::<Option<bool>>("case_insensitive")?
+                .unwrap_or(false),
+            literal_separator: table
+                .get::<Option<bool>>("literal_separator")?
+                .unwrap_or(false),
+        })
+    }
+}
+
+/// A compiled glob, so that configs can match the same pattern against many paths
+/// without looking it up each time.
+#[derive(Debug, Clone)]
+struct Glob {
+    /// The compiled pattern.
+    pattern: Pattern,
+    /// The options the pattern is matched with.
+    options: GlobOptions,
+}
+
+impl UserData for Glob {
+    fn add_methods<M: UserDataMethods<Self>>(methods: &mut M) {
+        methods.add_method("matches", |_, this, (path,): (String,)| {
+            Ok(this
+                .pattern
+                .matches_path_with(Path::new(&path), this.options.match_options()))
+        });
+        methods.add_meta_method("__tostring", |_, this, ()| {
+            Ok(this.pattern.as_str().to_owned())
+        });
+    }
+}
+
 #[inline]
-fn glob_matches_impl<S, P>(raw: S, path: P) -> bool
+fn glob_matches_impl<S, P>(raw: S, path: P, options: GlobOptions) -> bool
 where
     String: From<S>,
     P: AsRef<Path>,
 {
     /// Caches compiled globs so that they aren't recompiled.
-    static GLOB_MEMO: LazyLock<RwLock<HashMap<String, Option<glob::Pattern>>>> =
-        LazyLock::new(|| {
-            let map = HashMap::new();
-            RwLock::new(map)
-        });
+    static GLOB_MEMO: LazyLock<RwLock<HashMap<String, Option<Pattern>>>> = LazyLock::new(|| {
+        let map = HashMap::new();
+        RwLock::new(map)
+    });
 
     /// Creates a new glob pattern, or `None` if it can't be compiled.
     #[inline]
-    fn create_glob(pattern: &str) -> Option<glob::Pattern> {
-        glob::Pattern::new(pattern).ok()
+    fn create_glob(pattern: &str) -> Option<Pattern> {
+        Pattern::new(pattern).ok()
     }
 
-    let matches = |glob: &glob::Pattern| glob.matches_path(path.as_ref());
+    let options = options.match_options();
+    let matches = |glob: &Pattern| glob.matches_path_with(path.as_ref(), options);
 
     let raw = String::from(raw);
 
@@ -1,6 +1,8 @@
 use crate::git::Git;
 use crate::lua;
+use crate::testing::Fixture;
 use rstest::rstest;
+use std::path::PathBuf;
 
 #[rstest]
 #[case(include_str!("./test_path_filename_case_1.lua"))]
@@ -19,9 +21,36 @@ fn test_path_filename(#[case] module: &str) {
     assert_eq!(expected, actual);
 }
 
+#[rstest]
+#[case(include_str!("./test_path_helpers_case_1.lua"))]
+#[case(include_str!("./test_path_helpers_case_2.lua"))]
+#[case(include_str!("./test_path_helpers_case_3.lua"))]
+#[case(include_str!("./test_path_helpers_case_4.lua"))]
+#[case(include_str!("./test_path_helpers_case_5.lua"))]
+#[case(include_str!("./test_path_helpers_case_6.lua"))]
+#[case(include_str!("./test_path_helpers_case_7.lua"))]
+#[case(include_str!("./test_path_helpers_case_8.lua"))]
+#[case(include_str!("./test_path_helpers_case_9.lua"))]
+#[case(include_str!("./test_path_helpers_case_10.lua"))]
+#[case(include_str!("./test_path_helpers_case_11.lua"))]
+fn test_path_helpers(#[case] module: &str) {
+    type TestCase = (Option<String>, Option<String>);
+
+    let state = lua::state::Builder::new()
+        .build()
+        .expect("The Lua object should be valid");
+    let lua = state.to_inner();
+    let chunk = lua.load(module);
+
+    let (actual, expected): TestCase = chunk.call(()).expect("Chunk should run");
+    assert_eq!(expected, actual);
+}
+
 #[rstest]
 #[case(include_str!("./test_path_glob_matches_case_1.lua"))]
 #[case(include_str!("./test_path_glob_matches_case_2.lua"))]
+#[case(include_str!("./test_path_glob_matches_case_3.lua"))]
+#[case(include_str!("./test_path_glob_matches_case_4.lua"))]
 fn test_path_glob_matches(#[case] module: &str) {
     type TestCase = (bool, bool);
 
@@ -35,6 +64,40 @@ fn test_path_glob_matches(#[case] module: &str) {
     assert_eq!(expected, actual);
 }
 
+#[rstest]
+#[case(include_str!("./test_path_glob_case_1.lua"))]
+#[case(include_str!("./test_path_glob_case_2.lua"))]
+#[case(include_str!("./test_path_glob_case_3.lua"))]
+fn test_path_glob(#[case] module: &str) {
+    type TestCase = (bool, bool);
+
+    let state = lua::state::Builder::new()
+        .build()
+        .expect("The Lua object should be valid");
+    let lua = state.to_inner();
+    let chunk = lua.load(module);
+
+    let (actual, expected): TestCase = chunk.call(()).expect("Chunk should run");
+    assert_eq!(expected, actual);
+}
+
+#[rstest]
+#[case(include_str!("./test_path_expand_case_1.lua"))]
+#[case(include_str!("./test_path_expand_case_2.lua"))]
+fn test_path_expand(#[case] module: &str) {
+    type TestCase = (String, String);
+
+    let state = lua::state::Builder::new()
+        .with_env(|name| (name == "FANCY_TREE_TEST_EXPAND").then(|| String::from("expanded")))
+        .build()
+        .expect("The Lua object should be valid");
+    let lua = state.to_inner();
+    let chunk = lua.load(module);
+
+    let (actual, expected): TestCase = chunk.call(()).expect("Chunk should run");
+    assert_eq!(expected, actual);
+}
+
 #[rstest]
 #[case(include_str!("./test_git_is_ignored_case_1.lua"))]
 #[case(include_str!("./test_git_is_ignored_case_2.lua"))]
@@ -72,3 +135,124 @@ fn test_git_is_ignored(#[case] module: &str) {
         })
         .expect("Lua-scoped function should succeed");
 }
+
+#[rstest]
+#[case(include_str!("./test_api_read_only_case_1.lua"))]
+#[case(include_str!("./test_api_read_only_case_2.lua"))]
+#[case(include_str!("./test_api_read_only_case_3.lua"))]
+fn test_api_read_only(#[case] module: &str) {
+    type TestCase = (bool, bool);
+
+    let state = lua::state::Builder::new()
+        .build()
+        .expect("The Lua object should be valid");
+    let lua = state.to_inner();
+    let chunk = lua.load(module);
+
+    let (actual, expected): TestCase = chunk.call(()).expect("Chunk should run");
+    assert_eq!(expected, actual);
+}
+
+#[rstest]
+#[case(include_str!("./test_git_status_case_1.lua"))]
+#[case(include_str!("./test_git_status_case_2.lua"))]
+#[case(include_str!("./test_git_status_case_3.lua"))]
+#[case(include_str!("./test_git_status_case_4.lua"))]
+#[case(include_str!("./test_git_status_case_5.lua"))]
+fn test_git_status(#[case] module: &str) {
+    type TestCase = (Option<String>, Option<String>);
+
+    let fixture = Fixture::builder()
+        .git_init()
+        .file("tracked", "")
+        .file("clean", "")
+        .git_commit("Initial commit")
+        .file("tracked", "modified")
+        .file("new", "")
+        .build()
+        .unwrap();
+    let git = Git::new(fixture.path())
+        .unwrap()
+        .expect("The fixture should be a repository");
+
+    let state = lua::state::Builder::new()
+        .with_git(&git)
+        .build()
+        .expect("The lua object should be valid");
+    let lua = state.to_inner();
+    let chunk = lua.load(module);
+
+    state
+        .in_git_scope(|| {
+            let (actual, expected): TestCase = chunk.call(()).expect("Chunk should run");
+            assert_eq!(expected, actual);
+            Ok(())
+        })
+        .expect("Lua-scoped function should succeed");
+}
+
+#[test]
+fn test_git_root() {
+    let fixture = Fixture::builder().git_init().build().unwrap();
+    let git = Git::new(fixture.path())
+        .unwrap()
+        .expect("The fixture should be a repository");
+
+    let state = lua::state::Builder::new()
+        .with_git(&git)
+        .build()
+        .expect("The lua object should be valid");
+    let lua = state.to_inner();
+
+    let root = state
+        .in_git_scope(|| lua.load("return fancytree.git.root()").eval::<PathBuf>())
+        .expect("Lua-scoped function should succeed");
+    assert_eq!(
+        fixture.path().canonicalize().unwrap(),
+        root.canonicalize().unwrap()
+    );
+}
+
+#[test]
+fn test_git_branch() {
+    let fixture = Fixture::builder()
+        .git_init()
+        .file("tracked", "")
+        .git_commit("Initial commit")
+        .file("new", "")
+        .build()
+        .unwrap();
+    let git = Git::new(fixture.path())
+        .unwrap()
+        .expect("The fixture should be a repository");
+
+    let state = lua::state::Builder::new()
+        .with_git(&git)
+        .build()
+        .expect("The lua object should be valid");
+    let lua = state.to_inner();
+
+    let chunk = r#"
+      local ahead, behind = fancytree.git.ahead_behind()
+      return fancytree.git.branch(), ahead == nil and behind == nil, fancytree.git.changed_files()
+    "#;
+    let (branch, no_upstream, changed) = state
+        .in_git_scope(|| lua.load(chunk).eval::<(String, bool, usize)>())
+        .expect("Lua-scoped function should succeed");
+    assert_eq!("main", branch);
+    assert!(no_upstream);
+    assert_eq!(1, changed);
+}
+
+#[test]
+fn test_builtin() {
+    let state = lua::state::Builder::new()
+        .build()
+        .expect("The Lua object should be valid");
+    let lua = state.to_inner();
+    let builtin = lua
+        .load("return fancytree.builtin")
+        .eval::<super::Builtin>();
+    assert_eq!(super::Builtin, builtin.unwrap());
+    assert!(lua.load("return {}").eval::<super::Builtin>().is_err());
+}
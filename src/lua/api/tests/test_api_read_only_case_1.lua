@@ -0,0 +1,2 @@
+local ok = pcall(function() fancytree.os = "other" end)
+return ok, false
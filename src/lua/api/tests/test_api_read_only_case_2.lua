@@ -0,0 +1,2 @@
+local ok = pcall(function() fancytree.path.filename = nil end)
+return ok, false
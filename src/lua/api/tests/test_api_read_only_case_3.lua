@@ -0,0 +1,5 @@
+local keys = 0
+for _ in pairs(fancytree) do
+  keys = keys + 1
+end
+return keys > 0 and fancytree.api_version >= 1, true
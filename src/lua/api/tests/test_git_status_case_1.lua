@@ -0,0 +1 @@
+return fancytree.git.status("tracked").untracked, "modified"
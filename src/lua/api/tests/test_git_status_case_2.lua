@@ -0,0 +1 @@
+return fancytree.git.status("new").untracked, "added"
@@ -0,0 +1 @@
+return fancytree.git.status("clean").untracked, nil
@@ -0,0 +1 @@
+return fancytree.git.status("new/../tracked").untracked, "modified"
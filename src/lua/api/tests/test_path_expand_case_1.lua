@@ -0,0 +1 @@
+return fancytree.path.expand("$FANCY_TREE_TEST_EXPAND/src"), "expanded/src"
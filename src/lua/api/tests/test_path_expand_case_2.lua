@@ -0,0 +1 @@
+return fancytree.path.expand("no/variables"), "no/variables"
@@ -0,0 +1,2 @@
+local images = fancytree.path.glob("*.png")
+return images:matches("A.PNG"), false
@@ -0,0 +1,2 @@
+local images = fancytree.path.glob("*.[jJ]peg", { case_insensitive = true })
+return images:matches("a.JPEG") and not images:matches("a.png"), true
@@ -0,0 +1 @@
+return pcall(fancytree.path.glob, "[unclosed"), false
@@ -0,0 +1 @@
+return fancytree.path.glob_matches("*.JPG", "photo.jpg", { case_insensitive = true }), true
@@ -0,0 +1 @@
+return fancytree.path.glob_matches("*.rs", "src/main.rs", { literal_separator = true }), false
@@ -0,0 +1 @@
+return fancytree.path.stem("path/to/config.lua"), "config"
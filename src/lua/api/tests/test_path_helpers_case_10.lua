@@ -0,0 +1 @@
+return fancytree.path.relative_to("src/main.rs", "lua"), nil
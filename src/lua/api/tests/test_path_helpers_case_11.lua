@@ -0,0 +1 @@
+return tostring(fancytree.path.is_absolute("src")), "false"
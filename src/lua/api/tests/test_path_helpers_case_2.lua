@@ -0,0 +1 @@
+return fancytree.path.extension("archive.tar.gz"), "gz"
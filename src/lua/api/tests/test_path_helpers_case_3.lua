@@ -0,0 +1 @@
+return fancytree.path.extension("Makefile"), nil
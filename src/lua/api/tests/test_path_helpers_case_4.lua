@@ -0,0 +1 @@
+return table.concat({ fancytree.path.double_extension("archive.tar.gz") }, ","), "tar,gz"
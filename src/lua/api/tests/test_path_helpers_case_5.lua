@@ -0,0 +1 @@
+return fancytree.path.double_extension("config.lua"), nil
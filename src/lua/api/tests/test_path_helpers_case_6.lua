@@ -0,0 +1 @@
+return fancytree.path.join("src", "lua", "api.rs"), (fancytree.is_unix and "src/lua/api.rs" or "src\\lua\\api.rs")
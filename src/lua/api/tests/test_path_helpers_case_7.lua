@@ -0,0 +1 @@
+return fancytree.path.parent("src/main.rs"), "src"
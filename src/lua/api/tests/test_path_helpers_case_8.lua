@@ -0,0 +1 @@
+return fancytree.path.parent("main.rs"), nil
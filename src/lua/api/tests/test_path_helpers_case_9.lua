@@ -0,0 +1 @@
+return fancytree.path.relative_to("src/lua/api.rs", "src"), "lua/api.rs"
@@ -1,72 +1,439 @@
 //! Crate for interoperability between non-config types and the Lua config files.
+use crate::color;
 use crate::tree::Entry;
 use crate::tree::entry::Attributes;
-use mlua::{IntoLua, Lua};
-use std::path::Path;
+use crate::vcs::status::FileStatus;
+use mlua::{FromLua, IntoLua, Lua, UserData, UserDataFields, UserDataMethods, WeakLua};
+use std::cell::{Cell, OnceCell};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// FileAttributes tracks various file stats.
-pub struct FileAttributes<'a, P: AsRef<Path>>(&'a Entry<P>);
-
-impl<'a, P> FileAttributes<'a, P>
-where
-    P: AsRef<Path>,
-{
+///
+/// This is passed to Lua as userdata. Values that are cheap to get are copied from
+/// the entry, and values that need to read the filesystem are only computed when a
+/// config reads them.
+#[derive(Clone)]
+pub struct FileAttributes {
+    /// The path to the entry, used to compute values lazily.
+    path: PathBuf,
+    /// The file type (string enum).
+    file_type: &'static str,
     /// Is the file hidden?
+    hidden: bool,
+    /// Is the file an executable?
+    executable: bool,
+    /// The file's code language.
+    language: Option<&'static str>,
+    /// The size of the file in bytes. `None` for directories and symlinks.
+    size: Option<u64>,
+    /// The lazily computed number of children if the file is a directory.
+    child_count: OnceCell<Option<usize>>,
+    /// The lazily computed total size of the contents if the file is a directory.
+    total_size: OnceCell<Option<u64>>,
+    /// When the file was last modified, if the platform supports it.
+    modified: Option<SystemTime>,
+    /// The formatted permissions, like `-rw-r--r--`.
+    permissions: String,
+    /// The target if the file is a symlink.
+    target: Option<PathBuf>,
+    /// Is the file a symlink whose target doesn't exist?
+    broken: bool,
+    /// The version control status, if it was set before the attributes were passed
+    /// to Lua.
+    status: Option<FileStatus>,
+    /// Gets the version control status when a config reads it, if it wasn't set.
+    /// This is a function that only works while a config is called.
+    get_status: Option<mlua::Function>,
+    /// Are the directory's entries written below it? `None` for non-directories,
+    /// and when it isn't known yet.
+    expanded: Option<bool>,
+}
+
+impl FileAttributes {
+    const DIRECTORY: &str = "directory";
+    const FILE: &str = "file";
+    const SYMLINK: &str = "symlink";
+
+    /// Sets the version control status of the entry.
     #[inline]
-    fn is_hidden(&self) -> bool {
-        self.0.is_hidden()
+    #[must_use]
+    pub fn with_status(self, status: Option<FileStatus>) -> Self {
+        Self { status, ..self }
     }
 
-    /// Is the file an executable?
+    /// Sets if the directory's entries are written below it. Ignored for
+    /// non-directories.
     #[inline]
-    fn is_executable(&self) -> bool {
-        self.0.is_executable()
+    #[must_use]
+    pub fn with_expanded(self, expanded: bool) -> Self {
+        let expanded = self.is_dir().then_some(expanded);
+        Self { expanded, ..self }
     }
 
-    /// What is the file type (string enum)?
-    fn file_type(&self) -> &str {
-        const DIRECTORY: &str = "directory";
-        const FILE: &str = "file";
-        const SYMLINK: &str = "symlink";
-
-        match self.0.attributes() {
-            Attributes::Directory(_) => DIRECTORY,
-            Attributes::File(_) => FILE,
-            Attributes::Symlink(_) => SYMLINK,
+    /// The version control status, if the entry is in a repository.
+    fn status(&self, lua: &Lua) -> mlua::Result<mlua::Value> {
+        match (self.status, self.get_status.as_ref()) {
+            (Some(status), _) => status.into_lua(lua),
+            // NOTE The function can't be called after the config returns, like when
+            //      a config keeps the attributes, so the status is unknown then.
+            (None, Some(get_status)) => Ok(get_status.call(()).unwrap_or(mlua::Value::Nil)),
+            (None, None) => Ok(mlua::Value::Nil),
         }
     }
 
-    /// The file's code language.
-    fn language(&self) -> Option<&'static str> {
-        self.0
-            .attributes()
-            .file()
-            .and_then(|file| file.language())
-            .map(|language| language.name())
+    /// Is the file a directory?
+    #[inline]
+    fn is_dir(&self) -> bool {
+        self.file_type == Self::DIRECTORY
+    }
+
+    /// The number of children if the file is a directory.
+    fn child_count(&self) -> Option<usize> {
+        *self.child_count.get_or_init(|| {
+            self.is_dir()
+                .then(|| fs::read_dir(&self.path).ok().map(Iterator::count))
+                .flatten()
+        })
+    }
+
+    /// The total size of the contents in bytes if the file is a directory.
+    fn total_size(&self) -> Option<u64> {
+        *self
+            .total_size
+            .get_or_init(|| self.is_dir().then(|| crate::tree::total_size(&self.path)))
+    }
+
+    /// Seconds since the Unix epoch when the file was last modified.
+    fn modified(&self) -> Option<i64> {
+        let modified = self.modified?;
+        let seconds = match modified.duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs().try_into().ok()?,
+            Err(e) => -i64::try_from(e.duration().as_secs()).ok()?,
+        };
+        Some(seconds)
+    }
+
+    /// Seconds since the file was last modified. Times in the future are `0`.
+    fn age(&self) -> Option<u64> {
+        let modified = self.modified?;
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        Some(age.as_secs())
+    }
+
+    /// Is the file an empty directory? `nil` for non-directories.
+    fn is_empty(&self) -> Option<bool> {
+        self.is_dir().then(|| self.child_count() == Some(0))
     }
 }
 
-impl<'a, P> IntoLua for FileAttributes<'a, P>
+impl UserData for FileAttributes {
+    fn add_fields<F: UserDataFields<Self>>(fields: &mut F) {
+        // NOTE These were plain table fields before, so they're kept as fields for
+        //      existing configs.
+        fields.add_field_method_get("is_hidden", |_, this| Ok(this.hidden));
+        fields.add_field_method_get("is_executable", |_, this| Ok(this.executable));
+        fields.add_field_method_get("file_type", |_, this| Ok(this.file_type));
+        fields.add_field_method_get("is_empty", |_, this| Ok(this.is_empty()));
+        fields.add_field_method_get("is_expanded", |_, this| Ok(this.expanded));
+    }
+
+    fn add_methods<M: UserDataMethods<Self>>(methods: &mut M) {
+        methods.add_method("path", |_, this, ()| Ok(this.path.clone()));
+        methods.add_method("is_dir", |_, this, ()| Ok(this.is_dir()));
+        methods.add_method("size", |_, this, ()| Ok(this.size));
+        methods.add_method("total_size", |_, this, ()| Ok(this.total_size()));
+        methods.add_method("language", |_, this, ()| Ok(this.language));
+        methods.add_method("child_count", |_, this, ()| Ok(this.child_count()));
+        methods.add_method("modified", |_, this, ()| Ok(this.modified()));
+        methods.add_method("age", |_, this, ()| Ok(this.age()));
+        methods.add_method("permissions", |_, this, ()| Ok(this.permissions.clone()));
+        methods.add_method("target", |_, this, ()| Ok(this.target.clone()));
+        methods.add_method("is_broken", |_, this, ()| Ok(this.broken));
+        methods.add_method("git_status", |lua, this, ()| this.status(lua));
+    }
+}
+
+impl<P> From<&Entry<P>> for FileAttributes
 where
     P: AsRef<Path>,
 {
+    fn from(value: &Entry<P>) -> Self {
+        let attributes = value.attributes();
+        let file_type = match attributes {
+            Attributes::Directory(_) => Self::DIRECTORY,
+            Attributes::File(_) => Self::FILE,
+            Attributes::Symlink(_) => Self::SYMLINK,
+        };
+        let file = attributes.file();
+        let symlink = attributes.symlink();
+        // NOTE The count is copied if it was already computed, so that it isn't
+        //      read again.
+        let child_count = attributes
+            .directory()
+            .and_then(|directory| directory.cached_child_count())
+            .map_or_else(OnceCell::new, OnceCell::from);
+        // NOTE Like the count, the total size is copied if the tree computed it.
+        let total_size = attributes
+            .directory()
+            .and_then(|directory| directory.cached_total_size())
+            .map_or_else(OnceCell::new, |size| OnceCell::from(Some(size)));
+        Self {
+            path: value.path().to_path_buf(),
+            file_type,
+            hidden: value.is_hidden(),
+            executable: value.is_executable(),
+            language: file
+                .and_then(|file| file.language())
+                .map(|language| language.name()),
+            size: file.map(|file| file.size()),
+            child_count,
+            total_size,
+            modified: attributes.modified(),
+            permissions: String::from(attributes.permissions()),
+            target: symlink
+                .and_then(|symlink| symlink.target())
+                .map(Path::to_path_buf),
+            broken: symlink.is_some_and(|symlink| symlink.is_broken()),
+            status: None,
+            get_status: None,
+            expanded: None,
+        }
+    }
+}
+
+/// The Lua state that a configuration was loaded from, so that the attributes that
+/// its functions receive can get values only when the functions read them.
+///
+/// This is a weak reference, so that it doesn't keep the state alive. Without a
+/// state, like for a default configuration, values are read before the functions
+/// are called.
+#[derive(Clone, Default)]
+pub struct Scope(Option<WeakLua>);
+
+impl Scope {
+    /// Creates a scope for a configuration that's loaded from a Lua state.
+    #[inline]
+    pub fn new(lua: &Lua) -> Self {
+        Self(Some(lua.weak()))
+    }
+
+    /// Calls `f` with the attributes, which get the entry's version control status
+    /// from `status` the first time that a config reads it, instead of before the
+    /// config is called.
+    pub fn with_status<S, T, F>(&self, attributes: FileAttributes, status: S, f: F) -> Option<T>
+    where
+        S: FnOnce() -> Option<FileStatus>,
+        F: FnOnce(FileAttributes) -> Option<T>,
+    {
+        let Some(lua) = self.0.as_ref().and_then(WeakLua::try_upgrade) else {
+            return f(attributes.with_status(status()));
+        };
+        // NOTE The status is kept, since the attributes can be passed to several
+        //      functions, like each plugin's hook.
+        let status = Cell::new(Some(status));
+        let cached = OnceCell::new();
+        lua.scope(|scope| {
+            let get_status = scope.create_function(|_, ()| {
+                let status = cached.get_or_init(|| status.take().and_then(|status| status()));
+                Ok(*status)
+            })?;
+            let attributes = FileAttributes {
+                get_status: Some(get_status),
+                ..attributes
+            };
+            Ok(f(attributes))
+        })
+        .ok()
+        .flatten()
+    }
+}
+
+impl fmt::Debug for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Scope").finish_non_exhaustive()
+    }
+}
+
+/// A written line of the tree.
+///
+/// This is passed to Lua as a table like `{ text = "...", styled = "..." }`, where
+/// `text` doesn't have colors or hyperlinks, and `styled` is the line as it was
+/// written. A line is converted back from such a table, or from a string. When the
+/// table's `text` was changed, the line is the new text without styles, since the
+/// styles can't be matched to it. Otherwise, the line is `styled`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line(pub String);
+
+impl IntoLua for Line {
     fn into_lua(self, lua: &Lua) -> mlua::Result<mlua::Value> {
         let table = lua.create_table()?;
-        table.set("is_hidden", self.is_hidden())?;
-        table.set("is_executable", self.is_executable())?;
-        table.set("file_type", self.file_type())?;
-        table.set("language", self.language())?;
-        let table = mlua::Value::Table(table);
-        Ok(table)
+        table.set("text", color::strip_escapes(&self.0))?;
+        table.set("styled", self.0)?;
+        table.into_lua(lua)
     }
 }
 
-impl<'a, P> From<&'a Entry<P>> for FileAttributes<'a, P>
-where
-    P: AsRef<Path>,
-{
-    #[inline]
-    fn from(value: &'a Entry<P>) -> Self {
-        Self(value)
+impl FromLua for Line {
+    fn from_lua(value: mlua::Value, lua: &Lua) -> mlua::Result<Self> {
+        match value {
+            mlua::Value::Table(table) => {
+                let text = table.get::<Option<String>>("text")?;
+                let styled = table.get::<Option<String>>("styled")?;
+                match (text, styled) {
+                    (Some(text), Some(styled)) if text != color::strip_escapes(&styled) => {
+                        Ok(Self(text))
+                    }
+                    (_, Some(styled)) => Ok(Self(styled)),
+                    (Some(text), None) => Ok(Self(text)),
+                    (None, None) => Err(mlua::Error::FromLuaConversionError {
+                        from: "table",
+                        to: String::from("Line"),
+                        message: Some(String::from("expected `text` or `styled`")),
+                    }),
+                }
+            }
+            value => String::from_lua(value, lua).map(Self),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vcs::status::Status;
+    use mlua::Lua;
+    use rstest::rstest;
+    use tempfile::TempDir;
+
+    #[rstest]
+    #[case("return attributes.file_type", "directory")]
+    #[case("return tostring(attributes:child_count())", "2")]
+    #[case("return tostring(attributes:language())", "nil")]
+    #[case("return tostring(attributes.is_empty)", "false")]
+    #[case("return tostring(attributes:is_dir())", "true")]
+    #[case("return tostring(attributes:size())", "nil")]
+    #[case("return tostring(attributes:total_size())", "0")]
+    #[case("return attributes:git_status().untracked", "added")]
+    #[case("return tostring(attributes.is_expanded)", "true")]
+    fn test_directory(#[case] chunk: &str, #[case] expected: &str) {
+        let root = TempDir::with_prefix("fancy-tree-").unwrap();
+        fs::write(root.path().join("a"), "").unwrap();
+        fs::write(root.path().join("b"), "").unwrap();
+        let entry = Entry::new(root.path()).unwrap();
+        let status = FileStatus {
+            untracked: Some(Status::Added),
+            ..FileStatus::CURRENT
+        };
+        let attributes = FileAttributes::from(&entry)
+            .with_status(Some(status))
+            .with_expanded(true);
+
+        let lua = Lua::new();
+        lua.globals().set("attributes", attributes).unwrap();
+        assert_eq!(expected, lua.load(chunk).eval::<String>().unwrap());
+    }
+
+    #[rstest]
+    #[case("return attributes.file_type", "file")]
+    #[case("return tostring(attributes:size())", "5")]
+    #[case("return tostring(attributes:total_size())", "nil")]
+    #[case("return tostring(attributes.is_empty)", "nil")]
+    #[case("return tostring(attributes:git_status())", "nil")]
+    #[case("return tostring(attributes:modified())", "992606400")]
+    #[case("return tostring(attributes:age() > 0)", "true")]
+    #[case("return tostring(attributes:target())", "nil")]
+    #[case("return tostring(attributes.is_expanded)", "nil")]
+    fn test_file(#[case] chunk: &str, #[case] expected: &str) {
+        let root = TempDir::with_prefix("fancy-tree-").unwrap();
+        let path = root.path().join("file");
+        fs::write(&path, "12345").unwrap();
+        let modified = UNIX_EPOCH + std::time::Duration::from_secs(992_606_400);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        let entry = Entry::new(path).unwrap();
+
+        let lua = Lua::new();
+        let attributes = FileAttributes::from(&entry).with_expanded(true);
+        lua.globals().set("attributes", attributes).unwrap();
+        assert_eq!(expected, lua.load(chunk).eval::<String>().unwrap());
+    }
+
+    #[cfg(unix)]
+    #[rstest]
+    #[case("return attributes:target()", "missing")]
+    #[case("return tostring(attributes:is_broken())", "true")]
+    #[case("return attributes.file_type", "symlink")]
+    fn test_symlink(#[case] chunk: &str, #[case] expected: &str) {
+        let root = TempDir::with_prefix("fancy-tree-").unwrap();
+        let path = root.path().join("link");
+        std::os::unix::fs::symlink("missing", &path).unwrap();
+        let entry = Entry::new(path).unwrap();
+
+        let lua = Lua::new();
+        let attributes = FileAttributes::from(&entry);
+        lua.globals().set("attributes", attributes).unwrap();
+        assert_eq!(expected, lua.load(chunk).eval::<String>().unwrap());
+    }
+
+    #[rstest]
+    #[case("return lines[1].text", "red")]
+    #[case("return lines[1].styled", "\x1b[31mred\x1b[0m")]
+    fn test_line_into_lua(#[case] chunk: &str, #[case] expected: &str) {
+        let lua = Lua::new();
+        let lines = vec![Line(String::from("\x1b[31mred\x1b[0m"))];
+        lua.globals().set("lines", lines).unwrap();
+        assert_eq!(expected, lua.load(chunk).eval::<String>().unwrap());
+    }
+
+    #[rstest]
+    #[case(
+        r#"return { text = "b", styled = "\27[31mb\27[0m" }"#,
+        "\x1b[31mb\x1b[0m"
+    )]
+    #[case(r#"return { text = "a", styled = "\27[31mb\27[0m" }"#, "a")]
+    #[case(r#"return { text = "a" }"#, "a")]
+    #[case(r#"return { styled = "b" }"#, "b")]
+    #[case(r#"return "c""#, "c")]
+    fn test_line_from_lua(#[case] chunk: &str, #[case] expected: &str) {
+        let lua = Lua::new();
+        let line = lua.load(chunk).eval::<Line>().unwrap();
+        assert_eq!(Line(String::from(expected)), line);
+    }
+
+    #[test]
+    fn test_line_from_lua_err() {
+        let lua = Lua::new();
+        assert!(lua.load("return {}").eval::<Line>().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = TempDir::with_prefix("fancy-tree-").unwrap();
+        let path = root.path().join("file");
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        let entry = Entry::new(path).unwrap();
+
+        let lua = Lua::new();
+        lua.globals()
+            .set("attributes", FileAttributes::from(&entry))
+            .unwrap();
+        let permissions = lua
+            .load("return attributes:permissions()")
+            .eval::<String>()
+            .unwrap();
+        assert_eq!("-rw-r-----", permissions);
     }
 }
@@ -1,5 +1,5 @@
 //! Module for lua utilities.
 
-mod api;
+pub(crate) mod api;
 pub mod interop;
 pub mod state;
@@ -1,25 +1,63 @@
 //! Module for the state builder.
 use super::State;
+use crate::diagnostics::Diagnostics;
 use crate::git::Git;
 use crate::lua::api;
 use mlua::Lua;
 
 /// Builds the Lua state.
-#[derive(Default)]
 pub struct Builder<'git> {
     git: Option<&'git Git>,
+    diagnostics: Diagnostics,
+    /// Reads environment variables for the API.
+    env: Option<api::Env>,
+}
+
+impl Default for Builder<'_> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<'git> Builder<'git> {
     /// Creates a new builder.
     pub fn new() -> Self {
-        Self { git: None }
+        Self {
+            git: None,
+            diagnostics: Diagnostics::default(),
+            env: None,
+        }
     }
 
     /// Adds git to the builder.
     #[must_use]
     pub fn with_git(self, git: &'git Git) -> Self {
-        Self { git: Some(git) }
+        Self {
+            git: Some(git),
+            ..self
+        }
+    }
+
+    /// Reports errors from the configurations that are loaded with the state to
+    /// existing diagnostics, like ones that already have errors from before the
+    /// state was built.
+    #[must_use]
+    pub fn with_diagnostics(self, diagnostics: Diagnostics) -> Self {
+        Self {
+            diagnostics,
+            ..self
+        }
+    }
+
+    /// Reads environment variables for the API with `env` instead of from the process,
+    /// like to test `fancytree.path.expand` without changing the process's variables.
+    #[must_use]
+    pub fn with_env(self, env: api::Env) -> Self {
+        Self {
+            env: Some(env),
+            ..self
+        }
     }
 
     /// Builds the Lua state.
@@ -30,21 +68,33 @@ impl<'git> Builder<'git> {
         const API_NAME: &str = "fancytree";
 
         let inner = Lua::new_with(StdLib::TABLE | StdLib::STRING, LuaOptions::default())?;
+        self.diagnostics.attach(&inner);
 
-        let api = api::Builder::new().with_path().build(&inner)?;
-
-        if self.git.is_some() {
-            // NOTE We don't actually add any utilities here, because we need scoping.
-            let git = inner.create_table()?;
-            api.set("git", git)?;
+        let mut api_builder = api::Builder::new().with_path();
+        if let Some(env) = self.env {
+            api_builder = api_builder.with_env(env);
         }
+        let api = api_builder.build(&inner)?;
+
+        let git_api = self
+            .git
+            .map(|_| {
+                // NOTE We don't actually add any utilities here, because we need scoping.
+                let git_api = inner.create_table()?;
+                api.set("git", api::read_only(&inner, git_api.clone())?)?;
+                Ok::<_, mlua::Error>(git_api)
+            })
+            .transpose()?;
 
         let globals = inner.globals();
-        globals.set(API_NAME, api)?;
+        globals.set(API_NAME, api::read_only(&inner, api.clone())?)?;
 
         let state = State {
             inner,
             git: self.git,
+            api,
+            git_api,
+            diagnostics: self.diagnostics,
         };
         Ok(state)
     }
@@ -1,8 +1,12 @@
 //! Module for creating a Lua state object for the application.
+use crate::diagnostics::Diagnostics;
 use crate::git::Git;
+use crate::tree::{self, Stats};
+use crate::vcs::Vcs;
 pub use builder::Builder;
 use mlua::Lua;
 use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 
 mod builder;
 
@@ -14,6 +18,12 @@ pub struct State<'git> {
     inner: Lua,
     /// An optional git state for interfacing with a repository.
     git: Option<&'git Git>,
+    /// The API table behind the read-only `fancytree` global.
+    api: mlua::Table,
+    /// The git API table behind the read-only `fancytree.git`, if there's git.
+    git_api: Option<mlua::Table>,
+    /// Where errors from the configurations loaded with the state are reported.
+    diagnostics: Diagnostics,
 }
 
 impl<'git> State<'git> {
@@ -22,6 +32,12 @@ impl<'git> State<'git> {
         &self.inner
     }
 
+    /// Gets where errors from the configurations loaded with the state are reported.
+    #[inline]
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
     /// Gets the contained git instance.
     pub fn git(&self) -> Option<&'git Git> {
         self.git
@@ -36,7 +52,7 @@ impl<'git> State<'git> {
         //      API) because of lifetimes.
         // HACK Both git and git API must exist, so we can use a shortcut if neither exist.
         let Some(git) = self.git else { return f() };
-        let Some(git_api) = self.git_api()? else {
+        let Some(ref git_api) = self.git_api else {
             return f();
         };
 
@@ -46,15 +62,43 @@ impl<'git> State<'git> {
                 Ok(is_ignored)
             })?;
             git_api.set("is_ignored", is_ignored)?;
+            let status = scope.create_function(|_lua, path: OsString| {
+                // NOTE Paths like `./src` or `src/../lib.rs` are cleaned up, since
+                //      git only knows paths relative to the root.
+                let path = match git.root_dir() {
+                    Some(root) => tree::clean_path_for_git2(root, root.join(path)),
+                    None => Some(PathBuf::from(path)),
+                };
+                // NOTE Paths that can't be checked, like paths outside of the
+                //      repository, are treated like unmodified files.
+                let status = path
+                    .and_then(|path| git.status(&path).ok())
+                    .unwrap_or_default();
+                Ok(status)
+            })?;
+            git_api.set("status", status)?;
+            let root =
+                scope.create_function(|_lua, ()| Ok(git.root_dir().map(Path::to_path_buf)))?;
+            git_api.set("root", root)?;
+            // NOTE The repository's state can't be read for some reason, so it's like
+            //      there's nothing to report.
+            let branch = scope.create_function(|_lua, ()| {
+                Ok(git.head_info().ok().and_then(|head| head.branch))
+            })?;
+            git_api.set("branch", branch)?;
+            let ahead_behind = scope.create_function(|_lua, ()| {
+                let ahead_behind = git.head_info().ok().and_then(|head| head.ahead_behind);
+                Ok(ahead_behind.unzip())
+            })?;
+            git_api.set("ahead_behind", ahead_behind)?;
+            let changed_files = scope.create_function(|_lua, ()| Ok(git.changed_files()))?;
+            git_api.set("changed_files", changed_files)?;
             f()
         })
     }
 
-    /// Gets a reference to the git table.
-    fn git_api(&self) -> mlua::Result<Option<mlua::Table>> {
-        let globals = self.inner.globals();
-        // TODO These hard-coded keys should be shared variables instead.
-        let api = globals.get::<mlua::Table>("fancytree")?;
-        api.get::<Option<mlua::Table>>("git")
+    /// Sets `fancytree.stats` to the numbers of directories and files in the tree.
+    pub fn set_stats(&self, stats: Stats) -> mlua::Result<()> {
+        self.api.set("stats", stats)
     }
 }
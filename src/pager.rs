@@ -0,0 +1,477 @@
+//! Module for paging output that's taller than the terminal, like `git` does.
+use crate::i18n::Lang;
+use clap::ValueEnum;
+use mlua::{FromLua, Lua};
+use std::env;
+use std::io::{self, BufRead as _, IsTerminal as _, Stdout, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+/// The environment variables that are checked for a pager, in order.
+const ENV_VARS: [&str; 2] = ["FANCY_TREE_PAGER", "PAGER"];
+
+/// The pager that's tried when none of the environment variables are set. Without
+/// it, the built-in pager is used.
+const DEFAULT: &str = "less";
+
+/// The height of the built-in pager's pages when the terminal's height is unknown.
+const DEFAULT_HEIGHT: usize = 24;
+
+/// The options that `less` gets when `$LESS` isn't set: quit if the output fits on
+/// one screen, write colors, and don't clear the screen when quitting.
+const LESS_DEFAULT: &str = "FRX";
+
+/// When output is piped through a pager.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Paging {
+    /// Page output that's taller than the terminal.
+    #[default]
+    Auto,
+    /// Page output, even when it fits or isn't written to a terminal.
+    Always,
+    /// Never page output.
+    Never,
+}
+
+/// A program that output is piped through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pager {
+    /// A program that was found on `$PATH`, with its arguments.
+    Program {
+        /// The path to the pager's executable.
+        program: PathBuf,
+        /// The arguments that are passed to the pager.
+        args: Vec<String>,
+        /// The environment variables that are set for the pager, like `$LESS`.
+        vars: Vec<(String, String)>,
+    },
+    /// The built-in pager, which waits for Enter after each page.
+    Builtin,
+}
+
+/// Output that's written to stdout until it's taller than the terminal, and that's
+/// piped through a pager after that.
+pub struct Output {
+    /// Where the output is written now.
+    state: State,
+}
+
+/// Where output is written.
+enum State {
+    /// Output is held until it has more lines than the terminal's height.
+    Held {
+        /// The output so far.
+        buffer: Vec<u8>,
+        /// The number of lines in the output so far.
+        lines: usize,
+        /// The terminal's height.
+        height: usize,
+        /// The pager to start when the output doesn't fit.
+        pager: Pager,
+        /// The language of the built-in pager's prompt.
+        lang: Lang,
+    },
+    /// Output is written to stdout.
+    Stdout(Stdout),
+    /// Output is piped to a pager.
+    Piped(Child, ChildStdin),
+    /// Output is written by the built-in pager.
+    Builtin(Builtin),
+    /// The pager quit, so the rest of the output is dropped.
+    Closed,
+}
+
+/// The built-in pager, which writes a page at a time to stdout.
+struct Builtin {
+    /// Stdout.
+    stdout: Stdout,
+    /// The number of lines that fit on a page, leaving room for the prompt.
+    page: usize,
+    /// The number of lines written since the last prompt.
+    lines: usize,
+    /// The language of the prompt.
+    lang: Lang,
+}
+
+impl FromLua for Paging {
+    fn from_lua(value: mlua::Value, _lua: &Lua) -> mlua::Result<Self> {
+        const VALID_VALUES: [&str; 3] = ["auto", "always", "never"];
+        let type_name = value.type_name();
+        let make_conversion_error = || mlua::Error::FromLuaConversionError {
+            from: type_name,
+            to: String::from("Paging"),
+            message: Some(format!("Must be one of {VALID_VALUES:?} or nil")),
+        };
+        let paging = value
+            .as_string()
+            .ok_or_else(make_conversion_error)?
+            .to_string_lossy();
+        let paging = match paging.as_str() {
+            "auto" => Self::Auto,
+            "always" => Self::Always,
+            "never" => Self::Never,
+            _ => return Err(make_conversion_error()),
+        };
+        Ok(paging)
+    }
+}
+
+impl Pager {
+    /// Finds the pager from `$FANCY_TREE_PAGER` or `$PAGER`, or else `less`, or else
+    /// the built-in pager. `None` means that paging is turned off, like with
+    /// `PAGER=cat`.
+    pub fn find() -> Option<Self> {
+        Self::find_with(
+            |var| env::var(var).ok(),
+            |program| which::which(program).ok(),
+        )
+    }
+
+    /// Finds the pager, using `env` to read environment variables and `which` to find
+    /// programs.
+    fn find_with<E, W>(env: E, which: W) -> Option<Self>
+    where
+        E: Fn(&str) -> Option<String>,
+        W: Fn(&str) -> Option<PathBuf>,
+    {
+        for var in ENV_VARS {
+            let Some(value) = env(var).filter(|value| !value.trim().is_empty()) else {
+                continue;
+            };
+            let Ok(words) = shell_words::split(&value) else {
+                continue;
+            };
+            let mut words = words.into_iter();
+            let Some(program) = words.next() else {
+                continue;
+            };
+            // NOTE Like with git, `cat` means that output shouldn't be paged.
+            if program == "cat" {
+                return None;
+            }
+            if let Some(program) = which(&program) {
+                return Some(Self::program(program, words.collect(), &env));
+            }
+        }
+        match which(DEFAULT) {
+            Some(program) => Some(Self::program(program, Vec::new(), &env)),
+            None => Some(Self::Builtin),
+        }
+    }
+
+    /// Creates a pager for a program, making sure that `less` writes colors instead
+    /// of escaping them.
+    fn program<E>(program: PathBuf, mut args: Vec<String>, env: E) -> Self
+    where
+        E: Fn(&str) -> Option<String>,
+    {
+        let mut vars = Vec::new();
+        if is_less(&program) {
+            match env("LESS") {
+                None => vars.push((String::from("LESS"), String::from(LESS_DEFAULT))),
+                Some(less) => {
+                    let words = shell_words::split(&less).unwrap_or_default();
+                    if !has_raw_flag(&words) && !has_raw_flag(&args) {
+                        args.push(String::from("-R"));
+                    }
+                }
+            }
+        }
+        Self::Program {
+            program,
+            args,
+            vars,
+        }
+    }
+
+    /// Starts the pager.
+    fn start(&self, lang: Lang, height: usize) -> io::Result<State> {
+        let (program, args, vars) = match self {
+            Self::Program {
+                program,
+                args,
+                vars,
+            } => (program, args, vars),
+            Self::Builtin => {
+                return Ok(State::Builtin(Builtin {
+                    stdout: io::stdout(),
+                    page: height.saturating_sub(1).max(1),
+                    lines: 0,
+                    lang,
+                }));
+            }
+        };
+        let mut child = Command::new(program)
+            .args(args)
+            .envs(vars.iter().map(|(name, value)| (name, value)))
+            .stdin(Stdio::piped())
+            .spawn()?;
+        let stdin = child
+            .stdin
+            .take()
+            .expect("The pager's stdin should be piped");
+        Ok(State::Piped(child, stdin))
+    }
+}
+
+impl Output {
+    /// Creates the output. With [`Paging::Auto`], the output is only paged if stdout
+    /// is a terminal, and if the output is taller than it.
+    pub fn new(paging: Paging, lang: Lang) -> Self {
+        let stdout = || Self {
+            state: State::Stdout(io::stdout()),
+        };
+        if paging == Paging::Never || (paging == Paging::Auto && !io::stdout().is_terminal()) {
+            return stdout();
+        }
+        let height = terminal_size::terminal_size().map(|(_, height)| usize::from(height.0));
+        let Some(pager) = Pager::find() else {
+            return stdout();
+        };
+        let height = match (paging, height) {
+            (Paging::Always, height) => {
+                let height = height.unwrap_or(DEFAULT_HEIGHT);
+                let state = pager
+                    .start(lang, height)
+                    .unwrap_or_else(|_| State::Stdout(io::stdout()));
+                return Self { state };
+            }
+            (_, Some(height)) => height,
+            (_, None) => return stdout(),
+        };
+        let state = State::Held {
+            buffer: Vec::new(),
+            lines: 0,
+            height,
+            pager,
+            lang,
+        };
+        Self { state }
+    }
+
+    /// Writes the output that's held, and waits for the pager to quit. Nothing else
+    /// is written after this.
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.flush()?;
+        match std::mem::replace(&mut self.state, State::Closed) {
+            State::Held { buffer, .. } => {
+                let mut stdout = io::stdout();
+                stdout.write_all(&buffer)?;
+                stdout.flush()
+            }
+            State::Piped(mut child, stdin) => {
+                drop(stdin);
+                child.wait()?;
+                Ok(())
+            }
+            State::Stdout(_) | State::Builtin(_) | State::Closed => Ok(()),
+        }
+    }
+}
+
+// NOTE If writing fails, the pager is still waited for, so that it isn't left
+//      running after this process exits.
+impl Drop for Output {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let result = match self.state {
+            State::Held {
+                ref mut buffer,
+                ref mut lines,
+                height,
+                ref pager,
+                lang,
+            } => {
+                buffer.extend_from_slice(buf);
+                *lines += buf.iter().filter(|&&byte| byte == b'\n').count();
+                // NOTE The last row of the terminal is left for the prompt after the
+                //      output.
+                if *lines < height {
+                    return Ok(buf.len());
+                }
+                let buffer = std::mem::take(buffer);
+                // NOTE If the pager can't be started, the output is written like it
+                //      wasn't paged.
+                self.state = pager
+                    .start(lang, height)
+                    .unwrap_or_else(|_| State::Stdout(io::stdout()));
+                self.write_all(&buffer).map(|()| buf.len())
+            }
+            State::Stdout(ref mut stdout) => stdout.write(buf),
+            State::Piped(_, ref mut stdin) => stdin.write(buf),
+            State::Builtin(ref mut builtin) => builtin.write(buf),
+            State::Closed => return Ok(buf.len()),
+        };
+        self.close_on_broken_pipe(result, buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let result = match self.state {
+            State::Stdout(ref mut stdout) => stdout.flush(),
+            State::Piped(_, ref mut stdin) => stdin.flush(),
+            State::Builtin(ref mut builtin) => builtin.flush(),
+            State::Held { .. } | State::Closed => return Ok(()),
+        };
+        self.close_on_broken_pipe(result.map(|()| 0), 0).map(|_| ())
+    }
+}
+
+impl Output {
+    /// Stops writing if the pager quit, like when `q` is pressed before the end of
+    /// the output. Other errors are returned.
+    fn close_on_broken_pipe(
+        &mut self,
+        result: io::Result<usize>,
+        written: usize,
+    ) -> io::Result<usize> {
+        match result {
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {
+                if let State::Piped(ref mut child, _) = self.state {
+                    child.wait()?;
+                }
+                self.state = State::Closed;
+                Ok(written)
+            }
+            result => result,
+        }
+    }
+}
+
+impl Builtin {
+    /// Waits for Enter after a page. Returns an error if `q` was entered.
+    fn prompt(&mut self) -> io::Result<()> {
+        let prompt = self.lang.message("pager-prompt", &[]);
+        write!(self.stdout, "{prompt}")?;
+        self.stdout.flush()?;
+        let mut answer = String::new();
+        io::stdin().lock().read_line(&mut answer)?;
+        // NOTE The prompt is cleared, so that the pages are continuous.
+        write!(self.stdout, "\x1b[1A\x1b[2K")?;
+        if answer.trim().eq_ignore_ascii_case("q") {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+        Ok(())
+    }
+}
+
+impl Write for Builtin {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for line in buf.split_inclusive(|&byte| byte == b'\n') {
+            if self.lines >= self.page {
+                self.prompt()?;
+                self.lines = 0;
+            }
+            self.stdout.write_all(line)?;
+            if line.ends_with(b"\n") {
+                self.lines += 1;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stdout.flush()
+    }
+}
+
+/// Checks if a program is `less`.
+fn is_less(program: &Path) -> bool {
+    program
+        .file_stem()
+        .is_some_and(|stem| stem.eq_ignore_ascii_case("less"))
+}
+
+/// Checks if `less`'s options include `-R` or `-r`, which write colors instead of
+/// escaping them.
+fn has_raw_flag<S>(words: &[S]) -> bool
+where
+    S: AsRef<str>,
+{
+    words.iter().map(AsRef::as_ref).any(|word| match word {
+        "--RAW-CONTROL-CHARS" | "--raw-control-chars" => true,
+        word if word.starts_with("--") => false,
+        // NOTE `$LESS` can also be flags without a `-`, like `FRX`.
+        word => word.trim_start_matches('-').contains(['R', 'r']),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use rstest::rstest;
+
+    /// Finds the pager with the environment variables, where only the programs in
+    /// `installed` are on `$PATH`.
+    fn find(vars: &[(&str, &str)], installed: &[&str]) -> Option<Pager> {
+        Pager::find_with(testing::env(vars), |program| {
+            installed
+                .contains(&program)
+                .then(|| Path::new("/bin").join(program))
+        })
+    }
+
+    /// Creates the pager for a program.
+    fn program(name: &str, args: &[&str], vars: &[(&str, &str)]) -> Pager {
+        Pager::Program {
+            program: Path::new("/bin").join(name),
+            args: args.iter().copied().map(String::from).collect(),
+            vars: vars
+                .iter()
+                .map(|&(name, value)| (String::from(name), String::from(value)))
+                .collect(),
+        }
+    }
+
+    #[rstest]
+    #[case(&[("PAGER", "most")], &["less", "most"], Some(program("most", &[], &[])))]
+    #[case(
+        &[("FANCY_TREE_PAGER", "moar -quit-if-one-screen"), ("PAGER", "most")],
+        &["moar", "most"],
+        Some(program("moar", &["-quit-if-one-screen"], &[]))
+    )]
+    #[case(&[("PAGER", "most")], &["less"], Some(program("less", &[], &[("LESS", "FRX")])))]
+    #[case(&[("PAGER", "less"), ("LESS", "-FRX")], &["less"], Some(program("less", &[], &[])))]
+    #[case(&[("PAGER", "less -S"), ("LESS", "F")], &["less"], Some(program("less", &["-S", "-R"], &[])))]
+    #[case(&[("PAGER", "less -r"), ("LESS", "-F")], &["less"], Some(program("less", &["-r"], &[])))]
+    #[case(&[("PAGER", "cat")], &["less"], None)]
+    #[case(&[("PAGER", "  ")], &[], Some(Pager::Builtin))]
+    #[case(&[], &[], Some(Pager::Builtin))]
+    fn test_find(
+        #[case] vars: &[(&str, &str)],
+        #[case] installed: &[&str],
+        #[case] expected: Option<Pager>,
+    ) {
+        assert_eq!(expected, find(vars, installed));
+    }
+
+    #[rstest]
+    #[case(&["-FRX"], true)]
+    #[case(&["FRX"], true)]
+    #[case(&["-F", "--RAW-CONTROL-CHARS"], true)]
+    #[case(&["--incsearch", "-F"], false)]
+    #[case(&[], false)]
+    fn test_has_raw_flag(#[case] words: &[&str], #[case] expected: bool) {
+        assert_eq!(expected, has_raw_flag(words));
+    }
+
+    #[test]
+    fn test_held_until_taller() {
+        let mut output = Output {
+            state: State::Held {
+                buffer: Vec::new(),
+                lines: 0,
+                height: 3,
+                pager: Pager::Builtin,
+                lang: Lang::En,
+            },
+        };
+        output.write_all(b"a\nb\n").unwrap();
+        assert!(matches!(output.state, State::Held { lines: 2, .. }));
+    }
+}
@@ -0,0 +1,318 @@
+//! Module for the built-in rules that map names to icons and colors, so that they can
+//! be exported for tools like theme generators.
+use crate::color::Color;
+use crate::colors;
+use crate::icons::{self, IconSet, Kind};
+use mlua::{FromLua, Lua};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+
+/// What a rule's pattern is matched against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Matcher {
+    /// A file's whole name, like `Cargo.toml`.
+    Filename,
+    /// A file's last two extensions, like `tar.gz`.
+    DoubleExtension,
+    /// A file's extension, without the leading `.`, like `rs`.
+    Extension,
+    /// A glob that a file's name matches, like `Dockerfile.*`.
+    Glob,
+    /// A directory's name, like `src`.
+    Directory,
+}
+
+/// What decided an entry's icon or color, to help find out why a rule doesn't
+/// apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Source {
+    /// A built-in rule for a file's whole name.
+    Filename,
+    /// A built-in rule for a file's last two extensions.
+    DoubleExtension,
+    /// A built-in rule for a file's extension.
+    Extension,
+    /// A built-in rule for a glob.
+    Glob,
+    /// A built-in rule for a directory's name.
+    Directory,
+    /// The language that was detected from a file's contents.
+    Language,
+    /// An [`IconTheme`](crate::tree::IconTheme) that was set when embedding.
+    IconTheme,
+    /// A function in the Lua configuration that changed the default.
+    Lua,
+    /// A plugin that changed the value it received.
+    Plugin,
+    /// The default for the kind of entry, like directories.
+    Default,
+}
+
+/// How the built-in rules and the Lua functions for icons and colors decide an
+/// entry's icon and color.
+///
+/// In every mode, a function can return `fancytree.builtin` to use the built-in
+/// choice.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Precedence {
+    /// Entries that a built-in rule matches don't call the function.
+    BuiltinFirst,
+    /// The function receives the built-in choice as its default, and decides.
+    #[default]
+    LuaFirst,
+    /// The function's value is combined with the built-in choice: `nil` keeps the
+    /// built-in choice, and styles get the colors that they don't set from it.
+    Merge,
+}
+
+/// A rule that maps names that match a pattern to a value, like an icon's kind.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Rule<T> {
+    /// What the pattern is matched against.
+    #[serde(rename = "match")]
+    pub matcher: Matcher,
+    /// The name, extension, or glob.
+    pub pattern: String,
+    /// Is the pattern matched ignoring case?
+    pub ignore_case: bool,
+    /// The value for names that match.
+    #[serde(flatten)]
+    pub value: T,
+}
+
+/// The icons of a kind in each icon set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Icon {
+    /// The kind of entry that the icons represent.
+    pub kind: Kind,
+    /// The Nerd Font icon.
+    pub nerd: &'static str,
+    /// The emoji.
+    pub emoji: &'static str,
+    /// The ASCII character.
+    pub ascii: &'static str,
+}
+
+/// A color, as a color name, an xterm color's number, or a hex code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleColor {
+    /// The color.
+    pub color: String,
+}
+
+/// Every built-in rule, in the order that the rules are checked. The first rule that
+/// matches a path decides its icon or color.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Rules {
+    /// The rules for icons.
+    pub icons: Vec<Rule<Icon>>,
+    /// The rules for colors.
+    pub colors: Vec<Rule<RuleColor>>,
+}
+
+impl Source {
+    /// Checks if a built-in rule or the detected language decided the value.
+    pub(crate) fn is_builtin(self) -> bool {
+        !matches!(
+            self,
+            Self::IconTheme | Self::Lua | Self::Plugin | Self::Default
+        )
+    }
+
+    /// Gets the source of a value after `source`, like a Lua function that receives
+    /// the default, turned `before` into `after`. Unchanged values keep this source.
+    pub(crate) fn changed_by<T>(self, before: &T, after: &T, source: Self) -> Self
+    where
+        T: PartialEq,
+    {
+        if before == after { self } else { source }
+    }
+}
+
+impl From<Matcher> for Source {
+    fn from(value: Matcher) -> Self {
+        match value {
+            Matcher::Filename => Self::Filename,
+            Matcher::DoubleExtension => Self::DoubleExtension,
+            Matcher::Extension => Self::Extension,
+            Matcher::Glob => Self::Glob,
+            Matcher::Directory => Self::Directory,
+        }
+    }
+}
+
+impl FromLua for Precedence {
+    fn from_lua(value: mlua::Value, _lua: &Lua) -> mlua::Result<Self> {
+        const VALID_VALUES: [&str; 3] = ["builtin-first", "lua-first", "merge"];
+        let type_name = value.type_name();
+        let make_conversion_error = || mlua::Error::FromLuaConversionError {
+            from: type_name,
+            to: String::from("Precedence"),
+            message: Some(format!("Must be one of {VALID_VALUES:?} or nil")),
+        };
+        let precedence = value
+            .as_string()
+            .ok_or_else(make_conversion_error)?
+            .to_string_lossy();
+        let precedence = match precedence.as_str() {
+            "builtin-first" => Self::BuiltinFirst,
+            "lua-first" => Self::LuaFirst,
+            "merge" => Self::Merge,
+            _ => return Err(make_conversion_error()),
+        };
+        Ok(precedence)
+    }
+}
+
+impl<T> Rule<T> {
+    /// Creates a rule that matches the pattern exactly.
+    pub(crate) fn new<S>(matcher: Matcher, pattern: S, value: T) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            matcher,
+            pattern: pattern.into(),
+            ignore_case: false,
+            value,
+        }
+    }
+
+    /// Matches the pattern ignoring case.
+    #[inline]
+    #[must_use]
+    pub(crate) fn ignore_case(self) -> Self {
+        Self {
+            ignore_case: true,
+            ..self
+        }
+    }
+
+    /// Maps the rule's value.
+    fn map<U, F>(self, f: F) -> Rule<U>
+    where
+        F: FnOnce(T) -> U,
+    {
+        Rule {
+            matcher: self.matcher,
+            pattern: self.pattern,
+            ignore_case: self.ignore_case,
+            value: f(self.value),
+        }
+    }
+}
+
+impl Icon {
+    /// Gets the icons of a kind.
+    fn new(kind: Kind) -> Self {
+        Self {
+            kind,
+            nerd: IconSet::Nerd.icon(kind),
+            emoji: IconSet::Emoji.icon(kind),
+            ascii: IconSet::Ascii.icon(kind),
+        }
+    }
+}
+
+impl Rules {
+    /// Gets the built-in rules.
+    pub fn builtin() -> Self {
+        let icons = icons::rules().map(|rule| rule.map(Icon::new)).collect();
+        let colors = colors::rules()
+            .map(|rule| {
+                rule.map(|color: Color| RuleColor {
+                    color: color.to_string(),
+                })
+            })
+            .collect();
+        Self { icons, colors }
+    }
+
+    /// Writes the rules as pretty-printed JSON.
+    pub fn write_json<W>(&self, mut writer: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writeln!(writer)?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Gets a path that the rule matches. Globs are tested with the lookups that
+    /// use them.
+    fn sample<T>(rule: &Rule<T>) -> Option<String> {
+        match rule.matcher {
+            Matcher::Filename | Matcher::Directory => Some(rule.pattern.clone()),
+            Matcher::DoubleExtension | Matcher::Extension => {
+                Some(format!("sample.{}", rule.pattern))
+            }
+            Matcher::Glob => None,
+        }
+    }
+
+    #[test]
+    fn test_icon_rules_match() {
+        let kind_for = |matcher, path: &str| match matcher {
+            Matcher::Directory => icons::kind_for_directory(path),
+            _ => icons::kind_for_path(path),
+        };
+        for rule in icons::rules() {
+            let Some(path) = sample(&rule) else {
+                continue;
+            };
+            assert_eq!(Some(rule.value), kind_for(rule.matcher, &path), "{path}");
+            if rule.ignore_case {
+                let path = path.to_ascii_uppercase();
+                assert_eq!(Some(rule.value), kind_for(rule.matcher, &path), "{path}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_color_rules_match() {
+        for rule in colors::rules() {
+            let Some(path) = sample(&rule) else {
+                continue;
+            };
+            assert_eq!(Some(rule.value), colors::for_path(&path), "{path}");
+        }
+    }
+
+    #[test]
+    fn test_write_json() {
+        let mut json = Vec::new();
+        Rules::builtin().write_json(&mut json).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+
+        let rust = value["icons"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|rule| rule["pattern"] == "rs")
+            .unwrap();
+        assert_eq!("extension", rust["match"]);
+        assert_eq!("Rust", rust["kind"]);
+        assert_eq!(true, rust["ignore_case"]);
+        assert_eq!(IconSet::Ascii.icon(Kind::Rust), rust["ascii"]);
+
+        let tar_gz = value["colors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|rule| rule["pattern"] == "tar.gz")
+            .unwrap();
+        assert_eq!("double-extension", tar_gz["match"]);
+        assert_eq!("green", tar_gz["color"]);
+
+        let mut again = Vec::new();
+        Rules::builtin().write_json(&mut again).unwrap();
+        assert_eq!(json, again);
+    }
+}
@@ -0,0 +1,478 @@
+//! Module for writing trees from other programs, like the C ABI and the Python
+//! module, with the same setup as the command-line.
+use crate::Tree;
+use crate::color::ColorChoice;
+use crate::config::{self, ConfigDir, ConfigLoader, ProjectOverrides};
+use crate::diagnostics::{Diagnostics, Source};
+use crate::git::Git;
+use crate::icons::IconSet;
+use crate::lua;
+use crate::tree::export::FlatNode;
+use crate::tree::{self, Format};
+use crate::vcs::{Mercurial, Vcs};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::{fs, io, iter};
+
+/// The options for writing a tree. Missing options use the defaults.
+///
+/// The values are named like the command-line's values, so that they can come from
+/// JSON like `{"format": "json", "level": 2}`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Options {
+    /// The format, like `"tree"` or `"json"`. Defaults to `"tree"`.
+    pub format: Option<String>,
+    /// The color choice, like `"on"` or `"ansi"`. Defaults to `"off"`, since the
+    /// output usually isn't written to a terminal.
+    pub color: Option<String>,
+    /// The icon set, like `"emoji"`. Defaults to `"nerd"`, or `icon_set` from the
+    /// user's configuration.
+    pub icons: Option<String>,
+    /// The maximum depth of the tree.
+    pub level: Option<usize>,
+    /// The maximum number of lines to write.
+    pub max_lines: Option<usize>,
+    /// Load the user's configuration files, instead of only using the defaults.
+    pub config: bool,
+    /// The directory of the configuration files, instead of the platform's
+    /// configuration directory. Used with `config`.
+    pub config_dir: Option<PathBuf>,
+    /// Don't merge the project's configuration file over the user's configuration.
+    /// Used with `config`.
+    pub no_project_config: bool,
+    /// Use the same configuration on every machine, like `--deterministic`. This
+    /// overrides `config`.
+    pub deterministic: bool,
+}
+
+/// A tree to write, with its options.
+///
+/// Each call reads the file system and the repository again, so the session can be
+/// kept and called again after the files change.
+#[derive(Debug, Clone)]
+pub struct Session {
+    /// The root of the tree.
+    root: PathBuf,
+    /// The options for writing the tree.
+    options: Options,
+}
+
+impl Session {
+    /// Creates a session for the tree at the root, with the default options.
+    pub fn new<P>(root: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            root: root.into(),
+            options: Options::default(),
+        }
+    }
+
+    /// Sets the options.
+    #[inline]
+    #[must_use]
+    pub fn options(self, options: Options) -> Self {
+        Self { options, ..self }
+    }
+
+    /// Writes the tree in the format of the options.
+    pub fn render(&self) -> crate::Result<String> {
+        let mut output = Vec::new();
+        self.with_tree(|tree| tree.write(&mut output))?;
+        Ok(String::from_utf8_lossy(&output).into_owned())
+    }
+
+    /// Gets the tree's entries in depth-first order, starting with the root.
+    pub fn entries(&self) -> crate::Result<Vec<FlatNode>> {
+        let node = self.with_tree(|tree| tree.export())?;
+        Ok(node.flatten())
+    }
+
+    /// Sets up the tree like the command-line does, and calls a function with it.
+    fn with_tree<T, F>(&self, f: F) -> crate::Result<T>
+    where
+        F: FnOnce(&Tree<'_, '_, &Path>) -> io::Result<T>,
+    {
+        let options = &self.options;
+        let root = self.root.as_path();
+        let format = options
+            .format
+            .as_deref()
+            .map(|format| parse_value::<Format>("format", format))
+            .transpose()?
+            .unwrap_or_default();
+        let color_choice = options
+            .color
+            .as_deref()
+            .map(|color| parse_value::<ColorChoice>("color", color))
+            .transpose()?
+            .unwrap_or(ColorChoice::Off);
+        let icon_set = options
+            .icons
+            .as_deref()
+            .map(|icons| parse_value::<IconSet>("icons", icons))
+            .transpose()?;
+
+        let mut setup = Setup::new(root, Diagnostics::default());
+        if options.deterministic {
+            setup = setup.deterministic();
+        } else if options.config {
+            let mut loader = ConfigLoader::new();
+            if let Ok(config_dir) = ConfigDir::new(options.config_dir.as_deref()) {
+                loader = loader.global_dir(config_dir);
+            }
+            if !options.no_project_config
+                && let Some(overrides) = project_overrides(root)
+            {
+                loader = loader.project_overrides(overrides);
+            }
+            setup = setup.loader(loader);
+        }
+        setup.run(|parts| {
+            let lua_state = parts.lua_state;
+            let mut builder = parts
+                .builder(root)
+                .format(format)
+                .color_choice(color_choice);
+            if let Some(icon_set) = icon_set {
+                builder = builder.icon_set(icon_set);
+            }
+            if let Some(level) = options.level {
+                builder = builder.max_level(level);
+            }
+            if let Some(max_lines) = options.max_lines {
+                builder = builder.max_lines(max_lines);
+            }
+            let tree = builder.build();
+
+            let value = lua_state.in_git_scope(|| f(&tree).map_err(mlua::Error::external))?;
+            Ok(value)
+        })
+    }
+}
+
+/// The setup that the command-line and sessions share: finding the repository,
+/// creating the Lua state, and loading the configuration files.
+pub(crate) struct Setup<'a> {
+    /// The root of the tree.
+    root: &'a Path,
+    /// Where errors are reported.
+    diagnostics: Diagnostics,
+    /// The loader of the user's configuration files. Without it, only the defaults
+    /// are used.
+    loader: Option<ConfigLoader>,
+    /// The theme, instead of the configuration's theme.
+    theme: Option<&'a str>,
+    /// Use the same configuration on every machine.
+    deterministic: bool,
+}
+
+/// What a [`Setup`] creates for a tree.
+pub(crate) struct Parts<'a, 'git> {
+    /// The git repository that contains the root.
+    pub git: Option<&'git Git>,
+    /// The repository that contains the root, either git or Mercurial.
+    pub vcs: Option<&'git dyn Vcs>,
+    /// The Lua state that the configurations were loaded with.
+    pub lua_state: &'a lua::state::State<'git>,
+    /// The main configuration.
+    pub config: Option<config::Main>,
+    /// The icon configuration.
+    pub icons: Option<config::Icons>,
+    /// The color configuration, with the theme.
+    pub colors: Option<config::Colors>,
+    /// The Lua plugins.
+    pub plugins: Option<config::Plugins>,
+    /// The WASM plugins.
+    pub wasm_plugins: Option<config::WasmPlugins>,
+}
+
+impl<'a> Setup<'a> {
+    /// Creates the setup for the tree at the root, which only uses the defaults.
+    pub fn new(root: &'a Path, diagnostics: Diagnostics) -> Self {
+        Self {
+            root,
+            diagnostics,
+            loader: None,
+            theme: None,
+            deterministic: false,
+        }
+    }
+
+    /// Loads the user's configuration files and plugins with the loader.
+    #[inline]
+    #[must_use]
+    pub fn loader(self, loader: ConfigLoader) -> Self {
+        Self {
+            loader: Some(loader),
+            ..self
+        }
+    }
+
+    /// Uses the theme instead of the configuration's theme.
+    #[inline]
+    #[must_use]
+    pub fn theme(self, theme: Option<&'a str>) -> Self {
+        Self { theme, ..self }
+    }
+
+    /// Uses the same configuration on every machine. The user's configuration files
+    /// and the global git excludes file are skipped, but the built-in themes can
+    /// still be used.
+    #[inline]
+    #[must_use]
+    pub fn deterministic(self) -> Self {
+        Self {
+            deterministic: true,
+            ..self
+        }
+    }
+
+    /// Sets up the tree and calls a function with the parts.
+    pub fn run<T, F>(self, f: F) -> crate::Result<T>
+    where
+        F: FnOnce(Parts<'_, '_>) -> crate::Result<T>,
+    {
+        let root = self.root;
+        // NOTE A repository that can't be read is reported and skipped.
+        let git = if self.deterministic {
+            Git::isolated(root)
+        } else {
+            Git::new(root)
+        };
+        let git = self.diagnostics.check(Source::Vcs, git).flatten();
+        // NOTE Mercurial is only checked when the path isn't in a git repository.
+        let hg = if git.is_none() {
+            self.diagnostics
+                .check(Source::Vcs, Mercurial::new(root))
+                .flatten()
+        } else {
+            None
+        };
+        let vcs = git
+            .as_ref()
+            .map(|git| git as &dyn Vcs)
+            .or(hg.as_ref().map(|hg| hg as &dyn Vcs));
+
+        // NOTE The Lua state must live as long as the configuration values.
+        let lua_state = {
+            let mut builder = lua::state::Builder::new().with_diagnostics(self.diagnostics);
+            if let Some(ref git) = git {
+                builder = builder.with_git(git);
+            }
+            builder.build()?
+        };
+
+        let (config, icons, colors, plugins, wasm_plugins) = match self.loader {
+            Some(ref loader) if !self.deterministic => {
+                let (config, icons, colors) = load_configs(&lua_state, loader, self.theme);
+                let plugins = loader.load_plugins(lua_state.to_inner());
+                let wasm_plugins = config
+                    .as_ref()
+                    .map(|config| loader.load_wasm_plugins(config));
+                (config, icons, colors, Some(plugins), wasm_plugins)
+            }
+            _ => {
+                // NOTE User configurations are skipped, but the built-in themes are
+                //      the same everywhere.
+                let config = self.deterministic.then(config::Main::deterministic);
+                let colors = load_theme(&lua_state, &ConfigLoader::new(), self.theme)
+                    .map(|theme| config::Colors::default().theme(theme));
+                (config, None, colors, None, None)
+            }
+        };
+
+        if let Some(ref git) = git {
+            let options = config
+                .as_ref()
+                .map(|config| *config.git_options())
+                .unwrap_or_default();
+            // NOTE The tree is searched while statuses are computed. If they can't be
+            //      computed, each status is read when it's needed instead.
+            git.load_statuses_in_background(options);
+        }
+
+        f(Parts {
+            git: git.as_ref(),
+            vcs,
+            lua_state: &lua_state,
+            config,
+            icons,
+            colors,
+            plugins,
+            wasm_plugins,
+        })
+    }
+}
+
+impl<'git> Parts<'_, 'git> {
+    /// Creates a builder for the tree at the root, with the repository and the
+    /// configurations.
+    pub fn builder<'charset, P>(self, root: P) -> tree::Builder<'git, 'charset, P>
+    where
+        P: AsRef<Path>,
+    {
+        let mut builder = tree::Builder::new(root);
+        if let Some(config) = self.config {
+            builder = builder.config(config);
+        }
+        if let Some(icons) = self.icons {
+            builder = builder.icons(icons);
+        }
+        if let Some(colors) = self.colors {
+            builder = builder.colors(colors);
+        }
+        if let Some(plugins) = self.plugins {
+            builder = builder.plugins(plugins);
+        }
+        if let Some(wasm_plugins) = self.wasm_plugins {
+            builder = builder.wasm_plugins(wasm_plugins);
+        }
+        if let Some(vcs) = self.vcs {
+            builder = builder.vcs(vcs);
+        }
+        builder
+    }
+}
+
+/// Parses the value of an option that's named like a command-line value.
+fn parse_value<T>(name: &str, value: &str) -> crate::Result<T>
+where
+    T: ValueEnum,
+{
+    T::from_str(value, true).map_err(|_| format!("invalid {name} {value:?}").into())
+}
+
+/// Loads the user's configuration files, reporting the ones that can't be loaded.
+///
+/// The theme is used instead of the configuration's theme.
+pub(crate) fn load_configs(
+    lua_state: &lua::state::State,
+    loader: &ConfigLoader,
+    theme: Option<&str>,
+) -> (
+    Option<config::Main>,
+    Option<config::Icons>,
+    Option<config::Colors>,
+) {
+    let lua_inner = lua_state.to_inner();
+    // NOTE A configuration that can't be loaded is reported, and the defaults are
+    //      used instead.
+    let diagnostics = lua_state.diagnostics();
+    let config = diagnostics
+        .check(Source::Config, loader.load_main(lua_inner))
+        .and_then(|config| config.value);
+    let icons = diagnostics
+        .check(Source::Icons, loader.load_icons(lua_inner, config.as_ref()))
+        .and_then(|icons| icons.value);
+    let colors = diagnostics
+        .check(
+            Source::Colors,
+            loader.load_colors(lua_inner, config.as_ref()),
+        )
+        .and_then(|colors| colors.value);
+    let theme = theme.or_else(|| config.as_ref().and_then(config::Main::theme));
+    let colors = match load_theme(lua_state, loader, theme) {
+        Some(theme) => Some(colors.unwrap_or_default().theme(theme)),
+        None => colors,
+    };
+    (config, icons, colors)
+}
+
+/// Loads a theme by its name, reporting a theme that can't be loaded.
+fn load_theme(
+    lua_state: &lua::state::State,
+    loader: &ConfigLoader,
+    name: Option<&str>,
+) -> Option<config::Theme> {
+    lua_state.diagnostics().check(
+        Source::Colors,
+        loader.load_theme(lua_state.to_inner(), name?),
+    )
+}
+
+/// Finds the project's configuration file in the tree's root, or else in the root
+/// of the repository that contains it.
+pub(crate) fn project_overrides(root: &Path) -> Option<ProjectOverrides> {
+    let root = fs::canonicalize(root).ok()?;
+    let repository = root
+        .ancestors()
+        .find(|dir| dir.join(".git").exists() || dir.join(".hg").exists());
+    ProjectOverrides::find(iter::once(root.as_path()).chain(repository))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Fixture;
+    use crate::tree::export::EntryType;
+
+    #[test]
+    fn test_entries() {
+        let fixture = Fixture::builder()
+            .file("src/main.rs", "")
+            .file("README.md", "")
+            .build()
+            .unwrap();
+        let session = Session::new(fixture.path());
+
+        let entries = session.entries().unwrap();
+        let paths = entries
+            .iter()
+            .map(|entry| entry.path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![".", "README.md", "src", "src/main.rs"], paths);
+        assert_eq!(EntryType::Directory, entries[2].entry_type);
+        assert_eq!(2, entries[3].depth);
+
+        let session = session.options(Options {
+            level: Some(1),
+            ..Options::default()
+        });
+        assert_eq!(3, session.entries().unwrap().len());
+    }
+
+    #[test]
+    fn test_config_options() {
+        let fixture = Fixture::builder()
+            .file("config/config.lua", "return { level = 1 }")
+            .file("tree/src/main.rs", "")
+            .file("tree/.fancytree.lua", "return { level = 0 }")
+            .build()
+            .unwrap();
+        let options = Options {
+            config: true,
+            config_dir: Some(fixture.join("config")),
+            ..Options::default()
+        };
+        let count = |options: &Options| {
+            let session = Session::new(fixture.join("tree")).options(options.clone());
+            session.entries().unwrap().len()
+        };
+
+        assert_eq!(1, count(&options));
+        let options = Options {
+            no_project_config: true,
+            ..options
+        };
+        assert_eq!(2, count(&options));
+        let options = Options {
+            deterministic: true,
+            ..options
+        };
+        assert_eq!(3, count(&options));
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        let session = Session::new(".").options(Options {
+            icons: Some(String::from("nerdfont")),
+            ..Options::default()
+        });
+        let error = session.render().unwrap_err();
+        assert_eq!(r#"invalid icons "nerdfont""#, error.to_string());
+    }
+}
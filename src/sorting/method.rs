@@ -3,6 +3,7 @@
 use mlua::{FromLua, Lua};
 use std::cmp::Ordering;
 use std::ffi::OsStr;
+use std::iter::{self, Peekable};
 
 /// How items should be sorted.
 #[non_exhaustive]
@@ -37,8 +38,8 @@ impl Method {
 
     /// Naturally sort two OS strings.
     fn cmp_natural(left: &OsStr, right: &OsStr) -> Ordering {
-        let mut left = left.as_encoded_bytes().iter().copied();
-        let mut right = right.as_encoded_bytes().iter().copied();
+        let mut left = left.as_encoded_bytes().iter().copied().peekable();
+        let mut right = right.as_encoded_bytes().iter().copied().peekable();
         loop {
             let (left_char, right_char) = match (left.next(), right.next()) {
                 (None, None) => break Ordering::Equal,
@@ -56,7 +57,8 @@ impl Method {
             // NOTE Both are ASCII digits, we should consume and compare.
             let left = Self::consume_digits(left_char, &mut left);
             let right = Self::consume_digits(right_char, &mut right);
-            let comparison = left.cmp(&right);
+            // NOTE Without leading zeros, a longer number is always larger.
+            let comparison = left.len().cmp(&right.len()).then_with(|| left.cmp(&right));
             if comparison.is_ne() {
                 break comparison;
             }
@@ -78,18 +80,20 @@ impl Method {
 
     /// Consumes part of a byte iterator to get a numerical string. The first char is the
     /// "trigger" to call this, and should be prepended.
-    fn consume_digits<I>(first_digit: u8, bytes: I) -> usize
+    ///
+    /// Leading zeros are removed. The digits aren't parsed, so that numbers too large
+    /// for an integer can still be compared.
+    fn consume_digits<I>(first_digit: u8, bytes: &mut Peekable<I>) -> Vec<u8>
     where
         I: Iterator<Item = u8>,
     {
-        let remaining_digits = bytes.take_while(|b| b.is_ascii_digit());
-        let digits = [first_digit]
+        // NOTE Peeking leaves the byte after the number to be compared.
+        let remaining_digits = iter::from_fn(|| bytes.next_if(u8::is_ascii_digit));
+        [first_digit]
             .into_iter()
             .chain(remaining_digits)
-            .collect::<Vec<u8>>();
-        // TODO If we're 100% confident, we can use the unsafe `from_utf8_unchecked` method.
-        let digits = String::from_utf8(digits).expect("The digits should all be valid UTF-8");
-        digits.parse().expect("The string should be a valid number")
+            .skip_while(|&b| b == b'0')
+            .collect()
     }
 
     /// Converts a string to `Self`.
@@ -142,6 +146,15 @@ mod tests {
     #[case::natural(Method::Natural, "12.txt", "10.txt", Ordering::Greater)]
     #[case::natural(Method::Natural, "1-2.txt", "10.txt", Ordering::Less)]
     #[case::natural(Method::Natural, "100-a.txt", "100-b.txt", Ordering::Less)]
+    #[case::natural(Method::Natural, "007.txt", "7.txt", Ordering::Equal)]
+    #[case::natural(Method::Natural, "1a", "1b", Ordering::Less)]
+    #[case::natural(Method::Natural, "v2.1", "v2.10", Ordering::Less)]
+    #[case::natural(
+        Method::Natural,
+        "99999999999999999999999999.txt",
+        "100000000000000000000000000.txt",
+        Ordering::Less
+    )]
     fn test_cmp(
         #[case] method: Method,
         #[case] left: &str,
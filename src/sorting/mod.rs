@@ -7,10 +7,12 @@ use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::ffi::OsStr;
 use std::path::Path;
+pub use tie_break::TieBreak;
 
 mod direction;
 mod directories;
 mod method;
+mod tie_break;
 
 /// Sorting options for paths.
 ///
@@ -18,6 +20,10 @@ mod method;
 ///
 /// 1. directories
 /// 2. method
+/// 3. tie break
+///
+/// The tie break compares the full file names, so only identical names are equal,
+/// and entries are listed in the same order on every run.
 #[derive(Debug)]
 #[non_exhaustive]
 pub struct Sorting {
@@ -47,6 +53,9 @@ pub struct Sorting {
     /// 2. `Dockerfile`
     /// 3. `.editorconfig`
     pub ignore_dot: bool,
+    /// How to order names that are equal after cleaning, like `README` and
+    /// `readme` when case is ignored.
+    pub tie_break: TieBreak,
 }
 
 impl Sorting {
@@ -80,10 +89,7 @@ impl Sorting {
 
     /// Cleans the filename for the path.
     fn clean_path<'a>(&self, path: &'a Path) -> Cow<'a, OsStr> {
-        let file_name = path
-            .file_name()
-            .expect("Path should always terminate in a named component");
-        let file_name = self.clean_dot(file_name);
+        let file_name = self.clean_dot(file_name(path));
         self.clean_casing(file_name)
     }
 
@@ -93,11 +99,18 @@ impl Sorting {
         L: AsRef<Path>,
         R: AsRef<Path>,
     {
-        let ordering = self.directories.cmp(&left, &right).then_with(|| {
-            let left = self.clean_path(left.as_ref());
-            let right = self.clean_path(right.as_ref());
-            self.method.cmp(left, right)
-        });
+        let ordering = self
+            .directories
+            .cmp(&left, &right)
+            .then_with(|| {
+                let left = self.clean_path(left.as_ref());
+                let right = self.clean_path(right.as_ref());
+                self.method.cmp(left, right)
+            })
+            .then_with(|| {
+                self.tie_break
+                    .cmp(file_name(left.as_ref()), file_name(right.as_ref()))
+            });
         match self.direction {
             Direction::Asc => ordering,
             Direction::Desc => ordering.reverse(),
@@ -105,6 +118,54 @@ impl Sorting {
     }
 }
 
+/// Gets the file name that paths are sorted by.
+#[inline]
+pub(crate) fn file_name(path: &Path) -> &OsStr {
+    path.file_name()
+        .expect("Path should always terminate in a named component")
+}
+
+/// Sorts with a stable merge sort that never panics, even if `compare` isn't a total
+/// order.
+///
+/// The standard library's sorts may panic on an inconsistent order, which a custom
+/// Lua function can return, and which can happen if an entry becomes a directory
+/// while sorting. Here, an inconsistent order only makes the result unspecified.
+pub(crate) fn sort_by<T, F>(items: &mut Vec<T>, mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    /// Sorts recursively, so that the comparison is always the same type.
+    fn merge_sort<T, F>(items: &mut Vec<T>, compare: &mut F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let len = items.len();
+        if len < 2 {
+            return;
+        }
+        let mut right = items.split_off(len / 2);
+        merge_sort(items, compare);
+        merge_sort(&mut right, compare);
+
+        let mut left = std::mem::take(items).into_iter().peekable();
+        let mut right = right.into_iter().peekable();
+        items.reserve(len);
+        loop {
+            // NOTE Taking from the left on ties keeps the sort stable.
+            let next = match (left.peek(), right.peek()) {
+                (Some(l), Some(r)) if compare(r, l).is_lt() => right.next(),
+                (Some(_), _) => left.next(),
+                (None, Some(_)) => right.next(),
+                (None, None) => break,
+            };
+            items.extend(next);
+        }
+    }
+
+    merge_sort(items, &mut compare);
+}
+
 impl Default for Sorting {
     fn default() -> Self {
         Self {
@@ -113,6 +174,7 @@ impl Default for Sorting {
             directories: Default::default(),
             ignore_case: Self::DEFAULT_IGNORE_CASE,
             ignore_dot: Self::DEFAULT_IGNORE_DOT,
+            tie_break: Default::default(),
         }
     }
 }
@@ -133,6 +195,9 @@ impl FromLua for Sorting {
         let ignore_dot = table
             .get::<Option<bool>>("ignore_dot")?
             .unwrap_or(Self::DEFAULT_IGNORE_DOT);
+        let tie_break = table
+            .get::<Option<TieBreak>>("tie_break")?
+            .unwrap_or_default();
 
         let sorting = Self {
             method,
@@ -140,6 +205,7 @@ impl FromLua for Sorting {
             directories,
             ignore_case,
             ignore_dot,
+            tie_break,
         };
         Ok(sorting)
     }
@@ -148,6 +214,7 @@ impl FromLua for Sorting {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
     use rstest::rstest;
 
     #[rstest]
@@ -174,4 +241,126 @@ mod tests {
 
         assert_eq!(OsStr::new(expected), sorting.clean_casing(OsStr::new(s)))
     }
+
+    /// Any combination of sorting options.
+    fn sorting() -> impl Strategy<Value = Sorting> {
+        let method = prop_oneof![Just(Method::Naive), Just(Method::Natural)];
+        let direction = prop_oneof![Just(Direction::Asc), Just(Direction::Desc)];
+        let directories = prop_oneof![
+            Just(Directories::Mixed),
+            Just(Directories::First),
+            Just(Directories::Last),
+        ];
+        let tie_break = prop_oneof![Just(TieBreak::Bytes), Just(TieBreak::Lowercase)];
+        (
+            method,
+            direction,
+            directories,
+            any::<bool>(),
+            any::<bool>(),
+            tie_break,
+        )
+            .prop_map(
+                |(method, direction, directories, ignore_case, ignore_dot, tie_break)| Sorting {
+                    method,
+                    direction,
+                    directories,
+                    ignore_case,
+                    ignore_dot,
+                    tie_break,
+                },
+            )
+    }
+
+    /// File names that are likely to have digits, dots, and mixed case in common.
+    fn entries() -> impl Strategy<Value = Vec<(String, bool)>> {
+        let name = "[.]?[aAbB0-9._-]{0,6}[0-9]{0,3}[aAbB]?".prop_filter("name", |name| {
+            !name.is_empty() && name != "." && name != ".."
+        });
+        proptest::collection::vec((name, any::<bool>()), 3..8)
+    }
+
+    /// Creates the entries, each in its own directory so that names can repeat.
+    fn create(root: &Path, entries: &[(String, bool)]) -> Vec<std::path::PathBuf> {
+        entries
+            .iter()
+            .enumerate()
+            .map(|(index, (name, is_dir))| {
+                let path = root.join(index.to_string()).join(name);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                if *is_dir {
+                    std::fs::create_dir(&path).unwrap();
+                } else {
+                    std::fs::write(&path, "").unwrap();
+                }
+                path
+            })
+            .collect()
+    }
+
+    proptest! {
+        #[test]
+        fn test_sort_by_inconsistent(mut items in proptest::collection::vec(any::<u8>(), 0..64), seed: u64) {
+            let expected = {
+                let mut sorted = items.clone();
+                sorted.sort_unstable();
+                sorted
+            };
+            // NOTE An order that changes between calls, like a misbehaving Lua function.
+            let mut state = seed;
+            sort_by(&mut items, |_, _| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                (state >> 62).cmp(&1)
+            });
+            let mut actual = items;
+            actual.sort_unstable();
+            prop_assert_eq!(expected, actual);
+        }
+
+        #[test]
+        fn test_sort_by(mut items in proptest::collection::vec(any::<u8>(), 0..64)) {
+            let mut expected = items.clone();
+            expected.sort();
+            sort_by(&mut items, Ord::cmp);
+            prop_assert_eq!(expected, items);
+        }
+
+        #[test]
+        fn test_only_identical_names_tie(sorting in sorting(), entries in entries()) {
+            let root = tempfile::TempDir::with_prefix("fancy-tree-").unwrap();
+            let paths = create(root.path(), &entries);
+
+            for a in &paths {
+                for b in &paths {
+                    let is_tie = sorting.cmp(a, b).is_eq();
+                    // NOTE A file and a directory with the same name can still be
+                    //      ordered by their types.
+                    let is_identical = a.file_name() == b.file_name() && a.is_dir() == b.is_dir();
+                    if is_tie {
+                        prop_assert_eq!(a.file_name(), b.file_name());
+                    }
+                    prop_assert!(!is_identical || is_tie, "{:?} {:?}", a, b);
+                }
+            }
+        }
+
+        #[test]
+        fn test_total_order(sorting in sorting(), entries in entries()) {
+            let root = tempfile::TempDir::with_prefix("fancy-tree-").unwrap();
+            let paths = create(root.path(), &entries);
+
+            for a in &paths {
+                prop_assert!(sorting.cmp(a, a).is_eq());
+                for b in &paths {
+                    let ordering = sorting.cmp(a, b);
+                    prop_assert_eq!(ordering.reverse(), sorting.cmp(b, a), "{:?} {:?}", a, b);
+                    for c in &paths {
+                        if ordering.is_le() && sorting.cmp(b, c).is_le() {
+                            prop_assert!(sorting.cmp(a, c).is_le(), "{:?} {:?} {:?}", a, b, c);
+                        }
+                    }
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,116 @@
+//! Module for ordering names that are otherwise equal.
+use mlua::{FromLua, Lua};
+use std::cmp::Ordering;
+use std::ffi::OsStr;
+
+/// How to order names that compare as equal, like `README` and `readme` when case
+/// is ignored.
+///
+/// Ties that remain, which only happens for identical names, keep the order they
+/// were read in.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TieBreak {
+    /// Compare the names byte by byte, so uppercase comes before lowercase.
+    Bytes,
+    /// Like [`TieBreak::Bytes`], but lowercase comes before uppercase.
+    Lowercase,
+}
+
+impl TieBreak {
+    const BYTES_NAME: &'static str = "bytes";
+    const LOWERCASE_NAME: &'static str = "lowercase";
+
+    /// Converts a string to `Self`.
+    fn from_string(s: &str) -> Option<Self> {
+        use TieBreak::*;
+
+        [(Self::BYTES_NAME, Bytes), (Self::LOWERCASE_NAME, Lowercase)]
+            .into_iter()
+            .find_map(|(name, t)| (s == name).then_some(t))
+    }
+
+    /// Compares two names.
+    pub fn cmp(&self, left: &OsStr, right: &OsStr) -> Ordering {
+        let left = left.as_encoded_bytes();
+        let right = right.as_encoded_bytes();
+        match self {
+            Self::Bytes => left.cmp(right),
+            // NOTE Swapping the case of every letter puts lowercase first, and is
+            //      reversible, so different names are never equal.
+            Self::Lowercase => {
+                let swap = |byte: &u8| {
+                    if byte.is_ascii_alphabetic() {
+                        byte ^ 0x20
+                    } else {
+                        *byte
+                    }
+                };
+                left.iter().map(swap).cmp(right.iter().map(swap))
+            }
+        }
+    }
+}
+
+impl Default for TieBreak {
+    #[inline]
+    fn default() -> Self {
+        Self::Bytes
+    }
+}
+
+impl FromLua for TieBreak {
+    fn from_lua(value: mlua::Value, lua: &Lua) -> mlua::Result<Self> {
+        let type_name = value.type_name();
+
+        let conversion_error = || {
+            let choices = [Self::BYTES_NAME, Self::LOWERCASE_NAME].join(", ");
+
+            mlua::Error::FromLuaConversionError {
+                from: type_name,
+                to: String::from("TieBreak"),
+                message: Some(choices),
+            }
+        };
+
+        let s = String::from_lua(value, lua)?;
+        Self::from_string(&s).ok_or_else(conversion_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(r#""bytes""#, TieBreak::Bytes)]
+    #[case(r#""lowercase""#, TieBreak::Lowercase)]
+    fn test_from_lua(#[case] chunk: &str, #[case] expected: TieBreak) {
+        let lua = Lua::new();
+        let actual: TieBreak = lua.load(chunk).eval().unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_from_lua_err() {
+        let lua = Lua::new();
+        let chunk = r#""uppercase""#;
+        assert!(lua.load(chunk).eval::<TieBreak>().is_err())
+    }
+
+    #[rstest]
+    #[case(TieBreak::Bytes, "README", "readme", Ordering::Less)]
+    #[case(TieBreak::Bytes, "Readme", "README", Ordering::Greater)]
+    #[case(TieBreak::Bytes, ".env", "env", Ordering::Less)]
+    #[case(TieBreak::Lowercase, "README", "readme", Ordering::Greater)]
+    #[case(TieBreak::Lowercase, "Readme", "README", Ordering::Less)]
+    #[case(TieBreak::Lowercase, "readme", "readme", Ordering::Equal)]
+    fn test_cmp(
+        #[case] tie_break: TieBreak,
+        #[case] left: &str,
+        #[case] right: &str,
+        #[case] expected: Ordering,
+    ) {
+        assert_eq!(expected, tie_break.cmp(OsStr::new(left), OsStr::new(right)));
+    }
+}
@@ -0,0 +1,260 @@
+//! Module for a file system that's kept in memory.
+use super::{EntryKind, FileSystem};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// A [`FileSystem`] that's kept in memory, so that fixtures can be created without
+/// touching the disk.
+///
+/// Paths are relative to the root, and can't leave it. Symlinks are followed like on
+/// disk, with their targets relative to the directory they're in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryFs {
+    /// The entries by their paths. The root isn't included, and is always a
+    /// directory.
+    entries: BTreeMap<PathBuf, Node>,
+}
+
+/// An entry of a [`MemoryFs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    /// A file and its contents.
+    File(Vec<u8>),
+    /// A directory.
+    Directory,
+    /// A symlink and its target.
+    Symlink(PathBuf),
+}
+
+impl MemoryFs {
+    /// The most symlinks that are followed for one path, like on Linux.
+    const MAX_LINKS: usize = 40;
+
+    /// Creates an empty file system.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolves the symlinks in a path, returning the path of the entry that it
+    /// refers to. The last component is only resolved if `follow_last` is `true`.
+    ///
+    /// Components that don't exist are kept, so that entries can be created there.
+    fn resolve(&self, path: &Path, follow_last: bool) -> io::Result<PathBuf> {
+        let mut pending = normalize(Path::new(""), path)?;
+        let mut resolved = PathBuf::new();
+        let mut links = 0;
+        while let Some(name) = pending.pop() {
+            let path = resolved.join(&name);
+            match self.entries.get(&path) {
+                Some(Node::Symlink(target)) if follow_last || !pending.is_empty() => {
+                    links += 1;
+                    if links > Self::MAX_LINKS {
+                        return Err(io::Error::other("too many levels of symlinks"));
+                    }
+                    // NOTE The target replaces the symlink, and is resolved again.
+                    pending.append(&mut normalize(&resolved, target)?);
+                    resolved = PathBuf::new();
+                }
+                Some(Node::File(_)) if !pending.is_empty() => {
+                    return Err(io::ErrorKind::NotADirectory.into());
+                }
+                _ => resolved = path,
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Gets the entry at a resolved path. The root is a directory.
+    fn get(&self, path: &Path) -> io::Result<&Node> {
+        const ROOT: &Node = &Node::Directory;
+
+        if path.as_os_str().is_empty() {
+            return Ok(ROOT);
+        }
+        self.entries.get(path).ok_or(io::ErrorKind::NotFound.into())
+    }
+
+    /// Adds an entry whose parent is a directory.
+    fn insert(&mut self, path: &Path, node: Node) -> io::Result<()> {
+        let path = self.resolve(path, false)?;
+        let parent = path.parent().ok_or(io::ErrorKind::InvalidInput)?;
+        if !matches!(self.get(parent)?, Node::Directory) {
+            return Err(io::ErrorKind::NotADirectory.into());
+        }
+        match self.entries.get(&path) {
+            Some(Node::Directory) => Err(io::ErrorKind::IsADirectory.into()),
+            Some(Node::Symlink(_)) if !matches!(node, Node::File(_)) => {
+                Err(io::ErrorKind::AlreadyExists.into())
+            }
+            _ => {
+                self.entries.insert(path, node);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl FileSystem for MemoryFs {
+    fn write(&mut self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        // NOTE Like on disk, writing to a symlink writes to its target.
+        let path = self.resolve(path, true)?;
+        self.insert(&path, Node::File(contents.to_vec()))
+    }
+
+    fn create_dir_all(&mut self, path: &Path) -> io::Result<()> {
+        let path = self.resolve(path, true)?;
+        for dir in path.ancestors().collect::<Vec<_>>().into_iter().rev() {
+            match self.get(dir) {
+                Ok(Node::Directory) => {}
+                Ok(_) => return Err(io::ErrorKind::AlreadyExists.into()),
+                Err(_) => {
+                    self.entries.insert(dir.to_path_buf(), Node::Directory);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn symlink(&mut self, path: &Path, target: &Path) -> io::Result<()> {
+        if self.kind(path).is_ok() {
+            return Err(io::ErrorKind::AlreadyExists.into());
+        }
+        self.insert(path, Node::Symlink(target.to_path_buf()))
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        match self.get(&self.resolve(path, true)?)? {
+            Node::File(contents) => Ok(contents.clone()),
+            Node::Directory => Err(io::ErrorKind::IsADirectory.into()),
+            Node::Symlink(_) => Err(io::ErrorKind::NotFound.into()),
+        }
+    }
+
+    fn kind(&self, path: &Path) -> io::Result<EntryKind> {
+        let kind = match self.get(&self.resolve(path, false)?)? {
+            Node::File(_) => EntryKind::File,
+            Node::Directory => EntryKind::Directory,
+            Node::Symlink(target) => EntryKind::Symlink(target.clone()),
+        };
+        Ok(kind)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<OsString>> {
+        let path = self.resolve(path, true)?;
+        if !matches!(self.get(&path)?, Node::Directory) {
+            return Err(io::ErrorKind::NotADirectory.into());
+        }
+        let names = self
+            .entries
+            .keys()
+            .filter(|child| child.parent() == Some(&path))
+            .filter_map(|child| child.file_name())
+            .map(OsString::from)
+            .collect();
+        Ok(names)
+    }
+}
+
+/// Joins a relative path to a base, removing `.` and `..`, and returns the names of
+/// its components in reverse, so that the first can be popped.
+///
+/// Absolute paths, and paths that leave the root, are invalid.
+fn normalize(base: &Path, path: &Path) -> io::Result<Vec<OsString>> {
+    let mut names = Vec::new();
+    for component in base.components().chain(path.components()) {
+        match component {
+            Component::Normal(name) => names.push(name.to_os_string()),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                names.pop().ok_or(io::ErrorKind::InvalidInput)?;
+            }
+            Component::RootDir | Component::Prefix(_) => {
+                return Err(io::ErrorKind::InvalidInput.into());
+            }
+        }
+    }
+    names.reverse();
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    /// Creates a file system with a file, an empty directory, and symlinks.
+    fn setup() -> MemoryFs {
+        let mut fs = MemoryFs::new();
+        fs.create_dir_all(Path::new("a/b")).unwrap();
+        fs.write(Path::new("a/b/file"), b"contents").unwrap();
+        fs.create_dir_all(Path::new("empty")).unwrap();
+        fs.symlink(Path::new("link"), Path::new("a")).unwrap();
+        fs.symlink(Path::new("a/up"), Path::new("../link/b"))
+            .unwrap();
+        fs.symlink(Path::new("loop"), Path::new("loop")).unwrap();
+        fs
+    }
+
+    #[rstest]
+    #[case("a/b/file")]
+    #[case("./a/b/../b/file")]
+    #[case("link/b/file")]
+    #[case("a/up/file")]
+    fn test_read(#[case] path: &str) {
+        let fs = setup();
+        assert_eq!(b"contents".to_vec(), fs.read(Path::new(path)).unwrap());
+    }
+
+    #[rstest]
+    #[case("missing", io::ErrorKind::NotFound)]
+    #[case("a", io::ErrorKind::IsADirectory)]
+    #[case("a/b/file/inner", io::ErrorKind::NotADirectory)]
+    #[case("../outside", io::ErrorKind::InvalidInput)]
+    #[case("/absolute", io::ErrorKind::InvalidInput)]
+    #[case("loop", io::ErrorKind::Other)]
+    fn test_read_error(#[case] path: &str, #[case] expected: io::ErrorKind) {
+        let fs = setup();
+        assert_eq!(expected, fs.read(Path::new(path)).unwrap_err().kind());
+    }
+
+    #[rstest]
+    #[case("a", EntryKind::Directory)]
+    #[case("a/b/file", EntryKind::File)]
+    #[case("link", EntryKind::Symlink(PathBuf::from("a")))]
+    #[case("link/b", EntryKind::Directory)]
+    fn test_kind(#[case] path: &str, #[case] expected: EntryKind) {
+        let fs = setup();
+        assert_eq!(expected, fs.kind(Path::new(path)).unwrap());
+    }
+
+    #[test]
+    fn test_read_dir() {
+        let fs = setup();
+        let names = |path| fs.read_dir(Path::new(path)).unwrap();
+        assert_eq!(
+            ["a", "empty", "link", "loop"].map(OsString::from),
+            *names("")
+        );
+        assert_eq!(["b", "up"].map(OsString::from), *names("link"));
+        assert!(names("empty").is_empty());
+    }
+
+    #[test]
+    fn test_write() {
+        let mut fs = setup();
+        fs.write(Path::new("link/b/file"), b"changed").unwrap();
+        assert_eq!(b"changed".to_vec(), fs.read(Path::new("a/b/file")).unwrap());
+
+        let error = fs.write(Path::new("missing/file"), b"").unwrap_err();
+        assert_eq!(io::ErrorKind::NotFound, error.kind());
+        let error = fs.write(Path::new("a"), b"").unwrap_err();
+        assert_eq!(io::ErrorKind::IsADirectory, error.kind());
+        let error = fs
+            .symlink(Path::new("a/b/file"), Path::new("a"))
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::AlreadyExists, error.kind());
+    }
+}
@@ -0,0 +1,423 @@
+//! Fixtures for testing code that uses fancy-tree.
+//!
+//! A [`Builder`] writes its steps through the [`FileSystem`] trait. Trees read the
+//! real filesystem, so [`Builder::build`] writes to a temporary directory that is
+//! removed when the [`Fixture`] is dropped. Code that only needs files, directories,
+//! and symlinks can use [`Builder::build_in_memory`] instead, which never touches
+//! the disk.
+//!
+//! ```no_run
+//! use fancy_tree::testing::Fixture;
+//!
+//! let fixture = Fixture::builder()
+//!     .git_init()
+//!     .file("src/main.rs", "fn main() {}")
+//!     .git_commit("Initial commit")
+//!     .file("README.md", "# Example")
+//!     .build()
+//!     .unwrap();
+//! let tree = fancy_tree::tree::Builder::new(fixture.path()).build();
+//! ```
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+pub use memory::MemoryFs;
+
+mod memory;
+
+/// A file system that fixtures can be written to.
+///
+/// Paths are relative to the file system's root.
+pub trait FileSystem {
+    /// Writes a file, replacing its contents if it exists.
+    fn write(&mut self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Creates a directory and its parents.
+    fn create_dir_all(&mut self, path: &Path) -> io::Result<()>;
+
+    /// Creates a symlink at `path` that points to `target`.
+    fn symlink(&mut self, path: &Path, target: &Path) -> io::Result<()>;
+
+    /// Reads a file, following symlinks.
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Gets the kind of an entry, without following it if it's a symlink.
+    fn kind(&self, path: &Path) -> io::Result<EntryKind>;
+
+    /// Gets the names of a directory's entries, sorted.
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<OsString>>;
+
+    /// Runs git with the arguments in the root.
+    ///
+    /// Git needs a repository on disk, so this fails unless the file system
+    /// supports it.
+    fn git(&mut self, args: &[OsString]) -> io::Result<()> {
+        let _ = args;
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "git needs a file system on disk",
+        ))
+    }
+}
+
+/// The kind of an entry in a [`FileSystem`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryKind {
+    /// A file.
+    File,
+    /// A directory.
+    Directory,
+    /// A symlink and its target.
+    Symlink(PathBuf),
+}
+
+/// A temporary directory that was created by a [`Builder`].
+pub struct Fixture {
+    /// The directory, which is removed when dropped.
+    dir: TempDir,
+}
+
+impl Fixture {
+    /// Creates a builder for a new fixture.
+    #[inline]
+    pub fn builder() -> Builder {
+        Builder::new()
+    }
+
+    /// Gets the root of the fixture.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Gets a path relative to the root of the fixture.
+    #[inline]
+    pub fn join<P>(&self, path: P) -> PathBuf
+    where
+        P: AsRef<Path>,
+    {
+        self.path().join(path)
+    }
+}
+
+impl FileSystem for Fixture {
+    #[inline]
+    fn write(&mut self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        fs::write(self.join(path), contents)
+    }
+
+    #[inline]
+    fn create_dir_all(&mut self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(self.join(path))
+    }
+
+    #[inline]
+    fn symlink(&mut self, path: &Path, target: &Path) -> io::Result<()> {
+        symlink(target, self.join(path))
+    }
+
+    #[inline]
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(self.join(path))
+    }
+
+    fn kind(&self, path: &Path) -> io::Result<EntryKind> {
+        let path = self.join(path);
+        let file_type = fs::symlink_metadata(&path)?.file_type();
+        let kind = if file_type.is_symlink() {
+            EntryKind::Symlink(fs::read_link(path)?)
+        } else if file_type.is_dir() {
+            EntryKind::Directory
+        } else {
+            EntryKind::File
+        };
+        Ok(kind)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<OsString>> {
+        let mut names = fs::read_dir(self.join(path))?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<io::Result<Vec<_>>>()?;
+        names.sort();
+        Ok(names)
+    }
+
+    #[inline]
+    fn git(&mut self, args: &[OsString]) -> io::Result<()> {
+        git(self.path(), args)
+    }
+}
+
+/// A step for creating a fixture.
+enum Step {
+    /// Writes a file, creating its parent directories.
+    File(PathBuf, Vec<u8>),
+    /// Creates a directory and its parents.
+    Directory(PathBuf),
+    /// Creates a symlink to the target.
+    Symlink(PathBuf, PathBuf),
+    /// Runs git with the arguments.
+    Git(Vec<OsString>),
+}
+
+/// Builds a fixture by running its steps in order.
+///
+/// Git steps run the `git` executable, so that fixtures don't depend on the git
+/// backend fancy-tree was built with. They fail in file systems that aren't on
+/// disk.
+#[derive(Default)]
+pub struct Builder {
+    /// The steps to run.
+    steps: Vec<Step>,
+}
+
+impl Builder {
+    /// Creates a builder for an empty directory.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes a file, creating its parent directories.
+    #[inline]
+    #[must_use]
+    pub fn file<P, C>(self, path: P, contents: C) -> Self
+    where
+        P: Into<PathBuf>,
+        C: Into<Vec<u8>>,
+    {
+        self.step(Step::File(path.into(), contents.into()))
+    }
+
+    /// Creates a directory and its parents.
+    #[inline]
+    #[must_use]
+    pub fn dir<P>(self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.step(Step::Directory(path.into()))
+    }
+
+    /// Creates a symlink at `path` that points to `target`.
+    ///
+    /// On Windows, the target must exist when the symlink is created, so that the
+    /// correct kind of symlink is used.
+    #[inline]
+    #[must_use]
+    pub fn symlink<P, T>(self, path: P, target: T) -> Self
+    where
+        P: Into<PathBuf>,
+        T: Into<PathBuf>,
+    {
+        self.step(Step::Symlink(path.into(), target.into()))
+    }
+
+    /// Initializes a git repository in the root of the fixture.
+    #[inline]
+    #[must_use]
+    pub fn git_init(self) -> Self {
+        self.git(["init", "--quiet", "--initial-branch=main"])
+    }
+
+    /// Stages every file and commits them.
+    #[inline]
+    #[must_use]
+    pub fn git_commit<S>(self, message: S) -> Self
+    where
+        S: Into<OsString>,
+    {
+        self.git(["add", "--all"]).git([
+            OsString::from("commit"),
+            OsString::from("--quiet"),
+            OsString::from("--allow-empty"),
+            OsString::from("--message"),
+            message.into(),
+        ])
+    }
+
+    /// Runs git with any arguments in the root of the fixture.
+    #[must_use]
+    pub fn git<I, S>(self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        let args = args.into_iter().map(Into::into).collect();
+        self.step(Step::Git(args))
+    }
+
+    /// Creates the fixture in a temporary directory.
+    pub fn build(self) -> io::Result<Fixture> {
+        let mut fixture = Fixture {
+            dir: TempDir::with_prefix("fancy-tree-")?,
+        };
+        self.build_in(&mut fixture)?;
+        Ok(fixture)
+    }
+
+    /// Creates the fixture in memory.
+    pub fn build_in_memory(self) -> io::Result<MemoryFs> {
+        let mut fs = MemoryFs::new();
+        self.build_in(&mut fs)?;
+        Ok(fs)
+    }
+
+    /// Runs the steps in a file system.
+    pub fn build_in<F>(self, fs: &mut F) -> io::Result<()>
+    where
+        F: FileSystem + ?Sized,
+    {
+        for step in self.steps {
+            match step {
+                Step::File(path, contents) => {
+                    if let Some(parent) = path.parent() {
+                        fs.create_dir_all(parent)?;
+                    }
+                    fs.write(&path, &contents)?;
+                }
+                Step::Directory(path) => fs.create_dir_all(&path)?,
+                Step::Symlink(path, target) => fs.symlink(&path, &target)?,
+                Step::Git(args) => fs.git(&args)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds a step.
+    #[inline]
+    fn step(mut self, step: Step) -> Self {
+        self.steps.push(step);
+        self
+    }
+}
+
+/// Creates a lookup of environment variables from a list of names and values, for
+/// code that takes a lookup instead of reading the process's variables.
+pub fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+    |name| {
+        vars.iter()
+            .find(|(var, _)| *var == name)
+            .map(|(_, value)| String::from(*value))
+    }
+}
+
+/// Creates a symlink.
+#[cfg(not(windows))]
+#[inline]
+fn symlink(target: &Path, path: PathBuf) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, path)
+}
+
+/// Creates a symlink, using the kind of the target.
+#[cfg(windows)]
+fn symlink(target: &Path, path: PathBuf) -> io::Result<()> {
+    use std::os::windows::fs::{symlink_dir, symlink_file};
+
+    let resolved = path
+        .parent()
+        .map_or_else(|| target.to_path_buf(), |parent| parent.join(target));
+    if resolved.is_dir() {
+        symlink_dir(target, path)
+    } else {
+        symlink_file(target, path)
+    }
+}
+
+/// Runs git in a directory, with an identity so that commits work anywhere.
+fn git(root: &Path, args: &[OsString]) -> io::Result<()> {
+    let status = Command::new("git")
+        .args(["-c", "user.name=fancy-tree"])
+        .args(["-c", "user.email=fancy-tree@example.com"])
+        .args(["-c", "commit.gpgsign=false"])
+        .args(args)
+        .current_dir(root)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env(
+            "GIT_CONFIG_GLOBAL",
+            if cfg!(windows) { "NUL" } else { "/dev/null" },
+        )
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "git {} failed with {status}",
+            args.join(" ".as_ref()).display()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::Git;
+
+    #[test]
+    fn test_fixture() {
+        let fixture = Fixture::builder()
+            .file("a/b/file", "contents")
+            .dir("empty")
+            .symlink("link", "a")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            "contents",
+            fs::read_to_string(fixture.join("a/b/file")).unwrap()
+        );
+        assert!(fixture.join("empty").is_dir());
+        assert!(fixture.join("link/b/file").is_file());
+    }
+
+    #[test]
+    fn test_build_in_memory() {
+        let builder = || {
+            Fixture::builder()
+                .file("a/b/file", "contents")
+                .dir("empty")
+                .symlink("link", "a")
+        };
+        let fixture = builder().build().unwrap();
+        let memory = builder().build_in_memory().unwrap();
+
+        for path in ["", "a", "link"] {
+            let path = Path::new(path);
+            assert_eq!(
+                fixture.read_dir(path).unwrap(),
+                memory.read_dir(path).unwrap()
+            );
+        }
+        for path in ["a", "a/b/file", "empty", "link"] {
+            let path = Path::new(path);
+            assert_eq!(fixture.kind(path).unwrap(), memory.kind(path).unwrap());
+        }
+        let path = Path::new("link/b/file");
+        assert_eq!(fixture.read(path).unwrap(), memory.read(path).unwrap());
+
+        let error = Fixture::builder().git_init().build_in_memory().unwrap_err();
+        assert_eq!(io::ErrorKind::Unsupported, error.kind());
+    }
+
+    #[test]
+    fn test_git() {
+        let fixture = Fixture::builder()
+            .git_init()
+            .file(".gitignore", "ignored\n")
+            .file("tracked", "")
+            .git_commit("Initial commit")
+            .file("ignored", "")
+            .build()
+            .unwrap();
+
+        let git = Git::new(fixture.path())
+            .unwrap()
+            .expect("The fixture should be a repository");
+        assert!(git.is_ignored("ignored").unwrap());
+        assert!(!git.is_ignored("tracked").unwrap());
+    }
+}
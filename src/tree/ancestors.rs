@@ -0,0 +1,53 @@
+//! Module for detecting loops, where a directory is reached again inside itself.
+use same_file::Handle;
+use std::rc::Rc;
+
+/// The directories that contain an entry, used to detect loops.
+///
+/// A directory is identified by device and inode on Unix and by file index on
+/// Windows, so that one that's reached again through a symlink or a bind mount is
+/// detected even though its path is different. Directories can be searched long
+/// after their parents, like when the tree is written level by level, so each one
+/// keeps its own ancestors, sharing them with its siblings.
+#[derive(Clone, Default)]
+pub(super) struct Ancestors(Option<Rc<(Handle, Ancestors)>>);
+
+impl Ancestors {
+    /// Adds a directory that contains the entries below it.
+    #[must_use]
+    pub fn with(&self, handle: Handle) -> Self {
+        Self(Some(Rc::new((handle, self.clone()))))
+    }
+
+    /// Checks if a directory is one of the ancestors.
+    pub fn contains(&self, handle: &Handle) -> bool {
+        let mut ancestors = self;
+        while let Some(ref node) = ancestors.0 {
+            if node.0 == *handle {
+                return true;
+            }
+            ancestors = &node.1;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Fixture;
+
+    #[test]
+    fn test_contains() {
+        let fixture = Fixture::builder().dir("a").dir("b").build().unwrap();
+        let handle = |path| Handle::from_path(fixture.path().join(path)).unwrap();
+
+        let root = Ancestors::default().with(handle(""));
+        let a = root.with(handle("a"));
+        assert!(a.contains(&handle("")));
+        assert!(a.contains(&handle("a")));
+        assert!(!a.contains(&handle("b")));
+        assert!(!root.contains(&handle("a")));
+        assert!(!Ancestors::default().contains(&handle("")));
+    }
+}
@@ -0,0 +1,246 @@
+//! Module for annotating entries with the output of external commands.
+use super::NamePattern;
+use mlua::{FromLua, Lua};
+use std::io::Read;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// External commands whose output annotates the entries that they match, like the
+/// dimensions of images.
+///
+/// The entry's path is passed as the last argument, and the trimmed output is
+/// written after the entry's name. Commands that fail, write nothing, or run longer
+/// than the timeout don't annotate anything.
+#[derive(Debug, Clone)]
+pub struct Annotations {
+    /// The commands, where the first one that matches an entry is used.
+    commands: Vec<Annotation>,
+    /// The most commands to run at once.
+    jobs: NonZeroUsize,
+    /// How long a command can run before it's stopped.
+    timeout: Duration,
+}
+
+/// A command for the entries whose names match a pattern.
+#[derive(Debug, Clone)]
+struct Annotation {
+    /// The pattern for file names.
+    pattern: NamePattern,
+    /// The program and its arguments.
+    command: Vec<String>,
+}
+
+impl Annotations {
+    /// How long a command can run when the timeout isn't configured.
+    const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// How often to check if a command has exited.
+    const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+    /// Runs the matching command for each path, running up to `jobs` commands at
+    /// once. Returns the annotation for each path, in the same order.
+    pub fn run<P>(&self, paths: &[P]) -> Vec<Option<String>>
+    where
+        P: AsRef<Path> + Sync,
+    {
+        let jobs = paths
+            .iter()
+            .enumerate()
+            .filter_map(|(index, path)| Some((index, path.as_ref(), self.command_for(path)?)))
+            .collect::<Vec<_>>();
+        let annotations = Mutex::new(vec![None; paths.len()]);
+        let next = AtomicUsize::new(0);
+        let workers = self.jobs.get().min(jobs.len());
+
+        // NOTE Each worker takes the next job until there are none left, so that a
+        //      slow command doesn't hold up the others.
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| {
+                    while let Some((index, path, command)) =
+                        jobs.get(next.fetch_add(1, Ordering::Relaxed))
+                    {
+                        let annotation = self.run_command(command, path);
+                        annotations
+                            .lock()
+                            .expect("Workers shouldn't panic while holding the lock")[*index] =
+                            annotation;
+                    }
+                });
+            }
+        });
+        annotations
+            .into_inner()
+            .expect("Workers shouldn't panic while holding the lock")
+    }
+
+    /// Gets the command for a path, if it matches any.
+    fn command_for<P>(&self, path: P) -> Option<&[String]>
+    where
+        P: AsRef<Path>,
+    {
+        self.commands
+            .iter()
+            .find(|annotation| annotation.pattern.matches(path.as_ref()))
+            .map(|annotation| annotation.command.as_slice())
+    }
+
+    /// Runs a command for a path, and gets its output if it succeeds in time.
+    fn run_command(&self, command: &[String], path: &Path) -> Option<String> {
+        let (program, args) = command.split_first()?;
+        let mut child = Command::new(program)
+            .args(args)
+            .arg(path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+
+        // NOTE Output is read while waiting, so that a command that fills the pipe
+        //      doesn't block until the timeout.
+        let mut stdout = child.stdout.take()?;
+        let reader = thread::spawn(move || {
+            let mut output = Vec::new();
+            stdout.read_to_end(&mut output).map(|_| output)
+        });
+
+        let deadline = Instant::now() + self.timeout;
+        let succeeded = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status.success(),
+                Ok(None) if Instant::now() < deadline => thread::sleep(Self::POLL_INTERVAL),
+                _ => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return None;
+                }
+            }
+        };
+        let output = reader.join().ok()?.ok()?;
+        if !succeeded {
+            return None;
+        }
+
+        // NOTE Annotations are written on the entry's line, so lines are joined.
+        let output = String::from_utf8_lossy(&output);
+        let annotation = output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        (!annotation.is_empty()).then_some(annotation)
+    }
+}
+
+impl FromLua for Annotations {
+    fn from_lua(value: mlua::Value, lua: &Lua) -> mlua::Result<Self> {
+        let conversion_error = |message: String| mlua::Error::FromLuaConversionError {
+            from: "table",
+            to: String::from("Annotations"),
+            message: Some(message),
+        };
+
+        let table = mlua::Table::from_lua(value, lua)?;
+        let jobs = match table.get::<Option<usize>>("jobs")? {
+            Some(jobs) => NonZeroUsize::new(jobs)
+                .ok_or_else(|| conversion_error(String::from("jobs must be at least 1")))?,
+            None => thread::available_parallelism().unwrap_or(NonZeroUsize::MIN),
+        };
+        let timeout = match table.get::<Option<f64>>("timeout")? {
+            Some(seconds) => Duration::try_from_secs_f64(seconds).map_err(|_| {
+                conversion_error(String::from("timeout must be a positive number of seconds"))
+            })?,
+            None => Self::DEFAULT_TIMEOUT,
+        };
+        let commands = table
+            .get::<Option<Vec<mlua::Table>>>("commands")?
+            .unwrap_or_default()
+            .into_iter()
+            .map(|command| {
+                let glob = command.get::<String>("glob")?;
+                let pattern = glob
+                    .parse()
+                    .map_err(|e| conversion_error(format!("invalid glob {glob:?}: {e}")))?;
+                let command = command.get::<Vec<String>>("command")?;
+                if command.is_empty() {
+                    return Err(conversion_error(format!(
+                        "the command for {glob:?} is empty"
+                    )));
+                }
+                Ok(Annotation { pattern, command })
+            })
+            .collect::<mlua::Result<_>>()?;
+
+        Ok(Self {
+            commands,
+            jobs,
+            timeout,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    /// Parses annotations from Lua.
+    fn annotations(chunk: &str) -> mlua::Result<Annotations> {
+        Lua::new().load(chunk).eval()
+    }
+
+    #[rstest]
+    #[case("{ jobs = 0 }")]
+    #[case("{ timeout = -1 }")]
+    #[case(r#"{ commands = { { glob = "*.png", command = {} } } }"#)]
+    #[case(r#"{ commands = { { glob = "[", command = { "echo" } } } }"#)]
+    fn test_from_lua_err(#[case] chunk: &str) {
+        assert!(annotations(chunk).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run() {
+        let annotations = annotations(
+            r#"{
+              jobs = 2,
+              commands = {
+                { glob = "*.png", command = { "echo", "png" } },
+                { glob = "*.txt|*.png", command = { "sh", "-c", "printf ' a\nb \n'" } },
+                { glob = "*.md", command = { "false" } },
+              },
+            }"#,
+        )
+        .unwrap();
+
+        let paths = ["a.png", "a.txt", "a.md", "a.rs", "b.png"];
+        let expected = vec![
+            Some(String::from("png a.png")),
+            Some(String::from("a b")),
+            None,
+            None,
+            Some(String::from("png b.png")),
+        ];
+        assert_eq!(expected, annotations.run(&paths));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_timeout() {
+        let annotations = annotations(
+            r#"{ timeout = 0.1, commands = { { glob = "*", command = { "sh", "-c", "sleep 5" } } } }"#,
+        )
+        .unwrap();
+
+        let start = Instant::now();
+        assert_eq!(vec![None], annotations.run(&["a"]));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,77 @@
+//! Module for keeping right-to-left names from reordering the rest of a line.
+//!
+//! A terminal that supports bidirectional text reorders a run of right-to-left
+//! characters, like Arabic or Hebrew, together with the neutral characters around
+//! it. A name like `ملف.txt` could pull the status, the symlink target, or the
+//! guides into its run. Wrapping the name in an isolate keeps its ordering to
+//! itself, and ends any direction overrides inside of it.
+use std::borrow::Cow;
+
+/// Starts an isolate whose direction is decided by its first strong character.
+pub const FIRST_STRONG_ISOLATE: char = '\u{2068}';
+
+/// Ends the isolate.
+pub const POP_DIRECTIONAL_ISOLATE: char = '\u{2069}';
+
+/// Checks if text should be isolated, because it has right-to-left characters or
+/// characters that change the direction of the text after them.
+///
+/// Left-to-right text is never isolated, so that it's written as it was before.
+pub fn needs_isolation(s: &str) -> bool {
+    s.chars().any(|c| is_right_to_left(c) || is_control(c))
+}
+
+/// Wraps text in an isolate if it [needs one](needs_isolation).
+pub fn isolate(s: &str) -> Cow<'_, str> {
+    if needs_isolation(s) {
+        Cow::Owned(format!(
+            "{FIRST_STRONG_ISOLATE}{s}{POP_DIRECTIONAL_ISOLATE}"
+        ))
+    } else {
+        Cow::Borrowed(s)
+    }
+}
+
+/// Checks if a character is in a block of right-to-left scripts, like Hebrew,
+/// Arabic, Syriac, and Thaana.
+fn is_right_to_left(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0590}'..='\u{08FF}'
+            | '\u{FB1D}'..='\u{FDFF}'
+            | '\u{FE70}'..='\u{FEFF}'
+            | '\u{10800}'..='\u{10FFF}'
+            | '\u{1E800}'..='\u{1EFFF}'
+    )
+}
+
+/// Checks if a character is an explicit directional formatting character, like a
+/// right-to-left override.
+fn is_control(c: char) -> bool {
+    matches!(
+        c,
+        '\u{061C}' | '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use unicode_width::UnicodeWidthStr;
+
+    #[rstest]
+    #[case::ascii("main.rs", false)]
+    #[case::wide("日本語.txt", false)]
+    #[case::arabic("ملف.txt", true)]
+    #[case::hebrew("קובץ", true)]
+    #[case::override_("evil\u{202E}txt.exe", true)]
+    fn test_isolate(#[case] name: &str, #[case] expected: bool) {
+        let isolated = isolate(name);
+        assert_eq!(expected, needs_isolation(name));
+        assert_eq!(expected, isolated.len() > name.len());
+        assert!(isolated.contains(name));
+        // NOTE The isolate doesn't take up any columns, so alignment is unchanged.
+        assert_eq!(name.width(), isolated.width());
+    }
+}
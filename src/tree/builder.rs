@@ -1,27 +1,107 @@
 //! Provides tools for building a [`Tree`].
-use super::Tree;
 use super::charset::Charset;
+use super::{
+    Annotations, FileLimitMode, Format, GitDir, Histogram, IconTheme, MtimeFormat, NamePattern,
+    RecentWindow, Size, SizeMode, SortKey, Stats, StatusGlyphs, Tree,
+};
 use crate::color::ColorChoice;
 use crate::config;
-use crate::git::Git;
+use crate::i18n::Lang;
+use crate::icons::IconSet;
+use crate::ignore::Ignore;
+use crate::sorting::Sorting;
+use crate::vcs::{Churn, LastCommits, Vcs};
+use std::cell::{Cell, OnceCell, RefCell};
+use std::collections::HashMap;
 use std::path::Path;
+use std::time::SystemTime;
 
-pub struct Builder<'git, 'charset, P: AsRef<Path>> {
+pub struct Builder<'vcs, 'charset, P: AsRef<Path>> {
     /// The root path for the [`Tree`].
     root: P,
-    /// The optional git state.
-    git: Option<&'git Git>,
+    format: Option<Format>,
+    /// The optional URL to link entries to in HTML output.
+    html_base: Option<String>,
+    /// Wrap names in backticks in Markdown output.
+    markdown_code: bool,
+    /// The optional version control state.
+    vcs: Option<&'vcs dyn Vcs>,
+    /// The optional commit counts to display.
+    churn: Option<Churn>,
+    /// The optional last commit times to color file names by.
+    last_commits: Option<LastCommits>,
     color_choice: Option<ColorChoice>,
     charset: Option<Charset<'charset>>,
     max_level: Option<usize>,
+    max_lines: Option<usize>,
+    /// The optional number of entries to write in each directory.
+    file_limit: Option<usize>,
+    /// What happens to directories with more entries than the file limit.
+    file_limit_mode: Option<FileLimitMode>,
+    /// The optional size above which files are highlighted.
+    warn_size: Option<Size>,
+    /// The optional ignore files to check.
+    ignore: Option<Ignore>,
+    /// Disable all ignore rules.
+    no_ignore: bool,
+    /// Show hidden entries.
+    show_hidden: bool,
+    /// The optional pattern that file names must match.
+    pattern: Option<NamePattern>,
+    /// Hide directories without displayed entries.
+    prune: Option<bool>,
+    size_mode: Option<SizeMode>,
+    /// Add the total size to the summary.
+    disk_usage: bool,
+    /// Show modification times.
+    mtime: Option<bool>,
+    /// The optional format for modification times.
+    mtime_format: Option<MtimeFormat>,
+    /// Highlight recently modified files.
+    recent: Option<bool>,
+    /// The optional window for recently modified files.
+    recent_window: Option<RecentWindow>,
+    /// The optional commands that annotate entries.
+    annotations: Option<Annotations>,
+    /// Show permissions.
+    permissions: Option<bool>,
+    /// Link names to their files.
+    hyperlinks: Option<bool>,
+    /// Write the root's path as a breadcrumb.
+    breadcrumb: Option<bool>,
+    /// Write version control statuses after the tree.
+    two_pass: bool,
+    /// Write deleted paths that are still tracked.
+    show_deleted: bool,
+    /// Write the tree level by level.
+    breadth_first: bool,
+    /// What a flat listing is sorted by.
+    sort_key: SortKey,
+    /// Sort a flat listing in reverse.
+    reverse: bool,
+    /// Traverse symlinks to directories.
+    follow_symlinks: Option<bool>,
+    /// The optional `.git` directory mode, which overrides the configuration.
+    git_dir: Option<GitDir>,
+    /// The optional icon set, which overrides the configuration.
+    icon_set: Option<IconSet>,
+    /// The optional sorting, which overrides the configuration.
+    sorting: Option<Sorting>,
+    /// The optional icons, which override the icon configuration.
+    icon_theme: Option<IconTheme>,
+    status_glyphs: Option<StatusGlyphs>,
     /// Override the level limit that may be set by the configuration.
     unset_level: bool,
+    /// The language of the summary.
+    lang: Lang,
     config: Option<config::Main>,
     icons: Option<config::Icons>,
     colors: Option<config::Colors>,
+    plugins: Option<config::Plugins>,
+    wasm_plugins: Option<config::WasmPlugins>,
 }
 
-impl<'git, 'charset, P> Builder<'git, 'charset, P>
+impl<'vcs, 'charset, P> Builder<'vcs, 'charset, P>
 where
     P: AsRef<Path>,
 {
@@ -30,28 +110,123 @@ where
     pub fn new(root: P) -> Self {
         Self {
             root,
-            git: None,
+            format: None,
+            html_base: None,
+            markdown_code: false,
+            vcs: None,
+            churn: None,
+            last_commits: None,
             max_level: None,
+            max_lines: None,
+            file_limit: None,
+            file_limit_mode: None,
+            warn_size: None,
+            ignore: None,
+            no_ignore: false,
+            show_hidden: false,
+            pattern: None,
+            prune: None,
+            size_mode: None,
+            disk_usage: false,
+            mtime: None,
+            mtime_format: None,
+            recent: None,
+            recent_window: None,
+            annotations: None,
+            permissions: None,
+            hyperlinks: None,
+            breadcrumb: None,
+            two_pass: false,
+            show_deleted: false,
+            breadth_first: false,
+            sort_key: SortKey::default(),
+            reverse: false,
+            follow_symlinks: None,
+            git_dir: None,
+            icon_set: None,
+            sorting: None,
+            icon_theme: None,
+            status_glyphs: None,
             unset_level: false,
+            lang: Lang::default(),
             charset: None,
             color_choice: None,
             config: None,
             icons: None,
             colors: None,
+            plugins: None,
+            wasm_plugins: None,
         }
     }
 
-    /// Adds a git state for the [`Tree`].
+    /// Sets the [`Format`] to write the [`Tree`] in.
     #[inline]
     #[must_use]
-    pub fn git(self, git: &'git Git) -> Self {
+    pub fn format(self, format: Format) -> Self {
         Self {
-            git: Some(git),
+            format: Some(format),
+            ..self
+        }
+    }
+
+    /// Links entries to their paths relative to `base` when the [`Tree`] is written
+    /// as HTML.
+    #[inline]
+    #[must_use]
+    pub fn html_base<S>(self, base: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            html_base: Some(base.into()),
+            ..self
+        }
+    }
+
+    /// Wraps names in backticks when the [`Tree`] is written as Markdown.
+    #[inline]
+    #[must_use]
+    pub fn markdown_code(self) -> Self {
+        Self {
+            markdown_code: true,
+            ..self
+        }
+    }
+
+    /// Adds a version control state for the [`Tree`].
+    #[inline]
+    #[must_use]
+    pub fn vcs(self, vcs: &'vcs dyn Vcs) -> Self {
+        Self {
+            vcs: Some(vcs),
+            ..self
+        }
+    }
+
+    /// Adds commit counts to display for each file in the [`Tree`].
+    #[inline]
+    #[must_use]
+    pub fn churn(self, churn: Churn) -> Self {
+        Self {
+            churn: Some(churn),
+            ..self
+        }
+    }
+
+    /// Colors file names in the [`Tree`] by the age of their last commit.
+    #[inline]
+    #[must_use]
+    pub fn last_commits(self, last_commits: LastCommits) -> Self {
+        Self {
+            last_commits: Some(last_commits),
             ..self
         }
     }
 
     /// Sets the maximum depth level for the [`Tree`].
+    ///
+    /// The root is at level 0, so `0` writes only the root, and `1` also writes the
+    /// root's entries.
     #[inline]
     #[must_use]
     pub fn max_level(self, level: usize) -> Self {
@@ -61,6 +236,335 @@ where
         }
     }
 
+    /// Sets the maximum number of lines to write for the [`Tree`].
+    #[inline]
+    #[must_use]
+    pub fn max_lines(self, lines: usize) -> Self {
+        Self {
+            max_lines: Some(lines),
+            ..self
+        }
+    }
+
+    /// Sets how many entries to write in each directory of the [`Tree`] before
+    /// truncating it.
+    #[inline]
+    #[must_use]
+    pub fn file_limit(self, limit: usize) -> Self {
+        Self {
+            file_limit: Some(limit),
+            ..self
+        }
+    }
+
+    /// Sets what happens to directories with more entries than the file limit.
+    #[inline]
+    #[must_use]
+    pub fn file_limit_mode(self, file_limit_mode: FileLimitMode) -> Self {
+        Self {
+            file_limit_mode: Some(file_limit_mode),
+            ..self
+        }
+    }
+
+    /// Sets the size above which files are highlighted in the [`Tree`].
+    #[inline]
+    #[must_use]
+    pub fn warn_size(self, size: Size) -> Self {
+        Self {
+            warn_size: Some(size),
+            ..self
+        }
+    }
+
+    /// Sets the ignore files to check for the [`Tree`], in addition to version
+    /// control.
+    #[inline]
+    #[must_use]
+    pub fn ignore(self, ignore: Ignore) -> Self {
+        Self {
+            ignore: Some(ignore),
+            ..self
+        }
+    }
+
+    /// Disables ignore rules for the [`Tree`], from both version control and ignore
+    /// files, so that ignored entries are treated like any other entry.
+    #[inline]
+    #[must_use]
+    pub fn no_ignore(self) -> Self {
+        Self {
+            no_ignore: true,
+            ..self
+        }
+    }
+
+    /// Shows hidden entries in the [`Tree`], like dotfiles, instead of skipping them
+    /// by default. The configuration's `skip` function can still skip them.
+    #[inline]
+    #[must_use]
+    pub fn show_hidden(self) -> Self {
+        Self {
+            show_hidden: true,
+            ..self
+        }
+    }
+
+    /// Only displays files whose names match the pattern in the [`Tree`].
+    #[inline]
+    #[must_use]
+    pub fn pattern(self, pattern: NamePattern) -> Self {
+        Self {
+            pattern: Some(pattern),
+            ..self
+        }
+    }
+
+    /// Sets whether directories that don't have any displayed entries are hidden in
+    /// the [`Tree`]. This overrides the configuration.
+    #[inline]
+    #[must_use]
+    pub fn prune(self, prune: bool) -> Self {
+        Self {
+            prune: Some(prune),
+            ..self
+        }
+    }
+
+    /// Sets which entries have their sizes displayed in the [`Tree`].
+    #[inline]
+    #[must_use]
+    pub fn size_mode(self, size_mode: SizeMode) -> Self {
+        Self {
+            size_mode: Some(size_mode),
+            ..self
+        }
+    }
+
+    /// Shows the total sizes of directories in the [`Tree`], like `du`, and adds the
+    /// total size of the tree to its [`Stats`]. This overrides the size mode.
+    ///
+    /// The sizes of all directories are computed at once, on several threads.
+    #[inline]
+    #[must_use]
+    pub fn disk_usage(self) -> Self {
+        Self {
+            disk_usage: true,
+            ..self
+        }
+    }
+
+    /// Sets whether modification times are shown in the [`Tree`]. This overrides the
+    /// configuration.
+    #[inline]
+    #[must_use]
+    pub fn mtime(self, mtime: bool) -> Self {
+        Self {
+            mtime: Some(mtime),
+            ..self
+        }
+    }
+
+    /// Sets the format for modification times in the [`Tree`]. This overrides the
+    /// format that may be set by the configuration.
+    #[inline]
+    #[must_use]
+    pub fn mtime_format(self, format: MtimeFormat) -> Self {
+        Self {
+            mtime_format: Some(format),
+            ..self
+        }
+    }
+
+    /// Sets whether files in the [`Tree`] that were modified recently are highlighted.
+    /// This overrides the configuration.
+    #[inline]
+    #[must_use]
+    pub fn recent(self, recent: bool) -> Self {
+        Self {
+            recent: Some(recent),
+            ..self
+        }
+    }
+
+    /// Sets how recently files must have been modified to be highlighted in the
+    /// [`Tree`]. This overrides the window that may be set by the configuration.
+    #[inline]
+    #[must_use]
+    pub fn recent_window(self, window: RecentWindow) -> Self {
+        Self {
+            recent_window: Some(window),
+            ..self
+        }
+    }
+
+    /// Sets the commands whose output annotates entries in the [`Tree`]. This
+    /// overrides the commands that may be set by the configuration.
+    #[inline]
+    #[must_use]
+    pub fn annotations(self, annotations: Annotations) -> Self {
+        Self {
+            annotations: Some(annotations),
+            ..self
+        }
+    }
+
+    /// Writes the [`Tree`] without waiting for version control statuses, and lists
+    /// the statuses of its entries after it. This only affects the text tree.
+    #[inline]
+    #[must_use]
+    pub fn two_pass(self) -> Self {
+        Self {
+            two_pass: true,
+            ..self
+        }
+    }
+
+    /// Writes the paths that were deleted from the working tree but are still
+    /// tracked, struck through in the removed status's style, in the [`Tree`]. This
+    /// only affects the text tree.
+    #[inline]
+    #[must_use]
+    pub fn show_deleted(self) -> Self {
+        Self {
+            show_deleted: true,
+            ..self
+        }
+    }
+
+    /// Writes the [`Tree`] level by level, listing all entries at one depth before
+    /// the entries at the next depth. Each directory's entries are written under
+    /// its path. This only affects the text tree.
+    #[inline]
+    #[must_use]
+    pub fn breadth_first(self) -> Self {
+        Self {
+            breadth_first: true,
+            ..self
+        }
+    }
+
+    /// Sets what a flat listing of the [`Tree`] is sorted by. Every entry in the
+    /// tree is sorted at once, instead of the entries of each directory.
+    #[inline]
+    #[must_use]
+    pub fn sort_key(self, sort_key: SortKey) -> Self {
+        Self { sort_key, ..self }
+    }
+
+    /// Sorts a flat listing of the [`Tree`] in reverse, like to list the largest
+    /// files first.
+    #[inline]
+    #[must_use]
+    pub fn reverse(self) -> Self {
+        Self {
+            reverse: true,
+            ..self
+        }
+    }
+
+    /// Sets whether permissions are shown in the [`Tree`]. This overrides the
+    /// configuration.
+    #[inline]
+    #[must_use]
+    pub fn permissions(self, permissions: bool) -> Self {
+        Self {
+            permissions: Some(permissions),
+            ..self
+        }
+    }
+
+    /// Sets whether the names in the [`Tree`] link to their files with OSC 8
+    /// hyperlinks, which terminals can open when they're clicked. Names are only linked
+    /// when colors are written, and in formats for terminals. This overrides the
+    /// configuration.
+    #[inline]
+    #[must_use]
+    pub fn hyperlinks(self, hyperlinks: bool) -> Self {
+        Self {
+            hyperlinks: Some(hyperlinks),
+            ..self
+        }
+    }
+
+    /// Sets whether the root's path in the [`Tree`] is written as a breadcrumb, like
+    /// `a › b › c`, instead of one long path. When links can be written, each directory
+    /// in it links to itself. This overrides the configuration.
+    #[inline]
+    #[must_use]
+    pub fn breadcrumb(self, breadcrumb: bool) -> Self {
+        Self {
+            breadcrumb: Some(breadcrumb),
+            ..self
+        }
+    }
+
+    /// Sets whether symlinks to directories in the [`Tree`] are traversed, except for
+    /// links to their own ancestors. This overrides the configuration.
+    #[inline]
+    #[must_use]
+    pub fn follow_symlinks(self, follow_symlinks: bool) -> Self {
+        Self {
+            follow_symlinks: Some(follow_symlinks),
+            ..self
+        }
+    }
+
+    /// Sets how `.git` directories are written in the [`Tree`]. This overrides the
+    /// mode that may be set by the configuration.
+    #[inline]
+    #[must_use]
+    pub fn git_dir(self, git_dir: GitDir) -> Self {
+        Self {
+            git_dir: Some(git_dir),
+            ..self
+        }
+    }
+
+    /// Sets the set of default icons in the [`Tree`], like emoji for terminals
+    /// without a Nerd Font. This overrides the set that may be set by the
+    /// configuration. [`IconSet::Auto`] is detected from the environment when the
+    /// tree is built.
+    #[inline]
+    #[must_use]
+    pub fn icon_set(self, icon_set: IconSet) -> Self {
+        Self {
+            icon_set: Some(icon_set),
+            ..self
+        }
+    }
+
+    /// Sets how entries are sorted in the [`Tree`]. This overrides the sorting that
+    /// may be set by the configuration.
+    #[inline]
+    #[must_use]
+    pub fn sorting(self, sorting: Sorting) -> Self {
+        Self {
+            sorting: Some(sorting),
+            ..self
+        }
+    }
+
+    /// Sets the icons for the [`Tree`]. This overrides the icon configuration.
+    #[inline]
+    #[must_use]
+    pub fn icon_theme(self, icon_theme: IconTheme) -> Self {
+        Self {
+            icon_theme: Some(icon_theme),
+            ..self
+        }
+    }
+
+    /// Sets the characters for version control statuses in the [`Tree`].
+    #[inline]
+    #[must_use]
+    pub fn status_glyphs(self, status_glyphs: StatusGlyphs) -> Self {
+        Self {
+            status_glyphs: Some(status_glyphs),
+            ..self
+        }
+    }
+
     /// Unsets the maximum depth level for the [`Tree`], returning to the default
     /// behavior of searching infinitely deep.
     ///
@@ -74,6 +578,13 @@ where
         }
     }
 
+    /// Sets the language of the [`Tree`]'s summary, which is English by default.
+    #[inline]
+    #[must_use]
+    pub fn lang(self, lang: Lang) -> Self {
+        Self { lang, ..self }
+    }
+
     /// Sets the [`Charset`] for the [`Tree`].
     #[inline]
     #[must_use]
@@ -125,12 +636,32 @@ where
         }
     }
 
+    /// Sets the plugins for the [`Tree`].
+    #[inline]
+    #[must_use]
+    pub fn plugins(self, plugins: config::Plugins) -> Self {
+        Self {
+            plugins: Some(plugins),
+            ..self
+        }
+    }
+
+    /// Sets the WASM plugins for the [`Tree`].
+    #[inline]
+    #[must_use]
+    pub fn wasm_plugins(self, wasm_plugins: config::WasmPlugins) -> Self {
+        Self {
+            wasm_plugins: Some(wasm_plugins),
+            ..self
+        }
+    }
+
     /// Creates the [`Tree`].
     ///
     /// # Panics
     ///
     /// - Panics if `max_level` and `unset_level` were both called.
-    pub fn build(self) -> Tree<'git, 'charset, P> {
+    pub fn build(self) -> Tree<'vcs, 'charset, P> {
         assert!(
             !(self.unset_level && self.max_level.is_some()),
             "max_level cannot be set when unset_level is true"
@@ -141,15 +672,147 @@ where
             self.max_level
                 .or(self.config.as_ref().and_then(|config| config.level()))
         };
+        let max_lines = self
+            .max_lines
+            .or(self.config.as_ref().and_then(|config| config.max_lines()));
+        let file_limit = self
+            .file_limit
+            .or(self.config.as_ref().and_then(|config| config.filelimit()));
+        let size_mode = if self.disk_usage {
+            SizeMode::Recursive
+        } else {
+            self.size_mode
+                .or(self.config.as_ref().and_then(|config| config.size_mode()))
+                .unwrap_or_default()
+        };
+        // NOTE A value that was set, like from a flag, overrides the configuration.
+        let or_config = |value: Option<bool>, f: fn(&config::Main) -> bool| {
+            value.unwrap_or_else(|| self.config.as_ref().is_some_and(f))
+        };
+        let mtime = or_config(self.mtime, config::Main::mtime);
+        let recent = or_config(self.recent, config::Main::recent);
+        let permissions = or_config(self.permissions, config::Main::permissions);
+        let hyperlinks = or_config(self.hyperlinks, config::Main::hyperlinks);
+        let breadcrumb = or_config(self.breadcrumb, config::Main::breadcrumb);
+        let prune = or_config(self.prune, config::Main::prune);
+        let follow_symlinks = or_config(self.follow_symlinks, config::Main::follow_symlinks);
+        let file_limit_mode = self
+            .file_limit_mode
+            .or(self
+                .config
+                .as_ref()
+                .and_then(|config| config.filelimit_mode()))
+            .unwrap_or_default();
+        let git_dir = self
+            .git_dir
+            .or(self
+                .config
+                .as_ref()
+                .and_then(|config| config.show_git_dir()))
+            .unwrap_or_default();
+        let icon_set = self
+            .icon_set
+            .or(self.config.as_ref().and_then(|config| config.icon_set()))
+            .unwrap_or_default()
+            .resolve();
+        let rule_precedence = self
+            .config
+            .as_ref()
+            .map(|config| config.rule_precedence())
+            .unwrap_or_default();
+        let warn_size = self
+            .warn_size
+            .or(self.config.as_ref().and_then(|config| config.warn_size()));
+        let mtime_format = mtime.then(|| {
+            self.mtime_format
+                .or_else(|| {
+                    self.config
+                        .as_ref()
+                        .and_then(|config| config.mtime_format().cloned())
+                })
+                .unwrap_or_default()
+        });
+        let recent_since = recent.then(|| {
+            self.recent_window
+                .or_else(|| {
+                    self.config
+                        .as_ref()
+                        .and_then(|config| config.recent_window())
+                })
+                .unwrap_or_default()
+                .start(SystemTime::now())
+        });
+        let annotations = self.annotations.or_else(|| {
+            self.config
+                .as_ref()
+                .and_then(|config| config.annotations().cloned())
+        });
         Tree {
             root: self.root,
-            git: self.git,
+            format: self.format.unwrap_or_default(),
+            html_base: self.html_base,
+            markdown_code: self.markdown_code,
+            vcs: self.vcs,
+            nested: RefCell::new(Vec::new()),
+            followed: RefCell::new(HashMap::new()),
+            churn: self.churn,
+            last_commits: self.last_commits,
             max_level,
-            charset: self.charset.unwrap_or_default(),
+            max_lines,
+            file_limit,
+            file_limit_mode,
+            lines: Cell::new(0),
+            stats: Cell::new(Stats::default()),
+            lang: self.lang,
+            histogram: RefCell::new(Histogram::default()),
+            prefetcher: OnceCell::new(),
+            last_entries: RefCell::new(Vec::new()),
+            warn_size,
+            ignore: self.ignore,
+            respect_ignores: !self.no_ignore,
+            show_hidden: self.show_hidden,
+            pattern: self.pattern,
+            prune,
+            pruned: RefCell::new(HashMap::new()),
+            size_mode,
+            disk_usage: self.disk_usage,
+            totals: OnceCell::new(),
+            mtime_format,
+            recent_since,
+            annotations,
+            annotated: RefCell::new(HashMap::new()),
+            permissions,
+            hyperlinks,
+            breadcrumb,
+            two_pass: self.two_pass,
+            show_deleted: self.show_deleted,
+            deferred: RefCell::new(Vec::new()),
+            breadth_first: self.breadth_first,
+            sort_key: self.sort_key,
+            reverse: self.reverse,
+            follow_symlinks,
+            git_dir,
+            sorting: self.sorting,
+            icon_theme: self.icon_theme,
+            status_glyphs: self.status_glyphs.unwrap_or_default(),
+            charset: self
+                .charset
+                .or_else(|| {
+                    self.config
+                        .as_ref()
+                        .and_then(|config| config.charset().cloned())
+                })
+                .unwrap_or_default(),
             color_choice: self.color_choice,
             config: self.config.unwrap_or_default(),
-            icons: self.icons.unwrap_or_default(),
-            colors: self.colors.unwrap_or_default(),
+            icons: self
+                .icons
+                .unwrap_or_default()
+                .with_set(icon_set)
+                .with_precedence(rule_precedence),
+            colors: self.colors.unwrap_or_default().precedence(rule_precedence),
+            plugins: self.plugins.unwrap_or_default(),
+            wasm_plugins: self.wasm_plugins.unwrap_or_default(),
         }
     }
 }
@@ -157,10 +820,32 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use mlua::Lua;
+    use rstest::rstest;
 
     #[test]
     #[should_panic]
     fn test_cannot_build_unset_level_with_max_level() {
         Builder::new(".").max_level(1).unset_level().build();
     }
+
+    #[rstest]
+    #[case(None, true)]
+    #[case(Some(true), true)]
+    #[case(Some(false), false)]
+    fn test_flags_override_config(#[case] flag: Option<bool>, #[case] expected: bool) {
+        let lua = Lua::new();
+        let config = lua
+            .load("return { prune = true, permissions = true }")
+            .eval::<config::Main>()
+            .unwrap();
+        let mut builder = Builder::new(".").config(config);
+        if let Some(flag) = flag {
+            builder = builder.prune(flag).permissions(flag);
+        }
+
+        let tree = builder.build();
+        assert_eq!(expected, tree.prune);
+        assert_eq!(expected, tree.permissions);
+    }
 }
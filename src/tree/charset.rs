@@ -1,22 +1,30 @@
 //! Module for collections of `char`s.
+use clap::ValueEnum;
+use mlua::{FromLua, Lua};
+use std::borrow::Cow;
+use std::fmt;
+use unicode_width::UnicodeWidthStr;
 
 /// Provides text used for generating a tree. Could be considered the "branches" of the
 /// tree.
 ///
-/// When implementing this, ideally `depth`, `breadth`, and `indent` should all be the
-/// same visual length.
+/// `depth`, `last_depth`, `breadth`, and `indent` should all be the same visual
+/// length, which [`Charset::new`] checks.
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
 pub struct Charset<'a> {
     /// The text to print when traveling deeper into the directory structure.
     ///
     /// Typically should resemble a horizontal line.
-    pub depth: &'a str,
+    pub depth: Cow<'a, str>,
+    /// The text to print instead of `depth` for the last entry in a directory.
+    pub last_depth: Cow<'a, str>,
     /// The text to print when traversing the breadth of a directory.
     ///
     /// Typically a vertical line. Also helps control padding between branches.
-    pub breadth: &'a str,
+    pub breadth: Cow<'a, str>,
     /// The text to use to indent tree branches with each level.
-    pub indent: &'a str,
+    pub indent: Cow<'a, str>,
 }
 
 const EMPTY_TEXT: &str = "    ";
@@ -24,18 +32,91 @@ const EMPTY_TEXT: &str = "    ";
 impl<'a> Charset<'a> {
     /// The standard charset. Pretty characters, but not too fancy.
     pub const STANDARD: Self = Self {
-        depth: "├── ",
+        depth: Cow::Borrowed("├── "),
+        last_depth: Cow::Borrowed("└── "),
         // NOTE U+00A0 is a non-breaking space
-        breadth: "│\u{00A0}\u{00A0} ",
-        indent: "    ",
+        breadth: Cow::Borrowed("│\u{00A0}\u{00A0} "),
+        indent: Cow::Borrowed("    "),
+    };
+
+    /// Plain ASCII, for terminals and fonts without line-drawing characters.
+    pub const ASCII: Self = Self {
+        depth: Cow::Borrowed("|-- "),
+        last_depth: Cow::Borrowed("`-- "),
+        breadth: Cow::Borrowed("|   "),
+        indent: Cow::Borrowed("    "),
+    };
+
+    /// Like the standard charset, but the last entry has a rounded corner.
+    pub const ROUNDED: Self = Self {
+        depth: Cow::Borrowed("├── "),
+        last_depth: Cow::Borrowed("╰── "),
+        breadth: Cow::Borrowed("│\u{00A0}\u{00A0} "),
+        indent: Cow::Borrowed("    "),
+    };
+
+    /// Double lines.
+    pub const DOUBLE: Self = Self {
+        depth: Cow::Borrowed("╠══ "),
+        last_depth: Cow::Borrowed("╚══ "),
+        breadth: Cow::Borrowed("║\u{00A0}\u{00A0} "),
+        indent: Cow::Borrowed("    "),
     };
 
     /// Empty charset. The tree is invisible.
     pub const EMPTY: Self = Self {
-        depth: EMPTY_TEXT,
-        breadth: EMPTY_TEXT,
-        indent: EMPTY_TEXT,
+        depth: Cow::Borrowed(EMPTY_TEXT),
+        last_depth: Cow::Borrowed(EMPTY_TEXT),
+        breadth: Cow::Borrowed(EMPTY_TEXT),
+        indent: Cow::Borrowed(EMPTY_TEXT),
     };
+
+    /// Creates a charset, checking that it won't misalign the tree.
+    pub fn new<S>(depth: S, last_depth: S, breadth: S, indent: S) -> Result<Self, CharsetError>
+    where
+        S: Into<Cow<'a, str>>,
+    {
+        let charset = Self {
+            depth: depth.into(),
+            last_depth: last_depth.into(),
+            breadth: breadth.into(),
+            indent: indent.into(),
+        };
+        charset.validate()?;
+        Ok(charset)
+    }
+
+    /// Checks that the strings have no control characters, and that they're all the
+    /// same width in a terminal.
+    pub fn validate(&self) -> Result<(), CharsetError> {
+        let fields = [
+            ("depth", &self.depth),
+            ("last_depth", &self.last_depth),
+            ("breadth", &self.breadth),
+            ("indent", &self.indent),
+        ];
+        if let Some((field, _)) = fields.iter().find(|(_, s)| s.chars().any(char::is_control)) {
+            return Err(CharsetError::ControlCharacter(field));
+        }
+        let expected = self.depth.width();
+        fields
+            .into_iter()
+            .map(|(field, s)| (field, s.width()))
+            .find(|&(_, width)| width != expected)
+            .map_or(Ok(()), |(field, width)| {
+                Err(CharsetError::Width {
+                    field,
+                    width,
+                    expected,
+                })
+            })
+    }
+
+    /// Gets the display width of each string.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.depth.width()
+    }
 }
 
 impl<'a> Default for Charset<'a> {
@@ -44,3 +125,185 @@ impl<'a> Default for Charset<'a> {
         Charset::STANDARD
     }
 }
+
+impl FromLua for Charset<'static> {
+    /// Converts from the name of a [`CharsetStyle`], or from a table with `branch`,
+    /// `last_branch`, `vertical`, and `indent` strings. Missing strings are taken from
+    /// the standard charset.
+    fn from_lua(value: mlua::Value, lua: &Lua) -> mlua::Result<Self> {
+        let Some(table) = value.as_table() else {
+            return CharsetStyle::from_lua(value, lua).map(CharsetStyle::charset);
+        };
+        let get = |key: &str, default: Cow<'static, str>| {
+            table
+                .get::<Option<String>>(key)
+                .map(|s| s.map_or(default, Cow::Owned))
+        };
+        let standard = Self::STANDARD;
+        let charset = Self {
+            depth: get("branch", standard.depth)?,
+            last_depth: get("last_branch", standard.last_depth)?,
+            breadth: get("vertical", standard.breadth)?,
+            indent: get("indent", standard.indent)?,
+        };
+        charset.validate().map_err(|err| {
+            // NOTE The error should use the keys from the configuration.
+            let message = err
+                .to_string()
+                .replace("last_depth", "last_branch")
+                .replace("depth", "branch")
+                .replace("breadth", "vertical");
+            mlua::Error::FromLuaConversionError {
+                from: "table",
+                to: String::from("Charset"),
+                message: Some(message),
+            }
+        })?;
+        Ok(charset)
+    }
+}
+
+/// The built-in charsets.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CharsetStyle {
+    /// Line-drawing characters.
+    #[default]
+    Unicode,
+    /// Only ASCII characters.
+    Ascii,
+    /// Line-drawing characters with a rounded corner.
+    Rounded,
+    /// Double line-drawing characters.
+    Double,
+    /// Spaces, so that only the indentation is visible.
+    None,
+}
+
+impl CharsetStyle {
+    /// Gets the charset for the style.
+    pub const fn charset(self) -> Charset<'static> {
+        match self {
+            Self::Unicode => Charset::STANDARD,
+            Self::Ascii => Charset::ASCII,
+            Self::Rounded => Charset::ROUNDED,
+            Self::Double => Charset::DOUBLE,
+            Self::None => Charset::EMPTY,
+        }
+    }
+}
+
+impl FromLua for CharsetStyle {
+    fn from_lua(value: mlua::Value, _lua: &Lua) -> mlua::Result<Self> {
+        const VALID_VALUES: [&str; 5] = ["unicode", "ascii", "rounded", "double", "none"];
+        let type_name = value.type_name();
+        let make_conversion_error = || mlua::Error::FromLuaConversionError {
+            from: type_name,
+            to: String::from("Charset"),
+            message: Some(format!("Must be one of {VALID_VALUES:?}, a table, or nil")),
+        };
+        let style = value
+            .as_string()
+            .ok_or_else(make_conversion_error)?
+            .to_string_lossy();
+        let style = match style.as_str() {
+            "unicode" => Self::Unicode,
+            "ascii" => Self::Ascii,
+            "rounded" => Self::Rounded,
+            "double" => Self::Double,
+            "none" => Self::None,
+            _ => return Err(make_conversion_error()),
+        };
+        Ok(style)
+    }
+}
+
+/// An error from a charset that would misalign the tree.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CharsetError {
+    /// A string contains a control character, like a newline or tab.
+    ControlCharacter(&'static str),
+    /// A string isn't the same width as `depth`.
+    Width {
+        /// The name of the string.
+        field: &'static str,
+        /// The width of the string.
+        width: usize,
+        /// The width of `depth`.
+        expected: usize,
+    },
+}
+
+impl fmt::Display for CharsetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ControlCharacter(field) => {
+                write!(f, "charset {field} must not contain control characters")
+            }
+            Self::Width {
+                field,
+                width,
+                expected,
+            } => write!(
+                f,
+                "charset {field} has a width of {width}, but depth has a width of {expected}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CharsetError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(CharsetStyle::Unicode)]
+    #[case(CharsetStyle::Ascii)]
+    #[case(CharsetStyle::Rounded)]
+    #[case(CharsetStyle::Double)]
+    #[case(CharsetStyle::None)]
+    fn test_builtin_valid(#[case] style: CharsetStyle) {
+        assert_eq!(Ok(()), style.charset().validate());
+    }
+
+    #[rstest]
+    #[case("|-- ", "|   ", "    ", Ok(4))]
+    #[case("|-- ", "|\t", "    ", Err(CharsetError::ControlCharacter("breadth")))]
+    #[case(
+        "|-- ",
+        "| ",
+        "    ",
+        Err(CharsetError::Width { field: "breadth", width: 2, expected: 4 })
+    )]
+    #[case(
+        "├─ ",
+        "│  ",
+        "全  ",
+        Err(CharsetError::Width { field: "indent", width: 4, expected: 3 })
+    )]
+    fn test_new(
+        #[case] depth: &str,
+        #[case] breadth: &str,
+        #[case] indent: &str,
+        #[case] expected: Result<usize, CharsetError>,
+    ) {
+        let actual = Charset::new(depth, depth, breadth, indent).map(|charset| charset.width());
+        assert_eq!(expected, actual);
+    }
+
+    #[rstest]
+    #[case(r#"return "ascii""#, Ok(Charset::ASCII))]
+    #[case(
+        r#"return { branch = "+-- ", last_branch = "\\-- " }"#,
+        Charset::new("+-- ", "\\-- ", "│\u{00A0}\u{00A0} ", "    ").map_err(|_| ())
+    )]
+    #[case(r#"return { vertical = "|" }"#, Err(()))]
+    #[case(r#"return "fancy""#, Err(()))]
+    fn test_from_lua(#[case] chunk: &str, #[case] expected: Result<Charset<'static>, ()>) {
+        let lua = Lua::new();
+        let actual = lua.load(chunk).eval::<Charset>().map_err(|_| ());
+        assert_eq!(expected, actual);
+    }
+}
@@ -1,20 +1,41 @@
 //! Module for directory attributes.
 
-use super::interop::has_hidden_attribute;
+use super::interop::{format_permissions, has_hidden_attribute};
+use std::cell::OnceCell;
 use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 /// Attributes for a directory.
 pub struct DirectoryAttributes {
+    /// The path to the directory, used to lazily read its children.
+    path: PathBuf,
     /// Is the directory hidden?
     hidden: bool,
+    /// The lazily computed number of children. `None` if the directory can't be read.
+    child_count: OnceCell<Option<usize>>,
+    /// The lazily computed total size of the directory's contents in bytes.
+    total_size: OnceCell<u64>,
+    /// When the directory was last modified, if the platform supports it.
+    modified: Option<SystemTime>,
+    /// The formatted permissions of the directory.
+    permissions: String,
 }
 
 impl DirectoryAttributes {
     /// Creates new directory attributes.
     #[inline]
-    pub(super) fn new(metadata: Metadata) -> Self {
+    pub(super) fn new<P>(path: P, metadata: Metadata) -> Self
+    where
+        P: AsRef<Path>,
+    {
         Self {
+            path: path.as_ref().to_path_buf(),
             hidden: has_hidden_attribute(&metadata),
+            child_count: OnceCell::new(),
+            total_size: OnceCell::new(),
+            modified: metadata.modified().ok(),
+            permissions: format_permissions(&metadata),
         }
     }
 
@@ -23,4 +44,74 @@ impl DirectoryAttributes {
     pub const fn is_hidden(&self) -> bool {
         self.hidden
     }
+
+    /// Gets when the directory was last modified.
+    #[inline]
+    pub const fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    /// Gets the formatted permissions of the directory.
+    #[inline]
+    pub fn permissions(&self) -> &str {
+        &self.permissions
+    }
+
+    /// Gets the number of direct children in the directory.
+    ///
+    /// This is computed the first time it is called. Returns `None` if the directory
+    /// can't be read.
+    pub fn child_count(&self) -> Option<usize> {
+        *self.child_count.get_or_init(|| {
+            self.path
+                .read_dir()
+                .ok()
+                .map(|entries| entries.filter_map(Result::ok).count())
+        })
+    }
+
+    /// Gets the number of direct children if it was already computed.
+    #[inline]
+    pub fn cached_child_count(&self) -> Option<Option<usize>> {
+        self.child_count.get().copied()
+    }
+
+    /// Sets the number of direct children, like when the directory's entries were read
+    /// to be written. This does nothing if the number was already computed.
+    #[inline]
+    pub(crate) fn set_child_count(&self, count: usize) {
+        let _ = self.child_count.set(Some(count));
+    }
+
+    /// Gets the total size in bytes of the files inside the directory, searching
+    /// subdirectories.
+    ///
+    /// This is computed the first time it is called. Symlinks aren't followed, and
+    /// entries that can't be read are skipped.
+    pub fn total_size(&self) -> u64 {
+        *self
+            .total_size
+            .get_or_init(|| crate::tree::size::total_size(&self.path))
+    }
+
+    /// Gets the total size if it was already computed.
+    #[inline]
+    pub fn cached_total_size(&self) -> Option<u64> {
+        self.total_size.get().copied()
+    }
+
+    /// Sets the total size, like when the sizes of a whole tree were computed at
+    /// once. This does nothing if the size was already computed.
+    #[inline]
+    pub(crate) fn set_total_size(&self, size: u64) {
+        let _ = self.total_size.set(size);
+    }
+
+    /// Is the directory empty?
+    ///
+    /// Directories that can't be read are not considered empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.child_count() == Some(0)
+    }
 }
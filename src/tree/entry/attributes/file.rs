@@ -1,9 +1,10 @@
 //! Module for file attributes.
-use super::interop::{has_hidden_attribute, is_executable};
+use super::interop::{format_permissions, has_hidden_attribute, is_executable};
 use gengo_language::Language;
 use std::fs::{File, Metadata};
 use std::io::{self, Read};
 use std::path::Path;
+use std::time::SystemTime;
 
 /// The maximum number of bytes to read from a file to determine its language.
 const READ_LIMIT: u16 = 1024 * 16; // 16 KiB
@@ -18,6 +19,12 @@ pub struct FileAttributes {
     language: Option<Language>,
     /// Is the file an executable?
     executable: bool,
+    /// The size of the file in bytes.
+    size: u64,
+    /// When the file was last modified, if the platform supports it.
+    modified: Option<SystemTime>,
+    /// The formatted permissions of the file.
+    permissions: String,
 }
 
 impl FileAttributes {
@@ -36,6 +43,9 @@ impl FileAttributes {
             hidden: has_hidden_attribute(&metadata),
             language,
             executable: is_executable(path, &metadata),
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+            permissions: format_permissions(&metadata),
         };
         Ok(attributes)
     }
@@ -52,6 +62,24 @@ impl FileAttributes {
         self.executable
     }
 
+    /// Get the size of the file in bytes.
+    #[inline]
+    pub const fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Get when the file was last modified.
+    #[inline]
+    pub const fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    /// Get the formatted permissions of the file.
+    #[inline]
+    pub fn permissions(&self) -> &str {
+        &self.permissions
+    }
+
     /// Get the file's language.
     #[inline]
     pub const fn language(&self) -> Option<Language> {
@@ -91,3 +91,113 @@ where
 
     PATH_EXTS.contains(&extension)
 }
+
+/// The width of the formatted permissions.
+#[cfg(not(windows))]
+pub const PERMISSIONS_WIDTH: usize = 10;
+
+/// Formats the file type and mode bits like `ls -l`, such as `drwxr-xr-x`.
+#[cfg(not(windows))]
+pub fn format_permissions(metadata: &Metadata) -> String {
+    use std::os::unix::fs::MetadataExt;
+
+    const SETUID: u32 = 0o4000;
+    const SETGID: u32 = 0o2000;
+    const STICKY: u32 = 0o1000;
+
+    let mode = metadata.mode();
+    let file_type = metadata.file_type();
+    let type_char = if file_type.is_symlink() {
+        'l'
+    } else if file_type.is_dir() {
+        'd'
+    } else {
+        '-'
+    };
+
+    /// Gets the character for an execute bit, which can also show a special bit.
+    fn execute_char(is_executable: bool, is_special: bool, special: char) -> char {
+        match (is_executable, is_special) {
+            (true, true) => special,
+            (false, true) => special.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        }
+    }
+
+    let bit = |mask: u32, c: char| if mode & mask != 0 { c } else { '-' };
+    [
+        type_char,
+        bit(0o400, 'r'),
+        bit(0o200, 'w'),
+        execute_char(mode & 0o100 != 0, mode & SETUID != 0, 's'),
+        bit(0o040, 'r'),
+        bit(0o020, 'w'),
+        execute_char(mode & 0o010 != 0, mode & SETGID != 0, 's'),
+        bit(0o004, 'r'),
+        bit(0o002, 'w'),
+        execute_char(mode & 0o001 != 0, mode & STICKY != 0, 't'),
+    ]
+    .into_iter()
+    .collect()
+}
+
+/// The width of the formatted permissions.
+#[cfg(windows)]
+pub const PERMISSIONS_WIDTH: usize = 6;
+
+/// Formats the file attributes like PowerShell's mode column, such as `d-r-h-`.
+///
+/// The flags are directory, archive, read-only, hidden, system, and reparse point.
+#[cfg(windows)]
+pub fn format_permissions(metadata: &Metadata) -> String {
+    use std::os::windows::fs::MetadataExt;
+
+    // NOTE See https://learn.microsoft.com/en-us/windows/win32/fileio/file-attribute-constants
+    const FLAGS: [(u32, char); 6] = [
+        (0x00000010, 'd'),
+        (0x00000020, 'a'),
+        (0x00000001, 'r'),
+        (0x00000002, 'h'),
+        (0x00000004, 's'),
+        (0x00000400, 'l'),
+    ];
+
+    let file_attributes = metadata.file_attributes();
+    FLAGS
+        .into_iter()
+        .map(|(flag, c)| if file_attributes & flag != 0 { c } else { '-' })
+        .collect()
+}
+
+#[cfg(all(test, not(windows)))]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::fs::{self, Permissions};
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    #[rstest]
+    #[case(0o644, "-rw-r--r--")]
+    #[case(0o755, "-rwxr-xr-x")]
+    #[case(0o4755, "-rwsr-xr-x")]
+    #[case(0o2644, "-rw-r-Sr--")]
+    #[case(0o1777, "-rwxrwxrwt")]
+    fn test_format_permissions(#[case] mode: u32, #[case] expected: &str) {
+        let dir = TempDir::with_prefix("fancy-tree-").unwrap();
+        let path = dir.path().join("file");
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, Permissions::from_mode(mode)).unwrap();
+        let metadata = fs::symlink_metadata(&path).unwrap();
+        assert_eq!(expected, format_permissions(&metadata));
+    }
+
+    #[test]
+    fn test_format_permissions_directory() {
+        let dir = TempDir::with_prefix("fancy-tree-").unwrap();
+        fs::set_permissions(dir.path(), Permissions::from_mode(0o750)).unwrap();
+        let metadata = fs::symlink_metadata(dir.path()).unwrap();
+        assert_eq!("drwxr-x---", format_permissions(&metadata));
+    }
+}
@@ -4,6 +4,7 @@ pub use file::FileAttributes;
 use std::fs::{self, File, Metadata};
 use std::io;
 use std::path::Path;
+use std::time::SystemTime;
 pub use symlink::SymlinkAttributes;
 
 mod directory;
@@ -22,19 +23,24 @@ pub enum Attributes {
 }
 
 impl Attributes {
+    /// The width of the formatted [permissions](Attributes::permissions).
+    pub const PERMISSIONS_WIDTH: usize = interop::PERMISSIONS_WIDTH;
+
     /// Creates new [`Attributes`].
     pub fn new<P>(path: P) -> io::Result<Self>
     where
         P: AsRef<Path>,
     {
         let path = path.as_ref();
-        let metadata = fs::metadata(path)?;
+        // NOTE symlink_metadata does not follow links, so that symlinks can be
+        //      classified as symlinks instead of as their targets.
+        let metadata = fs::symlink_metadata(path)?;
         let file_type = metadata.file_type();
 
         if file_type.is_symlink() {
-            Ok(Self::new_symlink())
+            Ok(Self::new_symlink(path, metadata))
         } else if file_type.is_dir() {
-            Ok(Self::new_directory(metadata))
+            Ok(Self::new_directory(path, metadata))
         } else if file_type.is_file() {
             let file = File::open(path)?;
             Self::new_file(path, file, metadata)
@@ -55,14 +61,20 @@ impl Attributes {
 
     /// Creates directory attributes.
     #[inline]
-    fn new_directory(metadata: Metadata) -> Self {
-        Self::Directory(DirectoryAttributes::new(metadata))
+    fn new_directory<P>(path: P, metadata: Metadata) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Self::Directory(DirectoryAttributes::new(path, metadata))
     }
 
     /// Creates symlink attributes.
     #[inline]
-    fn new_symlink() -> Self {
-        Self::Symlink(SymlinkAttributes)
+    fn new_symlink<P>(path: P, metadata: Metadata) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Self::Symlink(SymlinkAttributes::new(path, metadata))
     }
 
     /// Gets a reference to the file attributes.
@@ -95,6 +107,26 @@ impl Attributes {
         }
     }
 
+    /// Gets when the entry was last modified. For symlinks, this is the symlink itself
+    /// instead of its target.
+    pub fn modified(&self) -> Option<SystemTime> {
+        match self {
+            Self::Directory(attributes) => attributes.modified(),
+            Self::File(attributes) => attributes.modified(),
+            Self::Symlink(attributes) => attributes.modified(),
+        }
+    }
+
+    /// Gets the formatted permissions, like `drwxr-xr-x` on Unix. For symlinks, these
+    /// are the permissions of the symlink itself.
+    pub fn permissions(&self) -> &str {
+        match self {
+            Self::Directory(attributes) => attributes.permissions(),
+            Self::File(attributes) => attributes.permissions(),
+            Self::Symlink(attributes) => attributes.permissions(),
+        }
+    }
+
     /// Checks if the file is an executable.
     pub fn is_executable(&self) -> bool {
         self.is_file_and(|attributes| attributes.is_executable())
@@ -148,3 +180,51 @@ impl Attributes {
         matches!(self, Self::Symlink(_))
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_symlink_to_directory_is_symlink() {
+        let container = TempDir::with_prefix("fancy-tree-").unwrap();
+        let target = container.path().join("target");
+        let link = container.path().join("link");
+        fs::create_dir(&target).unwrap();
+        symlink(&target, &link).unwrap();
+
+        let attributes = Attributes::new(&link).expect("Attributes should be created");
+        let symlink = attributes.symlink().expect("Should be a symlink");
+        assert!(symlink.is_directory());
+        assert!(!symlink.is_broken());
+        assert_eq!(Some(target.as_path()), symlink.target());
+    }
+
+    #[test]
+    fn test_dangling_symlink_is_broken() {
+        let container = TempDir::with_prefix("fancy-tree-").unwrap();
+        let link = container.path().join("link");
+        symlink(container.path().join("missing"), &link).unwrap();
+
+        let attributes = Attributes::new(&link).expect("Attributes should be created");
+        let symlink = attributes.symlink().expect("Should be a symlink");
+        assert!(symlink.is_broken());
+    }
+
+    #[test]
+    fn test_directory_child_count() {
+        let container = TempDir::with_prefix("fancy-tree-").unwrap();
+        let attributes = Attributes::new(container.path()).expect("Attributes should be created");
+        let directory = attributes.directory().expect("Should be a directory");
+        assert!(directory.is_empty());
+
+        fs::write(container.path().join("foo.txt"), "").unwrap();
+        fs::create_dir(container.path().join("bar")).unwrap();
+        let attributes = Attributes::new(container.path()).expect("Attributes should be created");
+        let directory = attributes.directory().expect("Should be a directory");
+        assert_eq!(Some(2), directory.child_count());
+        assert!(!directory.is_empty());
+    }
+}
@@ -1,4 +1,77 @@
 //! Module for symlink attributes.
+use super::interop::format_permissions;
+use std::fs::{self, Metadata};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 /// Attributes for a symlink.
-pub struct SymlinkAttributes;
+pub struct SymlinkAttributes {
+    /// The path that the symlink points to, if it could be read.
+    target: Option<PathBuf>,
+    /// The metadata of the resolved target. `None` if the link is broken.
+    target_metadata: Option<Metadata>,
+    /// When the symlink itself was last modified, if the platform supports it.
+    modified: Option<SystemTime>,
+    /// The formatted permissions of the symlink itself.
+    permissions: String,
+}
+
+impl SymlinkAttributes {
+    /// Creates new symlink attributes.
+    pub(super) fn new<P>(path: P, metadata: Metadata) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        // NOTE A failure to read the link or its target shouldn't prevent the symlink
+        //      itself from being displayed.
+        let target = fs::read_link(path).ok();
+        let target_metadata = fs::metadata(path).ok();
+        Self {
+            target,
+            target_metadata,
+            modified: metadata.modified().ok(),
+            permissions: format_permissions(&metadata),
+        }
+    }
+
+    /// Gets the path that the symlink points to.
+    #[inline]
+    pub fn target(&self) -> Option<&Path> {
+        self.target.as_deref()
+    }
+
+    /// Gets the metadata of the resolved target.
+    ///
+    /// Returns `None` if the target does not exist.
+    #[inline]
+    pub fn target_metadata(&self) -> Option<&Metadata> {
+        self.target_metadata.as_ref()
+    }
+
+    /// Gets when the symlink itself was last modified.
+    #[inline]
+    pub const fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    /// Gets the formatted permissions of the symlink itself.
+    #[inline]
+    pub fn permissions(&self) -> &str {
+        &self.permissions
+    }
+
+    /// Is the symlink broken (does the target not exist)?
+    #[inline]
+    pub fn is_broken(&self) -> bool {
+        self.target_metadata.is_none()
+    }
+
+    /// Does the symlink point to a directory?
+    #[inline]
+    pub fn is_directory(&self) -> bool {
+        self.target_metadata
+            .as_ref()
+            .is_some_and(|metadata| metadata.is_dir())
+    }
+}
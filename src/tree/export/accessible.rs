@@ -0,0 +1,96 @@
+//! Module for writing an exported tree for screen readers.
+use super::{EntryType, Node};
+use crate::tree::render::{RenderNode, Renderer};
+use crate::vcs::status::FileStatus;
+use std::io::{self, Write};
+
+/// Writes an exported tree as plain sentences, like `folder src, 3 items`, for
+/// screen readers and other tools that read text aloud.
+///
+/// Icons, colors, and box-drawing characters are left out, since they're read as
+/// noise or not at all. Each level is indented with plain spaces instead.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AccessibleRenderer;
+
+impl AccessibleRenderer {
+    /// The indentation for each level of the tree.
+    const INDENT: &str = "  ";
+
+    /// Creates a new renderer.
+    #[inline]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Renderer for AccessibleRenderer {
+    fn entry(&mut self, writer: &mut dyn Write, node: &RenderNode<'_>) -> io::Result<()> {
+        let indent = Self::INDENT.repeat(node.depth());
+        writeln!(writer, "{indent}{}", describe(node.node))
+    }
+}
+
+/// Describes an entry, like `folder src, 3 items` or `file main.rs, modified`.
+fn describe(node: &Node) -> String {
+    let kind = match node.entry_type {
+        EntryType::Directory => "folder",
+        EntryType::File => "file",
+        EntryType::Symlink => "link",
+    };
+    let mut description = format!("{kind} {}", node.name);
+    if node.deleted {
+        description.push_str(", deleted");
+    }
+    if let Some(children) = node.children.as_deref() {
+        // NOTE The entries over the file limit are still in the folder.
+        let items = match children.len() + node.omitted.unwrap_or(0) {
+            0 => String::from("empty"),
+            1 => String::from("1 item"),
+            n => format!("{n} items"),
+        };
+        description.push_str(", ");
+        description.push_str(&items);
+    }
+    if let Some(FileStatus { tracked, untracked }) = node.status {
+        if let Some(status) = tracked {
+            description.push_str(&format!(", {} in index", status.name()));
+        }
+        if let Some(status) = untracked {
+            description.push_str(&format!(", {}", status.name()));
+        }
+    }
+    description
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vcs::status::Status;
+
+    #[test]
+    fn test_render() {
+        let lib = Node {
+            icon: Some(String::from("R")),
+            status: Some(FileStatus {
+                tracked: Some(Status::Added),
+                untracked: Some(Status::Modified),
+            }),
+            ..Node::new("lib.rs", "src/lib.rs", EntryType::File, None)
+        };
+        let src = Node {
+            omitted: Some(1),
+            ..Node::new("src", "src", EntryType::Directory, Some(vec![lib]))
+        };
+        let empty = Node::new("empty", "empty", EntryType::Directory, Some(Vec::new()));
+        let link = Node {
+            deleted: true,
+            ..Node::new("link", "link", EntryType::Symlink, None)
+        };
+        let root = Node::new(".", "", EntryType::Directory, Some(vec![src, empty, link]));
+
+        let mut buf = Vec::new();
+        AccessibleRenderer::new().render(&mut buf, &root).unwrap();
+        let expected = "folder ., 3 items\n  folder src, 2 items\n    file lib.rs, added in index, modified\n  folder empty, empty\n  link link, deleted\n";
+        assert_eq!(expected, String::from_utf8(buf).unwrap());
+    }
+}
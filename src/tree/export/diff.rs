@@ -0,0 +1,361 @@
+//! Module for comparing two exports.
+use super::{FlatNode, Node};
+use crate::color::ColorChoice;
+use crate::config;
+use crate::tree::Charset;
+use crate::vcs::status::Status;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+
+/// Either kind of export that can be read back in.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum Export {
+    /// An export made with the `json` format.
+    Nested(Node),
+    /// An export made with the `json-flat` format.
+    Flat(Vec<FlatNode>),
+}
+
+impl Export {
+    /// Converts the export into a flat list of entries.
+    pub fn into_flat(self) -> Vec<FlatNode> {
+        match self {
+            Self::Nested(node) => node.flatten(),
+            Self::Flat(nodes) => nodes,
+        }
+    }
+}
+
+/// An entry in the combined old and new exports.
+struct DiffNode {
+    /// The entry.
+    node: FlatNode,
+    /// How the entry changed. `None` if it's the same in both exports.
+    status: Option<Status>,
+}
+
+/// The differences between two exports.
+pub struct Diff {
+    /// All entries from both exports, keyed by ID.
+    nodes: HashMap<String, DiffNode>,
+    /// The IDs of each entry's children, sorted by name.
+    children: HashMap<String, Vec<String>>,
+    /// The IDs of entries that changed, or that contain entries that changed.
+    changed: HashSet<String>,
+    /// The IDs of the roots. There is normally only one.
+    roots: Vec<String>,
+}
+
+impl Diff {
+    /// Compares the entries of an old and a new export.
+    pub fn new(old: Vec<FlatNode>, new: Vec<FlatNode>) -> Self {
+        let mut old = old
+            .into_iter()
+            .map(|node| (node.id.clone(), node))
+            .collect::<HashMap<_, _>>();
+
+        let new = new.into_iter().map(|node| {
+            let status = match old.remove(&node.id) {
+                None => Some(Status::Added),
+                Some(old) if Self::is_modified(&old, &node) => Some(Status::Modified),
+                Some(_) => None,
+            };
+            DiffNode { node, status }
+        });
+        let new = new.collect::<Vec<_>>();
+        let removed = old.into_values().map(|node| DiffNode {
+            node,
+            status: Some(Status::Removed),
+        });
+        let nodes = new
+            .into_iter()
+            .chain(removed)
+            .map(|node| (node.node.id.clone(), node))
+            .collect::<HashMap<_, _>>();
+
+        let mut children: HashMap<String, Vec<String>> = HashMap::new();
+        let mut roots = Vec::new();
+        for (id, node) in nodes.iter() {
+            match node.node.parent {
+                Some(ref parent) if nodes.contains_key(parent) => {
+                    children.entry(parent.clone()).or_default().push(id.clone());
+                }
+                _ => roots.push(id.clone()),
+            }
+        }
+        let by_name = |id: &String| nodes[id].node.name.clone();
+        children
+            .values_mut()
+            .for_each(|children| children.sort_by_key(by_name));
+        roots.sort_by_key(by_name);
+
+        let mut changed = HashSet::new();
+        for (id, node) in nodes.iter().filter(|(_, node)| node.status.is_some()) {
+            changed.insert(id.clone());
+            let mut parent = node.node.parent.as_ref();
+            // NOTE Stop early if the ancestors have already been marked.
+            while let Some(id) = parent.filter(|&id| changed.insert(id.clone())) {
+                parent = nodes.get(id).and_then(|node| node.node.parent.as_ref());
+            }
+        }
+
+        Self {
+            nodes,
+            children,
+            changed,
+            roots,
+        }
+    }
+
+    /// Checks if an entry that exists in both exports was modified.
+    ///
+    /// Every exported field is compared, except the ID, which is how the entries were
+    /// matched.
+    fn is_modified(old: &FlatNode, new: &FlatNode) -> bool {
+        // NOTE Destructuring makes new fields fail to compile until they're compared.
+        let FlatNode {
+            id: _,
+            parent,
+            name,
+            path,
+            entry_type,
+            depth,
+            status,
+            icon,
+            icon_source,
+            color,
+            color_source,
+            deleted,
+            omitted,
+        } = new;
+        // NOTE The root's name isn't compared, because it's the path the user
+        //      specified, which can be different for the same directory.
+        let is_root = parent.is_none();
+        (!is_root && old.name != *name)
+            || old.parent != *parent
+            || old.path != *path
+            || old.entry_type != *entry_type
+            || old.depth != *depth
+            || old.status != *status
+            || old.icon != *icon
+            || old.icon_source != *icon_source
+            || old.color != *color
+            || old.color_source != *color_source
+            || old.deleted != *deleted
+            || old.omitted != *omitted
+    }
+
+    /// Checks if there are any differences.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty()
+    }
+
+    /// Writes the entries that changed, and the directories that contain them, as a
+    /// tree.
+    pub fn write<W>(
+        &self,
+        writer: &mut W,
+        charset: &Charset,
+        colors: &config::Colors,
+        color_choice: ColorChoice,
+    ) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let style = Style {
+            charset,
+            colors,
+            color_choice,
+        };
+        for root in self.roots.iter() {
+            self.write_depth(writer, &style, root, &mut Vec::new())?;
+        }
+        writer.flush()
+    }
+
+    /// Writes an entry and its changed descendants.
+    ///
+    /// `last_entries` has, for each level above the entry, whether the entry at that
+    /// level was the last in its directory.
+    fn write_depth<W>(
+        &self,
+        writer: &mut W,
+        style: &Style,
+        id: &str,
+        last_entries: &mut Vec<bool>,
+    ) -> io::Result<()>
+    where
+        W: Write,
+    {
+        const NO_STATUS: &str = " ";
+
+        let node = &self.nodes[id];
+        if let Some((&is_last, ancestors)) = last_entries.split_last() {
+            for &ancestor_is_last in ancestors {
+                let indentation = if ancestor_is_last {
+                    &style.charset.indent
+                } else {
+                    &style.charset.breadth
+                };
+                style.write_connector(writer, indentation)?;
+            }
+            let branch = if is_last {
+                &style.charset.last_depth
+            } else {
+                &style.charset.depth
+            };
+            style.write_connector(writer, branch)?;
+        }
+        let status = node.status;
+        let color = status.and_then(|status| style.colors.for_tracked_git_status(status));
+        let status = status.map(|status| status.as_str()).unwrap_or(NO_STATUS);
+        style.color_choice.write_to(writer, status, color)?;
+        writeln!(writer, " {}", node.node.name)?;
+
+        let children = self.children.get(id).into_iter().flatten();
+        let children = children
+            .filter(|child| self.changed.contains(*child))
+            .collect::<Vec<_>>();
+        let count = children.len();
+        for (index, child) in children.into_iter().enumerate() {
+            last_entries.push(index + 1 == count);
+            let result = self.write_depth(writer, style, child, last_entries);
+            last_entries.pop();
+            result?;
+        }
+        Ok(())
+    }
+}
+
+/// Options for writing a [`Diff`].
+struct Style<'a, 'charset> {
+    /// The characters for the tree's branches.
+    charset: &'a Charset<'charset>,
+    /// The colors for statuses.
+    colors: &'a config::Colors,
+    /// Controls if colors are written.
+    color_choice: ColorChoice,
+}
+
+impl Style<'_, '_> {
+    /// Writes part of the tree's branches.
+    fn write_connector<W>(&self, writer: &mut W, text: &str) -> io::Result<()>
+    where
+        W: Write,
+    {
+        self.color_choice
+            .write_to(writer, text, self.colors.for_connectors())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Source;
+    use crate::tree::export::EntryType;
+    use crate::vcs::status::FileStatus;
+    use rstest::rstest;
+
+    /// Creates flat nodes from `(path, type)` pairs under a root directory.
+    fn export(entries: &[(&str, EntryType)]) -> Vec<FlatNode> {
+        let mut nodes = vec![Node::new(".", "", EntryType::Directory, None)];
+        for (path, entry_type) in entries {
+            let name = path.rsplit('/').next().unwrap();
+            nodes.push(Node::new(name, path, *entry_type, None));
+        }
+        let ids = nodes
+            .iter()
+            .map(|node| (node.path.clone(), node.id.clone()))
+            .collect::<HashMap<_, _>>();
+        nodes
+            .into_iter()
+            .map(|node| {
+                let parent = (node.path != ".").then(|| {
+                    let parent = node.path.rsplit_once('/').map_or(".", |(parent, _)| parent);
+                    ids[parent].clone()
+                });
+                FlatNode {
+                    parent,
+                    ..node.flatten().remove(0)
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        use EntryType::*;
+
+        let old = export(&[
+            ("kept", Directory),
+            ("kept/same.txt", File),
+            ("kept/removed.txt", File),
+            ("changed", File),
+            ("unchanged", Directory),
+            ("unchanged/file.txt", File),
+        ]);
+        let new = export(&[
+            ("kept", Directory),
+            ("kept/same.txt", File),
+            ("kept/added.txt", File),
+            ("changed", Directory),
+            ("unchanged", Directory),
+            ("unchanged/file.txt", File),
+        ]);
+        let diff = Diff::new(old, new);
+        assert!(!diff.is_empty());
+
+        let mut output = Vec::new();
+        let colors = config::Colors::default();
+        diff.write(&mut output, &Charset::STANDARD, &colors, ColorChoice::Off)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        let expected = [
+            "  .",
+            "├── ~ changed",
+            "└──   kept",
+            "    ├── + added.txt",
+            "    └── - removed.txt",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(expected, output);
+    }
+
+    #[rstest]
+    #[case(|node: &mut FlatNode| node.icon = Some("I".into()))]
+    #[case(|node: &mut FlatNode| node.icon_source = Some(Source::Lua))]
+    #[case(|node: &mut FlatNode| node.color = Some("red".into()))]
+    #[case(|node: &mut FlatNode| node.color_source = Some(Source::Plugin))]
+    #[case(|node: &mut FlatNode| node.status = Some(FileStatus::default()))]
+    #[case(|node: &mut FlatNode| node.deleted = true)]
+    #[case(|node: &mut FlatNode| node.omitted = Some(1))]
+    fn test_diff_modified(#[case] modify: fn(&mut FlatNode)) {
+        let old = export(&[("file.txt", EntryType::File)]);
+        let mut new = old.clone();
+        modify(&mut new[1]);
+        let id = new[1].id.clone();
+
+        let diff = Diff::new(old, new);
+        assert_eq!(Some(Status::Modified), diff.nodes[&id].status);
+    }
+
+    #[test]
+    fn test_diff_root_name() {
+        let old = export(&[("file.txt", EntryType::File)]);
+        let mut new = old.clone();
+        new[0].name = "./".into();
+        assert!(Diff::new(old, new).is_empty());
+    }
+
+    #[test]
+    fn test_diff_empty() {
+        let old = export(&[("file.txt", EntryType::File)]);
+        let new = old.clone();
+        assert!(Diff::new(old, new).is_empty());
+    }
+}
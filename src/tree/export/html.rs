@@ -0,0 +1,236 @@
+//! Module for writing an exported tree as HTML.
+use super::{EntryType, Node};
+use crate::color::Color;
+use crate::tree::render::{RenderNode, Renderer};
+use std::io::{self, Write};
+
+/// Writes an exported tree as an HTML document of nested lists, like `tree -H`.
+///
+/// Icons are colored with inline styles, so the document doesn't need a stylesheet.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HtmlRenderer<'a> {
+    /// The optional URL that links to entries are relative to.
+    base: Option<&'a str>,
+}
+
+impl<'a> HtmlRenderer<'a> {
+    /// Creates a renderer that doesn't link to entries.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Links each entry to its path, relative to `base`.
+    #[inline]
+    #[must_use]
+    pub fn links(self, base: &'a str) -> Self {
+        Self { base: Some(base) }
+    }
+}
+
+impl Renderer for HtmlRenderer<'_> {
+    fn start(&mut self, writer: &mut dyn Write, root: &Node) -> io::Result<()> {
+        writeln!(writer, "<!DOCTYPE html>")?;
+        writeln!(writer, "<html>")?;
+        writeln!(writer, "<head>")?;
+        writeln!(writer, "<meta charset=\"utf-8\">")?;
+        writeln!(writer, "<title>{}</title>", escape(&root.name))?;
+        writeln!(writer, "</head>")?;
+        writeln!(writer, "<body>")?;
+        writeln!(writer, "<ul class=\"fancy-tree\">")
+    }
+
+    /// Writes the entry as a list item. The item is closed after its children.
+    fn entry(&mut self, writer: &mut dyn Write, node: &RenderNode<'_>) -> io::Result<()> {
+        let node = node.node;
+        write!(writer, "<li>")?;
+        if let Some(icon) = node.icon.as_deref().filter(|icon| !icon.is_empty()) {
+            match node.color.as_deref().and_then(|color| color.parse().ok()) {
+                Some(color) => write!(
+                    writer,
+                    "<span style=\"color: {}\">{}</span> ",
+                    css(color),
+                    escape(icon)
+                )?,
+                None => write!(writer, "<span>{}</span> ", escape(icon))?,
+            }
+        }
+        // NOTE Deleted entries aren't linked, since their files don't exist.
+        match self.base {
+            _ if node.deleted => write!(writer, "<del>{}</del>", escape(&node.name))?,
+            Some(base) => write!(
+                writer,
+                "<a href=\"{}\">{}</a>",
+                escape(&href(base, node)),
+                escape(&node.name)
+            )?,
+            None => write!(writer, "{}", escape(&node.name))?,
+        }
+        if let Some(omitted) = node.omitted {
+            write!(writer, " (+{omitted} more)")?;
+        }
+        let has_children = node
+            .children
+            .as_deref()
+            .is_some_and(|children| !children.is_empty());
+        if has_children {
+            writeln!(writer)
+        } else {
+            writeln!(writer, "</li>")
+        }
+    }
+
+    fn enter(&mut self, writer: &mut dyn Write, _node: &RenderNode<'_>) -> io::Result<()> {
+        writeln!(writer, "<ul>")
+    }
+
+    fn leave(&mut self, writer: &mut dyn Write, _node: &RenderNode<'_>) -> io::Result<()> {
+        writeln!(writer, "</ul>")?;
+        writeln!(writer, "</li>")
+    }
+
+    fn finish(&mut self, writer: &mut dyn Write, _root: &Node) -> io::Result<()> {
+        writeln!(writer, "</ul>")?;
+        writeln!(writer, "</body>")?;
+        writeln!(writer, "</html>")
+    }
+}
+
+/// Converts a color to a CSS hex code.
+fn css(color: Color) -> String {
+    let (r, g, b) = color.rgb();
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Creates the link to a node. Directories end with `/`, so that relative links
+/// inside them resolve correctly.
+fn href(base: &str, node: &Node) -> String {
+    let base = base.trim_end_matches('/');
+    let mut href = if node.path == "." {
+        String::from(base)
+    } else {
+        format!("{base}/{}", percent_encode(&node.path))
+    };
+    if node.entry_type == EntryType::Directory {
+        href.push('/');
+    }
+    href
+}
+
+/// Percent-encodes the bytes of a path that aren't allowed in a URL, keeping `/`
+/// separators.
+pub(in crate::tree) fn percent_encode<B>(path: B) -> String
+where
+    B: AsRef<[u8]>,
+{
+    path.as_ref()
+        .iter()
+        .map(|&byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                char::from(byte).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// Escapes the characters that have special meanings in HTML text and attributes.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    /// Creates a small tree to render.
+    fn tree() -> Node {
+        let file = Node {
+            icon: Some(String::from("F")),
+            color: Some(String::from("red")),
+            ..Node::new("a & b.rs", "src/a & b.rs", EntryType::File, None)
+        };
+        let src = Node {
+            icon: Some(String::from("D")),
+            color: Some(String::from("#ff0080")),
+            ..Node::new("src", "src", EntryType::Directory, Some(vec![file]))
+        };
+        Node::new(".", "", EntryType::Directory, Some(vec![src]))
+    }
+
+    #[test]
+    fn test_render() {
+        let mut buf = Vec::new();
+        HtmlRenderer::new().render(&mut buf, &tree()).unwrap();
+        let html = String::from_utf8(buf).unwrap();
+        let expected = "<ul class=\"fancy-tree\">\n\
+            <li>.\n\
+            <ul>\n\
+            <li><span style=\"color: #ff0080\">D</span> src\n\
+            <ul>\n\
+            <li><span style=\"color: #cd0000\">F</span> a &amp; b.rs</li>\n\
+            </ul>\n\
+            </li>\n\
+            </ul>\n\
+            </li>\n\
+            </ul>\n";
+        assert!(html.starts_with("<!DOCTYPE html>\n"));
+        assert!(html.contains(expected), "{html}");
+    }
+
+    #[test]
+    fn test_render_links() {
+        let mut buf = Vec::new();
+        HtmlRenderer::new()
+            .links("https://example.com/")
+            .render(&mut buf, &tree())
+            .unwrap();
+        let html = String::from_utf8(buf).unwrap();
+        assert!(html.contains("<a href=\"https://example.com/\">.</a>"));
+        assert!(html.contains("<a href=\"https://example.com/src/\">src</a>"));
+        assert!(
+            html.contains("<a href=\"https://example.com/src/a%20%26%20b.rs\">a &amp; b.rs</a>")
+        );
+    }
+
+    #[test]
+    fn test_render_deleted() {
+        let removed = Node {
+            deleted: true,
+            ..Node::new("old.rs", "src/old.rs", EntryType::File, None)
+        };
+        let src = Node {
+            omitted: Some(3),
+            ..Node::new("src", "src", EntryType::Directory, Some(vec![removed]))
+        };
+        let root = Node::new(".", "", EntryType::Directory, Some(vec![src]));
+
+        let mut buf = Vec::new();
+        HtmlRenderer::new()
+            .links("https://example.com/")
+            .render(&mut buf, &root)
+            .unwrap();
+        let html = String::from_utf8(buf).unwrap();
+        assert!(html.contains("src</a> (+3 more)\n"), "{html}");
+        assert!(html.contains("<li><del>old.rs</del></li>"), "{html}");
+    }
+
+    #[rstest]
+    #[case("plain", "plain")]
+    #[case("<b>\"&'", "&lt;b&gt;&quot;&amp;&#39;")]
+    fn test_escape(#[case] s: &str, #[case] expected: &str) {
+        assert_eq!(expected, escape(s));
+    }
+}
@@ -0,0 +1,39 @@
+//! Module for writing an exported tree as JSON.
+use super::Node;
+use crate::tree::render::Renderer;
+use std::io::{self, Write};
+
+/// Writes an exported tree as pretty-printed JSON.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JsonRenderer {
+    /// Write a flat array instead of nesting children?
+    flat: bool,
+}
+
+impl JsonRenderer {
+    /// Creates a renderer that nests each entry's children inside it.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes a flat array, where each entry refers to its parent's ID.
+    #[inline]
+    #[must_use]
+    pub fn flat(self) -> Self {
+        Self { flat: true }
+    }
+}
+
+impl Renderer for JsonRenderer {
+    /// Writes the whole tree at once, since JSON can't be written an entry at a time.
+    fn render(&mut self, writer: &mut dyn Write, root: &Node) -> io::Result<()> {
+        if self.flat {
+            serde_json::to_writer_pretty(&mut *writer, &root.flatten())?;
+        } else {
+            serde_json::to_writer_pretty(&mut *writer, root)?;
+        }
+        writeln!(writer)?;
+        writer.flush()
+    }
+}
@@ -0,0 +1,151 @@
+//! Module for writing an exported tree as Markdown.
+use super::EntryType;
+use crate::tree::render::{RenderNode, Renderer};
+use std::io::{self, Write};
+
+/// Writes an exported tree as a nested Markdown list, for pasting into READMEs and
+/// issues.
+///
+/// Icons and colors are left out, since they don't render the same everywhere.
+/// Directories end with `/` so that they can be told apart from files.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MarkdownRenderer {
+    /// Wrap names in backticks?
+    code: bool,
+}
+
+impl MarkdownRenderer {
+    /// The indentation for each level of the list.
+    const INDENT: &str = "  ";
+
+    /// Creates a renderer that writes names as plain text.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wraps each name in backticks, so that it's rendered as code.
+    #[inline]
+    #[must_use]
+    pub fn code(self) -> Self {
+        Self { code: true }
+    }
+}
+
+impl Renderer for MarkdownRenderer {
+    fn entry(&mut self, writer: &mut dyn Write, node: &RenderNode<'_>) -> io::Result<()> {
+        let level = node.depth();
+        let node = node.node;
+        let mut name = node.name.clone();
+        // NOTE The root is usually `.`, which looks odd with a trailing `/`.
+        if node.entry_type == EntryType::Directory && level > 0 && !name.ends_with('/') {
+            name.push('/');
+        }
+        let name = if self.code {
+            code_span(&name)
+        } else {
+            escape(&name)
+        };
+        let indent = Self::INDENT.repeat(level);
+        if node.deleted {
+            write!(writer, "{indent}- ~~{name}~~")?;
+        } else {
+            write!(writer, "{indent}- {name}")?;
+        }
+        if let Some(omitted) = node.omitted {
+            write!(writer, " (+{omitted} more)")?;
+        }
+        writeln!(writer)
+    }
+}
+
+/// Wraps text in enough backticks that backticks inside it don't end the code span.
+fn code_span(s: &str) -> String {
+    let longest_run = s
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest_run + 1);
+    // NOTE Spaces keep backticks at the edges from joining the fence, and are
+    //      stripped when rendered.
+    if s.starts_with('`') || s.ends_with('`') {
+        format!("{fence} {s} {fence}")
+    } else {
+        format!("{fence}{s}{fence}")
+    }
+}
+
+/// Escapes the characters that Markdown could treat as formatting.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '~'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::export::Node;
+    use rstest::rstest;
+
+    /// Creates a small tree to render.
+    fn tree() -> Node {
+        let file = Node {
+            icon: Some(String::from("F")),
+            color: Some(String::from("red")),
+            ..Node::new("__init__.py", "src/__init__.py", EntryType::File, None)
+        };
+        let src = Node {
+            omitted: Some(2),
+            ..Node::new("src", "src", EntryType::Directory, Some(vec![file]))
+        };
+        let readme = Node::new("README.md", "README.md", EntryType::File, None);
+        let license = Node {
+            deleted: true,
+            ..Node::new("LICENSE", "LICENSE", EntryType::File, None)
+        };
+        Node::new(
+            ".",
+            "",
+            EntryType::Directory,
+            Some(vec![src, readme, license]),
+        )
+    }
+
+    #[test]
+    fn test_render() {
+        let mut buf = Vec::new();
+        MarkdownRenderer::new().render(&mut buf, &tree()).unwrap();
+        let expected =
+            "- .\n  - src/ (+2 more)\n    - \\_\\_init\\_\\_.py\n  - README.md\n  - ~~LICENSE~~\n";
+        assert_eq!(expected, String::from_utf8(buf).unwrap());
+    }
+
+    #[test]
+    fn test_render_code() {
+        let mut buf = Vec::new();
+        MarkdownRenderer::new()
+            .code()
+            .render(&mut buf, &tree())
+            .unwrap();
+        let expected = "- `.`\n  - `src/` (+2 more)\n    - `__init__.py`\n  - `README.md`\n  - ~~`LICENSE`~~\n";
+        assert_eq!(expected, String::from_utf8(buf).unwrap());
+    }
+
+    #[rstest]
+    #[case("plain", "`plain`")]
+    #[case("a`b", "``a`b``")]
+    #[case("`a``", "``` `a`` ```")]
+    fn test_code_span(#[case] s: &str, #[case] expected: &str) {
+        assert_eq!(expected, code_span(s));
+    }
+}
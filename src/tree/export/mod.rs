@@ -0,0 +1,246 @@
+//! Module for structured exports of a tree.
+use super::entry::Attributes;
+use crate::rules::Source;
+use crate::vcs::status::FileStatus;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+pub mod accessible;
+pub mod diff;
+pub mod html;
+pub mod json;
+pub mod markdown;
+pub mod snapshot;
+
+/// The type of an exported entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryType {
+    /// A directory.
+    Directory,
+    /// A file.
+    File,
+    /// A symbolic link.
+    Symlink,
+}
+
+impl From<&Attributes> for EntryType {
+    fn from(value: &Attributes) -> Self {
+        match value {
+            Attributes::Directory(_) => Self::Directory,
+            Attributes::File(_) => Self::File,
+            Attributes::Symlink(_) => Self::Symlink,
+        }
+    }
+}
+
+/// An exported entry, with its children nested inside it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Node {
+    /// An identifier that stays the same as long as the relative path does.
+    pub id: String,
+    /// The name of the entry.
+    pub name: String,
+    /// The path relative to the tree's root, with `/` separators.
+    pub path: String,
+    /// The type of the entry.
+    #[serde(rename = "type")]
+    pub entry_type: EntryType,
+    /// How many levels below the tree's root the entry is. The root is `0`.
+    pub depth: usize,
+    /// The version control statuses. `None` if the tree isn't in a repository.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<FileStatus>,
+    /// The icon that's displayed for the entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// What decided the entry's icon, like a built-in rule for its extension.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_source: Option<Source>,
+    /// The color of the entry's icon, as a color name or a hex code.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// What decided the color of the entry's icon, like a Lua function.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_source: Option<Source>,
+    /// Was the entry deleted? Deleted entries are still tracked by version control.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deleted: bool,
+    /// How many of the entry's children were left out because of the file limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub omitted: Option<usize>,
+    /// The children of the entry. `None` if the entry's children weren't searched,
+    /// like when the entry isn't a directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<Node>>,
+}
+
+impl Node {
+    /// Creates a new node. `path` should be relative to the tree's root.
+    pub fn new<N, P>(name: N, path: P, entry_type: EntryType, children: Option<Vec<Node>>) -> Self
+    where
+        N: Into<String>,
+        P: AsRef<Path>,
+    {
+        let path = normalize_path(path.as_ref());
+        let depth = if path == "." {
+            0
+        } else {
+            path.matches('/').count() + 1
+        };
+        Self {
+            id: stable_id(&path),
+            name: name.into(),
+            path,
+            entry_type,
+            depth,
+            status: None,
+            icon: None,
+            icon_source: None,
+            color: None,
+            color_source: None,
+            deleted: false,
+            omitted: None,
+            children,
+        }
+    }
+
+    /// Flattens the node and its descendants into a list, where each entry refers to
+    /// its parent by ID.
+    pub fn flatten(&self) -> Vec<FlatNode> {
+        let mut nodes = Vec::new();
+        self.flatten_into(None, &mut nodes);
+        nodes
+    }
+
+    /// Adds the node and its descendants to the list, in depth-first order.
+    fn flatten_into(&self, parent: Option<&str>, nodes: &mut Vec<FlatNode>) {
+        nodes.push(FlatNode {
+            id: self.id.clone(),
+            parent: parent.map(String::from),
+            name: self.name.clone(),
+            path: self.path.clone(),
+            entry_type: self.entry_type,
+            depth: self.depth,
+            status: self.status,
+            icon: self.icon.clone(),
+            icon_source: self.icon_source,
+            color: self.color.clone(),
+            color_source: self.color_source,
+            deleted: self.deleted,
+            omitted: self.omitted,
+        });
+        for child in self.children.iter().flatten() {
+            child.flatten_into(Some(&self.id), nodes);
+        }
+    }
+}
+
+/// An exported entry that refers to its parent instead of containing its children.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlatNode {
+    /// An identifier that stays the same as long as the relative path does.
+    pub id: String,
+    /// The ID of the parent entry. `None` for the root.
+    pub parent: Option<String>,
+    /// The name of the entry.
+    pub name: String,
+    /// The path relative to the tree's root, with `/` separators.
+    pub path: String,
+    /// The type of the entry.
+    #[serde(rename = "type")]
+    pub entry_type: EntryType,
+    /// How many levels below the tree's root the entry is. The root is `0`.
+    pub depth: usize,
+    /// The version control statuses. `None` if the tree isn't in a repository.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<FileStatus>,
+    /// The icon that's displayed for the entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// What decided the entry's icon, like a built-in rule for its extension.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_source: Option<Source>,
+    /// The color of the entry's icon, as a color name or a hex code.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// What decided the color of the entry's icon, like a Lua function.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_source: Option<Source>,
+    /// Was the entry deleted? Deleted entries are still tracked by version control.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deleted: bool,
+    /// How many of the entry's children were left out because of the file limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub omitted: Option<usize>,
+}
+
+/// Converts a relative path to a string with `/` separators, so that it's the same on
+/// every platform. The root is `.`.
+fn normalize_path(path: &Path) -> String {
+    let components = path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>();
+    if components.is_empty() {
+        String::from(".")
+    } else {
+        components.join("/")
+    }
+}
+
+/// Creates an ID from a normalized path.
+///
+/// This uses 64-bit FNV-1a, which unlike the standard library's hashers is
+/// guaranteed to give the same result across versions and platforms.
+fn stable_id(path: &str) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let hash = path.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    });
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("", ".")]
+    #[case("src", "src")]
+    #[case("src/tree/mod.rs", "src/tree/mod.rs")]
+    #[cfg_attr(windows, case(r"src\tree\mod.rs", "src/tree/mod.rs"))]
+    fn test_normalize_path(#[case] path: &str, #[case] expected: &str) {
+        assert_eq!(expected, normalize_path(Path::new(path)));
+    }
+
+    #[rstest]
+    #[case("", "cbf29ce484222325")]
+    #[case("a", "af63dc4c8601ec8c")]
+    #[case("foobar", "85944171f73967e8")]
+    fn test_stable_id(#[case] path: &str, #[case] expected: &str) {
+        assert_eq!(expected, stable_id(path));
+    }
+
+    #[test]
+    fn test_flatten() {
+        let file = Node::new("lib.rs", "src/lib.rs", EntryType::File, None);
+        let src = Node::new("src", "src", EntryType::Directory, Some(vec![file]));
+        let root = Node::new(".", "", EntryType::Directory, Some(vec![src]));
+
+        let flat = root.flatten();
+        let paths = flat
+            .iter()
+            .map(|node| node.path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![".", "src", "src/lib.rs"], paths);
+        let depths = flat.iter().map(|node| node.depth).collect::<Vec<_>>();
+        assert_eq!(vec![0, 1, 2], depths);
+        assert_eq!(None, flat[0].parent);
+        assert_eq!(Some(&flat[0].id), flat[1].parent.as_ref());
+        assert_eq!(Some(&flat[1].id), flat[2].parent.as_ref());
+    }
+}
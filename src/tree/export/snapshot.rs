@@ -0,0 +1,33 @@
+//! Module for naming timestamped snapshots of a tree.
+use chrono::{DateTime, Utc};
+
+/// Creates a file name for a snapshot taken now.
+pub fn file_name() -> String {
+    file_name_at(Utc::now().timestamp())
+}
+
+/// Creates a file name for a snapshot taken at a Unix timestamp, like
+/// `2024-02-29T120000Z.json`.
+///
+/// The name uses UTC and avoids `:`, so that it's valid on every platform and so that
+/// sorting snapshots by name sorts them by time.
+fn file_name_at(seconds: i64) -> String {
+    // NOTE A time that can't be represented is treated as the epoch.
+    let time = DateTime::from_timestamp(seconds, 0).unwrap_or_default();
+    time.format("%Y-%m-%dT%H%M%SZ.json").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0, "1970-01-01T000000Z.json")]
+    #[case(951868800, "2000-03-01T000000Z.json")]
+    #[case(1709208000, "2024-02-29T120000Z.json")]
+    #[case(1735689599, "2024-12-31T235959Z.json")]
+    fn test_file_name_at(#[case] seconds: i64, #[case] expected: &str) {
+        assert_eq!(expected, file_name_at(seconds));
+    }
+}
@@ -0,0 +1,79 @@
+//! Module for deciding what happens to directories with more entries than the file
+//! limit.
+use clap::ValueEnum;
+use mlua::{FromLua, Lua};
+
+/// Controls how directories with more entries than the file limit are written.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FileLimitMode {
+    /// Write the entries up to the limit, followed by a line with the number of
+    /// entries that weren't written.
+    #[default]
+    Truncate,
+    /// Don't open the directory, like GNU tree, and only write the line with the
+    /// number of entries.
+    Skip,
+}
+
+impl FileLimitMode {
+    /// Gets how many of a directory's entries to write.
+    pub(super) fn shown(self, count: usize, limit: usize) -> usize {
+        match self {
+            _ if count <= limit => count,
+            Self::Truncate => limit,
+            Self::Skip => 0,
+        }
+    }
+}
+
+impl FromLua for FileLimitMode {
+    fn from_lua(value: mlua::Value, _lua: &Lua) -> mlua::Result<Self> {
+        const VALID_VALUES: [&str; 2] = ["truncate", "skip"];
+        let type_name = value.type_name();
+        let make_conversion_error = || mlua::Error::FromLuaConversionError {
+            from: type_name,
+            to: String::from("FileLimitMode"),
+            message: Some(format!("Must be one of {VALID_VALUES:?} or nil")),
+        };
+        let mode = value
+            .as_string()
+            .ok_or_else(make_conversion_error)?
+            .to_string_lossy();
+        let mode = match mode.as_str() {
+            "truncate" => Self::Truncate,
+            "skip" => Self::Skip,
+            _ => return Err(make_conversion_error()),
+        };
+        Ok(mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(FileLimitMode::Truncate, 3, 5, 3)]
+    #[case(FileLimitMode::Truncate, 10, 5, 5)]
+    #[case(FileLimitMode::Skip, 5, 5, 5)]
+    #[case(FileLimitMode::Skip, 10, 5, 0)]
+    fn test_shown(
+        #[case] mode: FileLimitMode,
+        #[case] count: usize,
+        #[case] limit: usize,
+        #[case] expected: usize,
+    ) {
+        assert_eq!(expected, mode.shown(count, limit));
+    }
+
+    #[rstest]
+    #[case(r#""truncate""#, Some(FileLimitMode::Truncate))]
+    #[case(r#""skip""#, Some(FileLimitMode::Skip))]
+    #[case(r#""hide""#, None)]
+    #[case("1", None)]
+    fn test_from_lua(#[case] chunk: &str, #[case] expected: Option<FileLimitMode>) {
+        let lua = Lua::new();
+        assert_eq!(expected, lua.load(chunk).eval::<FileLimitMode>().ok());
+    }
+}
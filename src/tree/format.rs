@@ -0,0 +1,38 @@
+//! Module for the formats a tree can be written in.
+use clap::ValueEnum;
+
+/// The format to write a tree in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// A text tree with icons, colors, and statuses.
+    #[default]
+    Tree,
+    /// A JSON tree, where each entry contains its children.
+    Json,
+    /// A flat JSON array, where each entry refers to its parent's ID.
+    JsonFlat,
+    /// An HTML document of nested lists, with colored icons.
+    Html,
+    /// A nested Markdown list without icons or colors.
+    Markdown,
+    /// One relative path per line, with icons, colors, and statuses, like `fd`.
+    Flat,
+    /// Plain sentences for screen readers, like `folder src, 3 items`, without
+    /// icons, colors, or box-drawing characters.
+    Accessible,
+}
+
+impl Format {
+    /// Checks if the format is text for a terminal, which can have a header and a
+    /// summary around it.
+    #[inline]
+    pub fn is_text(self) -> bool {
+        matches!(self, Self::Tree | Self::Flat | Self::Accessible)
+    }
+
+    /// Checks if the format can have ANSI escape codes, like colors.
+    #[inline]
+    pub fn is_ansi(self) -> bool {
+        matches!(self, Self::Tree | Self::Flat)
+    }
+}
@@ -0,0 +1,56 @@
+//! Module for deciding how `.git` directories are written.
+use clap::ValueEnum;
+use mlua::{FromLua, Lua};
+
+/// Controls how `.git` directories are written, instead of treating them like other
+/// hidden directories.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GitDir {
+    /// Write the directory with the checked out branch, without its entries.
+    #[default]
+    Collapsed,
+    /// Don't write the directory.
+    Hidden,
+    /// Write the directory and its entries.
+    Expanded,
+}
+
+impl FromLua for GitDir {
+    fn from_lua(value: mlua::Value, _lua: &Lua) -> mlua::Result<Self> {
+        const VALID_VALUES: [&str; 3] = ["collapsed", "hidden", "expanded"];
+        let type_name = value.type_name();
+        let make_conversion_error = || mlua::Error::FromLuaConversionError {
+            from: type_name,
+            to: String::from("GitDir"),
+            message: Some(format!("Must be one of {VALID_VALUES:?} or nil")),
+        };
+        let git_dir = value
+            .as_string()
+            .ok_or_else(make_conversion_error)?
+            .to_string_lossy();
+        let git_dir = match git_dir.as_str() {
+            "collapsed" => Self::Collapsed,
+            "hidden" => Self::Hidden,
+            "expanded" => Self::Expanded,
+            _ => return Err(make_conversion_error()),
+        };
+        Ok(git_dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(r#""collapsed""#, Some(GitDir::Collapsed))]
+    #[case(r#""hidden""#, Some(GitDir::Hidden))]
+    #[case(r#""expanded""#, Some(GitDir::Expanded))]
+    #[case(r#""open""#, None)]
+    #[case("true", None)]
+    fn test_from_lua(#[case] chunk: &str, #[case] expected: Option<GitDir>) {
+        let lua = Lua::new();
+        assert_eq!(expected, lua.load(chunk).eval::<GitDir>().ok());
+    }
+}
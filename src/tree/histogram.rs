@@ -0,0 +1,165 @@
+//! Module for summarizing where the entries of a written tree are.
+use super::bidi::isolate;
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::path::{Component, Path};
+use unicode_width::UnicodeWidthStr;
+
+/// The numbers of written entries at each depth and under each top-level directory,
+/// not including the root.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Histogram {
+    /// The number of entries at each depth, starting at depth 1.
+    depths: Vec<usize>,
+    /// The number of entries under each top-level directory, in the order that the
+    /// directories were written.
+    directories: Vec<(String, usize)>,
+    /// The index of each top-level directory in `directories`.
+    indices: HashMap<String, usize>,
+}
+
+impl Histogram {
+    /// The width of the longest bar.
+    const BAR_WIDTH: usize = 40;
+
+    /// Adds a written entry, where the path is relative to the root.
+    pub(super) fn add(&mut self, path: &Path, is_directory: bool) {
+        let mut components = path
+            .components()
+            .filter(|component| matches!(component, Component::Normal(_)));
+        let Some(top_level) = components.next() else {
+            return;
+        };
+        let depth = 1 + components.count();
+        if self.depths.len() < depth {
+            self.depths.resize(depth, 0);
+        }
+        self.depths[depth - 1] += 1;
+
+        let name = top_level.as_os_str().to_string_lossy();
+        if depth == 1 {
+            if is_directory && !self.indices.contains_key(name.as_ref()) {
+                self.indices
+                    .insert(name.to_string(), self.directories.len());
+                self.directories.push((name.into_owned(), 0));
+            }
+        } else if let Some(&index) = self.indices.get(name.as_ref()) {
+            self.directories[index].1 += 1;
+        }
+    }
+
+    /// The number of entries at each depth, starting at depth 1.
+    #[inline]
+    pub fn depths(&self) -> &[usize] {
+        &self.depths
+    }
+
+    /// The top-level directories and the number of entries under them, from the
+    /// most entries to the fewest.
+    pub fn directories(&self) -> Vec<(&str, usize)> {
+        let mut directories = self
+            .directories
+            .iter()
+            .map(|(name, count)| (name.as_str(), *count))
+            .collect::<Vec<_>>();
+        directories.sort_by(|(_, a), (_, b)| b.cmp(a));
+        directories
+    }
+
+    /// Writes a section, with each label's bar scaled to the largest count.
+    fn write_section<I>(f: &mut fmt::Formatter<'_>, title: &str, rows: I) -> fmt::Result
+    where
+        I: IntoIterator<Item = (String, usize)>,
+    {
+        let rows = rows.into_iter().collect::<Vec<_>>();
+        let label_width = rows.iter().map(|(label, _)| label.width()).max();
+        let max = rows.iter().map(|(_, count)| *count).max();
+        let (Some(label_width), Some(max)) = (label_width, max) else {
+            return Ok(());
+        };
+        writeln!(f, "{title}")?;
+        for (label, count) in rows {
+            // NOTE Any entries get a bar, so that small counts aren't hidden.
+            let bar = (count * Self::BAR_WIDTH)
+                .div_ceil(max.max(1))
+                .min(Self::BAR_WIDTH);
+            let padding = label_width - label.width();
+            writeln!(f, "  {label}{:padding$} | {} {count}", "", "#".repeat(bar))?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Histogram {
+    /// Writes a bar for each depth, then for each top-level directory.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let depths = self
+            .depths
+            .iter()
+            .enumerate()
+            .map(|(index, count)| ((index + 1).to_string(), *count));
+        Self::write_section(f, "Entries by depth:", depths)?;
+        let directories = self
+            .directories()
+            .into_iter()
+            .map(|(name, count)| (format!("{}/", isolate(name)), count));
+        Self::write_section(f, "Entries by top-level directory:", directories)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add() {
+        let mut histogram = Histogram::default();
+        histogram.add(Path::new("docs"), true);
+        histogram.add(Path::new("README.md"), false);
+        histogram.add(Path::new("src"), true);
+        histogram.add(Path::new("docs/index.md"), false);
+        histogram.add(Path::new("src/tree"), true);
+        histogram.add(Path::new("src/tree/mod.rs"), false);
+        histogram.add(Path::new("src/lib.rs"), false);
+
+        assert_eq!(&[3, 3, 1], histogram.depths());
+        assert_eq!(vec![("src", 3), ("docs", 1)], histogram.directories());
+    }
+
+    #[test]
+    fn test_display() {
+        let mut histogram = Histogram::default();
+        histogram.add(Path::new("a"), true);
+        histogram.add(Path::new("bb"), true);
+        histogram.add(Path::new("a/1"), false);
+        histogram.add(Path::new("a/2"), false);
+        histogram.add(Path::new("bb/1"), false);
+
+        let expected = format!(
+            "Entries by depth:\n  1 | {} 2\n  2 | {} 3\nEntries by top-level directory:\n  a/  | {} 2\n  bb/ | {} 1\n",
+            "#".repeat(27),
+            "#".repeat(40),
+            "#".repeat(40),
+            "#".repeat(20),
+        );
+        assert_eq!(expected, histogram.to_string());
+        assert_eq!("", Histogram::default().to_string());
+    }
+
+    #[test]
+    fn test_display_wide_and_right_to_left() {
+        let mut histogram = Histogram::default();
+        histogram.add(Path::new("日本"), true);
+        histogram.add(Path::new("עב"), true);
+        histogram.add(Path::new("a"), true);
+
+        let lines = histogram.to_string();
+        let bars = lines
+            .lines()
+            .skip(3)
+            .map(|line| line.find('|').map(|index| line[..index].width()))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![Some(8); 3], bars);
+        assert!(lines.contains("\u{2068}עב\u{2069}/"));
+    }
+}
@@ -0,0 +1,54 @@
+//! Module for OSC 8 hyperlinks, which terminals can open when they're clicked.
+//!
+//! A link starts with `ESC ] 8 ; ; URL ESC \`, and ends with the same sequence
+//! without a URL. Terminals that don't support links don't write the sequences.
+use super::export::html::percent_encode;
+use std::path::{Component, Path};
+
+/// Ends a link.
+pub const END: &str = "\x1b]8;;\x1b\\";
+
+/// Starts a link to the URL.
+pub fn start(url: &str) -> String {
+    format!("\x1b]8;;{url}\x1b\\")
+}
+
+/// Gets the `file://` URL of an absolute path, like `file:///home/me/a%20b.txt`.
+///
+/// The host is left out, so the URL is for a file on this machine.
+pub fn file_url(path: &Path) -> String {
+    let mut url = String::from("file://");
+    for component in path.components() {
+        match component {
+            // NOTE Windows paths look like `file:///C:/Users`.
+            Component::Prefix(prefix) => {
+                url.push('/');
+                url.push_str(&prefix.as_os_str().to_string_lossy());
+            }
+            Component::RootDir | Component::CurDir => {}
+            component => {
+                url.push('/');
+                url.push_str(&percent_encode(component.as_os_str().as_encoded_bytes()));
+            }
+        }
+    }
+    if url.len() == "file://".len() {
+        url.push('/');
+    }
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[cfg_attr(unix, case("/", "file:///"))]
+    #[cfg_attr(unix, case("/home/me/a b.txt", "file:///home/me/a%20b.txt"))]
+    #[cfg_attr(unix, case("/tmp/100%/ñ", "file:///tmp/100%25/%C3%B1"))]
+    #[cfg_attr(windows, case(r"C:\Users\me\a.txt", "file:///C:/Users/me/a.txt"))]
+    fn test_file_url(#[case] path: &str, #[case] expected: &str) {
+        assert_eq!(expected, file_url(Path::new(path)));
+    }
+}
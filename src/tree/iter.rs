@@ -0,0 +1,132 @@
+//! Module for iterating over a tree's entries without writing them.
+use super::walk::{Order, Visit, Walk};
+use super::{Entry, Tree};
+use crate::vcs::status::FileStatus;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// An iterator over the entries of a [`Tree`], created by [`Tree::iter`].
+///
+/// Entries are yielded depth-first, in the same order that they're written, as
+/// `(depth, entry, status)`. The root is at depth `0`, and the status is `None` if
+/// the tree doesn't have version control.
+///
+/// Like the written tree, entries are skipped, filtered, sorted, and limited by
+/// level and by the file limit. Deleted paths and the markers for entries over the
+/// file limit aren't entries, so they aren't yielded. The line limit only applies
+/// to writing, so it isn't used.
+pub struct Iter<'tree, 'vcs, 'charset, P: AsRef<Path>> {
+    /// The tree whose entries are yielded.
+    tree: &'tree Tree<'vcs, 'charset, P>,
+    /// The search of the tree.
+    walk: Walk<'tree, 'vcs, 'charset, P>,
+}
+
+impl<'tree, 'vcs, 'charset, P> Iter<'tree, 'vcs, 'charset, P>
+where
+    P: AsRef<Path>,
+{
+    /// Creates an iterator that starts at the tree's root.
+    pub(super) fn new(tree: &'tree Tree<'vcs, 'charset, P>) -> io::Result<Self> {
+        let root = Entry::new(tree.root.as_ref().to_path_buf())?;
+        let walk = Walk::new(tree, root, Order::DepthFirst);
+        Ok(Self { tree, walk })
+    }
+}
+
+impl<P> Iterator for Iter<'_, '_, '_, P>
+where
+    P: AsRef<Path>,
+{
+    type Item = (usize, Entry<PathBuf>, Option<FileStatus>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tree = self.tree;
+        self.walk.find_map(|node| match node.visit {
+            Visit::Entry { entry, .. } => {
+                let status = tree.status(entry.path());
+                Some((node.depth, *entry, status))
+            }
+            Visit::Deleted { .. } | Visit::Hidden { .. } => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::git::Git;
+    use crate::testing::Fixture;
+    use crate::tree::{Builder, GitDir};
+    use crate::vcs::status::Status;
+    use std::path::Path;
+
+    #[test]
+    fn test_iter() {
+        let fixture = Fixture::builder()
+            .file("b/c", "")
+            .file("a", "")
+            .dir("d")
+            .build()
+            .unwrap();
+        let tree = Builder::new(fixture.path()).max_level(2).build();
+
+        let items = tree
+            .iter()
+            .unwrap()
+            .map(|(depth, entry, status)| {
+                let path = entry.path().strip_prefix(fixture.path()).unwrap();
+                (depth, path.to_path_buf(), status)
+            })
+            .collect::<Vec<_>>();
+        let expected = [(0, ""), (1, "a"), (1, "b"), (2, "b/c"), (1, "d")]
+            .map(|(depth, path)| (depth, Path::new(path).to_path_buf(), None));
+        assert_eq!(expected.to_vec(), items);
+    }
+
+    #[test]
+    fn test_iter_level() {
+        let fixture = Fixture::builder().file("a/b", "").build().unwrap();
+        let tree = Builder::new(fixture.path()).max_level(1).build();
+
+        let depths = tree
+            .iter()
+            .unwrap()
+            .map(|(depth, _, _)| depth)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![0, 1], depths);
+    }
+
+    #[test]
+    fn test_iter_status() {
+        let fixture = Fixture::builder()
+            .git_init()
+            .file("tracked", "")
+            .git_commit("Initial commit")
+            .file("tracked", "modified")
+            .file("new", "")
+            .build()
+            .unwrap();
+        let git = Git::new(fixture.path())
+            .unwrap()
+            .expect("The fixture should be a repository");
+        let tree = Builder::new(fixture.path())
+            .vcs(&git)
+            .git_dir(GitDir::Hidden)
+            .build();
+
+        let statuses = tree
+            .iter()
+            .unwrap()
+            .skip(1)
+            .map(|(_, entry, status)| {
+                let name = entry.path().file_name().unwrap().to_owned();
+                (name, status.and_then(|status| status.untracked))
+            })
+            .collect::<Vec<_>>();
+        let expected = vec![
+            ("new".into(), Some(Status::Added)),
+            ("tracked".into(), Some(Status::Modified)),
+        ];
+        assert_eq!(expected, statuses);
+    }
+}
@@ -1,32 +1,186 @@
 //! Provides the utility for generating a tree.
-use crate::color::{Color, ColorChoice};
+use crate::color::{Color, ColorChoice, Style};
 use crate::config;
-use crate::git::status::StatusGetter;
-use crate::git::{
-    Git,
-    status::{self, Status},
-};
+use crate::git::Git;
+use crate::i18n::Lang;
+use crate::ignore::Ignore;
+use crate::lua::interop::Line;
+use crate::rules;
+use crate::sorting::{self, Sorting};
+use crate::vcs::status::{self, StatusGetter};
+pub use crate::vcs::status::{FileStatus, Status};
+use crate::vcs::{Churn, LastCommits, Vcs};
+use ancestors::Ancestors;
+pub use annotate::Annotations;
 pub use builder::Builder;
-pub use charset::Charset;
+pub use charset::{Charset, CharsetError, CharsetStyle};
 pub use entry::Entry;
+use export::accessible::AccessibleRenderer;
+use export::html::HtmlRenderer;
+use export::json::JsonRenderer;
+use export::markdown::MarkdownRenderer;
+pub use file_limit::FileLimitMode;
+pub use format::Format;
+pub use git_dir::GitDir;
+pub use histogram::Histogram;
+pub use iter::Iter;
+pub use mtime::MtimeFormat;
 use owo_colors::AnsiColors;
 use owo_colors::OwoColorize;
+pub use pattern::NamePattern;
+use prefetch::Prefetcher;
+pub use recent::RecentWindow;
+pub use render::{RenderNode, Renderer};
+use same_file::Handle;
+use size::DiskUsage;
+pub(crate) use size::total_size;
+pub use size::{Size, SizeMode};
+pub use sort_key::SortKey;
+pub use stats::Stats;
+use std::cell::{Cell, OnceCell, RefCell};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
 use std::fmt::Display;
+use std::fs;
 use std::io::{self, Write, stdout};
-use std::path::{self, Path, PathBuf};
+use std::path::{self, Component, Path, PathBuf, Prefix, PrefixComponent};
+use std::time::SystemTime;
+pub use theme::{IconTheme, StatusGlyphs};
+use unicode_width::UnicodeWidthStr;
+use walk::{Order, Visit, Walk};
 
+mod ancestors;
+mod annotate;
+mod bidi;
 mod builder;
 mod charset;
 pub mod entry;
+pub mod export;
+mod file_limit;
+mod format;
+mod git_dir;
+mod histogram;
+mod hyperlink;
+mod iter;
+mod mtime;
+mod pattern;
+mod prefetch;
+mod recent;
+pub mod render;
+pub mod report;
+mod size;
+mod sort_key;
+mod stats;
+pub mod suggest;
+mod theme;
+mod walk;
 
 /// Generates a tree.
-pub struct Tree<'git, 'charset, P: AsRef<Path>> {
+pub struct Tree<'vcs, 'charset, P: AsRef<Path>> {
     /// The root path to start from.
     root: P,
-    /// The optional git state of the directory.
-    git: Option<&'git Git>,
+    /// The format to write the tree in.
+    format: Format,
+    /// The optional URL to link entries to in HTML output.
+    html_base: Option<String>,
+    /// Should names be wrapped in backticks in Markdown output?
+    markdown_code: bool,
+    /// The optional version control state of the directory.
+    vcs: Option<&'vcs dyn Vcs>,
+    /// The git repositories inside the tree's repository, like submodules, by the
+    /// paths they were found at.
+    nested: RefCell<Vec<(PathBuf, Git)>>,
+    /// The followed symlinks to directories and their resolved targets, so that the
+    /// entries inside them get the statuses of the entries they link to.
+    followed: RefCell<HashMap<PathBuf, PathBuf>>,
+    /// The optional commit counts for each file.
+    churn: Option<Churn>,
+    /// The optional last commit times to color file names by.
+    last_commits: Option<LastCommits>,
     /// The maximum depth level to display.
     max_level: Option<usize>,
+    /// The maximum number of lines to write before truncating.
+    max_lines: Option<usize>,
+    /// The maximum number of entries to write in each directory.
+    file_limit: Option<usize>,
+    /// What happens to directories with more entries than the file limit.
+    file_limit_mode: FileLimitMode,
+    /// The number of lines that have been written.
+    lines: Cell<usize>,
+    /// The numbers of directories and files that have been written.
+    stats: Cell<Stats>,
+    /// The language of the summary and of the markers for entries that aren't
+    /// written.
+    lang: Lang,
+    /// The numbers of entries that have been written at each depth and under each
+    /// top-level directory.
+    histogram: RefCell<Histogram>,
+    /// Reads directories before they're written, once the text tree is written.
+    prefetcher: OnceCell<Prefetcher>,
+    /// For each level being written, is the entry the last in its directory?
+    ///
+    /// Levels where it isn't continue the vertical line of their branch.
+    last_entries: RefCell<Vec<bool>>,
+    /// The size above which files are highlighted.
+    warn_size: Option<Size>,
+    /// The optional ignore files to check, in addition to version control.
+    ignore: Option<Ignore>,
+    /// Should ignored entries be treated as ignored?
+    respect_ignores: bool,
+    /// The optional pattern that file names must match to be displayed.
+    pattern: Option<NamePattern>,
+    /// Should directories without any displayed entries be hidden?
+    prune: bool,
+    /// Whether each directory that was checked is pruned, so that each directory is
+    /// only searched once.
+    pruned: RefCell<HashMap<PathBuf, bool>>,
+    /// Controls which entries have their sizes displayed.
+    size_mode: SizeMode,
+    /// Should the total size of the tree be added to the summary?
+    disk_usage: bool,
+    /// The total sizes of the directories, computed when they're first needed.
+    totals: OnceCell<DiskUsage>,
+    /// The optional format for displaying modification times.
+    mtime_format: Option<MtimeFormat>,
+    /// The optional time since which modified files are highlighted as recent.
+    recent_since: Option<SystemTime>,
+    /// The optional commands whose output annotates entries.
+    annotations: Option<Annotations>,
+    /// The annotations for the entries of the directory that's being written.
+    annotated: RefCell<HashMap<PathBuf, String>>,
+    /// Should permissions be displayed?
+    permissions: bool,
+    /// Should names link to their files?
+    hyperlinks: bool,
+    /// Should the root's path be written as a breadcrumb?
+    breadcrumb: bool,
+    /// Should hidden entries be shown instead of skipped by default?
+    show_hidden: bool,
+    /// Should version control statuses be written after the tree, instead of
+    /// waiting for them?
+    two_pass: bool,
+    /// Should paths that were deleted but are still tracked be written?
+    show_deleted: bool,
+    /// The paths whose statuses are written after the tree.
+    deferred: RefCell<Vec<PathBuf>>,
+    /// What a flat listing is sorted by.
+    sort_key: SortKey,
+    /// Should a flat listing be sorted in reverse?
+    reverse: bool,
+    /// Should the text tree be written level by level, instead of nesting each
+    /// directory's entries under it?
+    breadth_first: bool,
+    /// Should symlinks to directories be traversed?
+    follow_symlinks: bool,
+    /// How `.git` directories are written.
+    git_dir: GitDir,
+    /// The optional sorting that overrides the configuration.
+    sorting: Option<Sorting>,
+    /// The optional icons that override the icon configuration.
+    icon_theme: Option<IconTheme>,
+    /// The characters for version control statuses.
+    status_glyphs: StatusGlyphs,
     /// Overrides the configured color choice (e.g. if specified in the CLI).
     color_choice: Option<ColorChoice>,
     /// Provides the characters to print when traversing the directory structure.
@@ -39,9 +193,13 @@ pub struct Tree<'git, 'charset, P: AsRef<Path>> {
     icons: config::Icons,
     /// Provides color configuration.
     colors: config::Colors,
+    /// Hooks from plugins, which run after the configuration.
+    plugins: config::Plugins,
+    /// WASM plugins, which decorate entries after the Lua plugins.
+    wasm_plugins: config::WasmPlugins,
 }
 
-impl<'git, 'charset, P> Tree<'git, 'charset, P>
+impl<'vcs, 'charset, P> Tree<'vcs, 'charset, P>
 where
     P: AsRef<Path>,
 {
@@ -61,299 +219,3363 @@ where
     where
         W: Write,
     {
-        let Ok(entry) = Entry::new(&self.root) else {
-            // HACK We can't read the first entry for some reason, so we'll just print
-            //      it and exit.
-            let path = self.root.as_ref();
-            Self::write_path(writer, path)?;
-            return writeln!(writer);
-        };
-        self.write_depth(writer, entry, 0)?;
-        writer.flush()
+        match self.format {
+            Format::Tree | Format::Flat if self.config.has_postprocess() => {
+                self.write_postprocessed(writer)
+            }
+            Format::Tree => self.write_tree(writer),
+            Format::Flat => self.write_flat(writer),
+            Format::Json => self.render(writer, &mut JsonRenderer::new()),
+            Format::JsonFlat => self.render(writer, &mut JsonRenderer::new().flat()),
+            Format::Html => {
+                let mut renderer = HtmlRenderer::new();
+                if let Some(base) = self.html_base.as_deref() {
+                    renderer = renderer.links(base);
+                }
+                self.render(writer, &mut renderer)
+            }
+            Format::Markdown => {
+                let mut renderer = MarkdownRenderer::new();
+                if self.markdown_code {
+                    renderer = renderer.code();
+                }
+                self.render(writer, &mut renderer)
+            }
+            Format::Accessible => self.render(writer, &mut AccessibleRenderer::new()),
+        }
     }
 
-    /// Writes the tree at a certain depth to the writer.
-    fn write_depth<W, P2>(&self, writer: &mut W, entry: Entry<P2>, depth: usize) -> io::Result<()>
+    /// Writes the text tree to a buffer, and then writes the lines that the
+    /// configuration's `postprocess` function changes them to.
+    fn write_postprocessed<W>(&self, writer: &mut W) -> io::Result<()>
     where
         W: Write,
-        P2: AsRef<Path>,
     {
-        let path = entry.path();
+        let mut buffer = Vec::new();
+        match self.format {
+            Format::Flat => self.write_flat(&mut buffer)?,
+            _ => self.write_tree(&mut buffer)?,
+        }
+        let lines = String::from_utf8_lossy(&buffer)
+            .lines()
+            .map(|line| Line(String::from(line)))
+            .collect();
+        for Line(line) in self.config.postprocess(lines) {
+            writeln!(writer, "{line}")?;
+        }
+        writer.flush()
+    }
 
-        // NOTE For the top level, we always print the full path the user specified.
-        self.write_entry(writer, &entry, depth == 0)?;
+    /// Writes the tree with a renderer, like a custom output format.
+    pub fn render<W, R>(&self, writer: &mut W, renderer: &mut R) -> io::Result<()>
+    where
+        W: Write,
+        R: Renderer + ?Sized,
+    {
+        let node = self.export()?;
+        renderer.render(writer, &node)
+    }
 
-        writeln!(writer)?;
-        if !path.is_dir() {
-            return Ok(());
-        }
+    /// Gets the plugins, like to check them for errors after writing the tree.
+    #[inline]
+    pub fn plugins(&self) -> &config::Plugins {
+        &self.plugins
+    }
 
-        // NOTE We'll just skip file read errors to continue printing the rest of the
-        //      tree.
-        let entries = match path.read_dir() {
-            Ok(entries) => entries.filter_map(Result::ok),
-            Err(_) => return Ok(()),
-        };
-        let entries = {
-            let entries = entries.map(|entry| entry.path()).map(Entry::new);
-            // NOTE If we can't read a directory entry, then we'll just ignore it so that
-            //      we don't stop early.
-            let entries = entries.filter_map(Result::ok);
+    /// Gets the WASM plugins that decorate the tree's entries.
+    #[inline]
+    pub fn wasm_plugins(&self) -> &config::WasmPlugins {
+        &self.wasm_plugins
+    }
 
-            // NOTE If the config exists and it successfully detects if a file should
-            //      be skipped, use that value. Otherwise, use default behavior.
-            let entries = entries.filter(|entry| !self.should_skip_entry(entry));
+    /// Iterates over the entries of the tree without writing them. See [`Iter`].
+    #[inline]
+    pub fn iter(&self) -> io::Result<Iter<'_, 'vcs, 'charset, P>> {
+        Iter::new(self)
+    }
 
-            let mut entries = entries.collect::<Vec<_>>();
-            entries.sort_by(|left, right| self.config.cmp(left.path(), right.path()));
-            entries
-        };
-        if self.max_level.map(|max| depth >= max).unwrap_or(false) {
-            return Ok(());
+    /// Creates a structured export of the tree.
+    pub fn export(&self) -> io::Result<export::Node> {
+        let entry = Entry::new(self.root.as_ref().to_path_buf())?;
+        self.fill_total_size(&entry);
+        self.stats.set(Stats::default());
+        self.histogram.take();
+        // NOTE Nodes are visited depth-first, so when a node is visited, the nodes
+        //      that are as deep or deeper are complete, and are added to their
+        //      parents.
+        let mut stack = Vec::new();
+        for node in Walk::new(self, entry, Order::DepthFirst) {
+            Self::close_exported(&mut stack, node.depth);
+            match node.visit {
+                Visit::Entry {
+                    entry,
+                    is_traversed,
+                    ..
+                } => {
+                    if node.depth > 0 {
+                        self.count(&entry);
+                    }
+                    stack.push(self.export_entry(&entry, node.depth, is_traversed));
+                }
+                Visit::Deleted {
+                    path, is_directory, ..
+                } => stack.push(self.export_deleted(&path, node.depth, is_directory)),
+                Visit::Hidden { count, .. } => {
+                    if let Some(parent) = stack.last_mut() {
+                        parent.omitted = Some(count);
+                    }
+                }
+            }
         }
+        Self::close_exported(&mut stack, 1);
+        Ok(stack.pop().expect("The root should always be visited"))
+    }
 
-        for entry in entries {
-            self.write_indentation(writer, depth)?;
-            write!(writer, "{}", self.charset.depth)?;
-            self.write_depth(writer, entry, depth + 1)?;
+    /// Adds the exported nodes that are at a depth or deeper to their parents.
+    fn close_exported(stack: &mut Vec<export::Node>, depth: usize) {
+        while stack.len() > depth.max(1) {
+            let node = stack
+                .pop()
+                .expect("The stack should have a node and its parent");
+            let parent = stack.last_mut().expect("The stack should have the parent");
+            parent.children.get_or_insert_with(Vec::new).push(node);
         }
-
-        Ok(())
     }
 
-    /// Writes an entry.
-    fn write_entry<W, P2>(&self, writer: &mut W, entry: &Entry<P2>, is_top: bool) -> io::Result<()>
+    /// Exports an entry, without its children.
+    fn export_entry<P2>(&self, entry: &Entry<P2>, depth: usize, is_traversed: bool) -> export::Node
     where
-        W: Write,
         P2: AsRef<Path>,
     {
         let path = entry.path();
-        self.write_statuses(writer, path)?;
-
-        let icon = self.icons.get_icon(entry);
-        self.write_colorized_for_entry(entry, writer, icon)?;
-        // NOTE Padding for the icons
-        write!(writer, " ")?;
-
-        // HACK is_path_ignored tries to strip the prefix, which we never want to do at
-        //      the top when the path is *only* the prefix. In fact, we don't want to
-        //      check ignore status here at all since the current implementation breaks
-        //      for paths that contain the directory `.`, it seems. Also, the top
-        //      should always be a directory, and the current implementation only seems
-        //      to work for files.
-        let is_ignored = !is_top && self.is_path_ignored(path);
-
-        let path = if is_top {
+        let relative_path = path.strip_prefix(&self.root).unwrap_or(path);
+        // NOTE Like the text tree, the top level uses the path the user specified.
+        let name = if depth == 0 {
             path.as_os_str()
         } else {
-            // NOTE The only time the path shouldn't have a file name is at the top
-            //      level, which could be a path like "." or "..". At the top level
-            //      call, `full_name` should always receive `true`.
             path.file_name()
                 .expect("A directory entry should always have a file name")
         };
+        let status = self.status(path);
+        let (icon, icon_source) = self.get_icon_with_source(entry);
+        let icon = Some(icon).filter(|icon| !icon.is_empty());
+        let (style, color_source) = self.icon_style_with_source(entry);
+        let color = style.and_then(|style| style.fg);
+        export::Node {
+            status,
+            icon_source: icon.is_some().then_some(icon_source),
+            icon,
+            color_source: color.is_some().then_some(color_source),
+            color: color.map(|color| color.to_string()),
+            ..export::Node::new(
+                name.to_string_lossy(),
+                relative_path,
+                entry.attributes().into(),
+                is_traversed.then(Vec::new),
+            )
+        }
+    }
 
-        if !is_ignored {
-            Self::write_path(writer, path)
+    /// Exports a path that was deleted but is still tracked, without the deleted
+    /// paths inside it.
+    fn export_deleted(&self, path: &Path, depth: usize, is_directory: bool) -> export::Node {
+        let relative_path = path.strip_prefix(&self.root).unwrap_or(path);
+        let name = path.file_name().unwrap_or(path.as_os_str());
+        let (entry_type, children) = if is_directory {
+            let children = (!self.is_max_level(depth)).then(Vec::new);
+            (export::EntryType::Directory, children)
         } else {
-            const TEXT_COLOR: Option<Color> = Some(Color::Ansi(AnsiColors::Black));
-            self.color_choice()
-                .write_to(writer, path.display(), TEXT_COLOR, None)
+            (export::EntryType::File, None)
+        };
+        let icon = self.icons.get_deleted_icon(path, is_directory);
+        export::Node {
+            status: self.status(path),
+            icon: Some(icon).filter(|icon| !icon.is_empty()).map(String::from),
+            deleted: true,
+            ..export::Node::new(name.to_string_lossy(), relative_path, entry_type, children)
         }
     }
 
-    /// Writes a path's name.
-    fn write_path<W, P2>(writer: &mut W, path: P2) -> io::Result<()>
+    /// Writes a timestamped JSON export of the tree to a directory, creating the
+    /// directory if needed. Returns the path of the written file.
+    pub fn write_snapshot<D>(&self, dir: D) -> io::Result<PathBuf>
+    where
+        D: AsRef<Path>,
+    {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let path = dir.join(export::snapshot::file_name());
+        let mut file = io::BufWriter::new(fs::File::create(&path)?);
+        self.render(&mut file, &mut JsonRenderer::new())?;
+        Ok(path)
+    }
+
+    /// Writes the text tree to the writer.
+    fn write_tree<W>(&self, writer: &mut W) -> io::Result<()>
     where
         W: Write,
-        P2: AsRef<Path>,
     {
-        let path = path.as_ref();
-        writer.write_all(path.as_os_str().as_encoded_bytes())
+        let Ok(entry) = Entry::new(self.root.as_ref().to_path_buf()) else {
+            // HACK We can't read the first entry for some reason, so we'll just print
+            //      it and exit.
+            let path = self.root.as_ref();
+            Self::write_path(writer, path)?;
+            return writeln!(writer);
+        };
+        self.fill_total_size(&entry);
+        self.lines.set(0);
+        self.stats.set(Stats::default());
+        self.histogram.take();
+        self.last_entries.borrow_mut().clear();
+        self.deferred.borrow_mut().clear();
+        if let Some(prefetcher) = self.prefetcher.get() {
+            prefetcher.clear();
+        }
+        if self.breadth_first {
+            let walk = Walk::new(self, entry, Order::BreadthFirst).prepared();
+            self.write_breadth_first(writer, walk)?;
+        } else {
+            let walk = Walk::new(self, entry, Order::DepthFirst).prepared();
+            self.write_depth_first(writer, walk)?;
+        }
+        self.write_deferred_statuses(writer)?;
+        writer.flush()
     }
 
-    /// Writes indentation.
-    fn write_indentation<W>(&self, writer: &mut W, level: usize) -> io::Result<()>
+    /// Writes each entry's path relative to the root on its own line, instead of
+    /// nesting entries under their directories.
+    fn write_flat<W>(&self, writer: &mut W) -> io::Result<()>
     where
         W: Write,
     {
-        for _ in 0..level {
-            write!(writer, "{}", self.charset.breadth)?;
+        self.lines.set(0);
+        self.stats.set(Stats::default());
+        self.histogram.take();
+        self.deferred.borrow_mut().clear();
+        let root = Entry::new(self.root.as_ref().to_path_buf())?;
+        // NOTE The root is every path's prefix, so it isn't listed. The markers for
+        //      entries over the file limit aren't sorted, so they're written last.
+        let (mut visits, hidden): (Vec<_>, Vec<_>) = Walk::new(self, root, Order::DepthFirst)
+            .skip(1)
+            .map(|node| node.visit)
+            .partition(|visit| !matches!(visit, Visit::Hidden { .. }));
+        self.sort_flat(&mut visits);
+        self.annotate(visits.iter().filter_map(|visit| match visit {
+            Visit::Entry { entry, .. } => Some(entry.path()),
+            Visit::Deleted { .. } | Visit::Hidden { .. } => None,
+        }));
+        let mut remaining = visits
+            .iter()
+            .chain(&hidden)
+            .map(Visit::count)
+            .sum::<usize>();
+        for visit in visits.into_iter().chain(hidden) {
+            // NOTE One line is kept for the marker if there are more entries.
+            let reserved = usize::from(remaining > visit.count());
+            if self.is_line_limit_reached(reserved) {
+                let text = self
+                    .lang
+                    .message("marker-truncated-count", &[("count", &remaining)]);
+                self.write_level_marker(writer, text)?;
+                break;
+            }
+            remaining -= visit.count();
+            match visit {
+                Visit::Entry { entry, tint, .. } => {
+                    self.write_entry(writer, &entry, false, tint)?;
+                    writeln!(writer)?;
+                    self.lines.set(self.lines.get() + 1);
+                    self.count(&entry);
+                }
+                Visit::Deleted {
+                    path,
+                    is_directory,
+                    tint,
+                } => self.write_deleted(writer, &path, is_directory, tint)?,
+                Visit::Hidden { directory, count } => {
+                    let directory = directory.strip_prefix(&self.root).unwrap_or(&directory);
+                    let marker = self.lang.message("marker-omitted", &[("count", &count)]);
+                    let text = if directory.as_os_str().is_empty() {
+                        marker
+                    } else {
+                        let separator = path::MAIN_SEPARATOR;
+                        format!("{}{separator}{marker}", directory.display())
+                    };
+                    self.lines.set(self.lines.get() + 1);
+                    self.write_level_marker(writer, text)?;
+                }
+            }
         }
-        Ok(())
+        self.write_deferred_statuses(writer)?;
+        writer.flush()
     }
 
-    /// Checks if an entry should be skipped.
-    ///
-    /// If the config exists, the config has a `skip` function, *and* that function
-    /// successfully returns a boolean value, then that value will be used. Otherwise,
-    /// it will just skip all hidden files.
-    fn should_skip_entry<P2>(&self, entry: &Entry<P2>) -> bool
+    /// Sorts the entries and deleted paths of a flat listing across the whole tree.
+    /// Entries that are equal stay in the order that the tree is written in.
+    fn sort_flat(&self, visits: &mut Vec<Visit>) {
+        let path = |visit: &Visit| match visit {
+            Visit::Entry { entry, .. } => entry.path().to_path_buf(),
+            Visit::Deleted { path, .. } => path.clone(),
+            Visit::Hidden { directory, .. } => directory.clone(),
+        };
+        // NOTE Deleted paths don't have attributes, so they don't have sizes or
+        //      modification times.
+        fn attributes(visit: &Visit) -> Option<&entry::Attributes> {
+            match visit {
+                Visit::Entry { entry, .. } => Some(entry.attributes()),
+                Visit::Deleted { .. } | Visit::Hidden { .. } => None,
+            }
+        }
+        // NOTE Directories' sizes come from the one walk of the whole tree, instead
+        //      of walking each directory that's compared.
+        let size = |visit: &Visit| match visit {
+            Visit::Entry { entry, .. } => {
+                let attributes = entry.attributes();
+                attributes.file().map(|file| file.size()).or_else(|| {
+                    (self.size_mode == SizeMode::Recursive && attributes.is_directory())
+                        .then(|| self.disk_usage_of(entry.path()))
+                        .flatten()
+                })
+            }
+            Visit::Deleted { .. } | Visit::Hidden { .. } => None,
+        };
+        let compare = |left: &Visit, right: &Visit| match self.sort_key {
+            SortKey::Path => Ordering::Equal,
+            SortKey::Name => self.cmp(&path(left), &path(right)),
+            SortKey::Size => size(left).cmp(&size(right)),
+            SortKey::Mtime => {
+                let modified =
+                    |visit| attributes(visit).and_then(|attributes| attributes.modified());
+                modified(left).cmp(&modified(right))
+            }
+        };
+        match (self.sort_key, self.reverse) {
+            (SortKey::Path, true) => visits.reverse(),
+            (SortKey::Path, false) => {}
+            (_, true) => sorting::sort_by(visits, |left, right| compare(left, right).reverse()),
+            (_, false) => sorting::sort_by(visits, compare),
+        }
+    }
+
+    /// Writes the text tree, with each directory's entries nested under it.
+    fn write_depth_first<W>(
+        &self,
+        writer: &mut W,
+        mut walk: Walk<'_, 'vcs, 'charset, P>,
+    ) -> io::Result<()>
     where
-        P2: AsRef<Path>,
+        W: Write,
     {
-        let path = entry.path();
-        self.config
-            .should_skip(entry, || self.is_path_ignored(path))
+        const RECURSIVE_COLOR: Option<Color> = Some(Color::Ansi(AnsiColors::Yellow));
+
+        // NOTE Once the limit is reached, the rest of each directory gets a marker,
+        //      even though the lines that were kept for them are being written.
+        let mut is_truncated = false;
+        while let Some(node) = walk.next() {
+            let depth = node.depth;
+            if depth > 0 {
+                // NOTE The deeper levels belonged to the entries before this one.
+                self.last_entries.borrow_mut().truncate(depth - 1);
+                // NOTE Each directory that has entries left gets a marker when the
+                //      limit is reached, so there's a line kept for each of them.
+                if is_truncated || self.is_line_limit_reached(walk.open_directories()) {
+                    is_truncated = true;
+                    let count = node.visit.count() + walk.remaining();
+                    let text = self
+                        .lang
+                        .message("marker-truncated-count", &[("count", &count)]);
+                    self.write_marker(writer, depth - 1, text)?;
+                    walk.skip_directory();
+                    continue;
+                }
+            }
+            match node.visit {
+                Visit::Entry {
+                    entry,
+                    tint,
+                    is_recursive,
+                    ..
+                } => {
+                    if depth > 0 {
+                        self.write_branch(writer, depth - 1, node.is_last)?;
+                        self.last_entries.borrow_mut().push(node.is_last);
+                    }
+                    // NOTE For the top level, we always print the full path the user
+                    //      specified.
+                    self.write_entry(writer, &entry, depth == 0, tint)?;
+                    // NOTE A directory that is already being written is reached again
+                    //      through a symlink or a bind mount, so it isn't traversed.
+                    if is_recursive {
+                        write!(writer, " ")?;
+                        self.color_choice()
+                            .write_to(writer, "[recursive]", RECURSIVE_COLOR)?;
+                    }
+                    writeln!(writer)?;
+                    self.lines.set(self.lines.get() + 1);
+                    // NOTE The first levels are shown right away, even if deeper levels
+                    //      are slow to read.
+                    if depth <= 1 {
+                        writer.flush()?;
+                    }
+                    if depth > 0 {
+                        self.count(&entry);
+                    }
+                }
+                Visit::Deleted {
+                    path,
+                    is_directory,
+                    tint,
+                } => {
+                    self.write_branch(writer, depth - 1, node.is_last)?;
+                    self.last_entries.borrow_mut().push(node.is_last);
+                    self.write_deleted(writer, &path, is_directory, tint)?;
+                }
+                Visit::Hidden { count, .. } => {
+                    self.lines.set(self.lines.get() + 1);
+                    let text = self.lang.message("marker-omitted", &[("count", &count)]);
+                    self.write_marker(writer, depth - 1, text)?;
+                }
+            }
+        }
+        Ok(())
     }
 
-    /// Checks if a path is ignored.
-    fn is_path_ignored<P2>(&self, path: P2) -> bool
+    /// Writes the indentation and the branch of an entry at a certain depth.
+    fn write_branch<W>(&self, writer: &mut W, depth: usize, is_last: bool) -> io::Result<()>
     where
-        P2: AsRef<Path>,
+        W: Write,
     {
-        self.git
-            .and_then(|git| {
-                // HACK This function doesn't expect a `./` prefix. It seems to return
-                //      `true` when it's present???
-                let path = self
-                    .clean_path_for_git2(path)
-                    .expect("Should be able to resolve path relative to git root");
-                git.is_ignored(path).ok()
-            })
-            .unwrap_or(false)
+        self.write_indentation(writer, depth)?;
+        let branch = if is_last {
+            &self.charset.last_depth
+        } else {
+            &self.charset.depth
+        };
+        self.write_connector(writer, branch)
     }
 
-    /// Writes the text in a colored style.
-    fn write_colorized_for_entry<W, D, P2>(
+    /// Writes a path that was deleted but is still tracked, struck through in the
+    /// removed status's style, layered over the tint of its directory.
+    fn write_deleted<W>(
         &self,
-        entry: &Entry<P2>,
         writer: &mut W,
-        display: D,
+        path: &Path,
+        is_directory: bool,
+        tint: Option<Style>,
     ) -> io::Result<()>
     where
         W: Write,
-        D: Display + OwoColorize,
-        P2: AsRef<Path>,
     {
-        let color_choice = self.color_choice();
+        // NOTE Deleted paths don't have attributes, so their columns are left blank.
+        if self.permissions {
+            write!(
+                writer,
+                "{:width$} ",
+                "",
+                width = entry::Attributes::PERMISSIONS_WIDTH
+            )?;
+        }
+        if let Some(ref churn) = self.churn {
+            write!(writer, "{:width$} ", "", width = churn.width())?;
+        }
+        if self.size_mode != SizeMode::Off {
+            write!(writer, "{:width$} ", "", width = Size::WIDTH)?;
+        }
+        if let Some(ref format) = self.mtime_format {
+            write!(writer, "{:width$} ", "", width = format.width())?;
+        }
+        self.write_statuses(writer, path)?;
 
-        // HACK Optimization to avoid calculating colors when they're disabled.
-        if color_choice.is_off() {
-            return write!(writer, "{display}");
+        // NOTE A deletion that's staged has the tracked status's color.
+        let is_staged = self
+            .status(path)
+            .is_some_and(|status| status.tracked == Some(Status::Removed));
+        let style = if is_staged {
+            self.colors.for_tracked_git_status(Status::Removed)
+        } else {
+            self.colors.for_untracked_git_status(Status::Removed)
+        };
+        let style = Style::layer(style, tint);
+
+        let icon = self.icons.get_deleted_icon(path, is_directory);
+        if !icon.is_empty() {
+            self.color_choice().write_to(writer, icon, style)?;
+            write!(writer, " ")?;
         }
+        let name = if self.format == Format::Flat {
+            path.strip_prefix(&self.root).unwrap_or(path).as_os_str()
+        } else {
+            path.file_name().unwrap_or(path.as_os_str())
+        };
+        let style = Style::from(style).strikethrough();
+        let name = bidi::isolate(&name.to_string_lossy()).into_owned();
+        self.color_choice().write_to(writer, name, style)?;
+        writeln!(writer)?;
+        self.lines.set(self.lines.get() + 1);
+        Ok(())
+    }
 
-        let fg = self.colors.for_icon(entry);
-        color_choice.write_to(writer, display, fg, None)
+    /// Gets the paths directly in a directory that were deleted but are still
+    /// tracked, sorted like entries, if they're shown.
+    fn deleted_children(&self, directory: &Path) -> Vec<PathBuf> {
+        if !self.show_deleted {
+            return Vec::new();
+        }
+        let deleted = |directory: &Path| {
+            self.with_vcs(directory, |vcs, path| vcs.deleted(path))
+                .unwrap_or_default()
+        };
+        // NOTE Deleted paths don't have attributes, so the configuration's `skip`
+        //      function isn't called with them. Instead, they're hidden like dotfiles
+        //      unless hidden entries are shown, and deleted files are only written if
+        //      they match the pattern.
+        let mut children = deleted(directory)
+            .into_iter()
+            .filter_map(|path| path.file_name().map(|name| directory.join(name)))
+            .filter(|path| {
+                let is_dotfile = path
+                    .file_name()
+                    .is_some_and(|name| name.as_encoded_bytes().starts_with(b"."));
+                self.show_hidden || !(cfg!(not(windows)) && is_dotfile)
+            })
+            .filter(|path| {
+                self.pattern
+                    .as_ref()
+                    .is_none_or(|pattern| pattern.matches(path) || !deleted(path).is_empty())
+            })
+            .collect::<Vec<_>>();
+        children.sort_by(|left, right| self.cmp(left, right));
+        children
     }
 
-    /// Writes colorized git statuses.
-    fn write_statuses<W>(&self, writer: &mut W, path: &Path) -> io::Result<()>
+    /// Writes the text tree level by level. The entries at each depth are written
+    /// before the entries at the next depth, under the path of their directory.
+    fn write_breadth_first<W>(
+        &self,
+        writer: &mut W,
+        mut walk: Walk<'_, 'vcs, 'charset, P>,
+    ) -> io::Result<()>
     where
         W: Write,
     {
-        let Some(git) = self.git else { return Ok(()) };
-
-        // HACK cached status keys don't have a ./ prefix and git2 apparently doesn't expect it.
-        let path = self
-            .clean_path_for_git2(path)
-            .expect("Should be able to resolve path relative to git root");
+        const RECURSIVE_COLOR: Option<Color> = Some(Color::Ansi(AnsiColors::Yellow));
+        const HEADER_COLOR: Option<Color> = Some(Color::Ansi(AnsiColors::Blue));
 
-        self.write_status::<status::Untracked, _, _>(writer, git, &path)?;
-        self.write_status::<status::Tracked, _, _>(writer, git, path)?;
+        while let Some(node) = walk.next() {
+            let depth = node.depth;
+            // NOTE The first level is under the top, which is written first.
+            if let Some(directory) = node.directory.filter(|_| depth > 1) {
+                // NOTE The header is two lines, and it's followed by at least an entry
+                //      or the marker.
+                if self.is_line_limit_reached(2) {
+                    let text = self.lang.message("marker-truncated", &[]);
+                    return self.write_level_marker(writer, text);
+                }
+                let path = directory.strip_prefix(&self.root).unwrap_or(&directory);
+                let header = format!("{}{}", path.display(), path::MAIN_SEPARATOR);
+                writeln!(writer)?;
+                self.color_choice().write_to(writer, header, HEADER_COLOR)?;
+                writeln!(writer)?;
+                self.lines.set(self.lines.get() + 2);
+            }
+            let is_hidden = matches!(node.visit, Visit::Hidden { .. });
+            let reserved = usize::from(walk.open_directories() > 0);
+            if depth > 0 && !is_hidden && self.is_line_limit_reached(reserved) {
+                let count = node.visit.count() + walk.remaining();
+                let text = self
+                    .lang
+                    .message("marker-truncated-count", &[("count", &count)]);
+                return self.write_level_marker(writer, text);
+            }
+            match node.visit {
+                Visit::Entry {
+                    entry,
+                    tint,
+                    is_recursive,
+                    ..
+                } => {
+                    if depth > 0 {
+                        self.write_connector(writer, &self.charset.indent)?;
+                    }
+                    self.write_entry(writer, &entry, depth == 0, tint)?;
+                    if is_recursive {
+                        write!(writer, " ")?;
+                        self.color_choice()
+                            .write_to(writer, "[recursive]", RECURSIVE_COLOR)?;
+                    }
+                    writeln!(writer)?;
+                    self.lines.set(self.lines.get() + 1);
+                    if depth == 0 {
+                        writer.flush()?;
+                    } else {
+                        self.count(&entry);
+                    }
+                }
+                Visit::Deleted {
+                    path,
+                    is_directory,
+                    tint,
+                } => {
+                    self.write_connector(writer, &self.charset.indent)?;
+                    self.write_deleted(writer, &path, is_directory, tint)?;
+                }
+                Visit::Hidden { count, .. } => {
+                    self.lines.set(self.lines.get() + 1);
+                    let text = self.lang.message("marker-omitted", &[("count", &count)]);
+                    self.write_level_marker(writer, text)?;
+                }
+            }
+        }
         Ok(())
     }
 
-    /// Writes a colorized untracked (worktree) git status.
-    fn write_status<S, W, P2>(&self, writer: &mut W, git: &Git, path: P2) -> io::Result<()>
+    /// Writes a marker for entries that weren't written, when entries aren't written
+    /// on branches, like when the tree is written level by level.
+    fn write_level_marker<W>(&self, writer: &mut W, text: String) -> io::Result<()>
     where
-        S: StatusGetter + ColoredStatus,
         W: Write,
+    {
+        const TEXT_COLOR: Option<Color> = Some(Color::Ansi(AnsiColors::Black));
+
+        self.write_connector(writer, &self.charset.indent)?;
+        self.color_choice().write_to(writer, text, TEXT_COLOR)?;
+        writeln!(writer)
+    }
+
+    /// Starts reading the directories among entries at a certain depth, so that
+    /// they're ready when they're written.
+    fn prefetch(&self, entries: &[Entry<PathBuf>], depth: usize) {
+        if self.is_max_level(depth) {
+            return;
+        }
+        let prefetcher = self.prefetcher.get_or_init(Prefetcher::new);
+        entries
+            .iter()
+            .filter(|entry| entry.attributes().is_directory())
+            .for_each(|entry| prefetcher.request(entry.path()));
+    }
+
+    /// Gets how many entries to write in a directory, or `None` to write all of them.
+    fn file_limit_for<P2>(&self, entry: &Entry<P2>) -> Option<usize>
+    where
         P2: AsRef<Path>,
     {
-        const NO_STATUS: &str = " ";
+        let status = || self.status(entry.path());
+        self.config.filelimit_for(entry, status, self.file_limit)
+    }
 
-        let status = git.status::<S, _>(path).ok().flatten();
-        let color = status.and_then(|status| S::get_color(&self.colors, status));
-        let status = status.map(|status| status.as_str()).unwrap_or(NO_STATUS);
-        self.color_choice().write_to(writer, status, color, None)
+    /// Gets a handle that identifies the directory an entry would be traversed
+    /// into, by device and inode on Unix and by file index on Windows.
+    ///
+    /// Returns `None` if the entry isn't traversed or can't be opened.
+    fn directory_handle<P2>(&self, entry: &Entry<P2>, depth: usize) -> Option<Handle>
+    where
+        P2: AsRef<Path>,
+    {
+        let is_dir = entry.attributes().is_directory() || depth == 0 || self.follows(entry);
+        is_dir
+            .then(|| Handle::from_path(entry.path()).ok())
+            .flatten()
     }
 
-    /// Strips the root path prefix, which is necessary for git tools.
-    fn clean_path_for_git2<P2>(&self, path: P2) -> Option<PathBuf>
+    /// Checks if an entry is a symlink to a directory that should be traversed.
+    #[inline]
+    fn follows<P2>(&self, entry: &Entry<P2>) -> bool
     where
         P2: AsRef<Path>,
     {
-        let git_root = self.git.and_then(|git| git.root_dir())?;
-        clean_path_for_git2(git_root, path)
+        self.follow_symlinks
+            && entry
+                .attributes()
+                .symlink()
+                .is_some_and(|symlink| symlink.is_directory())
     }
 
-    /// Gets the color choice to use.
-    fn color_choice(&self) -> ColorChoice {
-        self.color_choice.unwrap_or(self.config.color_choice())
+    /// Gets the ancestors of an entry's children, adding the entry if it's a
+    /// directory that would be traversed.
+    ///
+    /// Returns `None` if the entry is a directory that's already one of its
+    /// ancestors, reached again through a symlink or a bind mount, since traversing
+    /// it would repeat forever. Every search of the tree checks this before it
+    /// searches a directory's children.
+    fn enter<P2>(&self, entry: &Entry<P2>, depth: usize, ancestors: &Ancestors) -> Option<Ancestors>
+    where
+        P2: AsRef<Path>,
+    {
+        match self.directory_handle(entry, depth) {
+            Some(handle) if ancestors.contains(&handle) => None,
+            Some(handle) => Some(ancestors.with(handle)),
+            None => Some(ancestors.clone()),
+        }
     }
-}
 
-/// Private trait to generalize writing statuses.
-trait ColoredStatus {
-    /// Gets the color for the status.
-    fn get_color(config: &config::Colors, status: Status) -> Option<Color>;
-}
+    /// Gets the children of an entry like [`Self::children`], with their ancestors.
+    /// See [`Self::enter`].
+    fn descend<P2>(
+        &self,
+        entry: &Entry<P2>,
+        depth: usize,
+        ancestors: &Ancestors,
+    ) -> Option<(Vec<Entry<PathBuf>>, Ancestors)>
+    where
+        P2: AsRef<Path>,
+    {
+        let ancestors = self.enter(entry, depth, ancestors)?;
+        let children = self.children(entry, depth, &ancestors)?;
+        Some((children, ancestors))
+    }
 
-impl ColoredStatus for status::Untracked {
-    #[inline]
-    fn get_color(config: &config::Colors, status: Status) -> Option<Color> {
-        config.for_untracked_git_status(status)
+    /// Adds a written entry to the stats.
+    fn count<P2>(&self, entry: &Entry<P2>)
+    where
+        P2: AsRef<Path>,
+    {
+        let mut stats = self.stats.get();
+        let is_directory = entry.attributes().is_directory() || self.follows(entry);
+        if is_directory {
+            stats.directories += 1;
+        } else {
+            stats.files += 1;
+        }
+        self.stats.set(stats);
+        if let Ok(path) = entry.path().strip_prefix(self.root.as_ref()) {
+            self.histogram.borrow_mut().add(path, is_directory);
+        }
     }
-}
 
-impl ColoredStatus for status::Tracked {
+    /// Gets the numbers of directories and files in the last written tree, and its
+    /// total size with [`Builder::disk_usage`].
+    pub fn stats(&self) -> Stats {
+        let total_size = self
+            .totals
+            .get()
+            .filter(|_| self.disk_usage)
+            .and_then(|totals| totals.get(self.root.as_ref()));
+        Stats {
+            total_size,
+            ..self.stats.get()
+        }
+    }
+
+    /// Sets the total size of a directory from one walk of the whole tree, which is
+    /// computed the first time, when directories' total sizes are shown.
+    fn fill_total_size<P2>(&self, entry: &Entry<P2>)
+    where
+        P2: AsRef<Path>,
+    {
+        if self.size_mode != SizeMode::Recursive {
+            return;
+        }
+        let Some(directory) = entry.attributes().directory() else {
+            return;
+        };
+        if let Some(size) = self.disk_usage_of(entry.path()) {
+            directory.set_total_size(size);
+        }
+    }
+
+    /// Gets the total size of a directory from one walk of the whole tree, which is
+    /// done the first time that a total size is needed.
+    fn disk_usage_of(&self, path: &Path) -> Option<u64> {
+        self.totals
+            .get_or_init(|| DiskUsage::new(self.root.as_ref()))
+            .get(path)
+    }
+
+    /// Gets the numbers of entries at each depth and under each top-level directory
+    /// in the last written tree.
     #[inline]
-    fn get_color(config: &config::Colors, status: Status) -> Option<Color> {
-        config.for_tracked_git_status(status)
+    pub fn histogram(&self) -> Histogram {
+        self.histogram.borrow().clone()
     }
-}
 
-/// Helper for cleaning up a file path so that it can be used with the opened
-/// [`git2::Repository`].
-fn clean_path_for_git2<P1, P2>(git_root: P1, path: P2) -> Option<PathBuf>
-where
-    P1: AsRef<Path>,
-    P2: AsRef<Path>,
-{
-    let git_root = git_root.as_ref();
+    /// Gets the summary of the last written tree, like `2 directories, 3 files`, as
+    /// customized by the configuration. Returns `None` if it should be hidden.
+    pub fn summary(&self) -> Option<String> {
+        self.config
+            .summary(self.stats().summary(self.lang))
+            .map(|summary| self.plugins.summary(summary))
+    }
 
-    // HACK Git root seems to have `/` separators, which breaks path cleanup on
-    //      Windows. This cleans up the git root so it can be used with
-    //      strip_prefix.
-    #[cfg(windows)]
-    let git_root = path::absolute(git_root)
-        .expect("Git root should be non-empty and should be able to get the current directory");
-
-    let path = path.as_ref();
-    let path = path::absolute(path)
-        .expect("Path should be non-empty and should be able to get the current directory");
-    let path = path
-        .strip_prefix(git_root)
-        .expect("Path should have the git root as a prefix");
-    Some(path.to_path_buf())
-}
+    /// Gets the sorted entries of a directory that should be displayed.
+    ///
+    /// `ancestors` are the directories that contain the entries, from
+    /// [`Self::enter`]. Returns `None` if the entry isn't a directory that should be
+    /// traversed.
+    fn children<P2>(
+        &self,
+        entry: &Entry<P2>,
+        depth: usize,
+        ancestors: &Ancestors,
+    ) -> Option<Vec<Entry<PathBuf>>>
+    where
+        P2: AsRef<Path>,
+    {
+        if depth > 0 && self.git_dir == GitDir::Collapsed && Self::is_git_dir(entry) {
+            return None;
+        }
+        // NOTE The statuses of the children come from the repository they're in.
+        if depth > 0 {
+            self.open_nested_repository(entry.path());
+        }
+        // NOTE If the config exists and it successfully detects if a file should
+        //      be skipped, use that value. Otherwise, use default behavior.
+        self.children_filtered(entry, depth, |entry| {
+            self.should_skip_entry(entry)
+                || !self.matches_pattern(entry)
+                || self.is_pruned(entry, depth + 1, ancestors)
+        })
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use rstest::rstest;
-    use std::fs::{self, File};
-    use tempfile::TempDir;
+    /// Checks if an entry matches the name pattern. Directories always match, so that
+    /// matching files inside them can be found.
+    fn matches_pattern<P2>(&self, entry: &Entry<P2>) -> bool
+    where
+        P2: AsRef<Path>,
+    {
+        self.pattern.as_ref().is_none_or(|pattern| {
+            entry.attributes().is_directory() || pattern.matches(entry.path())
+        })
+    }
 
-    #[rstest]
-    #[cfg_attr(unix, case("repo", "repo/src/lib.rs", Some("src/lib.rs")))]
-    #[cfg_attr(windows, case("Dir/Repo", r"Dir\Repo\src\lib.rs", Some(r"src\lib.rs")))]
-    fn test_clean_path_for_git2(
-        #[case] git_root: &str,
-        #[case] path: &str,
-        #[case] expected: Option<&str>,
-    ) {
-        // NOTE Create the "repository" and its files in a temporary directory.
-        let container = TempDir::with_prefix("fancy-tree-").unwrap();
-        let git_root = container.path().join(git_root);
-        let path = container.path().join(path);
-        fs::create_dir_all(&git_root).unwrap();
-        fs::create_dir_all(path.parent().unwrap()).unwrap();
-        File::create_new(&path).unwrap();
+    /// Checks if an entry is a directory whose entries are written below it, instead
+    /// of being cut off by the maximum level or collapsed.
+    fn is_expanded<P2>(&self, entry: &Entry<P2>) -> bool
+    where
+        P2: AsRef<Path>,
+    {
+        let depth = entry
+            .path()
+            .strip_prefix(self.root.as_ref())
+            .map_or(0, |path| path.components().count());
+        entry.attributes().is_directory()
+            && !self.is_max_level(depth)
+            && !(depth > 0 && self.git_dir == GitDir::Collapsed && Self::is_git_dir(entry))
+    }
 
-        let expected = expected.map(PathBuf::from);
+    /// Checks if entries at this depth are at the maximum level, so that their
+    /// children aren't written. The root is at depth 0.
+    #[inline]
+    fn is_max_level(&self, depth: usize) -> bool {
+        self.max_level.is_some_and(|max| depth >= max)
+    }
 
-        assert_eq!(expected, clean_path_for_git2(git_root, path));
+    /// Checks if an entry is a directory that should be hidden because none of its
+    /// entries would be displayed.
+    ///
+    /// Directories at the maximum level aren't pruned, since their entries are hidden
+    /// by the level instead of by being empty. Directories that are their own
+    /// ancestors are pruned, since their entries are already displayed.
+    fn is_pruned<P2>(&self, entry: &Entry<P2>, depth: usize, ancestors: &Ancestors) -> bool
+    where
+        P2: AsRef<Path>,
+    {
+        if !(self.prune
+            && entry.attributes().is_directory()
+            && !self.is_max_level(depth)
+            && !(self.git_dir == GitDir::Collapsed && Self::is_git_dir(entry)))
+        {
+            return false;
+        }
+        if let Some(&is_pruned) = self.pruned.borrow().get(entry.path()) {
+            return is_pruned;
+        }
+        // NOTE Getting the children checks whether each of them is pruned first, so
+        //      the whole directory is searched bottom-up. The results are kept, since
+        //      the children are checked again when the directory is written.
+        let is_pruned = self
+            .descend(entry, depth, ancestors)
+            .is_none_or(|(children, _)| children.is_empty());
+        self.pruned
+            .borrow_mut()
+            .insert(entry.path().to_path_buf(), is_pruned);
+        is_pruned
+    }
+
+    /// Gets the sorted entries of a directory that aren't skipped by `skip`.
+    ///
+    /// Returns `None` if the entry isn't a directory that should be traversed.
+    fn children_filtered<P2, F>(
+        &self,
+        entry: &Entry<P2>,
+        depth: usize,
+        skip: F,
+    ) -> Option<Vec<Entry<PathBuf>>>
+    where
+        P2: AsRef<Path>,
+        F: Fn(&Entry<PathBuf>) -> bool,
+    {
+        let path = entry.path();
+        // NOTE Symlinks are not followed unless requested, except for the top level,
+        //      where the user explicitly specified the path.
+        let is_dir = entry.attributes().is_directory()
+            || (depth == 0 && path.is_dir())
+            || self.follows(entry);
+        if !is_dir || self.is_max_level(depth) {
+            return None;
+        }
+
+        // NOTE We'll just skip file read errors to continue printing the rest of the
+        //      tree.
+        let entries = self
+            .prefetcher
+            .get()
+            .and_then(|prefetcher| prefetcher.take(path))
+            .unwrap_or_else(|| prefetch::read(path))?;
+        // NOTE The root's path is what the user chose, so it isn't resolved.
+        if depth > 0
+            && self.follows(entry)
+            && let Ok(target) = fs::canonicalize(path)
+        {
+            self.followed
+                .borrow_mut()
+                .insert(path.to_path_buf(), target);
+        }
+        if let Some(directory) = entry.attributes().directory() {
+            directory.set_child_count(entries.len());
+        }
+        entries.iter().for_each(|entry| self.fill_total_size(entry));
+        let mut entries = entries
+            .into_iter()
+            .filter(|entry| !skip(entry))
+            .collect::<Vec<_>>();
+        sorting::sort_by(&mut entries, |left, right| {
+            self.cmp(left.path(), right.path())
+        });
+        Some(entries)
+    }
+
+    /// Finds the entries for the requested reports.
+    ///
+    /// Unlike the written tree, ignored entries are searched so that they can be
+    /// reported as orphans. Orphans are only found when the tree is in a repository.
+    pub fn report(&self, reports: &[report::Report]) -> io::Result<report::Findings> {
+        let mut findings = report::Findings::new(reports);
+        let entry = Entry::new(&self.root)?;
+        self.find_reported(entry, 0, &Ancestors::default(), &mut findings);
+        Ok(findings)
+    }
+
+    /// Adds the reported entries in an entry to the findings.
+    fn find_reported<P2>(
+        &self,
+        entry: Entry<P2>,
+        depth: usize,
+        ancestors: &Ancestors,
+        findings: &mut report::Findings,
+    ) where
+        P2: AsRef<Path>,
+    {
+        let path = entry.path();
+        // NOTE The top level is never an orphan, since it's what the user is looking
+        //      at.
+        if let Some(orphan) = (depth > 0).then(|| self.orphan(path)).flatten() {
+            // NOTE Everything inside an orphaned directory is also orphaned, so it
+            //      isn't searched.
+            return findings.add_orphan(path, orphan);
+        }
+        if entry
+            .attributes()
+            .directory()
+            .is_some_and(|attributes| attributes.is_empty())
+        {
+            findings.add_empty(path);
+        }
+        let Some(ancestors) = self.enter(&entry, depth, ancestors) else {
+            return;
+        };
+        // NOTE The skip rule is told that nothing is ignored, so that ignored entries
+        //      are still found.
+        let children = self.children_filtered(&entry, depth, |entry| {
+            self.config.should_skip(
+                entry,
+                self.show_hidden,
+                || self.status(entry.path()),
+                || false,
+            )
+        });
+        for child in children.into_iter().flatten() {
+            self.find_reported(child, depth + 1, &ancestors, findings);
+        }
+    }
+
+    /// Checks if a path is untracked or ignored by version control.
+    fn orphan(&self, path: &Path) -> Option<report::Orphan> {
+        self.with_vcs(path, |vcs, path| {
+            if vcs.is_ignored(path).unwrap_or(false) {
+                return Some(report::Orphan::Ignored);
+            }
+            let status = vcs.status(path).ok().and_then(status::Untracked::get);
+            (status == Some(Status::Added)).then_some(report::Orphan::Untracked)
+        })
+        .flatten()
+    }
+
+    /// Finds the files that are larger than the warning size, in the order they
+    /// appear in the tree.
+    ///
+    /// Unlike the written tree, this isn't limited by the maximum number of lines.
+    pub fn oversized_files(&self) -> io::Result<Vec<(PathBuf, Size)>> {
+        let mut files = Vec::new();
+        if self.warn_size.is_some() {
+            let entry = Entry::new(&self.root)?;
+            self.find_oversized(entry, 0, &Ancestors::default(), &mut files);
+        }
+        Ok(files)
+    }
+
+    /// Gets the size above which files are highlighted.
+    #[inline]
+    pub fn warn_size(&self) -> Option<Size> {
+        self.warn_size
+    }
+
+    /// Adds the oversized files in an entry to the list.
+    fn find_oversized<P2>(
+        &self,
+        entry: Entry<P2>,
+        depth: usize,
+        ancestors: &Ancestors,
+        files: &mut Vec<(PathBuf, Size)>,
+    ) where
+        P2: AsRef<Path>,
+    {
+        if let Some(size) = self.oversize(&entry) {
+            files.push((entry.path().to_path_buf(), size));
+        }
+        let Some((children, ancestors)) = self.descend(&entry, depth, ancestors) else {
+            return;
+        };
+        for child in children {
+            self.find_oversized(child, depth + 1, &ancestors, files);
+        }
+    }
+
+    /// Finds untracked entries that look like build artifacts, and groups them into
+    /// suggested `.gitignore` entries.
+    ///
+    /// This is always empty when the tree isn't in a repository.
+    pub fn suggest_ignores(&self) -> io::Result<Vec<suggest::Suggestion>> {
+        let mut suggestions = Vec::new();
+        if self.vcs.is_some() {
+            let entry = Entry::new(&self.root)?;
+            self.find_artifacts(entry, 0, &Ancestors::default(), &mut suggestions);
+        }
+        Ok(suggestions)
+    }
+
+    /// Adds the untracked artifacts in an entry to the suggestions.
+    fn find_artifacts<P2>(
+        &self,
+        entry: Entry<P2>,
+        depth: usize,
+        ancestors: &Ancestors,
+        suggestions: &mut Vec<suggest::Suggestion>,
+    ) where
+        P2: AsRef<Path>,
+    {
+        let path = entry.path();
+        let is_dir = entry.attributes().is_directory();
+        // NOTE The top level is never suggested, since it's what the user is looking
+        //      at.
+        let artifact = (depth > 0)
+            .then(|| suggest::Artifact::find(path, is_dir))
+            .flatten()
+            .filter(|_| self.is_untracked(path));
+        if let Some(artifact) = artifact {
+            let size = Size(size::total_size(path));
+            suggest::add(suggestions, artifact, size);
+            return;
+        }
+        let Some((children, ancestors)) = self.descend(&entry, depth, ancestors) else {
+            return;
+        };
+        for child in children {
+            self.find_artifacts(child, depth + 1, &ancestors, suggestions);
+        }
+    }
+
+    /// Checks if a path is untracked and not ignored.
+    fn is_untracked(&self, path: &Path) -> bool {
+        self.with_vcs(path, |vcs, path| {
+            // NOTE Untracked directories are reported as a whole, so they have a
+            //      status just like untracked files.
+            let status = vcs.status(path).ok().and_then(status::Untracked::get);
+            status == Some(Status::Added) && !vcs.is_ignored(path).unwrap_or(false)
+        })
+        .unwrap_or(false)
+    }
+
+    /// Gets the size of an entry if it's a file that's larger than the warning size.
+    fn oversize<P2>(&self, entry: &Entry<P2>) -> Option<Size>
+    where
+        P2: AsRef<Path>,
+    {
+        let warn_size = self.warn_size?;
+        let size = Size(entry.attributes().file()?.size());
+        (size > warn_size).then_some(size)
+    }
+
+    /// Checks if the next line can't be written, since the maximum number of lines
+    /// would be written without room for the `reserved` lines after it, like the
+    /// markers for the entries that weren't written.
+    fn is_line_limit_reached(&self, reserved: usize) -> bool {
+        self.max_lines
+            .is_some_and(|max_lines| self.lines.get() + reserved >= max_lines)
+    }
+
+    /// Writes a marker for a directory whose remaining entries weren't written.
+    fn write_marker<W>(&self, writer: &mut W, depth: usize, text: String) -> io::Result<()>
+    where
+        W: Write,
+    {
+        const TEXT_COLOR: Option<Color> = Some(Color::Ansi(AnsiColors::Black));
+
+        self.write_indentation(writer, depth)?;
+        self.write_connector(writer, &self.charset.last_depth)?;
+        self.color_choice().write_to(writer, text, TEXT_COLOR)?;
+        writeln!(writer)
+    }
+
+    /// Writes an entry, with its tint layered under the styles of its icon and name.
+    fn write_entry<W, P2>(
+        &self,
+        writer: &mut W,
+        entry: &Entry<P2>,
+        is_top: bool,
+        tint: Option<Style>,
+    ) -> io::Result<()>
+    where
+        W: Write,
+        P2: AsRef<Path>,
+    {
+        let path = entry.path();
+        self.write_permissions(writer, entry)?;
+        self.write_churn(writer, path)?;
+        self.write_size(writer, entry)?;
+        self.write_mtime(writer, entry)?;
+        self.write_statuses(writer, path)?;
+
+        let icon = self.get_icon(entry);
+        // NOTE Without icons, the padding is left out too, so names line up with the
+        //      branches.
+        if !icon.is_empty() {
+            self.write_colorized_for_entry(entry, writer, icon, tint)?;
+            // NOTE Padding for the icons
+            write!(writer, " ")?;
+        }
+
+        // HACK is_entry_ignored tries to strip the prefix, which we never want to do at
+        //      the top when the path is *only* the prefix. In fact, we don't want to
+        //      check ignore status here at all since the current implementation breaks
+        //      for paths that contain the directory `.`, it seems. Also, the top
+        //      should always be a directory, and the current implementation only seems
+        //      to work for files.
+        let is_ignored = !is_top && self.is_entry_ignored(entry);
+
+        let name = if is_top {
+            path.as_os_str()
+        } else if self.format == Format::Flat {
+            path.strip_prefix(&self.root).unwrap_or(path).as_os_str()
+        } else {
+            // NOTE The only time the path shouldn't have a file name is at the top
+            //      level, which could be a path like "." or "..". At the top level
+            //      call, `full_name` should always receive `true`.
+            path.file_name()
+                .expect("A directory entry should always have a file name")
+        };
+
+        // NOTE The breadcrumb replaces the root's path, so that each of its directories
+        //      can be clicked.
+        if is_top && self.breadcrumb {
+            self.write_breadcrumb(writer, path, tint)?;
+        } else {
+            let link = self.hyperlink(path);
+            if let Some(ref link) = link {
+                write!(writer, "{}", hyperlink::start(link))?;
+            }
+            if let Some(size) = self.oversize(entry) {
+                const TEXT_STYLE: Style = Style::new().fg(Color::Ansi(AnsiColors::Red));
+                // NOTE The size is included so that the warning is visible without colors.
+                let text = format!("{} ({size})", bidi::isolate(&name.to_string_lossy()));
+                let style = Style::layer(Some(TEXT_STYLE), tint);
+                self.color_choice().write_to(writer, text, style)?;
+            } else if is_ignored {
+                let text = bidi::isolate(&name.to_string_lossy()).into_owned();
+                let style = Style::layer(self.colors.for_ignored(), tint);
+                self.color_choice().write_to(writer, text, style)?;
+            } else if let Some(color) = self.age_color(path) {
+                let text = bidi::isolate(&name.to_string_lossy()).into_owned();
+                let style = Style::layer(Some(Style::from(color)), tint);
+                self.color_choice().write_to(writer, text, style)?;
+            } else if tint.is_some() {
+                let text = bidi::isolate(&name.to_string_lossy()).into_owned();
+                self.color_choice().write_to(writer, text, tint)?;
+            } else {
+                Self::write_path(writer, name)?;
+            }
+            if link.is_some() {
+                write!(writer, "{}", hyperlink::END)?;
+            }
+        }
+        self.write_symlink_target(writer, entry)?;
+        if !is_top {
+            self.write_nested_repository(writer, entry)?;
+            self.write_git_dir_head(writer, entry)?;
+            self.write_untracked_files(writer, entry)?;
+        }
+        self.write_recent(writer, entry)?;
+        self.write_annotation(writer, entry)
+    }
+
+    /// Gets the `file://` URL that an entry's name links to, if names are linked.
+    fn hyperlink(&self, path: &Path) -> Option<String> {
+        if !self.hyperlinks {
+            return None;
+        }
+        self.link_to(path)
+    }
+
+    /// Gets the `file://` URL of a path, if links can be written.
+    ///
+    /// Links are escape codes, so they're only written with colors, and only in
+    /// formats that can have escape codes.
+    fn link_to(&self, path: &Path) -> Option<String> {
+        if !self.format.is_ansi() || self.color_choice().is_off() {
+            return None;
+        }
+        normalize_absolute(path).map(|path| hyperlink::file_url(&path))
+    }
+
+    /// Writes the root's path as a breadcrumb, like `a › b › c`, where each directory
+    /// links to itself.
+    fn write_breadcrumb<W>(
+        &self,
+        writer: &mut W,
+        path: &Path,
+        tint: Option<Style>,
+    ) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let separator = if self.charset == Charset::ASCII {
+            " > "
+        } else {
+            " › "
+        };
+        let mut ancestor = PathBuf::new();
+        let mut label = OsString::new();
+        let mut is_first = true;
+        for component in path.components() {
+            ancestor.push(component);
+            label.push(component);
+            // NOTE The root, like `/` or `C:\`, is part of the first directory's crumb.
+            if matches!(component, Component::Prefix(_) | Component::RootDir) {
+                continue;
+            }
+            if !is_first {
+                write!(writer, "{separator}")?;
+            }
+            is_first = false;
+            self.write_crumb(writer, &label, &ancestor, tint)?;
+            label.clear();
+        }
+        // NOTE A path that's only a root, like `/`, hasn't been written yet.
+        if !label.is_empty() {
+            self.write_crumb(writer, &label, &ancestor, tint)?;
+        }
+        Ok(())
+    }
+
+    /// Writes one directory of a breadcrumb, linked to its path.
+    fn write_crumb<W>(
+        &self,
+        writer: &mut W,
+        label: &OsStr,
+        path: &Path,
+        tint: Option<Style>,
+    ) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let link = self.link_to(path);
+        if let Some(ref link) = link {
+            write!(writer, "{}", hyperlink::start(link))?;
+        }
+        let text = bidi::isolate(&label.to_string_lossy()).into_owned();
+        self.color_choice().write_to(writer, text, tint)?;
+        if link.is_some() {
+            write!(writer, "{}", hyperlink::END)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a marker if the entry is the root of a git repository inside the
+    /// tree's repository, like `[submodule]`.
+    fn write_nested_repository<W, P2>(&self, writer: &mut W, entry: &Entry<P2>) -> io::Result<()>
+    where
+        W: Write,
+        P2: AsRef<Path>,
+    {
+        const TEXT_COLOR: Option<Color> = Some(Color::Ansi(AnsiColors::Cyan));
+
+        if self.vcs.is_none() || !entry.attributes().is_directory() {
+            return Ok(());
+        }
+        let Some(kind) = Self::nested_repository_kind(entry.path()) else {
+            return Ok(());
+        };
+        write!(writer, " ")?;
+        self.color_choice()
+            .write_to(writer, format!("[{kind}]"), TEXT_COLOR)
+    }
+
+    /// Writes the number of new files in a directory that's untracked as a whole,
+    /// like `+3`, since its files don't have their own statuses. This is written even
+    /// if the directory isn't expanded.
+    fn write_untracked_files<W, P2>(&self, writer: &mut W, entry: &Entry<P2>) -> io::Result<()>
+    where
+        W: Write,
+        P2: AsRef<Path>,
+    {
+        // NOTE Two passes don't wait for statuses, and a nested repository's files
+        //      aren't new files of this repository.
+        let path = entry.path();
+        if self.two_pass
+            || !entry.attributes().is_directory()
+            || Self::nested_repository_kind(path).is_some()
+        {
+            return Ok(());
+        }
+        let count = self
+            .with_vcs(path, |vcs, path| vcs.untracked_files(path))
+            .flatten()
+            .filter(|&count| count > 0);
+        let Some(count) = count else {
+            return Ok(());
+        };
+        write!(writer, " ")?;
+        let color = self.colors.for_untracked_git_status(Status::Added);
+        self.color_choice()
+            .write_to(writer, format!("+{count}"), color)
+    }
+
+    /// Writes the checked out branch after a collapsed `.git` directory, like
+    /// `[main]`, or the commit if `HEAD` is detached.
+    fn write_git_dir_head<W, P2>(&self, writer: &mut W, entry: &Entry<P2>) -> io::Result<()>
+    where
+        W: Write,
+        P2: AsRef<Path>,
+    {
+        const TEXT_COLOR: Option<Color> = Some(Color::Ansi(AnsiColors::Cyan));
+        /// The length of an abbreviated commit ID.
+        const SHORT_ID_LEN: usize = 7;
+
+        if self.git_dir != GitDir::Collapsed || !Self::is_git_dir(entry) {
+            return Ok(());
+        }
+        let head = self
+            .with_vcs(entry.path(), |vcs, _| vcs.head_info().ok())
+            .flatten();
+        let Some(head) = head else {
+            return Ok(());
+        };
+        let name = match (head.branch, head.id) {
+            (Some(branch), _) => branch,
+            (None, Some(id)) => id.chars().take(SHORT_ID_LEN).collect(),
+            (None, None) => return Ok(()),
+        };
+        write!(writer, " ")?;
+        self.color_choice()
+            .write_to(writer, format!("[{name}]"), TEXT_COLOR)
+    }
+
+    /// Checks if an entry is a `.git` directory, which is written according to the
+    /// `.git` directory mode instead of like other hidden directories.
+    fn is_git_dir<P2>(entry: &Entry<P2>) -> bool
+    where
+        P2: AsRef<Path>,
+    {
+        entry.attributes().is_directory()
+            && entry.path().file_name().is_some_and(|name| name == ".git")
+    }
+
+    /// Runs the annotation commands for the paths of entries before they're
+    /// written, so that the commands can run at the same time.
+    fn annotate<'a, I>(&self, paths: I)
+    where
+        I: IntoIterator<Item = &'a Path>,
+    {
+        let Some(ref annotations) = self.annotations else {
+            return;
+        };
+        let paths = paths.into_iter().collect::<Vec<_>>();
+        let annotated = paths
+            .iter()
+            .zip(annotations.run(&paths))
+            .filter_map(|(path, annotation)| Some((path.to_path_buf(), annotation?)));
+        self.annotated.borrow_mut().extend(annotated);
+    }
+
+    /// Writes the annotation for an entry, if a command annotated it.
+    fn write_annotation<W, P2>(&self, writer: &mut W, entry: &Entry<P2>) -> io::Result<()>
+    where
+        W: Write,
+        P2: AsRef<Path>,
+    {
+        const TEXT_COLOR: Option<Color> = Some(Color::Ansi(AnsiColors::Black));
+
+        let Some(annotation) = self.annotated.borrow_mut().remove(entry.path()) else {
+            return Ok(());
+        };
+        write!(writer, " ")?;
+        self.color_choice().write_to(writer, annotation, TEXT_COLOR)
+    }
+
+    /// Writes a marker if the entry is a file that was modified recently.
+    fn write_recent<W, P2>(&self, writer: &mut W, entry: &Entry<P2>) -> io::Result<()>
+    where
+        W: Write,
+        P2: AsRef<Path>,
+    {
+        const MARKER_COLOR: Option<Color> = Some(Color::Ansi(AnsiColors::BrightGreen));
+
+        let Some(since) = self.recent_since else {
+            return Ok(());
+        };
+        let attributes = entry.attributes();
+        let is_recent = attributes.file().is_some()
+            && attributes
+                .modified()
+                .is_some_and(|modified| modified >= since);
+        if !is_recent {
+            return Ok(());
+        }
+        write!(writer, " ")?;
+        self.color_choice()
+            .write_to(writer, "[recent]", MARKER_COLOR)
+    }
+
+    /// Writes ` -> target` if the entry is a symlink. Broken targets are red.
+    fn write_symlink_target<W, P2>(&self, writer: &mut W, entry: &Entry<P2>) -> io::Result<()>
+    where
+        W: Write,
+        P2: AsRef<Path>,
+    {
+        const BROKEN_COLOR: Option<Color> = Some(Color::Ansi(AnsiColors::Red));
+
+        let Some(symlink) = entry.attributes().symlink() else {
+            return Ok(());
+        };
+        let Some(target) = symlink.target() else {
+            return Ok(());
+        };
+        write!(writer, " -> ")?;
+        if symlink.is_broken() {
+            self.color_choice()
+                .write_to(writer, target.display(), BROKEN_COLOR)
+        } else {
+            Self::write_path(writer, target)
+        }
+    }
+
+    /// Gets the color for a path based on the age of its last commit.
+    fn age_color(&self, path: &Path) -> Option<Color> {
+        let last_commits = self.last_commits.as_ref()?;
+        let time = self
+            .clean_path_for_git2(path)
+            .and_then(|path| last_commits.get(path))?;
+        let staleness = last_commits.staleness(time);
+        Some(self.colors.for_age(staleness))
+    }
+
+    /// Writes a path's name, isolating right-to-left names so that they don't
+    /// reorder the rest of the line.
+    fn write_path<W, P2>(writer: &mut W, path: P2) -> io::Result<()>
+    where
+        W: Write,
+        P2: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let isolated = bidi::needs_isolation(&path.to_string_lossy());
+        if isolated {
+            write!(writer, "{}", bidi::FIRST_STRONG_ISOLATE)?;
+        }
+        writer.write_all(path.as_os_str().as_encoded_bytes())?;
+        if isolated {
+            write!(writer, "{}", bidi::POP_DIRECTIONAL_ISOLATE)?;
+        }
+        Ok(())
+    }
+
+    /// Writes indentation.
+    fn write_indentation<W>(&self, writer: &mut W, level: usize) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let last_entries = self.last_entries.borrow();
+        for &is_last in last_entries.iter().take(level) {
+            // NOTE There's nothing below the last entry, so its line ends.
+            let indentation = if is_last {
+                &self.charset.indent
+            } else {
+                &self.charset.breadth
+            };
+            self.write_connector(writer, indentation)?;
+        }
+        Ok(())
+    }
+
+    /// Writes part of the tree's branches.
+    fn write_connector<W>(&self, writer: &mut W, text: &str) -> io::Result<()>
+    where
+        W: Write,
+    {
+        self.color_choice()
+            .write_to(writer, text, self.colors.for_connectors())
+    }
+
+    /// Checks if an entry should be skipped.
+    ///
+    /// If the config exists, the config has a `skip` function, *and* that function
+    /// successfully returns a boolean value, then that value will be used. Otherwise,
+    /// it will just skip all hidden files, unless hidden files are shown.
+    fn should_skip_entry<P2>(&self, entry: &Entry<P2>) -> bool
+    where
+        P2: AsRef<Path>,
+    {
+        // NOTE `.git` directories are hidden by the `.git` directory mode instead of
+        //      like other hidden directories.
+        if Self::is_git_dir(entry) {
+            return self.git_dir == GitDir::Hidden;
+        }
+        let status = || self.status(entry.path());
+        let skip = self
+            .config
+            .should_skip(entry, self.show_hidden, status, || {
+                self.is_entry_ignored(entry)
+            });
+        self.plugins.should_skip(entry, status, skip)
+    }
+
+    /// Checks if an entry is ignored by version control or by ignore files.
+    ///
+    /// This is always `false` if ignore rules are disabled.
+    fn is_entry_ignored<P2>(&self, entry: &Entry<P2>) -> bool
+    where
+        P2: AsRef<Path>,
+    {
+        if !self.respect_ignores {
+            return false;
+        }
+        let path = entry.path();
+        let is_dir = entry.attributes().is_directory();
+        self.ignore
+            .as_ref()
+            .is_some_and(|ignore| ignore.is_ignored(path, is_dir))
+            || self.is_path_ignored_by_vcs(path)
+    }
+
+    /// Checks if a path is ignored by version control.
+    fn is_path_ignored_by_vcs<P2>(&self, path: P2) -> bool
+    where
+        P2: AsRef<Path>,
+    {
+        // HACK This function doesn't expect a `./` prefix. It seems to return `true`
+        //      when it's present???
+        // NOTE Version control ignores its own directory, which is only written when
+        //      the `.git` directory mode expands it.
+        self.with_vcs(path.as_ref(), |vcs, path| {
+            let is_git_dir = path
+                .components()
+                .any(|component| component.as_os_str() == ".git");
+            (!is_git_dir).then(|| vcs.is_ignored(path).ok()).flatten()
+        })
+        .flatten()
+        .unwrap_or(false)
+    }
+
+    /// Writes the text in the style of the entry's icon, layered over its tint.
+    fn write_colorized_for_entry<W, D, P2>(
+        &self,
+        entry: &Entry<P2>,
+        writer: &mut W,
+        display: D,
+        tint: Option<Style>,
+    ) -> io::Result<()>
+    where
+        W: Write,
+        D: Display + OwoColorize,
+        P2: AsRef<Path>,
+    {
+        let color_choice = self.color_choice();
+
+        // HACK Optimization to avoid calculating colors when they're disabled.
+        if color_choice.is_off() {
+            return write!(writer, "{display}");
+        }
+
+        let style = Style::layer(self.icon_style(entry), tint);
+        color_choice.write_to(writer, display, style)
+    }
+
+    /// Gets an entry's tint, starting from the tint of its directory.
+    fn tint<P2>(&self, entry: &Entry<P2>, inherited: Option<Style>) -> Option<Style>
+    where
+        P2: AsRef<Path>,
+    {
+        // HACK Optimization to avoid calling the configuration when colors are
+        //      disabled.
+        if self.color_choice().is_off() {
+            return None;
+        }
+        let status = || self.status(entry.path());
+        self.colors.for_tint(entry, status, inherited)
+    }
+
+    /// Gets the style of an entry's icon from the color configuration and plugins.
+    #[inline]
+    fn icon_style<P2>(&self, entry: &Entry<P2>) -> Option<Style>
+    where
+        P2: AsRef<Path>,
+    {
+        self.icon_style_with_source(entry).0
+    }
+
+    /// Gets the style of an entry's icon, and what decided it.
+    fn icon_style_with_source<P2>(&self, entry: &Entry<P2>) -> (Option<Style>, rules::Source)
+    where
+        P2: AsRef<Path>,
+    {
+        let status = || self.status(entry.path());
+        let (style, source) = self.colors.for_icon_with_source(entry, status);
+        let plugged = self.plugins.color(entry, status, style);
+        let plugged = self.wasm_plugins.color(entry, status, plugged);
+        (
+            plugged,
+            source.changed_by(&style, &plugged, rules::Source::Plugin),
+        )
+    }
+
+    /// Writes the colorized number of commits that changed the path.
+    fn write_churn<W>(&self, writer: &mut W, path: &Path) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let Some(ref churn) = self.churn else {
+            return Ok(());
+        };
+        let width = churn.width();
+        let count = self
+            .clean_path_for_git2(path)
+            .and_then(|path| churn.get(path));
+        let Some(count) = count else {
+            return write!(writer, "{:width$} ", "");
+        };
+
+        // NOTE The colors go from cold to hot as the count approaches the highest
+        //      count.
+        let heat = churn.heat(count);
+        let color = if heat < 1.0 / 3.0 {
+            AnsiColors::Green
+        } else if heat < 2.0 / 3.0 {
+            AnsiColors::Yellow
+        } else {
+            AnsiColors::Red
+        };
+        let count = format!("{count:>width$}");
+        self.color_choice()
+            .write_to(writer, count, Some(Color::Ansi(color)))?;
+        write!(writer, " ")
+    }
+
+    /// Writes the human-readable size of an entry, or padding if it doesn't have a
+    /// size to display.
+    fn write_size<W, P2>(&self, writer: &mut W, entry: &Entry<P2>) -> io::Result<()>
+    where
+        W: Write,
+        P2: AsRef<Path>,
+    {
+        const WIDTH: usize = Size::WIDTH;
+
+        let attributes = entry.attributes();
+        let size = match self.size_mode {
+            SizeMode::Off => return Ok(()),
+            SizeMode::Files => attributes.file().map(|file| file.size()),
+            SizeMode::Recursive => attributes
+                .file()
+                .map(|file| file.size())
+                .or_else(|| attributes.directory().map(|dir| dir.total_size())),
+        };
+        match size {
+            Some(size) => write!(writer, "{:>WIDTH$} ", Size(size).to_string()),
+            None => write!(writer, "{:WIDTH$} ", ""),
+        }
+    }
+
+    /// Writes the colorized permissions of an entry.
+    fn write_permissions<W, P2>(&self, writer: &mut W, entry: &Entry<P2>) -> io::Result<()>
+    where
+        W: Write,
+        P2: AsRef<Path>,
+    {
+        if !self.permissions {
+            return Ok(());
+        }
+        let permissions = entry.attributes().permissions();
+        let color = self.colors.for_permissions(permissions);
+        self.color_choice().write_to(writer, permissions, color)?;
+        write!(writer, " ")
+    }
+
+    /// Writes the modification time of an entry, or padding if it isn't available.
+    fn write_mtime<W, P2>(&self, writer: &mut W, entry: &Entry<P2>) -> io::Result<()>
+    where
+        W: Write,
+        P2: AsRef<Path>,
+    {
+        const TEXT_COLOR: Option<Color> = Some(Color::Ansi(AnsiColors::Blue));
+
+        let Some(format) = self.mtime_format.as_ref() else {
+            return Ok(());
+        };
+        let width = format.width();
+        let text = entry
+            .attributes()
+            .modified()
+            .map(|modified| format.format(modified))
+            .unwrap_or_default();
+        // NOTE Formatting pads by characters, but names like months can have wide
+        //      characters.
+        let padding = width.saturating_sub(text.width());
+        let text = format!("{text}{:padding$}", "");
+        self.color_choice().write_to(writer, text, TEXT_COLOR)?;
+        write!(writer, " ")
+    }
+
+    /// Writes colorized git statuses.
+    fn write_statuses<W>(&self, writer: &mut W, path: &Path) -> io::Result<()>
+    where
+        W: Write,
+    {
+        if self.vcs.is_none() {
+            return Ok(());
+        }
+        if self.two_pass {
+            self.deferred.borrow_mut().push(path.to_path_buf());
+            return write!(writer, "  ");
+        }
+
+        // HACK cached status keys don't have a ./ prefix and git2 apparently doesn't expect it.
+        let written = self.with_vcs(path, |vcs, path| {
+            self.write_status::<status::Untracked, _>(writer, vcs, path)?;
+            self.write_status::<status::Tracked, _>(writer, vcs, path)
+        });
+        // NOTE Padding keeps the names aligned when the path can't be resolved.
+        written.unwrap_or_else(|| write!(writer, "  "))
+    }
+
+    /// Writes the statuses of the written entries that aren't current, like
+    /// `git status --short`, after a tree written with `two_pass`.
+    fn write_deferred_statuses<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let deferred = self.deferred.take();
+        let changed = deferred.iter().filter(|path| {
+            self.status(path)
+                .is_some_and(|status| status != FileStatus::CURRENT)
+        });
+        for (index, path) in changed.enumerate() {
+            if index == 0 {
+                writeln!(writer)?;
+            }
+            self.with_vcs(path, |vcs, path| {
+                self.write_status::<status::Untracked, _>(writer, vcs, path)?;
+                self.write_status::<status::Tracked, _>(writer, vcs, path)
+            })
+            .transpose()?;
+            write!(writer, " ")?;
+            // NOTE Paths are relative to the tree's repository, like `git status`.
+            match self.clean_path_for_git2(path) {
+                Some(path) => Self::write_path(writer, path)?,
+                None => Self::write_path(writer, path)?,
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a colorized untracked (worktree) git status.
+    fn write_status<S, W>(&self, writer: &mut W, vcs: &dyn Vcs, path: &Path) -> io::Result<()>
+    where
+        S: StatusGetter + ColoredStatus,
+        W: Write,
+    {
+        const NO_STATUS: char = ' ';
+
+        let status = vcs.status(path).ok().and_then(S::get);
+        let color = status.and_then(|status| S::get_color(&self.colors, status));
+        let status = status.map_or(NO_STATUS, |status| self.status_glyphs.get(status));
+        self.color_choice().write_to(writer, status, color)
+    }
+
+    /// Gets the version control status of a path, if it's in a repository.
+    fn status(&self, path: &Path) -> Option<FileStatus> {
+        self.with_vcs(path, |vcs, path| vcs.status(path).ok())
+            .flatten()
+    }
+
+    /// Calls a function with the repository that a path is in, and the path relative
+    /// to that repository's root.
+    ///
+    /// A nested repository's own root is an entry in the repository around it, so
+    /// it gets its status from there. Returns `None` if the path isn't in a
+    /// repository.
+    fn with_vcs<T, F>(&self, path: &Path, f: F) -> Option<T>
+    where
+        F: FnOnce(&dyn Vcs, &Path) -> T,
+    {
+        let resolved = self.resolve_followed(path);
+        let path = resolved.as_deref().unwrap_or(path);
+        let nested = self.nested.borrow();
+        let innermost = nested
+            .iter()
+            .filter_map(|(root, git)| {
+                let path = path.strip_prefix(root).ok()?;
+                (!path.as_os_str().is_empty()).then_some((git, path))
+            })
+            .min_by_key(|(_, path)| path.components().count());
+        if let Some((git, path)) = innermost {
+            return Some(f(git, path));
+        }
+        let vcs = self.vcs?;
+        let path = self.clean_path_for_git2(path)?;
+        Some(f(vcs, &path))
+    }
+
+    /// Resolves a path inside a followed symlink to the path that it links to, so that
+    /// it's looked up in version control like the entry it links to. The symlink
+    /// itself isn't resolved, since it's an entry of its own.
+    ///
+    /// Returns `None` if the path isn't inside a followed symlink.
+    fn resolve_followed(&self, path: &Path) -> Option<PathBuf> {
+        let followed = self.followed.borrow();
+        followed
+            .iter()
+            .filter_map(|(link, target)| {
+                let inner = path.strip_prefix(link).ok()?;
+                (!inner.as_os_str().is_empty()).then(|| (link, target.join(inner)))
+            })
+            .max_by_key(|(link, _)| link.components().count())
+            .map(|(_, path)| path)
+    }
+
+    /// Opens the git repository at a directory, if it's a repository inside the
+    /// tree's repository, like a submodule, so that its entries get their statuses
+    /// from it.
+    fn open_nested_repository(&self, path: &Path) {
+        if self.vcs.is_none() || Self::nested_repository_kind(path).is_none() {
+            return;
+        }
+        let mut nested = self.nested.borrow_mut();
+        if nested.iter().any(|(root, _)| root == path) {
+            return;
+        }
+        // NOTE A `.git` that isn't a valid repository would find the repository
+        //      around it instead.
+        let Ok(Some(git)) = Git::new(path) else {
+            return;
+        };
+        let is_root = git
+            .root_dir()
+            .and_then(|root| clean_path_for_git2(root, path))
+            .is_some_and(|path| path.as_os_str().is_empty());
+        if !is_root {
+            return;
+        }
+        // NOTE Statuses that can't be loaded are computed for each file instead.
+        let _ = git.load_statuses(self.config.git_options());
+        nested.push((path.to_path_buf(), git));
+    }
+
+    /// Gets the kind of repository at a directory, from its `.git`. Submodules
+    /// have a `.git` file that points to the repository in the superproject.
+    fn nested_repository_kind(path: &Path) -> Option<&'static str> {
+        const SUBMODULE: &str = "submodule";
+        const REPOSITORY: &str = "repository";
+
+        let metadata = fs::symlink_metadata(path.join(".git")).ok()?;
+        Some(if metadata.is_file() {
+            SUBMODULE
+        } else {
+            REPOSITORY
+        })
+    }
+
+    /// Strips the root path prefix, which is necessary for git tools.
+    fn clean_path_for_git2<P2>(&self, path: P2) -> Option<PathBuf>
+    where
+        P2: AsRef<Path>,
+    {
+        let git_root = self.vcs.and_then(|vcs| vcs.root_dir())?;
+        clean_path_for_git2(git_root, path)
+    }
+
+    /// Compares two paths for sorting.
+    fn cmp(&self, left: &Path, right: &Path) -> Ordering {
+        match self.sorting {
+            Some(ref sorting) => sorting.cmp(left, right),
+            None => self.config.cmp(left, right),
+        }
+    }
+
+    /// Gets the icon for an entry.
+    #[inline]
+    fn get_icon<P2>(&self, entry: &Entry<P2>) -> String
+    where
+        P2: AsRef<Path>,
+    {
+        self.get_icon_with_source(entry).0
+    }
+
+    /// Gets the icon for an entry, and what decided it.
+    fn get_icon_with_source<P2>(&self, entry: &Entry<P2>) -> (String, rules::Source)
+    where
+        P2: AsRef<Path>,
+    {
+        let status = || self.status(entry.path());
+        let (icon, source) = match self.icon_theme {
+            Some(ref icon_theme) => (
+                String::from(icon_theme.get(entry)),
+                rules::Source::IconTheme,
+            ),
+            None => self
+                .icons
+                .get_icon_with_source(entry, self.is_expanded(entry), status),
+        };
+        let plugged = self.plugins.icon(entry, status, icon.clone());
+        let plugged = self.wasm_plugins.icon(entry, status, plugged);
+        let source = source.changed_by(&icon, &plugged, rules::Source::Plugin);
+        (plugged, source)
+    }
+
+    /// Gets the color choice to use.
+    fn color_choice(&self) -> ColorChoice {
+        self.color_choice
+            .unwrap_or(self.config.color_choice())
+            .resolve()
+    }
+}
+
+/// Private trait to generalize writing statuses.
+trait ColoredStatus {
+    /// Gets the style for the status.
+    fn get_color(config: &config::Colors, status: Status) -> Option<Style>;
+}
+
+impl ColoredStatus for status::Untracked {
+    #[inline]
+    fn get_color(config: &config::Colors, status: Status) -> Option<Style> {
+        config.for_untracked_git_status(status)
+    }
+}
+
+impl ColoredStatus for status::Tracked {
+    #[inline]
+    fn get_color(config: &config::Colors, status: Status) -> Option<Style> {
+        config.for_tracked_git_status(status)
+    }
+}
+
+/// Helper for cleaning up a file path so that it can be used with the opened
+/// [`git2::Repository`].
+///
+/// Returns `None` if the path isn't inside the git root.
+pub(crate) fn clean_path_for_git2<P1, P2>(git_root: P1, path: P2) -> Option<PathBuf>
+where
+    P1: AsRef<Path>,
+    P2: AsRef<Path>,
+{
+    // HACK Git root seems to have `/` separators, which breaks path cleanup on
+    //      Windows. Making it absolute cleans it up so it can be used with
+    //      strip_prefix.
+    let git_root = normalize_absolute(git_root.as_ref())?;
+    let path = normalize_absolute(path.as_ref())?;
+    if let Ok(path) = path.strip_prefix(&git_root) {
+        return Some(path.to_path_buf());
+    }
+
+    // NOTE The path might reach the git root through a symlink, like a symlinked
+    //      home directory. Only the parent is resolved, so that a symlink entry
+    //      isn't replaced by its target.
+    let git_root = fs::canonicalize(git_root).ok()?;
+    let path = match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => fs::canonicalize(parent).ok()?.join(name),
+        _ => fs::canonicalize(path).ok()?,
+    };
+    path.strip_prefix(git_root).ok().map(Path::to_path_buf)
+}
+
+/// Makes a path absolute and removes `.` and `..` components without touching the
+/// file system, so that paths like `../other` can be compared by prefix.
+///
+/// On Windows, verbatim prefixes are also simplified, so that paths like
+/// `\\?\C:\repo` and `C:\repo` have the same prefix.
+fn normalize_absolute(path: &Path) -> Option<PathBuf> {
+    let path = path::absolute(path).ok()?;
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => normalized.push(simplify_prefix(prefix)),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                // NOTE The root's parent is the root, like in a shell. This includes
+                //      drive roots like `C:\` and shares like `\\server\share\`.
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    Some(normalized)
+}
+
+/// Converts a verbatim prefix, like `\\?\C:` or `\\?\UNC\server\share`, to its
+/// usual form, like `C:` or `\\server\share`. `fs::canonicalize` returns verbatim
+/// paths on Windows, while git and users usually don't.
+fn simplify_prefix(prefix: PrefixComponent<'_>) -> OsString {
+    match prefix.kind() {
+        Prefix::VerbatimDisk(letter) => format!("{}:", char::from(letter)).into(),
+        Prefix::VerbatimUNC(server, share) => {
+            let mut simplified = OsString::from(r"\\");
+            simplified.push(server);
+            simplified.push(r"\");
+            simplified.push(share);
+            simplified
+        }
+        _ => prefix.as_os_str().to_os_string(),
+    }
+}
+
+/// Entry points for the fuzz targets, which can't reach private helpers.
+#[cfg(fuzzing)]
+#[doc(hidden)]
+pub mod fuzzing {
+    use std::path::{Path, PathBuf};
+
+    pub fn clean_path_for_git2(git_root: &Path, path: &Path) -> Option<PathBuf> {
+        super::clean_path_for_git2(git_root, path)
+    }
+
+    pub fn normalize_absolute(path: &Path) -> Option<PathBuf> {
+        super::normalize_absolute(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::icons::{IconSet, Kind};
+    use crate::testing::Fixture;
+    use rstest::rstest;
+    use std::fs;
+
+    /// Writes a tree and returns the text.
+    fn write<P>(tree: &Tree<'_, '_, P>) -> String
+    where
+        P: AsRef<Path>,
+    {
+        let mut output = Vec::new();
+        tree.write(&mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[rstest]
+    #[cfg_attr(unix, case("repo", "repo/src/lib.rs", Some("src/lib.rs")))]
+    #[cfg_attr(unix, case("repo", "repo/./src/lib.rs", Some("src/lib.rs")))]
+    #[cfg_attr(unix, case("repo", "repo/src/../src/lib.rs", Some("src/lib.rs")))]
+    #[cfg_attr(unix, case("repo", "other/../repo/src/lib.rs", Some("src/lib.rs")))]
+    #[cfg_attr(unix, case("repo/../repo", "repo/src/lib.rs", Some("src/lib.rs")))]
+    #[cfg_attr(unix, case("repo", "other/lib.rs", None))]
+    #[cfg_attr(windows, case("Dir/Repo", r"Dir\Repo\src\lib.rs", Some(r"src\lib.rs")))]
+    fn test_clean_path_for_git2(
+        #[case] git_root: &str,
+        #[case] path: &str,
+        #[case] expected: Option<&str>,
+    ) {
+        // NOTE Create the "repository" and its files in a temporary directory.
+        let fixture = Fixture::builder()
+            .dir(git_root)
+            .file(path, "")
+            .build()
+            .unwrap();
+        let git_root = fixture.join(git_root);
+        let path = fixture.join(path);
+
+        let expected = expected.map(PathBuf::from);
+
+        assert_eq!(expected, clean_path_for_git2(git_root, path));
+    }
+
+    #[rstest]
+    #[case(".", ".")]
+    #[case("..", "..")]
+    #[case("a/../b", "b")]
+    #[case("a/./b/..", "a")]
+    fn test_normalize_absolute(#[case] path: &str, #[case] expected: &str) {
+        let cwd = std::env::current_dir().unwrap();
+        let expected = normalize_absolute(&cwd.join(expected)).unwrap();
+        assert_eq!(expected, normalize_absolute(Path::new(path)).unwrap());
+        assert!(!expected.components().any(|c| c == Component::ParentDir));
+    }
+
+    #[cfg(unix)]
+    #[rstest]
+    #[case("/..", "/")]
+    #[case("/../a", "/a")]
+    fn test_normalize_absolute_root(#[case] path: &str, #[case] expected: &str) {
+        assert_eq!(
+            Some(PathBuf::from(expected)),
+            normalize_absolute(Path::new(path))
+        );
+    }
+
+    #[cfg(windows)]
+    #[rstest]
+    #[case(r"C:\", r"C:\")]
+    #[case(r"C:\..", r"C:\")]
+    #[case(r"C:\a\..\..\b", r"C:\b")]
+    #[case(r"\\?\C:\a\b", r"C:\a\b")]
+    #[case(r"\\server\share\..", r"\\server\share\")]
+    #[case(r"\\server\share\dir\.\file", r"\\server\share\dir\file")]
+    #[case(r"\\?\UNC\server\share\dir", r"\\server\share\dir")]
+    fn test_normalize_absolute_prefix(#[case] path: &str, #[case] expected: &str) {
+        assert_eq!(
+            Some(PathBuf::from(expected)),
+            normalize_absolute(Path::new(path))
+        );
+    }
+
+    #[rstest]
+    #[cfg_attr(unix, case("/", "/src/lib.rs", Some("src/lib.rs")))]
+    #[cfg_attr(unix, case("/", "/", Some("")))]
+    #[cfg_attr(windows, case(r"C:\", r"C:\src\lib.rs", Some(r"src\lib.rs")))]
+    #[cfg_attr(windows, case("C:/repo/", r"\\?\C:\repo\lib.rs", Some("lib.rs")))]
+    #[cfg_attr(windows, case(r"\\?\C:\repo", r"C:\repo\lib.rs", Some("lib.rs")))]
+    #[cfg_attr(
+        windows,
+        case(
+            r"\\server\share\repo",
+            r"\\?\UNC\server\share\repo\lib.rs",
+            Some("lib.rs")
+        )
+    )]
+    fn test_clean_path_for_git2_prefix(
+        #[case] git_root: &str,
+        #[case] path: &str,
+        #[case] expected: Option<&str>,
+    ) {
+        // NOTE These paths don't need to exist, since their prefixes match without
+        //      resolving them.
+        let expected = expected.map(PathBuf::from);
+        assert_eq!(expected, clean_path_for_git2(git_root, path));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_clean_path_for_git2_through_symlink() {
+        let fixture = Fixture::builder()
+            .file("repo/src/lib.rs", "")
+            .symlink("link", "repo")
+            .build()
+            .unwrap();
+        let git_root = fixture.join("repo");
+
+        let path = fixture.join("link/src/lib.rs");
+        let expected = Some(PathBuf::from("src/lib.rs"));
+        assert_eq!(expected, clean_path_for_git2(&git_root, path));
+    }
+
+    #[test]
+    fn test_max_lines() {
+        let fixture = Fixture::builder()
+            .dir("a")
+            .file("a/1", "")
+            .file("a/2", "")
+            .file("a/3", "")
+            .file("b", "")
+            .file("c", "")
+            .build()
+            .unwrap();
+        let tree = Builder::new(fixture.path())
+            .color_choice(ColorChoice::Off)
+            .max_lines(5)
+            .build();
+
+        let output = write(&tree);
+        let lines = output.lines().collect::<Vec<_>>();
+
+        assert_eq!(5, lines.len(), "{output}");
+        assert!(lines[2].ends_with(" 1"));
+        assert!(lines[3].ends_with("… 2 more (truncated)"));
+        assert!(lines[4].ends_with("… 2 more (truncated)"));
+        assert!(lines[4].starts_with(&*Charset::STANDARD.last_depth));
+    }
+
+    #[rstest]
+    #[case::tree(Format::Tree, false)]
+    #[case::breadth_first(Format::Tree, true)]
+    #[case::flat(Format::Flat, false)]
+    fn test_max_lines_includes_markers(#[case] format: Format, #[case] breadth_first: bool) {
+        let fixture = Fixture::builder()
+            .file("a/b/1", "")
+            .file("a/b/2", "")
+            .file("a/3", "")
+            .file("c/4", "")
+            .file("d", "")
+            .build()
+            .unwrap();
+        for max_lines in 1..=12 {
+            let mut builder = Builder::new(fixture.path())
+                .color_choice(ColorChoice::Off)
+                .format(format)
+                .max_lines(max_lines);
+            if breadth_first {
+                builder = builder.breadth_first();
+            }
+            let output = write(&builder.build());
+            // NOTE The root is always written.
+            let expected = max_lines.max(2);
+            assert!(output.lines().count() <= expected, "{max_lines}:\n{output}");
+        }
+    }
+
+    #[rstest]
+    #[case(Lang::En, "more")]
+    #[case(Lang::Es, "más")]
+    fn test_file_limit(#[case] lang: Lang, #[case] more: &str) {
+        let fixture = Fixture::builder()
+            .file("a/1", "")
+            .file("a/2", "")
+            .file("a/3", "")
+            .file("b", "")
+            .build()
+            .unwrap();
+        let tree = Builder::new(fixture.path())
+            .color_choice(ColorChoice::Off)
+            .charset(Charset::ASCII)
+            .file_limit(1)
+            .lang(lang)
+            .build();
+
+        let output = write(&tree);
+        let lines = output.lines().skip(1).collect::<Vec<_>>();
+
+        assert_eq!(4, lines.len());
+        assert!(lines[0].ends_with(" a"));
+        assert!(lines[1].ends_with(" 1"));
+        assert_eq!(format!("|   `-- … (+2 {more})"), lines[2]);
+        assert_eq!(format!("`-- … (+1 {more})"), lines[3]);
+    }
+
+    #[test]
+    fn test_file_limit_skip() {
+        let fixture = Fixture::builder()
+            .file("a/1", "")
+            .file("a/2", "")
+            .file("a/3", "")
+            .file("b/1", "")
+            .build()
+            .unwrap();
+        let tree = Builder::new(fixture.path())
+            .color_choice(ColorChoice::Off)
+            .charset(Charset::ASCII)
+            .file_limit(2)
+            .file_limit_mode(FileLimitMode::Skip)
+            .build();
+
+        let output = write(&tree);
+        let lines = output.lines().skip(1).collect::<Vec<_>>();
+
+        assert_eq!(4, lines.len(), "{output}");
+        assert!(lines[0].ends_with(" a"), "{output}");
+        assert_eq!("|   `-- … (+3 more)", lines[1]);
+        assert!(lines[2].ends_with(" b"), "{output}");
+        assert!(lines[3].ends_with(" 1"), "{output}");
+    }
+
+    #[test]
+    fn test_file_limit_formats() {
+        let fixture = Fixture::builder()
+            .file("a/1", "")
+            .file("a/2", "")
+            .file("a/3", "")
+            .file("b", "")
+            .build()
+            .unwrap();
+        let builder = || {
+            Builder::new(fixture.path())
+                .color_choice(ColorChoice::Off)
+                .icon_set(IconSet::None)
+                .file_limit(1)
+        };
+
+        let export = builder().build().export().unwrap();
+        assert_eq!(Some(1), export.omitted);
+        let children = export.children.unwrap();
+        assert_eq!(
+            vec!["a"],
+            children.iter().map(|node| &node.name).collect::<Vec<_>>()
+        );
+        assert_eq!(Some(2), children[0].omitted);
+        assert_eq!(1, children[0].children.as_ref().unwrap().len());
+
+        let tree = builder().build();
+        let depths = tree.iter().unwrap().map(|(depth, _, _)| depth);
+        assert_eq!(vec![0, 1, 2], depths.collect::<Vec<_>>());
+
+        let tree = builder().format(Format::Flat).build();
+        let output = write(&tree);
+        let lines = output.lines().map(str::trim_start).collect::<Vec<_>>();
+        let separator = path::MAIN_SEPARATOR;
+        let a = format!("a{separator}… (+2 more)");
+        assert_eq!(4, lines.len(), "{output}");
+        assert!(lines[1].ends_with(&format!("a{separator}1")), "{output}");
+        assert_eq!(a, lines[2]);
+        assert_eq!("… (+1 more)", lines[3]);
+    }
+
+    #[test]
+    fn test_pattern_and_prune() {
+        let fixture = Fixture::builder()
+            .dir("docs")
+            .dir("empty")
+            .file("Cargo.toml", "")
+            .file("docs/README.md", "")
+            .file("src/main.rs", "")
+            .file("src/nested/lib.rs", "")
+            .build()
+            .unwrap();
+        let names = |prune: bool| {
+            let mut builder = Builder::new(fixture.path())
+                .color_choice(ColorChoice::Off)
+                .pattern("*.rs".parse().unwrap());
+            if prune {
+                builder = builder.prune(true);
+            }
+            write(&builder.build())
+                .lines()
+                .skip(1)
+                .map(|line| line.rsplit(' ').next().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            vec!["docs", "empty", "src", "main.rs", "nested", "lib.rs"],
+            names(false)
+        );
+        assert_eq!(vec!["src", "main.rs", "nested", "lib.rs"], names(true));
+    }
+
+    #[test]
+    fn test_prune_checks_each_directory_once() {
+        let fixture = Fixture::builder()
+            .dir("a/b/c")
+            .file("d/e/file", "")
+            .build()
+            .unwrap();
+        let tree = Builder::new(fixture.path())
+            .color_choice(ColorChoice::Off)
+            .prune(true)
+            .build();
+
+        let output = write(&tree);
+        assert_eq!(4, output.lines().count(), "{output}");
+
+        let pruned = tree.pruned.borrow();
+        let expected = [
+            ("a", true),
+            ("a/b", true),
+            ("a/b/c", true),
+            ("d", false),
+            ("d/e", false),
+        ];
+        assert_eq!(expected.len(), pruned.len(), "{pruned:?}");
+        for (path, is_pruned) in expected {
+            assert_eq!(Some(&is_pruned), pruned.get(&fixture.join(path)), "{path}");
+        }
+    }
+
+    #[rstest]
+    #[case(SizeMode::Files, "      ", "   4B ")]
+    #[case(SizeMode::Recursive, "  12B ", "   4B ")]
+    fn test_size_mode(
+        #[case] size_mode: SizeMode,
+        #[case] expected_dir: &str,
+        #[case] expected_file: &str,
+    ) {
+        let fixture = Fixture::builder()
+            .file("dir/a", "1234")
+            .file("dir/b", "12345678")
+            .build()
+            .unwrap();
+        let tree = Builder::new(fixture.path())
+            .color_choice(ColorChoice::Off)
+            .size_mode(size_mode)
+            .build();
+
+        let output = write(&tree);
+        let lines = output.lines().collect::<Vec<_>>();
+        let Charset {
+            depth,
+            last_depth,
+            indent,
+            ..
+        } = Charset::STANDARD;
+        assert!(lines[1].starts_with(&format!("{last_depth}{expected_dir}")));
+        assert!(lines[2].starts_with(&format!("{indent}{depth}{expected_file}")));
+    }
+
+    #[test]
+    fn test_disk_usage() {
+        let fixture = Fixture::builder()
+            .file("dir/deep/a", "1234")
+            .file("dir/b", "12345678")
+            .build()
+            .unwrap();
+        let tree = Builder::new(fixture.path())
+            .color_choice(ColorChoice::Off)
+            .max_level(1)
+            .disk_usage()
+            .build();
+
+        let output = write(&tree);
+        let lines = output.lines().collect::<Vec<_>>();
+        let last_depth = Charset::STANDARD.last_depth;
+        assert!(lines[0].starts_with("  12B "), "{output}");
+        assert!(
+            lines[1].starts_with(&format!("{last_depth}  12B ")),
+            "{output}"
+        );
+        assert_eq!(Some(12), tree.stats().total_size);
+    }
+
+    #[rstest]
+    #[case::tree(Format::Tree)]
+    #[case::accessible(Format::Accessible)]
+    fn test_stats(#[case] format: Format) {
+        let fixture = Fixture::builder()
+            .dir("a/b")
+            .file("a/file", "")
+            .file("file", "")
+            .build()
+            .unwrap();
+        let tree = Builder::new(fixture.path())
+            .color_choice(ColorChoice::Off)
+            .format(format)
+            .build();
+
+        tree.write(&mut Vec::new()).unwrap();
+        let expected = Stats {
+            directories: 2,
+            files: 2,
+            total_size: None,
+        };
+        assert_eq!(expected, tree.stats());
+    }
+
+    #[rstest]
+    #[case::depth_first(false)]
+    #[case::breadth_first(true)]
+    fn test_histogram(#[case] breadth_first: bool) {
+        let fixture = Fixture::builder()
+            .dir("c")
+            .file("a/b/file", "")
+            .file("file", "")
+            .build()
+            .unwrap();
+        let mut builder = Builder::new(fixture.path()).color_choice(ColorChoice::Off);
+        if breadth_first {
+            builder = builder.breadth_first();
+        }
+        let tree = builder.build();
+
+        tree.write(&mut Vec::new()).unwrap();
+        let histogram = tree.histogram();
+        assert_eq!(&[3, 1, 1], histogram.depths());
+        assert_eq!(vec![("a", 2), ("c", 0)], histogram.directories());
+    }
+
+    #[test]
+    fn test_mtime() {
+        let fixture = Fixture::builder().file("file", "").build().unwrap();
+        let file = fs::File::options()
+            .write(true)
+            .open(fixture.join("file"))
+            .unwrap();
+        // NOTE Mid-year, so that the year is the same in every time zone.
+        let modified = std::time::UNIX_EPOCH + std::time::Duration::from_secs(992_606_400);
+        file.set_modified(modified).unwrap();
+        let tree = Builder::new(fixture.path())
+            .color_choice(ColorChoice::Off)
+            .mtime(true)
+            .mtime_format("[%Y]".parse().unwrap())
+            .build();
+
+        let output = write(&tree);
+        let lines = output.lines().collect::<Vec<_>>();
+        let depth = Charset::STANDARD.last_depth;
+        assert!(lines[1].starts_with(&format!("{depth}[2001] ")));
+    }
+
+    #[test]
+    fn test_two_pass() {
+        let fixture = Fixture::builder()
+            .git_init()
+            .file("clean", "")
+            .file("tracked", "")
+            .git_commit("Initial commit")
+            .file("tracked", "modified")
+            .build()
+            .unwrap();
+        let git = crate::git::Git::new(fixture.path()).unwrap().unwrap();
+        git.load_statuses_in_background(Default::default());
+        let tree = Builder::new(fixture.path())
+            .vcs(&git)
+            .color_choice(ColorChoice::Off)
+            .git_dir(GitDir::Hidden)
+            .two_pass()
+            .build();
+
+        let output = write(&tree);
+        let lines = output.lines().collect::<Vec<_>>();
+
+        assert_eq!(5, lines.len(), "{output}");
+        assert!(lines[2].ends_with(" tracked"));
+        assert!(!lines[2].contains('~'));
+        assert_eq!("", lines[3]);
+        assert_eq!("~  tracked", lines[4]);
+    }
+
+    #[test]
+    fn test_show_deleted() {
+        let fixture = Fixture::builder()
+            .git_init()
+            .file("kept", "")
+            .file("staged", "")
+            .file("old/deep/file", "")
+            .git_commit("Initial commit")
+            .git(["rm", "--quiet", "staged"])
+            .build()
+            .unwrap();
+        fs::remove_dir_all(fixture.join("old")).unwrap();
+        let git = crate::git::Git::new(fixture.path()).unwrap().unwrap();
+        let tree = Builder::new(fixture.path())
+            .vcs(&git)
+            .color_choice(ColorChoice::On)
+            .icon_set(IconSet::None)
+            .git_dir(GitDir::Hidden)
+            .show_deleted()
+            .build();
+
+        let output = write(&tree);
+        let lines = output.lines().skip(1).collect::<Vec<_>>();
+
+        let struck = |name, color| {
+            let mut text = Vec::new();
+            let style = Style::from(Color::Ansi(color)).strikethrough();
+            ColorChoice::On.write_to(&mut text, name, style).unwrap();
+            String::from_utf8(text).unwrap()
+        };
+        assert_eq!(5, lines.len(), "{output}");
+        assert!(lines[0].ends_with(" kept"), "{output}");
+        // NOTE Deletions that aren't staged have the untracked status's color.
+        let deleted = [
+            ("old", AnsiColors::BrightRed),
+            ("deep", AnsiColors::BrightRed),
+            ("file", AnsiColors::BrightRed),
+            ("staged", AnsiColors::Red),
+        ];
+        for (line, (name, color)) in lines[1..].iter().zip(deleted) {
+            assert!(line.ends_with(&struck(name, color)), "{output}");
+        }
+        let depth = Charset::STANDARD.last_depth;
+        assert!(lines[4].starts_with(&*depth), "{output}");
+    }
+
+    #[rstest]
+    #[case(false, None, vec!["kept", "file.rs", "file.txt", "old", "file.rs"])]
+    #[case(true, None, vec!["kept", ".hidden", "file.rs", "file.txt", "old", "file.rs"])]
+    #[case(false, Some("*.rs"), vec!["file.rs", "old", "file.rs"])]
+    fn test_show_deleted_filters(
+        #[case] show_hidden: bool,
+        #[case] pattern: Option<&str>,
+        #[case] expected: Vec<&str>,
+    ) {
+        let fixture = Fixture::builder()
+            .git_init()
+            .file("kept", "")
+            .file(".hidden", "")
+            .file("file.rs", "")
+            .file("file.txt", "")
+            .file("old/file.rs", "")
+            .git_commit("Initial commit")
+            .build()
+            .unwrap();
+        for path in [".hidden", "file.rs", "file.txt"] {
+            fs::remove_file(fixture.join(path)).unwrap();
+        }
+        fs::remove_dir_all(fixture.join("old")).unwrap();
+        let git = crate::git::Git::new(fixture.path()).unwrap().unwrap();
+        let mut builder = Builder::new(fixture.path())
+            .vcs(&git)
+            .color_choice(ColorChoice::Off)
+            .icon_set(IconSet::None)
+            .git_dir(GitDir::Hidden)
+            .show_deleted();
+        if show_hidden {
+            builder = builder.show_hidden();
+        }
+        if let Some(pattern) = pattern {
+            builder = builder.pattern(pattern.parse().unwrap());
+        }
+
+        let output = write(&builder.build());
+        let names = output
+            .lines()
+            .skip(1)
+            .map(|line| line.rsplit(' ').next().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(expected, names, "{output}");
+    }
+
+    #[test]
+    fn test_show_deleted_breadth_first() {
+        let fixture = Fixture::builder()
+            .git_init()
+            .file("kept", "")
+            .file("old/file", "")
+            .git_commit("Initial commit")
+            .build()
+            .unwrap();
+        fs::remove_dir_all(fixture.join("old")).unwrap();
+        let git = crate::git::Git::new(fixture.path()).unwrap().unwrap();
+        let tree = Builder::new(fixture.path())
+            .vcs(&git)
+            .color_choice(ColorChoice::Off)
+            .icon_set(IconSet::None)
+            .git_dir(GitDir::Hidden)
+            .show_deleted()
+            .breadth_first()
+            .build();
+
+        let output = write(&tree);
+        let lines = output.lines().skip(1).collect::<Vec<_>>();
+
+        let old = format!("old{}", path::MAIN_SEPARATOR);
+        assert_eq!(5, lines.len(), "{output}");
+        assert!(lines[0].ends_with(" kept"), "{output}");
+        assert!(lines[1].ends_with(" old"), "{output}");
+        assert_eq!("", lines[2]);
+        assert_eq!(old, lines[3]);
+        assert!(lines[4].ends_with(" file"), "{output}");
+    }
+
+    #[rstest]
+    #[case::collapsed(Some(1))]
+    #[case::expanded(None)]
+    fn test_untracked_files(#[case] max_level: Option<usize>) {
+        let fixture = Fixture::builder()
+            .git_init()
+            .file(".gitignore", "ignored")
+            .file("tracked/file", "")
+            .git_commit("Initial commit")
+            .file("tracked/new", "")
+            .file("new/a", "")
+            .file("new/ignored", "")
+            .file("new/sub/b", "")
+            .file("new/sub/c", "")
+            .build()
+            .unwrap();
+        let git = crate::git::Git::new(fixture.path()).unwrap().unwrap();
+        let builder = Builder::new(fixture.path())
+            .vcs(&git)
+            .color_choice(ColorChoice::Off)
+            .git_dir(GitDir::Hidden);
+        let tree = match max_level {
+            Some(max_level) => builder.max_level(max_level),
+            None => builder,
+        }
+        .build();
+
+        let output = write(&tree);
+        let has_line = |suffix: &str| output.lines().any(|line| line.ends_with(suffix));
+        assert!(has_line(" new +3"), "{output}");
+        // NOTE A directory with tracked files isn't untracked as a whole.
+        assert!(has_line(" tracked"), "{output}");
+        assert_eq!(max_level.is_none(), has_line(" sub +2"), "{output}");
+    }
+
+    #[test]
+    fn test_nested_repository() {
+        let fixture = Fixture::builder()
+            .git_init()
+            .file("outer", "")
+            .git_commit("Initial commit")
+            .file("inner/tracked", "")
+            .git(["-C", "inner", "init", "--quiet"])
+            .git(["-C", "inner", "add", "--all"])
+            .git([
+                "-C",
+                "inner",
+                "commit",
+                "--quiet",
+                "--message",
+                "Inner commit",
+            ])
+            .file("inner/tracked", "modified")
+            .build()
+            .unwrap();
+        let git = crate::git::Git::new(fixture.path()).unwrap().unwrap();
+        let tree = Builder::new(fixture.path())
+            .vcs(&git)
+            .color_choice(ColorChoice::Off)
+            .git_dir(GitDir::Hidden)
+            .build();
+
+        let output = write(&tree);
+        let lines = output.lines().collect::<Vec<_>>();
+
+        assert_eq!(4, lines.len(), "{output}");
+        assert!(lines[1].ends_with(" inner [repository]"), "{output}");
+        assert!(lines[1].contains('+'), "{output}");
+        assert!(lines[2].ends_with(" tracked"), "{output}");
+        assert!(lines[2].contains('~'), "{output}");
+        assert!(!lines[3].contains('~'), "{output}");
+    }
+
+    #[rstest]
+    #[case::collapsed(GitDir::Collapsed, &[".git [main]", "tracked"])]
+    #[case::hidden(GitDir::Hidden, &["tracked"])]
+    #[case::expanded(GitDir::Expanded, &[".git", "HEAD", "tracked"])]
+    fn test_git_dir(#[case] git_dir: GitDir, #[case] expected: &[&str]) {
+        let fixture = Fixture::builder()
+            .git_init()
+            .file("tracked", "")
+            .git_commit("Initial commit")
+            .build()
+            .unwrap();
+        let git = crate::git::Git::new(fixture.path()).unwrap().unwrap();
+        let tree = Builder::new(fixture.path())
+            .vcs(&git)
+            .color_choice(ColorChoice::Off)
+            .git_dir(git_dir)
+            .build();
+
+        let output = write(&tree);
+        let lines = output.lines().skip(1).collect::<Vec<_>>();
+        // NOTE An expanded `.git` directory has many entries, so only some are checked.
+        let found = expected
+            .iter()
+            .all(|name| lines.iter().any(|line| line.ends_with(&format!(" {name}"))));
+        assert!(found, "{output}");
+        if git_dir != GitDir::Expanded {
+            assert_eq!(expected.len(), lines.len(), "{output}");
+        }
+    }
+
+    #[test]
+    fn test_recent() {
+        let fixture = Fixture::builder()
+            .file("new", "")
+            .file("old", "")
+            .build()
+            .unwrap();
+        let old = fs::File::options()
+            .write(true)
+            .open(fixture.join("old"))
+            .unwrap();
+        old.set_modified(std::time::UNIX_EPOCH).unwrap();
+        let tree = Builder::new(fixture.path())
+            .color_choice(ColorChoice::Off)
+            .recent(true)
+            .recent_window("1h".parse().unwrap())
+            .build();
+
+        let output = write(&tree);
+        assert!(output.contains(" new [recent]\n"));
+        assert!(output.contains(" old\n"));
+    }
+
+    #[test]
+    fn test_warn_size() {
+        let fixture = Fixture::builder()
+            .file("a/big", [0; 2048])
+            .file("small", [0; 16])
+            .build()
+            .unwrap();
+        let tree = Builder::new(fixture.path())
+            .color_choice(ColorChoice::Off)
+            .warn_size(Size(1024))
+            .build();
+
+        let output = write(&tree);
+        assert!(output.contains(" big (2.0K)\n"));
+        assert!(output.contains(" small\n"));
+
+        let expected = vec![(fixture.join("a/big"), Size(2048))];
+        assert_eq!(expected, tree.oversized_files().unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks() {
+        let fixture = Fixture::builder()
+            .file("dir/file", "")
+            .symlink("link", "dir")
+            .symlink("broken", "missing")
+            .symlink("dir/loop", ".")
+            .build()
+            .unwrap();
+        let tree = Builder::new(fixture.path())
+            .color_choice(ColorChoice::Off)
+            .follow_symlinks(true)
+            .build();
+
+        let output = write(&tree);
+        assert!(output.contains(" broken -> missing\n"));
+        assert_eq!(2, output.matches(" loop -> . [recursive]\n").count());
+        assert!(output.contains(" link -> dir\n"));
+        assert_eq!(2, output.matches(" file\n").count());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_followed_symlink_statuses() {
+        let fixture = Fixture::builder()
+            .git_init()
+            .file("tracked/file", "")
+            .symlink("linked", "tracked")
+            .git_commit("Initial commit")
+            .file("tracked/file", "modified")
+            .file("new/deep/a", "")
+            .file("new/deep/b", "")
+            .symlink("new_link", "new")
+            .build()
+            .unwrap();
+        let git = crate::git::Git::new(fixture.path()).unwrap().unwrap();
+        let tree = Builder::new(fixture.path())
+            .vcs(&git)
+            .color_choice(ColorChoice::Off)
+            .charset(Charset::ASCII)
+            .icon_set(IconSet::None)
+            .follow_symlinks(true)
+            .build();
+
+        let output = write(&tree);
+        let lines = output.lines().skip(2).collect::<Vec<_>>();
+        let expected = [
+            "|--   linked -> tracked",
+            "|   `-- ~ file",
+            "|-- + new +2",
+            "|   `--   deep +2",
+            "|       |--   a",
+            "|       `--   b",
+            "|-- + new_link -> new",
+            "|   `--   deep +2",
+            "|       |--   a",
+            "|       `--   b",
+            "`--   tracked",
+            "    `-- ~ file",
+        ];
+        assert_eq!(expected, lines.as_slice(), "{output}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_mutual_symlinks() {
+        let fixture = Fixture::builder()
+            .file("a/file", "a")
+            .file("b/file", "b")
+            .build()
+            .unwrap();
+        std::os::unix::fs::symlink("../b", fixture.path().join("a/to_b")).unwrap();
+        std::os::unix::fs::symlink("../a", fixture.path().join("b/to_a")).unwrap();
+        let builder = || {
+            Builder::new(fixture.path())
+                .color_choice(ColorChoice::Off)
+                .follow_symlinks(true)
+        };
+
+        // NOTE a, a/to_b, and a/to_b/to_a, and the same under b.
+        let tree = builder().build();
+        let output = write(&tree);
+        assert_eq!(2, output.matches(" [recursive]\n").count(), "{output}");
+        assert_eq!(4, output.matches(" file\n").count(), "{output}");
+
+        let paths = tree.iter().unwrap().count();
+        assert_eq!(11, paths);
+        let node = tree.export().unwrap();
+        assert_eq!(11, node.flatten().len());
+        let tree = builder().format(Format::Flat).build();
+        assert_eq!(10, write(&tree).lines().count());
+        let tree = builder().warn_size(Size(0)).prune(true).build();
+        assert_eq!(4, tree.oversized_files().unwrap().len());
+    }
+
+    #[test]
+    fn test_theme_connectors() {
+        let fixture = Fixture::builder().file("a", "").build().unwrap();
+        let theme = mlua::Lua::new()
+            .load(r#"return { connectors = "red" }"#)
+            .eval::<config::Theme>()
+            .unwrap();
+        let tree = Builder::new(fixture.path())
+            .color_choice(ColorChoice::On)
+            .charset(Charset::ASCII)
+            .colors(config::Colors::default().theme(theme))
+            .build();
+
+        let output = write(&tree);
+        assert!(output.contains("\x1b[31m`-- \x1b[39m"), "{output:?}");
+    }
+
+    #[test]
+    fn test_connectors() {
+        let fixture = Fixture::builder()
+            .file("a/b/c", "")
+            .file("a/d", "")
+            .file("e", "")
+            .build()
+            .unwrap();
+        let tree = Builder::new(fixture.path())
+            .color_choice(ColorChoice::Off)
+            .charset(Charset::ASCII)
+            .build();
+
+        let output = write(&tree);
+        let branches = output
+            .lines()
+            .skip(1)
+            .map(|line| line.split_once(|c: char| c.is_alphanumeric() || !c.is_ascii()))
+            .map(|split| split.unwrap().0)
+            .collect::<Vec<_>>();
+        let expected = vec!["|-- ", "|   |-- ", "|   |   `-- ", "|   `-- ", "`-- "];
+        assert_eq!(expected, branches);
+    }
+
+    #[rstest]
+    #[case::tree(Format::Tree)]
+    #[case::flat(Format::Flat)]
+    fn test_right_to_left_and_wide_names(#[case] format: Format) {
+        let fixture = Fixture::builder()
+            .file("مجلد/ملف.txt", "")
+            .file("קובץ.md", "")
+            .file("日本語.txt", "")
+            .build()
+            .unwrap();
+        let tree = Builder::new(fixture.path())
+            .color_choice(ColorChoice::Off)
+            .charset(Charset::ASCII)
+            .format(format)
+            .build();
+
+        let output = write(&tree);
+        let isolated = |name: &str| format!("\u{2068}{name}\u{2069}");
+        let expected_names = if format == Format::Tree {
+            vec![isolated("مجلد"), isolated("ملف.txt"), isolated("קובץ.md")]
+        } else {
+            vec![
+                isolated("مجلد"),
+                isolated("مجلد/ملف.txt"),
+                isolated("קובץ.md"),
+            ]
+        };
+        for name in expected_names {
+            assert!(output.contains(&format!("{name}\n")), "{output}");
+        }
+        assert!(output.contains(" 日本語.txt\n"), "{output}");
+        if format == Format::Tree {
+            let branches = output
+                .lines()
+                .skip(1)
+                .map(|line| line.split_once(|c: char| !c.is_ascii()).unwrap().0)
+                .collect::<Vec<_>>();
+            assert!(branches.iter().all(|branch| branch.starts_with(['|', '`'])));
+            assert_eq!(4, branches.len());
+        }
+    }
+
+    #[rstest]
+    #[case::ascii(IconSet::Ascii, vec!["|-- / dir", "|   `-- - file", "`-- z file.zip"])]
+    #[case::none(IconSet::None, vec!["|-- dir", "|   `-- file", "`-- file.zip"])]
+    fn test_icon_set(#[case] icon_set: IconSet, #[case] expected: Vec<&str>) {
+        let fixture = Fixture::builder()
+            .file("dir/file", "")
+            .file("file.zip", "")
+            .build()
+            .unwrap();
+        let tree = Builder::new(fixture.path())
+            .color_choice(ColorChoice::Off)
+            .charset(Charset::ASCII)
+            .icon_set(icon_set)
+            .build();
+
+        let output = write(&tree);
+        assert_eq!(expected, output.lines().skip(1).collect::<Vec<_>>());
+    }
+
+    // NOTE A collapsed directory isn't read just to find out if it's empty.
+    #[rstest]
+    #[case::expanded(None, Kind::OpenDirectory, Kind::EmptyDirectory)]
+    #[case::collapsed(Some(1), Kind::Directory, Kind::Directory)]
+    fn test_directory_icons(
+        #[case] level: Option<usize>,
+        #[case] kind: Kind,
+        #[case] empty_kind: Kind,
+    ) {
+        let fixture = Fixture::builder()
+            .dir("empty")
+            .file("a/file", "")
+            .file("tests/file", "")
+            .build()
+            .unwrap();
+        let mut builder = Builder::new(fixture.path())
+            .color_choice(ColorChoice::Off)
+            .charset(Charset::ASCII)
+            .icon_set(IconSet::Nerd);
+        if let Some(level) = level {
+            builder = builder.max_level(level);
+        }
+        let tree = builder.build();
+
+        let output = write(&tree);
+        let icon = |kind| IconSet::Nerd.icon(kind);
+        let lines = output.lines().collect::<Vec<_>>();
+        assert!(lines.contains(&format!("|-- {} a", icon(kind)).as_str()));
+        let empty = format!("|-- {} empty", icon(empty_kind));
+        assert!(lines.contains(&empty.as_str()), "{output}");
+        let tests = format!("`-- {} tests", icon(Kind::TestDirectory));
+        assert!(lines.contains(&tests.as_str()), "{output}");
+    }
+
+    #[test]
+    fn test_breadth_first() {
+        let fixture = Fixture::builder()
+            .dir("empty")
+            .file("a/b/c", "")
+            .file("a/d", "")
+            .file("e", "")
+            .build()
+            .unwrap();
+        let tree = Builder::new(fixture.path())
+            .color_choice(ColorChoice::Off)
+            .breadth_first()
+            .build();
+
+        let output = write(&tree);
+        let lines = output
+            .lines()
+            .skip(1)
+            .map(|line| match line.strip_prefix("    ") {
+                Some(line) => line.rsplit(' ').next().unwrap(),
+                None => line,
+            })
+            .collect::<Vec<_>>();
+        let separator = path::MAIN_SEPARATOR;
+        let a = format!("a{separator}");
+        let b = format!("a{separator}b{separator}");
+        let expected = vec!["a", "e", "empty", "", &a, "b", "d", "", &b, "c"];
+        assert_eq!(expected, lines);
+    }
+
+    #[test]
+    fn test_flat() {
+        let fixture = Fixture::builder()
+            .file("a/b/c", "")
+            .file("a/d", "")
+            .file("e", "")
+            .build()
+            .unwrap();
+        let tree = Builder::new(fixture.path())
+            .color_choice(ColorChoice::Off)
+            .format(Format::Flat)
+            .build();
+
+        let output = write(&tree);
+        let paths = output
+            .lines()
+            .map(|line| line.rsplit(' ').next().unwrap())
+            .map(PathBuf::from)
+            .collect::<Vec<_>>();
+        let expected = ["a", "a/b", "a/b/c", "a/d", "e"].map(PathBuf::from);
+        assert_eq!(expected.to_vec(), paths);
+        assert_eq!(
+            Stats {
+                directories: 2,
+                files: 3,
+                total_size: None,
+            },
+            tree.stats()
+        );
+    }
+
+    #[rstest]
+    #[case(SortKey::Path, false, vec!["a", "a/b", "a/c", "d"])]
+    #[case(SortKey::Path, true, vec!["d", "a/c", "a/b", "a"])]
+    #[case(SortKey::Name, false, vec!["a", "a/b", "a/c", "d"])]
+    #[case(SortKey::Size, false, vec!["a", "a/c", "d", "a/b"])]
+    #[case(SortKey::Size, true, vec!["a/b", "d", "a/c", "a"])]
+    fn test_flat_sort_key(
+        #[case] sort_key: SortKey,
+        #[case] reverse: bool,
+        #[case] expected: Vec<&str>,
+    ) {
+        let fixture = Fixture::builder()
+            .file("a/b", "large")
+            .file("a/c", "")
+            .file("d", "1")
+            .build()
+            .unwrap();
+        let mut builder = Builder::new(fixture.path())
+            .color_choice(ColorChoice::Off)
+            .format(Format::Flat)
+            .sort_key(sort_key);
+        if reverse {
+            builder = builder.reverse();
+        }
+
+        let output = write(&builder.build());
+        let paths = output
+            .lines()
+            .map(|line| line.rsplit(' ').next().unwrap())
+            .map(PathBuf::from)
+            .collect::<Vec<_>>();
+        let expected = expected.into_iter().map(PathBuf::from).collect::<Vec<_>>();
+        assert_eq!(expected, paths);
+    }
+
+    #[rstest]
+    #[case(0, false, vec![])]
+    #[case(1, false, vec!["a", "empty", "file"])]
+    #[case(1, true, vec!["a", "empty", "file"])]
+    #[case(2, true, vec!["a", "b", "file"])]
+    fn test_max_level(#[case] level: usize, #[case] prune: bool, #[case] expected: Vec<&str>) {
+        let fixture = Fixture::builder()
+            .dir("a/b/c")
+            .dir("empty")
+            .file("file", "")
+            .build()
+            .unwrap();
+        let mut builder = Builder::new(fixture.path())
+            .color_choice(ColorChoice::Off)
+            .max_level(level);
+        if prune {
+            builder = builder.prune(true);
+        }
+
+        let output = write(&builder.build());
+        let names = output
+            .lines()
+            .skip(1)
+            .map(|line| line.rsplit(' ').next().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(expected, names);
+    }
+
+    #[rstest]
+    #[case(false, Format::Tree)]
+    #[case(true, Format::Tree)]
+    #[case(false, Format::Flat)]
+    fn test_tint(#[case] breadth_first: bool, #[case] format: Format) {
+        let fixture = Fixture::builder()
+            .file("vendor/dep/lib.rs", "")
+            .file("vendor/notes", "")
+            .file("src/main.rs", "")
+            .build()
+            .unwrap();
+        let lua = mlua::Lua::new();
+        let colors = lua
+            .load(
+                r#"return {
+                    tint = function(path, attributes, inherited)
+                        if path:match("notes$") then return nil end
+                        if path:match("vendor$") then return { dim = true } end
+                        return inherited
+                    end,
+                }"#,
+            )
+            .eval::<config::Colors>()
+            .unwrap();
+        let builder = Builder::new(fixture.path())
+            .color_choice(ColorChoice::On)
+            .icon_set(IconSet::None)
+            .format(format)
+            .colors(colors);
+        let tree = if breadth_first {
+            builder.breadth_first()
+        } else {
+            builder
+        }
+        .build();
+
+        let output = write(&tree);
+        let line = |name| output.lines().find(|line| line.contains(name)).unwrap();
+        for name in ["vendor", "dep", "lib.rs"] {
+            assert!(line(name).contains("\x1b[2m"), "{name}: {output:?}");
+        }
+        for name in ["notes", "main.rs"] {
+            assert!(!line(name).contains("\x1b[2m"), "{name}: {output:?}");
+        }
+    }
+
+    #[rstest]
+    #[case(ColorChoice::On, Format::Tree, true)]
+    #[case(ColorChoice::On, Format::Flat, true)]
+    #[case(ColorChoice::Off, Format::Tree, false)]
+    #[case(ColorChoice::On, Format::Markdown, false)]
+    fn test_hyperlinks(
+        #[case] color_choice: ColorChoice,
+        #[case] format: Format,
+        #[case] expected: bool,
+    ) {
+        let fixture = Fixture::builder().file("a b", "").build().unwrap();
+        let tree = Builder::new(fixture.path())
+            .color_choice(color_choice)
+            .format(format)
+            .hyperlinks(true)
+            .build();
+
+        let output = write(&tree);
+        let url = hyperlink::file_url(&normalize_absolute(&fixture.join("a b")).unwrap());
+        let link = format!("{}a b{}", hyperlink::start(&url), hyperlink::END);
+        assert_eq!(expected, output.contains(&link), "{output:?}");
+        assert_eq!(expected, output.contains("\x1b]8;;"), "{output:?}");
+    }
+
+    #[rstest]
+    #[case(ColorChoice::Off, Charset::STANDARD, false)]
+    #[case(ColorChoice::Off, Charset::ASCII, false)]
+    #[case(ColorChoice::On, Charset::STANDARD, true)]
+    fn test_breadcrumb(
+        #[case] color_choice: ColorChoice,
+        #[case] charset: Charset<'static>,
+        #[case] is_linked: bool,
+    ) {
+        let fixture = Fixture::builder().dir("a/b").build().unwrap();
+        let path = fixture.join("a").join("b");
+        let separator = if charset == Charset::ASCII {
+            " > "
+        } else {
+            " › "
+        };
+        let tree = Builder::new(&path)
+            .color_choice(color_choice)
+            .charset(charset)
+            .icon_set(IconSet::None)
+            .breadcrumb(true)
+            .build();
+
+        let output = write(&tree);
+        let top = output.lines().next().unwrap();
+        let link = |path: &Path, label| {
+            let url = hyperlink::file_url(&normalize_absolute(path).unwrap());
+            format!("{}{label}{}", hyperlink::start(&url), hyperlink::END)
+        };
+        let expected = if is_linked {
+            let a = link(&fixture.join("a"), "a");
+            let b = link(&path, "b");
+            format!("{a}{separator}{b}")
+        } else {
+            format!("a{separator}b")
+        };
+        assert!(top.ends_with(&expected), "{top:?}");
+        assert_eq!(is_linked, top.contains("\x1b]8;;"), "{top:?}");
+    }
+
+    // NOTE Dotfiles are only hidden on Unix.
+    #[cfg(not(windows))]
+    #[rstest]
+    #[case(false, vec!["a"])]
+    #[case(true, vec![".hidden", "a"])]
+    fn test_show_hidden(#[case] show_hidden: bool, #[case] expected: Vec<&str>) {
+        let fixture = Fixture::builder()
+            .file(".hidden", "")
+            .file("a", "")
+            .build()
+            .unwrap();
+        let builder = Builder::new(fixture.path())
+            .color_choice(ColorChoice::Off)
+            .format(Format::Flat);
+        let tree = if show_hidden {
+            builder.show_hidden()
+        } else {
+            builder
+        }
+        .build();
+
+        let output = write(&tree);
+        let names = output
+            .lines()
+            .map(|line| line.rsplit(' ').next().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(expected, names);
+    }
+
+    #[test]
+    fn test_export_sources() {
+        use crate::rules::Source;
+
+        let fixture = Fixture::builder()
+            .dir("src")
+            .file("Cargo.toml", "")
+            .file("a.tar.gz", "")
+            .file("b.png", "")
+            .file("c.jpg", "")
+            .file("Dockerfile.dev", "")
+            .file("notes", "")
+            .build()
+            .unwrap();
+        let lua = mlua::Lua::new();
+        let colors = lua
+            .load(
+                r#"return {
+                    icons = function(path, attributes, default)
+                        if path:match("%.png$") then return "red" end
+                        return default
+                    end,
+                }"#,
+            )
+            .eval::<config::Colors>()
+            .unwrap();
+        let tree = Builder::new(fixture.path()).colors(colors).build();
+
+        let node = tree.export().unwrap();
+        let sources = node
+            .children
+            .unwrap()
+            .into_iter()
+            .map(|node| (node.name, (node.icon_source, node.color_source)))
+            .collect::<HashMap<_, _>>();
+        let expected = [
+            ("Cargo.toml", Some(Source::Filename), Some(Source::Language)),
+            (
+                "a.tar.gz",
+                Some(Source::DoubleExtension),
+                Some(Source::DoubleExtension),
+            ),
+            ("b.png", Some(Source::Extension), Some(Source::Lua)),
+            ("c.jpg", Some(Source::Extension), Some(Source::Extension)),
+            ("Dockerfile.dev", Some(Source::Glob), None),
+            ("src", Some(Source::Directory), Some(Source::Default)),
+            ("notes", Some(Source::Default), None),
+        ];
+        for (name, icon_source, color_source) in expected {
+            assert_eq!(
+                Some(&(icon_source, color_source)),
+                sources.get(name),
+                "{name}"
+            );
+        }
     }
 }
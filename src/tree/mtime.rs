@@ -0,0 +1,102 @@
+//! Module for displaying modification times.
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, NaiveDate};
+use mlua::{FromLua, Lua};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::time::SystemTime;
+use unicode_width::UnicodeWidthStr;
+
+/// A strftime-style format for modification times, like `%Y-%m-%d %H:%M`.
+///
+/// The format is validated when it's parsed, so formatting a time can't fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MtimeFormat(String);
+
+impl MtimeFormat {
+    /// The format used when none is configured.
+    pub const DEFAULT: &str = "%Y-%m-%d %H:%M";
+
+    /// Formats a time in the local time zone.
+    pub fn format(&self, time: SystemTime) -> String {
+        let time: DateTime<Local> = time.into();
+        time.format(&self.0).to_string()
+    }
+
+    /// Gets the width of the column for this format.
+    ///
+    /// This is the width of a time with the longest month and weekday names, so that
+    /// most formats have the same width for every time.
+    pub fn width(&self) -> usize {
+        // NOTE September 27th, 2000 is a Wednesday.
+        let sample = NaiveDate::from_ymd_opt(2000, 9, 27)
+            .and_then(|date| date.and_hms_opt(22, 22, 22))
+            .expect("The sample time should be valid");
+        let sample = sample.and_local_timezone(Local).earliest();
+        sample.map_or(0, |sample| sample.format(&self.0).to_string().width())
+    }
+}
+
+impl Default for MtimeFormat {
+    #[inline]
+    fn default() -> Self {
+        Self(String::from(Self::DEFAULT))
+    }
+}
+
+impl FromStr for MtimeFormat {
+    type Err = ParseMtimeFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let is_valid = StrftimeItems::new(s).all(|item| !matches!(item, Item::Error));
+        if is_valid {
+            Ok(Self(s.to_owned()))
+        } else {
+            Err(ParseMtimeFormatError)
+        }
+    }
+}
+
+impl FromLua for MtimeFormat {
+    fn from_lua(value: mlua::Value, lua: &Lua) -> mlua::Result<Self> {
+        let s = String::from_lua(value, lua)?;
+        s.parse().map_err(mlua::Error::external)
+    }
+}
+
+/// Error when a modification time format contains an invalid specifier.
+#[derive(Debug)]
+pub struct ParseMtimeFormatError;
+
+impl Display for ParseMtimeFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid strftime format for modification times")
+    }
+}
+
+impl std::error::Error for ParseMtimeFormatError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("%Y-%m-%d %H:%M", true)]
+    #[case("%b %e %H:%M", true)]
+    #[case("modified %%", true)]
+    #[case("%Q", false)]
+    #[case("%", false)]
+    fn test_parse(#[case] s: &str, #[case] is_valid: bool) {
+        assert_eq!(is_valid, s.parse::<MtimeFormat>().is_ok());
+    }
+
+    #[rstest]
+    #[case(MtimeFormat::DEFAULT, 16)]
+    #[case("%B %A", 19)]
+    #[case("%Y年%m月", 10)]
+    fn test_width(#[case] format: &str, #[case] expected: usize) {
+        let format: MtimeFormat = format.parse().unwrap();
+        assert_eq!(expected, format.width());
+    }
+}
@@ -0,0 +1,54 @@
+//! Module for filtering files by name.
+use glob::Pattern;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Glob patterns for file names, separated by `|`, like `*.rs|*.toml`.
+#[derive(Debug, Clone)]
+pub struct NamePattern(Vec<Pattern>);
+
+impl NamePattern {
+    /// Checks if a path's file name matches any of the patterns.
+    pub fn matches(&self, path: &Path) -> bool {
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            return false;
+        };
+        self.0.iter().any(|pattern| pattern.matches(name))
+    }
+}
+
+impl FromStr for NamePattern {
+    type Err = glob::PatternError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split('|')
+            .map(Pattern::new)
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl fmt::Display for NamePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let patterns = self.0.iter().map(Pattern::as_str).collect::<Vec<_>>();
+        write!(f, "{}", patterns.join("|"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("*.rs", "src/main.rs", true)]
+    #[case("*.rs", "src/main.rs.bak", false)]
+    #[case("*.rs|*.toml", "Cargo.toml", true)]
+    #[case("main.*", "src/main.rs", true)]
+    #[case("src", "src/main.rs", false)]
+    fn test_matches(#[case] pattern: &str, #[case] path: &str, #[case] expected: bool) {
+        let pattern: NamePattern = pattern.parse().unwrap();
+        assert_eq!(expected, pattern.matches(Path::new(path)));
+    }
+}
@@ -0,0 +1,133 @@
+//! Module for reading directories before they're written.
+use super::Entry;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// The entries of a directory, or `None` if it couldn't be read.
+type Listing = Option<Vec<Entry<PathBuf>>>;
+
+/// Reads directories on other threads, so that the directories after the one that's
+/// being written are ready by the time they're reached.
+///
+/// This only reads entries from the filesystem. Skipping and sorting them can call
+/// the configuration, so they still happen in order on the writing thread. This
+/// hides the latency of slow filesystems, like network drives, without changing the
+/// output.
+pub(super) struct Prefetcher {
+    /// Sends directories to the workers. The workers stop when this is dropped.
+    jobs: Sender<(PathBuf, Sender<Listing>)>,
+    /// The directories that were requested and haven't been taken yet.
+    pending: RefCell<HashMap<PathBuf, Receiver<Listing>>>,
+}
+
+impl Prefetcher {
+    /// The number of threads that read directories.
+    const WORKERS: usize = 4;
+
+    /// The most directories that can be requested and not taken, which limits how
+    /// many entries are kept in memory.
+    const MAX_PENDING: usize = 64;
+
+    /// Starts the worker threads.
+    pub(super) fn new() -> Self {
+        let (jobs, receiver) = mpsc::channel::<(PathBuf, Sender<Listing>)>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..Self::WORKERS {
+            let receiver = Arc::clone(&receiver);
+            // NOTE A thread that can't be started only makes reading slower, since
+            //      directories that weren't read are read when they're needed.
+            let _ = thread::Builder::new().spawn(move || {
+                loop {
+                    let job = receiver
+                        .lock()
+                        .ok()
+                        .and_then(|receiver| receiver.recv().ok());
+                    let Some((path, result)) = job else {
+                        break;
+                    };
+                    let _ = result.send(read(&path));
+                }
+            });
+        }
+        Self {
+            jobs,
+            pending: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Starts reading a directory, unless too many directories are already pending.
+    pub(super) fn request(&self, path: &Path) {
+        let mut pending = self.pending.borrow_mut();
+        if pending.len() >= Self::MAX_PENDING || pending.contains_key(path) {
+            return;
+        }
+        let (sender, receiver) = mpsc::channel();
+        if self.jobs.send((path.to_path_buf(), sender)).is_ok() {
+            pending.insert(path.to_path_buf(), receiver);
+        }
+    }
+
+    /// Forgets the directories that were requested, like before the tree is written
+    /// again.
+    pub(super) fn clear(&self) {
+        self.pending.borrow_mut().clear();
+    }
+
+    /// Takes the entries of a directory that was requested, waiting for them if
+    /// they're still being read.
+    ///
+    /// Returns `None` if the directory wasn't requested, so that it should be read
+    /// on this thread.
+    pub(super) fn take(&self, path: &Path) -> Option<Listing> {
+        let receiver = self.pending.borrow_mut().remove(path)?;
+        receiver.recv().ok()
+    }
+}
+
+/// Reads the entries of a directory. Entries that can't be read are left out, so
+/// that the rest of the tree is still written.
+pub(super) fn read(path: &Path) -> Listing {
+    let entries = path.read_dir().ok()?.filter_map(Result::ok);
+    let entries = entries.map(|entry| entry.path()).map(Entry::new);
+    Some(entries.filter_map(Result::ok).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Fixture;
+
+    #[test]
+    fn test_take() {
+        let fixture = Fixture::builder()
+            .file("a/1", "")
+            .file("a/2", "")
+            .dir("b")
+            .build()
+            .unwrap();
+        let prefetcher = Prefetcher::new();
+        let a = fixture.path().join("a");
+        prefetcher.request(&a);
+        prefetcher.request(&fixture.path().join("missing"));
+
+        let mut names = prefetcher
+            .take(&a)
+            .flatten()
+            .unwrap()
+            .iter()
+            .map(|entry| entry.path().file_name().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(vec!["1", "2"], names);
+        assert!(matches!(
+            prefetcher.take(&fixture.path().join("missing")),
+            Some(None)
+        ));
+        assert!(prefetcher.take(&a).is_none());
+        assert!(prefetcher.take(&fixture.path().join("b")).is_none());
+    }
+}
@@ -0,0 +1,81 @@
+//! Module for highlighting recently modified files.
+use crate::duration::{self, ParseDurationError};
+use mlua::{FromLua, Lua};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+/// How long ago a file can have been modified to be highlighted as recent, like
+/// `15min`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecentWindow(pub Duration);
+
+impl RecentWindow {
+    /// The window used when none is configured.
+    pub const DEFAULT: Self = Self(Duration::from_secs(15 * 60));
+
+    /// Gets the earliest time that counts as recent.
+    #[inline]
+    pub fn start(&self, now: SystemTime) -> SystemTime {
+        now.checked_sub(self.0).unwrap_or(SystemTime::UNIX_EPOCH)
+    }
+}
+
+impl Default for RecentWindow {
+    #[inline]
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl Display for RecentWindow {
+    /// Writes the window in the largest unit that it's a whole amount of.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        duration::display(self.0).fmt(f)
+    }
+}
+
+impl FromStr for RecentWindow {
+    type Err = ParseDurationError;
+
+    /// Parses an amount of time, like `90s`, `15min`, `2h`, or `1d`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        duration::parse(s).map(Self)
+    }
+}
+
+impl FromLua for RecentWindow {
+    fn from_lua(value: mlua::Value, lua: &Lua) -> mlua::Result<Self> {
+        let s = String::from_lua(value, lua)?;
+        s.parse().map_err(mlua::Error::external)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("90s", 90)]
+    #[case("15min", 15 * 60)]
+    #[case("2h", 2 * 60 * 60)]
+    #[case("1d", 24 * 60 * 60)]
+    fn test_from_str(#[case] s: &str, #[case] expected: u64) {
+        let window = s.parse::<RecentWindow>().unwrap();
+        assert_eq!(Duration::from_secs(expected), window.0);
+        assert_eq!(s, window.to_string());
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("min")]
+    #[case("15m")]
+    #[case("15")]
+    #[case("15x")]
+    #[case("-1m")]
+    #[case("15é")]
+    fn test_from_str_invalid(#[case] s: &str) {
+        assert!(s.parse::<RecentWindow>().is_err());
+    }
+}
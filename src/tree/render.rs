@@ -0,0 +1,169 @@
+//! Module for writing a tree in custom formats.
+//!
+//! Every format is written from the same search of a [`Tree`](super::Tree), so they
+//! all have the same entries, deleted paths, and markers for the file limit. The
+//! search resolves icons, colors, and statuses into an
+//! [`export::Node`](super::export::Node), and a [`Renderer`] then writes that node,
+//! receiving each entry in depth-first order.
+//!
+//! The text tree is written from the search as it goes instead of from a node, so
+//! that output starts before large directories are finished and `max_lines` can
+//! stop the search early.
+use super::export::Node;
+use std::io::{self, Write};
+
+/// An entry that's being rendered.
+#[derive(Debug, Clone, Copy)]
+pub struct RenderNode<'a> {
+    /// The exported entry. Its children are rendered after it.
+    pub node: &'a Node,
+    /// For each level below the root, is the entry on the path to this one the last
+    /// in its directory? This is empty for the root.
+    pub last_entries: &'a [bool],
+}
+
+impl RenderNode<'_> {
+    /// How many levels below the root the entry is.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.last_entries.len()
+    }
+
+    /// Is the entry the last in its directory? This is `true` for the root.
+    #[inline]
+    pub fn is_last(&self) -> bool {
+        self.last_entries.last().copied().unwrap_or(true)
+    }
+}
+
+/// Writes an exported tree in some format.
+///
+/// Every method does nothing by default, so a renderer only implements the events
+/// that it needs. [`render`](Renderer::render) can be overridden to write the whole
+/// tree at once.
+pub trait Renderer {
+    /// Writes the tree, calling the other methods for each entry in depth-first order.
+    fn render(&mut self, writer: &mut dyn Write, root: &Node) -> io::Result<()> {
+        self.start(writer, root)?;
+        walk(self, writer, root, &mut Vec::new())?;
+        self.finish(writer, root)?;
+        writer.flush()
+    }
+
+    /// Called before anything else is written.
+    fn start(&mut self, _writer: &mut dyn Write, _root: &Node) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Writes an entry.
+    fn entry(&mut self, _writer: &mut dyn Write, _node: &RenderNode<'_>) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Called after an entry is written, before its children. This isn't called for
+    /// entries without children.
+    fn enter(&mut self, _writer: &mut dyn Write, _node: &RenderNode<'_>) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Called after all of an entry's children are written. This isn't called for
+    /// entries without children.
+    fn leave(&mut self, _writer: &mut dyn Write, _node: &RenderNode<'_>) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Called after everything else is written.
+    fn finish(&mut self, _writer: &mut dyn Write, _root: &Node) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Renders a node and its descendants.
+fn walk<R>(
+    renderer: &mut R,
+    writer: &mut dyn Write,
+    node: &Node,
+    last_entries: &mut Vec<bool>,
+) -> io::Result<()>
+where
+    R: Renderer + ?Sized,
+{
+    renderer.entry(writer, &RenderNode { node, last_entries })?;
+    let Some(children) = node
+        .children
+        .as_deref()
+        .filter(|children| !children.is_empty())
+    else {
+        return Ok(());
+    };
+    renderer.enter(writer, &RenderNode { node, last_entries })?;
+    for (index, child) in children.iter().enumerate() {
+        last_entries.push(index + 1 == children.len());
+        let result = walk(renderer, writer, child, last_entries);
+        last_entries.pop();
+        result?;
+    }
+    renderer.leave(writer, &RenderNode { node, last_entries })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::export::EntryType;
+
+    /// Records the events it receives.
+    struct Recorder;
+
+    impl Renderer for Recorder {
+        fn start(&mut self, writer: &mut dyn Write, root: &Node) -> io::Result<()> {
+            writeln!(writer, "start {}", root.name)
+        }
+
+        fn entry(&mut self, writer: &mut dyn Write, node: &RenderNode<'_>) -> io::Result<()> {
+            writeln!(
+                writer,
+                "entry {} {} {}",
+                node.node.name,
+                node.depth(),
+                node.is_last()
+            )
+        }
+
+        fn enter(&mut self, writer: &mut dyn Write, node: &RenderNode<'_>) -> io::Result<()> {
+            writeln!(writer, "enter {}", node.node.name)
+        }
+
+        fn leave(&mut self, writer: &mut dyn Write, node: &RenderNode<'_>) -> io::Result<()> {
+            writeln!(writer, "leave {}", node.node.name)
+        }
+
+        fn finish(&mut self, writer: &mut dyn Write, root: &Node) -> io::Result<()> {
+            writeln!(writer, "finish {}", root.name)
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let file = Node::new("lib.rs", "src/lib.rs", EntryType::File, None);
+        let src = Node::new("src", "src", EntryType::Directory, Some(vec![file]));
+        let empty = Node::new("empty", "empty", EntryType::Directory, Some(vec![]));
+        let root = Node::new(".", "", EntryType::Directory, Some(vec![src, empty]));
+
+        let mut buf = Vec::new();
+        Recorder.render(&mut buf, &root).unwrap();
+        let expected = [
+            "start .",
+            "entry . 0 true",
+            "enter .",
+            "entry src 1 false",
+            "enter src",
+            "entry lib.rs 2 true",
+            "leave src",
+            "entry empty 1 true",
+            "leave .",
+            "finish .",
+        ];
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(expected.to_vec(), output.lines().collect::<Vec<_>>());
+    }
+}
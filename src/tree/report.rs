@@ -0,0 +1,141 @@
+//! Module for reports about a tree's entries that are written after the tree.
+use crate::i18n::Lang;
+use clap::ValueEnum;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// A kind of report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Report {
+    /// Directories without any entries.
+    Empty,
+    /// Entries that aren't tracked by version control, because they're untracked or
+    /// ignored.
+    Orphans,
+}
+
+/// Why an entry is an orphan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orphan {
+    /// The entry hasn't been added to version control.
+    Untracked,
+    /// The entry is ignored by version control.
+    Ignored,
+}
+
+impl Orphan {
+    /// Gets the ID of the reason's label in the message catalogs.
+    fn message_id(&self) -> &'static str {
+        match self {
+            Self::Untracked => "orphan-untracked",
+            Self::Ignored => "orphan-ignored",
+        }
+    }
+}
+
+/// The entries found for the requested reports.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Findings {
+    /// The empty directories. `None` if they weren't requested.
+    pub empty: Option<Vec<PathBuf>>,
+    /// The orphaned entries. `None` if they weren't requested.
+    ///
+    /// When a whole directory is orphaned, only the directory is included.
+    pub orphans: Option<Vec<(PathBuf, Orphan)>>,
+}
+
+impl Findings {
+    /// Creates empty findings for the requested reports.
+    pub fn new(reports: &[Report]) -> Self {
+        Self {
+            empty: reports.contains(&Report::Empty).then(Vec::new),
+            orphans: reports.contains(&Report::Orphans).then(Vec::new),
+        }
+    }
+
+    /// Writes the findings in the language, with a heading for each report.
+    pub fn write<W>(&self, writer: &mut W, lang: Lang) -> io::Result<()>
+    where
+        W: Write,
+    {
+        if let Some(ref empty) = self.empty {
+            let count = empty.len();
+            writeln!(writer)?;
+            writeln!(
+                writer,
+                "{}",
+                lang.message("report-empty", &[("count", &count)])
+            )?;
+            for path in empty {
+                writeln!(writer, "  {}", path.display())?;
+            }
+        }
+        if let Some(ref orphans) = self.orphans {
+            let count = orphans.len();
+            writeln!(writer)?;
+            writeln!(
+                writer,
+                "{}",
+                lang.message("report-orphans", &[("count", &count)])
+            )?;
+            for (path, orphan) in orphans {
+                let slash = if path.is_dir() { "/" } else { "" };
+                let label = lang.message(orphan.message_id(), &[]);
+                writeln!(writer, "  {}{slash} ({label})", path.display())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds an empty directory.
+    pub(super) fn add_empty(&mut self, path: &Path) {
+        if let Some(ref mut empty) = self.empty {
+            empty.push(path.to_path_buf());
+        }
+    }
+
+    /// Adds an orphaned entry.
+    pub(super) fn add_orphan(&mut self, path: &Path, orphan: Orphan) {
+        if let Some(ref mut orphans) = self.orphans {
+            orphans.push((path.to_path_buf(), orphan));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_requested_only() {
+        let mut findings = Findings::new(&[Report::Orphans]);
+        findings.add_empty(Path::new("empty"));
+        findings.add_orphan(Path::new("build.log"), Orphan::Ignored);
+
+        let mut output = Vec::new();
+        findings.write(&mut output, Lang::En).unwrap();
+        let expected = ["", "Orphans (1):", "  build.log (ignored)", ""].join("\n");
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn test_write_translated() {
+        let mut findings = Findings::new(&[Report::Empty, Report::Orphans]);
+        findings.add_empty(Path::new("empty"));
+        findings.add_orphan(Path::new("build.log"), Orphan::Untracked);
+
+        let mut output = Vec::new();
+        findings.write(&mut output, Lang::Es).unwrap();
+        let expected = [
+            "",
+            "Directorios vacíos (1):",
+            "  empty",
+            "",
+            "Huérfanos (1):",
+            "  build.log (sin seguimiento)",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+}
@@ -0,0 +1,305 @@
+//! Module for file sizes.
+use clap::ValueEnum;
+use mlua::{FromLua, Lua};
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::fs;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// A number of bytes, which can be parsed from human-readable text like `50M`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Size(pub u64);
+
+impl Size {
+    /// The width of a column of sizes, which fits sizes like `1023B` and `10.5K`.
+    pub(crate) const WIDTH: usize = 5;
+
+    /// The binary unit prefixes, from smallest to largest.
+    const UNITS: [char; 5] = ['K', 'M', 'G', 'T', 'P'];
+
+    /// Gets the number of bytes.
+    #[inline]
+    pub const fn bytes(&self) -> u64 {
+        self.0
+    }
+}
+
+impl FromStr for Size {
+    type Err = ParseSizeError;
+
+    /// Parses a size like `512`, `10K`, `1.5MiB`, or `2GB`. Units are binary, so `1K`
+    /// is 1024 bytes.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s
+            .strip_suffix("iB")
+            .or_else(|| s.strip_suffix(['B', 'b']))
+            .unwrap_or(s);
+        let (number, exponent) = match s.char_indices().next_back() {
+            Some((index, unit)) if unit.is_ascii_alphabetic() => {
+                let exponent = Self::UNITS
+                    .iter()
+                    .position(|&u| u.eq_ignore_ascii_case(&unit))
+                    .ok_or(ParseSizeError)?;
+                (&s[..index], exponent as i32 + 1)
+            }
+            _ => (s, 0),
+        };
+        let number: f64 = number.trim().parse().map_err(|_| ParseSizeError)?;
+        if !number.is_finite() || number < 0.0 {
+            return Err(ParseSizeError);
+        }
+        let bytes = number * 1024f64.powi(exponent);
+        Ok(Self(bytes as u64))
+    }
+}
+
+impl Display for Size {
+    /// Writes the size with the largest unit that keeps the number at least 1, like
+    /// `1.5M`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut size = self.0 as f64;
+        let mut unit = None;
+        for u in Self::UNITS {
+            if size < 1024.0 {
+                break;
+            }
+            size /= 1024.0;
+            unit = Some(u);
+        }
+        match unit {
+            None => write!(f, "{}B", self.0),
+            Some(unit) if size < 10.0 => write!(f, "{size:.1}{unit}"),
+            Some(unit) => write!(f, "{size:.0}{unit}"),
+        }
+    }
+}
+
+impl FromLua for Size {
+    /// Converts from a number of bytes, or from text like `"50M"`.
+    fn from_lua(value: mlua::Value, lua: &Lua) -> mlua::Result<Self> {
+        if let Some(s) = value.as_string() {
+            return s.to_str()?.parse().map_err(mlua::Error::external);
+        }
+        u64::from_lua(value, lua).map(Self)
+    }
+}
+
+/// Controls which entries have their sizes displayed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SizeMode {
+    /// Don't display sizes.
+    #[default]
+    Off,
+    /// Display the sizes of files.
+    Files,
+    /// Display the sizes of files, and the total sizes of directories' contents.
+    Recursive,
+}
+
+impl FromLua for SizeMode {
+    fn from_lua(value: mlua::Value, _lua: &Lua) -> mlua::Result<Self> {
+        const VALID_VALUES: [&str; 3] = ["off", "files", "recursive"];
+        let type_name = value.type_name();
+        let make_conversion_error = || mlua::Error::FromLuaConversionError {
+            from: type_name,
+            to: String::from("SizeMode"),
+            message: Some(format!("Must be one of {VALID_VALUES:?} or nil")),
+        };
+        let size_mode = value
+            .as_string()
+            .ok_or_else(make_conversion_error)?
+            .to_string_lossy();
+        let size_mode = match size_mode.as_str() {
+            "off" => Self::Off,
+            "files" => Self::Files,
+            "recursive" => Self::Recursive,
+            _ => return Err(make_conversion_error()),
+        };
+        Ok(size_mode)
+    }
+}
+
+/// Gets the total size of a path, including everything inside it if it's a directory.
+///
+/// Symlinks aren't followed, and entries that can't be read are skipped.
+pub(crate) fn total_size(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| total_size(&entry.path()))
+        .sum()
+}
+
+/// The total sizes of a directory and of every directory inside it, which are
+/// computed in one walk instead of walking each directory when its size is needed.
+#[derive(Debug, Default)]
+pub(crate) struct DiskUsage {
+    /// The total size of each directory, by path.
+    totals: HashMap<PathBuf, u64>,
+}
+
+impl DiskUsage {
+    /// The most threads that walk directories.
+    const MAX_WORKERS: usize = 8;
+
+    /// Walks a directory like [`total_size`]. Its entries are split between threads,
+    /// since walking a large tree is mostly waiting on the filesystem.
+    pub(crate) fn new(root: &Path) -> Self {
+        let Ok(entries) = fs::read_dir(root) else {
+            return Self::default();
+        };
+        let paths = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .collect::<Vec<_>>();
+        let next = AtomicUsize::new(0);
+        let work = || {
+            let mut totals = HashMap::new();
+            let mut total = 0;
+            while let Some(path) = paths.get(next.fetch_add(1, Ordering::Relaxed)) {
+                total += walk(path, &mut totals);
+            }
+            (totals, total)
+        };
+        let workers = thread::available_parallelism()
+            .map_or(1, NonZeroUsize::get)
+            .min(Self::MAX_WORKERS);
+
+        let mut totals = HashMap::new();
+        let mut total = 0;
+        thread::scope(|scope| {
+            // NOTE This thread works too, so the walk finishes even if no threads
+            //      can be started.
+            let handles = (1..workers)
+                .filter_map(|_| thread::Builder::new().spawn_scoped(scope, work).ok())
+                .collect::<Vec<_>>();
+            let results = handles
+                .into_iter()
+                .filter_map(|handle| handle.join().ok())
+                .chain([work()]);
+            for (walked, walked_total) in results {
+                totals.extend(walked);
+                total += walked_total;
+            }
+        });
+        totals.insert(root.to_path_buf(), total);
+        Self { totals }
+    }
+
+    /// Gets the total size of a directory that was walked.
+    #[inline]
+    pub(crate) fn get(&self, path: &Path) -> Option<u64> {
+        self.totals.get(path).copied()
+    }
+}
+
+/// Gets the total size of a path like [`total_size`], and adds the total sizes of
+/// the directories to `totals`.
+fn walk(path: &Path, totals: &mut HashMap<PathBuf, u64>) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    let total = fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| walk(&entry.path(), totals))
+                .sum()
+        })
+        .unwrap_or(0);
+    totals.insert(path.to_path_buf(), total);
+    total
+}
+
+/// Error for an invalid [`Size`] value.
+#[derive(Debug)]
+pub struct ParseSizeError;
+
+impl fmt::Display for ParseSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expected an amount of bytes, optionally with a unit (K, M, G, T, or P), like 50M"
+        )
+    }
+}
+
+impl std::error::Error for ParseSizeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("512", 512)]
+    #[case("10K", 10 * 1024)]
+    #[case("50M", 50 * 1024 * 1024)]
+    #[case("50mb", 50 * 1024 * 1024)]
+    #[case("1.5MiB", 3 * 512 * 1024)]
+    #[case("2G", 2 * 1024 * 1024 * 1024)]
+    #[case("100B", 100)]
+    fn test_parse(#[case] s: &str, #[case] expected: u64) {
+        assert_eq!(Size(expected), s.parse().unwrap());
+    }
+
+    #[rstest]
+    #[case("return 2048", 2048)]
+    #[case(r#"return "2K""#, 2048)]
+    fn test_from_lua(#[case] chunk: &str, #[case] expected: u64) {
+        let lua = Lua::new();
+        assert_eq!(Size(expected), lua.load(chunk).eval::<Size>().unwrap());
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("M")]
+    #[case("10X")]
+    #[case("-1K")]
+    fn test_parse_invalid(#[case] s: &str) {
+        assert!(s.parse::<Size>().is_err());
+    }
+
+    #[test]
+    fn test_disk_usage() {
+        let fixture = crate::testing::Fixture::builder()
+            .file("a/b/file", "12345")
+            .file("a/file", "123")
+            .file("file", "1")
+            .dir("empty")
+            .build()
+            .unwrap();
+        let usage = DiskUsage::new(fixture.path());
+
+        assert_eq!(Some(9), usage.get(fixture.path()));
+        assert_eq!(Some(8), usage.get(&fixture.join("a")));
+        assert_eq!(Some(5), usage.get(&fixture.join("a/b")));
+        assert_eq!(Some(0), usage.get(&fixture.join("empty")));
+        assert_eq!(None, usage.get(&fixture.join("file")));
+        assert_eq!(9, total_size(fixture.path()));
+    }
+
+    #[rstest]
+    #[case(512, "512B")]
+    #[case(1536, "1.5K")]
+    #[case(50 * 1024 * 1024, "50M")]
+    fn test_display(#[case] bytes: u64, #[case] expected: &str) {
+        assert_eq!(expected, Size(bytes).to_string());
+    }
+}
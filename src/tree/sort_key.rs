@@ -0,0 +1,19 @@
+//! Module for sorting flat listings across the whole tree.
+use clap::ValueEnum;
+
+/// What to sort a flat listing by. Unlike the tree's sorting, which orders the
+/// entries of each directory, this orders every entry in the tree at once, like the
+/// largest files anywhere in the tree.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    /// The order that the tree is written in.
+    #[default]
+    Path,
+    /// File names, sorted like the entries in a directory.
+    Name,
+    /// File sizes. Directories are sorted by the total sizes of their contents
+    /// with `--size=recursive`, and are otherwise smaller than any file.
+    Size,
+    /// Modification times, oldest first.
+    Mtime,
+}
@@ -0,0 +1,91 @@
+//! Module for counting the entries in a written tree.
+use super::Size;
+use crate::i18n::Lang;
+use mlua::{IntoLua, Lua};
+use std::fmt::{self, Display};
+
+/// The numbers of directories and files that were written, not including the root.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// The number of directories.
+    pub directories: usize,
+    /// The number of files, including symlinks.
+    pub files: usize,
+    /// The total size of the tree in bytes, when it was computed, like with
+    /// `--du`. This includes entries that weren't written.
+    pub total_size: Option<u64>,
+}
+
+impl Stats {
+    /// Gets a summary like `2 directories, 1 file` in the language, followed by the
+    /// total size if it's known.
+    pub fn summary(&self, lang: Lang) -> String {
+        let summary = lang.message(
+            "summary",
+            &[("directories", &self.directories), ("files", &self.files)],
+        );
+        match self.total_size {
+            Some(size) => lang.message(
+                "summary-total-size",
+                &[("summary", &summary), ("size", &Size(size))],
+            ),
+            None => summary,
+        }
+    }
+}
+
+impl Display for Stats {
+    /// Writes a summary like `2 directories, 1 file` in English.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.summary(Lang::En))
+    }
+}
+
+impl IntoLua for Stats {
+    fn into_lua(self, lua: &Lua) -> mlua::Result<mlua::Value> {
+        let table = lua.create_table()?;
+        table.set("directories", self.directories)?;
+        table.set("files", self.files)?;
+        table.set("total_size", self.total_size)?;
+        table.into_lua(lua)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0, 0, "0 directories, 0 files")]
+    #[case(1, 1, "1 directory, 1 file")]
+    #[case(2, 3, "2 directories, 3 files")]
+    fn test_display(#[case] directories: usize, #[case] files: usize, #[case] expected: &str) {
+        let stats = Stats {
+            directories,
+            files,
+            total_size: None,
+        };
+        assert_eq!(expected, stats.to_string());
+    }
+
+    #[test]
+    fn test_display_total_size() {
+        let stats = Stats {
+            directories: 1,
+            files: 2,
+            total_size: Some(1536),
+        };
+        assert_eq!("1 directory, 2 files, 1.5K in total", stats.to_string());
+    }
+
+    #[test]
+    fn test_summary() {
+        let stats = Stats {
+            directories: 1,
+            files: 2,
+            total_size: None,
+        };
+        assert_eq!("1 directorio, 2 archivos", stats.summary(Lang::Es));
+    }
+}
@@ -0,0 +1,151 @@
+//! Module for suggesting `.gitignore` entries for build artifacts.
+use super::Size;
+use std::fmt::{self, Display};
+use std::path::Path;
+
+/// A common kind of build artifact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Artifact {
+    /// A directory with this name.
+    Directory(&'static str),
+    /// A file with this extension.
+    Extension(&'static str),
+}
+
+impl Artifact {
+    /// Common build artifacts.
+    pub const COMMON: &[Self] = &[
+        Self::Directory("target"),
+        Self::Directory("node_modules"),
+        Self::Directory("__pycache__"),
+        Self::Directory("build"),
+        Self::Directory("dist"),
+        Self::Directory(".venv"),
+        Self::Directory(".gradle"),
+        Self::Directory(".next"),
+        Self::Directory("coverage"),
+        Self::Extension("o"),
+        Self::Extension("obj"),
+        Self::Extension("a"),
+        Self::Extension("so"),
+        Self::Extension("dylib"),
+        Self::Extension("dll"),
+        Self::Extension("exe"),
+        Self::Extension("class"),
+        Self::Extension("pyc"),
+        Self::Extension("log"),
+    ];
+
+    /// Finds the artifact that a path looks like.
+    pub fn find(path: &Path, is_dir: bool) -> Option<Self> {
+        let matches = |artifact: &&Self| match **artifact {
+            Self::Directory(name) => is_dir && path.file_name().is_some_and(|n| n == name),
+            Self::Extension(extension) => {
+                !is_dir && path.extension().is_some_and(|e| e == extension)
+            }
+        };
+        Self::COMMON.iter().find(matches).copied()
+    }
+}
+
+impl Display for Artifact {
+    /// Writes the artifact as a `.gitignore` pattern.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Directory(name) => write!(f, "{name}/"),
+            Self::Extension(extension) => write!(f, "*.{extension}"),
+        }
+    }
+}
+
+/// A suggested `.gitignore` entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// The kind of artifact that was found.
+    pub artifact: Artifact,
+    /// How many untracked matches were found.
+    pub count: usize,
+    /// The total size of the matches.
+    pub size: Size,
+}
+
+impl Suggestion {
+    /// Writes suggestions as `.gitignore` lines, with the largest first.
+    pub fn write_all<W>(writer: &mut W, suggestions: &mut [Self]) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        suggestions.sort_by(|left, right| right.size.cmp(&left.size));
+        writeln!(writer)?;
+        writeln!(writer, "# Suggested .gitignore lines")?;
+        for suggestion in suggestions.iter() {
+            let Self {
+                artifact,
+                count,
+                size,
+            } = suggestion;
+            let noun = match (artifact, count) {
+                (Artifact::Directory(_), 1) => "directory",
+                (Artifact::Directory(_), _) => "directories",
+                (Artifact::Extension(_), 1) => "file",
+                (Artifact::Extension(_), _) => "files",
+            };
+            writeln!(writer, "# {count} untracked {noun}, {size}")?;
+            writeln!(writer, "{artifact}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Adds a match to the list of suggestions.
+pub(super) fn add(suggestions: &mut Vec<Suggestion>, artifact: Artifact, size: Size) {
+    match suggestions.iter_mut().find(|s| s.artifact == artifact) {
+        Some(suggestion) => {
+            suggestion.count += 1;
+            suggestion.size = Size(suggestion.size.bytes() + size.bytes());
+        }
+        None => suggestions.push(Suggestion {
+            artifact,
+            count: 1,
+            size,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("project/target", true, Some(Artifact::Directory("target")))]
+    #[case("project/target", false, None)]
+    #[case("src/main.o", false, Some(Artifact::Extension("o")))]
+    #[case("src/main.o", true, None)]
+    #[case("src/main.rs", false, None)]
+    fn test_find(#[case] path: &str, #[case] is_dir: bool, #[case] expected: Option<Artifact>) {
+        assert_eq!(expected, Artifact::find(Path::new(path), is_dir));
+    }
+
+    #[test]
+    fn test_write_all() {
+        let mut suggestions = Vec::new();
+        add(&mut suggestions, Artifact::Extension("o"), Size(512));
+        add(&mut suggestions, Artifact::Directory("target"), Size(2048));
+        add(&mut suggestions, Artifact::Extension("o"), Size(512));
+
+        let mut output = Vec::new();
+        Suggestion::write_all(&mut output, &mut suggestions).unwrap();
+        let expected = [
+            "",
+            "# Suggested .gitignore lines",
+            "# 1 untracked directory, 2.0K",
+            "target/",
+            "# 2 untracked files, 1.0K",
+            "*.o",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+}
@@ -0,0 +1,155 @@
+//! Module for configuring a tree's appearance without Lua.
+use super::Entry;
+use super::entry::Attributes;
+use crate::config;
+use crate::icons;
+use crate::vcs::status::Status;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The icons for entries.
+///
+/// Icons are chosen from the file name, then the extension, then the built-in icons
+/// for the path, and finally the icon for the kind of entry.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct IconTheme {
+    /// Icons for exact file names, like `Cargo.toml`.
+    pub names: HashMap<String, String>,
+    /// Icons for extensions without the leading `.`, like `rs`.
+    pub extensions: HashMap<String, String>,
+    /// Use the built-in icons for paths that don't match a name or extension.
+    pub builtin: bool,
+    /// The icon for files.
+    pub file: String,
+    /// The icon for executables.
+    pub executable: String,
+    /// The icon for directories.
+    pub directory: String,
+    /// The icon for empty directories.
+    pub empty_directory: String,
+    /// The icon for symlinks.
+    pub symlink: String,
+    /// The icon for symlinks whose targets don't exist.
+    pub broken_symlink: String,
+}
+
+impl IconTheme {
+    /// Gets the icon for an entry.
+    pub fn get<P>(&self, entry: &Entry<P>) -> &str
+    where
+        P: AsRef<Path>,
+    {
+        let path = entry.path();
+        let name = path.file_name().and_then(|name| name.to_str());
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        name.and_then(|name| self.names.get(name))
+            .or_else(|| extension.and_then(|extension| self.extensions.get(extension)))
+            .map(String::as_str)
+            .or_else(|| self.builtin.then(|| icons::for_path(path)).flatten())
+            .unwrap_or_else(|| self.for_kind(entry))
+    }
+
+    /// Gets the icon for the kind of entry.
+    fn for_kind<P>(&self, entry: &Entry<P>) -> &str
+    where
+        P: AsRef<Path>,
+    {
+        match entry.attributes() {
+            Attributes::Directory(attributes) if attributes.is_empty() => &self.empty_directory,
+            Attributes::Directory(_) => &self.directory,
+            Attributes::File(attributes) if attributes.is_executable() => &self.executable,
+            Attributes::File(attributes) => attributes
+                .language()
+                .filter(|_| self.builtin)
+                .and_then(|language| language.nerd_font_glyph())
+                .unwrap_or(&self.file),
+            Attributes::Symlink(attributes) if attributes.is_broken() => &self.broken_symlink,
+            Attributes::Symlink(_) => &self.symlink,
+        }
+    }
+}
+
+impl Default for IconTheme {
+    /// The same icons as the default icon configuration.
+    fn default() -> Self {
+        Self {
+            names: HashMap::new(),
+            extensions: HashMap::new(),
+            builtin: true,
+            file: String::from(config::Icons::DEFAULT_FILE_ICON),
+            executable: String::from(config::Icons::DEFAULT_EXECUTABLE_ICON),
+            directory: String::from(config::Icons::DEFAULT_DIRECTORY_ICON),
+            empty_directory: String::from(config::Icons::DEFAULT_EMPTY_DIRECTORY_ICON),
+            symlink: String::from(config::Icons::DEFAULT_SYMLINK_ICON),
+            broken_symlink: String::from(config::Icons::DEFAULT_BROKEN_SYMLINK_ICON),
+        }
+    }
+}
+
+/// The characters for version control statuses.
+///
+/// Each glyph should be a single column wide, so that names stay aligned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct StatusGlyphs {
+    /// The glyph for added files.
+    pub added: char,
+    /// The glyph for modified files.
+    pub modified: char,
+    /// The glyph for removed files.
+    pub removed: char,
+    /// The glyph for renamed files.
+    pub renamed: char,
+}
+
+impl StatusGlyphs {
+    /// Gets the glyph for a status.
+    pub(crate) const fn get(&self, status: Status) -> char {
+        match status {
+            Status::Added => self.added,
+            Status::Modified => self.modified,
+            Status::Removed => self.removed,
+            Status::Renamed => self.renamed,
+        }
+    }
+}
+
+impl Default for StatusGlyphs {
+    fn default() -> Self {
+        Self {
+            added: '+',
+            modified: '~',
+            removed: '-',
+            renamed: 'R',
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[rstest]
+    #[case("Cargo.toml", "name")]
+    #[case("main.rs", "extension")]
+    #[case("README", "file")]
+    fn test_icon_theme(#[case] file_name: &str, #[case] expected: &str) {
+        let root = TempDir::with_prefix("fancy-tree-").unwrap();
+        let path = root.path().join(file_name);
+        fs::write(&path, "").unwrap();
+        let entry = Entry::new(path).unwrap();
+
+        let theme = IconTheme {
+            names: HashMap::from([(String::from("Cargo.toml"), String::from("name"))]),
+            extensions: HashMap::from([(String::from("rs"), String::from("extension"))]),
+            builtin: false,
+            file: String::from("file"),
+            ..Default::default()
+        };
+        assert_eq!(expected, theme.get(&entry));
+    }
+}
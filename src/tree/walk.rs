@@ -0,0 +1,334 @@
+//! Module for the search of a tree that every format is written from.
+use super::ancestors::Ancestors;
+use super::{Entry, Tree};
+use crate::color::Style;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+/// The order that a [`Walk`] visits entries in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Order {
+    /// Each directory's entries are visited right after it.
+    DepthFirst,
+    /// The entries at each depth are visited before the entries at the next depth.
+    BreadthFirst,
+}
+
+/// What a [`Node`] visits.
+pub(super) enum Visit {
+    /// An entry of the tree.
+    Entry {
+        /// The entry.
+        entry: Box<Entry<PathBuf>>,
+        /// The entry's tint, which the entries inside it inherit.
+        tint: Option<Style>,
+        /// Is the entry a directory that's one of its own ancestors? These aren't
+        /// traversed, since they would repeat forever.
+        is_recursive: bool,
+        /// Were the entry's children searched? This is `false` for files and for
+        /// directories at the maximum level.
+        is_traversed: bool,
+    },
+    /// A path that was deleted but is still tracked.
+    Deleted {
+        /// The deleted path.
+        path: PathBuf,
+        /// Was the path a directory, with deleted paths inside it?
+        is_directory: bool,
+        /// The tint of the directory that the path was in.
+        tint: Option<Style>,
+    },
+    /// The entries of a directory that aren't visited because of the file limit.
+    Hidden {
+        /// The directory.
+        directory: PathBuf,
+        /// How many entries aren't visited.
+        count: usize,
+    },
+}
+
+/// A step of a [`Walk`].
+pub(super) struct Node {
+    /// How many levels below the root the node is. The root is at depth `0`.
+    pub depth: usize,
+    /// Is this the last node in its directory?
+    pub is_last: bool,
+    /// The directory that the node is in, when it's the first node in it, so that
+    /// the directory can be written before its entries.
+    pub directory: Option<PathBuf>,
+    /// What the node visits.
+    pub visit: Visit,
+}
+
+/// Searches a [`Tree`], visiting its entries in the order that they're written.
+///
+/// This is where entries are skipped, sorted, limited by level and by the file
+/// limit, and checked for loops, and where deleted paths are added, so that every
+/// format gets the same entries.
+pub(super) struct Walk<'tree, 'vcs, 'charset, P: AsRef<Path>> {
+    /// The tree that's searched.
+    tree: &'tree Tree<'vcs, 'charset, P>,
+    /// The order that entries are visited in.
+    order: Order,
+    /// Should each directory's entries be annotated and prefetched when they're
+    /// found, since they'll be written soon?
+    prepare: bool,
+    /// The root, until it's visited.
+    root: Option<Entry<PathBuf>>,
+    /// The directories whose entries haven't all been visited.
+    frames: VecDeque<Frame>,
+    /// Did the last node add a directory for its entries?
+    expanded: bool,
+}
+
+/// A directory whose entries are being visited.
+struct Frame {
+    /// The directory's path.
+    directory: PathBuf,
+    /// The depth of the directory's entries.
+    depth: usize,
+    /// The entries that haven't been visited.
+    items: VecDeque<Item>,
+    /// The directory and the directories that contain it.
+    ancestors: Ancestors,
+    /// The directory's tint.
+    tint: Option<Style>,
+    /// Has an entry been visited?
+    is_started: bool,
+}
+
+/// Something in a directory that hasn't been visited.
+enum Item {
+    /// An entry.
+    Entry(Box<Entry<PathBuf>>),
+    /// A deleted path.
+    Deleted(PathBuf),
+    /// The number of entries that aren't visited because of the file limit.
+    Hidden(usize),
+}
+
+impl Visit {
+    /// Gets how many entries the node stands for, like in a count of the entries
+    /// that weren't written.
+    pub fn count(&self) -> usize {
+        match self {
+            Self::Hidden { count, .. } => *count,
+            Self::Entry { .. } | Self::Deleted { .. } => 1,
+        }
+    }
+}
+
+impl Item {
+    /// Gets how many entries the item stands for.
+    fn count(&self) -> usize {
+        match self {
+            Self::Hidden(count) => *count,
+            Self::Entry(_) | Self::Deleted(_) => 1,
+        }
+    }
+}
+
+impl<'tree, 'vcs, 'charset, P> Walk<'tree, 'vcs, 'charset, P>
+where
+    P: AsRef<Path>,
+{
+    /// Creates a walk that starts at an entry, which is at depth `0`.
+    pub fn new(tree: &'tree Tree<'vcs, 'charset, P>, root: Entry<PathBuf>, order: Order) -> Self {
+        Self {
+            tree,
+            order,
+            prepare: false,
+            root: Some(root),
+            frames: VecDeque::new(),
+            expanded: false,
+        }
+    }
+
+    /// Annotates and prefetches each directory's entries when they're found, for
+    /// walks whose entries are written as they're visited.
+    #[inline]
+    #[must_use]
+    pub fn prepared(self) -> Self {
+        Self {
+            prepare: true,
+            ..self
+        }
+    }
+
+    /// Gets how many entries are left in the directory of the last node, not
+    /// including the last node.
+    pub fn remaining(&self) -> usize {
+        self.current()
+            .map_or(0, |frame| frame.items.iter().map(Item::count).sum())
+    }
+
+    /// Gets how many directories still have entries that haven't been visited,
+    /// including the last node's entries.
+    pub fn open_directories(&self) -> usize {
+        self.frames
+            .iter()
+            .filter(|frame| !frame.items.is_empty())
+            .count()
+    }
+
+    /// Skips the rest of the directory of the last node, and the last node's
+    /// entries, like when the maximum number of lines have been written.
+    pub fn skip_directory(&mut self) {
+        if self.expanded {
+            self.frames.pop_back();
+            self.expanded = false;
+        }
+        let frame = match self.order {
+            Order::DepthFirst => self.frames.back_mut(),
+            Order::BreadthFirst => self.frames.front_mut(),
+        };
+        if let Some(frame) = frame {
+            frame.items.clear();
+        }
+    }
+
+    /// Gets the directory of the last node.
+    fn current(&self) -> Option<&Frame> {
+        match self.order {
+            Order::DepthFirst => self.frames.iter().rev().nth(usize::from(self.expanded)),
+            Order::BreadthFirst => self.frames.front(),
+        }
+    }
+
+    /// Visits an entry, adding its entries to the walk if it's a directory that's
+    /// traversed.
+    fn visit_entry(
+        &mut self,
+        entry: Entry<PathBuf>,
+        depth: usize,
+        ancestors: &Ancestors,
+        inherited: Option<Style>,
+    ) -> Visit {
+        let tree = self.tree;
+        let tint = tree.tint(&entry, inherited);
+        let inner = tree.enter(&entry, depth, ancestors);
+        let is_recursive = inner.is_none();
+        let children = inner.and_then(|ancestors| {
+            let children = tree.children(&entry, depth, &ancestors)?;
+            Some((children, ancestors))
+        });
+        let is_traversed = children.is_some();
+        if let Some((mut children, ancestors)) = children {
+            let count = children.len();
+            let shown = tree
+                .file_limit_for(&entry)
+                .map_or(count, |limit| tree.file_limit_mode.shown(count, limit));
+            children.truncate(shown);
+            if self.prepare {
+                tree.annotate(children.iter().map(Entry::path));
+                tree.prefetch(&children, depth + 1);
+            }
+            let deleted = tree.deleted_children(entry.path());
+            let hidden = (shown < count).then_some(Item::Hidden(count - shown));
+            let items = children
+                .into_iter()
+                .map(|child| Item::Entry(Box::new(child)))
+                .chain(deleted.into_iter().map(Item::Deleted))
+                .chain(hidden)
+                .collect();
+            self.push_frame(Frame {
+                directory: entry.path().to_path_buf(),
+                depth: depth + 1,
+                items,
+                ancestors,
+                tint,
+                is_started: false,
+            });
+        }
+        Visit::Entry {
+            entry: Box::new(entry),
+            tint,
+            is_recursive,
+            is_traversed,
+        }
+    }
+
+    /// Visits a deleted path, adding the deleted paths inside it to the walk.
+    fn visit_deleted(&mut self, path: PathBuf, depth: usize, tint: Option<Style>) -> Visit {
+        let children = self.tree.deleted_children(&path);
+        let is_directory = !children.is_empty();
+        if is_directory && !self.tree.is_max_level(depth) {
+            self.push_frame(Frame {
+                directory: path.clone(),
+                depth: depth + 1,
+                items: children.into_iter().map(Item::Deleted).collect(),
+                ancestors: Ancestors::default(),
+                tint,
+                is_started: false,
+            });
+        }
+        Visit::Deleted {
+            path,
+            is_directory,
+            tint,
+        }
+    }
+
+    /// Adds a directory's entries to the walk, if it has any.
+    fn push_frame(&mut self, frame: Frame) {
+        if !frame.items.is_empty() {
+            self.frames.push_back(frame);
+            self.expanded = true;
+        }
+    }
+}
+
+impl<P> Iterator for Walk<'_, '_, '_, P>
+where
+    P: AsRef<Path>,
+{
+    type Item = Node;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.expanded = false;
+        if let Some(root) = self.root.take() {
+            let visit = self.visit_entry(root, 0, &Ancestors::default(), None);
+            return Some(Node {
+                depth: 0,
+                is_last: true,
+                directory: None,
+                visit,
+            });
+        }
+        loop {
+            let frame = match self.order {
+                Order::DepthFirst => self.frames.back_mut(),
+                Order::BreadthFirst => self.frames.front_mut(),
+            }?;
+            let Some(item) = frame.items.pop_front() else {
+                match self.order {
+                    Order::DepthFirst => self.frames.pop_back(),
+                    Order::BreadthFirst => self.frames.pop_front(),
+                };
+                continue;
+            };
+            let is_last = frame.items.is_empty();
+            let directory = (!frame.is_started).then(|| frame.directory.clone());
+            frame.is_started = true;
+            let depth = frame.depth;
+            let tint = frame.tint;
+            let visit = match item {
+                Item::Entry(entry) => {
+                    let ancestors = frame.ancestors.clone();
+                    self.visit_entry(*entry, depth, &ancestors, tint)
+                }
+                Item::Deleted(path) => self.visit_deleted(path, depth, tint),
+                Item::Hidden(count) => Visit::Hidden {
+                    directory: frame.directory.clone(),
+                    count,
+                },
+            };
+            return Some(Node {
+                depth,
+                is_last,
+                directory,
+                visit,
+            });
+        }
+    }
+}
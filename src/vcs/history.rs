@@ -0,0 +1,264 @@
+//! Module for summarizing the commit history of files.
+use crate::duration::{self, ParseDurationError};
+use chrono::{NaiveDate, NaiveTime};
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The number of commits that touched each file.
+#[derive(Debug, Default)]
+pub struct Churn {
+    /// Commit counts for paths relative to the repository's root.
+    counts: HashMap<PathBuf, usize>,
+    /// The highest count, used for scaling.
+    max: usize,
+}
+
+impl Churn {
+    /// Counts a commit that touched the path.
+    pub fn add<P>(&mut self, path: P)
+    where
+        P: Into<PathBuf>,
+    {
+        let count = self.counts.entry(path.into()).or_default();
+        *count += 1;
+        self.max = self.max.max(*count);
+    }
+
+    /// Gets the number of commits that touched the path.
+    ///
+    /// The path should be relative to the repository's root.
+    pub fn get<P>(&self, path: P) -> Option<usize>
+    where
+        P: AsRef<Path>,
+    {
+        self.counts.get(path.as_ref()).copied()
+    }
+
+    /// Gets how hot a count is compared to the highest count, from `0.0` to `1.0`.
+    pub fn heat(&self, count: usize) -> f64 {
+        if self.max == 0 {
+            0.0
+        } else {
+            count as f64 / self.max as f64
+        }
+    }
+
+    /// Gets the width needed to display any count.
+    pub fn width(&self) -> usize {
+        self.max.to_string().len()
+    }
+}
+
+/// The time of the most recent commit that touched each file.
+#[derive(Debug, Default)]
+pub struct LastCommits {
+    /// Commit times, in seconds since the Unix epoch, for paths relative to the
+    /// repository's root.
+    times: HashMap<PathBuf, i64>,
+    /// The oldest and newest times of all files, which are found from the final
+    /// times, since a file's time changes when a more recent commit is added.
+    range: OnceCell<(i64, i64)>,
+}
+
+impl LastCommits {
+    /// Records a commit time for the path, keeping it if it's the most recent one.
+    /// Commits can be added in any order.
+    pub fn add<P>(&mut self, path: P, time: i64)
+    where
+        P: Into<PathBuf>,
+    {
+        let last = self.times.entry(path.into()).or_insert(time);
+        *last = (*last).max(time);
+        self.range.take();
+    }
+
+    /// Gets the time of the most recent commit that touched the path.
+    ///
+    /// The path should be relative to the repository's root.
+    pub fn get<P>(&self, path: P) -> Option<i64>
+    where
+        P: AsRef<Path>,
+    {
+        self.times.get(path.as_ref()).copied()
+    }
+
+    /// Gets how stale a time is compared to the newest and oldest times, from `0.0`
+    /// (newest) to `1.0` (oldest).
+    pub fn staleness(&self, time: i64) -> f64 {
+        let (oldest, newest) = *self.range.get_or_init(|| {
+            let oldest = self.times.values().copied().min().unwrap_or_default();
+            let newest = self.times.values().copied().max().unwrap_or_default();
+            (oldest, newest)
+        });
+        let range = newest - oldest;
+        if range <= 0 {
+            0.0
+        } else {
+            (newest - time) as f64 / range as f64
+        }
+    }
+}
+
+/// The start of a window of time to count commits in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Since(i64);
+
+impl Since {
+    /// Gets the time in seconds since the Unix epoch.
+    #[inline]
+    pub fn seconds(&self) -> i64 {
+        self.0
+    }
+
+    /// Parses a relative time like `30d` into the number of seconds it spans.
+    fn parse_relative(s: &str) -> Result<i64, ParseDurationError> {
+        let span = duration::parse(s)?;
+        i64::try_from(span.as_secs()).map_err(|_| ParseDurationError::Invalid)
+    }
+
+    /// Checks if the text is meant to be a date, because it starts with a year and a
+    /// `-`, so that an invalid date isn't reported as an invalid amount of time.
+    fn is_date(s: &str) -> bool {
+        s.split_once('-')
+            .is_some_and(|(year, _)| !year.is_empty() && year.bytes().all(|b| b.is_ascii_digit()))
+    }
+
+    /// Parses a `YYYY-MM-DD` date into seconds since the Unix epoch, at midnight UTC.
+    fn parse_date(s: &str) -> Option<i64> {
+        let date = s.parse::<NaiveDate>().ok()?;
+        Some(date.and_time(NaiveTime::MIN).and_utc().timestamp())
+    }
+}
+
+impl FromStr for Since {
+    type Err = ParseSinceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if Self::is_date(s) {
+            return Self::parse_date(s).map(Self).ok_or(ParseSinceError::Date);
+        }
+        let span = Self::parse_relative(s).map_err(ParseSinceError::Duration)?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs() as i64)
+            .unwrap_or_default();
+        Ok(Self(now - span))
+    }
+}
+
+/// Error for an invalid [`Since`] value.
+#[derive(Debug)]
+pub enum ParseSinceError {
+    /// The value looks like a date, but it isn't a valid `YYYY-MM-DD` date, like
+    /// `2024-02-31`.
+    Date,
+    /// The value isn't a valid amount of time.
+    Duration(ParseDurationError),
+}
+
+impl fmt::Display for ParseSinceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Date => write!(f, "expected a valid date (YYYY-MM-DD), like 2024-02-29"),
+            Self::Duration(ParseDurationError::Invalid) => write!(
+                f,
+                "expected a date (YYYY-MM-DD) or an amount of time, like 30d, 2w, 6mo, or 1y"
+            ),
+            Self::Duration(error @ ParseDurationError::Ambiguous) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ParseSinceError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("1970-01-01", 0)]
+    #[case("2000-03-01", 951868800)]
+    #[case("2024-02-29", 1709164800)]
+    fn test_since_date(#[case] s: &str, #[case] expected: i64) {
+        assert_eq!(Since(expected), s.parse().unwrap());
+    }
+
+    #[rstest]
+    #[case("1d", 86400)]
+    #[case("2w", 86400 * 14)]
+    #[case("1mo", 86400 * 30)]
+    #[case("1y", 86400 * 365)]
+    #[case("12h", 60 * 60 * 12)]
+    fn test_since_relative(#[case] s: &str, #[case] expected: i64) {
+        assert_eq!(Ok(expected), Since::parse_relative(s));
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("d")]
+    #[case("10")]
+    #[case("10x")]
+    #[case("1m")]
+    #[case("-1d")]
+    #[case("2024-13-01")]
+    #[case("2024-02-31")]
+    #[case("2023-02-29")]
+    #[case("2024-01-01x")]
+    #[case("2024-")]
+    fn test_since_invalid(#[case] s: &str) {
+        assert!(s.parse::<Since>().is_err());
+    }
+
+    #[rstest]
+    #[case("2024-02-31")]
+    #[case("2024-00-10")]
+    fn test_since_invalid_date(#[case] s: &str) {
+        assert!(matches!(s.parse::<Since>(), Err(ParseSinceError::Date)));
+    }
+
+    #[test]
+    fn test_churn() {
+        let mut churn = Churn::default();
+        churn.add("a");
+        churn.add("a");
+        churn.add("b");
+        assert_eq!(Some(2), churn.get("a"));
+        assert_eq!(Some(1), churn.get("b"));
+        assert_eq!(None, churn.get("c"));
+        assert_eq!(0.5, churn.heat(1));
+    }
+
+    #[rstest]
+    #[case::newest_first(&[("a", 200), ("a", 100), ("b", 100)])]
+    #[case::oldest_first(&[("a", 100), ("b", 100), ("a", 200)])]
+    #[case::interleaved(&[("b", 100), ("a", 50), ("a", 200)])]
+    fn test_last_commits_order(#[case] commits: &[(&str, i64)]) {
+        let mut last_commits = LastCommits::default();
+        for &(path, time) in commits {
+            last_commits.add(path, time);
+        }
+        assert_eq!(Some(200), last_commits.get("a"));
+        assert_eq!(Some(100), last_commits.get("b"));
+        assert_eq!(0.0, last_commits.staleness(200));
+        assert_eq!(1.0, last_commits.staleness(100));
+    }
+
+    #[test]
+    fn test_last_commits() {
+        let mut last_commits = LastCommits::default();
+        last_commits.add("a", 300);
+        last_commits.add("b", 200);
+        last_commits.add("a", 100);
+        last_commits.add("c", 100);
+        assert_eq!(Some(300), last_commits.get("a"));
+        assert_eq!(Some(200), last_commits.get("b"));
+        assert_eq!(0.0, last_commits.staleness(300));
+        assert_eq!(0.5, last_commits.staleness(200));
+        assert_eq!(1.0, last_commits.staleness(100));
+    }
+}
@@ -0,0 +1,362 @@
+//! Module for Mercurial integration.
+use super::status::{FileStatus, Status};
+use super::{Error, Head, Since, Vcs};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{self, Path, PathBuf};
+use std::process::Command;
+
+/// The main struct for Mercurial integration.
+///
+/// Mercurial doesn't provide a stable library API, so this runs the `hg` executable
+/// once when it's created and caches the results.
+///
+/// Ignored files aren't listed, since they can be every file in a build directory.
+/// Every file is tracked, has a status, or is ignored, so a file that isn't one of
+/// the others is ignored.
+pub struct Mercurial {
+    /// The root directory of the working copy.
+    root: PathBuf,
+    /// Statuses of files that are not current (unmodified or ignored).
+    statuses: HashMap<PathBuf, FileStatus>,
+    /// Tracked files, and files that have a status.
+    kept: HashSet<PathBuf>,
+    /// The directories that contain kept files.
+    kept_dirs: HashSet<PathBuf>,
+}
+
+impl Mercurial {
+    /// The executable to run.
+    const EXECUTABLE: &str = "hg";
+
+    /// The directory that marks the root of a repository.
+    const REPOSITORY_DIR: &str = ".hg";
+
+    /// Creates a new Mercurial struct.
+    ///
+    /// If the repository does not exist, or if `hg` isn't installed, this returns
+    /// `Ok(None)`. Other errors get passed back to the caller.
+    pub fn new<P>(root: P) -> Result<Option<Self>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let root = path::absolute(root)?;
+        let Some(workdir) = Self::discover(&root) else {
+            return Ok(None);
+        };
+        let scope = super::scope(&workdir, &root);
+        let scope = scope.as_deref();
+
+        let statuses = match Self::run(&workdir, Self::status_args(scope)) {
+            Ok(statuses) => statuses,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let statuses = parse_statuses(&statuses);
+        let tracked = Self::run(&workdir, Self::files_args(scope))?;
+        let kept = split_output(&tracked)
+            .map(bytes_to_path)
+            .chain(statuses.keys().cloned())
+            .collect::<HashSet<_>>();
+        let kept_dirs = kept_dirs(&kept);
+
+        let hg = Self {
+            root: workdir,
+            statuses,
+            kept,
+            kept_dirs,
+        };
+        Ok(Some(hg))
+    }
+
+    /// Finds the closest directory containing a repository.
+    fn discover(root: &Path) -> Option<PathBuf> {
+        root.ancestors()
+            .find(|dir| dir.join(Self::REPOSITORY_DIR).is_dir())
+            .map(Path::to_path_buf)
+    }
+
+    /// Arguments to list the statuses of all files that aren't clean or ignored.
+    fn status_args(scope: Option<&Path>) -> Vec<String> {
+        let args = [
+            "status",
+            "--print0",
+            "--modified",
+            "--added",
+            "--removed",
+            "--deleted",
+            "--unknown",
+        ];
+        Self::with_scope(args, scope)
+    }
+
+    /// Arguments to list all tracked files.
+    fn files_args(scope: Option<&Path>) -> Vec<String> {
+        Self::with_scope(["files", "--print0"], scope)
+    }
+
+    /// Limits the arguments to the scope, if any.
+    fn with_scope<const N: usize>(args: [&str; N], scope: Option<&Path>) -> Vec<String> {
+        let args = args.into_iter().map(String::from);
+        // NOTE The `path:` prefix makes Mercurial treat the scope literally.
+        let scope = scope.map(|scope| format!("path:{}", scope.display()));
+        args.chain(scope).collect()
+    }
+
+    /// Runs `hg` in the working directory, returning its output.
+    fn run(workdir: &Path, args: Vec<String>) -> io::Result<Vec<u8>> {
+        let output = Command::new(Self::EXECUTABLE)
+            .args(args)
+            .current_dir(workdir)
+            // NOTE This disables any user settings that may change the output.
+            .env("HGPLAIN", "1")
+            .output()?;
+        // NOTE `hg files` exits with 1 when no files match, which isn't an error here.
+        match output.status.code() {
+            Some(0 | 1) => Ok(output.stdout),
+            _ => Err(io::Error::other(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            )),
+        }
+    }
+}
+
+impl Vcs for Mercurial {
+    #[inline]
+    fn root_dir(&self) -> Option<&Path> {
+        Some(&self.root)
+    }
+
+    fn status(&self, path: &Path) -> Result<FileStatus, Error> {
+        let status = self
+            .statuses
+            .get(path)
+            .copied()
+            .unwrap_or(FileStatus::CURRENT);
+        Ok(status)
+    }
+
+    fn is_ignored(&self, path: &Path) -> Result<bool, Error> {
+        // NOTE Mercurial doesn't report its own directory, like git.
+        let is_repository_dir = path
+            .components()
+            .next()
+            .is_some_and(|component| component.as_os_str() == Self::REPOSITORY_DIR);
+        if is_repository_dir || self.kept.contains(path) || self.kept_dirs.contains(path) {
+            return Ok(false);
+        }
+        Ok(contains_file(&self.root.join(path)))
+    }
+
+    fn head_info(&self) -> Result<Head, Error> {
+        /// The ID of the null revision, which is the parent of an empty repository.
+        const NULL_ID: &str = "0000000000000000000000000000000000000000";
+
+        let args = ["log", "--rev", ".", "--template", "{branch}\\0{node}"]
+            .map(String::from)
+            .to_vec();
+        let output = Self::run(&self.root, args)?;
+        let output = String::from_utf8(output)?;
+        let (branch, id) = output.split_once('\0').unwrap_or((&output, ""));
+        let head = Head {
+            branch: (!branch.is_empty()).then(|| branch.to_string()),
+            id: (!id.is_empty() && id != NULL_ID).then(|| id.to_string()),
+            // NOTE Mercurial doesn't track upstream branches.
+            ahead_behind: None,
+        };
+        Ok(head)
+    }
+
+    fn walk_history(
+        &self,
+        since: Option<Since>,
+        visit: &mut dyn FnMut(&Path, i64),
+    ) -> Result<(), Error> {
+        // NOTE `hgdate` is the Unix timestamp followed by the UTC offset.
+        let template = "{files % \"{date|hgdate} {file}\\0\"}";
+        let mut args = ["log", "--no-merges", "--template", template]
+            .map(String::from)
+            .to_vec();
+        if let Some(since) = since {
+            // NOTE Mercurial accepts dates as a Unix timestamp with a UTC offset.
+            args.extend([String::from("--date"), format!(">{} 0", since.seconds())]);
+        }
+        let output = Self::run(&self.root, args)?;
+        for (time, path) in split_output(&output).filter_map(parse_history_item) {
+            visit(&path, time);
+        }
+        Ok(())
+    }
+}
+
+/// Splits NUL-separated command output.
+fn split_output(output: &[u8]) -> impl Iterator<Item = &[u8]> {
+    output
+        .split(|&b| b == b'\0')
+        .filter(|item| !item.is_empty())
+}
+
+/// Converts a path from the command output.
+fn bytes_to_path(bytes: &[u8]) -> PathBuf {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+    }
+    #[cfg(not(unix))]
+    {
+        PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+    }
+}
+
+/// Parses a `<timestamp> <offset> <path>` item from the history output.
+fn parse_history_item(item: &[u8]) -> Option<(i64, PathBuf)> {
+    let mut parts = item.splitn(3, |&b| b == b' ');
+    let time = parts.next()?;
+    let time = std::str::from_utf8(time).ok()?.parse().ok()?;
+    let _offset = parts.next()?;
+    let path = parts.next()?;
+    Some((time, bytes_to_path(path)))
+}
+
+/// Parses the output of `hg status --print0` into statuses.
+fn parse_statuses(output: &[u8]) -> HashMap<PathBuf, FileStatus> {
+    let mut statuses = HashMap::new();
+
+    for line in split_output(output) {
+        let Some((&code, path)) = line.split_first() else {
+            continue;
+        };
+        let path = bytes_to_path(path.strip_prefix(b" ").unwrap_or(path));
+        // NOTE Mercurial has no staging area, but files that were explicitly added
+        //      or removed are recorded before they're committed, so they're treated
+        //      as tracked (indexed).
+        let status = match code {
+            b'A' => FileStatus {
+                tracked: Some(Status::Added),
+                untracked: None,
+            },
+            b'R' => FileStatus {
+                tracked: Some(Status::Removed),
+                untracked: None,
+            },
+            b'M' => FileStatus {
+                tracked: None,
+                untracked: Some(Status::Modified),
+            },
+            b'!' => FileStatus {
+                tracked: None,
+                untracked: Some(Status::Removed),
+            },
+            b'?' => FileStatus {
+                tracked: None,
+                untracked: Some(Status::Added),
+            },
+            _ => continue,
+        };
+        statuses.insert(path, status);
+    }
+
+    statuses
+}
+
+/// Gets the directories that contain the kept files.
+fn kept_dirs(kept: &HashSet<PathBuf>) -> HashSet<PathBuf> {
+    let mut kept_dirs = HashSet::new();
+    for path in kept {
+        for dir in path.ancestors().skip(1) {
+            if dir.as_os_str().is_empty() || !kept_dirs.insert(dir.to_path_buf()) {
+                break;
+            }
+        }
+    }
+    kept_dirs
+}
+
+/// Checks if a path is a file, or a directory with a file somewhere inside it.
+///
+/// Mercurial only ignores files, so a directory that isn't kept is ignored when it
+/// has any files. The search stops at the first file.
+fn contains_file(path: &Path) -> bool {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => fs::read_dir(path).is_ok_and(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .any(|entry| contains_file(&entry.path()))
+        }),
+        Ok(_) => true,
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Fixture;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(b"A new.txt\0", "new.txt", Some(Status::Added), None)]
+    #[case(b"R gone.txt\0", "gone.txt", Some(Status::Removed), None)]
+    #[case(b"M src/lib.rs\0", "src/lib.rs", None, Some(Status::Modified))]
+    #[case(b"! missing.txt\0", "missing.txt", None, Some(Status::Removed))]
+    #[case(b"? with space.txt\0", "with space.txt", None, Some(Status::Added))]
+    fn test_parse_statuses(
+        #[case] output: &[u8],
+        #[case] path: &str,
+        #[case] tracked: Option<Status>,
+        #[case] untracked: Option<Status>,
+    ) {
+        let statuses = parse_statuses(output);
+        let expected = FileStatus { tracked, untracked };
+        assert_eq!(Some(&expected), statuses.get(Path::new(path)));
+    }
+
+    #[rstest]
+    #[case(b"1700000000 0 src/lib.rs", Some((1700000000, "src/lib.rs")))]
+    #[case(b"1700000000 -3600 with space.txt", Some((1700000000, "with space.txt")))]
+    #[case(b"invalid", None)]
+    fn test_parse_history_item(#[case] item: &[u8], #[case] expected: Option<(i64, &str)>) {
+        let expected = expected.map(|(time, path)| (time, PathBuf::from(path)));
+        assert_eq!(expected, parse_history_item(item));
+    }
+
+    #[test]
+    fn test_parse_statuses_ignored() {
+        let statuses = parse_statuses(b"M a.txt\0I target/out\0");
+        assert_eq!(1, statuses.len());
+    }
+
+    #[rstest]
+    #[case("target/debug/out", true)]
+    #[case("target/debug", true)]
+    #[case("target", true)]
+    #[case("src/cache.pyc", true)]
+    #[case("src", false)]
+    #[case("src/lib.rs", false)]
+    #[case("src/new.rs", false)]
+    #[case("empty", false)]
+    #[case("missing", false)]
+    #[case(".hg/store", false)]
+    fn test_is_ignored(#[case] path: &str, #[case] expected: bool) {
+        let fixture = Fixture::builder()
+            .file(".hg/store/data", "")
+            .file("target/debug/out", "")
+            .file("src/cache.pyc", "")
+            .file("src/lib.rs", "")
+            .file("src/new.rs", "")
+            .dir("empty")
+            .build()
+            .unwrap();
+        let statuses = parse_statuses(b"? src/new.rs\0");
+        let kept = ["src/lib.rs", "src/new.rs"].map(PathBuf::from).into();
+        let hg = Mercurial {
+            root: fixture.path().to_path_buf(),
+            statuses,
+            kept_dirs: kept_dirs(&kept),
+            kept,
+        };
+        assert_eq!(expected, hg.is_ignored(Path::new(path)).unwrap());
+    }
+}
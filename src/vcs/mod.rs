@@ -0,0 +1,135 @@
+//! Module for version control integration.
+//!
+//! [`Git`](crate::git::Git) is checked first, and [`Mercurial`] is used as a fallback
+//! for directories that aren't in a git repository.
+pub use history::{Churn, LastCommits, Since};
+pub use mercurial::Mercurial;
+use status::FileStatus;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+mod history;
+mod mercurial;
+pub mod status;
+
+/// The error type for version control operations.
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Information about the current `HEAD` (or the equivalent working copy parent).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Head {
+    /// The short name of the checked out branch. `None` if `HEAD` is detached.
+    pub branch: Option<String>,
+    /// The commit ID that `HEAD` points to. `None` if there are no commits yet.
+    pub id: Option<String>,
+    /// The numbers of commits that the branch is ahead of and behind its upstream
+    /// branch. `None` if there is no upstream branch.
+    pub ahead_behind: Option<(usize, usize)>,
+}
+
+/// Common behavior for version control systems that can decorate a tree.
+pub trait Vcs {
+    /// Gets the root directory of the repository's working tree.
+    ///
+    /// Returns `None` if there is no working tree (e.g. a bare repository).
+    fn root_dir(&self) -> Option<&Path>;
+
+    /// Gets the combined statuses for a file.
+    ///
+    /// Path should be relative to the repository's root, and ideally should be as
+    /// `path/to/file.ext`. In other words, paths should be as simple as possible, and
+    /// not have `./` or `../`
+    fn status(&self, path: &Path) -> Result<FileStatus, Error>;
+
+    /// Checks if a path is ignored. The path is relative to the repository's root.
+    fn is_ignored(&self, path: &Path) -> Result<bool, Error>;
+
+    /// Gets information about the repository's `HEAD`.
+    fn head_info(&self) -> Result<Head, Error>;
+
+    /// Counts the files in a directory that's untracked as a whole, which only has
+    /// one status for the directory. The directory is relative to the repository's
+    /// root.
+    ///
+    /// Returns `None` if the directory isn't untracked as a whole. By default, this
+    /// isn't known.
+    fn untracked_files(&self, _directory: &Path) -> Option<usize> {
+        None
+    }
+
+    /// Gets the paths directly in a directory that were deleted from the working tree
+    /// but are still tracked. The directory and the paths are relative to the
+    /// repository's root.
+    ///
+    /// A deleted directory is included if anything in it was deleted. By default,
+    /// nothing is known to be deleted.
+    fn deleted(&self, _directory: &Path) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    /// Walks the commit history, newest first, calling `visit` with each path that a
+    /// commit changed and the time of that commit in seconds since the Unix epoch.
+    ///
+    /// The walk stops at commits older than `since` if it's set. Merge commits are
+    /// skipped.
+    fn walk_history(
+        &self,
+        since: Option<Since>,
+        visit: &mut dyn FnMut(&Path, i64),
+    ) -> Result<(), Error>;
+
+    /// Counts the commits that touched each file.
+    fn churn(&self, since: Option<Since>) -> Result<Churn, Error> {
+        let mut churn = Churn::default();
+        self.walk_history(since, &mut |path, _| churn.add(path))?;
+        Ok(churn)
+    }
+
+    /// Gets the time of the most recent commit that touched each file.
+    fn last_commits(&self) -> Result<LastCommits, Error> {
+        let mut last_commits = LastCommits::default();
+        self.walk_history(None, &mut |path, time| last_commits.add(path, time))?;
+        Ok(last_commits)
+    }
+}
+
+/// Gets the path of `root` relative to a repository's working directory, so that
+/// statuses can be limited to it.
+///
+/// Returns `None` if `root` is the working directory itself, or if the relative
+/// path can't be resolved.
+pub fn scope<W, P>(workdir: W, root: P) -> Option<PathBuf>
+where
+    W: AsRef<Path>,
+    P: AsRef<Path>,
+{
+    // NOTE Canonicalizing both paths makes sure that symlinks, `.`, and `..`
+    //      don't prevent the prefix from being stripped.
+    let workdir = fs::canonicalize(workdir).ok()?;
+    let root = fs::canonicalize(root).ok()?;
+    let scope = root.strip_prefix(workdir).ok()?;
+    (!scope.as_os_str().is_empty()).then(|| scope.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use tempfile::TempDir;
+
+    #[rstest]
+    #[case("repo", None)]
+    #[case("repo/src", Some("src"))]
+    #[case("repo/src/deep", Some("src/deep"))]
+    #[case("repo/src/../src", Some("src"))]
+    fn test_scope(#[case] root: &str, #[case] expected: Option<&str>) {
+        let container = TempDir::with_prefix("fancy-tree-").unwrap();
+        let workdir = container.path().join("repo");
+        fs::create_dir_all(workdir.join("src/deep")).unwrap();
+        let root = container.path().join(root);
+
+        let expected = expected.map(PathBuf::from);
+
+        assert_eq!(expected, scope(workdir, root));
+    }
+}
@@ -0,0 +1,223 @@
+//! Module for helpers for version control statuses.
+
+use mlua::{IntoLua, Lua};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Statuses (tracked/indexed or untracked/worktree) for a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// A new file.
+    Added,
+    /// A file was changed.
+    Modified,
+    /// A file was removed.
+    Removed,
+    /// A file was renamed.
+    Renamed,
+}
+
+impl Status {
+    /// Gets the string representation of a status.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Status::Added => "+",
+            Status::Modified => "~",
+            Status::Removed => "-",
+            Status::Renamed => "R",
+        }
+    }
+
+    /// Gets the name of a status, like `"modified"`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Status::Added => "added",
+            Status::Modified => "modified",
+            Status::Removed => "removed",
+            Status::Renamed => "renamed",
+        }
+    }
+}
+
+impl IntoLua for Status {
+    fn into_lua(self, lua: &Lua) -> mlua::Result<mlua::Value> {
+        self.name().into_lua(lua)
+    }
+}
+
+/// The combined tracked (index) and untracked (worktree) statuses of a file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStatus {
+    /// The tracked status.
+    pub tracked: Option<Status>,
+    /// The untracked status.
+    pub untracked: Option<Status>,
+}
+
+impl FileStatus {
+    /// The status of an unmodified file.
+    pub const CURRENT: Self = Self {
+        tracked: None,
+        untracked: None,
+    };
+
+    /// Combines the statuses of two entries, like for a directory that contains
+    /// both. Statuses that are different combine into [`Status::Modified`].
+    pub fn combine(self, other: Self) -> Self {
+        let combine = |left: Option<Status>, right: Option<Status>| match (left, right) {
+            (Some(left), Some(right)) if left != right => Some(Status::Modified),
+            (left, right) => left.or(right),
+        };
+        Self {
+            tracked: combine(self.tracked, other.tracked),
+            untracked: combine(self.untracked, other.untracked),
+        }
+    }
+}
+
+/// Adds the combined statuses of their contents to the directories that contain the
+/// paths, including the root, which has an empty path.
+pub fn roll_up(statuses: &mut HashMap<PathBuf, FileStatus>) {
+    let mut directories = HashMap::<PathBuf, FileStatus>::new();
+    for (path, status) in statuses.iter() {
+        for ancestor in path.ancestors().skip(1) {
+            directories
+                .entry(ancestor.to_path_buf())
+                .and_modify(|combined| *combined = combined.combine(*status))
+                .or_insert(*status);
+        }
+    }
+    for (directory, status) in directories {
+        statuses
+            .entry(directory)
+            .and_modify(|combined| *combined = combined.combine(status))
+            .or_insert(status);
+    }
+}
+
+/// Counts the changed paths, leaving out the directories that statuses were rolled up
+/// to.
+pub fn count_changed(statuses: &HashMap<PathBuf, FileStatus>) -> usize {
+    let mut paths = statuses.keys().collect::<Vec<_>>();
+    // NOTE Sorting puts the contents of a directory right after it, so a directory
+    //      that statuses were rolled up to is followed by a path inside it.
+    paths.sort();
+    paths
+        .iter()
+        .enumerate()
+        .filter(|(index, path)| {
+            paths
+                .get(index + 1)
+                .is_none_or(|next| !next.starts_with(path))
+        })
+        .count()
+}
+
+impl IntoLua for FileStatus {
+    /// Converts to a table like `{ tracked = "modified", untracked = "added" }`.
+    fn into_lua(self, lua: &Lua) -> mlua::Result<mlua::Value> {
+        let table = lua.create_table()?;
+        table.set("tracked", self.tracked)?;
+        table.set("untracked", self.untracked)?;
+        table.into_lua(lua)
+    }
+}
+
+/// Trait to generalize getting a status.
+pub trait StatusGetter {
+    /// Gets the status from a file's combined statuses.
+    fn get(status: FileStatus) -> Option<Status>;
+}
+
+/// The tracked git status.
+pub struct Tracked;
+
+impl StatusGetter for Tracked {
+    /// Gets the index status.
+    #[inline]
+    fn get(status: FileStatus) -> Option<Status> {
+        status.tracked
+    }
+}
+
+/// The untracked git status.
+pub struct Untracked;
+
+impl StatusGetter for Untracked {
+    /// Gets the worktree status.
+    #[inline]
+    fn get(status: FileStatus) -> Option<Status> {
+        status.untracked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    /// Creates a status with only an untracked status.
+    fn untracked(status: Status) -> FileStatus {
+        FileStatus {
+            tracked: None,
+            untracked: Some(status),
+        }
+    }
+
+    #[rstest]
+    #[case(
+        FileStatus::CURRENT,
+        untracked(Status::Added),
+        untracked(Status::Added)
+    )]
+    #[case(
+        untracked(Status::Added),
+        untracked(Status::Added),
+        untracked(Status::Added)
+    )]
+    #[case(
+        untracked(Status::Added),
+        untracked(Status::Removed),
+        untracked(Status::Modified)
+    )]
+    fn test_combine(
+        #[case] left: FileStatus,
+        #[case] right: FileStatus,
+        #[case] expected: FileStatus,
+    ) {
+        assert_eq!(expected, left.combine(right));
+    }
+
+    #[test]
+    fn test_roll_up() {
+        let mut statuses = HashMap::from([
+            (PathBuf::from("src/new.rs"), untracked(Status::Added)),
+            (PathBuf::from("src/lib/old.rs"), untracked(Status::Removed)),
+            (PathBuf::from("docs/guide.md"), untracked(Status::Added)),
+        ]);
+        roll_up(&mut statuses);
+
+        assert_eq!(7, statuses.len());
+        assert_eq!(untracked(Status::Modified), statuses[&PathBuf::from("src")]);
+        assert_eq!(
+            untracked(Status::Removed),
+            statuses[&PathBuf::from("src/lib")]
+        );
+        assert_eq!(untracked(Status::Added), statuses[&PathBuf::from("docs")]);
+        assert_eq!(untracked(Status::Modified), statuses[&PathBuf::new()]);
+        assert_eq!(3, count_changed(&statuses));
+    }
+
+    #[test]
+    fn test_count_changed() {
+        let statuses = HashMap::from([
+            (PathBuf::from("src.rs"), untracked(Status::Added)),
+            (PathBuf::from("src/new.rs"), untracked(Status::Added)),
+            (PathBuf::from("untracked"), untracked(Status::Added)),
+        ]);
+        assert_eq!(3, count_changed(&statuses));
+        assert_eq!(0, count_changed(&HashMap::new()));
+    }
+}